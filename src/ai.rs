@@ -0,0 +1,1273 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use chrono::Utc;
+use log::{info, warn};
+use rig::{completion::Prompt, providers::gemini};
+
+use serde::Deserialize;
+
+use crate::assess::Chunk;
+use crate::file_index::DirectoryView;
+use crate::report;
+use crate::types::{
+    AiInsights, Citation, IssueTriage, ModuleSummary, QuestionAnswer, ReleaseDiff,
+    RepositoryAnalysis, RepositoryTag,
+};
+use crate::utils::describe_detections;
+
+// Model used for report generation
+pub const DEFAULT_MODEL: &str = "gemini-2.5-flash";
+
+// System prompt for the technical report agent
+pub const REPORT_PREAMBLE: &str = "You are an expert software engineer and technical analyst specializing in code repository analysis. You will be provided with detailed analysis data about a GitHub repository in JSON format.
+
+Your task is to generate a comprehensive technical development report that includes:
+
+## Executive Summary
+- Brief overview of the project's purpose and main functionality
+- Key technologies and architecture highlights
+- Current development status and maturity level
+
+## Technical Architecture
+- Primary programming languages and their usage distribution
+- Framework and library ecosystem
+- Project structure and organization patterns
+- Build system and deployment configurations
+
+## Getting Started
+- How to install, build, test and run the project, based on the detected run instructions, entry points and tasks
+
+## Code Quality Assessment
+- Code metrics analysis (lines of code, complexity, file organization, code quality, duplication, following best practices)
+- Security considerations and potential vulnerabilities
+- Documentation completeness and quality
+- Testing coverage and framework usage
+
+## Development Activity
+- Git history analysis (commit frequency, contributor engagement)
+- Recent development trends and focus areas
+- Release management and versioning strategy
+
+## Strengths and Opportunities
+- Key strengths of the codebase
+- Potential areas for improvement
+- Technical debt assessment
+- Recommendations for future development
+
+## Risk Assessment
+- Security vulnerabilities or concerns
+- Outdated dependencies or compatibility issues
+- Maintenance challenges or scalability concerns
+
+Provide your analysis in a clear, professional format with specific examples from the data when relevant. Be concise but thorough, focusing on actionable insights that would help developers understand and improve the project.";
+
+// System prompt for the issue triage agent
+pub const TRIAGE_PREAMBLE: &str = "You are an experienced open-source maintainer triaging the issue tracker of a GitHub repository. You will be given recent issues with their number, title, state, labels, age in days and comment count.
+
+Identify the main themes users struggle with, group related bug reports into clusters and suggest labels for issues that are missing useful ones.
+
+Respond with a single JSON object and nothing else, using exactly this shape:
+{
+  \"themes_summary\": \"One or two sentences, e.g. 'Most user pain is around X'\",
+  \"bug_clusters\": [{\"title\": \"...\", \"description\": \"...\", \"issue_numbers\": [1, 2]}],
+  \"suggested_labels\": [{\"issue_number\": 1, \"labels\": [\"bug\"]}]
+}";
+
+// System prompt for the per-module summaries
+pub const MODULES_PREAMBLE: &str = "You are a senior engineer writing an onboarding guide for a software repository. You will be given its top-level directories, each with its languages, its largest files and previews of a few of them.
+
+For every directory, describe in one or two sentences what it is for, name its most important types, traits, classes or functions, and list the other directories and external libraries it relies on. Only use what the data shows.
+
+Respond with a single JSON object and nothing else, keyed by directory name, using exactly this shape:
+{
+  \"modules\": {
+    \"src\": {\"purpose\": \"...\", \"key_types\": [\"Parser\"], \"dependencies\": [\"lib\", \"serde\"]}
+  }
+}";
+
+// System prompt for the executive summary of the one-pager
+pub const EXECUTIVE_PREAMBLE: &str = "You are briefing a manager who will not read a technical report. You will be given a JSON summary of a software repository: what it is, its risk assessment, its key metrics and its development activity.
+
+Write exactly three short paragraphs in plain language, without jargon or Markdown:
+1. What the project is, who it is for and how mature it is.
+2. Its main risks and strengths, based on the risk assessment.
+3. A recommendation: whether it is safe to depend on or invest in, and what to address first.
+
+Respond with a single JSON object and nothing else, using exactly this shape:
+{
+  \"paragraphs\": [\"...\", \"...\", \"...\"]
+}";
+
+// System prompt for repository tagging
+pub const TAGS_PREAMBLE: &str = "You are cataloguing software repositories so that large collections of them can be filtered and grouped. You will be given a summary of one repository, its metadata and the start of its README.
+
+Tag the repository in these categories:
+- domain: what it is about, e.g. web-framework, database, machine-learning, devops, security, game, blockchain, developer-tools
+- maturity: one of prototype, early, active, mature, maintenance, abandoned
+- audience: who uses it, e.g. developers, data-scientists, operators, end-users, researchers
+- architecture: how it is built, e.g. library, cli, web-service, microservices, monolith, plugin, desktop-app, mobile-app
+
+Use lowercase hyphenated values, prefer the examples when they fit, and give each tag a confidence between 0 and 1. A category may have several tags.
+
+Respond with a single JSON object and nothing else, using exactly this shape:
+{
+  \"tags\": [{\"category\": \"domain\", \"value\": \"web-framework\", \"confidence\": 0.9}]
+}";
+
+// System prompt for answering due diligence questions
+pub const ASSESS_PREAMBLE: &str = "You are a technical due diligence analyst answering questions about a software repository. You will be given a short summary of the repository, a question and numbered excerpts of repository files, each labelled with its path, its kind (doc, config or code), its language and documentation section if known, and its line range.
+
+Answer using only the information in the excerpts and the summary. Cite the excerpts that support your answer. If the excerpts do not contain enough information, say so and use low confidence.
+
+Respond with a single JSON object and nothing else, using exactly this shape:
+{
+  \"answer\": \"...\",
+  \"confidence\": \"high\" | \"medium\" | \"low\",
+  \"citations\": [{\"path\": \"src/lib.rs\", \"start_line\": 1, \"end_line\": 40}]
+}";
+
+// System prompt for reordering retrieved excerpts by relevance
+pub const RERANK_PREAMBLE: &str = "You are ranking repository excerpts for a technical due diligence question. You will be given a question and numbered excerpts of repository files, each labelled with its path, its kind (doc, config or code), its language and documentation section if known, and its line range.
+
+Order the excerpts from the most to the least useful for answering the question. Leave out excerpts that are unrelated to it.
+
+Respond with a single JSON object and nothing else, using exactly this shape:
+{
+  \"ranking\": [3, 1, 2]
+}";
+
+// System prompt for release summaries
+pub const RELEASE_PREAMBLE: &str = "You are a release manager writing release notes for a software project. You will be given a JSON summary of the changes between two tags: line counts, the most changed files, contributors, dependency changes and newly added modules.
+
+Write a short release summary in Markdown: a one paragraph overview, then the highlights, notable dependency changes and a thank-you line for the contributors. Only describe changes supported by the data.";
+
+// Expected length of a release summary, used for cost estimation
+const ESTIMATED_RELEASE_OUTPUT_TOKENS: usize = 800;
+
+// Most changed files included in the release summary prompt
+const RELEASE_PROMPT_FILES: usize = 50;
+
+// Expected length of an answer, used for cost estimation
+const ESTIMATED_ANSWER_OUTPUT_TOKENS: usize = 500;
+
+// Expected length of a rerank response, used for cost estimation
+const ESTIMATED_RERANK_OUTPUT_TOKENS: usize = 100;
+
+// Excerpt order as returned by the model, 1-based
+#[derive(Debug, Deserialize)]
+struct ModelRanking {
+    ranking: Vec<usize>,
+}
+
+// Answer as returned by the model, before the question is attached
+#[derive(Debug, Deserialize)]
+struct ModelAnswer {
+    answer: String,
+    confidence: String,
+    #[serde(default)]
+    citations: Vec<Citation>,
+}
+
+// Expected length of an executive summary, used for cost estimation
+const ESTIMATED_EXECUTIVE_OUTPUT_TOKENS: usize = 500;
+
+// Paragraphs of the executive summary
+const EXECUTIVE_PARAGRAPHS: usize = 3;
+
+// Executive summary as returned by the model
+#[derive(Debug, Deserialize)]
+struct ModelExecutiveSummary {
+    paragraphs: Vec<String>,
+}
+
+// Expected length of a tagging response, used for cost estimation
+const ESTIMATED_TAGS_OUTPUT_TOKENS: usize = 300;
+
+// Tag categories, in the order tags are listed
+pub const TAG_CATEGORIES: &[&str] = &["domain", "maturity", "audience", "architecture"];
+
+// README lines included in the tagging prompt
+const TAGS_README_LINES: usize = 60;
+
+// Tags as returned by the model, before normalization
+#[derive(Debug, Deserialize)]
+struct ModelTags {
+    tags: Vec<RepositoryTag>,
+}
+
+// Expected length of the module summaries, used for cost estimation
+const ESTIMATED_MODULES_OUTPUT_TOKENS: usize = 2_000;
+
+// Top-level directories summarized, the ones with the most code first
+const MAX_SUMMARIZED_MODULES: usize = 20;
+
+// Files listed and previewed per module in the summaries prompt
+const MODULE_PROMPT_FILES: usize = 15;
+const MODULE_PREVIEW_FILES: usize = 2;
+const MODULE_PREVIEW_LINES: usize = 20;
+
+// Module summaries as returned by the model
+#[derive(Debug, Deserialize)]
+struct ModelModules {
+    modules: BTreeMap<String, ModuleSummary>,
+}
+
+// Expected length of a triage response, used for cost estimation
+const ESTIMATED_TRIAGE_OUTPUT_TOKENS: usize = 1_000;
+// Issues triaged, the most recently updated
+const TRIAGE_ISSUES: usize = 20;
+
+// Bumped whenever the way report prompts are assembled changes
+const PROMPT_TEMPLATE_VERSION: &str = "report-v5";
+
+// Maximum directory depth included in the prompt outline
+const OUTLINE_MAX_DEPTH: usize = 2;
+
+// Expected length of a generated report, used for cost estimation
+const ESTIMATED_REPORT_OUTPUT_TOKENS: usize = 4_000;
+
+// Published prices in USD per million tokens: (model prefix, input, output)
+const MODEL_PRICING: &[(&str, f64, f64)] = &[
+    ("gemini-2.5-pro", 1.25, 10.0),
+    ("gemini-2.5-flash-lite", 0.10, 0.40),
+    ("gemini-2.5-flash", 0.30, 2.50),
+    ("gemini-2.0-flash-lite", 0.075, 0.30),
+    ("gemini-2.0-flash", 0.10, 0.40),
+    ("gemini-1.5-pro", 1.25, 5.0),
+    ("gemini-1.5-flash", 0.075, 0.30),
+];
+
+// Limits applied to AI requests before they are sent
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AiBudget {
+    pub max_tokens: Option<usize>,
+    pub max_cost: Option<f64>,
+}
+
+impl AiBudget {
+    fn allows(&self, estimate: &CostEstimate) -> bool {
+        let within_tokens = self
+            .max_tokens
+            .is_none_or(|max_tokens| estimate.input_tokens <= max_tokens);
+        let within_cost = match (self.max_cost, estimate.cost_usd) {
+            (Some(max_cost), Some(cost)) => cost <= max_cost,
+            _ => true,
+        };
+        within_tokens && within_cost
+    }
+}
+
+// Token and cost estimate for a single AI request
+#[derive(Debug, Clone)]
+pub struct CostEstimate {
+    pub model: String,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    pub cost_usd: Option<f64>, // None when the model price is unknown
+}
+
+impl CostEstimate {
+    pub fn new(model: &str, input_tokens: usize, output_tokens: usize) -> Self {
+        let cost_usd = MODEL_PRICING
+            .iter()
+            .find(|(prefix, _, _)| model.starts_with(prefix))
+            .map(|(_, input_price, output_price)| {
+                (input_tokens as f64 * input_price + output_tokens as f64 * output_price)
+                    / 1_000_000.0
+            });
+
+        Self {
+            model: model.to_string(),
+            input_tokens,
+            output_tokens,
+            cost_usd,
+        }
+    }
+}
+
+impl fmt::Display for CostEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} input tokens, ~{} output tokens",
+            self.model, self.input_tokens, self.output_tokens
+        )?;
+        match self.cost_usd {
+            Some(cost) => write!(f, ", estimated cost ${:.4}", cost),
+            None => write!(f, ", cost unknown for this model"),
+        }
+    }
+}
+
+// On-disk cache of AI responses, keyed by repository HEAD, prompt template and model
+pub struct AiCache {
+    dir: PathBuf,
+}
+
+impl AiCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn key(head_sha: &str, template: &str, model: &str) -> String {
+        let template_hash = md5::compute(format!("{}\n{}", PROMPT_TEMPLATE_VERSION, template));
+        let key = md5::compute(format!("{}:{:x}:{}", head_sha, template_hash, model));
+        format!("{:x}", key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        let path = self.entry_path(key);
+        let response = fs::read_to_string(&path).ok()?;
+        // Hits count as uses for the cache eviction, which goes by age
+        if let Err(e) = fs::File::options()
+            .append(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+        {
+            warn!("Failed to touch cached AI response: {}", e);
+        }
+        Some(response)
+    }
+
+    pub fn put(&self, key: &str, response: &str) {
+        if let Err(e) =
+            fs::create_dir_all(&self.dir).and_then(|_| fs::write(self.entry_path(key), response))
+        {
+            warn!("Failed to cache AI response: {}", e);
+        }
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.txt", key))
+    }
+}
+
+// Generates AI insights for an analysis using the Gemini provider
+pub struct AiAnalyst {
+    client: gemini::Client,
+    model: String,
+    rerank_model: Option<String>, // Reorders retrieved excerpts when set
+    budget: AiBudget,
+    cache: AiCache,
+    force: bool,
+}
+
+impl AiAnalyst {
+    pub fn new(client: gemini::Client, budget: AiBudget, cache: AiCache, force: bool) -> Self {
+        Self {
+            client,
+            model: DEFAULT_MODEL.to_string(),
+            rerank_model: None,
+            budget,
+            cache,
+            force,
+        }
+    }
+
+    // Rerank retrieved excerpts with the given model, the answer model when None
+    pub fn with_reranking(mut self, model: Option<String>) -> Self {
+        self.rerank_model = Some(model.unwrap_or_else(|| self.model.clone()));
+        self
+    }
+
+    pub fn reranks(&self) -> bool {
+        self.rerank_model.is_some()
+    }
+
+    pub async fn generate_insights(&self, analysis: &RepositoryAnalysis) -> AiInsights {
+        AiInsights {
+            report: self.generate_report(analysis).await,
+            issue_triage: self.triage_issues(analysis).await,
+            module_summaries: self.summarize_modules(analysis).await,
+            executive_summary: self.summarize_for_executives(analysis).await,
+        }
+    }
+
+    async fn generate_report(&self, analysis: &RepositoryAnalysis) -> Option<String> {
+        let cache_key = analysis
+            .git_analysis
+            .head_sha
+            .as_deref()
+            .map(|sha| AiCache::key(sha, REPORT_PREAMBLE, &self.model));
+
+        if let Some(report) = self.cached(&cache_key) {
+            info!("Using cached AI report (use --force-ai to regenerate)");
+            return Some(report);
+        }
+
+        info!("Generating AI-powered technical report...");
+        let (prompt, estimate) = match fit_report_prompt(analysis, &self.model, &self.budget) {
+            Ok(fitted) => fitted,
+            Err(e) => {
+                warn!(
+                    "Skipping AI report: {}. Proceeding with standard analysis.",
+                    e
+                );
+                return None;
+            }
+        };
+        info!("AI request estimate: {}", estimate);
+
+        match self.prompt(REPORT_PREAMBLE, &prompt).await {
+            Ok(response) => {
+                if let Some(key) = &cache_key {
+                    self.cache.put(key, &response);
+                }
+                info!("AI report generated successfully!");
+                Some(response)
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to generate AI report: {}. Proceeding with standard analysis.",
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    async fn triage_issues(&self, analysis: &RepositoryAnalysis) -> Option<IssueTriage> {
+        if analysis.recent_issues.is_empty() {
+            return None;
+        }
+
+        let prompt = build_triage_prompt(analysis);
+        // Issues change independently of the code, so they are part of the cache key
+        let cache_key = analysis.git_analysis.head_sha.as_deref().map(|sha| {
+            AiCache::key(
+                sha,
+                &format!("{}\n{}", TRIAGE_PREAMBLE, prompt),
+                &self.model,
+            )
+        });
+
+        let cached = self
+            .cached(&cache_key)
+            .and_then(|response| parse_json_response::<IssueTriage>(&response).ok());
+        if let Some(triage) = cached {
+            info!("Using cached issue triage (use --force-ai to regenerate)");
+            return Some(triage);
+        }
+
+        let estimate = CostEstimate::new(
+            &self.model,
+            count_tokens(TRIAGE_PREAMBLE) + count_tokens(&prompt),
+            ESTIMATED_TRIAGE_OUTPUT_TOKENS,
+        );
+        if !self.budget.allows(&estimate) {
+            warn!(
+                "Skipping issue triage: exceeds the AI budget ({})",
+                estimate
+            );
+            return None;
+        }
+
+        info!("Generating AI issue triage...");
+        info!("AI request estimate: {}", estimate);
+        let response = match self.prompt(TRIAGE_PREAMBLE, &prompt).await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Failed to generate issue triage: {}", e);
+                return None;
+            }
+        };
+
+        match parse_json_response::<IssueTriage>(&response) {
+            Ok(triage) => {
+                if let Some(key) = &cache_key {
+                    self.cache.put(key, &response);
+                }
+                Some(triage)
+            }
+            Err(e) => {
+                warn!("Failed to parse issue triage response: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn summarize_for_executives(&self, analysis: &RepositoryAnalysis) -> Vec<String> {
+        let prompt = match build_executive_prompt(analysis) {
+            Ok(prompt) => prompt,
+            Err(e) => {
+                warn!("Failed to build the executive summary prompt: {}", e);
+                return Vec::new();
+            }
+        };
+        let cache_key = analysis.git_analysis.head_sha.as_deref().map(|sha| {
+            AiCache::key(
+                sha,
+                &format!("{}\n{}", EXECUTIVE_PREAMBLE, prompt),
+                &self.model,
+            )
+        });
+
+        let cached = self
+            .cached(&cache_key)
+            .and_then(|response| parse_json_response::<ModelExecutiveSummary>(&response).ok());
+        if let Some(summary) = cached {
+            info!("Using cached executive summary (use --force-ai to regenerate)");
+            return executive_paragraphs(summary);
+        }
+
+        let estimate = CostEstimate::new(
+            &self.model,
+            count_tokens(EXECUTIVE_PREAMBLE) + count_tokens(&prompt),
+            ESTIMATED_EXECUTIVE_OUTPUT_TOKENS,
+        );
+        if !self.budget.allows(&estimate) {
+            warn!(
+                "Skipping executive summary: exceeds the AI budget ({})",
+                estimate
+            );
+            return Vec::new();
+        }
+
+        info!("Generating AI executive summary...");
+        info!("AI request estimate: {}", estimate);
+        let response = match self.prompt(EXECUTIVE_PREAMBLE, &prompt).await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Failed to generate executive summary: {}", e);
+                return Vec::new();
+            }
+        };
+
+        match parse_json_response::<ModelExecutiveSummary>(&response) {
+            Ok(summary) => {
+                if let Some(key) = &cache_key {
+                    self.cache.put(key, &response);
+                }
+                executive_paragraphs(summary)
+            }
+            Err(e) => {
+                warn!("Failed to parse executive summary response: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    // Normalized tags, empty when tagging is skipped or fails
+    pub async fn tag_repository(&self, analysis: &RepositoryAnalysis) -> Vec<RepositoryTag> {
+        let prompt = build_tags_prompt(analysis);
+        let cache_key =
+            analysis.git_analysis.head_sha.as_deref().map(|sha| {
+                AiCache::key(sha, &format!("{}\n{}", TAGS_PREAMBLE, prompt), &self.model)
+            });
+
+        let cached = self
+            .cached(&cache_key)
+            .and_then(|response| parse_json_response::<ModelTags>(&response).ok());
+        if let Some(tags) = cached {
+            info!("Using cached repository tags (use --force-ai to regenerate)");
+            return normalize_tags(tags.tags);
+        }
+
+        let estimate = CostEstimate::new(
+            &self.model,
+            count_tokens(TAGS_PREAMBLE) + count_tokens(&prompt),
+            ESTIMATED_TAGS_OUTPUT_TOKENS,
+        );
+        if !self.budget.allows(&estimate) {
+            warn!(
+                "Skipping repository tags: exceeds the AI budget ({})",
+                estimate
+            );
+            return Vec::new();
+        }
+
+        info!("Generating AI repository tags...");
+        info!("AI request estimate: {}", estimate);
+        let response = match self.prompt(TAGS_PREAMBLE, &prompt).await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Failed to generate repository tags: {}", e);
+                return Vec::new();
+            }
+        };
+
+        match parse_json_response::<ModelTags>(&response) {
+            Ok(tags) => {
+                if let Some(key) = &cache_key {
+                    self.cache.put(key, &response);
+                }
+                normalize_tags(tags.tags)
+            }
+            Err(e) => {
+                warn!("Failed to parse repository tags response: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    async fn summarize_modules(
+        &self,
+        analysis: &RepositoryAnalysis,
+    ) -> BTreeMap<String, ModuleSummary> {
+        let Some(prompt) = build_modules_prompt(analysis) else {
+            return BTreeMap::new();
+        };
+        let cache_key = analysis.git_analysis.head_sha.as_deref().map(|sha| {
+            AiCache::key(
+                sha,
+                &format!("{}\n{}", MODULES_PREAMBLE, prompt),
+                &self.model,
+            )
+        });
+
+        let cached = self
+            .cached(&cache_key)
+            .and_then(|response| parse_json_response::<ModelModules>(&response).ok());
+        if let Some(modules) = cached {
+            info!("Using cached module summaries (use --force-ai to regenerate)");
+            return modules.modules;
+        }
+
+        let estimate = CostEstimate::new(
+            &self.model,
+            count_tokens(MODULES_PREAMBLE) + count_tokens(&prompt),
+            ESTIMATED_MODULES_OUTPUT_TOKENS,
+        );
+        if !self.budget.allows(&estimate) {
+            warn!(
+                "Skipping module summaries: exceeds the AI budget ({})",
+                estimate
+            );
+            return BTreeMap::new();
+        }
+
+        info!("Generating AI module summaries...");
+        info!("AI request estimate: {}", estimate);
+        let response = match self.prompt(MODULES_PREAMBLE, &prompt).await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Failed to generate module summaries: {}", e);
+                return BTreeMap::new();
+            }
+        };
+
+        match parse_json_response::<ModelModules>(&response) {
+            Ok(modules) => {
+                if let Some(key) = &cache_key {
+                    self.cache.put(key, &response);
+                }
+                // Names the model made up are dropped
+                let root = analysis.file_structure.root();
+                modules
+                    .modules
+                    .into_iter()
+                    .filter(|(name, _)| root.subdirectories().any(|dir| dir.name() == name))
+                    .collect()
+            }
+            Err(e) => {
+                warn!("Failed to parse module summaries response: {}", e);
+                BTreeMap::new()
+            }
+        }
+    }
+
+    pub async fn summarize_release(&self, diff: &ReleaseDiff) -> Option<String> {
+        // Tags can move, so both ends of the range are part of the key
+        let cache_key = Some(AiCache::key(
+            &diff.to_sha,
+            &format!("{}\n{}", RELEASE_PREAMBLE, diff.from_sha),
+            &self.model,
+        ));
+        if let Some(summary) = self.cached(&cache_key) {
+            info!("Using cached release summary (use --force-ai to regenerate)");
+            return Some(summary);
+        }
+
+        let mut trimmed = diff.clone();
+        trimmed
+            .files
+            .sort_by_key(|file| std::cmp::Reverse(file.additions + file.deletions));
+        trimmed.files.truncate(RELEASE_PROMPT_FILES);
+        let prompt = match serde_json::to_string_pretty(&trimmed) {
+            Ok(json) => format!(
+                "Changes from {} to {}:\n\n```json\n{}\n```",
+                diff.from_tag, diff.to_tag, json
+            ),
+            Err(e) => {
+                warn!("Failed to serialize release diff: {}", e);
+                return None;
+            }
+        };
+
+        let estimate = CostEstimate::new(
+            &self.model,
+            count_tokens(RELEASE_PREAMBLE) + count_tokens(&prompt),
+            ESTIMATED_RELEASE_OUTPUT_TOKENS,
+        );
+        if !self.budget.allows(&estimate) {
+            warn!(
+                "Skipping release summary: exceeds the AI budget ({})",
+                estimate
+            );
+            return None;
+        }
+
+        info!("Generating AI release summary...");
+        info!("AI request estimate: {}", estimate);
+        match self.prompt(RELEASE_PREAMBLE, &prompt).await {
+            Ok(response) => {
+                if let Some(key) = &cache_key {
+                    self.cache.put(key, &response);
+                }
+                Some(response)
+            }
+            Err(e) => {
+                warn!("Failed to generate release summary: {}", e);
+                None
+            }
+        }
+    }
+
+    pub async fn answer_question(
+        &self,
+        summary: &str,
+        question: &str,
+        chunks: &[&Chunk],
+    ) -> Result<QuestionAnswer> {
+        let mut prompt = format!(
+            "Repository summary:\n{}\n\nQuestion: {}\n\nExcerpts:\n",
+            summary, question
+        );
+        push_excerpts(&mut prompt, chunks);
+
+        let estimate = CostEstimate::new(
+            &self.model,
+            count_tokens(ASSESS_PREAMBLE) + count_tokens(&prompt),
+            ESTIMATED_ANSWER_OUTPUT_TOKENS,
+        );
+        if !self.budget.allows(&estimate) {
+            anyhow::bail!("exceeds the AI budget ({})", estimate);
+        }
+        info!("AI request estimate: {}", estimate);
+
+        let response = self.prompt(ASSESS_PREAMBLE, &prompt).await?;
+        let answer: ModelAnswer = parse_json_response(&response)?;
+
+        Ok(QuestionAnswer {
+            question: question.to_string(),
+            answer: answer.answer,
+            confidence: answer.confidence,
+            citations: answer.citations,
+            grounded: false, // Set once the citations are checked
+        })
+    }
+
+    // Positions of the chunks from the most to the least relevant. Chunks the
+    // model leaves out are dropped
+    pub async fn rerank(&self, question: &str, chunks: &[&Chunk]) -> Result<Vec<usize>> {
+        let Some(model) = &self.rerank_model else {
+            return Ok((0..chunks.len()).collect());
+        };
+        let mut prompt = format!("Question: {}\n\nExcerpts:\n", question);
+        push_excerpts(&mut prompt, chunks);
+
+        let estimate = CostEstimate::new(
+            model,
+            count_tokens(RERANK_PREAMBLE) + count_tokens(&prompt),
+            ESTIMATED_RERANK_OUTPUT_TOKENS,
+        );
+        if !self.budget.allows(&estimate) {
+            anyhow::bail!("exceeds the AI budget ({})", estimate);
+        }
+        info!("AI rerank estimate: {}", estimate);
+
+        let response = self.prompt_model(model, RERANK_PREAMBLE, &prompt).await?;
+        let ranking: ModelRanking = parse_json_response(&response)?;
+
+        let mut order: Vec<usize> = Vec::new();
+        for position in ranking.ranking {
+            if (1..=chunks.len()).contains(&position) && !order.contains(&(position - 1)) {
+                order.push(position - 1);
+            }
+        }
+        if order.is_empty() {
+            anyhow::bail!("the ranking names none of the excerpts");
+        }
+        Ok(order)
+    }
+
+    fn cached(&self, cache_key: &Option<String>) -> Option<String> {
+        match cache_key {
+            Some(key) if !self.force => self.cache.get(key),
+            _ => None,
+        }
+    }
+
+    async fn prompt(&self, preamble: &str, prompt: &str) -> Result<String> {
+        self.prompt_model(&self.model, preamble, prompt).await
+    }
+
+    async fn prompt_model(&self, model: &str, preamble: &str, prompt: &str) -> Result<String> {
+        let agent = self
+            .client
+            .agent(model)
+            .temperature(0.0)
+            .preamble(preamble)
+            .build();
+        Ok(agent.prompt(prompt).await?)
+    }
+}
+
+// Numbered excerpts labelled with their path, kind, language, section and lines
+fn push_excerpts(prompt: &mut String, chunks: &[&Chunk]) {
+    for (index, chunk) in chunks.iter().enumerate() {
+        let mut label = chunk.kind.as_str().to_string();
+        if let Some(language) = &chunk.language {
+            label.push_str(&format!(", {}", language));
+        }
+        if let Some(heading) = &chunk.heading {
+            label.push_str(&format!(", section \"{}\"", heading));
+        }
+        prompt.push_str(&format!(
+            "\n[{}] {} ({}, lines {}-{})\n```\n{}\n```\n",
+            index + 1,
+            chunk.path.display(),
+            label,
+            chunk.start_line,
+            chunk.end_line,
+            chunk.text
+        ));
+    }
+}
+
+// Count tokens with a tiktoken-compatible encoding
+pub fn count_tokens(text: &str) -> usize {
+    tiktoken_rs::o200k_base_singleton()
+        .encode_ordinary(text)
+        .len()
+}
+
+// Prompt split into a mandatory header and optional key file sections
+struct PromptParts {
+    header: String,
+    key_files: Vec<String>,
+}
+
+impl PromptParts {
+    fn join(&self, key_file_count: usize) -> String {
+        let mut prompt = self.header.clone();
+        if key_file_count > 0 {
+            prompt.push_str("\n## Key Files\n\nThe following files were selected as the most architecturally relevant. Previews show the first lines of each file.\n");
+            for section in self.key_files.iter().take(key_file_count) {
+                prompt.push_str(section);
+            }
+        }
+        prompt
+    }
+}
+
+// Build the report prompt from the analysis, replacing the full file tree with
+// a compact outline and the previews of the selected key files. When the
+// prompt exceeds the budget, key file previews are dropped first and then
+// bulky file contents.
+pub fn fit_report_prompt(
+    analysis: &RepositoryAnalysis,
+    model: &str,
+    budget: &AiBudget,
+) -> Result<(String, CostEstimate)> {
+    let preamble_tokens = count_tokens(REPORT_PREAMBLE);
+    let mut smallest: Option<CostEstimate> = None;
+
+    for compact in [false, true] {
+        let parts = report_prompt_parts(analysis, compact)?;
+        let header_tokens = preamble_tokens + count_tokens(&parts.header);
+        let section_tokens: Vec<usize> = parts.key_files.iter().map(|s| count_tokens(s)).collect();
+
+        for key_file_count in (0..=parts.key_files.len()).rev() {
+            let input_tokens =
+                header_tokens + section_tokens[..key_file_count].iter().sum::<usize>();
+            let estimate = CostEstimate::new(model, input_tokens, ESTIMATED_REPORT_OUTPUT_TOKENS);
+
+            if budget.allows(&estimate) {
+                return Ok((parts.join(key_file_count), estimate));
+            }
+            smallest = Some(estimate);
+        }
+    }
+
+    match smallest {
+        Some(estimate) => anyhow::bail!(
+            "prompt does not fit the AI budget even after trimming ({})",
+            estimate
+        ),
+        None => anyhow::bail!("prompt does not fit the AI budget"),
+    }
+}
+
+fn report_prompt_parts(analysis: &RepositoryAnalysis, compact: bool) -> Result<PromptParts> {
+    let mut data = serde_json::to_value(analysis)?;
+    if let Some(object) = data.as_object_mut() {
+        object.remove("file_structure");
+        // Listed in their own section below
+        object.remove("keywords");
+
+        // Per-file function lists are summarized in their own section below
+        if let Some(metrics) = object
+            .get_mut("code_metrics")
+            .and_then(|v| v.as_object_mut())
+        {
+            for list in ["largest_files", "most_complex_files"] {
+                if let Some(files) = metrics.get_mut(list).and_then(|v| v.as_array_mut()) {
+                    for file in files {
+                        if let Some(file) = file.as_object_mut() {
+                            file.remove("functions");
+                        }
+                    }
+                }
+            }
+        }
+
+        if compact {
+            // Drop raw file contents and commit listings, keeping the parsed data
+            for section in ["config_files", "documentation"] {
+                if let Some(files) = object.get_mut(section).and_then(|v| v.as_array_mut()) {
+                    for file in files {
+                        if let Some(file) = file.as_object_mut() {
+                            file.remove("content");
+                        }
+                    }
+                }
+            }
+            if let Some(git) = object
+                .get_mut("git_analysis")
+                .and_then(|v| v.as_object_mut())
+            {
+                git.remove("recent_commits");
+            }
+        }
+    }
+
+    let mut header = String::new();
+    let status = &analysis.repository_status;
+    if status.read_only {
+        header.push_str("Note: this repository is archived and read-only. Treat it as a finished or historical project: do not assess development activity or suggest contributing, and focus on what it offers as-is and the risks of depending on it.\n\n");
+    }
+    if status.is_mirror {
+        header.push_str(&format!(
+            "Note: this repository is a mirror of {}, where development actually happens. Do not treat the mirror's issues, contributors or activity as the project's own.\n\n",
+            status.canonical_source.as_deref().unwrap_or("another repository")
+        ));
+    }
+    header.push_str(&format!(
+        "Please analyze this repository data and generate a comprehensive technical report:\n\n{}",
+        serde_json::to_string_pretty(&data)?
+    ));
+
+    header.push_str("\n\n## Directory Outline\n\n");
+    append_directory_outline(analysis.file_structure.root(), 0, &mut header);
+
+    if !analysis.keywords.is_empty() {
+        header.push_str(&format!(
+            "\n## Domain Vocabulary\n\nThe most salient words of the docs and code identifiers, use them to name what the project is about: {}\n",
+            analysis.keywords.join(", ")
+        ));
+    }
+
+    if !analysis.code_metrics.longest_functions.is_empty() {
+        header.push_str("\n## Longest Functions\n\n");
+        for function in &analysis.code_metrics.longest_functions {
+            header.push_str(&format!(
+                "- {} ({}:{}-{}): {} lines, complexity {}, {} parameters\n",
+                function.name,
+                function.file.display(),
+                function.start_line,
+                function.end_line,
+                function.lines_of_code,
+                function.complexity,
+                function.parameter_count
+            ));
+        }
+    }
+
+    let key_files = analysis
+        .interesting_files
+        .iter()
+        .map(|file| {
+            let mut section = format!(
+                "\n### {} ({})\nSelected because: {}\n",
+                file.path.display(),
+                file.category,
+                file.reasons.join(", ")
+            );
+            if let Some(preview) = &file.preview {
+                section.push_str(&format!("```\n{}\n```\n", preview));
+            }
+            section
+        })
+        .collect();
+
+    Ok(PromptParts { header, key_files })
+}
+
+fn build_triage_prompt(analysis: &RepositoryAnalysis) -> String {
+    let now = Utc::now();
+    let mut prompt = format!("Recent issues of {}:\n\n", analysis.metadata.full_name);
+
+    for issue in analysis.recent_issues.iter().take(TRIAGE_ISSUES) {
+        let labels = if issue.labels.is_empty() {
+            "none".to_string()
+        } else {
+            issue.labels.join(", ")
+        };
+        prompt.push_str(&format!(
+            "- #{} [{}] {} (labels: {}; age: {} days; comments: {})\n",
+            issue.number,
+            issue.state,
+            issue.title,
+            labels,
+            (now - issue.created_at).num_days(),
+            issue.comments
+        ));
+    }
+
+    prompt
+}
+
+fn build_executive_prompt(analysis: &RepositoryAnalysis) -> Result<String> {
+    let metrics: serde_json::Map<String, serde_json::Value> = report::key_metrics(analysis)
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.into()))
+        .collect();
+    let data = serde_json::json!({
+        "repository": analysis.metadata.full_name,
+        "description": analysis.metadata.description,
+        "status": analysis.repository_status.notes,
+        "summary": analysis.analysis_summary,
+        "risk_assessment": analysis.risk_assessment,
+        "key_metrics": metrics,
+        "activity": analysis.git_analysis.activity,
+        "contributors": analysis.git_analysis.contributors.len(),
+        "releases": analysis.releases.len(),
+    });
+    Ok(format!(
+        "Repository data:\n{}",
+        serde_json::to_string_pretty(&data)?
+    ))
+}
+
+// Non-empty paragraphs, at most EXECUTIVE_PARAGRAPHS
+fn executive_paragraphs(summary: ModelExecutiveSummary) -> Vec<String> {
+    summary
+        .paragraphs
+        .into_iter()
+        .map(|paragraph| paragraph.trim().to_string())
+        .filter(|paragraph| !paragraph.is_empty())
+        .take(EXECUTIVE_PARAGRAPHS)
+        .collect()
+}
+
+fn build_tags_prompt(analysis: &RepositoryAnalysis) -> String {
+    let metadata = &analysis.metadata;
+    let mut prompt = format!(
+        "Repository: {}\nDescription: {}\nTopics: {}\nStars: {}, forks: {}\nCreated {}, last push {}\nArchived: {}\nReleases: {}\nProject types: {}\nFrameworks: {}\nKeywords: {}\n\nSummary:\n{}\n",
+        metadata.full_name,
+        metadata.description.as_deref().unwrap_or("none"),
+        metadata.topics.join(", "),
+        metadata.stargazers_count,
+        metadata.forks_count,
+        metadata.created_at.format("%Y-%m-%d"),
+        metadata.pushed_at.format("%Y-%m-%d"),
+        metadata.archived,
+        analysis.releases.len(),
+        describe_detections(&analysis.project_info.project_type),
+        describe_detections(&analysis.project_info.frameworks),
+        analysis.keywords.join(", "),
+        analysis.analysis_summary
+    );
+
+    if let Some(readme) = analysis
+        .documentation
+        .iter()
+        .filter(|doc| doc.file_type == "readme")
+        .min_by_key(|doc| doc.path.components().count())
+    {
+        let lines: Vec<&str> = readme.content.lines().take(TAGS_README_LINES).collect();
+        prompt.push_str(&format!("\nREADME:\n```\n{}\n```\n", lines.join("\n")));
+    }
+    prompt
+}
+
+// Keeps the known categories, hyphenates values, clamps confidences and drops
+// duplicates, keeping the most confident
+pub fn normalize_tags(tags: Vec<RepositoryTag>) -> Vec<RepositoryTag> {
+    let mut normalized: Vec<RepositoryTag> = Vec::new();
+    for tag in tags {
+        let category = tag.category.trim().to_lowercase();
+        if !TAG_CATEGORIES.contains(&category.as_str()) {
+            continue;
+        }
+        let value = tag
+            .value
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        if value.is_empty() {
+            continue;
+        }
+        let confidence = if tag.confidence.is_finite() {
+            tag.confidence.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        match normalized
+            .iter_mut()
+            .find(|t| t.category == category && t.value == value)
+        {
+            Some(existing) => existing.confidence = existing.confidence.max(confidence),
+            None => normalized.push(RepositoryTag {
+                category,
+                value,
+                confidence,
+            }),
+        }
+    }
+
+    let position = |category: &str| TAG_CATEGORIES.iter().position(|c| *c == category);
+    normalized.sort_by(|a, b| {
+        position(&a.category)
+            .cmp(&position(&b.category))
+            .then(b.confidence.total_cmp(&a.confidence))
+            .then(a.value.cmp(&b.value))
+    });
+    normalized
+}
+
+// Top-level directories with their languages, largest files and a few
+// previews. None when the repository has no directories with files
+fn build_modules_prompt(analysis: &RepositoryAnalysis) -> Option<String> {
+    let mut modules: Vec<(DirectoryView<'_>, u32)> = analysis
+        .file_structure
+        .root()
+        .subdirectories()
+        .filter(|dir| !dir.name().starts_with('.') && !dir.all_files().is_empty())
+        .map(|dir| {
+            let loc = dir
+                .all_files()
+                .iter()
+                .filter_map(|file| file.lines_of_code)
+                .sum();
+            (dir, loc)
+        })
+        .collect();
+    if modules.is_empty() {
+        return None;
+    }
+    modules.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.name().cmp(b.0.name())));
+    modules.truncate(MAX_SUMMARIZED_MODULES);
+
+    let mut prompt = format!(
+        "Top-level directories of {}:\n",
+        analysis.metadata.full_name
+    );
+    for (dir, loc) in modules {
+        let mut languages: HashMap<&str, u32> = HashMap::new();
+        for file in dir.all_files() {
+            if let Some(language) = &file.language {
+                *languages.entry(language).or_insert(0) += 1;
+            }
+        }
+        let mut languages: Vec<(&str, u32)> = languages.into_iter().collect();
+        languages.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        let languages: Vec<&str> = languages.iter().take(3).map(|(name, _)| *name).collect();
+
+        prompt.push_str(&format!(
+            "\n## {}/ ({} files, {} lines of code; {})\n",
+            dir.name(),
+            dir.all_files().len(),
+            loc,
+            if languages.is_empty() {
+                "no detected language".to_string()
+            } else {
+                languages.join(", ")
+            }
+        ));
+
+        let mut files: Vec<_> = dir.all_files().iter().collect();
+        files.sort_by(|a, b| {
+            b.lines_of_code
+                .unwrap_or(0)
+                .cmp(&a.lines_of_code.unwrap_or(0))
+                .then(a.path.cmp(&b.path))
+        });
+        for file in files.iter().take(MODULE_PROMPT_FILES) {
+            prompt.push_str(&format!("- {}\n", file.path.display()));
+        }
+        for file in files
+            .iter()
+            .filter(|file| file.content_preview.is_some())
+            .take(MODULE_PREVIEW_FILES)
+        {
+            let preview = file.content_preview.as_deref().unwrap_or_default();
+            let preview: Vec<&str> = preview.lines().take(MODULE_PREVIEW_LINES).collect();
+            prompt.push_str(&format!(
+                "\n{}:\n```\n{}\n```\n",
+                file.path.display(),
+                preview.join("\n")
+            ));
+        }
+    }
+
+    Some(prompt)
+}
+
+// Parse a JSON object from a model response, tolerating surrounding prose or
+// code fences. The object starts at the first brace and the text after it is
+// ignored, braces in that text included
+fn parse_json_response<T: serde::de::DeserializeOwned>(response: &str) -> Result<T> {
+    let start = response
+        .find('{')
+        .ok_or_else(|| anyhow::anyhow!("no JSON object in response"))?;
+    serde_json::Deserializer::from_str(&response[start..])
+        .into_iter::<T>()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("no JSON object in response"))?
+        .map_err(Into::into)
+}
+
+fn append_directory_outline(dir: DirectoryView<'_>, depth: usize, outline: &mut String) {
+    for subdir in dir.subdirectories() {
+        outline.push_str(&format!(
+            "{}- {}/ ({} files, {} subdirectories)\n",
+            "  ".repeat(depth),
+            subdir.name(),
+            subdir.file_count(),
+            subdir.subdirectory_count()
+        ));
+        if depth + 1 < OUTLINE_MAX_DEPTH {
+            append_directory_outline(subdir, depth + 1, outline);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_is_found_between_prose_and_fences() {
+        let response = "Here you go:\n```json\n{\"answer\": \"yes\", \"confidence\": \"high\"}\n```\nLet me know {if} you need more.";
+        let answer: ModelAnswer = parse_json_response(response).unwrap();
+        assert_eq!(answer.answer, "yes");
+        assert_eq!(answer.confidence, "high");
+    }
+
+    #[test]
+    fn braces_out_of_order_are_an_error() {
+        assert!(parse_json_response::<ModelAnswer>("} nothing here {").is_err());
+        assert!(parse_json_response::<ModelAnswer>("no object at all }").is_err());
+        assert!(parse_json_response::<ModelAnswer>("{\"answer\": \"cut off").is_err());
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::analyzers::scan::{FileClassifier, METADATA_DEPTH, ScannedFile};
+use crate::types::{ApiDocs, ApiDocsUrl, DocumentationFile, EvidenceRef, ProjectInfo};
+use crate::utils::{distinct_labels, first_line};
+
+// Links checked per repository, declared ones first
+pub const MAX_CHECKED_URLS: usize = 5;
+
+// Lowercase content markers of API doc generators in manifests and configs,
+// as (file name, marker, tool)
+const CONFIG_MARKERS: &[(&str, &str, &str)] = &[
+    ("cargo.toml", "[package.metadata.docs.rs]", "docs.rs"),
+    ("package.json", "\"typedoc\"", "TypeDoc"),
+    ("package.json", "\"jsdoc\"", "JSDoc"),
+    (
+        "package.json",
+        "\"@microsoft/api-extractor\"",
+        "API Extractor",
+    ),
+    ("conf.py", "sphinx.ext.autodoc", "Sphinx autodoc"),
+    ("conf.py", "autoapi", "Sphinx AutoAPI"),
+    ("mkdocs.yml", "mkdocstrings", "mkdocstrings"),
+    ("pyproject.toml", "pdoc", "pdoc"),
+    ("pom.xml", "maven-javadoc-plugin", "Javadoc"),
+    ("build.gradle", "withjavadocjar", "Javadoc"),
+    ("build.gradle", "dokka", "Dokka"),
+    ("build.gradle.kts", "withjavadocjar", "Javadoc"),
+    ("build.gradle.kts", "dokka", "Dokka"),
+];
+
+// Config files that only exist for an API doc generator
+const CONFIG_FILES: &[(&str, &str)] = &[
+    ("typedoc.json", "TypeDoc"),
+    ("jsdoc.json", "JSDoc"),
+    (".jsdoc.json", "JSDoc"),
+    ("jsdoc.conf.json", "JSDoc"),
+    ("api-extractor.json", "API Extractor"),
+    ("doxyfile", "Doxygen"),
+];
+
+// Lowercase workflow markers of API doc builds, as (marker, tool)
+const CI_MARKERS: &[(&str, &str)] = &[
+    ("cargo doc", "rustdoc"),
+    ("typedoc", "TypeDoc"),
+    ("jsdoc", "JSDoc"),
+    ("pdoc", "pdoc"),
+    ("javadoc", "Javadoc"),
+    ("dokka", "Dokka"),
+    ("doxygen", "Doxygen"),
+];
+
+// A Cargo package seen during the walk, published to docs.rs if it is a
+// library
+struct Crate {
+    dir: PathBuf,
+    name: String,
+    has_lib_section: bool,
+}
+
+// API doc generators, documentation links declared in manifests and the
+// packages whose reference docs can be looked up by name, found during the
+// shared walk
+pub struct ApiDocsClassifier {
+    documentation_key: Regex,
+    crates: Vec<Crate>,
+    lib_dirs: HashSet<PathBuf>, // Crate directories with a src/lib.rs
+    api_docs: ApiDocs,
+}
+
+impl Default for ApiDocsClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApiDocsClassifier {
+    pub fn new() -> Self {
+        Self {
+            // Cargo's documentation field, [project.urls] and Poetry keys
+            documentation_key: Regex::new(
+                r#"^\s*"?(?i:documentation|docs|api)"?\s*=\s*"(https?://[^"]+)""#,
+            )
+            .unwrap(),
+            crates: Vec::new(),
+            lib_dirs: HashSet::new(),
+            api_docs: ApiDocs::default(),
+        }
+    }
+
+    pub fn finish(mut self) -> ApiDocs {
+        // docs.rs builds every published library crate
+        for krate in &self.crates {
+            if krate.has_lib_section || self.lib_dirs.contains(&krate.dir) {
+                add_url(
+                    &mut self.api_docs.urls,
+                    format!("https://docs.rs/{}", krate.name),
+                    "crate name",
+                    true,
+                );
+            }
+        }
+        self.api_docs
+            .tools
+            .sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.label.cmp(&b.label)));
+        self.api_docs
+            .tools
+            .dedup_by(|a, b| a.label == b.label && a.path == b.path);
+        self.api_docs
+    }
+
+    fn cargo_manifest(&mut self, file: &ScannedFile<'_>, content: &str) {
+        let Ok(manifest) = content.parse::<toml::Value>() else {
+            return;
+        };
+        let Some(package) = manifest.get("package") else {
+            return;
+        };
+        if package.get("publish").and_then(|p| p.as_bool()) == Some(false) {
+            return;
+        }
+        let Some(name) = package.get("name").and_then(|n| n.as_str()) else {
+            return;
+        };
+        let has_lib_section = manifest.get("lib").is_some();
+        if has_lib_section {
+            self.api_docs.library = true;
+        }
+        self.crates.push(Crate {
+            dir: file
+                .relative
+                .parent()
+                .unwrap_or(Path::new(""))
+                .to_path_buf(),
+            name: name.to_string(),
+            has_lib_section,
+        });
+    }
+
+    fn package_json(&mut self, content: &str) {
+        let Ok(manifest) = serde_json::from_str::<serde_json::Value>(content) else {
+            return;
+        };
+        // Published packages with an entry point others import
+        if manifest["private"].as_bool() != Some(true)
+            && ["main", "module", "exports", "types"]
+                .iter()
+                .any(|field| !manifest[field].is_null())
+        {
+            self.api_docs.library = true;
+        }
+    }
+
+    fn pyproject(&mut self, content: &str) {
+        let Ok(manifest) = content.parse::<toml::Value>() else {
+            return;
+        };
+        let project = manifest
+            .get("project")
+            .or_else(|| manifest.get("tool").and_then(|tool| tool.get("poetry")));
+        // Packages without console scripts are imported, not run
+        if let Some(project) = project
+            && project.get("name").is_some()
+            && project.get("scripts").is_none()
+        {
+            self.api_docs.library = true;
+        }
+    }
+
+    fn go_module(&mut self, content: &str) {
+        let Some(module) = content
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("module "))
+        else {
+            return;
+        };
+        let module = module.trim().trim_matches('"');
+        // pkg.go.dev only serves modules under a domain
+        if module
+            .split('/')
+            .next()
+            .is_some_and(|host| host.contains('.'))
+        {
+            add_url(
+                &mut self.api_docs.urls,
+                format!("https://pkg.go.dev/{}", module),
+                "Go module path",
+                true,
+            );
+        }
+    }
+
+    fn detect_ci(&mut self, file: &ScannedFile<'_>, content: &str) {
+        let content = content.to_lowercase();
+        for (marker, tool) in CI_MARKERS {
+            if let Some(line) = first_line(&content, |line| {
+                !line.trim_start().starts_with('#') && line.contains(marker)
+            }) {
+                self.api_docs
+                    .tools
+                    .push(evidence(tool, file.relative, Some(line)));
+            }
+        }
+    }
+}
+
+impl FileClassifier for ApiDocsClassifier {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        let relative = file.relative;
+        if relative.parent() == Some(Path::new(".github/workflows")) {
+            if let Some(content) = file.content() {
+                self.detect_ci(file, content);
+            }
+            return;
+        }
+        let name = file.file_name().to_lowercase();
+        if name == "lib.rs"
+            && let Some(src) = relative.parent()
+            && src.file_name().is_some_and(|dir| dir == "src")
+        {
+            self.lib_dirs
+                .insert(src.parent().unwrap_or(Path::new("")).to_path_buf());
+            return;
+        }
+        if file.depth > METADATA_DEPTH || !file.in_structure {
+            return;
+        }
+
+        if let Some((_, tool)) = CONFIG_FILES
+            .iter()
+            .find(|(file_name, _)| *file_name == name)
+        {
+            self.api_docs.tools.push(evidence(tool, relative, None));
+            return;
+        }
+        let markers: Vec<&(&str, &str, &str)> = CONFIG_MARKERS
+            .iter()
+            .filter(|(file_name, _, _)| *file_name == name)
+            .collect();
+        let is_manifest = matches!(
+            name.as_str(),
+            "cargo.toml" | "package.json" | "pyproject.toml" | "go.mod"
+        );
+        if markers.is_empty() && !is_manifest {
+            return;
+        }
+        let Some(content) = file.content() else {
+            return;
+        };
+
+        let lowercase = content.to_lowercase();
+        for (_, marker, tool) in markers {
+            if let Some(line) = first_line(&lowercase, |line| line.contains(marker)) {
+                self.api_docs
+                    .tools
+                    .push(evidence(tool, relative, Some(line)));
+            }
+        }
+        if matches!(name.as_str(), "cargo.toml" | "pyproject.toml") {
+            for line in content.lines() {
+                if let Some(captures) = self.documentation_key.captures(line) {
+                    let source = format!("{} documentation link", file.file_name());
+                    add_url(
+                        &mut self.api_docs.urls,
+                        captures[1].to_string(),
+                        &source,
+                        false,
+                    );
+                }
+            }
+        }
+        match name.as_str() {
+            "cargo.toml" => self.cargo_manifest(file, content),
+            "package.json" => self.package_json(content),
+            "pyproject.toml" => self.pyproject(content),
+            "go.mod" if file.depth == 1 => self.go_module(content),
+            _ => {}
+        }
+    }
+}
+
+// Adds the docs links of the README badges and the library project type,
+// then says what was found
+pub fn add_project_context(
+    api_docs: &mut ApiDocs,
+    documentation: &[DocumentationFile],
+    project_info: &ProjectInfo,
+) {
+    let docs_link = Regex::new(
+        r#"https?://(?:docs\.rs|pkg\.go\.dev|godoc\.org|(?:www\.)?javadoc\.io|[\w-]+\.readthedocs\.io)/[^\s)\]"'<>]*"#,
+    )
+    .unwrap();
+    for readme in documentation.iter().filter(|doc| doc.file_type == "readme") {
+        for link in docs_link.find_iter(&readme.content) {
+            let url = link.as_str();
+            // Badge images link to themselves
+            if url.contains("/badge") || url.ends_with(".svg") || url.ends_with(".png") {
+                continue;
+            }
+            add_url(&mut api_docs.urls, url.to_string(), "README link", false);
+        }
+    }
+    // Linked ones are the project's own word, check them first
+    api_docs.urls.sort_by_key(|url| url.inferred);
+
+    if project_info
+        .project_type
+        .iter()
+        .any(|detection| detection.name == "library")
+    {
+        api_docs.library = true;
+    }
+    api_docs.signals = signals(api_docs);
+}
+
+// What the tools and links say about the project's API reference
+pub fn signals(api_docs: &ApiDocs) -> Vec<String> {
+    let mut signals = Vec::new();
+    if !api_docs.tools.is_empty() {
+        signals.push(format!(
+            "API docs are generated with {}.",
+            distinct_labels(&api_docs.tools).join(", ")
+        ));
+    }
+    for url in &api_docs.urls {
+        match url.resolves {
+            Some(true) => signals.push(format!("API docs are published at {}.", url.url)),
+            Some(false) if !url.inferred => {
+                signals.push(format!("The {} {} does not resolve.", url.source, url.url))
+            }
+            Some(false) => {}
+            None if !url.inferred => signals.push(format!("API docs are linked at {}.", url.url)),
+            None => {}
+        }
+    }
+    // Unchecked links built from package names may well resolve
+    let published = api_docs.urls.iter().any(|url| url.resolves != Some(false));
+    if api_docs.library && api_docs.tools.is_empty() && !published {
+        signals.push(
+            "This library ships no API documentation: no doc generator is set up and no published reference was found."
+                .to_string(),
+        );
+    }
+    signals
+}
+
+// Once per URL, a link the project declares wins over an inferred one
+fn add_url(urls: &mut Vec<ApiDocsUrl>, url: String, source: &str, inferred: bool) {
+    let url = url.trim_end_matches('/').to_string();
+    if let Some(existing) = urls.iter_mut().find(|existing| existing.url == url) {
+        if existing.inferred && !inferred {
+            existing.source = source.to_string();
+            existing.inferred = false;
+        }
+        return;
+    }
+    urls.push(ApiDocsUrl {
+        url,
+        source: source.to_string(),
+        inferred,
+        resolves: None,
+    });
+}
+
+fn evidence(label: &str, path: &Path, line: Option<u32>) -> EvidenceRef {
+    EvidenceRef {
+        label: label.to_string(),
+        path: path.to_path_buf(),
+        line,
+        url: None,
+    }
+}
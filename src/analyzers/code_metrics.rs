@@ -1,28 +1,148 @@
 use std::collections::HashMap;
 
+use anyhow::Result;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::analyzers::linguist::LinguistClassifier;
+use crate::file_index::FileIndex;
 use crate::types::CodeMetrics;
-use crate::types::DirectoryInfo;
+use crate::types::ConfigFile;
+use crate::types::Distribution;
+use crate::types::ExtensionStats;
 use crate::types::FileInfo;
+use crate::types::FunctionMetrics;
+use crate::types::HistogramBucket;
 use crate::types::LanguageStats;
+use crate::types::MetricDistributions;
+
+// Raw values collected per file before building distributions
+#[derive(Default)]
+struct MetricSamples {
+    file_size: Vec<u64>,
+    lines_of_code: Vec<u64>,
+    complexity: Vec<u64>,
+    function_length: Vec<u64>,
+}
+
+impl MetricSamples {
+    fn add(&mut self, file: &FileInfo) {
+        self.file_size.push(file.size);
+        if let Some(loc) = file.lines_of_code {
+            self.lines_of_code.push(loc as u64);
+        }
+        if let Some(complexity) = file.complexity {
+            self.complexity.push(complexity as u64);
+        }
+        self.function_length.extend(
+            file.functions
+                .iter()
+                .map(|function| function.lines_of_code as u64),
+        );
+    }
+
+    fn distributions(self) -> MetricDistributions {
+        MetricDistributions {
+            file_size: distribution(self.file_size),
+            lines_of_code: distribution(self.lines_of_code),
+            complexity: distribution(self.complexity),
+            function_length: distribution(self.function_length),
+        }
+    }
+}
+
+// Paths left out of the top-file and longest-function rankings by default
+const DEFAULT_RANK_EXCLUDES: &[&str] = &[
+    "*.lock",
+    "**/package-lock.json",
+    "**/pnpm-lock.yaml",
+    "*.min.js",
+    "*.min.css",
+    "**/node_modules/**",
+    "**/vendor/**",
+    "**/dist/**",
+];
 
 // Code metrics calculator
-pub struct CodeMetricsCalculator;
+pub struct CodeMetricsCalculator {
+    linguist: bool,
+    top_files: usize,
+    rank_excludes: GlobSet,
+}
+
+impl Default for CodeMetricsCalculator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl CodeMetricsCalculator {
-    pub fn calculate_metrics(&self, directory_info: &DirectoryInfo) -> CodeMetrics {
+    pub fn new() -> Self {
+        Self {
+            linguist: false,
+            top_files: 10,
+            rank_excludes: build_glob_set(DEFAULT_RANK_EXCLUDES.iter().copied())
+                .unwrap_or_default(),
+        }
+    }
+
+    // Language stats computed the way GitHub Linguist does, so percentages match GitHub
+    pub fn with_linguist(mut self, linguist: bool) -> Self {
+        self.linguist = linguist;
+        self
+    }
+
+    // Length of the top-N lists and extra globs excluded from them
+    pub fn with_ranking(mut self, top_files: usize, excludes: &[String]) -> Result<Self> {
+        self.top_files = top_files;
+        self.rank_excludes = build_glob_set(
+            DEFAULT_RANK_EXCLUDES
+                .iter()
+                .copied()
+                .chain(excludes.iter().map(|glob| glob.as_str())),
+        )?;
+        Ok(self)
+    }
+
+    pub fn calculate_metrics(
+        &self,
+        file_structure: &FileIndex,
+        config_files: &[ConfigFile],
+    ) -> CodeMetrics {
+        let classifier = self.linguist.then(|| LinguistClassifier::new(config_files));
         let mut language_stats: HashMap<String, LanguageStats> = HashMap::new();
+        let mut extension_stats: HashMap<String, ExtensionStats> = HashMap::new();
+        let mut language_bytes = 0u64;
+        let mut samples = MetricSamples::default();
+        let mut language_samples: HashMap<String, MetricSamples> = HashMap::new();
         let mut total_files = 0u32;
         let mut total_lines = 0u32;
         let mut total_loc = 0u32;
         let mut total_blank_lines = 0u32;
         let mut total_comment_lines = 0u32;
         let mut total_size = 0u64;
-        let mut all_files = Vec::new();
+        let all_files = file_structure.files();
 
-        self.collect_file_stats(directory_info, &mut all_files);
+        for file in all_files {
+            let extension = file
+                .extension
+                .as_ref()
+                .map(|e| e.to_lowercase())
+                .unwrap_or_else(|| "(none)".to_string());
+            let ext_stats =
+                extension_stats
+                    .entry(extension.clone())
+                    .or_insert_with(|| ExtensionStats {
+                        extension,
+                        file_count: 0,
+                        total_bytes: 0,
+                        lines_of_code: 0,
+                    });
+            ext_stats.file_count += 1;
+            ext_stats.total_bytes += file.size;
+            ext_stats.lines_of_code += file.lines_of_code.unwrap_or(0);
 
-        for file in &all_files {
             if file.is_text {
+                samples.add(file);
                 total_files += 1;
                 total_size += file.size;
 
@@ -34,7 +154,17 @@ impl CodeMetricsCalculator {
                 total_blank_lines += file.blank_lines.unwrap_or(0);
                 total_comment_lines += file.comment_lines.unwrap_or(0);
 
-                if let Some(language) = &file.language {
+                let language = match &classifier {
+                    Some(classifier) => classifier.language(file),
+                    None => file.language.clone(),
+                };
+
+                if let Some(language) = &language {
+                    language_bytes += file.size;
+                    language_samples
+                        .entry(language.clone())
+                        .or_default()
+                        .add(file);
                     let stats =
                         language_stats
                             .entry(language.clone())
@@ -58,8 +188,12 @@ impl CodeMetricsCalculator {
             }
         }
 
-        // Calculate percentages
-        let total_bytes = total_size;
+        // Calculate percentages; Linguist only counts the bytes of detected languages
+        let total_bytes = if self.linguist {
+            language_bytes
+        } else {
+            total_size
+        };
         for stats in language_stats.values_mut() {
             stats.percentage = if total_bytes > 0 {
                 (stats.total_bytes as f64 / total_bytes as f64) * 100.0
@@ -68,19 +202,45 @@ impl CodeMetricsCalculator {
             };
         }
 
-        // Find largest files
-        let mut largest_files = all_files.clone();
-        largest_files.sort_by(|a, b| b.size.cmp(&a.size));
-        largest_files.truncate(10);
+        // Average estimated complexity per source file of each language
+        for (language, stats) in language_stats.iter_mut() {
+            if let Some(samples) = language_samples.get(language)
+                && !samples.complexity.is_empty()
+            {
+                let total: u64 = samples.complexity.iter().sum();
+                stats.complexity_score = Some(total as f64 / samples.complexity.len() as f64);
+            }
+        }
+
+        let language_distributions = language_samples
+            .into_iter()
+            .map(|(language, samples)| (language, samples.distributions()))
+            .collect();
+
+        // Find largest files, only the ranked ones are copied
+        let ranked_files: Vec<&FileInfo> = all_files
+            .iter()
+            .filter(|f| !self.rank_excludes.is_match(&f.path))
+            .collect();
+
+        let mut largest_files = ranked_files.clone();
+        largest_files.sort_by_key(|f| std::cmp::Reverse(f.size));
+        largest_files.truncate(self.top_files);
+        let largest_files: Vec<FileInfo> = largest_files.into_iter().cloned().collect();
+
+        // Longest functions across the codebase
+        let mut longest_functions: Vec<FunctionMetrics> = ranked_files
+            .iter()
+            .flat_map(|f| f.functions.iter().cloned())
+            .collect();
+        longest_functions.sort_by_key(|f| std::cmp::Reverse(f.lines_of_code));
+        longest_functions.truncate(self.top_files);
 
         // Find most complex files (using LOC as a simple complexity metric)
-        let mut most_complex_files = all_files.clone();
-        most_complex_files.sort_by(|a, b| {
-            let a_complexity = a.lines_of_code.unwrap_or(0);
-            let b_complexity = b.lines_of_code.unwrap_or(0);
-            b_complexity.cmp(&a_complexity)
-        });
-        most_complex_files.truncate(10);
+        let mut most_complex_files = ranked_files;
+        most_complex_files.sort_by_key(|f| std::cmp::Reverse(f.lines_of_code.unwrap_or(0)));
+        most_complex_files.truncate(self.top_files);
+        let most_complex_files: Vec<FileInfo> = most_complex_files.into_iter().cloned().collect();
 
         let average_file_size = if total_files > 0 {
             total_size as f64 / total_files as f64
@@ -96,19 +256,63 @@ impl CodeMetricsCalculator {
             total_comment_lines,
             total_size,
             language_stats,
+            extension_stats,
+            distributions: samples.distributions(),
+            language_distributions,
+            longest_functions,
             average_file_size,
             largest_files,
             most_complex_files,
+            // Filled in from the shared walk
+            panic_density: None,
+            error_handling: None,
         }
     }
+}
 
-    fn collect_file_stats(&self, dir: &DirectoryInfo, all_files: &mut Vec<FileInfo>) {
-        for file in &dir.files {
-            all_files.push(file.clone());
-        }
+// Percentiles use the nearest-rank method, histogram buckets double in size
+fn distribution(mut values: Vec<u64>) -> Distribution {
+    if values.is_empty() {
+        return Distribution::default();
+    }
+    values.sort_unstable();
+
+    let percentile = |p: f64| {
+        let rank = ((p / 100.0) * values.len() as f64).ceil() as usize;
+        values[rank.clamp(1, values.len()) - 1]
+    };
 
-        for subdir in &dir.subdirectories {
-            self.collect_file_stats(subdir, all_files);
+    let mut histogram: Vec<HistogramBucket> = Vec::new();
+    for &value in &values {
+        let upper_bound = value.max(1).next_power_of_two();
+        match histogram.last_mut() {
+            Some(bucket) if bucket.upper_bound == upper_bound => bucket.count += 1,
+            _ => histogram.push(HistogramBucket {
+                upper_bound,
+                count: 1,
+            }),
         }
     }
+
+    Distribution {
+        count: values.len() as u32,
+        min: values[0],
+        max: values[values.len() - 1],
+        mean: values.iter().sum::<u64>() as f64 / values.len() as f64,
+        p50: percentile(50.0),
+        p90: percentile(90.0),
+        p99: percentile(99.0),
+        histogram,
+    }
+}
+
+fn build_glob_set<'a>(patterns: impl Iterator<Item = &'a str>) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(
+            Glob::new(pattern)
+                .map_err(|e| anyhow::anyhow!("Invalid exclude glob '{}': {}", pattern, e))?,
+        );
+    }
+    Ok(builder.build()?)
 }
@@ -0,0 +1,280 @@
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::types::FunctionMetrics;
+
+// Words that precede '(' in a signature without being the function name
+const NON_NAMES: &[&str] = &["fn", "func", "fun", "function", "def", "async"];
+
+// Rough, language-agnostic complexity estimates computed from source text:
+// cyclomatic complexity from decision points and function boundaries from
+// brace or indentation structure. There is no parser behind this, so names
+// and spans are best effort.
+pub struct ComplexityEstimator {
+    decision_points: Regex,
+    brace_function: Regex,
+    indent_function: Regex,
+    function_name: Regex,
+    assigned_name: Regex,
+}
+
+impl Default for ComplexityEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ComplexityEstimator {
+    pub fn new() -> Self {
+        Self {
+            decision_points: Regex::new(
+                r"\b(if|elif|for|foreach|while|case|catch|except|when)\b|&&|\|\|",
+            )
+            .unwrap(),
+            brace_function: Regex::new(
+                r"(\bfn\s+\w+|\bfunc\s+|\bfun\s+\w+|\bfunction\b|=>\s*\{\s*$|^\s*(?:[\w:<>\[\],*&]+\s+)+\**[\w:~]+\s*\([^;]*\)\s*(?:const\s*)?(?:\{\s*)?$)",
+            )
+            .unwrap(),
+            indent_function: Regex::new(r"^\s*(?:async\s+)?def\s+\w+").unwrap(),
+            function_name: Regex::new(r"(\w+)\s*(?:<[^>(]*>)?\s*\(").unwrap(),
+            assigned_name: Regex::new(r"(\w+)\s*[:=]\s*(?:async\s*)?(?:function\b|\(|\w+\s*=>)")
+                .unwrap(),
+        }
+    }
+
+    // Languages with code structure worth estimating, data and markup are skipped
+    pub fn supports(&self, language: &str) -> bool {
+        matches!(
+            language,
+            "Rust"
+                | "Python"
+                | "JavaScript"
+                | "TypeScript"
+                | "Java"
+                | "C"
+                | "C++"
+                | "C/C++ Header"
+                | "C++ Header"
+                | "C#"
+                | "Go"
+                | "PHP"
+                | "Ruby"
+                | "Swift"
+                | "Kotlin"
+                | "Scala"
+                | "Shell"
+        )
+    }
+
+    // 1 + number of decision points
+    pub fn cyclomatic_complexity(&self, lines: &[&str]) -> u32 {
+        1 + lines
+            .iter()
+            .map(|line| self.decision_points.find_iter(line).count() as u32)
+            .sum::<u32>()
+    }
+
+    // Functions found in the file, with their line span, complexity and parameter count
+    pub fn functions(&self, lines: &[&str], language: &str, file: &Path) -> Vec<FunctionMetrics> {
+        let spans = match language {
+            "Python" | "Ruby" => self.indented_function_spans(lines, language == "Ruby"),
+            _ => self.braced_function_spans(lines),
+        };
+
+        spans
+            .into_iter()
+            .map(|(start, end)| {
+                let body = &lines[start..=end];
+                let signature = lines[start..=(start + 2).min(end)].join(" ");
+
+                FunctionMetrics {
+                    name: self.function_name(lines[start]),
+                    file: file.to_path_buf(),
+                    start_line: start as u32 + 1,
+                    end_line: end as u32 + 1,
+                    lines_of_code: body.iter().filter(|line| !line.trim().is_empty()).count()
+                        as u32,
+                    complexity: self.cyclomatic_complexity(body),
+                    parameter_count: parameter_count(&signature),
+                }
+            })
+            .collect()
+    }
+
+    fn function_name(&self, line: &str) -> String {
+        self.function_name
+            .captures_iter(line)
+            .filter_map(|captures| captures.get(1))
+            .map(|name| name.as_str())
+            .find(|name| !NON_NAMES.contains(name))
+            .or_else(|| {
+                self.assigned_name
+                    .captures(line)
+                    .and_then(|captures| captures.get(1))
+                    .map(|name| name.as_str())
+            })
+            .unwrap_or("<anonymous>")
+            .to_string()
+    }
+
+    // (start, end) line indices, inclusive
+    fn braced_function_spans(&self, lines: &[&str]) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        let mut index = 0;
+
+        while index < lines.len() {
+            let line = lines[index];
+            let trimmed = line.trim_start();
+            let is_control_flow = ["if", "for", "while", "switch", "catch", "return", "else"]
+                .iter()
+                .any(|keyword| trimmed.starts_with(keyword));
+
+            if is_control_flow || !self.brace_function.is_match(line) {
+                index += 1;
+                continue;
+            }
+
+            // The body starts at the first brace on this line or the next two
+            let Some(body_start) =
+                (index..lines.len().min(index + 3)).find(|&i| lines[i].contains('{'))
+            else {
+                index += 1;
+                continue;
+            };
+
+            let mut depth = 0i32;
+            let mut end = None;
+            for (i, body_line) in lines.iter().enumerate().skip(body_start) {
+                depth += body_line.matches('{').count() as i32;
+                depth -= body_line.matches('}').count() as i32;
+                if depth <= 0 {
+                    end = Some(i);
+                    break;
+                }
+            }
+
+            match end {
+                Some(end) => {
+                    spans.push((index, end));
+                    index = end + 1;
+                }
+                None => break,
+            }
+        }
+
+        spans
+    }
+
+    fn indented_function_spans(
+        &self,
+        lines: &[&str],
+        ends_with_keyword: bool,
+    ) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+
+        for (index, line) in lines.iter().enumerate() {
+            if !self.indent_function.is_match(line) {
+                continue;
+            }
+
+            let indent = indentation(line);
+            let mut end = index;
+            for (i, body_line) in lines.iter().enumerate().skip(index + 1) {
+                if body_line.trim().is_empty() {
+                    continue;
+                }
+                if indentation(body_line) <= indent {
+                    // Ruby closes the method with an `end` at the same indentation
+                    if ends_with_keyword && body_line.trim() == "end" {
+                        end = i;
+                    }
+                    break;
+                }
+                end = i;
+            }
+
+            spans.push((index, end));
+        }
+
+        spans
+    }
+}
+
+// Parameters between the first pair of parentheses, ignoring the receiver
+fn parameter_count(signature: &str) -> u32 {
+    let Some(open) = signature.find('(') else {
+        return 0;
+    };
+
+    let mut depth = 0;
+    let mut params = String::new();
+    let mut previous = ' ';
+    for c in signature[open..].chars() {
+        match c {
+            // The arrow of `-> T` and `=> x` closes nothing
+            '>' if matches!(previous, '-' | '=') => {}
+            '(' | '<' | '[' | '{' => depth += 1,
+            ')' | '>' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+        previous = c;
+        if depth == 0 {
+            break;
+        }
+        // Only top-level commas separate parameters
+        params.push(if c == ',' && depth > 1 { ';' } else { c });
+    }
+
+    params
+        .trim_start_matches('(')
+        .split(',')
+        .map(|param| param.trim())
+        .filter(|param| {
+            !param.is_empty()
+                && !matches!(
+                    *param,
+                    "self" | "&self" | "&mut self" | "mut self" | "cls" | "this"
+                )
+        })
+        .count() as u32
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generic_parameters_count_once() {
+        assert_eq!(
+            parameter_count("fn f(h: HashMap<K, V>, v: Vec<(u8, u8)>) {"),
+            2
+        );
+        assert_eq!(
+            parameter_count("fn f<T: Into<String>>(&self, value: T) -> Option<T> {"),
+            1
+        );
+    }
+
+    #[test]
+    fn arrows_in_parameters_close_nothing() {
+        assert_eq!(parameter_count("fn f(g: fn(u8) -> u8, n: usize) {"), 2);
+        assert_eq!(
+            parameter_count(
+                "fn f(f: impl Fn(u8) -> u8, m: HashMap<u8, Box<dyn Fn() -> u8>>, n: u8) {"
+            ),
+            3
+        );
+        assert_eq!(parameter_count("function f(a, cb = (x) => x, b) {"), 3);
+    }
+
+    #[test]
+    fn receivers_are_not_parameters() {
+        assert_eq!(parameter_count("fn f(&mut self) -> u8 {"), 0);
+        assert_eq!(parameter_count("def f(self, a, b):"), 2);
+    }
+}
@@ -0,0 +1,516 @@
+use std::path::{Component, Path, PathBuf};
+
+use log::debug;
+use serde::Deserialize;
+use serde_yaml::Value;
+
+use crate::analyzers::entry_points::dockerfile_instructions;
+use crate::analyzers::scan::{FileClassifier, ScannedFile};
+use crate::types::{DeployedService, DeploymentTopology, ServiceLink};
+use crate::utils::to_slash_path;
+
+// Services kept in the topology, large clusters are cut off
+const MAX_SERVICES: usize = 50;
+
+// Kubernetes kinds whose pods run the application
+const WORKLOAD_KINDS: &[&str] = &[
+    "Deployment",
+    "StatefulSet",
+    "DaemonSet",
+    "ReplicaSet",
+    "Job",
+    "CronJob",
+    "Pod",
+];
+
+// Aggregates Dockerfiles, compose files, Helm charts and Kubernetes manifests
+// into services and the links between them during the shared walk. Links are
+// resolved once every file is seen, since their targets can come later
+#[derive(Default)]
+pub struct DeploymentClassifier {
+    topology: DeploymentTopology,
+    charts: Vec<(PathBuf, Value)>, // Chart.yaml, by chart directory
+    chart_values: Vec<(PathBuf, Value)>, // values.yaml, by directory
+    builds: Vec<(usize, PathBuf)>, // Compose service, Dockerfile it builds
+    depends_on: Vec<(usize, PathBuf, String)>, // Compose service, compose file, dependency
+    selectors: Vec<(usize, serde_yaml::Mapping)>, // Kubernetes Service, pod selector
+    pod_labels: Vec<(usize, serde_yaml::Mapping)>, // Workload, pod template labels
+    backends: Vec<(usize, String)>, // Ingress, backend Service name
+}
+
+impl DeploymentClassifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn finish(mut self) -> DeploymentTopology {
+        let charts = std::mem::take(&mut self.charts);
+        for (dir, chart) in charts {
+            let values = self
+                .chart_values
+                .iter()
+                .find(|(values_dir, _)| *values_dir == dir)
+                .map(|(_, values)| values.clone())
+                .unwrap_or_default();
+            self.add_chart(&dir, &chart, &values);
+        }
+
+        let mut links = Vec::new();
+        for (from, dockerfile) in &self.builds {
+            if let Some(to) = self.find(|s| s.kind == "dockerfile" && s.source == *dockerfile) {
+                links.push((*from, to, "built from"));
+            }
+        }
+        for (from, compose_file, name) in &self.depends_on {
+            if let Some(to) = self.find(|s| s.source == *compose_file && s.name == *name) {
+                links.push((*from, to, "depends on"));
+            }
+        }
+        for (from, selector) in &self.selectors {
+            for (to, labels) in &self.pod_labels {
+                if !selector.is_empty() && selector.iter().all(|(k, v)| labels.get(k) == Some(v)) {
+                    links.push((*from, *to, "routes to"));
+                }
+            }
+        }
+        for (from, name) in &self.backends {
+            if let Some(to) = self.find(|s| s.kind.starts_with("Service") && s.name == *name) {
+                links.push((*from, to, "routes to"));
+            }
+        }
+
+        self.topology.links = links
+            .into_iter()
+            .map(|(from, to, relation)| ServiceLink {
+                from,
+                to,
+                relation: relation.to_string(),
+            })
+            .collect();
+        self.topology.sources.sort();
+        self.topology.sources.dedup();
+        self.topology
+    }
+
+    fn find(&self, predicate: impl Fn(&DeployedService) -> bool) -> Option<usize> {
+        self.topology.services.iter().position(predicate)
+    }
+
+    // Index of the added service, None once the topology is full
+    fn add_service(&mut self, service: DeployedService) -> Option<usize> {
+        if self.topology.services.len() >= MAX_SERVICES {
+            return None;
+        }
+        self.topology.sources.push(service.source.clone());
+        self.topology.services.push(service);
+        Some(self.topology.services.len() - 1)
+    }
+
+    // The final stage: its base image and exposed ports
+    fn add_dockerfile(&mut self, path: &Path, content: &str) {
+        let mut image = None;
+        let mut ports = Vec::new();
+        for instruction in dockerfile_instructions(content) {
+            let (keyword, argument) = instruction
+                .split_once(char::is_whitespace)
+                .unwrap_or((instruction.as_str(), ""));
+            match keyword.to_uppercase().as_str() {
+                "FROM" => {
+                    image = argument
+                        .split_whitespace()
+                        .find(|word| !word.starts_with("--"))
+                        .map(|word| word.to_string());
+                    ports.clear();
+                }
+                "EXPOSE" => ports.extend(
+                    argument
+                        .split_whitespace()
+                        .map(|port| port.trim_end_matches("/tcp").to_string()),
+                ),
+                _ => {}
+            }
+        }
+
+        // Dockerfile.worker and worker.dockerfile name their service
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        let variant = file_name
+            .split_once('.')
+            .map(|(stem, extension)| {
+                if stem.eq_ignore_ascii_case("dockerfile") {
+                    extension
+                } else {
+                    stem
+                }
+            })
+            .filter(|variant| !variant.is_empty());
+        let directory = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .and_then(|n| n.to_str());
+        let name = variant.or(directory).unwrap_or("app").to_string();
+
+        self.add_service(DeployedService {
+            name,
+            kind: "dockerfile".to_string(),
+            source: path.to_path_buf(),
+            image,
+            ports,
+            replicas: None,
+            cpu_request: None,
+            memory_request: None,
+        });
+    }
+
+    fn add_compose(&mut self, path: &Path, content: &str) {
+        let compose: Value = match serde_yaml::from_str(content) {
+            Ok(compose) => compose,
+            Err(e) => {
+                debug!("Can't parse {:?}: {}", path, e);
+                return;
+            }
+        };
+        let Some(services) = compose.get("services").and_then(|s| s.as_mapping()) else {
+            return;
+        };
+        let directory = path.parent().unwrap_or(Path::new(""));
+
+        for (name, service) in services {
+            let Some(name) = name.as_str() else {
+                continue;
+            };
+            let ports = service
+                .get("ports")
+                .and_then(|p| p.as_sequence())
+                .into_iter()
+                .flatten()
+                .filter_map(compose_port)
+                .collect();
+            let deploy = service.get("deploy");
+            let reservations = deploy
+                .and_then(|d| d.get("resources"))
+                .and_then(|r| r.get("reservations"));
+
+            let Some(index) = self.add_service(DeployedService {
+                name: name.to_string(),
+                kind: "compose".to_string(),
+                source: path.to_path_buf(),
+                image: service.get("image").and_then(scalar),
+                ports,
+                replicas: deploy
+                    .and_then(|d| d.get("replicas"))
+                    .and_then(|r| r.as_u64())
+                    .map(|r| r as u32),
+                cpu_request: reservations.and_then(|r| r.get("cpus")).and_then(scalar),
+                memory_request: reservations.and_then(|r| r.get("memory")).and_then(scalar),
+            }) else {
+                return;
+            };
+
+            // build: is a context directory or { context, dockerfile }
+            if let Some(build) = service.get("build") {
+                let context = build
+                    .as_str()
+                    .or_else(|| build.get("context").and_then(|c| c.as_str()))
+                    .unwrap_or(".");
+                let dockerfile = build
+                    .get("dockerfile")
+                    .and_then(|d| d.as_str())
+                    .unwrap_or("Dockerfile");
+                self.builds
+                    .push((index, normalize(&directory.join(context).join(dockerfile))));
+            }
+
+            // depends_on: is a list of names or a map keyed by them
+            match service.get("depends_on") {
+                Some(Value::Sequence(dependencies)) => {
+                    for dependency in dependencies.iter().filter_map(|d| d.as_str()) {
+                        self.depends_on
+                            .push((index, path.to_path_buf(), dependency.to_string()));
+                    }
+                }
+                Some(Value::Mapping(dependencies)) => {
+                    for dependency in dependencies.keys().filter_map(|d| d.as_str()) {
+                        self.depends_on
+                            .push((index, path.to_path_buf(), dependency.to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn add_chart(&mut self, dir: &Path, chart: &Value, values: &Value) {
+        let name = chart
+            .get("name")
+            .and_then(|n| n.as_str())
+            .or_else(|| dir.file_name().and_then(|n| n.to_str()))
+            .unwrap_or("chart");
+        let image = values.get("image").and_then(|image| {
+            let repository = image.get("repository").and_then(scalar)?;
+            Some(match image.get("tag").and_then(scalar) {
+                Some(tag) if !tag.is_empty() => format!("{}:{}", repository, tag),
+                _ => repository,
+            })
+        });
+        let requests = values.get("resources").and_then(|r| r.get("requests"));
+
+        self.add_service(DeployedService {
+            name: name.to_string(),
+            kind: "helm chart".to_string(),
+            source: dir.join("Chart.yaml"),
+            image,
+            ports: values
+                .get("service")
+                .and_then(|s| s.get("port"))
+                .and_then(scalar)
+                .into_iter()
+                .collect(),
+            replicas: values
+                .get("replicaCount")
+                .and_then(|r| r.as_u64())
+                .map(|r| r as u32),
+            cpu_request: requests.and_then(|r| r.get("cpu")).and_then(scalar),
+            memory_request: requests.and_then(|r| r.get("memory")).and_then(scalar),
+        });
+    }
+
+    fn add_manifests(&mut self, path: &Path, content: &str) {
+        for document in serde_yaml::Deserializer::from_str(content) {
+            let Ok(manifest) = Value::deserialize(document) else {
+                debug!("Can't parse a document of {:?}", path);
+                return;
+            };
+            let (Some(kind), Some(name)) = (
+                manifest.get("kind").and_then(|k| k.as_str()),
+                manifest
+                    .get("metadata")
+                    .and_then(|m| m.get("name"))
+                    .and_then(|n| n.as_str()),
+            ) else {
+                continue;
+            };
+            let spec = manifest.get("spec");
+
+            if WORKLOAD_KINDS.contains(&kind) {
+                self.add_workload(path, kind, name, &manifest);
+            } else if kind == "Service" {
+                let ports = spec
+                    .and_then(|s| s.get("ports"))
+                    .and_then(|p| p.as_sequence())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|port| {
+                        let exposed = port.get("port").and_then(scalar)?;
+                        Some(match port.get("targetPort").and_then(scalar) {
+                            Some(target) if target != exposed => format!("{}:{}", exposed, target),
+                            _ => exposed,
+                        })
+                    })
+                    .collect();
+                let kind = match spec.and_then(|s| s.get("type")).and_then(|t| t.as_str()) {
+                    Some(service_type) if service_type != "ClusterIP" => {
+                        format!("Service ({})", service_type)
+                    }
+                    _ => "Service".to_string(),
+                };
+                let selector = spec
+                    .and_then(|s| s.get("selector"))
+                    .and_then(|s| s.as_mapping())
+                    .cloned();
+                if let Some(index) = self.add_service(manifest_service(path, &kind, name, ports))
+                    && let Some(selector) = selector
+                {
+                    self.selectors.push((index, selector));
+                }
+            } else if kind == "Ingress" {
+                let mut backends = Vec::new();
+                let rules = spec
+                    .and_then(|s| s.get("rules"))
+                    .and_then(|r| r.as_sequence())
+                    .into_iter()
+                    .flatten();
+                for rule_path in rules
+                    .filter_map(|rule| rule.get("http")?.get("paths")?.as_sequence())
+                    .flatten()
+                {
+                    backends.extend(rule_path.get("backend").and_then(backend_service));
+                }
+                backends.extend(
+                    spec.and_then(|s| s.get("defaultBackend"))
+                        .and_then(backend_service),
+                );
+                if let Some(index) =
+                    self.add_service(manifest_service(path, kind, name, Vec::new()))
+                {
+                    for backend in backends {
+                        self.backends.push((index, backend));
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_workload(&mut self, path: &Path, kind: &str, name: &str, manifest: &Value) {
+        let spec = manifest.get("spec");
+        // Pod template of the workload, a Pod is its own
+        let template = match kind {
+            "Pod" => Some(manifest),
+            "CronJob" => spec
+                .and_then(|s| s.get("jobTemplate"))
+                .and_then(|j| j.get("spec"))
+                .and_then(|s| s.get("template")),
+            _ => spec.and_then(|s| s.get("template")),
+        };
+        let containers: Vec<&Value> = template
+            .and_then(|t| t.get("spec"))
+            .and_then(|s| s.get("containers"))
+            .and_then(|c| c.as_sequence())
+            .into_iter()
+            .flatten()
+            .collect();
+        let joined = |field: &dyn Fn(&Value) -> Option<String>| {
+            let values: Vec<String> = containers.iter().filter_map(|c| field(c)).collect();
+            (!values.is_empty()).then(|| values.join(", "))
+        };
+        let request = |resource: &str| {
+            joined(&|container| {
+                container
+                    .get("resources")?
+                    .get("requests")?
+                    .get(resource)
+                    .and_then(scalar)
+            })
+        };
+
+        let mut service = manifest_service(
+            path,
+            kind,
+            name,
+            containers
+                .iter()
+                .filter_map(|c| c.get("ports")?.as_sequence())
+                .flatten()
+                .filter_map(|port| port.get("containerPort").and_then(scalar))
+                .collect(),
+        );
+        service.image = joined(&|container| container.get("image").and_then(scalar));
+        service.replicas = spec
+            .and_then(|s| s.get("replicas"))
+            .and_then(|r| r.as_u64())
+            .map(|r| r as u32);
+        service.cpu_request = request("cpu");
+        service.memory_request = request("memory");
+
+        let labels = template
+            .and_then(|t| t.get("metadata"))
+            .and_then(|m| m.get("labels"))
+            .and_then(|l| l.as_mapping())
+            .cloned();
+        if let Some(index) = self.add_service(service)
+            && let Some(labels) = labels
+        {
+            self.pod_labels.push((index, labels));
+        }
+    }
+}
+
+impl FileClassifier for DeploymentClassifier {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        if !file.in_structure {
+            return;
+        }
+        let file_name = file.file_name().to_lowercase();
+        let is_yaml = file_name.ends_with(".yml") || file_name.ends_with(".yaml");
+        let is_dockerfile = file_name == "dockerfile"
+            || file_name == "containerfile"
+            || file_name.starts_with("dockerfile.")
+            || file_name.ends_with(".dockerfile");
+        if !is_dockerfile && !is_yaml {
+            return;
+        }
+        let Some(content) = file.content() else {
+            return;
+        };
+        let path = file.relative;
+        let directory = path.parent().unwrap_or(Path::new("")).to_path_buf();
+
+        if is_dockerfile {
+            self.add_dockerfile(path, content);
+        } else if file_name.starts_with("docker-compose") || file_name.starts_with("compose.") {
+            self.add_compose(path, content);
+        } else if file_name == "chart.yaml" {
+            if let Ok(chart) = serde_yaml::from_str(content) {
+                self.charts.push((directory, chart));
+            }
+        } else if file_name == "values.yaml" {
+            if let Ok(values) = serde_yaml::from_str(content) {
+                self.chart_values.push((directory, values));
+            }
+        } else if content.contains("apiVersion")
+            && content.contains("kind")
+            && !content.contains("{{")
+        {
+            // Helm templates aren't YAML until rendered and are skipped
+            self.add_manifests(path, content);
+        }
+    }
+}
+
+fn manifest_service(path: &Path, kind: &str, name: &str, ports: Vec<String>) -> DeployedService {
+    DeployedService {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        source: path.to_path_buf(),
+        image: None,
+        ports,
+        replicas: None,
+        cpu_request: None,
+        memory_request: None,
+    }
+}
+
+// A string or number, as YAML leaves ports and quantities either way
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+// "8080:80" short syntax, or { published, target } long syntax
+fn compose_port(port: &Value) -> Option<String> {
+    if let Some(port) = scalar(port) {
+        return Some(port);
+    }
+    let target = port.get("target").and_then(scalar)?;
+    Some(match port.get("published").and_then(scalar) {
+        Some(published) => format!("{}:{}", published, target),
+        None => target,
+    })
+}
+
+// networking.k8s.io/v1 service.name, or the older serviceName
+fn backend_service(backend: &Value) -> Option<String> {
+    backend
+        .get("service")
+        .and_then(|s| s.get("name"))
+        .or_else(|| backend.get("serviceName"))
+        .and_then(|n| n.as_str())
+        .map(|n| n.to_string())
+}
+
+// Resolves . and .. so build contexts compare equal to walked paths
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    to_slash_path(&normalized)
+}
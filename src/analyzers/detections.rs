@@ -0,0 +1,588 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobMatcher};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::file_index::FileIndex;
+use crate::types::{ConfigFile, Detection, EvidenceRef};
+use crate::utils::manifest_line;
+
+// Framework, tool and database detections shipped with the analyzer
+const BUILTIN_PACK: &str = include_str!("detections.yaml");
+
+// Default confidence of each kind of condition: a declared dependency is near
+// certain, a file name is a guess
+const DEPENDENCY_CONFIDENCE: f64 = 0.95;
+const MANIFEST_CONFIDENCE: f64 = 0.9;
+const CONTENT_CONFIDENCE: f64 = 0.7;
+const FILE_CONFIDENCE: f64 = 0.6;
+
+// Files kept per file or content condition, a `tests/**` glob shouldn't list
+// every test. Every manifest is kept
+const MAX_EVIDENCE: usize = 5;
+
+// `[detections]` in analyzer.toml
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DetectionsConfig {
+    pub packs: Vec<PathBuf>, // YAML rule packs, relative to the config file
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulePack {
+    detections: Vec<DetectionConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DetectionConfig {
+    name: String,
+    category: Category,
+    #[serde(default)]
+    all: Vec<ConditionConfig>,
+    #[serde(default)]
+    any: Vec<ConditionConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConditionConfig {
+    dependency: Option<String>,
+    manifest: Option<String>,
+    file: Option<String>,
+    content: Option<String>,
+    confidence: Option<f64>, // Defaults to the condition kind's
+}
+
+// ProjectInfo list a detection lands in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    ProjectType,
+    Framework,
+    BuildTool,
+    PackageManager,
+    TestingFramework,
+    CiCd,
+    Deployment,
+    Database,
+}
+
+enum Signal {
+    Dependency {
+        name: GlobMatcher,
+        manifest: Option<String>,
+    },
+    Manifest(String),
+    File(GlobMatcher),
+    Content {
+        pattern: Regex,
+        file: Option<GlobMatcher>,
+    },
+}
+
+struct Condition {
+    signal: Signal,
+    confidence: f64,
+}
+
+struct DetectionRule {
+    name: String,
+    category: Category,
+    all: Vec<Condition>,
+    any: Vec<Condition>,
+}
+
+// Data-driven detection rules: the built-in pack, then the packs listed under
+// `[detections]` in analyzer.toml, so new frameworks are a YAML entry away
+pub struct DetectionRules {
+    rules: Vec<DetectionRule>,
+}
+
+impl DetectionRules {
+    pub fn builtin() -> Self {
+        let mut rules = Self { rules: Vec::new() };
+        rules
+            .add_pack(BUILTIN_PACK)
+            .expect("the built-in detection pack is valid");
+        rules
+    }
+
+    // Detections of a pack are appended, except those replacing an earlier one
+    // with the same name and category
+    fn add_pack(&mut self, content: &str) -> Result<()> {
+        let pack: RulePack = serde_yaml::from_str(content)?;
+        for config in pack.detections {
+            let rule = DetectionRule::from_config(config)?;
+            match self
+                .rules
+                .iter_mut()
+                .find(|r| r.name == rule.name && r.category == rule.category)
+            {
+                Some(existing) => *existing = rule,
+                None => self.rules.push(rule),
+            }
+        }
+        Ok(())
+    }
+
+    // Names the rules of a category can detect
+    pub fn names(&self, category: Category) -> impl Iterator<Item = &str> {
+        self.rules
+            .iter()
+            .filter(move |rule| rule.category == category)
+            .map(|rule| rule.name.as_str())
+    }
+
+    // Matched detections in pack order
+    pub fn detect(
+        &self,
+        config_files: &[ConfigFile],
+        file_structure: &FileIndex,
+    ) -> Vec<(Category, Detection)> {
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                let detection = rule.evaluate(config_files, file_structure)?;
+                Some((rule.category, detection))
+            })
+            .collect()
+    }
+}
+
+impl DetectionRule {
+    fn from_config(config: DetectionConfig) -> Result<Self> {
+        if config.name.trim().is_empty() {
+            anyhow::bail!("a detection has no name");
+        }
+        if config.all.is_empty() && config.any.is_empty() {
+            anyhow::bail!("detection {} has no conditions", config.name);
+        }
+        let conditions = |conditions: Vec<ConditionConfig>| -> Result<Vec<Condition>> {
+            conditions
+                .into_iter()
+                .map(Condition::from_config)
+                .collect::<Result<_>>()
+                .with_context(|| format!("detection {}", config.name))
+        };
+        Ok(Self {
+            all: conditions(config.all)?,
+            any: conditions(config.any)?,
+            name: config.name,
+            category: config.category,
+        })
+    }
+
+    // The detection is as sure as its strongest matching condition, and keeps
+    // the first few signals of each as evidence
+    fn evaluate(
+        &self,
+        config_files: &[ConfigFile],
+        file_structure: &FileIndex,
+    ) -> Option<Detection> {
+        let mut detection = Detection {
+            name: self.name.clone(),
+            confidence: 0.0,
+            evidence: Vec::new(),
+        };
+        let mut matched = |condition: &Condition| {
+            let found = condition.signal.evidence(config_files, file_structure);
+            if found.is_empty() {
+                return false;
+            }
+            merge_detection(
+                &mut detection,
+                Detection {
+                    name: String::new(),
+                    confidence: condition.confidence,
+                    evidence: found,
+                },
+            );
+            true
+        };
+
+        if !self.all.iter().all(&mut matched) {
+            return None;
+        }
+        // Every `any` condition runs, for their evidence
+        let mut any_matched = self.any.is_empty();
+        for condition in &self.any {
+            any_matched |= matched(condition);
+        }
+        if !any_matched {
+            return None;
+        }
+        Some(detection)
+    }
+}
+
+// Adds a detection to a ProjectInfo list, folding it into an entry of the same
+// name: several manifests or rules showing one technology make one detection
+// with the evidence of each
+pub fn add_detection(detections: &mut Vec<Detection>, detection: Detection) {
+    match detections
+        .iter_mut()
+        .find(|d| d.name.eq_ignore_ascii_case(&detection.name))
+    {
+        Some(existing) => merge_detection(existing, detection),
+        None => detections.push(detection),
+    }
+}
+
+// Keeps the stronger confidence and the evidence not already recorded
+fn merge_detection(into: &mut Detection, from: Detection) {
+    into.confidence = into.confidence.max(from.confidence);
+    for evidence in from.evidence {
+        let known = into.evidence.iter().any(|e| {
+            e.path == evidence.path && e.line == evidence.line && e.label == evidence.label
+        });
+        if !known {
+            into.evidence.push(evidence);
+        }
+    }
+}
+
+impl Condition {
+    fn from_config(config: ConditionConfig) -> Result<Self> {
+        let (signal, default_confidence) = match config {
+            ConditionConfig {
+                dependency: Some(dependency),
+                manifest,
+                file: None,
+                content: None,
+                ..
+            } => (
+                Signal::Dependency {
+                    name: glob(&dependency, true)?,
+                    manifest,
+                },
+                DEPENDENCY_CONFIDENCE,
+            ),
+            ConditionConfig {
+                dependency: None,
+                manifest: Some(manifest),
+                file: None,
+                content: None,
+                ..
+            } => (Signal::Manifest(manifest), MANIFEST_CONFIDENCE),
+            ConditionConfig {
+                dependency: None,
+                manifest: None,
+                file: Some(file),
+                content: None,
+                ..
+            } => (Signal::File(glob(&file, false)?), FILE_CONFIDENCE),
+            ConditionConfig {
+                dependency: None,
+                manifest: None,
+                file,
+                content: Some(content),
+                ..
+            } => (
+                Signal::Content {
+                    pattern: Regex::new(&content)
+                        .with_context(|| format!("invalid content regex {}", content))?,
+                    file: file.map(|file| glob(&file, false)).transpose()?,
+                },
+                CONTENT_CONFIDENCE,
+            ),
+            _ => anyhow::bail!(
+                "a condition is one of dependency (with an optional manifest), manifest, file, or content (with an optional file)"
+            ),
+        };
+        let confidence = config.confidence.unwrap_or(default_confidence);
+        if !(0.0..=1.0).contains(&confidence) {
+            anyhow::bail!("confidence {} is not between 0 and 1", confidence);
+        }
+        Ok(Self { signal, confidence })
+    }
+}
+
+impl Signal {
+    // Where the signal was seen, labelled with what matched
+    fn evidence(
+        &self,
+        config_files: &[ConfigFile],
+        file_structure: &FileIndex,
+    ) -> Vec<EvidenceRef> {
+        let evidence = |label: String, path: &Path, line: Option<u32>| EvidenceRef {
+            label,
+            path: path.to_path_buf(),
+            line,
+            url: None, // Linked once the analysis is complete
+        };
+
+        match self {
+            Self::Dependency { name, manifest } => config_files
+                .iter()
+                .filter(|config| manifest.as_ref().is_none_or(|m| *m == config.file_type))
+                .filter_map(|config| {
+                    // Dev dependencies are keyed "name (dev)"
+                    let (dependency, version) = config
+                        .parsed_dependencies
+                        .as_ref()?
+                        .iter()
+                        .map(|(key, version)| (key.trim_end_matches(" (dev)"), version))
+                        .find(|(key, _)| name.is_match(key))?;
+                    Some(evidence(
+                        format!("dependency {}@{}", dependency, version),
+                        &config.path,
+                        manifest_line(&config.content, dependency),
+                    ))
+                })
+                .collect(),
+            Self::Manifest(manifest) => config_files
+                .iter()
+                .filter(|config| config.file_type == *manifest)
+                .map(|config| evidence(format!("{} manifest", manifest), &config.path, None))
+                .collect(),
+            Self::File(file) => {
+                let mut paths: Vec<&Path> = config_files
+                    .iter()
+                    .map(|config| config.path.as_path())
+                    .chain(file_structure.files().iter().map(|f| f.path.as_path()))
+                    .filter(|path| file.is_match(path))
+                    .collect();
+                paths.sort();
+                paths.dedup();
+                paths
+                    .into_iter()
+                    .take(MAX_EVIDENCE)
+                    .map(|path| evidence(format!("file matching {}", file.glob()), path, None))
+                    .collect()
+            }
+            Self::Content { pattern, file } => {
+                let in_scope = |path: &Path| file.as_ref().is_none_or(|file| file.is_match(path));
+                let configs = config_files
+                    .iter()
+                    .filter(|config| in_scope(&config.path))
+                    .map(|config| (config.path.as_path(), config.content.as_str()));
+                let previews = file_structure
+                    .files()
+                    .iter()
+                    .filter(|f| in_scope(&f.path))
+                    .filter(|f| !config_files.iter().any(|config| config.path == f.path))
+                    .filter_map(|f| Some((f.path.as_path(), f.content_preview.as_deref()?)));
+                configs
+                    .chain(previews)
+                    .filter_map(|(path, content)| {
+                        let found = pattern.find(content)?;
+                        let line = content[..found.start()].matches('\n').count() as u32 + 1;
+                        Some(evidence(
+                            format!("content matching {}", pattern),
+                            path,
+                            Some(line),
+                        ))
+                    })
+                    .take(MAX_EVIDENCE)
+                    .collect()
+            }
+        }
+    }
+}
+
+// Dependency names compare case-insensitively. Path wildcards stay within a
+// directory, `**` crosses them
+fn glob(pattern: &str, case_insensitive: bool) -> Result<GlobMatcher> {
+    Ok(GlobBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .literal_separator(!case_insensitive)
+        .build()
+        .with_context(|| format!("invalid glob {}", pattern))?
+        .compile_matcher())
+}
+
+// The built-in pack plus the configured ones
+pub fn load_detections(config: &DetectionsConfig) -> Result<DetectionRules> {
+    let mut rules = DetectionRules::builtin();
+    for pack in &config.packs {
+        let content = fs::read_to_string(pack)
+            .with_context(|| format!("detection pack {}", pack.display()))?;
+        rules
+            .add_pack(&content)
+            .with_context(|| format!("detection pack {}", pack.display()))?;
+    }
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn config_file(path: &str, file_type: &str, content: &str, deps: &[&str]) -> ConfigFile {
+        ConfigFile {
+            path: PathBuf::from(path),
+            file_type: file_type.to_string(),
+            content: content.to_string(),
+            parsed_dependencies: Some(
+                deps.iter()
+                    .map(|dep| (dep.to_string(), "1.0".to_string()))
+                    .collect::<HashMap<_, _>>(),
+            ),
+            scripts: None,
+        }
+    }
+
+    fn pack(rules: &str) -> Result<DetectionRules> {
+        let mut detections = DetectionRules { rules: Vec::new() };
+        detections.add_pack(rules)?;
+        Ok(detections)
+    }
+
+    fn detected(rules: &DetectionRules, config_files: &[ConfigFile]) -> Vec<(Category, Detection)> {
+        rules.detect(config_files, &FileIndex::new(Path::new("")))
+    }
+
+    #[test]
+    fn builtin_pack_loads() {
+        assert!(DetectionRules::builtin().names(Category::Framework).count() > 0);
+    }
+
+    #[test]
+    fn conditions_take_one_signal() {
+        let error = pack(
+            "detections:\n  - name: X\n    category: framework\n    any:\n      - dependency: x\n        file: x.toml\n",
+        )
+        .err()
+        .unwrap();
+        assert!(format!("{:#}", error).contains("a condition is one of"));
+        assert!(
+            pack("detections:\n  - name: X\n    category: framework\n    any:\n      - {}\n")
+                .is_err()
+        );
+        // A manifest narrows a dependency, a file narrows a content pattern
+        assert!(pack("detections:\n  - name: X\n    category: framework\n    any:\n      - dependency: x\n        manifest: Cargo.toml\n      - content: x\n        file: '*.rs'\n").is_ok());
+    }
+
+    #[test]
+    fn confidence_is_between_zero_and_one() {
+        let rule = |confidence: &str| {
+            pack(&format!(
+                "detections:\n  - name: X\n    category: framework\n    any:\n      - file: x\n        confidence: {confidence}\n"
+            ))
+        };
+        assert!(rule("1.5").is_err());
+        assert!(rule("-0.1").is_err());
+        assert!(rule("0").is_ok());
+        assert!(rule("1").is_ok());
+    }
+
+    #[test]
+    fn detections_need_a_name_and_conditions() {
+        assert!(pack("detections:\n  - name: X\n    category: framework\n").is_err());
+        assert!(
+            pack(
+                "detections:\n  - name: ' '\n    category: framework\n    any:\n      - file: x\n"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn all_conditions_must_match() {
+        let rules = pack(
+            "detections:\n  - name: Axum\n    category: framework\n    all:\n      - dependency: axum\n      - dependency: tokio\n",
+        )
+        .unwrap();
+        let axum_only = config_file("Cargo.toml", "Cargo.toml", "", &["axum"]);
+        assert!(detected(&rules, &[axum_only]).is_empty());
+
+        let both = config_file("Cargo.toml", "Cargo.toml", "", &["axum", "tokio"]);
+        let found = detected(&rules, &[both]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, Category::Framework);
+        assert_eq!(found[0].1.evidence.len(), 2);
+    }
+
+    #[test]
+    fn one_any_condition_is_enough_and_the_strongest_wins() {
+        let rules = pack(
+            "detections:\n  - name: Docker\n    category: deployment\n    any:\n      - file: Dockerfile\n      - content: '^FROM '\n        confidence: 0.8\n      - file: compose.yaml\n",
+        )
+        .unwrap();
+        let dockerfile = config_file("Dockerfile", "Dockerfile", "FROM rust\n", &[]);
+        let found = detected(&rules, &[dockerfile]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].1.confidence, 0.8);
+        // Both matching conditions leave evidence
+        assert_eq!(found[0].1.evidence.len(), 2);
+
+        let other = config_file("Cargo.toml", "Cargo.toml", "", &[]);
+        assert!(detected(&rules, &[other]).is_empty());
+    }
+
+    #[test]
+    fn all_and_any_combine() {
+        let rules = pack(
+            "detections:\n  - name: Next.js\n    category: framework\n    all:\n      - dependency: next\n    any:\n      - dependency: react\n      - dependency: preact\n",
+        )
+        .unwrap();
+        let next_only = config_file("package.json", "package.json", "", &["next"]);
+        assert!(detected(&rules, &[next_only]).is_empty());
+        let with_preact = config_file("package.json", "package.json", "", &["next", "preact"]);
+        assert_eq!(detected(&rules, &[with_preact]).len(), 1);
+    }
+
+    #[test]
+    fn packs_replace_rules_of_the_same_name_and_category() {
+        let mut rules = pack(
+            "detections:\n  - name: Redis\n    category: database\n    any:\n      - dependency: redis\n  - name: Redis\n    category: framework\n    any:\n      - dependency: redis\n",
+        )
+        .unwrap();
+        rules
+            .add_pack(
+                "detections:\n  - name: Redis\n    category: database\n    any:\n      - dependency: fred\n",
+            )
+            .unwrap();
+        assert_eq!(rules.rules.len(), 2);
+
+        let fred = config_file("Cargo.toml", "Cargo.toml", "", &["fred"]);
+        let found = detected(&rules, &[fred]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, Category::Database);
+        // The framework rule of the same name is kept as it was
+        let redis = config_file("Cargo.toml", "Cargo.toml", "", &["redis"]);
+        let found = detected(&rules, &[redis]);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, Category::Framework);
+    }
+
+    #[test]
+    fn dev_dependencies_match_by_their_name() {
+        let rules = pack(
+            "detections:\n  - name: Jest\n    category: testing_framework\n    any:\n      - dependency: jest\n        manifest: package.json\n",
+        )
+        .unwrap();
+        let manifest = config_file(
+            "package.json",
+            "package.json",
+            "{\n  \"devDependencies\": {\n    \"jest\": \"29\"\n  }\n}\n",
+            &["jest (dev)"],
+        );
+        let found = detected(&rules, std::slice::from_ref(&manifest));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].1.evidence[0].label, "dependency jest@1.0");
+        assert_eq!(found[0].1.evidence[0].line, Some(3));
+
+        // The manifest narrows the dependency to one ecosystem
+        let mut cargo = manifest;
+        cargo.file_type = "Cargo.toml".to_string();
+        assert!(detected(&rules, &[cargo]).is_empty());
+    }
+
+    #[test]
+    fn dependency_names_ignore_case_and_take_wildcards() {
+        let rules = pack(
+            "detections:\n  - name: Spring\n    category: framework\n    any:\n      - dependency: 'org.springframework*'\n",
+        )
+        .unwrap();
+        let pom = config_file("pom.xml", "pom.xml", "", &["Org.SpringFramework.Boot"]);
+        assert_eq!(detected(&rules, &[pom]).len(), 1);
+    }
+}
@@ -0,0 +1,159 @@
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::analyzers::filesystem::FileSystemAnalyzer;
+use crate::file_index::FileIndex;
+use crate::types::{DocsSite, RepositoryMetadata};
+
+// Documentation site generators, recognised by their config file name
+const GENERATORS: &[(&str, &str)] = &[
+    ("mkdocs.yml", "MkDocs"),
+    ("mkdocs.yaml", "MkDocs"),
+    ("docusaurus.config.js", "Docusaurus"),
+    ("docusaurus.config.ts", "Docusaurus"),
+    ("docusaurus.config.mjs", "Docusaurus"),
+    ("book.toml", "mdBook"),
+    ("conf.py", "Sphinx"),
+    ("_config.yml", "Jekyll"),
+    ("hugo.toml", "Hugo"),
+    ("antora.yml", "Antora"),
+    ("config.mts", "VitePress"),
+    ("config.ts", "VitePress"),
+    ("config.js", "VitePress"),
+];
+
+// Workflow content that indicates a docs build or Pages deployment
+const DOCS_WORKFLOW_MARKERS: &[&str] = &[
+    "mkdocs",
+    "mdbook",
+    "sphinx",
+    "docusaurus",
+    "vitepress",
+    "jekyll",
+    "hugo",
+    "antora",
+    "actions/deploy-pages",
+    "actions-gh-pages",
+    "gh-pages",
+    "cargo doc",
+];
+
+// Documentation site / GitHub Pages detector
+pub struct DocsSiteDetector;
+
+impl DocsSiteDetector {
+    pub fn detect_docs_site(
+        &self,
+        repo_path: &Path,
+        file_structure: &FileIndex,
+        metadata: &RepositoryMetadata,
+        fs_analyzer: &FileSystemAnalyzer,
+    ) -> Option<DocsSite> {
+        // Shallowest matching config wins
+        let mut candidates: Vec<(&PathBuf, &str)> = file_structure
+            .files()
+            .iter()
+            .map(|file| &file.path)
+            .filter_map(|path| {
+                let name = path.file_name()?.to_str()?;
+                let (_, generator) = GENERATORS.iter().find(|(file, _)| *file == name)?;
+                self.is_generator_config(path, generator)
+                    .then_some((path, *generator))
+            })
+            .collect();
+        candidates.sort_by_key(|(path, _)| path.components().count());
+        let config = candidates.first().copied();
+
+        if config.is_none() && !metadata.has_pages {
+            return None;
+        }
+
+        let config_url = config.and_then(|(path, generator)| {
+            let content = fs_analyzer.read_repo_file(repo_path, path)?;
+            self.site_url_from_config(generator, &content)
+        });
+
+        let build_workflows = fs_analyzer
+            .list_repo_dir(repo_path, Path::new(".github/workflows"))
+            .into_iter()
+            .filter(|path| {
+                fs_analyzer
+                    .read_repo_file(repo_path, path)
+                    .map(|content| {
+                        let content = content.to_lowercase();
+                        DOCS_WORKFLOW_MARKERS
+                            .iter()
+                            .any(|marker| content.contains(marker))
+                    })
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        Some(DocsSite {
+            generator: config.map(|(_, generator)| generator.to_string()),
+            config_path: config.map(|(path, _)| path.clone()),
+            site_url: config_url
+                .or_else(|| metadata.homepage.clone().filter(|url| !url.is_empty()))
+                .or_else(|| metadata.has_pages.then(|| self.pages_url(metadata))),
+            has_pages: metadata.has_pages,
+            build_workflows,
+        })
+    }
+
+    // Generic names only count in the places those generators use them
+    fn is_generator_config(&self, path: &Path, generator: &str) -> bool {
+        let path_str = path.to_string_lossy();
+        match generator {
+            "Sphinx" => path_str.starts_with("doc/") || path_str.starts_with("docs/"),
+            "Jekyll" => path_str == "_config.yml" || path_str == "docs/_config.yml",
+            "VitePress" => path_str.contains(".vitepress/"),
+            _ => path.components().count() <= 3,
+        }
+    }
+
+    fn site_url_from_config(&self, generator: &str, content: &str) -> Option<String> {
+        let capture = |pattern: &str| {
+            Regex::new(pattern)
+                .ok()?
+                .captures(content)
+                .and_then(|c| c.get(1))
+                .map(|m| m.as_str().trim_end_matches('/').to_string())
+        };
+
+        match generator {
+            "MkDocs" => capture(r"(?m)^site_url:\s*['\x22]?([^'\x22\s]+)"),
+            "Docusaurus" => {
+                let url = capture(r#"\burl:\s*['"]([^'"]+)['"]"#)?;
+                let base = capture(r#"\bbaseUrl:\s*['"]([^'"]+)['"]"#).unwrap_or_default();
+                Some(format!("{}{}", url, base))
+            }
+            "mdBook" => {
+                let book: toml::Value = content.parse().ok()?;
+                book.get("output")?
+                    .get("html")?
+                    .get("site-url")?
+                    .as_str()
+                    .map(|s| s.to_string())
+            }
+            "Sphinx" => capture(r#"html_baseurl\s*=\s*['"]([^'"]+)['"]"#),
+            "Jekyll" => {
+                let url = capture(r"(?m)^url:\s*['\x22]?([^'\x22\s]+)")?;
+                let base = capture(r"(?m)^baseurl:\s*['\x22]?([^'\x22\s]*)").unwrap_or_default();
+                Some(format!("{}{}", url, base))
+            }
+            "Hugo" => capture(r#"(?m)^baseURL\s*=\s*['"]([^'"]+)['"]"#),
+            _ => None,
+        }
+    }
+
+    // Default Pages URL: user/org sites live at the root, project sites under the repo name
+    fn pages_url(&self, metadata: &RepositoryMetadata) -> String {
+        let owner = metadata.owner.login.to_lowercase();
+        if metadata.name.to_lowercase() == format!("{}.github.io", owner) {
+            format!("https://{}.github.io/", owner)
+        } else {
+            format!("https://{}.github.io/{}/", owner, metadata.name)
+        }
+    }
+}
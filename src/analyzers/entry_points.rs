@@ -0,0 +1,529 @@
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::analyzers::filesystem::FileSystemAnalyzer;
+use crate::file_index::FileIndex;
+use crate::types::{ConfigFile, EntryPoint, ProjectTask, RunInstructions};
+
+// Task names that stand for a setup step, tried in order
+const INSTALL_TASKS: &[&str] = &["install", "setup", "bootstrap", "deps"];
+const BUILD_TASKS: &[&str] = &["build", "compile"];
+const TEST_TASKS: &[&str] = &["test", "tests"];
+const RUN_TASKS: &[&str] = &["start", "run", "serve", "dev"];
+
+// Entry points listed in the run step when no task starts the project
+const RUN_STEP_ENTRY_POINTS: usize = 3;
+
+// Python scripts deeper than this aren't checked for a __main__ guard
+const MAX_SCRIPT_DEPTH: usize = 2;
+
+// Binary, script and container entry point detector, and the run
+// instructions synthesized from them and the project's tasks
+pub struct EntryPointDetector;
+
+impl EntryPointDetector {
+    pub fn detect(
+        &self,
+        repo_path: &Path,
+        file_structure: &FileIndex,
+        config_files: &[ConfigFile],
+        fs_analyzer: &FileSystemAnalyzer,
+    ) -> RunInstructions {
+        let paths: Vec<&PathBuf> = file_structure
+            .files()
+            .iter()
+            .map(|file| &file.path)
+            .collect();
+
+        let mut entry_points = Vec::new();
+        for config in config_files {
+            match config.file_type.as_str() {
+                "cargo" => self.cargo_binaries(config, &paths, &mut entry_points),
+                "npm" => self.npm_entry_points(config, &mut entry_points),
+                "python" => self.python_scripts(config, &mut entry_points),
+                "docker" => self.docker_command(config, &mut entry_points),
+                _ => {}
+            }
+        }
+        self.python_mains(repo_path, &paths, fs_analyzer, &mut entry_points);
+
+        let tasks = self.tasks(repo_path, &paths, config_files, fs_analyzer);
+        let steps = self.steps(&paths, config_files, &entry_points, &tasks);
+
+        RunInstructions {
+            entry_points,
+            tasks,
+            steps,
+        }
+    }
+
+    // [[bin]] targets, plus the src/main.rs and src/bin binaries Cargo discovers
+    fn cargo_binaries(&self, config: &ConfigFile, paths: &[&PathBuf], found: &mut Vec<EntryPoint>) {
+        let Ok(toml) = config.content.parse::<toml::Value>() else {
+            return;
+        };
+        let Some(package) = toml.get("package") else {
+            return;
+        };
+        let package_name = package.get("name").and_then(|n| n.as_str()).unwrap_or("");
+        let dir = config.path.parent().unwrap_or(Path::new(""));
+        let mut binaries: Vec<(String, PathBuf)> = Vec::new();
+
+        for bin in toml
+            .get("bin")
+            .and_then(|b| b.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let Some(name) = bin.get("name").and_then(|n| n.as_str()) else {
+                continue;
+            };
+            let path = match bin.get("path").and_then(|p| p.as_str()) {
+                Some(path) => dir.join(path),
+                None => dir.join(format!("src/bin/{}.rs", name)),
+            };
+            binaries.push((name.to_string(), path));
+        }
+
+        if package.get("autobins").and_then(|a| a.as_bool()) != Some(false) {
+            let main = dir.join("src/main.rs");
+            if paths.contains(&&main) {
+                binaries.push((package_name.to_string(), main));
+            }
+            let bin_dir = dir.join("src/bin");
+            for path in paths.iter().filter(|path| path.parent() == Some(&bin_dir)) {
+                if path.extension().is_some_and(|ext| ext == "rs")
+                    && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+                {
+                    binaries.push((stem.to_string(), path.to_path_buf()));
+                }
+            }
+            for path in paths.iter().filter(|path| {
+                path.file_name().is_some_and(|name| name == "main.rs")
+                    && path.parent().and_then(|p| p.parent()) == Some(&bin_dir)
+            }) {
+                if let Some(name) = path
+                    .parent()
+                    .and_then(|p| p.file_name())
+                    .and_then(|n| n.to_str())
+                {
+                    binaries.push((name.to_string(), path.to_path_buf()));
+                }
+            }
+        }
+
+        for (name, path) in binaries {
+            if found
+                .iter()
+                .any(|entry| entry.kind == "cargo-bin" && entry.name == name)
+            {
+                continue;
+            }
+            found.push(EntryPoint {
+                command: Some(format!("cargo run --bin {}", name)),
+                name,
+                kind: "cargo-bin".to_string(),
+                path,
+            });
+        }
+    }
+
+    fn npm_entry_points(&self, config: &ConfigFile, found: &mut Vec<EntryPoint>) {
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(&config.content) else {
+            return;
+        };
+        let dir = config.path.parent().unwrap_or(Path::new(""));
+        let package_name = json["name"].as_str().unwrap_or("");
+
+        if let Some(main) = json["main"].as_str() {
+            let path = dir.join(main.trim_start_matches("./"));
+            found.push(EntryPoint {
+                name: package_name.to_string(),
+                kind: "npm-main".to_string(),
+                command: Some(format!("node {}", path.display())),
+                path,
+            });
+        }
+
+        // "bin" is a single path named after the package, or a name to path map
+        let bins: Vec<(&str, &str)> = match &json["bin"] {
+            serde_json::Value::String(path) => vec![(package_name, path.as_str())],
+            serde_json::Value::Object(bins) => bins
+                .iter()
+                .filter_map(|(name, path)| Some((name.as_str(), path.as_str()?)))
+                .collect(),
+            _ => Vec::new(),
+        };
+        for (name, path) in bins {
+            found.push(EntryPoint {
+                name: name.to_string(),
+                kind: "npm-bin".to_string(),
+                path: dir.join(path.trim_start_matches("./")),
+                command: Some(format!("npx {}", name)),
+            });
+        }
+    }
+
+    // Console scripts declared in pyproject.toml
+    fn python_scripts(&self, config: &ConfigFile, found: &mut Vec<EntryPoint>) {
+        let Ok(toml) = config.content.parse::<toml::Value>() else {
+            return;
+        };
+        let tables = [
+            toml.get("project").and_then(|p| p.get("scripts")),
+            toml.get("tool")
+                .and_then(|t| t.get("poetry"))
+                .and_then(|p| p.get("scripts")),
+        ];
+        for (name, target) in tables
+            .into_iter()
+            .flatten()
+            .filter_map(|table| table.as_table())
+            .flatten()
+        {
+            if found
+                .iter()
+                .any(|entry| entry.kind == "python-script" && entry.name == *name)
+            {
+                continue;
+            }
+            found.push(EntryPoint {
+                name: name.clone(),
+                kind: "python-script".to_string(),
+                path: config.path.clone(),
+                command: target.as_str().map(|_| name.clone()),
+            });
+        }
+    }
+
+    // __main__.py packages, and shallow scripts with an `if __name__ == "__main__"` guard
+    fn python_mains(
+        &self,
+        repo_path: &Path,
+        paths: &[&PathBuf],
+        fs_analyzer: &FileSystemAnalyzer,
+        found: &mut Vec<EntryPoint>,
+    ) {
+        let guard = Regex::new(r#"(?m)^if\s+__name__\s*==\s*['"]__main__['"]\s*:"#).unwrap();
+
+        for path in paths {
+            if path.extension().is_none_or(|ext| ext != "py") || self.is_test_path(path) {
+                continue;
+            }
+            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+            if file_name == "__main__.py" {
+                let package = path.parent().unwrap_or(Path::new(""));
+                let module = package
+                    .strip_prefix("src")
+                    .unwrap_or(package)
+                    .to_string_lossy()
+                    .replace('/', ".");
+                let command = if module.is_empty() {
+                    "python .".to_string()
+                } else {
+                    format!("python -m {}", module)
+                };
+                found.push(EntryPoint {
+                    name: if module.is_empty() {
+                        "__main__".to_string()
+                    } else {
+                        module
+                    },
+                    kind: "python-main".to_string(),
+                    path: path.to_path_buf(),
+                    command: Some(command),
+                });
+                continue;
+            }
+
+            if path.components().count() > MAX_SCRIPT_DEPTH || file_name == "setup.py" {
+                continue;
+            }
+            let Some(content) = fs_analyzer.read_repo_file(repo_path, path) else {
+                continue;
+            };
+            if guard.is_match(&content) {
+                found.push(EntryPoint {
+                    name: file_name.trim_end_matches(".py").to_string(),
+                    kind: "python-main".to_string(),
+                    path: path.to_path_buf(),
+                    command: Some(format!("python {}", path.display())),
+                });
+            }
+        }
+    }
+
+    // The ENTRYPOINT and CMD of the final stage
+    fn docker_command(&self, config: &ConfigFile, found: &mut Vec<EntryPoint>) {
+        let mut entrypoint = None;
+        let mut cmd = None;
+
+        for instruction in dockerfile_instructions(&config.content) {
+            let (keyword, argument) = instruction
+                .split_once(char::is_whitespace)
+                .unwrap_or((instruction.as_str(), ""));
+            match keyword.to_uppercase().as_str() {
+                "FROM" => {
+                    entrypoint = None;
+                    cmd = None;
+                }
+                "ENTRYPOINT" => entrypoint = Some(self.docker_arguments(argument)),
+                "CMD" => cmd = Some(self.docker_arguments(argument)),
+                _ => {}
+            }
+        }
+
+        let command: Vec<String> = entrypoint.into_iter().chain(cmd).collect();
+        if command.is_empty() {
+            return;
+        }
+        found.push(EntryPoint {
+            name: config.path.display().to_string(),
+            kind: "docker".to_string(),
+            path: config.path.clone(),
+            command: Some(command.join(" ")),
+        });
+    }
+
+    // Exec form (a JSON array) or shell form
+    fn docker_arguments(&self, argument: &str) -> String {
+        let argument = argument.trim();
+        match serde_json::from_str::<Vec<String>>(argument) {
+            Ok(arguments) => arguments.join(" "),
+            Err(_) => argument.to_string(),
+        }
+    }
+
+    fn is_test_path(&self, path: &Path) -> bool {
+        path.components().any(|component| {
+            matches!(
+                component.as_os_str().to_str(),
+                Some("test" | "tests" | "testing")
+            )
+        }) || path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| name.starts_with("test_") || name.ends_with("_test.py"))
+    }
+
+    // Make and just targets, then npm and composer scripts, of the repository root
+    fn tasks(
+        &self,
+        repo_path: &Path,
+        paths: &[&PathBuf],
+        config_files: &[ConfigFile],
+        fs_analyzer: &FileSystemAnalyzer,
+    ) -> Vec<ProjectTask> {
+        let mut tasks = Vec::new();
+
+        let make_target = Regex::new(r"^([A-Za-z0-9][\w.-]*)\s*:([^=]|$)").unwrap();
+        let just_recipe = Regex::new(r"^@?([A-Za-z_][\w-]*)[^:=]*:([^=]|$)").unwrap();
+
+        for path in paths.iter().filter(|path| path.components().count() == 1) {
+            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let (runner, pattern) = match file_name {
+                "Makefile" | "makefile" | "GNUmakefile" => ("make", &make_target),
+                "justfile" | "Justfile" | ".justfile" => ("just", &just_recipe),
+                _ => continue,
+            };
+            let Some(content) = fs_analyzer.read_repo_file(repo_path, path) else {
+                continue;
+            };
+            for line in content.lines() {
+                if let Some(captures) = pattern.captures(line)
+                    && !tasks
+                        .iter()
+                        .any(|task: &ProjectTask| task.runner == runner && task.name == captures[1])
+                {
+                    tasks.push(ProjectTask {
+                        name: captures[1].to_string(),
+                        runner: runner.to_string(),
+                        command: format!("{} {}", runner, &captures[1]),
+                        source: path.to_path_buf(),
+                    });
+                }
+            }
+        }
+
+        let npm_runner = self.npm_runner(paths);
+        for config in config_files
+            .iter()
+            .filter(|config| config.path.components().count() == 1)
+        {
+            let runner = match config.file_type.as_str() {
+                "npm" => npm_runner,
+                "composer" => "composer",
+                _ => continue,
+            };
+            let mut scripts: Vec<&String> = config
+                .scripts
+                .iter()
+                .flatten()
+                .map(|(name, _)| name)
+                .collect();
+            scripts.sort();
+            for name in scripts {
+                let command = match (runner, name.as_str()) {
+                    ("composer", _) => format!("composer run-script {}", name),
+                    (_, "start" | "test") => format!("{} {}", runner, name),
+                    _ => format!("{} run {}", runner, name),
+                };
+                tasks.push(ProjectTask {
+                    name: name.clone(),
+                    runner: runner.to_string(),
+                    command,
+                    source: config.path.clone(),
+                });
+            }
+        }
+
+        tasks
+    }
+
+    // The package manager whose lockfile is committed
+    fn npm_runner(&self, paths: &[&PathBuf]) -> &'static str {
+        let has_root_file = |name: &str| paths.iter().any(|path| path.as_os_str() == name);
+        if has_root_file("pnpm-lock.yaml") {
+            "pnpm"
+        } else if has_root_file("yarn.lock") {
+            "yarn"
+        } else if has_root_file("bun.lockb") || has_root_file("bun.lock") {
+            "bun"
+        } else {
+            "npm"
+        }
+    }
+
+    // Install, build, test and run steps. A task for the step wins over the
+    // ecosystem's default command
+    fn steps(
+        &self,
+        paths: &[&PathBuf],
+        config_files: &[ConfigFile],
+        entry_points: &[EntryPoint],
+        tasks: &[ProjectTask],
+    ) -> Vec<String> {
+        let root_types: Vec<&str> = config_files
+            .iter()
+            .filter(|config| config.path.components().count() == 1)
+            .map(|config| config.file_type.as_str())
+            .collect();
+        let has_type = |file_type: &str| root_types.contains(&file_type);
+        let task_for = |names: &[&str]| {
+            names.iter().find_map(|name| {
+                tasks
+                    .iter()
+                    .find(|task| task.name == *name)
+                    .map(|task| task.command.clone())
+            })
+        };
+        let gradle = if paths.iter().any(|path| path.as_os_str() == "gradlew") {
+            "./gradlew"
+        } else {
+            "gradle"
+        };
+        let mut steps = Vec::new();
+
+        let install = task_for(INSTALL_TASKS).or_else(|| {
+            let commands: Vec<String> = [
+                ("npm", format!("{} install", self.npm_runner(paths))),
+                ("pip", "pip install -r requirements.txt".to_string()),
+                ("python", "pip install -e .".to_string()),
+                ("bundler", "bundle install".to_string()),
+                ("composer", "composer install".to_string()),
+                ("dart", "dart pub get".to_string()),
+                ("mix", "mix deps.get".to_string()),
+                ("go", "go mod download".to_string()),
+            ]
+            .into_iter()
+            .filter(|(file_type, _)| has_type(file_type))
+            .map(|(_, command)| command)
+            .collect();
+            (!commands.is_empty()).then(|| commands.join(" && "))
+        });
+        if let Some(command) = install {
+            steps.push(format!("Install dependencies: {}", command));
+        }
+
+        let build = task_for(BUILD_TASKS).or_else(|| {
+            [
+                ("cargo", "cargo build".to_string()),
+                ("go", "go build ./...".to_string()),
+                ("maven", "mvn package".to_string()),
+                ("gradle", format!("{} build", gradle)),
+                ("sbt", "sbt compile".to_string()),
+                ("mix", "mix compile".to_string()),
+                ("cmake", "cmake -B build && cmake --build build".to_string()),
+            ]
+            .into_iter()
+            .find(|(file_type, _)| has_type(file_type))
+            .map(|(_, command)| command)
+        });
+        if let Some(command) = build {
+            steps.push(format!("Build: {}", command));
+        }
+
+        let test = task_for(TEST_TASKS).or_else(|| {
+            [
+                ("cargo", "cargo test".to_string()),
+                ("go", "go test ./...".to_string()),
+                ("maven", "mvn test".to_string()),
+                ("gradle", format!("{} test", gradle)),
+                ("sbt", "sbt test".to_string()),
+                ("mix", "mix test".to_string()),
+            ]
+            .into_iter()
+            .find(|(file_type, _)| has_type(file_type))
+            .map(|(_, command)| command)
+        });
+        if let Some(command) = test {
+            steps.push(format!("Run the tests: {}", command));
+        }
+
+        match task_for(RUN_TASKS) {
+            Some(command) => steps.push(format!("Run: {}", command)),
+            None => {
+                let commands: Vec<&str> = entry_points
+                    .iter()
+                    .filter_map(|entry| entry.command.as_deref())
+                    .take(RUN_STEP_ENTRY_POINTS)
+                    .collect();
+                if !commands.is_empty() {
+                    steps.push(format!("Run: {}", commands.join(" or ")));
+                }
+            }
+        }
+
+        steps
+    }
+}
+
+// Instructions with their continuation lines joined, comments dropped
+pub fn dockerfile_instructions(content: &str) -> Vec<String> {
+    let mut instructions = Vec::new();
+    let mut current = String::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('#') || (line.is_empty() && current.is_empty()) {
+            continue;
+        }
+        match line.strip_suffix('\\') {
+            Some(continued) => {
+                current.push_str(continued.trim_end());
+                current.push(' ');
+            }
+            None => {
+                current.push_str(line);
+                instructions.push(std::mem::take(&mut current));
+            }
+        }
+    }
+    if !current.is_empty() {
+        instructions.push(current);
+    }
+
+    instructions
+}
@@ -0,0 +1,329 @@
+use std::path::Path;
+
+use crate::analyzers::detections::add_detection;
+use crate::file_index::FileIndex;
+use crate::types::{ConfigFile, Detection, EvidenceRef};
+use crate::utils::first_line;
+
+// Crates that only build for the wasm32 target
+const WASM_CRATES: &[&str] = &[
+    "wasm-bindgen",
+    "wasm-bindgen-futures",
+    "web-sys",
+    "js-sys",
+    "gloo",
+    "wasm-bindgen-test",
+];
+
+// Hardware abstraction layers, runtimes and tooling of embedded Rust, as
+// crate name prefixes
+const EMBEDDED_CRATES: &[&str] = &[
+    "embedded-hal",
+    "cortex-m",
+    "riscv-rt",
+    "esp-hal",
+    "esp-idf-hal",
+    "esp-idf-svc",
+    "embassy-",
+    "rp2040-hal",
+    "rp-pico",
+    "stm32",
+    "nrf52",
+    "nrf-hal",
+    "avr-device",
+    "arduino-hal",
+    "msp430",
+    "defmt",
+    "panic-probe",
+];
+
+// Target triples of bare-metal microcontrollers
+const EMBEDDED_TARGETS: &[&str] = &[
+    "thumbv", "riscv32i", "riscv32e", "avr-", "xtensa-", "msp430-",
+];
+
+// npm dependencies that only run in a browser
+const BROWSER_PACKAGES: &[&str] = &[
+    "react-dom",
+    "vue",
+    "svelte",
+    "@angular/core",
+    "preact",
+    "solid-js",
+    "lit",
+];
+
+// Where a Rust or JavaScript project can run: wasm32, no_std, bare-metal
+// embedded targets, browsers, Node.js and Deno, filling in ProjectInfo
+pub struct ExecutionEnvironmentDetector;
+
+impl ExecutionEnvironmentDetector {
+    pub fn detect(
+        &self,
+        config_files: &[ConfigFile],
+        file_structure: &FileIndex,
+    ) -> Vec<Detection> {
+        let mut environments = Vec::new();
+        for config in config_files {
+            match config.file_type.as_str() {
+                "cargo" => self.cargo_manifest(config, &mut environments),
+                "npm" if config.path.ends_with("package.json") => {
+                    self.package_json(config, &mut environments)
+                }
+                "typescript" => {
+                    // lib: ["dom"] type-checks against browser APIs
+                    let content = config.content.to_lowercase();
+                    if let Some(line) = first_line(&content, |line| {
+                        line.contains("\"lib\"") && line.contains("\"dom")
+                    }) {
+                        add(
+                            &mut environments,
+                            "browser",
+                            0.7,
+                            "tsconfig lib dom",
+                            &config.path,
+                            Some(line),
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for file in file_structure.files() {
+            let name = file.name.to_lowercase();
+            let preview = file.content_preview.as_deref().unwrap_or("");
+            match name.as_str() {
+                // The crate root declares whether std is linked
+                "lib.rs" | "main.rs" => {
+                    if let Some(line) = first_line(preview, |line| {
+                        let line = line.trim();
+                        line.starts_with("#![no_std]")
+                    }) {
+                        add(
+                            &mut environments,
+                            "no_std",
+                            0.95,
+                            "#![no_std]",
+                            &file.path,
+                            Some(line),
+                        );
+                    } else if let Some(line) = first_line(preview, |line| {
+                        let line = line.trim();
+                        line.starts_with("#![cfg_attr(") && line.contains("no_std")
+                    }) {
+                        add(
+                            &mut environments,
+                            "no_std",
+                            0.85,
+                            "no_std without the std feature",
+                            &file.path,
+                            Some(line),
+                        );
+                    }
+                }
+                // Targets installed with the toolchain
+                "rust-toolchain.toml" | "rust-toolchain" => {
+                    let lowercase = preview.to_lowercase();
+                    if let Some(line) = first_line(&lowercase, |line| line.contains("wasm32")) {
+                        add(
+                            &mut environments,
+                            "wasm32",
+                            0.9,
+                            "wasm32 target",
+                            &file.path,
+                            Some(line),
+                        );
+                    }
+                    if let Some(line) = first_line(&lowercase, |line| {
+                        EMBEDDED_TARGETS.iter().any(|target| line.contains(target))
+                    }) {
+                        add(
+                            &mut environments,
+                            "embedded",
+                            0.9,
+                            "bare-metal target",
+                            &file.path,
+                            Some(line),
+                        );
+                    }
+                }
+                // The linker script cortex-m-rt and friends expect
+                "memory.x" => add(
+                    &mut environments,
+                    "embedded",
+                    0.7,
+                    "memory.x",
+                    &file.path,
+                    None,
+                ),
+                "deno.json" | "deno.jsonc" => add(
+                    &mut environments,
+                    "deno",
+                    0.9,
+                    "deno config",
+                    &file.path,
+                    None,
+                ),
+                _ => {}
+            }
+        }
+
+        environments.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        environments
+    }
+
+    fn cargo_manifest(&self, config: &ConfigFile, environments: &mut Vec<Detection>) {
+        let content = &config.content;
+        for (name, line) in cargo_dependencies(content) {
+            if WASM_CRATES.contains(&name.as_str()) {
+                let label = format!("{} dependency", name);
+                add(
+                    environments,
+                    "wasm32",
+                    0.9,
+                    &label,
+                    &config.path,
+                    Some(line),
+                );
+            }
+            if EMBEDDED_CRATES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+            {
+                let label = format!("{} dependency", name);
+                add(
+                    environments,
+                    "embedded",
+                    0.9,
+                    &label,
+                    &config.path,
+                    Some(line),
+                );
+            }
+        }
+        if let Some(line) = first_line(content, |line| {
+            line.trim_start().starts_with("[target.") && line.contains("wasm32")
+        }) {
+            add(
+                environments,
+                "wasm32",
+                0.8,
+                "wasm32 target section",
+                &config.path,
+                Some(line),
+            );
+        }
+    }
+
+    fn package_json(&self, config: &ConfigFile, environments: &mut Vec<Detection>) {
+        let Ok(manifest) = serde_json::from_str::<serde_json::Value>(&config.content) else {
+            return;
+        };
+        let content = &config.content;
+        let key_line = |key: &str| {
+            let quoted = format!("\"{}\"", key);
+            first_line(content, |line| line.contains(&quoted))
+        };
+        let mut signal = |environment: &str, confidence: f64, label: &str, key: &str| {
+            add(
+                environments,
+                environment,
+                confidence,
+                label,
+                &config.path,
+                key_line(key),
+            );
+        };
+
+        // Fields bundlers and CDNs read when building for the browser
+        for field in ["browser", "unpkg", "jsdelivr"] {
+            if !manifest[field].is_null() {
+                signal("browser", 0.9, &format!("{} field", field), field);
+            }
+        }
+        if !manifest["bin"].is_null() {
+            signal("node", 0.85, "bin field", "bin");
+        }
+        if manifest["engines"]["node"].is_string() {
+            signal("node", 0.8, "engines.node", "engines");
+        }
+        // Conditional exports name the environments explicitly
+        let exports = manifest["exports"].to_string();
+        for (condition, environment) in [
+            ("\"browser\"", "browser"),
+            ("\"node\"", "node"),
+            ("\"deno\"", "deno"),
+        ] {
+            if exports.contains(condition) {
+                let label = format!("{} export condition", environment);
+                signal(environment, 0.85, &label, "exports");
+            }
+        }
+        for section in ["dependencies", "devDependencies", "peerDependencies"] {
+            let Some(dependencies) = manifest[section].as_object() else {
+                continue;
+            };
+            for name in dependencies.keys() {
+                if BROWSER_PACKAGES.contains(&name.as_str()) {
+                    signal("browser", 0.8, &format!("{} dependency", name), name);
+                } else if name == "@types/node" {
+                    signal("node", 0.7, "@types/node dependency", name);
+                }
+            }
+        }
+    }
+}
+
+// Dependency names of a Cargo.toml with their lines, from every dependency
+// table including target-specific ones and [dependencies.name] tables
+fn cargo_dependencies(content: &str) -> Vec<(String, u32)> {
+    let mut dependencies = Vec::new();
+    let mut in_dependencies = false;
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.trim_end_matches(']');
+            in_dependencies = header.ends_with("dependencies");
+            // [dependencies.name], [target.'cfg(..)'.dependencies.name]
+            if let Some((_, name)) = header.rsplit_once("dependencies.") {
+                dependencies.push((name.trim_matches('"').to_string(), index as u32 + 1));
+            }
+            continue;
+        }
+        if !in_dependencies || line.starts_with('#') {
+            continue;
+        }
+        let Some(name) = line.split(['=', '.']).next() else {
+            continue;
+        };
+        let name = name.trim().trim_matches('"');
+        if !name.is_empty() {
+            dependencies.push((name.to_string(), index as u32 + 1));
+        }
+    }
+    dependencies
+}
+
+fn add(
+    environments: &mut Vec<Detection>,
+    name: &str,
+    confidence: f64,
+    label: &str,
+    path: &Path,
+    line: Option<u32>,
+) {
+    add_detection(
+        environments,
+        Detection {
+            name: name.to_string(),
+            confidence,
+            evidence: vec![EvidenceRef {
+                label: label.to_string(),
+                path: path.to_path_buf(),
+                line,
+                url: None,
+            }],
+        },
+    );
+}
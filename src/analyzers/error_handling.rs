@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::analyzers::scan::{FileClassifier, ScannedFile, strip_comments};
+use crate::types::{ErrorHandling, ErrorHandlingFinding, LanguageErrorHandling};
+use crate::utils::is_test_file;
+
+// Findings kept with their location, the counts cover all of them
+const MAX_FINDINGS: usize = 50;
+
+// Patterns of one language. Handlers are catch/except/rescue blocks (or Go's
+// err != nil checks), the other patterns flag the questionable ones
+struct LanguageRules {
+    language: &'static str,
+    extensions: &'static [&'static str],
+    hash_comments: bool, // # comments instead of // and /* */
+    handler: Regex,
+    broad: Option<Regex>,
+    empty: Option<Regex>,
+    ignored: Option<Regex>,
+}
+
+// Counts broad, empty and ignored error handling in non-test code of the
+// languages with exceptions or error values, Rust has its panic density
+pub struct ErrorHandlingClassifier {
+    rules: Vec<LanguageRules>,
+    languages: HashMap<&'static str, LanguageErrorHandling>,
+    findings: Vec<ErrorHandlingFinding>,
+}
+
+impl Default for ErrorHandlingClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ErrorHandlingClassifier {
+    pub fn new() -> Self {
+        let regex = |pattern: &str| Regex::new(pattern).unwrap();
+        let braced_empty = r"\bcatch\s*\([^)]*\)\s*\{\s*\}";
+
+        let rules = vec![
+            LanguageRules {
+                language: "Python",
+                extensions: &["py"],
+                hash_comments: true,
+                handler: regex(r"(?m)^\s*except\b"),
+                broad: Some(regex(
+                    r"(?m)^\s*except\s*(?::|\(?\s*(?:Exception|BaseException)\s*\)?\s*(?:as\s+\w+\s*)?:)",
+                )),
+                empty: Some(regex(
+                    r"(?m)^\s*except\b[^:\n]*:[ \t]*(?:\n\s*)?(?:pass|\.\.\.)[ \t]*$",
+                )),
+                ignored: None,
+            },
+            LanguageRules {
+                language: "JavaScript",
+                extensions: &["js", "jsx", "mjs", "cjs"],
+                hash_comments: false,
+                handler: regex(r"\bcatch\s*(?:\([^)]*\)\s*)?\{|\.catch\s*\("),
+                broad: None, // A catch clause always catches everything
+                empty: Some(regex(
+                    r"\bcatch\s*(?:\([^)]*\)\s*)?\{\s*\}|\.catch\s*\(\s*(?:\(\s*\w*\s*\)|\w+)\s*=>\s*(?:\{\s*\}|null|undefined)\s*\)",
+                )),
+                ignored: None,
+            },
+            LanguageRules {
+                language: "TypeScript",
+                extensions: &["ts", "tsx", "mts", "cts"],
+                hash_comments: false,
+                handler: regex(r"\bcatch\s*(?:\([^)]*\)\s*)?\{|\.catch\s*\("),
+                broad: None,
+                empty: Some(regex(
+                    r"\bcatch\s*(?:\([^)]*\)\s*)?\{\s*\}|\.catch\s*\(\s*(?:\(\s*\w*\s*\)|\w+)\s*=>\s*(?:\{\s*\}|null|undefined)\s*\)",
+                )),
+                ignored: None,
+            },
+            LanguageRules {
+                language: "Java",
+                extensions: &["java"],
+                hash_comments: false,
+                handler: regex(r"\bcatch\s*\("),
+                broad: Some(regex(
+                    r"\bcatch\s*\(\s*(?:final\s+)?(?:java\.lang\.)?(?:Exception|Throwable|RuntimeException)\s+\w+\s*\)",
+                )),
+                empty: Some(regex(braced_empty)),
+                ignored: None,
+            },
+            LanguageRules {
+                language: "Kotlin",
+                extensions: &["kt", "kts"],
+                hash_comments: false,
+                handler: regex(r"\bcatch\s*\("),
+                broad: Some(regex(
+                    r"\bcatch\s*\(\s*\w+\s*:\s*(?:kotlin\.|java\.lang\.)?(?:Exception|Throwable)\s*\)",
+                )),
+                empty: Some(regex(braced_empty)),
+                ignored: None,
+            },
+            LanguageRules {
+                language: "C#",
+                extensions: &["cs"],
+                hash_comments: false,
+                handler: regex(r"\bcatch\b"),
+                // A bare catch, or catching Exception without a when filter
+                broad: Some(regex(
+                    r"\bcatch\s*(?:\(\s*(?:System\.)?Exception(?:\s+\w+)?\s*\)\s*)?\{",
+                )),
+                empty: Some(regex(r"\bcatch\s*(?:\([^)]*\)\s*)?\{\s*\}")),
+                ignored: None,
+            },
+            LanguageRules {
+                language: "PHP",
+                extensions: &["php"],
+                hash_comments: false,
+                handler: regex(r"\bcatch\s*\("),
+                broad: Some(regex(
+                    r"\bcatch\s*\(\s*\\?(?:Exception|Throwable)\b(?:\s*\$\w+)?\s*\)",
+                )),
+                empty: Some(regex(braced_empty)),
+                ignored: None,
+            },
+            LanguageRules {
+                language: "Go",
+                extensions: &["go"],
+                hash_comments: false,
+                handler: regex(r"\bif\s+err\s*!=\s*nil\s*\{"),
+                broad: None,
+                empty: Some(regex(r"\bif\s+err\s*!=\s*nil\s*\{\s*\}")),
+                // `_ = err`, `_ = f.Close()` and `v, _ := strconv.Atoi(s)`
+                ignored: Some(regex(
+                    r"(?m)\b_\s*=\s*err\b|^\s*_\s*=\s*[\w.]*\w\(|,\s*_\s*:?=\s*[\w.]*\w\(",
+                )),
+            },
+            LanguageRules {
+                language: "Ruby",
+                extensions: &["rb"],
+                hash_comments: true,
+                handler: regex(r"\brescue\b"),
+                broad: Some(regex(
+                    r"(?m)^\s*rescue\s*(?:=>\s*\w+\s*)?$|\brescue\s+(?:::)?Exception\b",
+                )),
+                empty: Some(regex(r"(?m)^\s*rescue\b[^\n]*\n\s*end\b|\brescue\s+nil\b")),
+                ignored: None,
+            },
+            LanguageRules {
+                language: "Swift",
+                extensions: &["swift"],
+                hash_comments: false,
+                handler: regex(r"\bcatch\b"),
+                broad: None,
+                empty: Some(regex(r"\bcatch\b[^{\n]*\{\s*\}")),
+                ignored: Some(regex(r"\btry\?")),
+            },
+        ];
+
+        Self {
+            rules,
+            languages: HashMap::new(),
+            findings: Vec::new(),
+        }
+    }
+
+    // None when no supported language was found
+    pub fn finish(self) -> Option<ErrorHandling> {
+        if self.languages.is_empty() {
+            return None;
+        }
+
+        let mut languages: Vec<LanguageErrorHandling> = self
+            .languages
+            .into_values()
+            .map(|mut stats| {
+                let issues = stats.broad_catches + stats.empty_handlers + stats.ignored_errors;
+                if stats.loc > 0 {
+                    stats.issues_per_kloc = issues as f64 * 1000.0 / stats.loc as f64;
+                }
+                stats
+            })
+            .collect();
+        languages.sort_by(|a, b| b.loc.cmp(&a.loc).then(a.language.cmp(&b.language)));
+
+        Some(ErrorHandling {
+            languages,
+            findings: self.findings,
+        })
+    }
+
+    fn add_source(&mut self, rules: usize, file: &ScannedFile<'_>, content: &str) {
+        let rules = &self.rules[rules];
+        let code = if rules.hash_comments {
+            strip_hash_comments(content)
+        } else {
+            strip_comments(content)
+        };
+
+        let stats = self
+            .languages
+            .entry(rules.language)
+            .or_insert_with(|| LanguageErrorHandling {
+                language: rules.language.to_string(),
+                loc: 0,
+                handlers: 0,
+                broad_catches: 0,
+                empty_handlers: 0,
+                ignored_errors: 0,
+                issues_per_kloc: 0.0,
+            });
+        stats.loc += code.lines().filter(|line| !line.trim().is_empty()).count() as u32;
+        stats.handlers += rules.handler.find_iter(&code).count() as u32;
+
+        for (pattern, kind, count) in [
+            (&rules.broad, "broad catch", &mut stats.broad_catches),
+            (&rules.empty, "empty handler", &mut stats.empty_handlers),
+            (&rules.ignored, "ignored error", &mut stats.ignored_errors),
+        ] {
+            let Some(pattern) = pattern else {
+                continue;
+            };
+            for found in pattern.find_iter(&code) {
+                *count += 1;
+                if self.findings.len() < MAX_FINDINGS {
+                    // The match may start on the whitespace before the keyword
+                    let start = found.start() + (found.len() - found.as_str().trim_start().len());
+                    self.findings.push(ErrorHandlingFinding {
+                        path: file.relative.to_path_buf(),
+                        line: code[..start].matches('\n').count() as u32 + 1,
+                        language: rules.language.to_string(),
+                        kind: kind.to_string(),
+                        url: None,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl FileClassifier for ErrorHandlingClassifier {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        if !file.in_structure || is_test_file(file.relative) {
+            return;
+        }
+        let Some(extension) = file.relative.extension().and_then(|e| e.to_str()) else {
+            return;
+        };
+        let Some(rules) = self
+            .rules
+            .iter()
+            .position(|rules| rules.extensions.contains(&extension))
+        else {
+            return;
+        };
+        if let Some(content) = file.content() {
+            self.add_source(rules, file, content);
+        }
+    }
+}
+
+// Blanks lines that are # comments, keeping line numbers. Trailing comments
+// are left, handler keywords rarely appear in them
+fn strip_hash_comments(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| {
+            if line.trim_start().starts_with('#') {
+                ""
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,221 @@
+use std::collections::{BTreeSet, HashSet};
+
+use regex::Regex;
+
+use crate::file_index::FileIndex;
+use crate::types::{CargoFeature, ConfigFile, FeatureFlags};
+
+// Feature flag and experimentation SDKs, as (manifest content marker, system)
+const FLAG_SDKS: &[(&str, &str)] = &[
+    ("launchdarkly", "LaunchDarkly"),
+    ("unleash-client", "Unleash"),
+    ("unleash-proxy-client", "Unleash"),
+    ("@unleash/", "Unleash"),
+    ("unleash_client", "Unleash"),
+    ("flagsmith", "Flagsmith"),
+    ("growthbook", "GrowthBook"),
+    ("@splitsoftware/", "Split"),
+    ("splitio", "Split"),
+    ("configcat", "ConfigCat"),
+    ("openfeature", "OpenFeature"),
+    ("statsig", "Statsig"),
+    ("flipper", "Flipper"),
+    ("togglz", "Togglz"),
+    ("ff4j", "FF4J"),
+];
+
+// Config file types that declare dependencies
+const DEPENDENCY_MANIFESTS: &[&str] = &[
+    "npm", "cargo", "pip", "pipenv", "python", "maven", "gradle", "composer", "bundler", "go",
+    "dart", "mix", "sbt",
+];
+
+// Languages whose sources go through the C preprocessor
+const PREPROCESSED_LANGUAGES: &[&str] = &[
+    "C",
+    "C++",
+    "C/C++ Header",
+    "C++ Header",
+    "Objective-C",
+    "Objective-C++",
+];
+
+// Preprocessor flags kept in the result
+const MAX_PREPROCESSOR_FLAGS: usize = 50;
+
+// Feature flag system detector, filling in ProjectInfo
+pub struct FeatureFlagDetector;
+
+impl FeatureFlagDetector {
+    pub fn detect(&self, config_files: &[ConfigFile], file_structure: &FileIndex) -> FeatureFlags {
+        let mut systems: Vec<String> = Vec::new();
+
+        let mut cargo_features = Vec::new();
+        for config in config_files.iter().filter(|c| c.file_type == "cargo") {
+            cargo_features.extend(self.cargo_features(config));
+        }
+        if !cargo_features.is_empty() {
+            systems.push("Cargo features".to_string());
+        }
+
+        for config in config_files
+            .iter()
+            .filter(|c| DEPENDENCY_MANIFESTS.contains(&c.file_type.as_str()))
+        {
+            let content = config.content.to_lowercase();
+            for (marker, system) in FLAG_SDKS {
+                if content.contains(marker) && !systems.iter().any(|s| s == system) {
+                    systems.push(system.to_string());
+                }
+            }
+        }
+
+        let preprocessor_flags = self.preprocessor_flags(file_structure);
+        if !preprocessor_flags.is_empty() {
+            systems.push("Preprocessor flags".to_string());
+        }
+
+        FeatureFlags {
+            systems,
+            cargo_features,
+            preprocessor_flags,
+        }
+    }
+
+    // Features of a Cargo.toml with what each turns on. Optional dependencies
+    // never named with dep: get the implicit feature Cargo creates for them
+    fn cargo_features(&self, config: &ConfigFile) -> Vec<CargoFeature> {
+        let Ok(toml) = config.content.parse::<toml::Value>() else {
+            return Vec::new();
+        };
+        let table = |key: &str| toml.get(key).and_then(|t| t.as_table());
+
+        let mut optional: Vec<&str> = Vec::new();
+        for section in ["dependencies", "build-dependencies"] {
+            for (name, dep) in table(section).into_iter().flatten() {
+                if dep.get("optional").and_then(|o| o.as_bool()) == Some(true) {
+                    optional.push(name);
+                }
+            }
+        }
+
+        let declared: Vec<(&str, Vec<&str>)> = table("features")
+            .into_iter()
+            .flatten()
+            .map(|(name, entries)| {
+                let entries = entries
+                    .as_array()
+                    .map(|a| a.iter().filter_map(|e| e.as_str()).collect())
+                    .unwrap_or_default();
+                (name.as_str(), entries)
+            })
+            .collect();
+        let explicit: HashSet<&str> = declared
+            .iter()
+            .flat_map(|(_, entries)| entries.iter())
+            .filter_map(|entry| entry.strip_prefix("dep:"))
+            .collect();
+        let is_feature = |name: &str| declared.iter().any(|(feature, _)| *feature == name);
+
+        let mut features: Vec<CargoFeature> = declared
+            .iter()
+            .filter(|(name, _)| *name != "default")
+            .map(|(name, entries)| {
+                let mut feature = CargoFeature {
+                    manifest: config.path.clone(),
+                    name: name.to_string(),
+                    default: false,
+                    implicit: false,
+                    features: Vec::new(),
+                    dependencies: Vec::new(),
+                    dependency_features: Vec::new(),
+                };
+                for entry in entries {
+                    if let Some(dep) = entry.strip_prefix("dep:") {
+                        feature.dependencies.push(dep.to_string());
+                    } else if let Some((dep, dep_feature)) = entry.split_once('/') {
+                        // dep?/feature only applies if dep is enabled otherwise
+                        let weak = dep.ends_with('?');
+                        let dep = dep.trim_end_matches('?');
+                        feature
+                            .dependency_features
+                            .push(format!("{}/{}", dep, dep_feature));
+                        if !weak && optional.contains(&dep) {
+                            feature.dependencies.push(dep.to_string());
+                        }
+                    } else if is_feature(entry) {
+                        feature.features.push(entry.to_string());
+                    } else if optional.contains(entry) {
+                        feature.dependencies.push(entry.to_string());
+                    }
+                }
+                feature.dependencies.sort();
+                feature.dependencies.dedup();
+                feature
+            })
+            .collect();
+
+        for dep in optional {
+            if !explicit.contains(dep) && !is_feature(dep) {
+                features.push(CargoFeature {
+                    manifest: config.path.clone(),
+                    name: dep.to_string(),
+                    default: false,
+                    implicit: true,
+                    features: Vec::new(),
+                    dependencies: vec![dep.to_string()],
+                    dependency_features: Vec::new(),
+                });
+            }
+        }
+
+        // Whatever default turns on, directly or through other features
+        let mut pending: Vec<&str> = declared
+            .iter()
+            .find(|(name, _)| *name == "default")
+            .map(|(_, entries)| entries.clone())
+            .unwrap_or_default();
+        let mut enabled: HashSet<String> = HashSet::new();
+        while let Some(name) = pending.pop() {
+            if !enabled.insert(name.to_string()) {
+                continue;
+            }
+            if let Some((_, entries)) = declared.iter().find(|(feature, _)| *feature == name) {
+                pending.extend(entries.iter().filter(|entry| is_feature(entry)));
+            }
+        }
+        for feature in &mut features {
+            feature.default = enabled.contains(&feature.name);
+        }
+
+        features
+    }
+
+    // Macros guarding feature code, such as ENABLE_X or FEATURE_X. Only the
+    // previews of C-family sources are searched
+    fn preprocessor_flags(&self, file_structure: &FileIndex) -> Vec<String> {
+        let guard = Regex::new(r"^\s*#\s*(?:if|ifdef|ifndef|elif)\b").unwrap();
+        let flag = Regex::new(r"\b((?:ENABLE|FEATURE|WITH|USE|EXPERIMENTAL)_[A-Z0-9_]+)").unwrap();
+
+        let mut flags = BTreeSet::new();
+        for file in file_structure.files() {
+            if !file
+                .language
+                .as_deref()
+                .is_some_and(|language| PREPROCESSED_LANGUAGES.contains(&language))
+            {
+                continue;
+            }
+            let Some(preview) = &file.content_preview else {
+                continue;
+            };
+            for line in preview.lines().filter(|line| guard.is_match(line)) {
+                for captures in flag.captures_iter(line) {
+                    flags.insert(captures[1].to_string());
+                }
+            }
+        }
+
+        flags.into_iter().take(MAX_PREPROCESSOR_FLAGS).collect()
+    }
+}
@@ -1,7 +1,6 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs,
-    io::Read,
     path::{Path, PathBuf},
 };
 
@@ -9,19 +8,118 @@ use anyhow::Result;
 use ignore::WalkBuilder;
 use log::{info, warn};
 use regex::Regex;
-use walkdir::WalkDir;
 
-use crate::types::{ConfigFile, DirectoryInfo, DocumentationFile, FileInfo};
+use crate::{
+    analyzers::{
+        complexity::ComplexityEstimator,
+        embedded,
+        scan::{FileClassifier, METADATA_DEPTH, ScannedFile},
+    },
+    cancel,
+    file_index::FileIndex,
+    progress::{self, warning},
+    sandbox::{AnalysisLimits, PathGuard},
+    types::{
+        ConfigFile, DocumentationFile, EmbeddedCode, FileInfo, FunctionMetrics, ProgressEvent,
+    },
+    utils::{join_relative, long_path, to_slash_path},
+};
+
+// Parsed (dependencies, scripts) extracted from a config file
+type ParsedConfig = (
+    Option<HashMap<String, String>>,
+    Option<HashMap<String, String>>,
+);
+
+// (content preview, encoding, (lines of code, blank lines, comment lines),
+//  (cyclomatic complexity, functions))
+type TextFileInfo = (
+    Option<String>,
+    Option<String>,
+    (Option<u32>, Option<u32>, Option<u32>),
+    (Option<u32>, Vec<FunctionMetrics>),
+);
+
+// File name prefixes of config files (case-insensitive), as (pattern, file type)
+const CONFIG_PATTERNS: &[(&str, &str)] = &[
+    ("package.json", "npm"),
+    ("Cargo.toml", "cargo"),
+    ("requirements.txt", "pip"),
+    ("Pipfile", "pipenv"),
+    ("pyproject.toml", "python"),
+    ("pom.xml", "maven"),
+    ("build.gradle", "gradle"),
+    ("composer.json", "composer"),
+    ("Gemfile", "bundler"),
+    ("go.mod", "go"),
+    ("pubspec.yaml", "dart"),
+    ("project.clj", "leiningen"),
+    ("mix.exs", "mix"),
+    ("rebar.config", "rebar"),
+    ("stack.yaml", "stack"),
+    ("cabal.project", "cabal"),
+    ("dune-project", "dune"),
+    (".travis.yml", "travis"),
+    ("Dockerfile", "docker"),
+    ("docker-compose.yml", "docker-compose"),
+    ("kubernetes.yaml", "kubernetes"),
+    ("terraform.tf", "terraform"),
+    ("ansible.yml", "ansible"),
+    (".eslintrc", "eslint"),
+    (".prettierrc", "prettier"),
+    ("tsconfig.json", "typescript"),
+    ("webpack.config.js", "webpack"),
+    ("vite.config.js", "vite"),
+    ("rollup.config.js", "rollup"),
+    ("jest.config.js", "jest"),
+    ("cypress.json", "cypress"),
+    ("build.sbt", "sbt"),
+    ("CMakeLists.txt", "cmake"),
+    ("conanfile", "conan"),
+    ("vcpkg.json", "vcpkg"),
+    (".env", "environment"),
+    (".gitignore", "git"),
+    (".gitattributes", "git"),
+    ("CODEOWNERS", "codeowners"),
+];
+
+// File name prefixes of documentation files (case-insensitive), as (pattern, doc type)
+const DOC_PATTERNS: &[(&str, &str)] = &[
+    ("README", "readme"),
+    ("CHANGELOG", "changelog"),
+    ("CONTRIBUTING", "contributing"),
+    ("LICENSE", "license"),
+    ("CODE_OF_CONDUCT", "code_of_conduct"),
+    ("SECURITY", "security"),
+    ("INSTALL", "install"),
+    ("USAGE", "usage"),
+    ("API", "api"),
+    ("FUNDING", "funding"),
+    ("GOVERNANCE", "governance"),
+    ("MAINTAINERS", "maintainers"),
+    ("ROADMAP", "roadmap"),
+];
 
 // File system analyzer
 pub struct FileSystemAnalyzer {
     ignore_patterns: Vec<String>,
-    max_file_size: u64,
+    limits: AnalysisLimits,
+    complexity: ComplexityEstimator,
     max_preview_lines: usize,
 }
 
+impl Default for FileSystemAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FileSystemAnalyzer {
     pub fn new() -> Self {
+        Self::with_limits(AnalysisLimits::standard())
+    }
+
+    pub fn with_limits(limits: AnalysisLimits) -> Self {
         Self {
             ignore_patterns: vec![
                 ".git".to_string(),
@@ -38,26 +136,48 @@ impl FileSystemAnalyzer {
                 "*.tmp".to_string(),
                 "*.cache".to_string(),
             ],
-            max_file_size: 1_000_000, // 1MB
+            limits,
+            complexity: ComplexityEstimator::new(),
             max_preview_lines: 50,
         }
     }
 
-    pub fn analyze_directory(&self, repo_path: &Path) -> Result<DirectoryInfo> {
-        info!("Analyzing directory structure: {:?}", repo_path);
-        self.analyze_directory_recursive(repo_path, repo_path)
+    // The one walk of the clone. Every registered classifier sees each file the
+    // guard and the file limit let through; below directories the structure
+    // scan skips, files are only visited down to METADATA_DEPTH. Large files
+    // are mapped when `owned`, for clones in the work directory, and read
+    // otherwise. Returns the other projects' code the structure scan skipped
+    pub fn scan(
+        &self,
+        repo_path: &Path,
+        owned: bool,
+        classifiers: &mut [&mut dyn FileClassifier],
+    ) -> Result<Vec<EmbeddedCode>> {
+        info!("Scanning repository files: {:?}", repo_path);
+        let repo_path = &long_path(repo_path);
+        let mut walk = Walk {
+            guard: PathGuard::new(repo_path, &self.limits)?,
+            root: repo_path,
+            owned,
+            files_left: self.limits.max_files,
+            embedded: Vec::new(),
+            visited: HashSet::from([repo_path.canonicalize()?]),
+        };
+        self.scan_directory(&mut walk, repo_path, 0, true, classifiers)?;
+        Ok(walk.embedded)
     }
 
-    fn analyze_directory_recursive(
+    // A directory's files are classified before its subdirectories are
+    // entered, as the file index keeps each subtree's files contiguous
+    fn scan_directory(
         &self,
-        root_path: &Path,
+        walk: &mut Walk<'_>,
         current_path: &Path,
-    ) -> Result<DirectoryInfo> {
-        let mut files = Vec::new();
+        depth: usize,
+        in_structure: bool,
+        classifiers: &mut [&mut dyn FileClassifier],
+    ) -> Result<()> {
         let mut subdirectories = Vec::new();
-        let mut total_size = 0u64;
-        let mut file_count = 0u32;
-        let mut subdirectory_count = 0u32;
 
         let walker = WalkBuilder::new(current_path)
             .max_depth(Some(1))
@@ -66,6 +186,10 @@ impl FileSystemAnalyzer {
             .build();
 
         for entry in walker {
+            if cancel::is_cancelled() {
+                anyhow::bail!("File system scan cancelled");
+            }
+
             let entry = entry?;
             let path = entry.path();
 
@@ -73,66 +197,112 @@ impl FileSystemAnalyzer {
                 continue;
             }
 
-            // Skip ignored patterns
-            if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                if self.ignore_patterns.iter().any(|pattern| {
+            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if file_name == ".git" {
+                continue;
+            }
+
+            // Ignored patterns are left out of the structure only
+            let entry_in_structure = in_structure
+                && !self.ignore_patterns.iter().any(|pattern| {
                     pattern.trim_end_matches('*') == file_name
                         || file_name.starts_with(pattern.trim_end_matches('*'))
-                }) {
-                    continue;
-                }
+                });
+            if !entry_in_structure && depth + 1 > METADATA_DEPTH {
+                continue;
             }
 
-            let relative_path = path.strip_prefix(root_path).unwrap_or(path).to_path_buf();
+            if let Err(e) = walk.guard.check(path) {
+                warn!("Skipping {:?}: {}", path, e);
+                continue;
+            }
 
             if path.is_file() {
-                match self.analyze_file(path, relative_path) {
-                    Ok(file_info) => {
-                        total_size += file_info.size;
-                        file_count += 1;
-                        files.push(file_info);
-                    }
-                    Err(e) => {
-                        warn!("Failed to analyze file {:?}: {}", path, e);
-                    }
+                if walk.files_left == 0 {
+                    continue;
+                }
+                walk.files_left -= 1;
+                if walk.files_left == 0 {
+                    warning!(
+                        "File limit of {} reached, remaining files are skipped",
+                        self.limits.max_files
+                    );
+                }
+
+                let relative_path = to_slash_path(path.strip_prefix(walk.root).unwrap_or(path));
+                let file = ScannedFile::new(
+                    path,
+                    &relative_path,
+                    depth + 1,
+                    entry_in_structure,
+                    self.limits.max_file_size,
+                    walk.owned,
+                );
+                for classifier in classifiers.iter_mut() {
+                    classifier.classify(&file);
                 }
+                progress::emit(ProgressEvent::FileAnalyzed {
+                    path: relative_path,
+                    files_analyzed: self.limits.max_files - walk.files_left,
+                });
             } else if path.is_dir() {
-                match self.analyze_directory_recursive(root_path, path) {
-                    Ok(dir_info) => {
-                        total_size += dir_info.total_size;
-                        subdirectory_count += 1;
-                        subdirectories.push(dir_info);
-                    }
+                // Symlinks can lead back to a directory already walked, to an
+                // ancestor one at every level, each is only walked once
+                let first_visit = match path.canonicalize() {
+                    Ok(canonical) => walk.visited.insert(canonical),
                     Err(e) => {
-                        warn!("Failed to analyze directory {:?}: {}", path, e);
+                        warn!("Skipping {:?}: {}", path, e);
+                        continue;
                     }
+                };
+                if !first_visit {
+                    warn!("Skipping {:?}: directory already scanned", path);
+                    continue;
+                }
+
+                // Nested repositories and vendored projects are skipped like
+                // ignored directories
+                let entry_in_structure = entry_in_structure
+                    && match embedded::detect(walk.root, path, &self.limits) {
+                        Some(code) => {
+                            info!(
+                                "Skipping embedded {} {:?} ({})",
+                                code.kind, code.path, code.project
+                            );
+                            walk.embedded.push(code);
+                            false
+                        }
+                        None => true,
+                    };
+                if entry_in_structure || depth + 2 <= METADATA_DEPTH {
+                    subdirectories.push((path.to_path_buf(), entry_in_structure));
                 }
             }
         }
 
-        let dir_name = current_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("")
-            .to_string();
+        for (path, in_structure) in subdirectories {
+            for classifier in classifiers.iter_mut() {
+                classifier.enter_directory(&path, in_structure);
+            }
+            let result = self.scan_directory(walk, &path, depth + 1, in_structure, classifiers);
+            if let Err(e) = &result {
+                warn!("Failed to scan directory {:?}: {}", path, e);
+            }
+            for classifier in classifiers.iter_mut() {
+                classifier.leave_directory(in_structure, result.is_ok());
+            }
+        }
 
-        Ok(DirectoryInfo {
-            path: current_path.to_path_buf(),
-            name: dir_name,
-            file_count,
-            subdirectory_count,
-            total_size,
-            files,
-            subdirectories,
-        })
+        Ok(())
     }
 
-    fn analyze_file(&self, file_path: &Path, relative_path: PathBuf) -> Result<FileInfo> {
-        let metadata = fs::metadata(file_path)?;
-        let size = metadata.len();
+    // `bytes` is the whole file, usually mapped, so the hash, the binary sniff
+    // and the line statistics share a single read
+    fn analyze_file(&self, file_path: &Path, relative_path: PathBuf, bytes: &[u8]) -> FileInfo {
+        let size = bytes.len() as u64;
 
-        if size > self.max_file_size {
-            return Ok(FileInfo {
+        if size > self.limits.max_file_size {
+            return FileInfo {
                 path: relative_path.clone(),
                 name: file_path
                     .file_name()
@@ -152,26 +322,28 @@ impl FileSystemAnalyzer {
                 is_binary: true,
                 is_text: false,
                 encoding: None,
-                hash: self.calculate_file_hash(file_path)?,
+                hash: self.calculate_file_hash(bytes),
                 content_preview: None,
-            });
+                complexity: None,
+                functions: Vec::new(),
+            };
         }
 
         let mime_type = mime_guess::from_path(file_path)
             .first()
             .map(|m| m.to_string());
 
-        let is_binary = self.is_binary_file(file_path)?;
+        let is_binary = self.is_binary_file(file_path, bytes);
 
-        let (content_preview, encoding, lines_info) = if !is_binary {
-            self.read_text_file_info(file_path)?
+        let language = self.detect_language(file_path);
+
+        let (content_preview, encoding, lines_info, complexity_info) = if !is_binary {
+            self.read_text_file_info(file_path, &relative_path, language.as_deref(), bytes)
         } else {
-            (None, None, (None, None, None))
+            (None, None, (None, None, None), (None, Vec::new()))
         };
 
-        let language = self.detect_language(file_path);
-
-        Ok(FileInfo {
+        FileInfo {
             path: relative_path,
             name: file_path
                 .file_name()
@@ -191,18 +363,16 @@ impl FileSystemAnalyzer {
             is_binary,
             is_text: !is_binary,
             encoding,
-            hash: self.calculate_file_hash(file_path)?,
+            hash: self.calculate_file_hash(bytes),
             content_preview,
-        })
+            complexity: complexity_info.0,
+            functions: complexity_info.1,
+        }
     }
 
-    fn is_binary_file(&self, file_path: &Path) -> Result<bool> {
-        let mut file = fs::File::open(file_path)?;
-        let mut buffer = [0; 512];
-        let bytes_read = file.read(&mut buffer)?;
-
+    fn is_binary_file(&self, file_path: &Path, bytes: &[u8]) -> bool {
         // Check for null bytes (common in binary files)
-        let has_null_bytes = buffer[..bytes_read].contains(&0);
+        let has_null_bytes = bytes[..bytes.len().min(512)].contains(&0);
 
         // Check if it's a known binary extension
         let is_binary_ext = if let Some(ext) = file_path.extension().and_then(|e| e.to_str()) {
@@ -248,25 +418,21 @@ impl FileSystemAnalyzer {
             false
         };
 
-        Ok(has_null_bytes || is_binary_ext)
+        has_null_bytes || is_binary_ext
     }
 
     fn read_text_file_info(
         &self,
         file_path: &Path,
-    ) -> Result<(
-        Option<String>,
-        Option<String>,
-        (Option<u32>, Option<u32>, Option<u32>),
-    )> {
-        let content = fs::read(file_path)?;
-
-        // Detect encoding
-        let (decoded, encoding_used, _) = encoding_rs::UTF_8.decode(&content);
+        relative_path: &Path,
+        language: Option<&str>,
+        bytes: &[u8],
+    ) -> TextFileInfo {
+        // Detect encoding, valid UTF-8 is borrowed rather than copied
+        let (decoded, encoding_used, _) = encoding_rs::UTF_8.decode(bytes);
         let encoding_name = encoding_used.name().to_string();
 
-        let text = decoded.to_string();
-        let lines: Vec<&str> = text.lines().collect();
+        let lines: Vec<&str> = decoded.lines().collect();
 
         // Calculate line statistics
         let total_lines = lines.len() as u32;
@@ -284,11 +450,21 @@ impl FileSystemAnalyzer {
             None
         };
 
-        Ok((
+        // Complexity estimates for source code only
+        let complexity_info = match language {
+            Some(language) if self.complexity.supports(language) => (
+                Some(self.complexity.cyclomatic_complexity(&lines)),
+                self.complexity.functions(&lines, language, relative_path),
+            ),
+            _ => (None, Vec::new()),
+        };
+
+        (
             content_preview,
             Some(encoding_name),
             (Some(lines_of_code), Some(blank_lines), Some(comment_lines)),
-        ))
+            complexity_info,
+        )
     }
 
     fn count_comment_lines(&self, lines: &[&str], file_path: &Path) -> u32 {
@@ -398,122 +574,56 @@ impl FileSystemAnalyzer {
         Some(language.to_string())
     }
 
-    fn calculate_file_hash(&self, file_path: &Path) -> Result<String> {
-        let content = fs::read(file_path)?;
-        let digest = md5::compute(&content);
-        Ok(format!("{:x}", digest))
-    }
-
-    pub fn find_config_files(&self, repo_path: &Path) -> Result<Vec<ConfigFile>> {
-        let mut config_files = Vec::new();
-
-        let config_patterns = vec![
-            ("package.json", "npm"),
-            ("Cargo.toml", "cargo"),
-            ("requirements.txt", "pip"),
-            ("Pipfile", "pipenv"),
-            ("pyproject.toml", "python"),
-            ("pom.xml", "maven"),
-            ("build.gradle", "gradle"),
-            ("composer.json", "composer"),
-            ("Gemfile", "bundler"),
-            ("go.mod", "go"),
-            ("pubspec.yaml", "dart"),
-            ("project.clj", "leiningen"),
-            ("mix.exs", "mix"),
-            ("rebar.config", "rebar"),
-            ("stack.yaml", "stack"),
-            ("cabal.project", "cabal"),
-            ("dune-project", "dune"),
-            (".travis.yml", "travis"),
-            (".github/workflows", "github-actions"),
-            ("Dockerfile", "docker"),
-            ("docker-compose.yml", "docker-compose"),
-            ("kubernetes.yaml", "kubernetes"),
-            ("terraform.tf", "terraform"),
-            ("ansible.yml", "ansible"),
-            (".eslintrc", "eslint"),
-            (".prettierrc", "prettier"),
-            ("tsconfig.json", "typescript"),
-            ("webpack.config.js", "webpack"),
-            ("vite.config.js", "vite"),
-            ("rollup.config.js", "rollup"),
-            ("jest.config.js", "jest"),
-            ("cypress.json", "cypress"),
-            (".env", "environment"),
-            (".gitignore", "git"),
-            (".gitattributes", "git"),
-        ];
-
-        for (pattern, file_type) in config_patterns {
-            if let Ok(found_files) = self.find_files_by_pattern(repo_path, pattern) {
-                for file_path in found_files {
-                    if let Ok(content) = fs::read_to_string(&file_path) {
-                        let relative_path = file_path
-                            .strip_prefix(repo_path)
-                            .unwrap_or(&file_path)
-                            .to_path_buf();
-
-                        let (parsed_deps, scripts) = self.parse_config_file(&content, file_type);
-
-                        config_files.push(ConfigFile {
-                            path: relative_path,
-                            file_type: file_type.to_string(),
-                            content: content.clone(),
-                            parsed_dependencies: parsed_deps,
-                            scripts,
-                        });
-                    }
-                }
-            }
-        }
-
-        Ok(config_files)
+    fn calculate_file_hash(&self, bytes: &[u8]) -> String {
+        format!("{:x}", md5::compute(bytes))
     }
 
-    fn find_files_by_pattern(&self, repo_path: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
-        let mut found_files = Vec::new();
-
-        for entry in WalkDir::new(repo_path)
-            .max_depth(3)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                if file_name == pattern || file_name.starts_with(pattern) {
-                    found_files.push(path.to_path_buf());
-                }
-            }
-        }
-
-        Ok(found_files)
-    }
-
-    fn parse_config_file(
+    // Dependencies declared by a manifest with a dependency parser, keyed by name
+    pub fn manifest_dependencies(
         &self,
+        file_name: &str,
         content: &str,
-        file_type: &str,
-    ) -> (
-        Option<HashMap<String, String>>,
-        Option<HashMap<String, String>>,
-    ) {
+    ) -> Option<HashMap<String, String>> {
+        let file_type = match file_name {
+            "package.json" => "npm",
+            "Cargo.toml" => "cargo",
+            "requirements.txt" => "pip",
+            "pyproject.toml" => "python",
+            "Gemfile" | "Gemfile.lock" => "bundler",
+            "composer.json" => "composer",
+            "pubspec.yaml" => "dart",
+            "mix.exs" => "mix",
+            "build.sbt" => "sbt",
+            "CMakeLists.txt" => "cmake",
+            "conanfile.txt" | "conanfile.py" => "conan",
+            "vcpkg.json" => "vcpkg",
+            _ => return None,
+        };
+        self.parse_config_file(file_name, content, file_type).0
+    }
+
+    // Some types cover several file formats, told apart by the file name
+    fn parse_config_file(&self, file_name: &str, content: &str, file_type: &str) -> ParsedConfig {
         match file_type {
             "npm" => self.parse_package_json(content),
             "cargo" => self.parse_cargo_toml(content),
             "pip" => self.parse_requirements_txt(content),
             "python" => self.parse_pyproject_toml(content),
+            "bundler" if file_name.ends_with(".lock") => self.parse_gemfile_lock(content),
+            "bundler" => self.parse_gemfile(content),
+            "composer" => self.parse_composer_json(content),
+            "dart" => self.parse_pubspec_yaml(content),
+            "mix" => self.parse_mix_exs(content),
+            "sbt" => self.parse_build_sbt(content),
+            "cmake" => self.parse_cmake_lists(content),
+            "conan" if file_name.ends_with(".py") => self.parse_conanfile_py(content),
+            "conan" => self.parse_conanfile_txt(content),
+            "vcpkg" => self.parse_vcpkg_json(content),
             _ => (None, None),
         }
     }
 
-    fn parse_package_json(
-        &self,
-        content: &str,
-    ) -> (
-        Option<HashMap<String, String>>,
-        Option<HashMap<String, String>>,
-    ) {
+    fn parse_package_json(&self, content: &str) -> ParsedConfig {
         let json: serde_json::Value = match serde_json::from_str(content) {
             Ok(json) => json,
             Err(_) => return (None, None),
@@ -558,13 +668,7 @@ impl FileSystemAnalyzer {
         )
     }
 
-    fn parse_cargo_toml(
-        &self,
-        content: &str,
-    ) -> (
-        Option<HashMap<String, String>>,
-        Option<HashMap<String, String>>,
-    ) {
+    fn parse_cargo_toml(&self, content: &str) -> ParsedConfig {
         let toml: toml::Value = match content.parse() {
             Ok(toml) => toml,
             Err(_) => return (None, None),
@@ -598,13 +702,7 @@ impl FileSystemAnalyzer {
         )
     }
 
-    fn parse_requirements_txt(
-        &self,
-        content: &str,
-    ) -> (
-        Option<HashMap<String, String>>,
-        Option<HashMap<String, String>>,
-    ) {
+    fn parse_requirements_txt(&self, content: &str) -> ParsedConfig {
         let mut dependencies = HashMap::new();
 
         for line in content.lines() {
@@ -632,32 +730,25 @@ impl FileSystemAnalyzer {
         )
     }
 
-    fn parse_pyproject_toml(
-        &self,
-        content: &str,
-    ) -> (
-        Option<HashMap<String, String>>,
-        Option<HashMap<String, String>>,
-    ) {
+    fn parse_pyproject_toml(&self, content: &str) -> ParsedConfig {
         let toml: toml::Value = match content.parse() {
             Ok(toml) => toml,
             Err(_) => return (None, None),
         };
 
         let mut dependencies = HashMap::new();
-        if let Some(project) = toml["project"].as_table() {
-            if let Some(deps) = project["dependencies"].as_array() {
-                for dep in deps {
-                    if let Some(dep_str) = dep.as_str() {
-                        if let Some((name, version)) = dep_str.split_once("==") {
-                            dependencies
-                                .insert(name.trim().to_string(), version.trim().to_string());
-                        } else if let Some((name, version)) = dep_str.split_once(">=") {
-                            dependencies
-                                .insert(name.trim().to_string(), format!(">={}", version.trim()));
-                        } else {
-                            dependencies.insert(dep_str.to_string(), "*".to_string());
-                        }
+        if let Some(project) = toml["project"].as_table()
+            && let Some(deps) = project["dependencies"].as_array()
+        {
+            for dep in deps {
+                if let Some(dep_str) = dep.as_str() {
+                    if let Some((name, version)) = dep_str.split_once("==") {
+                        dependencies.insert(name.trim().to_string(), version.trim().to_string());
+                    } else if let Some((name, version)) = dep_str.split_once(">=") {
+                        dependencies
+                            .insert(name.trim().to_string(), format!(">={}", version.trim()));
+                    } else {
+                        dependencies.insert(dep_str.to_string(), "*".to_string());
                     }
                 }
             }
@@ -673,84 +764,304 @@ impl FileSystemAnalyzer {
         )
     }
 
-    pub fn find_documentation_files(&self, repo_path: &Path) -> Result<Vec<DocumentationFile>> {
-        let mut doc_files = Vec::new();
-
-        let doc_patterns = vec![
-            ("README", "readme"),
-            ("CHANGELOG", "changelog"),
-            ("CONTRIBUTING", "contributing"),
-            ("LICENSE", "license"),
-            ("CODE_OF_CONDUCT", "code_of_conduct"),
-            ("SECURITY", "security"),
-            ("INSTALL", "install"),
-            ("USAGE", "usage"),
-            ("API", "api"),
-            ("docs/", "documentation"),
-        ];
-
-        for (pattern, doc_type) in doc_patterns {
-            if let Ok(found_files) = self.find_documentation_by_pattern(repo_path, pattern) {
-                for file_path in found_files {
-                    if let Ok(content) = fs::read_to_string(&file_path) {
-                        let relative_path = file_path
-                            .strip_prefix(repo_path)
-                            .unwrap_or(&file_path)
-                            .to_path_buf();
-
-                        let word_count = content.split_whitespace().count() as u32;
-                        let has_badges = content.contains("[![") || content.contains("![");
-                        let has_toc = content.to_lowercase().contains("table of contents")
-                            || content.contains("## Contents")
-                            || content.contains("# Contents");
-
-                        let sections = self.extract_markdown_sections(&content);
-
-                        doc_files.push(DocumentationFile {
-                            path: relative_path,
-                            file_type: doc_type.to_string(),
-                            content,
-                            word_count,
-                            has_badges,
-                            has_toc,
-                            sections,
-                        });
-                    }
+    // gem "name", "~> 1.2", ">= 1.2.3"; only version constraints are kept
+    // out of the trailing arguments
+    fn parse_gemfile(&self, content: &str) -> ParsedConfig {
+        let gem_regex = Regex::new(r#"^gem\s+["']([^"']+)["'](.*)$"#).unwrap();
+        let arg_regex = Regex::new(r#"^["']([~><=!]*\s*\d[^"']*)["']$"#).unwrap();
+
+        let mut dependencies = HashMap::new();
+        for line in content.lines() {
+            let Some(captures) = gem_regex.captures(line.trim()) else {
+                continue;
+            };
+            let constraints: Vec<&str> = captures[2]
+                .split(',')
+                .filter_map(|arg| arg_regex.captures(arg.trim()))
+                .filter_map(|arg| arg.get(1).map(|c| c.as_str()))
+                .collect();
+            let version = if constraints.is_empty() {
+                "*".to_string()
+            } else {
+                constraints.join(", ")
+            };
+            dependencies.insert(captures[1].to_string(), version);
+        }
+
+        (non_empty(dependencies), None)
+    }
+
+    // Resolved gems are the four-space indented entries of the specs lists
+    fn parse_gemfile_lock(&self, content: &str) -> ParsedConfig {
+        let spec_regex = Regex::new(r"^ {4}([^\s(]+) \(([^)]+)\)$").unwrap();
+
+        let mut dependencies = HashMap::new();
+        for line in content.lines() {
+            if let Some(captures) = spec_regex.captures(line) {
+                dependencies.insert(captures[1].to_string(), captures[2].to_string());
+            }
+        }
+
+        (non_empty(dependencies), None)
+    }
+
+    // Platform requirements (php, ext-*) aren't packages
+    fn parse_composer_json(&self, content: &str) -> ParsedConfig {
+        let json: serde_json::Value = match serde_json::from_str(content) {
+            Ok(json) => json,
+            Err(_) => return (None, None),
+        };
+
+        let mut dependencies = HashMap::new();
+        for (section, suffix) in [("require", ""), ("require-dev", " (dev)")] {
+            let Some(deps) = json[section].as_object() else {
+                continue;
+            };
+            for (name, version) in deps {
+                if name == "php" || name.starts_with("ext-") {
+                    continue;
+                }
+                if let Some(ver_str) = version.as_str() {
+                    dependencies.insert(format!("{}{}", name, suffix), ver_str.to_string());
                 }
             }
         }
 
-        Ok(doc_files)
+        // Scripts are a command or a list of commands
+        let mut scripts = HashMap::new();
+        if let Some(script_obj) = json["scripts"].as_object() {
+            for (name, script) in script_obj {
+                let command = match script {
+                    serde_json::Value::String(command) => command.clone(),
+                    serde_json::Value::Array(commands) => commands
+                        .iter()
+                        .filter_map(|c| c.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" && "),
+                    _ => continue,
+                };
+                scripts.insert(name.clone(), command);
+            }
+        }
+
+        (non_empty(dependencies), non_empty(scripts))
     }
 
-    fn find_documentation_by_pattern(
-        &self,
-        repo_path: &Path,
-        pattern: &str,
-    ) -> Result<Vec<PathBuf>> {
-        let mut found_files = Vec::new();
-
-        for entry in WalkDir::new(repo_path)
-            .max_depth(3)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                let file_name_upper = file_name.to_uppercase();
-                let pattern_upper = pattern.to_uppercase();
-
-                if file_name_upper.starts_with(&pattern_upper)
-                    || (pattern.ends_with('/')
-                        && path.is_dir()
-                        && file_name_upper == pattern_upper.trim_end_matches('/'))
+    // SDK dependencies (flutter, flutter_test) come with the toolchain
+    fn parse_pubspec_yaml(&self, content: &str) -> ParsedConfig {
+        let yaml: serde_yaml::Value = match serde_yaml::from_str(content) {
+            Ok(yaml) => yaml,
+            Err(_) => return (None, None),
+        };
+
+        let mut dependencies = HashMap::new();
+        for (section, suffix) in [("dependencies", ""), ("dev_dependencies", " (dev)")] {
+            let Some(deps) = yaml.get(section).and_then(|d| d.as_mapping()) else {
+                continue;
+            };
+            for (name, dep) in deps {
+                let Some(name) = name.as_str() else {
+                    continue;
+                };
+                if dep.get("sdk").is_some() {
+                    continue;
+                }
+                let version = dep
+                    .as_str()
+                    .or_else(|| dep.get("version").and_then(|v| v.as_str()))
+                    .unwrap_or("*");
+                dependencies.insert(format!("{}{}", name, suffix), version.to_string());
+            }
+        }
+
+        (non_empty(dependencies), None)
+    }
+
+    // {:name, "~> 1.0"} tuples, or git, github and path sources without a version
+    fn parse_mix_exs(&self, content: &str) -> ParsedConfig {
+        let dep_regex = Regex::new(
+            r#"\{\s*:([a-z_][a-z0-9_]*)\s*,\s*(?:"([^"]+)"|(?:git|github|path|in_umbrella)\s*:)"#,
+        )
+        .unwrap();
+
+        let mut dependencies = HashMap::new();
+        for captures in dep_regex.captures_iter(content) {
+            let version = captures.get(2).map_or("*", |v| v.as_str());
+            dependencies.insert(captures[1].to_string(), version.to_string());
+        }
+
+        (non_empty(dependencies), None)
+    }
+
+    // "org" %% "name" % "1.0", with versions held in a val left as *
+    fn parse_build_sbt(&self, content: &str) -> ParsedConfig {
+        let dep_regex =
+            Regex::new(r#""([^"]+)"\s*%{1,3}\s*"([^"]+)"\s*%\s*(?:"([^"]+)"|[A-Za-z_])"#).unwrap();
+
+        let mut dependencies = HashMap::new();
+        for captures in dep_regex.captures_iter(content) {
+            let version = captures.get(3).map_or("*", |v| v.as_str());
+            dependencies.insert(
+                format!("{}:{}", &captures[1], &captures[2]),
+                version.to_string(),
+            );
+        }
+
+        (non_empty(dependencies), None)
+    }
+
+    // FetchContent_Declare blocks, versioned by their GIT_TAG or URL
+    fn parse_cmake_lists(&self, content: &str) -> ParsedConfig {
+        let declare_regex =
+            Regex::new(r"(?i)FetchContent_Declare\s*\(\s*([A-Za-z0-9_.+-]+)([^)]*)\)").unwrap();
+        let source_regex = Regex::new(r"(?:GIT_TAG|URL)\s+(\S+)").unwrap();
+
+        let mut dependencies = HashMap::new();
+        for captures in declare_regex.captures_iter(content) {
+            let version = source_regex
+                .captures(&captures[2])
+                .map_or("*", |source| source.get(1).map_or("*", |v| v.as_str()));
+            dependencies.insert(captures[1].to_string(), version.to_string());
+        }
+
+        (non_empty(dependencies), None)
+    }
+
+    // name/version[@user/channel] lines of the [requires] and tool sections
+    fn parse_conanfile_txt(&self, content: &str) -> ParsedConfig {
+        let mut dependencies = HashMap::new();
+        let mut suffix = None;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') {
+                suffix = match line {
+                    "[requires]" => Some(""),
+                    "[tool_requires]" | "[build_requires]" | "[test_requires]" => Some(" (dev)"),
+                    _ => None,
+                };
+                continue;
+            }
+            if let Some(suffix) = suffix
+                && let Some((name, version)) = line.split_once('/')
+            {
+                let version = version.split(['@', '#']).next().unwrap_or(version);
+                dependencies.insert(format!("{}{}", name, suffix), version.to_string());
+            }
+        }
+
+        (non_empty(dependencies), None)
+    }
+
+    // "name/version" references on requires lines and self.requires() calls
+    fn parse_conanfile_py(&self, content: &str) -> ParsedConfig {
+        let reference_regex =
+            Regex::new(r#"["']([A-Za-z0-9_.+-]+)/([^"'@#\s]+)[^"']*["']"#).unwrap();
+
+        let mut dependencies = HashMap::new();
+        for line in content.lines().filter(|line| line.contains("requires")) {
+            let suffix = if line.contains("tool_requires")
+                || line.contains("build_requires")
+                || line.contains("test_requires")
+            {
+                " (dev)"
+            } else {
+                ""
+            };
+            for captures in reference_regex.captures_iter(line) {
+                dependencies.insert(
+                    format!("{}{}", &captures[1], suffix),
+                    captures[2].to_string(),
+                );
+            }
+        }
+
+        (non_empty(dependencies), None)
+    }
+
+    // Dependencies are names or objects with a minimum version; overrides pin one
+    fn parse_vcpkg_json(&self, content: &str) -> ParsedConfig {
+        let json: serde_json::Value = match serde_json::from_str(content) {
+            Ok(json) => json,
+            Err(_) => return (None, None),
+        };
+
+        let mut dependencies = HashMap::new();
+        if let Some(deps) = json["dependencies"].as_array() {
+            for dep in deps {
+                let (name, version) = match dep {
+                    serde_json::Value::String(name) => (name.as_str(), "*".to_string()),
+                    _ => {
+                        let Some(name) = dep["name"].as_str() else {
+                            continue;
+                        };
+                        let version = dep["version>="]
+                            .as_str()
+                            .map_or("*".to_string(), |v| format!(">={}", v));
+                        (name, version)
+                    }
+                };
+                dependencies.insert(name.to_string(), version);
+            }
+        }
+        if let Some(overrides) = json["overrides"].as_array() {
+            for pin in overrides {
+                if let (Some(name), Some(version)) = (pin["name"].as_str(), pin["version"].as_str())
                 {
-                    found_files.push(path.to_path_buf());
+                    dependencies.insert(name.to_string(), version.to_string());
                 }
             }
         }
 
-        Ok(found_files)
+        (non_empty(dependencies), None)
+    }
+
+    // Read a file of the clone by its recorded relative path, within the limits
+    pub fn read_repo_file(&self, repo_path: &Path, relative_path: &Path) -> Option<String> {
+        let repo_path = long_path(repo_path);
+        let guard = PathGuard::new(&repo_path, &self.limits).ok()?;
+        let path = join_relative(&repo_path, relative_path);
+
+        if !path.is_file() || !self.is_readable(&guard, &path) {
+            return None;
+        }
+        fs::read_to_string(&path).ok()
+    }
+
+    // Relative paths of the readable files directly inside a directory of the clone.
+    // Used for directories the structure scan skips, such as .github
+    pub fn list_repo_dir(&self, repo_path: &Path, relative_dir: &Path) -> Vec<PathBuf> {
+        let repo_path = long_path(repo_path);
+        let Ok(guard) = PathGuard::new(&repo_path, &self.limits) else {
+            return Vec::new();
+        };
+        let Ok(entries) = fs::read_dir(join_relative(&repo_path, relative_dir)) else {
+            return Vec::new();
+        };
+
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && self.is_readable(&guard, path))
+            .filter_map(|path| path.strip_prefix(&repo_path).ok().map(to_slash_path))
+            .collect();
+        files.sort();
+        files
+    }
+
+    // Only paths that pass the guard and files within the size limit are read
+    fn is_readable(&self, guard: &PathGuard, path: &Path) -> bool {
+        if let Err(e) = guard.check(path) {
+            warn!("Skipping {:?}: {}", path, e);
+            return false;
+        }
+
+        fs::metadata(path)
+            .map(|m| m.is_dir() || m.len() <= self.limits.max_file_size)
+            .unwrap_or(false)
     }
 
     fn extract_markdown_sections(&self, content: &str) -> Vec<String> {
@@ -758,13 +1069,293 @@ impl FileSystemAnalyzer {
         let header_regex = Regex::new(r"^#+\s+(.+)$").unwrap();
 
         for line in content.lines() {
-            if let Some(captures) = header_regex.captures(line.trim()) {
-                if let Some(section_name) = captures.get(1) {
-                    sections.push(section_name.as_str().to_string());
-                }
+            if let Some(captures) = header_regex.captures(line.trim())
+                && let Some(section_name) = captures.get(1)
+            {
+                sections.push(section_name.as_str().to_string());
             }
         }
 
         sections
     }
 }
+
+fn non_empty(map: HashMap<String, String>) -> Option<HashMap<String, String>> {
+    if map.is_empty() { None } else { Some(map) }
+}
+
+// State of the walk shared by every directory it descends into
+struct Walk<'a> {
+    guard: PathGuard<'a>,
+    root: &'a Path,
+    owned: bool, // A clone in the work directory, whose files may be mapped
+    files_left: usize,
+    embedded: Vec<EmbeddedCode>, // Left out of the structure, in walk order
+    visited: HashSet<PathBuf>,   // Canonical directories entered so far
+}
+
+// Builds the file index out of the files the structure scan keeps
+pub struct StructureClassifier<'a> {
+    analyzer: &'a FileSystemAnalyzer,
+    index: FileIndex,
+    directories: Vec<usize>, // Open directories, the innermost last
+}
+
+impl<'a> StructureClassifier<'a> {
+    pub fn new(analyzer: &'a FileSystemAnalyzer, repo_path: &Path) -> Self {
+        Self {
+            analyzer,
+            index: FileIndex::new(&long_path(repo_path)),
+            directories: vec![0],
+        }
+    }
+
+    pub fn into_index(self) -> FileIndex {
+        self.index
+    }
+
+    fn current(&self) -> usize {
+        self.directories.last().copied().unwrap_or(0)
+    }
+}
+
+impl FileClassifier for StructureClassifier<'_> {
+    fn enter_directory(&mut self, path: &Path, in_structure: bool) {
+        if in_structure {
+            let directory = self.index.add_directory(self.current(), path);
+            self.directories.push(directory);
+        }
+    }
+
+    fn leave_directory(&mut self, in_structure: bool, completed: bool) {
+        if !in_structure {
+            return;
+        }
+        if let Some(directory) = self.directories.pop()
+            && !completed
+        {
+            self.index.remove_directory(directory);
+        }
+    }
+
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        if !file.in_structure {
+            return;
+        }
+        let Some(bytes) = file.bytes() else {
+            warn!("Failed to read file {:?}", file.path);
+            return;
+        };
+        let file_info = self
+            .analyzer
+            .analyze_file(file.path, file.relative.to_path_buf(), bytes);
+        self.index.add_file(self.current(), file_info);
+    }
+}
+
+// Picks up manifests and tool configs down to METADATA_DEPTH
+pub struct ConfigClassifier<'a> {
+    analyzer: &'a FileSystemAnalyzer,
+    found: Vec<(usize, ConfigFile)>, // With the index of the matched pattern
+}
+
+impl<'a> ConfigClassifier<'a> {
+    pub fn new(analyzer: &'a FileSystemAnalyzer) -> Self {
+        Self {
+            analyzer,
+            found: Vec::new(),
+        }
+    }
+
+    // Grouped by pattern, in the order CONFIG_PATTERNS lists them
+    pub fn into_config_files(mut self) -> Vec<ConfigFile> {
+        self.found.sort_by_key(|(pattern, _)| *pattern);
+        self.found.into_iter().map(|(_, config)| config).collect()
+    }
+}
+
+impl FileClassifier for ConfigClassifier<'_> {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        if file.depth > METADATA_DEPTH {
+            return;
+        }
+        // Case-insensitive, Windows and macOS file systems don't preserve a single casing
+        let file_name = file.file_name().to_lowercase();
+        for (index, (pattern, file_type)) in CONFIG_PATTERNS.iter().enumerate() {
+            if !file_name.starts_with(&pattern.to_lowercase()) {
+                continue;
+            }
+            let Some(content) = file.content() else {
+                return;
+            };
+            let (parsed_deps, scripts) =
+                self.analyzer
+                    .parse_config_file(file.file_name(), content, file_type);
+            self.found.push((
+                index,
+                ConfigFile {
+                    path: file.relative.to_path_buf(),
+                    file_type: file_type.to_string(),
+                    content: content.to_string(),
+                    parsed_dependencies: parsed_deps,
+                    scripts,
+                },
+            ));
+        }
+    }
+}
+
+// Picks up project documentation, issue and PR templates down to METADATA_DEPTH
+pub struct DocsClassifier<'a> {
+    analyzer: &'a FileSystemAnalyzer,
+    found: Vec<(usize, DocumentationFile)>, // Templates rank after every pattern
+}
+
+impl<'a> DocsClassifier<'a> {
+    pub fn new(analyzer: &'a FileSystemAnalyzer) -> Self {
+        Self {
+            analyzer,
+            found: Vec::new(),
+        }
+    }
+
+    pub fn into_documentation(mut self) -> Vec<DocumentationFile> {
+        self.found.sort_by_key(|(pattern, _)| *pattern);
+        self.found.into_iter().map(|(_, doc)| doc).collect()
+    }
+
+    fn add(&mut self, rank: usize, file: &ScannedFile<'_>, doc_type: &str) {
+        let Some(content) = file.content() else {
+            return;
+        };
+        let has_badges = content.contains("[![") || content.contains("![");
+        let has_toc = content.to_lowercase().contains("table of contents")
+            || content.contains("## Contents")
+            || content.contains("# Contents");
+
+        self.found.push((
+            rank,
+            DocumentationFile {
+                path: file.relative.to_path_buf(),
+                file_type: doc_type.to_string(),
+                content: content.to_string(),
+                word_count: content.split_whitespace().count() as u32,
+                has_badges,
+                has_toc,
+                sections: self.analyzer.extract_markdown_sections(content),
+            },
+        ));
+    }
+}
+
+impl FileClassifier for DocsClassifier<'_> {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        if file.depth > METADATA_DEPTH {
+            return;
+        }
+
+        let file_name = file.file_name().to_uppercase();
+        for (index, (pattern, doc_type)) in DOC_PATTERNS.iter().enumerate() {
+            if file_name.starts_with(pattern) {
+                self.add(index, file, doc_type);
+            }
+        }
+
+        // Issue/PR templates and the issue template chooser config
+        let is_template_file = matches!(
+            file.path.extension().and_then(|e| e.to_str()),
+            Some("md" | "yml" | "yaml")
+        );
+        if !is_template_file {
+            return;
+        }
+        let relative = file.relative.to_string_lossy().to_lowercase();
+        let doc_type = if relative.contains("pull_request_template") {
+            "pull_request_template"
+        } else if relative.contains("issue_template/config.") {
+            "issue_template_config"
+        } else if relative.contains("issue_template") {
+            "issue_template"
+        } else {
+            return;
+        };
+        self.add(DOC_PATTERNS.len(), file, doc_type);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Relative paths of the files the walk visits, in order
+    #[derive(Default)]
+    struct Visited(Vec<PathBuf>);
+
+    impl FileClassifier for Visited {
+        fn classify(&mut self, file: &ScannedFile<'_>) {
+            self.0.push(file.relative.to_path_buf());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn self_referencing_links_are_walked_once() {
+        let repo = tempfile::tempdir().unwrap();
+        fs::create_dir(repo.path().join("src")).unwrap();
+        fs::write(repo.path().join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(repo.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::os::unix::fs::symlink("..", repo.path().join("src/loop")).unwrap();
+        std::os::unix::fs::symlink(".", repo.path().join("src/self")).unwrap();
+
+        let mut visited = Visited::default();
+        FileSystemAnalyzer::new()
+            .scan(repo.path(), false, &mut [&mut visited])
+            .unwrap();
+
+        let mut files = visited.0;
+        files.sort();
+        assert_eq!(
+            files,
+            [PathBuf::from("Cargo.toml"), PathBuf::from("src/main.rs")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links_to_other_directories_are_still_followed() {
+        let repo = tempfile::tempdir().unwrap();
+        fs::create_dir(repo.path().join("docs")).unwrap();
+        fs::write(repo.path().join("docs/guide.md"), "# Guide\n").unwrap();
+        std::os::unix::fs::symlink("docs", repo.path().join("manual")).unwrap();
+
+        let mut visited = Visited::default();
+        FileSystemAnalyzer::new()
+            .scan(repo.path(), false, &mut [&mut visited])
+            .unwrap();
+
+        assert_eq!(visited.0.len(), 1);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn trees_deeper_than_max_path_are_walked() {
+        let repo = tempfile::tempdir().unwrap();
+        let mut deep = long_path(repo.path()).join("src");
+        for _ in 0..10 {
+            deep.push("a_directory_name_thirty_chars_");
+        }
+        fs::create_dir_all(&deep).unwrap();
+        fs::write(deep.join("main.rs"), "fn main() {}\n").unwrap();
+
+        // `..` and '/' are taken literally once a path is \\?\ prefixed
+        let unnormalized = repo.path().join("src/..");
+
+        let mut visited = Visited::default();
+        FileSystemAnalyzer::new()
+            .scan(&unnormalized, false, &mut [&mut visited])
+            .unwrap();
+
+        assert_eq!(visited.0.len(), 1);
+        assert!(visited.0[0].as_os_str().len() > 260);
+    }
+}
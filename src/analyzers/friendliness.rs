@@ -0,0 +1,197 @@
+use chrono::Utc;
+
+use crate::github::is_maintainer_association;
+use crate::types::{
+    ContributorFriendliness, DocumentationFile, GitHubIssue, RemoteRepositoryData,
+    TemplateInventory,
+};
+
+// Labels GitHub and common projects use for newcomer issues, searched as one
+pub const STARTER_LABELS: &[&str] = &[
+    "good first issue",
+    "good-first-issue",
+    "good first bug",
+    "first-timers-only",
+    "beginner",
+    "easy",
+];
+pub const HELP_WANTED_LABELS: &[&str] = &["help wanted", "help-wanted"];
+
+// Open starter issues at which a newcomer has a real choice
+const ENOUGH_STARTER_ISSUES: u32 = 3;
+
+// Recent outside issues whose first response is looked up, one request each
+pub const RESPONSE_SAMPLE: usize = 20;
+
+// Unanswered issues younger than this may still get an answer
+const RESPONSE_GRACE_DAYS: i64 = 7;
+
+// Median first response at which full, partial and minimal points are given
+const FAST_RESPONSE_HOURS: f64 = 48.0;
+const SLOW_RESPONSE_HOURS: f64 = 7.0 * 24.0;
+const VERY_SLOW_RESPONSE_HOURS: f64 = 30.0 * 24.0;
+
+// Rates how easy it is to start contributing from labeled starter issues,
+// contributing docs, templates and how fast maintainers answer newcomers.
+// Signals that couldn't be measured, such as the starter issues of a
+// read-only repository, are left out of the score rather than counted as 0
+pub struct FriendlinessAnalyzer;
+
+impl FriendlinessAnalyzer {
+    pub fn analyze(
+        &self,
+        remote: &RemoteRepositoryData,
+        documentation: &[DocumentationFile],
+        templates: &TemplateInventory,
+    ) -> ContributorFriendliness {
+        let has_doc = |file_type: &str| documentation.iter().any(|d| d.file_type == file_type);
+        let has_contributing_guide = has_doc("contributing");
+        let has_code_of_conduct = has_doc("code_of_conduct");
+        let has_issue_templates = templates.templates.iter().any(|t| t.kind == "issue");
+        let has_pull_request_template = templates.has_pull_request_template;
+        let starter_issues = remote.starter_issues.clone();
+        let (sampled_issues, responded_issues, median_first_response_hours) =
+            response_times(&remote.recent_issues);
+
+        let mut strengths = Vec::new();
+        let mut gaps = Vec::new();
+        // (points earned, points possible) of each measured signal
+        let mut points: Vec<(f64, f64)> = Vec::new();
+
+        match starter_issues.good_first_issues {
+            Some(0) => {
+                gaps.push("No open issues are labeled for newcomers.".to_string());
+                points.push((0.0, 25.0));
+            }
+            Some(count) => {
+                strengths.push(format!("{} open issues are labeled for newcomers.", count));
+                let earned = if count >= ENOUGH_STARTER_ISSUES {
+                    25.0
+                } else {
+                    15.0
+                };
+                points.push((earned, 25.0));
+            }
+            None => {}
+        }
+        match starter_issues.help_wanted_issues {
+            Some(0) => {
+                gaps.push("No open issues ask for help.".to_string());
+                points.push((0.0, 10.0));
+            }
+            Some(count) => {
+                strengths.push(format!("{} open issues ask for help.", count));
+                points.push((10.0, 10.0));
+            }
+            None => {}
+        }
+
+        let documents = [
+            (has_contributing_guide, 20.0, "a contributing guide"),
+            (has_issue_templates, 10.0, "issue templates"),
+            (has_pull_request_template, 5.0, "a pull request template"),
+            (has_code_of_conduct, 5.0, "a code of conduct"),
+        ];
+        for (present, weight, name) in documents {
+            if present {
+                strengths.push(format!("The repository has {}.", name));
+                points.push((weight, weight));
+            } else {
+                gaps.push(format!(
+                    "The repository has no {}.",
+                    name.trim_start_matches("a ")
+                ));
+                points.push((0.0, weight));
+            }
+        }
+
+        if sampled_issues > 0 {
+            let earned = match median_first_response_hours {
+                Some(hours) if hours <= FAST_RESPONSE_HOURS => 25.0,
+                Some(hours) if hours <= SLOW_RESPONSE_HOURS => 15.0,
+                Some(hours) if hours <= VERY_SLOW_RESPONSE_HOURS => 5.0,
+                _ => 0.0,
+            };
+            let summary = match median_first_response_hours {
+                Some(hours) => format!(
+                    "Maintainers answered {} of {} recent newcomer issues, with a median first response of {}.",
+                    responded_issues,
+                    sampled_issues,
+                    format_hours(hours)
+                ),
+                None => format!(
+                    "Maintainers answered {} of {} recent newcomer issues.",
+                    responded_issues, sampled_issues
+                ),
+            };
+            if earned >= 15.0 {
+                strengths.push(summary);
+            } else {
+                gaps.push(summary);
+            }
+            points.push((earned, 25.0));
+        }
+
+        let (earned, possible) = points
+            .iter()
+            .fold((0.0, 0.0), |(e, p), (earned, possible)| {
+                (e + earned, p + possible)
+            });
+        ContributorFriendliness {
+            score: if possible > 0.0 {
+                earned / possible * 100.0
+            } else {
+                0.0
+            },
+            starter_issues,
+            has_contributing_guide,
+            has_code_of_conduct,
+            has_issue_templates,
+            has_pull_request_template,
+            median_first_response_hours,
+            sampled_issues,
+            responded_issues,
+            strengths,
+            gaps,
+        }
+    }
+}
+
+// The most recent issues opened outside the team, how many a maintainer
+// answered and the median time to that answer. Unanswered issues count as slower than any
+// answer, so the median is None when most of them went unanswered
+fn response_times(issues: &[GitHubIssue]) -> (u32, u32, Option<f64>) {
+    let now = Utc::now();
+    let mut times: Vec<Option<f64>> = issues
+        .iter()
+        .filter(|issue| !is_maintainer_association(&issue.author_association))
+        .take(RESPONSE_SAMPLE)
+        .filter_map(|issue| match issue.first_response_at {
+            Some(at) => Some(Some((at - issue.created_at).num_minutes() as f64 / 60.0)),
+            None if (now - issue.created_at).num_days() < RESPONSE_GRACE_DAYS => None,
+            None => Some(None),
+        })
+        .collect();
+    let sampled = times.len() as u32;
+    let responded = times.iter().filter(|t| t.is_some()).count() as u32;
+    if times.is_empty() {
+        return (0, 0, None);
+    }
+
+    // None sorts first, put the unanswered ones after every answer instead
+    times.sort_by(|a, b| match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+    (sampled, responded, times[(times.len() - 1) / 2])
+}
+
+fn format_hours(hours: f64) -> String {
+    if hours < 48.0 {
+        format!("{:.0} hours", hours)
+    } else {
+        format!("{:.0} days", hours / 24.0)
+    }
+}
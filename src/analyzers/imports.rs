@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use regex::Regex;
+
+use crate::analyzers::scan::{FileClassifier, ScannedFile};
+
+// Modules, packages and headers each source file imports, as written, for the
+// custom rules. Each pattern captures the imported name in its first group
+pub struct ImportClassifier {
+    rules: Vec<(&'static [&'static str], Vec<Regex>)>, // Extensions and their patterns
+    imports: HashMap<PathBuf, Vec<String>>,
+}
+
+impl Default for ImportClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImportClassifier {
+    pub fn new() -> Self {
+        let regex = |patterns: &[&str]| -> Vec<Regex> {
+            patterns
+                .iter()
+                .map(|pattern| Regex::new(pattern).unwrap())
+                .collect()
+        };
+        let js = regex(&[
+            r#"(?m)^\s*import\s+(?:[^'";]*?\s+from\s+)?['"]([^'"]+)['"]"#,
+            r#"(?m)^\s*export\s+[^'";]*?\s+from\s+['"]([^'"]+)['"]"#,
+            r#"\brequire\s*\(\s*['"]([^'"]+)['"]\s*\)"#,
+            r#"\bimport\s*\(\s*['"]([^'"]+)['"]\s*\)"#,
+        ]);
+
+        let rules: Vec<(&'static [&'static str], Vec<Regex>)> = vec![
+            (
+                &["rs"],
+                regex(&[
+                    r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+(?:::)?([\w:]+)",
+                    r"(?m)^\s*extern\s+crate\s+(\w+)",
+                ]),
+            ),
+            (
+                &["py"],
+                regex(&[
+                    r"(?m)^\s*import\s+([\w.]+)",
+                    r"(?m)^\s*from\s+([\w.]+)\s+import\b",
+                ]),
+            ),
+            (&["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"], js),
+            (
+                &["go"],
+                regex(&[
+                    r#"(?m)^\s*import\s+(?:\w+\s+)?"([^"]+)""#,
+                    r#"(?m)^\s+(?:[\w.]+\s+)?"([^"]+)"\s*$"#, // Inside import ( ... )
+                ]),
+            ),
+            (
+                &["java", "kt", "kts", "scala"],
+                regex(&[r"(?m)^\s*import\s+(?:static\s+)?([\w.]+)"]),
+            ),
+            (
+                &["c", "h", "cc", "cpp", "cxx", "hpp", "hh", "m", "mm"],
+                regex(&[r#"(?m)^\s*#\s*include\s*[<"]([^>"]+)[>"]"#]),
+            ),
+            (
+                &["rb"],
+                regex(&[r#"(?m)^\s*require(?:_relative)?\s*\(?\s*['"]([^'"]+)['"]"#]),
+            ),
+            (&["php"], regex(&[r"(?m)^\s*use\s+\\?([\w\\]+)"])),
+            (
+                &["cs"],
+                regex(&[r"(?m)^\s*(?:global\s+)?using\s+(?:static\s+)?([\w.]+)\s*;"]),
+            ),
+            (&["swift"], regex(&[r"(?m)^\s*import\s+(\w+)"])),
+        ];
+
+        Self {
+            rules,
+            imports: HashMap::new(),
+        }
+    }
+
+    pub fn finish(self) -> HashMap<PathBuf, Vec<String>> {
+        self.imports
+    }
+}
+
+impl FileClassifier for ImportClassifier {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        if !file.in_structure {
+            return;
+        }
+        let Some(extension) = file.relative.extension().and_then(|e| e.to_str()) else {
+            return;
+        };
+        let extension = extension.to_lowercase();
+        let Some((_, patterns)) = self
+            .rules
+            .iter()
+            .find(|(extensions, _)| extensions.contains(&extension.as_str()))
+        else {
+            return;
+        };
+        let Some(content) = file.content() else {
+            return;
+        };
+
+        let mut imports: Vec<String> = Vec::new();
+        for pattern in patterns {
+            for captures in pattern.captures_iter(content) {
+                let import = captures[1].trim_end_matches("::").to_string();
+                if !imports.contains(&import) {
+                    imports.push(import);
+                }
+            }
+        }
+        if !imports.is_empty() {
+            self.imports.insert(file.relative.to_path_buf(), imports);
+        }
+    }
+}
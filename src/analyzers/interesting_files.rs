@@ -0,0 +1,244 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::file_index::FileIndex;
+use crate::types::{ConfigFile, FileInfo, InterestingFile};
+
+// Well-known entry point file names across ecosystems
+const ENTRY_POINT_NAMES: &[&str] = &[
+    "main.rs",
+    "lib.rs",
+    "build.rs",
+    "main.py",
+    "__main__.py",
+    "app.py",
+    "manage.py",
+    "wsgi.py",
+    "asgi.py",
+    "index.js",
+    "index.ts",
+    "main.js",
+    "main.ts",
+    "server.js",
+    "server.ts",
+    "app.js",
+    "app.ts",
+    "main.go",
+    "main.c",
+    "main.cpp",
+    "main.java",
+    "application.java",
+    "program.cs",
+    "startup.cs",
+    "main.kt",
+    "main.swift",
+    "app.rb",
+    "index.php",
+];
+
+// Files that act as the root of a module/package
+const MODULE_ROOT_NAMES: &[&str] = &[
+    "mod.rs",
+    "__init__.py",
+    "index.jsx",
+    "index.tsx",
+    "package-info.java",
+];
+
+// Directories that usually contain the core source of a project
+const CORE_DIRECTORIES: &[&str] = &[
+    "src/",
+    "lib/",
+    "pkg/",
+    "internal/",
+    "core/",
+    "app/",
+    "cmd/",
+    "server/",
+    "crates/",
+];
+
+// Path fragments that indicate code which is not architecturally relevant
+const NOISE_FRAGMENTS: &[&str] = &[
+    "test/",
+    "tests/",
+    "__tests__/",
+    "spec/",
+    "examples/",
+    "example/",
+    "vendor/",
+    "third_party/",
+    "fixtures/",
+    "benches/",
+    "generated/",
+];
+
+// Languages that describe data or markup rather than program structure
+const NON_CODE_LANGUAGES: &[&str] = &[
+    "JSON", "YAML", "TOML", "INI", "XML", "Markdown", "HTML", "CSS", "SCSS", "Sass", "Less",
+];
+
+// Number of largest source files considered "large exports"
+const LARGEST_FILES_CONSIDERED: usize = 5;
+
+// Minimum score for a file to be considered interesting
+const MIN_SCORE: f64 = 5.0;
+
+// Heuristic selector for the most architecturally relevant files of a repository
+pub struct InterestingFileSelector {
+    max_files: usize,
+}
+
+impl InterestingFileSelector {
+    pub fn new(max_files: usize) -> Self {
+        Self { max_files }
+    }
+
+    pub fn select(
+        &self,
+        file_structure: &FileIndex,
+        config_files: &[ConfigFile],
+    ) -> Vec<InterestingFile> {
+        let all_files = file_structure.files();
+
+        let config_paths: HashSet<&Path> = config_files
+            .iter()
+            .map(|config| config.path.as_path())
+            .collect();
+
+        // Largest source files by lines of code
+        let mut source_files: Vec<&FileInfo> = all_files
+            .iter()
+            .filter(|file| self.is_source_file(file) && !self.is_noise(file))
+            .collect();
+        source_files.sort_by_key(|file| std::cmp::Reverse(file.lines_of_code.unwrap_or(0)));
+        let largest: HashSet<&Path> = source_files
+            .iter()
+            .take(LARGEST_FILES_CONSIDERED)
+            .map(|file| file.path.as_path())
+            .collect();
+
+        let mut selected: Vec<InterestingFile> = all_files
+            .iter()
+            .filter_map(|file| self.score_file(file, &config_paths, &largest))
+            .collect();
+
+        selected.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        selected.truncate(self.max_files);
+        selected
+    }
+
+    fn score_file(
+        &self,
+        file: &FileInfo,
+        config_paths: &HashSet<&Path>,
+        largest: &HashSet<&Path>,
+    ) -> Option<InterestingFile> {
+        if file.is_binary || self.is_noise(file) {
+            return None;
+        }
+
+        let name = file.name.to_lowercase();
+        let path = file
+            .path
+            .to_string_lossy()
+            .replace('\\', "/")
+            .to_lowercase();
+        let depth = file.path.components().count();
+
+        let mut score = 0.0;
+        let mut reasons = Vec::new();
+        let mut categories = Vec::new();
+
+        if ENTRY_POINT_NAMES.contains(&name.as_str()) && self.is_source_file(file) {
+            score += 10.0;
+            reasons.push("entry point".to_string());
+            categories.push("entry-point");
+        }
+
+        if config_paths.contains(file.path.as_path()) && depth <= 2 {
+            score += 8.0;
+            reasons.push("project configuration".to_string());
+            categories.push("config");
+        }
+
+        if largest.contains(file.path.as_path()) {
+            score += 5.0;
+            reasons.push("one of the largest source files".to_string());
+            categories.push("large-export");
+        }
+
+        if self.is_source_file(file) {
+            if CORE_DIRECTORIES.iter().any(|dir| path.starts_with(dir)) {
+                score += 4.0;
+                reasons.push("core source directory".to_string());
+                categories.push("core-module");
+            }
+
+            if MODULE_ROOT_NAMES.contains(&name.as_str()) {
+                score += 3.0;
+                reasons.push("module root".to_string());
+                categories.push("core-module");
+            }
+        }
+
+        if score == 0.0 {
+            return None;
+        }
+
+        // Favour shallow files and files with more code
+        score += (4.0 - depth as f64).max(0.0);
+        if let Some(loc) = file.lines_of_code {
+            score += (loc as f64 + 1.0).ln() / 2.0;
+        }
+
+        if score < MIN_SCORE {
+            return None;
+        }
+
+        Some(InterestingFile {
+            path: file.path.clone(),
+            category: categories[0].to_string(),
+            score,
+            reasons,
+            preview: file.content_preview.clone(),
+        })
+    }
+
+    fn is_source_file(&self, file: &FileInfo) -> bool {
+        match &file.language {
+            Some(language) => !NON_CODE_LANGUAGES.contains(&language.as_str()),
+            None => false,
+        }
+    }
+
+    // Tests, examples, vendored and generated code, lockfiles and minified assets
+    pub fn is_noise(&self, file: &FileInfo) -> bool {
+        let path = format!(
+            "/{}",
+            file.path
+                .to_string_lossy()
+                .replace('\\', "/")
+                .to_lowercase()
+        );
+        let name = file.name.to_lowercase();
+
+        NOISE_FRAGMENTS
+            .iter()
+            .any(|fragment| path.contains(&format!("/{}", fragment)))
+            || name.ends_with(".lock")
+            || name.ends_with(".min.js")
+            || name.ends_with(".min.css")
+            || name == "package-lock.json"
+            || name == "pnpm-lock.yaml"
+            || name.contains("_test.")
+            || name.contains(".test.")
+            || name.contains(".spec.")
+            || name.starts_with("test_")
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::analyzers::scan::{FileClassifier, ScannedFile};
+
+// Keywords kept, most salient first
+const MAX_KEYWORDS: usize = 20;
+// Uses across the repository below which a word is noise
+const MIN_OCCURRENCES: u32 = 3;
+const MIN_LENGTH: usize = 3;
+const MAX_LENGTH: usize = 24;
+
+const CODE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "jsx", "mjs", "ts", "tsx", "go", "java", "kt", "scala", "c", "h", "cc",
+    "cpp", "hpp", "cs", "rb", "php", "swift", "ex", "exs", "hs", "ml", "lua", "dart",
+];
+const DOC_EXTENSIONS: &[&str] = &["md", "markdown", "rst", "adoc"];
+
+// English function words
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "was", "one", "our",
+    "out", "has", "have", "this", "that", "with", "from", "they", "will", "your", "into", "which",
+    "their", "there", "then", "than", "them", "these", "those", "its", "also", "more", "such",
+    "when", "what", "how", "other", "been", "being", "some", "each", "only", "just", "over",
+    "about", "like", "may", "well", "should", "would", "could", "must", "does", "doesn", "don",
+    "isn", "aren", "won", "here", "where", "while", "after", "before", "because", "both", "either",
+    "every", "many", "much", "most", "same", "very", "via", "per", "see", "yet", "still", "even",
+    "thus", "whether", "first", "last", "next", "once", "ever",
+];
+
+// Keywords and everyday vocabulary of programming languages, which every
+// codebase uses whatever it is about
+const CODE_WORDS: &[&str] = &[
+    "fn",
+    "let",
+    "mut",
+    "impl",
+    "self",
+    "pub",
+    "struct",
+    "enum",
+    "use",
+    "crate",
+    "mod",
+    "return",
+    "true",
+    "false",
+    "none",
+    "some",
+    "null",
+    "nil",
+    "var",
+    "const",
+    "function",
+    "class",
+    "def",
+    "import",
+    "package",
+    "func",
+    "type",
+    "types",
+    "string",
+    "str",
+    "int",
+    "bool",
+    "void",
+    "new",
+    "async",
+    "await",
+    "static",
+    "public",
+    "private",
+    "protected",
+    "println",
+    "format",
+    "unwrap",
+    "expect",
+    "err",
+    "error",
+    "errors",
+    "result",
+    "option",
+    "vec",
+    "len",
+    "iter",
+    "map",
+    "collect",
+    "clone",
+    "into",
+    "std",
+    "test",
+    "tests",
+    "assert",
+    "value",
+    "values",
+    "data",
+    "get",
+    "set",
+    "add",
+    "list",
+    "item",
+    "items",
+    "name",
+    "names",
+    "key",
+    "keys",
+    "index",
+    "count",
+    "size",
+    "default",
+    "args",
+    "arg",
+    "param",
+    "params",
+    "else",
+    "elif",
+    "case",
+    "switch",
+    "break",
+    "continue",
+    "loop",
+    "match",
+    "trait",
+    "dyn",
+    "ref",
+    "box",
+    "char",
+    "float",
+    "double",
+    "long",
+    "usize",
+    "isize",
+    "object",
+    "array",
+    "number",
+    "undefined",
+    "export",
+    "require",
+    "module",
+    "interface",
+    "extends",
+    "implements",
+    "throw",
+    "throws",
+    "try",
+    "catch",
+    "finally",
+    "lambda",
+    "yield",
+    "print",
+    "printf",
+    "log",
+    "info",
+    "warn",
+    "debug",
+    "trace",
+    "todo",
+    "fixme",
+    "foo",
+    "bar",
+    "baz",
+    "tmp",
+    "temp",
+    "init",
+    "main",
+    "run",
+    "call",
+    "ptr",
+    "buf",
+    "src",
+    "dst",
+    "idx",
+    "ctx",
+    "cfg",
+    "opts",
+    "vals",
+    "res",
+    "ret",
+    "val",
+    "obj",
+    "elem",
+    "http",
+    "https",
+    "www",
+    "com",
+    "org",
+    "github",
+];
+
+// Salient words of the docs and of the identifiers in the code, by TF-IDF
+// with every file a document, collected during the shared walk
+pub struct KeywordClassifier {
+    files: u32,
+    // Per word: summed term frequencies, files using it and total uses
+    terms: HashMap<String, (f64, u32, u32)>,
+}
+
+impl Default for KeywordClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeywordClassifier {
+    pub fn new() -> Self {
+        Self {
+            files: 0,
+            terms: HashMap::new(),
+        }
+    }
+
+    pub fn finish(self) -> Vec<String> {
+        let files = self.files as f64;
+        let mut scored: Vec<(String, f64)> = self
+            .terms
+            .into_iter()
+            .filter(|(_, (_, _, uses))| *uses >= MIN_OCCURRENCES)
+            .map(|(term, (tf, df, _))| {
+                // Smoothed, so words in every file still count a little
+                let idf = ((1.0 + files) / (1.0 + df as f64)).ln() + 1.0;
+                (term, tf * idf)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        scored
+            .into_iter()
+            .take(MAX_KEYWORDS)
+            .map(|(term, _)| term)
+            .collect()
+    }
+
+    fn add_document(&mut self, content: &str) {
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        let mut total = 0;
+        for word in identifiers(content).flat_map(split_identifier) {
+            total += 1;
+            if is_keyword(&word) {
+                *counts.entry(word).or_default() += 1;
+            }
+        }
+        if total == 0 {
+            return;
+        }
+        self.files += 1;
+        for (word, count) in counts {
+            let term = self.terms.entry(word).or_default();
+            term.0 += count as f64 / total as f64;
+            term.1 += 1;
+            term.2 += count;
+        }
+    }
+}
+
+impl FileClassifier for KeywordClassifier {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        if !file.in_structure {
+            return;
+        }
+        let extension = Path::new(file.file_name())
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if !CODE_EXTENSIONS.contains(&extension.as_str())
+            && !DOC_EXTENSIONS.contains(&extension.as_str())
+        {
+            return;
+        }
+        if let Some(content) = file.content() {
+            self.add_document(content);
+        }
+    }
+}
+
+// Identifiers in code, words in prose
+fn identifiers(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .filter(|token| !token.is_empty())
+}
+
+// parseHTTPRequest, parse_http_request -> parse, http, request
+fn split_identifier(identifier: &str) -> Vec<String> {
+    let chars: Vec<char> = identifier.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (index, c) in chars.iter().enumerate() {
+        if *c == '_' || c.is_ascii_digit() {
+            words.push(std::mem::take(&mut word));
+            continue;
+        }
+        let previous = index.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(index + 1);
+        let boundary = c.is_uppercase()
+            && (previous.is_some_and(|p| p.is_lowercase())
+                || (previous.is_some_and(|p| p.is_uppercase())
+                    && next.is_some_and(|n| n.is_lowercase())));
+        if boundary {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c.to_ascii_lowercase());
+    }
+    words.push(word);
+    words.retain(|word| !word.is_empty());
+    words
+}
+
+fn is_keyword(word: &str) -> bool {
+    (MIN_LENGTH..=MAX_LENGTH).contains(&word.len())
+        && !STOPWORDS.contains(&word)
+        && !CODE_WORDS.contains(&word)
+}
@@ -0,0 +1,268 @@
+use globset::{Glob, GlobBuilder, GlobMatcher};
+use regex::Regex;
+
+use crate::types::{ConfigFile, FileInfo};
+
+// Paths GitHub Linguist treats as vendored or generated by default
+const VENDORED_PATH_PATTERNS: &[&str] = &[
+    r"(^|/)node_modules/",
+    r"(^|/)bower_components/",
+    r"(^|/)[Vv]endor/",
+    r"(^|/)third[-_]?party/",
+    r"(^|/)deps/",
+    r"(^|/)dist/",
+    r"\.min\.(js|css)$",
+    r"(^|/)jquery[^/]*\.js$",
+    r"\.pb\.go$",
+    r"_pb2\.py$",
+    r"\.designer\.cs$",
+    r"(^|/)(package-lock\.json|yarn\.lock|Cargo\.lock|poetry\.lock)$",
+];
+
+// Paths GitHub Linguist treats as documentation by default
+const DOCUMENTATION_PATH_PATTERNS: &[&str] = &[
+    r"^[Dd]ocs?/",
+    r"(^|/)[Dd]ocumentation/",
+    r"(^|/)[Ee]xamples?/",
+];
+
+// Languages Linguist classifies as data or prose, which don't count towards the stats
+const NON_PROGRAMMING_LANGUAGES: &[&str] = &[
+    "JSON",
+    "YAML",
+    "TOML",
+    "INI",
+    "XML",
+    "Markdown",
+    "Protocol Buffers",
+    "GraphQL",
+];
+
+// A `.gitattributes` line with the linguist attributes it sets
+struct AttributeRule {
+    matcher: GlobMatcher,
+    vendored: Option<bool>,
+    generated: Option<bool>,
+    documentation: Option<bool>,
+    detectable: Option<bool>,
+    language: Option<String>,
+}
+
+// Linguist-compatible language classification: honours the root .gitattributes
+// overrides, excludes vendored/generated/documentation paths and resolves
+// ambiguous extensions from file content
+pub struct LinguistClassifier {
+    rules: Vec<AttributeRule>,
+    vendored_paths: Vec<Regex>,
+    documentation_paths: Vec<Regex>,
+}
+
+// Linguist attributes of one path, after every matching rule is applied
+#[derive(Default)]
+struct Attributes {
+    vendored: Option<bool>,
+    generated: Option<bool>,
+    documentation: Option<bool>,
+    detectable: Option<bool>,
+    language: Option<String>,
+}
+
+impl LinguistClassifier {
+    pub fn new(config_files: &[ConfigFile]) -> Self {
+        let rules = config_files
+            .iter()
+            .find(|config| config.path.to_string_lossy() == ".gitattributes")
+            .map(|config| Self::parse_gitattributes(&config.content))
+            .unwrap_or_default();
+
+        let compile = |patterns: &[&str]| {
+            patterns
+                .iter()
+                .filter_map(|pattern| Regex::new(pattern).ok())
+                .collect()
+        };
+        Self {
+            rules,
+            vendored_paths: compile(VENDORED_PATH_PATTERNS),
+            documentation_paths: compile(DOCUMENTATION_PATH_PATTERNS),
+        }
+    }
+
+    fn parse_gitattributes(content: &str) -> Vec<AttributeRule> {
+        let mut rules = Vec::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            let Some(matcher) = Self::pattern_matcher(pattern) else {
+                continue;
+            };
+
+            let mut rule = AttributeRule {
+                matcher,
+                vendored: None,
+                generated: None,
+                documentation: None,
+                detectable: None,
+                language: None,
+            };
+
+            for attribute in parts {
+                let (name, value) = match attribute.split_once('=') {
+                    Some((name, value)) => (name, Some(value)),
+                    None => (attribute, None),
+                };
+                let (name, enabled) = match name.strip_prefix('-') {
+                    Some(name) => (name, false),
+                    None => (name, value != Some("false")),
+                };
+
+                match name {
+                    "linguist-vendored" => rule.vendored = Some(enabled),
+                    "linguist-generated" => rule.generated = Some(enabled),
+                    "linguist-documentation" => rule.documentation = Some(enabled),
+                    "linguist-detectable" => rule.detectable = Some(enabled),
+                    "linguist-language" => rule.language = value.map(|v| v.replace('-', " ")),
+                    _ => {}
+                }
+            }
+
+            rules.push(rule);
+        }
+
+        rules
+    }
+
+    // gitattributes semantics: patterns without a slash match at any depth,
+    // patterns with one are anchored at the repository root
+    fn pattern_matcher(pattern: &str) -> Option<GlobMatcher> {
+        let pattern = pattern.trim_end_matches('/');
+        let glob = if pattern.contains('/') {
+            pattern.trim_start_matches('/').to_string()
+        } else {
+            format!("**/{}", pattern)
+        };
+
+        let matcher = GlobBuilder::new(&glob)
+            .literal_separator(true)
+            .build()
+            .or_else(|_| Glob::new(&glob))
+            .ok()?
+            .compile_matcher();
+        Some(matcher)
+    }
+
+    // Language the file counts towards, or None when Linguist would ignore it
+    pub fn language(&self, file: &FileInfo) -> Option<String> {
+        let path = file.path.to_string_lossy();
+        let attributes = self.attributes(&path);
+
+        let excluded_by_default = self.is_vendored_path(&path)
+            || self.documentation_paths.iter().any(|re| re.is_match(&path));
+        let excluded = attributes.vendored.unwrap_or(excluded_by_default)
+            || attributes.generated.unwrap_or(false)
+            || attributes.documentation.unwrap_or(false);
+        if excluded {
+            return None;
+        }
+
+        let language = attributes.language.or_else(|| self.detect_language(file))?;
+
+        match attributes.detectable {
+            Some(detectable) => detectable.then_some(language),
+            None if NON_PROGRAMMING_LANGUAGES.contains(&language.as_str()) => None,
+            None => Some(language),
+        }
+    }
+
+    // Vendored or generated code, by .gitattributes or Linguist's default paths
+    pub fn is_generated(&self, file: &FileInfo) -> bool {
+        let path = file.path.to_string_lossy();
+        let attributes = self.attributes(&path);
+        attributes
+            .vendored
+            .unwrap_or_else(|| self.is_vendored_path(&path))
+            || attributes.generated.unwrap_or(false)
+    }
+
+    fn is_vendored_path(&self, path: &str) -> bool {
+        self.vendored_paths.iter().any(|re| re.is_match(path))
+    }
+
+    // Later rules override earlier ones, attribute by attribute
+    fn attributes(&self, path: &str) -> Attributes {
+        let mut attributes = Attributes::default();
+        for rule in self.rules.iter().filter(|rule| {
+            rule.matcher.is_match(path) || self.matches_directory(&rule.matcher, path)
+        }) {
+            attributes.vendored = rule.vendored.or(attributes.vendored);
+            attributes.generated = rule.generated.or(attributes.generated);
+            attributes.documentation = rule.documentation.or(attributes.documentation);
+            attributes.detectable = rule.detectable.or(attributes.detectable);
+            attributes.language = rule.language.clone().or(attributes.language);
+        }
+        attributes
+    }
+
+    // A pattern naming a directory applies to everything below it
+    fn matches_directory(&self, matcher: &GlobMatcher, path: &str) -> bool {
+        path.match_indices('/')
+            .any(|(index, _)| matcher.is_match(&path[..index]))
+    }
+
+    // Content heuristics for extensions shared by several languages
+    fn detect_language(&self, file: &FileInfo) -> Option<String> {
+        let content = file.content_preview.as_deref().unwrap_or("");
+        let extension = file.extension.as_deref().map(|e| e.to_lowercase());
+
+        let language = match extension.as_deref() {
+            Some("h") => {
+                if content.contains("@interface") || content.contains("#import") {
+                    "Objective-C"
+                } else if ["class ", "namespace ", "template<", "template <", "std::"]
+                    .iter()
+                    .any(|marker| content.contains(marker))
+                {
+                    "C++"
+                } else {
+                    "C"
+                }
+            }
+            Some("m") => {
+                if content.contains("@interface")
+                    || content.contains("@implementation")
+                    || content.contains("#import")
+                {
+                    "Objective-C"
+                } else if content.lines().any(|line| {
+                    let line = line.trim_start();
+                    line.starts_with('%') || line.starts_with("function ")
+                }) {
+                    "MATLAB"
+                } else {
+                    "Objective-C"
+                }
+            }
+            Some("pl") => {
+                if content.contains(":-")
+                    && !content.contains("use strict")
+                    && !content.contains("my $")
+                {
+                    "Prolog"
+                } else {
+                    "Perl"
+                }
+            }
+            _ => return file.language.clone(),
+        };
+
+        Some(language.to_string())
+    }
+}
@@ -1,5 +1,40 @@
+pub mod api_docs;
 pub mod code_metrics;
+pub mod complexity;
+pub mod deployment;
+pub mod detections;
+pub mod docs_site;
+pub mod embedded;
+pub mod entry_points;
+pub mod environments;
+pub mod error_handling;
+pub mod feature_flags;
 pub mod filesystem;
+pub mod friendliness;
+pub mod imports;
+pub mod interesting_files;
+pub mod keywords;
+pub mod linguist;
+pub mod local_metadata;
+pub mod ownership;
+pub mod panic_density;
+pub mod performance;
+pub mod planning;
+pub mod platforms;
+pub mod prose;
+pub mod readme_summary;
+pub mod release_hygiene;
 pub mod repo;
+pub mod reproducibility;
+pub mod risk;
+pub mod rules;
+pub mod scan;
 pub mod security;
+pub mod spelling;
+pub mod sustainability;
+pub mod templates;
+pub mod testing_depth;
+pub mod toolchains;
+pub mod tooling;
 pub mod type_detector;
+pub mod unsafe_code;
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::file_index::{DirectoryView, FileIndex};
+use crate::types::{ConfigFile, GitAnalysis, OwnerActivity, OwnershipAnalysis, OwnershipRule};
+
+// CODEOWNERS locations in the order GitHub and GitLab look them up
+const CODEOWNERS_LOCATIONS: &[&str] = &[
+    ".github/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+];
+
+// Unowned paths reported at most
+const MAX_UNOWNED_PATHS: usize = 50;
+
+struct ParsedRule {
+    pattern: String,
+    owners: Vec<String>,
+    matchers: Vec<GlobMatcher>,
+}
+
+// Code ownership analyzer: maps CODEOWNERS rules onto the file tree
+pub struct OwnershipAnalyzer;
+
+impl OwnershipAnalyzer {
+    pub fn analyze_ownership(
+        &self,
+        file_structure: &FileIndex,
+        config_files: &[ConfigFile],
+        git_analysis: &GitAnalysis,
+    ) -> OwnershipAnalysis {
+        let Some(codeowners) = CODEOWNERS_LOCATIONS.iter().find_map(|location| {
+            config_files
+                .iter()
+                .find(|config| config.path.to_string_lossy() == *location)
+        }) else {
+            return OwnershipAnalysis::default();
+        };
+
+        let rules = self.parse_codeowners(&codeowners.content);
+
+        // The last matching rule wins, as in .gitignore
+        let mut matched_files = vec![0u32; rules.len()];
+        let mut file_owners: HashMap<PathBuf, Vec<String>> = HashMap::new();
+        for path in file_structure.files().iter().map(|file| &file.path) {
+            let path_str = path.to_string_lossy();
+            if let Some(index) = rules
+                .iter()
+                .rposition(|rule| rule.matchers.iter().any(|m| m.is_match(path_str.as_ref())))
+            {
+                matched_files[index] += 1;
+                // A rule without owners explicitly leaves the path unowned
+                if !rules[index].owners.is_empty() {
+                    file_owners.insert(path.clone(), rules[index].owners.clone());
+                }
+            }
+        }
+
+        let mut unowned_paths = Vec::new();
+        let root = file_structure.root();
+        self.collect_unowned(root, root.path(), &file_owners, &mut unowned_paths);
+        unowned_paths.truncate(MAX_UNOWNED_PATHS);
+
+        OwnershipAnalysis {
+            codeowners_path: Some(codeowners.path.clone()),
+            rules: rules
+                .iter()
+                .zip(matched_files)
+                .map(|(rule, matched_files)| OwnershipRule {
+                    pattern: rule.pattern.clone(),
+                    owners: rule.owners.clone(),
+                    matched_files,
+                })
+                .collect(),
+            owned_files: file_owners.len() as u32,
+            total_files: file_structure.files().len() as u32,
+            unowned_paths,
+            owners: self.owner_activity(&file_owners, git_analysis),
+        }
+    }
+
+    fn parse_codeowners(&self, content: &str) -> Vec<ParsedRule> {
+        let mut rules = Vec::new();
+
+        for line in content.lines() {
+            let line = line.split(" #").next().unwrap_or("").trim();
+            // Skip comments and GitLab section headers such as `[Docs]` or `^[Docs] @team`
+            if line.is_empty()
+                || line.starts_with('#')
+                || line.starts_with('[')
+                || line.starts_with("^[")
+            {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+
+            rules.push(ParsedRule {
+                pattern: pattern.to_string(),
+                owners: parts.map(|owner| owner.to_string()).collect(),
+                matchers: self.pattern_matchers(pattern),
+            });
+        }
+
+        rules
+    }
+
+    // gitignore-style semantics: patterns with a leading or inner slash are
+    // anchored at the root, others match at any depth, and a pattern naming
+    // a directory covers everything below it
+    fn pattern_matchers(&self, pattern: &str) -> Vec<GlobMatcher> {
+        let trimmed = pattern.trim_end_matches('/');
+        let anchored = pattern.starts_with('/') || trimmed.trim_start_matches('/').contains('/');
+        let base = trimmed.trim_start_matches('/');
+        let base = if anchored {
+            base.to_string()
+        } else {
+            format!("**/{}", base)
+        };
+
+        let mut globs = vec![format!("{}/**", base)];
+        if !pattern.ends_with('/') {
+            globs.push(base);
+        }
+
+        globs
+            .iter()
+            .filter_map(|glob| {
+                GlobBuilder::new(glob)
+                    .literal_separator(true)
+                    .build()
+                    .ok()
+                    .map(|g| g.compile_matcher())
+            })
+            .collect()
+    }
+
+    // Directories without any owned file are reported as a whole instead of file by file.
+    // Returns whether the directory contains at least one owned file.
+    fn collect_unowned(
+        &self,
+        dir: DirectoryView<'_>,
+        root: &Path,
+        file_owners: &HashMap<PathBuf, Vec<String>>,
+        unowned: &mut Vec<PathBuf>,
+    ) -> bool {
+        let has_owned = dir
+            .all_files()
+            .iter()
+            .any(|file| file_owners.contains_key(&file.path));
+
+        if !has_owned && dir.path() != root {
+            let relative = dir.path().strip_prefix(root).unwrap_or(dir.path());
+            unowned.push(crate::utils::to_slash_path(relative));
+            return false;
+        }
+
+        for file in dir.files() {
+            if !file_owners.contains_key(&file.path) {
+                unowned.push(file.path.clone());
+            }
+        }
+        for subdir in dir.subdirectories() {
+            self.collect_unowned(subdir, root, file_owners, unowned);
+        }
+
+        has_owned
+    }
+
+    fn owner_activity(
+        &self,
+        file_owners: &HashMap<PathBuf, Vec<String>>,
+        git_analysis: &GitAnalysis,
+    ) -> Vec<OwnerActivity> {
+        let mut owned_files: HashMap<&str, u32> = HashMap::new();
+        for owners in file_owners.values() {
+            for owner in owners {
+                *owned_files.entry(owner.as_str()).or_insert(0) += 1;
+            }
+        }
+
+        let mut activity: Vec<OwnerActivity> = owned_files
+            .into_iter()
+            .map(|(owner, owned_files)| {
+                // @user and user@example.com resolve to a login or author name, teams can't
+                let handle = owner.trim_start_matches('@');
+                let is_team = handle.contains('/');
+                let name = handle.split('@').next().unwrap_or(handle).to_lowercase();
+
+                let contributions = git_analysis
+                    .contributors
+                    .iter()
+                    .find(|user| !is_team && user.login.to_lowercase() == name)
+                    .and_then(|user| user.contributions);
+                let commits: Vec<_> = git_analysis
+                    .recent_commits
+                    .iter()
+                    .filter(|commit| !is_team && commit.author.login.to_lowercase() == name)
+                    .collect();
+
+                OwnerActivity {
+                    owner: owner.to_string(),
+                    is_team,
+                    owned_files,
+                    contributions,
+                    recent_commits: commits.len() as u32,
+                    last_commit_at: commits.iter().map(|commit| commit.date).max(),
+                }
+            })
+            .collect();
+
+        activity.sort_by_key(|owner| std::cmp::Reverse(owner.owned_files));
+        activity
+    }
+}
@@ -0,0 +1,135 @@
+use regex::Regex;
+
+use crate::analyzers::scan::{FileClassifier, ScannedFile, strip_comments};
+use crate::types::{PanicDensity, PanicHotspot};
+use crate::utils::is_test_file;
+
+// Panicking calls per KLOC at which the hygiene score is halved
+const HALF_SCORE_DENSITY: f64 = 5.0;
+
+// Files listed as the places to start tightening error handling
+const MAX_HOTSPOTS: usize = 10;
+
+// Counts unwrap()/expect()/panic!/todo! in non-test Rust code during the shared walk
+pub struct PanicDensityClassifier {
+    patterns: PanicPatterns,
+    result: PanicDensity,
+    hotspots: Vec<PanicHotspot>,
+}
+
+struct PanicPatterns {
+    unwrap: Regex,
+    expect: Regex,
+    panic: Regex,
+    todo: Regex,
+    test_module: Regex,
+}
+
+impl Default for PanicDensityClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PanicDensityClassifier {
+    pub fn new() -> Self {
+        Self {
+            patterns: PanicPatterns {
+                unwrap: Regex::new(r"\.\s*unwrap\s*\(\s*\)").unwrap(),
+                expect: Regex::new(r"\.\s*expect\s*\(").unwrap(),
+                panic: Regex::new(r"\bpanic!\s*[(\[{]").unwrap(),
+                todo: Regex::new(r"\b(?:todo|unimplemented)!\s*[(\[{]").unwrap(),
+                test_module: Regex::new(
+                    r"#\[\s*cfg\s*\(\s*test\s*\)\s*\]\s*(?:pub(?:\s*\([^)]*\))?\s+)?mod\s+\w+\s*\{",
+                )
+                .unwrap(),
+            },
+            result: PanicDensity::default(),
+            hotspots: Vec::new(),
+        }
+    }
+
+    // None when no Rust code outside tests was found
+    pub fn finish(mut self) -> Option<PanicDensity> {
+        if self.result.rust_loc == 0 {
+            return None;
+        }
+
+        let density = &mut self.result;
+        let total = density.unwraps + density.expects + density.panics + density.todos;
+        density.per_kloc = total as f64 * 1000.0 / density.rust_loc as f64;
+        density.score = 100.0 / (1.0 + density.per_kloc / HALF_SCORE_DENSITY);
+
+        self.hotspots
+            .sort_by(|a, b| b.count.cmp(&a.count).then(a.path.cmp(&b.path)));
+        self.hotspots.truncate(MAX_HOTSPOTS);
+        self.result.hotspots = self.hotspots;
+        Some(self.result)
+    }
+
+    fn add_source(&mut self, file: &ScannedFile<'_>, content: &str) {
+        let code = self.without_test_modules(&strip_comments(content));
+        let patterns = &self.patterns;
+        let count = |regex: &Regex| regex.find_iter(&code).count() as u32;
+
+        let unwraps = count(&patterns.unwrap);
+        let expects = count(&patterns.expect);
+        let panics = count(&patterns.panic);
+        let todos = count(&patterns.todo);
+
+        let density = &mut self.result;
+        density.rust_loc += code.lines().filter(|line| !line.trim().is_empty()).count() as u32;
+        density.unwraps += unwraps;
+        density.expects += expects;
+        density.panics += panics;
+        density.todos += todos;
+
+        let total = unwraps + expects + panics + todos;
+        if total > 0 {
+            self.hotspots.push(PanicHotspot {
+                path: file.relative.to_path_buf(),
+                count: total,
+                url: None,
+            });
+        }
+    }
+
+    // Drops #[cfg(test)] mod blocks, the usual home of unit tests. Braces are
+    // balanced reliably once comments and strings are gone
+    fn without_test_modules(&self, code: &str) -> String {
+        let mut kept = String::with_capacity(code.len());
+        let mut rest = code;
+
+        while let Some(found) = self.patterns.test_module.find(rest) {
+            kept.push_str(&rest[..found.start()]);
+            let mut depth = 1;
+            let mut end = rest.len();
+            for (offset, c) in rest[found.end()..].char_indices() {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    end = found.end() + offset + 1;
+                    break;
+                }
+            }
+            rest = &rest[end..];
+        }
+
+        kept.push_str(rest);
+        kept
+    }
+}
+
+impl FileClassifier for PanicDensityClassifier {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        if !file.in_structure || !file.file_name().ends_with(".rs") || is_test_file(file.relative) {
+            return;
+        }
+        if let Some(content) = file.content() {
+            self.add_source(file, content);
+        }
+    }
+}
@@ -0,0 +1,347 @@
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::analyzers::scan::{FileClassifier, ScannedFile};
+use crate::types::{EvidenceRef, PerformanceCulture};
+use crate::utils::{distinct_labels, first_line};
+
+// Directories benchmark suites are kept in
+const BENCHMARK_DIRS: &[&str] = &["benches", "benchmarks", "benchmark", "bench"];
+
+// Benchmark harnesses by source extension, as (marker, framework)
+const BENCHMARK_MARKERS: &[(&[&str], &str, &str)] = &[
+    (&["rs"], "criterion_group!", "criterion"),
+    (&["rs"], "divan::bench", "divan"),
+    (&["rs"], "iai_callgrind", "iai-callgrind"),
+    (&["rs"], "iai::main", "iai"),
+    (&["rs"], "#[bench]", "libtest bench"),
+    (&["java", "kt"], "org.openjdk.jmh", "JMH"),
+    (&["cs"], "BenchmarkDotNet", "BenchmarkDotNet"),
+    (
+        &["c", "cc", "cpp", "cxx", "h", "hpp"],
+        "benchmark/benchmark.h",
+        "Google Benchmark",
+    ),
+    (&["py"], "pytest_benchmark", "pytest-benchmark"),
+    (&["js", "mjs", "ts", "mts"], "tinybench", "tinybench"),
+    (&["js", "mjs", "ts", "mts"], "mitata", "mitata"),
+    (
+        &["js", "mjs", "ts", "mts"],
+        "require('benchmark')",
+        "benchmark.js",
+    ),
+    (
+        &["js", "mjs", "ts", "mts"],
+        "from 'benchmark'",
+        "benchmark.js",
+    ),
+];
+
+// Workflow content markers of performance jobs, as (marker, tool)
+const CI_MARKERS: &[(&str, &str)] = &[
+    (
+        "benchmark-action/github-action-benchmark",
+        "github-action-benchmark",
+    ),
+    ("codspeedhq/action", "CodSpeed"),
+    ("bencherdev/bencher", "Bencher"),
+    ("cargo bench", "cargo bench"),
+    ("cargo criterion", "cargo criterion"),
+    ("cargo codspeed", "CodSpeed"),
+    ("-bench=", "go test -bench"),
+    ("-bench ", "go test -bench"),
+    ("--benchmark-", "pytest-benchmark"),
+    ("asv run", "asv"),
+    ("asv continuous", "asv"),
+    ("hyperfine", "hyperfine"),
+    ("vitest bench", "vitest bench"),
+    ("run bench", "bench script"),
+];
+
+// Profiling crates, tools and build profiles, as (marker, tool). Looked for
+// in manifests, build scripts and Go sources
+const PROFILING_MARKERS: &[(&str, &str)] = &[
+    ("[profile.profiling]", "profiling build profile"),
+    ("[profile.bench]", "bench build profile"),
+    ("force-frame-pointers", "frame pointers"),
+    ("pprof", "pprof"),
+    ("flamegraph", "flamegraph"),
+    ("dhat", "dhat"),
+    ("tracy-client", "Tracy"),
+    ("tracing-tracy", "Tracy"),
+    ("puffin", "puffin"),
+    ("py-spy", "py-spy"),
+    ("pyinstrument", "pyinstrument"),
+    ("scalene", "scalene"),
+    ("memray", "memray"),
+    ("@clinic/", "clinic.js"),
+    ("clinic flame", "clinic.js"),
+    ("clinic doctor", "clinic.js"),
+    ("perf record", "perf"),
+    ("valgrind", "valgrind"),
+    ("heaptrack", "heaptrack"),
+    ("samply", "samply"),
+];
+
+// Files the profiling markers are looked for in
+const PROFILING_FILES: &[&str] = &[
+    "cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "requirements.txt",
+    "requirements-dev.txt",
+    "go.mod",
+    "makefile",
+    "justfile",
+];
+
+// Benchmark suites, performance CI jobs and profiling setups found during the
+// shared walk, to tell whether the project measures its performance
+pub struct PerformanceClassifier {
+    go_benchmark: Regex,
+    pytest_benchmark: Regex,
+    culture: PerformanceCulture,
+}
+
+impl Default for PerformanceClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PerformanceClassifier {
+    pub fn new() -> Self {
+        Self {
+            go_benchmark: Regex::new(r"func Benchmark\w*\(\s*\w+\s+\*testing\.B\s*\)").unwrap(),
+            pytest_benchmark: Regex::new(r"def test_\w+\([^)]*\bbenchmark\b").unwrap(),
+            culture: PerformanceCulture::default(),
+        }
+    }
+
+    pub fn finish(mut self) -> PerformanceCulture {
+        let culture = &mut self.culture;
+        culture.benchmark_dirs.sort();
+        culture.benchmark_dirs.dedup();
+        for refs in [
+            &mut culture.benchmarks,
+            &mut culture.ci_jobs,
+            &mut culture.profiling,
+        ] {
+            refs.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.label.cmp(&b.label)));
+        }
+        // One entry per profiling tool, the first file naming it
+        let mut tools: Vec<String> = Vec::new();
+        culture.profiling.retain(|evidence| {
+            let first = !tools.contains(&evidence.label);
+            if first {
+                tools.push(evidence.label.clone());
+            }
+            first
+        });
+
+        culture.measures_performance =
+            !culture.benchmarks.is_empty() || !culture.ci_jobs.is_empty();
+        culture.signals = signals(culture);
+        self.culture
+    }
+
+    fn detect_benchmark(&mut self, file: &ScannedFile<'_>, extension: &str) {
+        let name = file.file_name();
+        let is_go_test = extension == "go" && name.ends_with("_test.go");
+        let candidates: Vec<&(&[&str], &str, &str)> = BENCHMARK_MARKERS
+            .iter()
+            .filter(|(extensions, _, _)| extensions.contains(&extension))
+            .collect();
+        // vitest benchmarks are *.bench.ts files calling bench()
+        let is_vitest_bench =
+            name.contains(".bench.") && ["js", "mjs", "ts", "mts"].contains(&extension);
+        if !is_go_test && extension != "py" && candidates.is_empty() {
+            return;
+        }
+        let Some(content) = file.content() else {
+            return;
+        };
+
+        let found = if is_go_test {
+            first_line(content, |line| self.go_benchmark.is_match(line))
+                .map(|line| ("go test -bench", Some(line)))
+        } else if is_vitest_bench && content.contains("vitest") {
+            first_line(content, |line| line.contains("bench("))
+                .map(|line| ("vitest bench", Some(line)))
+        } else {
+            candidates
+                .iter()
+                .find(|(_, marker, _)| content.contains(marker))
+                .map(|(_, marker, framework)| {
+                    (
+                        *framework,
+                        first_line(content, |line| line.contains(marker)),
+                    )
+                })
+                .or_else(|| {
+                    if extension != "py" {
+                        return None;
+                    }
+                    // The pytest fixture, or asv suites of plain time_* functions
+                    // in a benchmark directory
+                    first_line(content, |line| self.pytest_benchmark.is_match(line))
+                        .map(|line| ("pytest-benchmark", Some(line)))
+                        .or_else(|| {
+                            benchmark_dir(file.relative)?;
+                            first_line(content, |line| line.trim_start().starts_with("def time_"))
+                                .map(|line| ("asv", Some(line)))
+                        })
+                })
+        };
+        if let Some((framework, line)) = found {
+            self.culture
+                .benchmarks
+                .push(evidence(framework, file.relative, line));
+        }
+    }
+
+    fn detect_ci_jobs(&mut self, file: &ScannedFile<'_>, content: &str) {
+        let content = content.to_lowercase();
+        let mut found: Vec<&str> = Vec::new();
+        for (marker, tool) in CI_MARKERS {
+            if found.contains(tool) {
+                continue;
+            }
+            if let Some(line) = first_line(&content, |line| line.contains(marker)) {
+                found.push(tool);
+                self.culture
+                    .ci_jobs
+                    .push(evidence(tool, file.relative, Some(line)));
+            }
+        }
+    }
+
+    fn detect_profiling(&mut self, file: &ScannedFile<'_>, content: &str) {
+        let content = content.to_lowercase();
+        for (marker, tool) in PROFILING_MARKERS {
+            if let Some(line) = first_line(&content, |line| line.contains(marker)) {
+                self.culture
+                    .profiling
+                    .push(evidence(tool, file.relative, Some(line)));
+            }
+        }
+    }
+}
+
+impl FileClassifier for PerformanceClassifier {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        let relative = file.relative;
+        let name = file.file_name().to_lowercase();
+        let extension = Path::new(&name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_string();
+
+        if relative.parent() == Some(Path::new(".github/workflows")) {
+            if let Some(content) = file.content() {
+                self.detect_ci_jobs(file, content);
+            }
+            return;
+        }
+        if !file.in_structure && !relative.starts_with(".cargo") {
+            return;
+        }
+
+        if let Some(dir) = benchmark_dir(relative) {
+            self.culture.benchmark_dirs.push(dir);
+        }
+        if name == "asv.conf.json" {
+            self.culture
+                .benchmarks
+                .push(evidence("asv", relative, None));
+            return;
+        }
+        // Cargo's config.toml, where frame pointers are forced for profiling
+        let is_cargo_config = relative.starts_with(".cargo") && name.starts_with("config");
+        if extension == "go" {
+            // Go programs profile through net/http/pprof or runtime/pprof
+            if let Some(line) = file
+                .content()
+                .and_then(|content| first_line(content, |line| line.contains("/pprof\"")))
+            {
+                self.culture
+                    .profiling
+                    .push(evidence("pprof", relative, Some(line)));
+            }
+        } else if (PROFILING_FILES.contains(&name.as_str()) || is_cargo_config)
+            && let Some(content) = file.content()
+        {
+            self.detect_profiling(file, content);
+        }
+        self.detect_benchmark(file, &extension);
+    }
+}
+
+// The benchmark directory a file is under, up to and including it
+fn benchmark_dir(relative: &Path) -> Option<PathBuf> {
+    let mut dir = PathBuf::new();
+    let parent = relative.parent()?;
+    for component in parent.components() {
+        dir.push(component);
+        let name = component.as_os_str().to_string_lossy().to_lowercase();
+        if BENCHMARK_DIRS.contains(&name.as_str()) {
+            return Some(dir);
+        }
+    }
+    None
+}
+
+// What the evidence says about how performance is measured
+fn signals(culture: &PerformanceCulture) -> Vec<String> {
+    let mut signals = Vec::new();
+    if !culture.benchmarks.is_empty() {
+        signals.push(format!(
+            "{} benchmark files use {}.",
+            culture.benchmarks.len(),
+            distinct_labels(&culture.benchmarks).join(", ")
+        ));
+    } else if !culture.benchmark_dirs.is_empty() {
+        signals.push(format!(
+            "Benchmark directories exist ({}) but no known harness was found in them.",
+            culture
+                .benchmark_dirs
+                .iter()
+                .map(|dir| dir.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    if culture.ci_jobs.is_empty() {
+        if !culture.benchmarks.is_empty() {
+            signals
+                .push("Benchmarks are not run in CI, regressions are caught by hand.".to_string());
+        }
+    } else {
+        signals.push(format!(
+            "CI runs performance jobs with {}.",
+            distinct_labels(&culture.ci_jobs).join(", ")
+        ));
+    }
+    if !culture.profiling.is_empty() {
+        signals.push(format!(
+            "Profiling is set up with {}.",
+            distinct_labels(&culture.profiling).join(", ")
+        ));
+    }
+    if signals.is_empty() {
+        signals
+            .push("No benchmarks, performance CI jobs or profiling setup were found.".to_string());
+    }
+    signals
+}
+
+fn evidence(label: &str, path: &Path, line: Option<u32>) -> EvidenceRef {
+    EvidenceRef {
+        label: label.to_string(),
+        path: path.to_path_buf(),
+        line,
+        url: None,
+    }
+}
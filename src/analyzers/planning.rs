@@ -0,0 +1,134 @@
+use chrono::{Duration, Utc};
+
+use crate::types::{DocumentationFile, MilestoneProgress, Planning, RemoteRepositoryData};
+
+// Milestones and boards left untouched for longer are not a live roadmap
+const STALE_DAYS: i64 = 180;
+
+// Roadmap work tracked in the open: milestones with their progress, public
+// Projects v2 boards and a ROADMAP document
+pub struct PlanningAnalyzer;
+
+impl PlanningAnalyzer {
+    pub fn analyze(
+        &self,
+        remote: &RemoteRepositoryData,
+        documentation: &[DocumentationFile],
+    ) -> Planning {
+        let now = Utc::now();
+        let is_recent = |at| now - at <= Duration::days(STALE_DAYS);
+
+        let mut milestones: Vec<MilestoneProgress> = remote
+            .milestones
+            .iter()
+            .map(|m| {
+                let issues = m.open_issues + m.closed_issues;
+                MilestoneProgress {
+                    title: m.title.clone(),
+                    state: m.state.clone(),
+                    due_on: m.due_on,
+                    open_issues: m.open_issues,
+                    closed_issues: m.closed_issues,
+                    completion: if issues > 0 {
+                        m.closed_issues as f64 / issues as f64
+                    } else {
+                        0.0
+                    },
+                    overdue: m.state == "open" && m.due_on.is_some_and(|due| due < now),
+                }
+            })
+            .collect();
+        // Open first, soonest due first, undated last
+        milestones.sort_by(|a, b| {
+            (a.state != "open")
+                .cmp(&(b.state != "open"))
+                .then_with(|| a.due_on.is_none().cmp(&b.due_on.is_none()))
+                .then_with(|| a.due_on.cmp(&b.due_on))
+        });
+        let open_milestones = milestones.iter().filter(|m| m.state == "open").count() as u32;
+        let closed_milestones = milestones.len() as u32 - open_milestones;
+        let overdue_milestones = milestones.iter().filter(|m| m.overdue).count() as u32;
+
+        let roadmap_files: Vec<_> = documentation
+            .iter()
+            .filter(|doc| doc.file_type == "roadmap")
+            .map(|doc| doc.path.clone())
+            .collect();
+
+        let mut signals = Vec::new();
+        let active_milestones = remote
+            .milestones
+            .iter()
+            .filter(|m| m.state == "open")
+            .filter(|m| m.due_on.is_some_and(|due| due >= now) || is_recent(m.updated_at))
+            .count();
+        if active_milestones > 0 {
+            let next_due = milestones
+                .iter()
+                .filter(|m| m.state == "open")
+                .filter_map(|m| m.due_on)
+                .find(|due| *due >= now);
+            signals.push(match next_due {
+                Some(due) => format!(
+                    "{} open milestones are in use, the next one due {}.",
+                    active_milestones,
+                    due.format("%Y-%m-%d")
+                ),
+                None => format!("{} open milestones are in use.", active_milestones),
+            });
+        }
+        if overdue_milestones > 0 {
+            signals.push(format!(
+                "{} open milestones are past their due date.",
+                overdue_milestones
+            ));
+        }
+
+        let public_boards = remote
+            .project_boards
+            .iter()
+            .flatten()
+            .filter(|board| board.public && !board.closed && board.items > 0)
+            .filter(|board| is_recent(board.updated_at))
+            .count();
+        if public_boards > 0 {
+            signals.push(format!(
+                "{} public project boards are kept up to date.",
+                public_boards
+            ));
+        }
+        for path in &roadmap_files {
+            signals.push(format!(
+                "The roadmap is written down in {}.",
+                path.display()
+            ));
+        }
+
+        let public_roadmap =
+            active_milestones > 0 || public_boards > 0 || !roadmap_files.is_empty();
+        if !public_roadmap {
+            signals.push(
+                match remote.project_boards {
+                    Some(_) => {
+                        "No open milestones, public project boards or roadmap document were found."
+                    }
+                    None => {
+                        "No open milestones or roadmap document were found, project boards need a token to be read."
+                    }
+                }
+                .to_string(),
+            );
+        }
+
+        Planning {
+            milestones,
+            open_milestones,
+            closed_milestones,
+            overdue_milestones,
+            project_boards: remote.project_boards.clone(),
+            roadmap_files,
+            public_roadmap,
+            signals,
+        }
+    }
+}
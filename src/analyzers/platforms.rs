@@ -0,0 +1,312 @@
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::analyzers::scan::{FileClassifier, ScannedFile};
+use crate::types::{EvidenceRef, GitHubRelease, PlatformSignals, PlatformSupport};
+use crate::utils::first_line;
+
+// Platforms of the support matrix, operating systems then architectures
+const PLATFORMS: &[&str] = &["Linux", "macOS", "Windows", "WASM", "ARM"];
+
+// Lowercase workflow tokens of runners and build targets, as (token, platform)
+const CI_TOKENS: &[(&str, &str)] = &[
+    ("ubuntu", "Linux"),
+    ("linux", "Linux"),
+    ("macos", "macOS"),
+    ("apple-darwin", "macOS"),
+    ("windows", "Windows"),
+    ("wasm32", "WASM"),
+    ("wasm-pack", "WASM"),
+    ("wasi", "WASM"),
+    ("emscripten", "WASM"),
+    ("aarch64", "ARM"),
+    ("arm64", "ARM"),
+    ("armv7", "ARM"),
+    ("ubuntu-24.04-arm", "ARM"),
+    ("ubuntu-22.04-arm", "ARM"),
+    ("macos-14", "ARM"),
+    ("macos-15", "ARM"),
+    ("macos-latest", "ARM"), // Apple silicon runners
+];
+
+// Lowercase release asset name tokens, as (token, platform). "win" alone
+// would match darwin
+const ASSET_TOKENS: &[(&str, &str)] = &[
+    ("linux", "Linux"),
+    ("musl", "Linux"),
+    (".deb", "Linux"),
+    (".rpm", "Linux"),
+    (".appimage", "Linux"),
+    ("darwin", "macOS"),
+    ("macos", "macOS"),
+    ("osx", "macOS"),
+    (".dmg", "macOS"),
+    ("windows", "Windows"),
+    ("win64", "Windows"),
+    ("win32", "Windows"),
+    (".exe", "Windows"),
+    (".msi", "Windows"),
+    ("wasm", "WASM"),
+    ("aarch64", "ARM"),
+    ("arm64", "ARM"),
+    ("armv7", "ARM"),
+    ("armhf", "ARM"),
+];
+
+// Go file name suffixes and build tags, as (token, platform)
+const GO_TOKENS: &[(&str, &str)] = &[
+    ("linux", "Linux"),
+    ("darwin", "macOS"),
+    ("windows", "Windows"),
+    ("wasm", "WASM"),
+    ("js", "WASM"),
+    ("arm64", "ARM"),
+    ("arm", "ARM"),
+];
+
+// C preprocessor macros, as (macro, platform)
+const C_MACROS: &[(&str, &str)] = &[
+    ("__linux__", "Linux"),
+    ("__APPLE__", "macOS"),
+    ("_WIN32", "Windows"),
+    ("_WIN64", "Windows"),
+    ("__EMSCRIPTEN__", "WASM"),
+    ("__wasm__", "WASM"),
+    ("__aarch64__", "ARM"),
+    ("__arm__", "ARM"),
+    ("_M_ARM64", "ARM"),
+];
+
+// Files shown per platform as examples of target-specific code
+const CODE_EXAMPLES: usize = 3;
+
+// Collects the CI runners and targets and the target-specific code of each
+// platform during the shared walk, release artifacts are added afterwards
+pub struct PlatformClassifier {
+    rust_cfg: Regex,
+    go_build: Regex,
+    python_platform: Regex,
+    support: PlatformSupport,
+}
+
+impl Default for PlatformClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlatformClassifier {
+    pub fn new() -> Self {
+        Self {
+            // Targets named on a line using cfg
+            rust_cfg: Regex::new(
+                r#"(?:\b(windows)\b|target_os\s*=\s*"(linux|macos|windows)"|target_(?:arch|family)\s*=\s*"(wasm32|wasm64|wasm|aarch64|arm)")"#,
+            )
+            .unwrap(),
+            go_build: Regex::new(r"^//\s*(?:go:build|\+build)\s+(.*)").unwrap(),
+            python_platform: Regex::new(
+                r#"(?:sys\.platform|platform\.system\(\)|os\.name)\s*(?:==|!=|\.startswith\()\s*["'](?i:(win32|windows|nt|darwin|linux))"#,
+            )
+            .unwrap(),
+            support: PlatformSupport {
+                platforms: PLATFORMS
+                    .iter()
+                    .map(|platform| PlatformSignals {
+                        platform: platform.to_string(),
+                        ..Default::default()
+                    })
+                    .collect(),
+            },
+        }
+    }
+
+    pub fn finish(mut self) -> PlatformSupport {
+        for signals in &mut self.support.platforms {
+            signals.ci.sort_by(|a, b| a.path.cmp(&b.path));
+            signals.code.sort_by(|a, b| a.path.cmp(&b.path));
+            signals.code.truncate(CODE_EXAMPLES);
+        }
+        self.support
+    }
+
+    fn platform(&mut self, platform: &str) -> &mut PlatformSignals {
+        let index = PLATFORMS.iter().position(|p| *p == platform).unwrap();
+        &mut self.support.platforms[index]
+    }
+
+    fn detect_ci(&mut self, file: &ScannedFile<'_>, content: &str) {
+        let content = content.to_lowercase();
+        for platform in PLATFORMS {
+            let line = first_line(&content, |line| {
+                let line = line.trim_start();
+                !line.starts_with('#')
+                    && CI_TOKENS
+                        .iter()
+                        .any(|(token, p)| p == platform && line.contains(token))
+            });
+            if let Some(line) = line {
+                self.platform(platform)
+                    .ci
+                    .push(evidence(platform, file.relative, Some(line)));
+            }
+        }
+    }
+
+    // Platforms the file has code specific to, with the first line of each
+    fn detect_code(&self, file: &ScannedFile<'_>, extension: &str) -> Vec<(&'static str, u32)> {
+        let mut found = Vec::new();
+        if extension == "go" {
+            // foo_linux.go, foo_linux_arm64.go
+            let stem = file.file_name().trim_end_matches(".go");
+            for part in stem.trim_end_matches("_test").split('_').skip(1) {
+                if let Some(platform) = go_platform(part) {
+                    add(&mut found, platform, 1);
+                }
+            }
+        }
+        let Some(content) = file.content() else {
+            return found;
+        };
+
+        match extension {
+            "go" => {
+                for (index, line) in content.lines().enumerate() {
+                    if line.starts_with("package ") {
+                        break;
+                    }
+                    let Some(captures) = self.go_build.captures(line) else {
+                        continue;
+                    };
+                    for token in captures[1].split(|c: char| !c.is_alphanumeric()) {
+                        if let Some(platform) = go_platform(token) {
+                            add(&mut found, platform, index as u32 + 1);
+                        }
+                    }
+                }
+            }
+            "rs" => {
+                for (index, line) in content.lines().enumerate() {
+                    if !line.contains("cfg") {
+                        continue;
+                    }
+                    for captures in self.rust_cfg.captures_iter(line) {
+                        let platform = match captures
+                            .get(1)
+                            .or(captures.get(2))
+                            .or(captures.get(3))
+                            .map(|m| m.as_str())
+                        {
+                            Some("windows") => "Windows",
+                            Some("linux") => "Linux",
+                            Some("macos") => "macOS",
+                            Some("aarch64" | "arm") => "ARM",
+                            _ => "WASM",
+                        };
+                        add(&mut found, platform, index as u32 + 1);
+                    }
+                }
+            }
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "m" | "mm" => {
+                for (index, line) in content.lines().enumerate() {
+                    let line = line.trim_start();
+                    if !line.starts_with("#if") && !line.starts_with("#elif") {
+                        continue;
+                    }
+                    for (name, platform) in C_MACROS {
+                        if line.contains(name) {
+                            add(&mut found, platform, index as u32 + 1);
+                        }
+                    }
+                }
+            }
+            "py" => {
+                for (index, line) in content.lines().enumerate() {
+                    if let Some(captures) = self.python_platform.captures(line) {
+                        let platform = match captures[1].to_lowercase().as_str() {
+                            "darwin" => "macOS",
+                            "linux" => "Linux",
+                            _ => "Windows",
+                        };
+                        add(&mut found, platform, index as u32 + 1);
+                    }
+                }
+            }
+            _ => {}
+        }
+        found
+    }
+}
+
+impl FileClassifier for PlatformClassifier {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        if file.relative.parent() == Some(Path::new(".github/workflows")) {
+            if let Some(content) = file.content() {
+                self.detect_ci(file, content);
+            }
+            return;
+        }
+        if !file.in_structure {
+            return;
+        }
+        let extension = Path::new(file.file_name())
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        for (platform, line) in self.detect_code(file, &extension) {
+            let signals = self.platform(platform);
+            signals.code_files += 1;
+            signals
+                .code
+                .push(evidence(platform, file.relative, Some(line)));
+        }
+    }
+}
+
+// Adds the artifacts of the latest release that ships any, then keeps the
+// platforms with at least one signal
+pub fn add_release_assets(support: &mut PlatformSupport, releases: &[GitHubRelease]) {
+    if let Some(release) = releases
+        .iter()
+        .find(|release| !release.draft && !release.assets.is_empty())
+    {
+        for asset in &release.assets {
+            let lowercase = asset.name.to_lowercase();
+            for signals in &mut support.platforms {
+                let matches = ASSET_TOKENS.iter().any(|(token, platform)| {
+                    *platform == signals.platform && lowercase.contains(token)
+                });
+                if matches {
+                    signals.release_assets.push(asset.name.clone());
+                }
+            }
+        }
+    }
+    support.platforms.retain(|signals| {
+        !signals.ci.is_empty() || signals.code_files > 0 || !signals.release_assets.is_empty()
+    });
+}
+
+// The first line of each platform only
+fn add(found: &mut Vec<(&'static str, u32)>, platform: &'static str, line: u32) {
+    if !found.iter().any(|(p, _)| *p == platform) {
+        found.push((platform, line));
+    }
+}
+
+fn go_platform(token: &str) -> Option<&'static str> {
+    GO_TOKENS
+        .iter()
+        .find(|(t, _)| *t == token)
+        .map(|(_, platform)| *platform)
+}
+
+fn evidence(label: &str, path: &Path, line: Option<u32>) -> EvidenceRef {
+    EvidenceRef {
+        label: label.to_string(),
+        path: path.to_path_buf(),
+        line,
+        url: None,
+    }
+}
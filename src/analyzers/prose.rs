@@ -0,0 +1,247 @@
+use regex::Regex;
+
+use crate::analyzers::scan::{FileClassifier, ScannedFile};
+use crate::analyzers::spelling::{DocLine, doc_lines, is_doc_file};
+use crate::types::DocsProse;
+
+const MAX_FILES: u32 = 200;
+
+// Fewer words give readability scores that swing with every sentence
+const MIN_SCORED_WORDS: u32 = 100;
+
+// Flesch reading ease bands, from the hardest
+const READABILITY: &[(f64, &str)] = &[
+    (30.0, "very difficult"),
+    (50.0, "difficult"),
+    (60.0, "fairly difficult"),
+    (70.0, "plain English"),
+    (80.0, "fairly easy"),
+    (f64::INFINITY, "easy"),
+];
+
+// Abbreviations whose period doesn't end the sentence
+const ABBREVIATIONS: &[&str] = &["e.g.", "i.e.", "vs.", "cf."];
+
+// Measures the prose of the same README and docs files as the spelling
+// check during the shared walk: words, sentences, syllables for the Flesch
+// scores, sections between headings, and lines of code blocks
+pub struct ProseClassifier {
+    inline_noise: Regex,
+    list_marker: Regex,
+    prose: DocsProse,
+    syllable_count: u64,
+    sentence_words: u32, // Of the sentence being read
+    section_words: u32,  // Since the last heading
+}
+
+impl Default for ProseClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProseClassifier {
+    pub fn new() -> Self {
+        Self {
+            // Images, inline code, URLs, link targets and HTML tags
+            inline_noise: Regex::new(
+                r"!\[[^\]]*\]\([^)]*\)|`[^`]*`|https?://\S+|\]\([^)]*\)|<[^>]+>",
+            )
+            .unwrap(),
+            list_marker: Regex::new(r"^\s*([-*+>]|\d+[.)])\s").unwrap(),
+            prose: DocsProse::default(),
+            syllable_count: 0,
+            sentence_words: 0,
+            section_words: 0,
+        }
+    }
+
+    pub fn finish(mut self) -> DocsProse {
+        let prose = &mut self.prose;
+        if prose.word_count > 0 {
+            let words = prose.word_count as f64;
+            let sentences = prose.sentence_count.max(1) as f64;
+            let syllables_per_word = self.syllable_count as f64 / words;
+            prose.avg_word_length = prose.character_count as f64 / words;
+            prose.avg_sentence_words = words / sentences;
+            prose.avg_section_words = words / prose.section_count.max(1) as f64;
+            if prose.word_count >= MIN_SCORED_WORDS {
+                prose.flesch_reading_ease =
+                    Some(206.835 - 1.015 * prose.avg_sentence_words - 84.6 * syllables_per_word);
+                prose.flesch_kincaid_grade =
+                    Some(0.39 * prose.avg_sentence_words + 11.8 * syllables_per_word - 15.59);
+            }
+        }
+        if prose.prose_lines > 0 {
+            prose.code_to_prose_ratio = prose.code_lines as f64 / prose.prose_lines as f64;
+        }
+        prose.signals = signals(prose);
+        self.prose
+    }
+
+    fn check(&mut self, content: &str) {
+        self.prose.files_checked += 1;
+        let lines = doc_lines(content);
+        for (index, (_, line)) in lines.iter().enumerate() {
+            let text = match line {
+                DocLine::Prose(text) => text.trim(),
+                DocLine::Code => {
+                    self.prose.code_lines += 1;
+                    self.end_sentence();
+                    continue;
+                }
+                DocLine::Blank => {
+                    self.end_sentence();
+                    continue;
+                }
+            };
+
+            // ATX headings, and Setext or reStructuredText titles over their
+            // underline
+            let underline = is_underline(text);
+            let underlined = matches!(
+                lines.get(index + 1),
+                Some((_, DocLine::Prose(next))) if is_underline(next.trim())
+            );
+            if underline || underlined || text.starts_with('#') {
+                self.end_sentence();
+                if !underline {
+                    self.end_section();
+                }
+                continue;
+            }
+            // Tables and HTML blocks aren't sentences
+            if text.starts_with(['|', '<']) {
+                self.end_sentence();
+                continue;
+            }
+            // Each list item and quote reads as its own sentence
+            if self.list_marker.is_match(text) {
+                self.end_sentence();
+            }
+
+            self.prose.prose_lines += 1;
+            let text = self.inline_noise.replace_all(text, " ");
+            for token in text.split_whitespace() {
+                let word = token.trim_matches(|c: char| !c.is_alphanumeric());
+                if !word.chars().any(|c| c.is_alphabetic()) {
+                    continue;
+                }
+                self.prose.word_count += 1;
+                self.prose.character_count +=
+                    word.chars().filter(|c| c.is_alphanumeric()).count() as u32;
+                self.syllable_count += syllables(word) as u64;
+                self.sentence_words += 1;
+                self.section_words += 1;
+
+                let end = token.trim_end_matches(['"', '\'', ')', ']', '*', '_']);
+                if end.ends_with(['.', '!', '?'])
+                    && !ABBREVIATIONS.contains(&token.to_lowercase().as_str())
+                {
+                    self.end_sentence();
+                }
+            }
+        }
+        self.end_sentence();
+        self.end_section();
+    }
+
+    fn end_sentence(&mut self) {
+        if self.sentence_words > 0 {
+            self.prose.sentence_count += 1;
+            self.sentence_words = 0;
+        }
+    }
+
+    fn end_section(&mut self) {
+        if self.section_words > 0 {
+            self.prose.section_count += 1;
+            self.section_words = 0;
+        }
+    }
+}
+
+impl FileClassifier for ProseClassifier {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        if self.prose.files_checked >= MAX_FILES || !is_doc_file(file) {
+            return;
+        }
+        if let Some(content) = file.content() {
+            self.check(content);
+        }
+    }
+}
+
+// A Setext or reStructuredText underline: one punctuation character repeated
+fn is_underline(text: &str) -> bool {
+    let mut chars = text.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    text.len() >= 3 && "=-~^\"'`#*+".contains(first) && chars.all(|c| c == first)
+}
+
+// Groups of vowels, less a silent final e ("make", but not "table")
+fn syllables(word: &str) -> u32 {
+    let word = word.to_lowercase();
+    let mut count = 0;
+    let mut previous_vowel = false;
+    for c in word.chars() {
+        let vowel = "aeiouy".contains(c);
+        if vowel && !previous_vowel {
+            count += 1;
+        }
+        previous_vowel = vowel;
+    }
+    if word.ends_with('e') && !word.ends_with("le") && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+// Readability first, then sentence and section lengths and the code samples
+fn signals(prose: &DocsProse) -> Vec<String> {
+    if prose.word_count == 0 {
+        return Vec::new();
+    }
+    let mut signals = Vec::new();
+    if let (Some(ease), Some(grade)) = (prose.flesch_reading_ease, prose.flesch_kincaid_grade) {
+        let band = READABILITY
+            .iter()
+            .find(|(below, _)| ease < *below)
+            .map(|(_, band)| *band)
+            .unwrap_or("easy");
+        signals.push(format!(
+            "The docs read as {} (Flesch reading ease {:.0}, about grade {:.0}).",
+            band,
+            ease,
+            grade.max(1.0)
+        ));
+    }
+    if prose.avg_sentence_words > 25.0 {
+        signals.push(format!(
+            "Sentences average {:.0} words, long enough to slow readers down.",
+            prose.avg_sentence_words
+        ));
+    }
+    if prose.avg_section_words > 500.0 {
+        signals.push(format!(
+            "Sections average {:.0} words, long stretches without headings to scan by.",
+            prose.avg_section_words
+        ));
+    } else if prose.section_count >= 5 && prose.avg_section_words < 30.0 {
+        signals.push(format!(
+            "Sections average {:.0} words, many headings have little under them.",
+            prose.avg_section_words
+        ));
+    }
+    if prose.code_lines == 0 && prose.word_count >= 300 {
+        signals.push("No code examples in the docs.".to_string());
+    } else if prose.code_to_prose_ratio > 1.0 {
+        signals.push(format!(
+            "{:.1} lines of code per line of prose, the docs are mostly examples.",
+            prose.code_to_prose_ratio
+        ));
+    }
+    signals
+}
@@ -0,0 +1,254 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+
+use crate::types::{DocumentationFile, ReadmeSummary};
+
+// Sentences in the summary, the lead sentence included
+const SUMMARY_SENTENCES: usize = 3;
+const SUMMARY_SECTIONS: usize = 8;
+// Candidates ranked, the rest of a long README adds little
+const MAX_CANDIDATES: usize = 200;
+// Sentences shorter than this are headings or fragments, longer ones run-ons
+const MIN_WORDS: usize = 6;
+const MAX_WORDS: usize = 60;
+
+// TextRank damping factor and iterations
+const DAMPING: f64 = 0.85;
+const ITERATIONS: usize = 30;
+
+// Lowercase heading words of sections that say how to set the project up or
+// who made it rather than what it is
+const SKIPPED_SECTIONS: &[&str] = &[
+    "install",
+    "license",
+    "licence",
+    "contribut",
+    "changelog",
+    "contents",
+    "acknowledg",
+    "author",
+    "sponsor",
+    "support",
+    "build",
+    "development",
+    "testing",
+];
+
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "had", "her", "was",
+    "one", "our", "out", "has", "have", "this", "that", "with", "from", "they", "will", "your",
+    "into", "which", "their", "there", "then", "than", "them", "these", "those", "its", "also",
+    "use", "uses", "used", "using", "more", "such", "when", "what", "how", "other", "been",
+    "being", "some", "each", "only", "just", "over", "about", "like", "may", "well",
+];
+
+// Extractive summary of the README for runs without an AI provider: the top
+// sections and the key sentences, picked with TextRank
+pub struct ReadmeSummarizer;
+
+impl ReadmeSummarizer {
+    pub fn summarize(&self, documentation: &[DocumentationFile]) -> Option<ReadmeSummary> {
+        let readme = documentation.iter().find(|doc| doc.file_type == "readme")?;
+        let (sentences, sections) = parse(&readme.content);
+        if sentences.is_empty() {
+            return None;
+        }
+
+        let words: Vec<HashSet<String>> = sentences
+            .iter()
+            .map(|sentence| content_words(&sentence.text))
+            .collect();
+        let scores = text_rank(&words);
+
+        // The first sentence of the introduction says what the project is,
+        // the highest ranked of the others follow
+        let mut picked: Vec<usize> = sentences
+            .iter()
+            .position(|sentence| sentence.in_intro)
+            .into_iter()
+            .collect();
+        let mut ranked: Vec<usize> = (0..sentences.len()).collect();
+        ranked.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]));
+        for index in ranked {
+            if picked.len() >= SUMMARY_SENTENCES {
+                break;
+            }
+            if !picked.contains(&index) {
+                picked.push(index);
+            }
+        }
+        picked.sort();
+
+        Some(ReadmeSummary {
+            sentences: picked
+                .into_iter()
+                .map(|index| sentences[index].text.clone())
+                .collect(),
+            sections: sections.into_iter().take(SUMMARY_SECTIONS).collect(),
+        })
+    }
+}
+
+struct Sentence {
+    text: String,
+    in_intro: bool, // Before the first section heading
+}
+
+// Prose sentences outside code, tables and skipped sections, and the second
+// level section headings
+fn parse(content: &str) -> (Vec<Sentence>, Vec<String>) {
+    let link = Regex::new(r"!?\[([^\]]*)\]\([^)]*\)").unwrap();
+    let noise = Regex::new(r"<[^>]+>|https?://\S+|[*`]").unwrap();
+    let sentence_end = Regex::new(r"[.!?]\s+").unwrap();
+
+    let mut sentences = Vec::new();
+    let mut sections = Vec::new();
+    let mut paragraphs: Vec<(String, bool)> = Vec::new();
+    let mut paragraph = String::new();
+    let mut in_fence = false;
+    let mut in_intro = true;
+    let mut skipped = false;
+
+    let mut flush = |paragraph: &mut String, in_intro: bool| {
+        if !paragraph.trim().is_empty() {
+            paragraphs.push((std::mem::take(paragraph), in_intro));
+        }
+        paragraph.clear();
+    };
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            flush(&mut paragraph, in_intro);
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        if let Some(heading) = trimmed.strip_prefix('#') {
+            flush(&mut paragraph, in_intro);
+            let level = 1 + heading.chars().take_while(|c| *c == '#').count();
+            // Without the emoji many READMEs start their headings with
+            let heading = noise
+                .replace_all(&link.replace_all(heading.trim_start_matches('#'), "$1"), "")
+                .trim_start_matches(|c: char| !c.is_alphanumeric())
+                .trim()
+                .to_string();
+            // The title heading doesn't end the introduction
+            if level == 1 && in_intro {
+                continue;
+            }
+            in_intro = false;
+            if level <= 2 {
+                let lowercase = heading.to_lowercase();
+                skipped = SKIPPED_SECTIONS
+                    .iter()
+                    .any(|section| lowercase.contains(section));
+                if !skipped && !heading.is_empty() {
+                    sections.push(heading);
+                }
+            }
+            continue;
+        }
+        // Badges, tables, HTML blocks, indented code and blank lines end
+        // paragraphs
+        if skipped
+            || trimmed.is_empty()
+            || trimmed.starts_with('|')
+            || trimmed.starts_with("[![")
+            || trimmed.starts_with("![")
+            || trimmed.starts_with('<')
+            || line.starts_with("    ")
+        {
+            flush(&mut paragraph, in_intro);
+            continue;
+        }
+        // List items are sentences of their own
+        let item = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+            .or_else(|| trimmed.strip_prefix("+ "));
+        if let Some(item) = item {
+            flush(&mut paragraph, in_intro);
+            paragraph.push_str(item);
+            flush(&mut paragraph, in_intro);
+            continue;
+        }
+        paragraph.push(' ');
+        paragraph.push_str(trimmed);
+    }
+    flush(&mut paragraph, in_intro);
+
+    for (paragraph, in_intro) in paragraphs {
+        let text = noise
+            .replace_all(&link.replace_all(&paragraph, "$1"), "")
+            .into_owned();
+        // Split after sentence punctuation, keeping it
+        let mut start = 0;
+        let mut parts = Vec::new();
+        for end in sentence_end.find_iter(&text) {
+            parts.push(&text[start..end.start() + 1]);
+            start = end.end();
+        }
+        parts.push(&text[start..]);
+        for part in parts {
+            let mut sentence = part.split_whitespace().collect::<Vec<_>>().join(" ");
+            let words = sentence.split_whitespace().count();
+            // List items often end without a period
+            if !sentence.ends_with(['.', '!', '?']) {
+                sentence.push('.');
+            }
+            if (MIN_WORDS..=MAX_WORDS).contains(&words) && sentences.len() < MAX_CANDIDATES {
+                sentences.push(Sentence {
+                    text: sentence,
+                    in_intro,
+                });
+            }
+        }
+    }
+    (sentences, sections)
+}
+
+fn content_words(sentence: &str) -> HashSet<String> {
+    sentence
+        .split(|c: char| !c.is_alphanumeric())
+        .map(|word| word.to_lowercase())
+        .filter(|word| word.len() > 2 && !STOPWORDS.contains(&word.as_str()))
+        .collect()
+}
+
+// PageRank over sentences linked by their shared words, normalized by the
+// sentence lengths as in the TextRank paper
+fn text_rank(words: &[HashSet<String>]) -> Vec<f64> {
+    let count = words.len();
+    let mut similarity = vec![vec![0.0; count]; count];
+    for i in 0..count {
+        for j in (i + 1)..count {
+            let (a, b) = (words[i].len() as f64, words[j].len() as f64);
+            if a < 2.0 || b < 2.0 {
+                continue;
+            }
+            let shared = words[i].intersection(&words[j]).count() as f64;
+            let weight = shared / (a.ln() + b.ln());
+            similarity[i][j] = weight;
+            similarity[j][i] = weight;
+        }
+    }
+    let out_weights: Vec<f64> = similarity.iter().map(|row| row.iter().sum()).collect();
+
+    let mut scores = vec![1.0; count];
+    for _ in 0..ITERATIONS {
+        scores = (0..count)
+            .map(|i| {
+                let incoming: f64 = (0..count)
+                    .filter(|j| out_weights[*j] > 0.0)
+                    .map(|j| similarity[j][i] / out_weights[j] * scores[j])
+                    .sum();
+                (1.0 - DAMPING) + DAMPING * incoming
+            })
+            .collect();
+    }
+    scores
+}
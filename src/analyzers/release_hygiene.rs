@@ -0,0 +1,210 @@
+use crate::types::{ChecksumVerification, GitHubRelease, ReleaseArtifacts, ReleaseHygiene};
+
+// Releases looked at, newest first
+pub const RECENT_RELEASES: usize = 5;
+
+// Lowercase names of checksum manifests, as substrings and suffixes
+const CHECKSUM_NAMES: &[&str] = &["sha256sums", "sha512sums", "checksums", "checksum"];
+const CHECKSUM_SUFFIXES: &[&str] = &[".sha256", ".sha512", ".sha256sum", ".sha512sum"];
+
+// Detached signatures, minisign and GPG ones, and cosign certificates and
+// Sigstore bundles
+const SIGNATURE_SUFFIXES: &[&str] = &[
+    ".sig",
+    ".asc",
+    ".minisig",
+    ".pem",
+    ".crt",
+    ".bundle",
+    ".sigstore",
+    ".sigstore.json",
+];
+
+// in-toto provenance, as SLSA generators and cosign attest upload it
+const ATTESTATION_MARKERS: &[&str] = &[".intoto.jsonl", "provenance", "attestation"];
+
+pub fn is_checksum_file(name: &str) -> bool {
+    let name = name.to_lowercase();
+    CHECKSUM_NAMES.iter().any(|marker| name.contains(marker))
+        || CHECKSUM_SUFFIXES
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+}
+
+fn is_signature(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SIGNATURE_SUFFIXES
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+}
+
+fn is_attestation(name: &str) -> bool {
+    let name = name.to_lowercase();
+    ATTESTATION_MARKERS
+        .iter()
+        .any(|marker| name.contains(marker))
+}
+
+// (hex digest, file name) entries of a checksum file: GNU `digest  name`
+// lines, BSD `SHA256 (name) = digest` lines, or a lone digest for the file
+// the checksum file is named after
+pub fn parse_checksums(checksum_file: &str, content: &str) -> Vec<(String, String)> {
+    let is_digest =
+        |text: &str| matches!(text.len(), 64 | 128) && text.chars().all(|c| c.is_ascii_hexdigit());
+    let mut entries = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(rest) = line
+            .strip_prefix("SHA256 (")
+            .or_else(|| line.strip_prefix("SHA512 ("))
+            && let Some((name, digest)) = rest.split_once(") = ")
+            && is_digest(digest.trim())
+        {
+            entries.push((digest.trim().to_lowercase(), name.to_string()));
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let (Some(digest), name) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        if !is_digest(digest) {
+            continue;
+        }
+        let name = match name {
+            // `*` marks binary mode, paths are relative to the build directory
+            Some(name) => name
+                .trim_start_matches('*')
+                .rsplit('/')
+                .next()
+                .unwrap_or(name),
+            None => {
+                let lowercase = checksum_file.to_lowercase();
+                let Some(suffix) = CHECKSUM_SUFFIXES
+                    .iter()
+                    .find(|suffix| lowercase.ends_with(*suffix))
+                else {
+                    continue;
+                };
+                &checksum_file[..checksum_file.len() - suffix.len()]
+            }
+        };
+        entries.push((digest.to_lowercase(), name.to_string()));
+    }
+    entries
+}
+
+// Checksum, signature and attestation files of the recent releases, and what
+// they say about the project's release supply chain
+pub struct ReleaseHygieneAnalyzer;
+
+impl ReleaseHygieneAnalyzer {
+    pub fn analyze(
+        &self,
+        releases: &[GitHubRelease],
+        verification: Option<ChecksumVerification>,
+    ) -> ReleaseHygiene {
+        let releases: Vec<ReleaseArtifacts> = releases
+            .iter()
+            .filter(|release| !release.draft)
+            .take(RECENT_RELEASES)
+            .map(|release| {
+                let mut artifacts = ReleaseArtifacts {
+                    tag_name: release.tag_name.clone(),
+                    artifacts: Vec::new(),
+                    checksum_files: Vec::new(),
+                    signatures: Vec::new(),
+                    attestations: Vec::new(),
+                };
+                for asset in &release.assets {
+                    let name = asset.name.clone();
+                    if is_attestation(&name) {
+                        artifacts.attestations.push(name);
+                    } else if is_signature(&name) {
+                        artifacts.signatures.push(name);
+                    } else if is_checksum_file(&name) {
+                        artifacts.checksum_files.push(name);
+                    } else {
+                        artifacts.artifacts.push(name);
+                    }
+                }
+                artifacts
+            })
+            .collect();
+        let signals = signals(&releases, verification.as_ref());
+        ReleaseHygiene {
+            releases,
+            verification,
+            signals,
+        }
+    }
+}
+
+fn signals(
+    releases: &[ReleaseArtifacts],
+    verification: Option<&ChecksumVerification>,
+) -> Vec<String> {
+    let shipping: Vec<&ReleaseArtifacts> = releases
+        .iter()
+        .filter(|release| !release.artifacts.is_empty())
+        .collect();
+    if shipping.is_empty() {
+        if !releases.is_empty() {
+            return vec![
+                "Recent releases ship no artifacts beyond GitHub's source archives.".to_string(),
+            ];
+        }
+        return Vec::new();
+    }
+
+    let mut signals = Vec::new();
+    let count =
+        |has: fn(&ReleaseArtifacts) -> bool| shipping.iter().filter(|release| has(release)).count();
+    let checksummed = count(|release| !release.checksum_files.is_empty());
+    let signed = count(|release| !release.signatures.is_empty());
+    let attested = count(|release| !release.attestations.is_empty());
+    let of = shipping.len();
+    signals.push(match checksummed {
+        0 => format!(
+            "None of the {} recent releases with artifacts ship checksums.",
+            of
+        ),
+        n => format!(
+            "{} of {} recent releases with artifacts ship checksums.",
+            n, of
+        ),
+    });
+    signals.push(match signed {
+        0 => "Release artifacts are not signed.".to_string(),
+        n => format!("{} of {} releases ship signatures.", n, of),
+    });
+    if attested > 0 {
+        signals.push(format!(
+            "{} of {} releases ship provenance attestations.",
+            attested, of
+        ));
+    }
+
+    match verification {
+        Some(check) if check.verified => signals.push(format!(
+            "{} of {} matches its {} checksum in {}.",
+            check.asset, check.tag_name, check.algorithm, check.checksum_file
+        )),
+        Some(check) => signals.push(format!(
+            "{} of {} does not match its {} checksum in {}: the download hashes to {}.",
+            check.asset, check.tag_name, check.algorithm, check.checksum_file, check.actual
+        )),
+        None if checksummed > 0 => {
+            signals.push("No checksum could be checked against its download.".to_string())
+        }
+        None => {}
+    }
+    if let Some(check) = verification
+        && check.attested == Some(true)
+    {
+        signals.push(format!(
+            "GitHub holds a build provenance attestation for {}.",
+            check.asset
+        ));
+    }
+    signals
+}
@@ -1,167 +1,1167 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use chrono::Utc;
+use futures::Stream;
 use log::info;
+use regex::Regex;
 
 use crate::{
     analyzers::{
-        code_metrics::CodeMetricsCalculator, filesystem::FileSystemAnalyzer,
-        security::SecurityAnalyzer, type_detector::ProjectTypeDetector,
+        api_docs::{self, ApiDocsClassifier},
+        code_metrics::CodeMetricsCalculator,
+        deployment::DeploymentClassifier,
+        detections::{self, DetectionRules},
+        docs_site::DocsSiteDetector,
+        entry_points::EntryPointDetector,
+        error_handling::ErrorHandlingClassifier,
+        filesystem::{ConfigClassifier, DocsClassifier, FileSystemAnalyzer, StructureClassifier},
+        friendliness::{self, FriendlinessAnalyzer},
+        imports::ImportClassifier,
+        interesting_files::InterestingFileSelector,
+        keywords::KeywordClassifier,
+        local_metadata,
+        ownership::OwnershipAnalyzer,
+        panic_density::PanicDensityClassifier,
+        performance::PerformanceClassifier,
+        planning::PlanningAnalyzer,
+        platforms::{self, PlatformClassifier},
+        prose::ProseClassifier,
+        readme_summary::ReadmeSummarizer,
+        release_hygiene::ReleaseHygieneAnalyzer,
+        reproducibility::ReproducibilityAnalyzer,
+        risk::RiskAssessor,
+        rules::{self, RuleSet},
+        scan::FileClassifier,
+        security::SecurityAnalyzer,
+        spelling::SpellingClassifier,
+        sustainability::SustainabilityAnalyzer,
+        templates::TemplateAnalyzer,
+        toolchains::{self, ToolchainClassifier},
+        tooling::ToolingAnalyzer,
+        type_detector::ProjectTypeDetector,
     },
-    git::GitManager,
-    github::GitHubClient,
-    types::{CodeMetrics, GitAnalysis, ProjectInfo, RepositoryAnalysis, RepositoryMetadata},
-    utils::parse_github_url,
+    bitbucket::BitbucketClient,
+    checkpoint::{self, CheckpointStore, FsScan},
+    config::AnalyzerConfig,
+    container::ContainerRunner,
+    evidence::EvidenceLinker,
+    export_context::{self, ContextFormat},
+    forge::{Forge, ForgeKind, parse_repository_url},
+    git::{self, GitBackend, GitManager},
+    github::{self, GitHubClient, GitHubConfig},
+    gitlab::GitLabClient,
+    progress::{self, warning},
+    registry::RegistryClient,
+    sandbox::AnalysisLimits,
+    types::{
+        AnalysisPhase, CodeMetrics, DependencyChange, GitAnalysis, MetadataSuggestions,
+        PackagePublishing, ProgressEvent, ProjectInfo, ReadmeSummary, RelatedProject, ReleaseDiff,
+        RemoteRepositoryData, RepositoryAnalysis, RepositoryMetadata, RepositoryStatus,
+        RiskAssessment, StarterIssues, ToolchainRequirements,
+    },
+    utils::describe_detections,
 };
 
+// Similar repositories looked up on GitHub
+const RELATED_PROJECTS: usize = 5;
+
+// Builds an analyzer from a loaded analyzer.toml and explicit settings, which
+// take precedence over the file's. Without a config the built-in defaults
+// apply, the current directory is not searched
+#[derive(Default)]
+pub struct RepositoryAnalyzerBuilder {
+    github_token: Option<String>,
+    gitlab_token: Option<String>,
+    bitbucket_token: Option<String>,
+    config: Option<AnalyzerConfig>,
+    work_dir: Option<PathBuf>,
+    git_backend: Option<GitBackend>,
+    github: Option<GitHubConfig>,
+    detections: Option<DetectionRules>,
+    rules: Option<RuleSet>,
+    limits: Option<AnalysisLimits>,
+    ranking: Option<(usize, Vec<String>)>,
+    linguist: bool,
+    resume: bool,
+    summarize_readme: bool,
+}
+
+impl RepositoryAnalyzerBuilder {
+    pub fn with_github_token(mut self, token: impl Into<String>) -> Self {
+        self.github_token = Some(token.into());
+        self
+    }
+
+    pub fn with_gitlab_token(mut self, token: impl Into<String>) -> Self {
+        self.gitlab_token = Some(token.into());
+        self
+    }
+
+    pub fn with_bitbucket_token(mut self, token: impl Into<String>) -> Self {
+        self.bitbucket_token = Some(token.into());
+        self
+    }
+
+    // Git backend, GitHub list limits, detection packs, custom rules and work
+    // dir from `[git]`, `[github]`, `[detections]`, `[rules]` and `[paths]`
+    pub fn with_config(mut self, config: &AnalyzerConfig) -> Self {
+        self.config = Some(config.clone());
+        self
+    }
+
+    // Clones, checkpoints and container outputs, under the temp dir by default
+    pub fn with_work_dir(mut self, work_dir: impl Into<PathBuf>) -> Self {
+        self.work_dir = Some(work_dir.into());
+        self
+    }
+
+    pub fn with_git_backend(mut self, backend: GitBackend) -> Self {
+        self.git_backend = Some(backend);
+        self
+    }
+
+    pub fn with_github_config(mut self, config: GitHubConfig) -> Self {
+        self.github = Some(config);
+        self
+    }
+
+    pub fn with_detections(mut self, rules: DetectionRules) -> Self {
+        self.detections = Some(rules);
+        self
+    }
+
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = Some(rules);
+        self
+    }
+
+    pub fn with_limits(mut self, limits: AnalysisLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    pub fn with_ranking(mut self, top_files: usize, excludes: &[String]) -> Self {
+        self.ranking = Some((top_files, excludes.to_vec()));
+        self
+    }
+
+    pub fn with_linguist(mut self, linguist: bool) -> Self {
+        self.linguist = linguist;
+        self
+    }
+
+    pub fn with_resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    pub fn with_readme_summary(mut self, summarize_readme: bool) -> Self {
+        self.summarize_readme = summarize_readme;
+        self
+    }
+
+    // Fails on an unreadable or invalid config file and invalid ranking globs
+    pub fn build(self) -> Result<RepositoryAnalyzer> {
+        let config = self.config.unwrap_or_default();
+        let work_dir = self.work_dir.or(config.paths.work_dir);
+        let git_backend = match self.git_backend {
+            Some(backend) => backend,
+            None => git::configured_backend(&config.git)?,
+        };
+        let github_config = self.github.unwrap_or(config.github);
+        let detections = match self.detections {
+            Some(detections) => detections,
+            None => detections::load_detections(&config.detections)?,
+        };
+        let rules = match self.rules {
+            Some(rules) => rules,
+            None => rules::load_rules(&config.rules)?,
+        };
+
+        let mut analyzer = RepositoryAnalyzer::new(self.github_token, work_dir)
+            .with_git_backend(git_backend)
+            .with_github_config(github_config)
+            .with_detections(detections)
+            .with_rules(rules)
+            .with_linguist(self.linguist)
+            .with_resume(self.resume)
+            .with_readme_summary(self.summarize_readme);
+        if let Some(token) = self.gitlab_token {
+            analyzer = analyzer.with_gitlab_token(token);
+        }
+        if let Some(token) = self.bitbucket_token {
+            analyzer = analyzer.with_bitbucket_token(token);
+        }
+        if let Some(limits) = self.limits {
+            analyzer = analyzer.with_limits(limits);
+        }
+        if let Some((top_files, excludes)) = self.ranking {
+            analyzer = analyzer.with_ranking(top_files, &excludes)?;
+        }
+        Ok(analyzer)
+    }
+}
+
 // Main repository analyzer
 pub struct RepositoryAnalyzer {
     github_client: GitHubClient,
+    github_config: GitHubConfig,
+    gitlab_client: GitLabClient,
+    bitbucket_client: BitbucketClient,
+    registry_client: RegistryClient,
     git_manager: GitManager,
     fs_analyzer: FileSystemAnalyzer,
     metrics_calculator: CodeMetricsCalculator,
     project_detector: ProjectTypeDetector,
     security_analyzer: SecurityAnalyzer,
+    ownership_analyzer: OwnershipAnalyzer,
+    template_analyzer: TemplateAnalyzer,
+    tooling_analyzer: ToolingAnalyzer,
+    reproducibility_analyzer: ReproducibilityAnalyzer,
+    docs_site_detector: DocsSiteDetector,
+    entry_point_detector: EntryPointDetector,
+    file_selector: InterestingFileSelector,
+    rules: RuleSet,
+    resume: bool,
+    summarize_readme: bool,
 }
 
 impl RepositoryAnalyzer {
     pub fn new(github_token: Option<String>, work_dir: Option<PathBuf>) -> Self {
         Self {
             github_client: GitHubClient::new(github_token),
+            github_config: GitHubConfig::default(),
+            gitlab_client: GitLabClient::new(None),
+            bitbucket_client: BitbucketClient::new(None),
+            registry_client: RegistryClient::new(),
             git_manager: GitManager::new(work_dir),
             fs_analyzer: FileSystemAnalyzer::new(),
-            metrics_calculator: CodeMetricsCalculator,
-            project_detector: ProjectTypeDetector,
-            security_analyzer: SecurityAnalyzer,
+            metrics_calculator: CodeMetricsCalculator::new(),
+            project_detector: ProjectTypeDetector::new(DetectionRules::builtin()),
+            security_analyzer: SecurityAnalyzer::new(),
+            ownership_analyzer: OwnershipAnalyzer,
+            template_analyzer: TemplateAnalyzer,
+            tooling_analyzer: ToolingAnalyzer,
+            reproducibility_analyzer: ReproducibilityAnalyzer::new(),
+            docs_site_detector: DocsSiteDetector,
+            entry_point_detector: EntryPointDetector,
+            file_selector: InterestingFileSelector::new(15),
+            rules: RuleSet::default(),
+            resume: false,
+            summarize_readme: false,
+        }
+    }
+
+    // Read clones and history with libgit2 or gitoxide
+    pub fn with_git_backend(mut self, backend: GitBackend) -> Self {
+        self.git_manager = self.git_manager.with_backend(backend);
+        self
+    }
+
+    pub fn with_gitlab_token(mut self, token: impl Into<String>) -> Self {
+        self.gitlab_client = GitLabClient::new(Some(token.into()));
+        self
+    }
+
+    // An access token, or "username:app password" for basic auth
+    pub fn with_bitbucket_token(mut self, token: impl Into<String>) -> Self {
+        self.bitbucket_client = BitbucketClient::new(Some(token.into()));
+        self
+    }
+
+    // Limit the contributors, issues and releases fetched
+    pub fn with_github_config(mut self, config: GitHubConfig) -> Self {
+        self.github_client = self.github_client.with_config(&config);
+        self.github_config = config;
+        self
+    }
+
+    // Replace the default limits applied while reading the cloned tree
+    pub fn with_limits(mut self, limits: AnalysisLimits) -> Self {
+        self.fs_analyzer = FileSystemAnalyzer::with_limits(limits);
+        self
+    }
+
+    // Compute language statistics the way GitHub Linguist does
+    pub fn with_linguist(mut self, linguist: bool) -> Self {
+        self.metrics_calculator =
+            std::mem::take(&mut self.metrics_calculator).with_linguist(linguist);
+        self
+    }
+
+    // Configure the length of the top-file lists and globs excluded from them
+    pub fn with_ranking(mut self, top_files: usize, excludes: &[String]) -> Result<Self> {
+        self.metrics_calculator =
+            std::mem::take(&mut self.metrics_calculator).with_ranking(top_files, excludes)?;
+        Ok(self)
+    }
+
+    // Detect frameworks, tools and databases with these rule packs
+    pub fn with_detections(mut self, rules: DetectionRules) -> Self {
+        self.project_detector = ProjectTypeDetector::new(rules);
+        self
+    }
+
+    // Evaluate custom rule scripts against every indexed file
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    // Reuse checkpoints left by an interrupted run instead of starting over
+    pub fn with_resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    // Describe the project in the summary from the README's key sentences,
+    // for runs without an AI provider
+    pub fn with_readme_summary(mut self, summarize_readme: bool) -> Self {
+        self.summarize_readme = summarize_readme;
+        self
+    }
+
+    pub fn builder() -> RepositoryAnalyzerBuilder {
+        RepositoryAnalyzerBuilder::default()
+    }
+
+    pub fn work_dir(&self) -> &Path {
+        self.git_manager.work_dir()
+    }
+
+    // Whether a tree is a clone in the work directory, as opposed to a
+    // checkout of the user's that may change while it's read
+    fn owns(&self, repo_path: &Path) -> bool {
+        match (repo_path.canonicalize(), self.work_dir().canonicalize()) {
+            (Ok(repo_path), Ok(work_dir)) => repo_path.starts_with(work_dir),
+            _ => false,
         }
     }
 
     pub async fn analyze_repository(&self, repo_url: &str) -> Result<RepositoryAnalysis> {
         info!("Starting analysis of repository: {}", repo_url);
 
-        // Parse GitHub URL
-        let (owner, repo) = parse_github_url(repo_url)?;
-        info!("Parsed repository: {}/{}", owner, repo);
+        let checkpoints = self.checkpoints(repo_url)?;
+        let (remote, repo_path) = self.fetch_remote(repo_url, &checkpoints).await?;
+        let mut analysis = self.analyze_local(remote, &repo_path, &checkpoints)?;
+        self.fetch_package_publishing(&mut analysis).await;
+        self.check_api_docs(&mut analysis).await;
+        self.fetch_related_projects(&mut analysis).await;
+        self.record_rate_limits(&mut analysis);
+        checkpoints.clear();
 
-        // Fetch repository metadata from GitHub API
-        info!("Fetching repository metadata...");
-        let metadata = self
-            .github_client
-            .get_repository_metadata(&owner, &repo)
+        info!("Repository analysis completed successfully!");
+        Ok(analysis)
+    }
+
+    // Analysis of a local checkout without any network access: no forge API,
+    // clone or registry lookups. The metadata comes from its git remote, the
+    // working tree and the history
+    pub fn analyze_path(&self, repo_path: &Path) -> Result<RepositoryAnalysis> {
+        info!("Starting offline analysis of: {}", repo_path.display());
+
+        let checkout = self.git_manager.read_checkout(repo_path)?;
+        let remote = local_metadata::remote_data(repo_path, &checkout);
+        let mut analysis = self.analyze_local(remote, repo_path, &CheckpointStore::disabled())?;
+        local_metadata::complete_metadata(&mut analysis);
+
+        info!("Repository analysis completed successfully!");
+        Ok(analysis)
+    }
+
+    // analyze_repository, with the phases, files, API calls and warnings
+    // streamed as they happen. The stream ends when the analysis completes,
+    // so poll both, e.g. with tokio::join!
+    pub fn analyze_with_progress<'a>(
+        &'a self,
+        repo_url: &'a str,
+    ) -> (
+        impl Stream<Item = ProgressEvent> + use<'a>,
+        impl Future<Output = Result<RepositoryAnalysis>> + 'a,
+    ) {
+        progress::track(self.analyze_repository(repo_url))
+    }
+
+    // What changed between two tags: files, lines, authors, dependencies and new modules
+    pub async fn release_diff(
+        &self,
+        repo_url: &str,
+        from_tag: &str,
+        to_tag: &str,
+    ) -> Result<ReleaseDiff> {
+        let (forge, owner, repo) = parse_repository_url(repo_url)?;
+        info!(
+            "Comparing {} and {} of {}/{}",
+            from_tag, to_tag, owner, repo
+        );
+
+        let repo_path = self
+            .git_manager
+            .clone_or_update_repository(&forge.clone_url(&owner, &repo), &repo)
             .await?;
 
-        // Fetch additional GitHub data
+        let mut diff = self.git_manager.diff_tags(&repo_path, from_tag, to_tag)?;
+        diff.url = repo_url.to_string();
+
+        info!("Comparing dependencies...");
+        for file in &diff.files {
+            let Some(file_name) = file.path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let dependencies_at = |tag: &str, path: &Path| {
+                self.git_manager
+                    .file_at_tag(&repo_path, tag, path)
+                    .and_then(|content| self.fs_analyzer.manifest_dependencies(file_name, &content))
+                    .unwrap_or_default()
+            };
+            let before = dependencies_at(from_tag, file.old_path.as_deref().unwrap_or(&file.path));
+            let after = dependencies_at(to_tag, &file.path);
+
+            let mut names: Vec<&String> = before.keys().chain(after.keys()).collect();
+            names.sort();
+            names.dedup();
+            for name in names {
+                let (from_version, to_version) = (before.get(name), after.get(name));
+                let change = match (from_version, to_version) {
+                    (None, Some(_)) => "added",
+                    (Some(_), None) => "removed",
+                    (Some(from), Some(to)) if from != to => "updated",
+                    _ => continue,
+                };
+                diff.dependency_changes.push(DependencyChange {
+                    manifest: file.path.clone(),
+                    name: name.clone(),
+                    change: change.to_string(),
+                    from_version: from_version.cloned(),
+                    to_version: to_version.cloned(),
+                });
+            }
+        }
+
+        Ok(diff)
+    }
+
+    // The tree and the most relevant file contents of a repository, or of a
+    // local checkout, as one document for `export-context`
+    pub async fn export_context(
+        &self,
+        target: &str,
+        format: ContextFormat,
+        max_tokens: usize,
+    ) -> Result<String> {
+        let (name, repo_path) = if Path::new(target).is_dir() {
+            let repo_path = PathBuf::from(target);
+            let name = repo_path
+                .canonicalize()?
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| target.to_string());
+            (name, repo_path)
+        } else {
+            let (forge, owner, repo) = parse_repository_url(target)?;
+            info!("Cloning repository...");
+            let repo_path = self
+                .git_manager
+                .clone_or_update_repository(&forge.clone_url(&owner, &repo), &repo)
+                .await?;
+            (repo, repo_path)
+        };
+
+        let mut structure = StructureClassifier::new(&self.fs_analyzer, &repo_path);
+        let mut configs = ConfigClassifier::new(&self.fs_analyzer);
+        let mut classifiers: [&mut dyn FileClassifier; 2] = [&mut structure, &mut configs];
+        self.fs_analyzer
+            .scan(&repo_path, self.owns(&repo_path), &mut classifiers)?;
+        let file_structure = structure.into_index();
+        let ranked = InterestingFileSelector::new(usize::MAX)
+            .select(&file_structure, &configs.into_config_files());
+
+        Ok(export_context::render(
+            &name,
+            &file_structure,
+            &ranked,
+            format,
+            max_tokens,
+            |path| self.fs_analyzer.read_repo_file(&repo_path, path),
+        ))
+    }
+
+    // Same as analyze_repository, but the cloned tree is only read inside the container
+    pub async fn analyze_repository_in_container(
+        &self,
+        repo_url: &str,
+        runner: &ContainerRunner,
+    ) -> Result<RepositoryAnalysis> {
+        info!("Starting sandboxed analysis of repository: {}", repo_url);
+
+        let checkpoints = self.checkpoints(repo_url)?;
+        let (remote, repo_path) = self.fetch_remote(repo_url, &checkpoints).await?;
+
+        info!(
+            "Running local analysis in {} container...",
+            runner.runtime()
+        );
+        let mut analysis =
+            runner.analyze(&remote, &repo_path, &self.work_dir().join("container"))?;
+        self.fetch_package_publishing(&mut analysis).await;
+        self.check_api_docs(&mut analysis).await;
+        self.fetch_related_projects(&mut analysis).await;
+        self.record_rate_limits(&mut analysis);
+        checkpoints.clear();
+
+        info!("Repository analysis completed successfully!");
+        Ok(analysis)
+    }
+
+    pub fn checkpoints(&self, repo_url: &str) -> Result<CheckpointStore> {
+        let (_, owner, repo) = parse_repository_url(repo_url)?;
+        let dir = self.work_dir().join("checkpoints").join(format!(
+            "{}-{}",
+            owner.replace('/', "-"),
+            repo
+        ));
+        Ok(CheckpointStore::new(dir, self.resume))
+    }
+
+    // Network phase: forge API data and the clone itself
+    pub async fn fetch_remote(
+        &self,
+        repo_url: &str,
+        checkpoints: &CheckpointStore,
+    ) -> Result<(RemoteRepositoryData, PathBuf)> {
+        let (forge, owner, repo) = parse_repository_url(repo_url)?;
+        info!("Parsed {} repository: {}/{}", forge, owner, repo);
+
+        // The metadata checkpoint is only written once the clone has completed
+        let repo_path = self.git_manager.work_dir().join(&repo);
+        if let Some(remote) = checkpoints.load::<RemoteRepositoryData>(checkpoint::METADATA)
+            && repo_path.exists()
+        {
+            return Ok((remote, repo_path));
+        }
+
+        let forge_phase = progress::phase(AnalysisPhase::ForgeData);
+        let remote = match forge {
+            ForgeKind::GitHub => {
+                let mut remote = self
+                    .fetch_forge_data(&self.github_client, repo_url, &owner, &repo)
+                    .await?;
+                self.fetch_github_data(&mut remote, &owner, &repo).await;
+                remote
+            }
+            ForgeKind::GitLab => {
+                self.fetch_forge_data(&self.gitlab_client, repo_url, &owner, &repo)
+                    .await?
+            }
+            ForgeKind::Bitbucket => {
+                self.fetch_forge_data(&self.bitbucket_client, repo_url, &owner, &repo)
+                    .await?
+            }
+        };
+
+        // Clone repository for local analysis
+        drop(forge_phase);
+        let _phase = progress::phase(AnalysisPhase::Clone);
+        info!("Cloning repository...");
+        let repo_path = self
+            .git_manager
+            .clone_or_update_repository(&remote.metadata.clone_url, &repo)
+            .await?;
+
+        checkpoints.save(checkpoint::METADATA, &remote);
+        Ok((remote, repo_path))
+    }
+
+    // Metadata, contributors, releases and issues, which every forge serves
+    async fn fetch_forge_data(
+        &self,
+        forge: &impl Forge,
+        repo_url: &str,
+        owner: &str,
+        repo: &str,
+    ) -> Result<RemoteRepositoryData> {
+        info!("Fetching repository metadata...");
+        let metadata = forge.get_repository_metadata(owner, repo).await?;
+
+        // Disabled repositories can't be cloned, read-only ones get a different report
+        let status = repository_status(&metadata);
+        if status.disabled {
+            anyhow::bail!(
+                "{} has been disabled by GitHub and cannot be analyzed",
+                metadata.full_name
+            );
+        }
+        for note in &status.notes {
+            warning!("{}", note);
+        }
+
         info!("Fetching contributors...");
-        let contributors = self
-            .github_client
-            .get_contributors(&owner, &repo)
+        let contributors = forge
+            .get_contributors(owner, repo, self.github_config.max_contributors)
             .await
             .unwrap_or_default();
 
         info!("Fetching releases...");
-        let releases = self
-            .github_client
-            .get_releases(&owner, &repo, 10)
+        let releases = forge
+            .get_releases(owner, repo, self.github_config.max_releases)
             .await
             .unwrap_or_default();
 
-        info!("Fetching recent issues...");
-        let recent_issues = self
+        // Issues of read-only repositories and mirrors aren't acted on here
+        let recent_issues = if status.read_only || status.is_mirror {
+            Vec::new()
+        } else {
+            info!("Fetching recent issues...");
+            forge
+                .get_recent_issues(owner, repo, self.github_config.max_issues)
+                .await
+                .unwrap_or_default()
+        };
+
+        Ok(RemoteRepositoryData {
+            url: repo_url.to_string(),
+            metadata,
+            contributors,
+            releases,
+            recent_issues,
+            related_projects: Vec::new(),
+            fork_relationship: None,
+            status,
+            starter_issues: StarterIssues::default(),
+            commit_authors: HashMap::new(),
+            milestones: Vec::new(),
+            project_boards: None,
+            security_advisories: None,
+            checksum_verification: None,
+            offline: false,
+        })
+    }
+
+    // What only GitHub's API has: commit author accounts, issue response
+    // times, starter issues, planning, release checksums, advisories,
+    // related projects and the fork's upstream
+    async fn fetch_github_data(&self, remote: &mut RemoteRepositoryData, owner: &str, repo: &str) {
+        info!("Linking commit authors to GitHub accounts...");
+        remote.commit_authors = self
             .github_client
-            .get_recent_issues(&owner, &repo, 20)
+            .get_commit_authors(owner, repo, 3)
             .await
-            .unwrap_or_default();
+            .unwrap_or_else(|e| {
+                warning!("Failed to fetch commit authors: {}", e);
+                HashMap::new()
+            });
 
-        // Clone repository for local analysis
-        info!("Cloning repository...");
-        let repo_path = self
-            .git_manager
-            .clone_or_update_repository(&metadata.clone_url, &repo)
-            .await?;
+        if !remote.status.read_only && !remote.status.is_mirror {
+            // Only issues from outside the team say how newcomers are answered,
+            // the most recent of them are sampled
+            for issue in remote
+                .recent_issues
+                .iter_mut()
+                .filter(|i| !github::is_maintainer_association(&i.author_association))
+                .take(friendliness::RESPONSE_SAMPLE)
+                .filter(|i| i.comments > 0)
+            {
+                issue.first_response_at = self
+                    .github_client
+                    .get_first_response(owner, repo, issue)
+                    .await
+                    .unwrap_or_else(|e| {
+                        warning!("Failed to fetch the comments of #{}: {}", issue.number, e);
+                        None
+                    });
+            }
+
+            info!("Counting starter issues...");
+            let count = async |labels: &[&str]| {
+                self.github_client
+                    .count_open_issues(owner, repo, labels)
+                    .await
+                    .inspect_err(|e| warning!("Failed to count starter issues: {}", e))
+                    .ok()
+            };
+            remote.starter_issues = StarterIssues {
+                good_first_issues: count(friendliness::STARTER_LABELS).await,
+                help_wanted_issues: count(friendliness::HELP_WANTED_LABELS).await,
+            };
+        }
+
+        info!("Fetching milestones and project boards...");
+        remote.milestones = self
+            .github_client
+            .get_milestones(owner, repo, 30)
+            .await
+            .unwrap_or_else(|e| {
+                warning!("Failed to fetch milestones: {}", e);
+                Vec::new()
+            });
+        remote.project_boards = self
+            .github_client
+            .get_project_boards(owner, repo)
+            .await
+            .unwrap_or_else(|e| {
+                warning!("Failed to fetch project boards: {}", e);
+                None
+            });
+
+        info!("Verifying a release checksum...");
+        remote.checksum_verification = self
+            .github_client
+            .verify_release_checksum(owner, repo, &remote.releases)
+            .await
+            .unwrap_or_else(|e| {
+                warning!("Failed to verify a release checksum: {}", e);
+                None
+            });
+
+        info!("Fetching published security advisories...");
+        remote.security_advisories = self
+            .github_client
+            .get_security_advisories(owner, repo)
+            .await
+            .inspect_err(|e| warning!("Failed to fetch security advisories: {}", e))
+            .ok();
+
+        // Without topics the search waits for the keywords of the local
+        // analysis
+        if !remote.metadata.topics.is_empty() {
+            info!("Searching related projects...");
+            remote.related_projects = self
+                .github_client
+                .search_similar_repositories(&remote.metadata, &[], RELATED_PROJECTS)
+                .await
+                .unwrap_or_default();
+        }
 
+        if remote.metadata.fork {
+            info!("Comparing fork with upstream...");
+            remote.fork_relationship = self
+                .github_client
+                .get_fork_relationship(&remote.metadata)
+                .await
+                .unwrap_or_else(|e| {
+                    warning!("Failed to analyze fork relationship: {}", e);
+                    None
+                });
+        }
+    }
+
+    // Offline phase: everything derived from the cloned tree, needs no network
+    pub fn analyze_local(
+        &self,
+        mut remote: RemoteRepositoryData,
+        repo_path: &Path,
+        checkpoints: &CheckpointStore,
+    ) -> Result<RepositoryAnalysis> {
         // Analyze Git history
-        info!("Analyzing Git history...");
-        let mut git_analysis = self.git_manager.analyze_git_history(&repo_path)?;
+        let mut git_analysis = match checkpoints.load::<GitAnalysis>(checkpoint::GIT_ANALYSIS) {
+            Some(git_analysis) => git_analysis,
+            None => {
+                let _phase = progress::phase(AnalysisPhase::GitHistory);
+                info!("Analyzing Git history...");
+                let git_analysis = self
+                    .git_manager
+                    .analyze_git_history(repo_path, &remote.commit_authors)?;
+                checkpoints.save(checkpoint::GIT_ANALYSIS, &git_analysis);
+                git_analysis
+            }
+        };
 
-        // Merge contributors from API with Git analysis
-        git_analysis.contributors = contributors;
+        // Merge contributors from API with Git analysis. Offline the people of
+        // the history stand in for the forge's list
+        git_analysis.contributors = if remote.offline {
+            git_analysis.history_contributors.clone()
+        } else {
+            std::mem::take(&mut remote.contributors)
+        };
+        git::link_contributor_logins(&mut git_analysis);
 
-        // Analyze file structure
-        info!("Analyzing file structure...");
-        let file_structure = self.fs_analyzer.analyze_directory(&repo_path)?;
+        // Scan the file system once: structure, config files, documentation
+        // and security findings
+        let fs_scan = match checkpoints.load::<FsScan>(checkpoint::FS_SCAN) {
+            Some(fs_scan) => fs_scan,
+            None => {
+                let _phase = progress::phase(AnalysisPhase::FileScan);
+                let mut structure = StructureClassifier::new(&self.fs_analyzer, repo_path);
+                let mut configs = ConfigClassifier::new(&self.fs_analyzer);
+                let mut docs = DocsClassifier::new(&self.fs_analyzer);
+                let mut security = self.security_analyzer.classifier();
+                let mut panics = PanicDensityClassifier::new();
+                let mut error_handling = ErrorHandlingClassifier::new();
+                let mut deployment = DeploymentClassifier::new();
+                let mut performance = PerformanceClassifier::new();
+                let mut platforms = PlatformClassifier::new();
+                let mut toolchains = ToolchainClassifier::new();
+                let mut api_docs = ApiDocsClassifier::new();
+                let mut spelling = SpellingClassifier::new(&remote.metadata.name);
+                let mut prose = ProseClassifier::new();
+                let mut keywords = KeywordClassifier::new();
+                // Imports are only read for the custom rules
+                let mut imports = (!self.rules.is_empty()).then(ImportClassifier::new);
+                let mut classifiers: Vec<&mut dyn FileClassifier> = vec![
+                    &mut structure,
+                    &mut configs,
+                    &mut docs,
+                    &mut security,
+                    &mut panics,
+                    &mut error_handling,
+                    &mut deployment,
+                    &mut performance,
+                    &mut platforms,
+                    &mut toolchains,
+                    &mut api_docs,
+                    &mut spelling,
+                    &mut prose,
+                    &mut keywords,
+                ];
+                if let Some(imports) = &mut imports {
+                    classifiers.push(imports);
+                }
+                let embedded_code =
+                    self.fs_analyzer
+                        .scan(repo_path, self.owns(repo_path), &mut classifiers)?;
 
-        // Calculate code metrics
-        info!("Calculating code metrics...");
-        let code_metrics = self.metrics_calculator.calculate_metrics(&file_structure);
+                let fs_scan = FsScan {
+                    file_structure: structure.into_index(),
+                    embedded_code,
+                    config_files: configs.into_config_files(),
+                    documentation: docs.into_documentation(),
+                    security: security.finish(),
+                    panic_density: panics.finish(),
+                    error_handling: error_handling.finish(),
+                    deployment: deployment.finish(),
+                    performance: performance.finish(),
+                    platforms: platforms.finish(),
+                    api_docs: api_docs.finish(),
+                    spelling: spelling.finish(),
+                    prose: prose.finish(),
+                    keywords: keywords.finish(),
+                    toolchains: toolchains.finish(),
+                    imports: imports.map(ImportClassifier::finish).unwrap_or_default(),
+                };
+                checkpoints.save(checkpoint::FS_SCAN, &fs_scan);
+                fs_scan
+            }
+        };
+        let FsScan {
+            file_structure,
+            embedded_code,
+            config_files,
+            documentation,
+            security,
+            panic_density,
+            error_handling,
+            deployment,
+            performance,
+            platforms: mut platform_support,
+            mut api_docs,
+            spelling: docs_spelling,
+            prose: docs_prose,
+            keywords,
+            toolchains: toolchain_requirements,
+            imports,
+        } = fs_scan;
+        platforms::add_release_assets(&mut platform_support, &remote.releases);
 
-        // Find and analyze config files
-        info!("Analyzing configuration files...");
-        let config_files = self.fs_analyzer.find_config_files(&repo_path)?;
+        let _phase = progress::phase(AnalysisPhase::LocalAnalysis);
 
-        // Find and analyze documentation
-        info!("Analyzing documentation...");
-        let documentation = self.fs_analyzer.find_documentation_files(&repo_path)?;
+        // Run the custom rules over the file index
+        let rule_findings = self.rules.evaluate(&file_structure, &imports);
+
+        // Calculate code metrics
+        info!("Calculating code metrics...");
+        let mut code_metrics = self
+            .metrics_calculator
+            .calculate_metrics(&file_structure, &config_files);
+        code_metrics.panic_density = panic_density;
+        code_metrics.error_handling = error_handling;
 
         // Detect project information
         info!("Detecting project type and technologies...");
-        let project_info = self
-            .project_detector
-            .detect_project_info(&config_files, &file_structure);
+        let project_info = self.project_detector.detect_project_info(
+            &config_files,
+            &file_structure,
+            remote.metadata.language.as_deref(),
+        );
+        api_docs::add_project_context(&mut api_docs, &documentation, &project_info);
 
         // Analyze security
         info!("Analyzing security aspects...");
-        let security_info = self
-            .security_analyzer
-            .analyze_security(&file_structure, &config_files);
+        let security_info = self.security_analyzer.analyze_security(
+            &file_structure,
+            &config_files,
+            security,
+            remote.security_advisories.take(),
+        );
+
+        // Inventory issue and pull request templates
+        info!("Analyzing issue and pull request templates...");
+        let contribution_templates = self.template_analyzer.analyze_templates(&documentation);
+
+        // Rate how approachable the project is for newcomers
+        let contributor_friendliness =
+            FriendlinessAnalyzer.analyze(&remote, &documentation, &contribution_templates);
+
+        // Detect linters, formatters and hooks
+        info!("Analyzing developer tooling...");
+        let developer_tooling = self
+            .tooling_analyzer
+            .analyze_tooling(&file_structure, &config_files);
+
+        // Detect entry points and tasks, and how to run the project
+        info!("Detecting entry points...");
+        let run_instructions = self.entry_point_detector.detect(
+            repo_path,
+            &file_structure,
+            &config_files,
+            &self.fs_analyzer,
+        );
+
+        // Check lockfiles, toolchain pins and Docker digests
+        info!("Analyzing build reproducibility...");
+        let build_reproducibility = self.reproducibility_analyzer.analyze_reproducibility(
+            repo_path,
+            &file_structure,
+            &self.fs_analyzer,
+        );
+
+        // Detect the documentation site generator and Pages setup
+        info!("Detecting documentation site...");
+        let docs_site = self.docs_site_detector.detect_docs_site(
+            repo_path,
+            &file_structure,
+            &remote.metadata,
+            &self.fs_analyzer,
+        );
+
+        // Map CODEOWNERS rules onto the tree
+        info!("Analyzing code ownership...");
+        let ownership = self.ownership_analyzer.analyze_ownership(
+            &file_structure,
+            &config_files,
+            &git_analysis,
+        );
+
+        // Look for funding, backing organizations and governance
+        info!("Analyzing sustainability...");
+        let sustainability = SustainabilityAnalyzer.analyze(&documentation, &git_analysis);
+
+        // See whether roadmap work is tracked in public
+        info!("Analyzing planning...");
+        let planning = PlanningAnalyzer.analyze(&remote, &documentation);
+
+        // Check the checksums and signatures shipped with the releases
+        let release_hygiene =
+            ReleaseHygieneAnalyzer.analyze(&remote.releases, remote.checksum_verification.take());
+
+        // Pick the README's key sentences with TextRank
+        let readme_summary = ReadmeSummarizer.summarize(&documentation);
+
+        // Select the most architecturally relevant files for AI context
+        info!("Selecting key files...");
+        let interesting_files = self.file_selector.select(&file_structure, &config_files);
 
         // Generate analysis summary
-        let analysis_summary =
-            self.generate_analysis_summary(&metadata, &code_metrics, &project_info, &git_analysis);
+        let analysis_summary = self.generate_analysis_summary(
+            &remote,
+            &code_metrics,
+            &project_info,
+            &git_analysis,
+            &toolchain_requirements,
+            readme_summary.as_ref().filter(|_| self.summarize_readme),
+        );
 
-        let analysis = RepositoryAnalysis {
-            url: repo_url.to_string(),
+        let mut analysis = RepositoryAnalysis {
+            url: remote.url,
             analyzed_at: Utc::now(),
-            metadata,
+            metadata: remote.metadata,
+            repository_status: remote.status,
             file_structure,
+            embedded_code,
             code_metrics,
             git_analysis,
             project_info,
             config_files,
             documentation,
+            contribution_templates,
+            contributor_friendliness,
+            docs_site,
+            api_docs,
+            docs_spelling,
+            docs_prose,
+            readme_summary,
+            keywords,
+            developer_tooling,
+            run_instructions,
+            deployment,
+            build_reproducibility,
+            toolchain_requirements,
             security_info,
-            releases,
-            recent_issues,
+            ownership,
+            sustainability,
+            planning,
+            performance,
+            platform_support,
+            interesting_files,
+            releases: remote.releases,
+            release_hygiene,
+            recent_issues: remote.recent_issues,
+            related_projects: remote.related_projects,
+            fork_relationship: remote.fork_relationship,
+            package_publishing: PackagePublishing::default(), // Needs network, filled in afterwards
+            risk_assessment: RiskAssessment::default(),
             analysis_summary,
             ai_insights: None, // Can be populated by AI analysis later
+            tags: Vec::new(),
+            rule_findings,
+            overrides: Vec::new(),       // Applied once the AI results are in
+            plugin_sections: Vec::new(), // Plugins run on the finished analysis
+            metadata_suggestions: MetadataSuggestions::default(), // Suggested after the overrides
+            telemetry: None,             // Filled in once the whole run is finished
+            github_rate_limits: Vec::new(), // Filled in once the GitHub requests are done
+            cancelled: false,
         };
 
-        info!("Repository analysis completed successfully!");
+        // Rate the risks from everything gathered above
+        analysis.risk_assessment = RiskAssessor.assess(&analysis);
+        // Link the findings to their files at the analyzed commit
+        EvidenceLinker::new(&analysis).link(&mut analysis);
         Ok(analysis)
     }
 
+    // Fetch registry data for packages published from the repository
+    pub async fn fetch_package_publishing(&self, analysis: &mut RepositoryAnalysis) {
+        let _phase = progress::phase(AnalysisPhase::PackageRegistries);
+        info!("Fetching package registry metadata...");
+        analysis.package_publishing = self
+            .registry_client
+            .fetch_publishing(&analysis.config_files)
+            .await;
+    }
+
+    // Check that the API docs links resolve, and say so in the signals
+    pub async fn check_api_docs(&self, analysis: &mut RepositoryAnalysis) {
+        let api_docs = &mut analysis.api_docs;
+        if api_docs.urls.is_empty() {
+            return;
+        }
+        let _phase = progress::phase(AnalysisPhase::ApiDocs);
+        info!("Checking API docs links...");
+        for url in api_docs.urls.iter_mut().take(api_docs::MAX_CHECKED_URLS) {
+            match self.registry_client.url_resolves(&url.url).await {
+                Ok(resolves) => url.resolves = Some(resolves),
+                Err(e) => warning!("Failed to check {}: {}", url.url, e),
+            }
+        }
+        api_docs.signals = api_docs::signals(api_docs);
+    }
+
+    // Search related projects by the keywords for repositories without
+    // topics, and name them last in the summary as the topic search does
+    pub async fn fetch_related_projects(&self, analysis: &mut RepositoryAnalysis) {
+        if !analysis.metadata.topics.is_empty() || !analysis.related_projects.is_empty() {
+            return;
+        }
+        let _phase = progress::phase(AnalysisPhase::RelatedProjects);
+        info!("Searching related projects...");
+        analysis.related_projects = self
+            .github_client
+            .search_similar_repositories(&analysis.metadata, &analysis.keywords, RELATED_PROJECTS)
+            .await
+            .unwrap_or_default();
+        if let Some(line) = related_projects_line(&analysis.related_projects) {
+            analysis.analysis_summary.push('\n');
+            analysis.analysis_summary.push_str(&line);
+        }
+    }
+
+    // The quota left after the last GitHub requests of the analysis
+    fn record_rate_limits(&self, analysis: &mut RepositoryAnalysis) {
+        analysis.github_rate_limits = self.github_client.rate_limits();
+        for quota in &analysis.github_rate_limits {
+            info!(
+                "GitHub API quota: {} of {} {} requests left, resets at {}",
+                quota.remaining, quota.limit, quota.resource, quota.reset_at
+            );
+        }
+    }
+
     fn generate_analysis_summary(
         &self,
-        metadata: &RepositoryMetadata,
+        remote: &RemoteRepositoryData,
         code_metrics: &CodeMetrics,
         project_info: &ProjectInfo,
         git_analysis: &GitAnalysis,
+        toolchain_requirements: &ToolchainRequirements,
+        readme_summary: Option<&ReadmeSummary>,
     ) -> String {
+        let metadata = &remote.metadata;
+        let status = &remote.status;
         let mut summary = Vec::new();
 
         summary.push(format!("Repository: {}", metadata.full_name));
         if let Some(description) = &metadata.description {
             summary.push(format!("Description: {}", description));
         }
+        if let Some(readme) = readme_summary {
+            summary.push(format!(
+                "About (from README): {}",
+                readme.sentences.join(" ")
+            ));
+            if !readme.sections.is_empty() {
+                summary.push(format!("README Sections: {}", readme.sections.join(", ")));
+            }
+        }
+        if status.is_mirror {
+            summary.push(format!(
+                "Mirror of: {}",
+                status
+                    .canonical_source
+                    .as_deref()
+                    .unwrap_or("unknown source")
+            ));
+        }
+        if status.read_only {
+            summary.push("Status: read-only (archived)".to_string());
+        }
 
-        summary.push(format!(
-            "Stars: {}, Forks: {}, Open Issues: {}",
-            metadata.stargazers_count, metadata.forks_count, metadata.open_issues_count
-        ));
+        if !remote.offline {
+            summary.push(format!(
+                "Stars: {}, Forks: {}, Open Issues: {}",
+                metadata.stargazers_count, metadata.forks_count, metadata.open_issues_count
+            ));
+        }
+
+        if let Some(fork) = &remote.fork_relationship {
+            summary.push(format!(
+                "Fork of {} ({}): {}",
+                fork.parent_full_name, fork.assessment, fork.note
+            ));
+        }
 
         if let Some(primary_lang) = &project_info.primary_language {
             summary.push(format!("Primary Language: {}", primary_lang));
         }
+        if let Some(requires) = toolchains::summary_line(toolchain_requirements) {
+            summary.push(requires);
+        }
+
+        let layout = &project_info.language_layout;
+        if !layout.boundaries.is_empty() && layout.classification != "single-language" {
+            let boundaries: Vec<String> = layout
+                .boundaries
+                .iter()
+                .map(|boundary| {
+                    format!("{}/ is {}", boundary.directory.display(), boundary.language)
+                })
+                .collect();
+            summary.push(format!(
+                "Language Layout: {} ({})",
+                layout.classification,
+                boundaries.join(", ")
+            ));
+        }
 
         summary.push(format!(
             "Total Files: {}, Lines of Code: {}, Size: {} KB",
@@ -176,17 +1176,50 @@ impl RepositoryAnalyzer {
             git_analysis.total_commits
         ));
 
+        // Activity isn't meaningful for repositories that can't change
+        if !status.read_only {
+            summary.push(format!(
+                "Recent Activity: {} commits in 30 days, {} in 90 days ({})",
+                git_analysis.activity.commits_last_30_days,
+                git_analysis.activity.commits_last_90_days,
+                git_analysis.activity.trend
+            ));
+        }
+        if let Some(quarter) = git_analysis
+            .commit_topics
+            .first()
+            .filter(|quarter| !quarter.topics.is_empty())
+        {
+            let topics: Vec<String> = quarter
+                .topics
+                .iter()
+                .map(|topic| format!("{} ({} commits)", topic.label, topic.commits))
+                .collect();
+            summary.push(format!(
+                "Recent Work ({}): {}",
+                quarter.quarter,
+                topics.join(", ")
+            ));
+        }
+
         if !project_info.frameworks.is_empty() {
             summary.push(format!(
                 "Frameworks: {}",
-                project_info.frameworks.join(", ")
+                describe_detections(&project_info.frameworks)
             ));
         }
 
         if !project_info.project_type.is_empty() {
             summary.push(format!(
                 "Project Types: {}",
-                project_info.project_type.join(", ")
+                describe_detections(&project_info.project_type)
+            ));
+        }
+
+        if !project_info.execution_environments.is_empty() {
+            summary.push(format!(
+                "Execution Environments: {}",
+                describe_detections(&project_info.execution_environments)
             ));
         }
 
@@ -201,6 +1234,26 @@ impl RepositoryAnalyzer {
             summary.push(format!("Languages: {}", top_languages.join(", ")));
         }
 
+        if !code_metrics.longest_functions.is_empty() {
+            let functions: Vec<String> = code_metrics
+                .longest_functions
+                .iter()
+                .take(3)
+                .map(|function| {
+                    format!(
+                        "{} ({}:{}, {} lines)",
+                        function.name,
+                        function.file.display(),
+                        function.start_line,
+                        function.lines_of_code
+                    )
+                })
+                .collect();
+            summary.push(format!("Longest Functions: {}", functions.join(", ")));
+        }
+
+        summary.extend(related_projects_line(&remote.related_projects));
+
         summary.join("\n")
     }
 
@@ -212,3 +1265,61 @@ impl RepositoryAnalyzer {
         Ok(serde_yaml::to_string(analysis)?)
     }
 }
+
+// Archive, disabled and mirror flags from the metadata. GitHub only sets
+// mirror_url for its own pull mirrors, so descriptions like "Mirror of ..." count too
+fn repository_status(metadata: &RepositoryMetadata) -> RepositoryStatus {
+    let description = metadata.description.as_deref().unwrap_or("");
+    let describes_mirror = Regex::new(r"(?i)\bmirror\b")
+        .map(|re| re.is_match(description))
+        .unwrap_or(false);
+    let is_mirror = metadata.mirror_url.is_some() || describes_mirror;
+
+    let canonical_source = metadata.mirror_url.clone().or_else(|| {
+        if !is_mirror {
+            return None;
+        }
+        description
+            .split_whitespace()
+            .map(|word| word.trim_end_matches(['.', ',', ')', ';']))
+            .find(|word| word.starts_with("https://") || word.starts_with("http://"))
+            .map(|url| url.to_string())
+            .or_else(|| metadata.homepage.clone().filter(|url| !url.is_empty()))
+    });
+
+    let mut notes = Vec::new();
+    if metadata.archived {
+        notes.push(format!(
+            "{} is archived: it is read-only and activity is not scored",
+            metadata.full_name
+        ));
+    }
+    if is_mirror {
+        notes.push(format!(
+            "{} is a mirror, development happens at {}",
+            metadata.full_name,
+            canonical_source.as_deref().unwrap_or("an unknown source")
+        ));
+    }
+
+    RepositoryStatus {
+        read_only: metadata.archived || metadata.disabled,
+        archived: metadata.archived,
+        disabled: metadata.disabled,
+        is_mirror,
+        canonical_source,
+        notes,
+    }
+}
+
+// The summary line naming the related projects, none without any
+fn related_projects_line(projects: &[RelatedProject]) -> Option<String> {
+    if projects.is_empty() {
+        return None;
+    }
+    let related: Vec<String> = projects
+        .iter()
+        .map(|project| format!("{} ({} stars)", project.full_name, project.stars))
+        .collect();
+    Some(format!("Related Projects: {}", related.join(", ")))
+}
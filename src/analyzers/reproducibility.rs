@@ -0,0 +1,335 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::analyzers::filesystem::FileSystemAnalyzer;
+use crate::file_index::FileIndex;
+use crate::types::{BuildReproducibility, DockerBaseImage, ManifestLock, ToolchainPin};
+
+// Manifest file name, ecosystem and the lockfiles that pin it
+const MANIFESTS: &[(&str, &str, &[&str])] = &[
+    ("Cargo.toml", "cargo", &["Cargo.lock"]),
+    (
+        "package.json",
+        "npm",
+        &[
+            "package-lock.json",
+            "npm-shrinkwrap.json",
+            "yarn.lock",
+            "pnpm-lock.yaml",
+            "bun.lockb",
+            "bun.lock",
+        ],
+    ),
+    (
+        "pyproject.toml",
+        "python",
+        &["poetry.lock", "uv.lock", "pdm.lock"],
+    ),
+    ("Pipfile", "pipenv", &["Pipfile.lock"]),
+    ("Gemfile", "bundler", &["Gemfile.lock"]),
+    ("go.mod", "go", &["go.sum"]),
+    ("composer.json", "composer", &["composer.lock"]),
+    ("pubspec.yaml", "dart", &["pubspec.lock"]),
+    ("mix.exs", "mix", &["mix.lock"]),
+    ("Package.swift", "swift", &["Package.resolved"]),
+    ("flake.nix", "nix", &["flake.lock"]),
+];
+
+// Files that pin a toolchain or runtime version
+const TOOLCHAIN_FILES: &[(&str, &str)] = &[
+    ("rust-toolchain.toml", "rust"),
+    ("rust-toolchain", "rust"),
+    (".nvmrc", "node"),
+    (".node-version", "node"),
+    (".python-version", "python"),
+    (".ruby-version", "ruby"),
+    (".go-version", "go"),
+    (".java-version", "java"),
+    (".sdkmanrc", "java"),
+    (".tool-versions", "asdf"),
+    (".terraform-version", "terraform"),
+];
+
+// Weights of the score components, out of 100
+const LOCKFILE_WEIGHT: f64 = 50.0;
+const TOOLCHAIN_WEIGHT: f64 = 25.0;
+const DOCKER_WEIGHT: f64 = 25.0;
+
+// Lockfile, toolchain pinning and Docker digest analyzer
+pub struct ReproducibilityAnalyzer {
+    from_line: Regex,
+}
+
+impl Default for ReproducibilityAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReproducibilityAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            from_line: Regex::new(r"(?im)^\s*FROM\s+(?:--platform=\S+\s+)?(\S+)(?:\s+AS\s+(\S+))?")
+                .unwrap(),
+        }
+    }
+
+    pub fn analyze_reproducibility(
+        &self,
+        repo_path: &Path,
+        file_structure: &FileIndex,
+        fs_analyzer: &FileSystemAnalyzer,
+    ) -> BuildReproducibility {
+        let paths: Vec<&PathBuf> = file_structure
+            .files()
+            .iter()
+            .map(|file| &file.path)
+            .collect();
+
+        let manifests = self.manifest_locks(repo_path, &paths, fs_analyzer);
+        let toolchain_pins = self.toolchain_pins(repo_path, &paths, fs_analyzer);
+        let docker_images = self.docker_images(repo_path, &paths, fs_analyzer);
+
+        let mut findings = Vec::new();
+        for manifest in manifests.iter().filter(|m| m.lockfile.is_none()) {
+            findings.push(format!(
+                "{} has no committed lockfile",
+                manifest.manifest.display()
+            ));
+        }
+        if toolchain_pins.is_empty() {
+            findings.push("No toolchain version file".to_string());
+        }
+        for image in docker_images.iter().filter(|i| !i.digest_pinned) {
+            findings.push(format!(
+                "{} uses {} without a digest",
+                image.path.display(),
+                image.image
+            ));
+        }
+
+        // Components that don't apply to the repository are left out of the score
+        let ratio = |pinned: usize, total: usize| (total > 0).then(|| pinned as f64 / total as f64);
+        let components = [
+            (
+                LOCKFILE_WEIGHT,
+                ratio(
+                    manifests.iter().filter(|m| m.lockfile.is_some()).count(),
+                    manifests.len(),
+                ),
+            ),
+            (
+                TOOLCHAIN_WEIGHT,
+                Some(if toolchain_pins.is_empty() { 0.0 } else { 1.0 }),
+            ),
+            (
+                DOCKER_WEIGHT,
+                ratio(
+                    docker_images.iter().filter(|i| i.digest_pinned).count(),
+                    docker_images.len(),
+                ),
+            ),
+        ];
+        let (applicable, earned) = components
+            .iter()
+            .filter_map(|(weight, ratio)| ratio.map(|ratio| (weight, weight * ratio)))
+            .fold((0.0, 0.0), |(applicable, earned), (weight, points)| {
+                (applicable + weight, earned + points)
+            });
+
+        BuildReproducibility {
+            manifests,
+            toolchain_pins,
+            docker_images,
+            score: earned / applicable * 100.0,
+            findings,
+        }
+    }
+
+    // A lockfile next to the manifest or in a parent directory (workspaces) counts
+    fn manifest_locks(
+        &self,
+        repo_path: &Path,
+        paths: &[&PathBuf],
+        fs_analyzer: &FileSystemAnalyzer,
+    ) -> Vec<ManifestLock> {
+        let mut manifests = Vec::new();
+        let known: HashSet<&PathBuf> = paths.iter().copied().collect();
+
+        for path in paths.iter().copied() {
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+
+            if file_name == "requirements.txt" {
+                // Fully pinned requirements files are their own lock
+                let pinned = fs_analyzer
+                    .read_repo_file(repo_path, path)
+                    .map(|content| self.requirements_pinned(&content))
+                    .unwrap_or(false);
+                manifests.push(ManifestLock {
+                    manifest: path.clone(),
+                    ecosystem: "pip".to_string(),
+                    lockfile: pinned.then(|| path.clone()),
+                });
+                continue;
+            }
+
+            let Some((_, ecosystem, lockfiles)) = MANIFESTS
+                .iter()
+                .find(|(manifest, _, _)| *manifest == file_name)
+            else {
+                continue;
+            };
+
+            let lockfile = path.ancestors().skip(1).find_map(|dir| {
+                lockfiles
+                    .iter()
+                    .map(|lockfile| dir.join(lockfile))
+                    .find(|candidate| known.contains(candidate))
+            });
+
+            manifests.push(ManifestLock {
+                manifest: path.clone(),
+                ecosystem: ecosystem.to_string(),
+                lockfile,
+            });
+        }
+
+        manifests
+    }
+
+    fn requirements_pinned(&self, content: &str) -> bool {
+        let requirements: Vec<&str> = content
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or("").trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('-'))
+            .collect();
+
+        !requirements.is_empty()
+            && requirements
+                .iter()
+                .all(|line| line.contains("==") || line.contains("--hash"))
+    }
+
+    fn toolchain_pins(
+        &self,
+        repo_path: &Path,
+        paths: &[&PathBuf],
+        fs_analyzer: &FileSystemAnalyzer,
+    ) -> Vec<ToolchainPin> {
+        let mut pins = Vec::new();
+
+        for path in paths
+            .iter()
+            .copied()
+            .filter(|path| path.components().count() <= 2)
+        {
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let Some((_, tool)) = TOOLCHAIN_FILES.iter().find(|(name, _)| *name == file_name)
+            else {
+                continue;
+            };
+
+            let content = fs_analyzer
+                .read_repo_file(repo_path, path)
+                .unwrap_or_default();
+            let version = match file_name {
+                "rust-toolchain.toml" => {
+                    content.parse::<toml::Value>().ok().and_then(|toolchain| {
+                        toolchain
+                            .get("toolchain")?
+                            .get("channel")?
+                            .as_str()
+                            .map(|s| s.to_string())
+                    })
+                }
+                _ => content
+                    .lines()
+                    .map(|line| line.trim())
+                    .find(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| line.to_string()),
+            };
+
+            pins.push(ToolchainPin {
+                path: path.clone(),
+                tool: tool.to_string(),
+                version,
+            });
+        }
+
+        // go.mod can pin the toolchain itself
+        for path in paths
+            .iter()
+            .copied()
+            .filter(|path| path.ends_with("go.mod"))
+        {
+            if let Some(toolchain) =
+                fs_analyzer
+                    .read_repo_file(repo_path, path)
+                    .and_then(|content| {
+                        content
+                            .lines()
+                            .find_map(|line| line.trim().strip_prefix("toolchain "))
+                            .map(|version| version.trim().to_string())
+                    })
+            {
+                pins.push(ToolchainPin {
+                    path: path.clone(),
+                    tool: "go".to_string(),
+                    version: Some(toolchain),
+                });
+            }
+        }
+
+        pins
+    }
+
+    // External base images of every Dockerfile; `FROM <stage>` and scratch are skipped
+    fn docker_images(
+        &self,
+        repo_path: &Path,
+        paths: &[&PathBuf],
+        fs_analyzer: &FileSystemAnalyzer,
+    ) -> Vec<DockerBaseImage> {
+        let mut images = Vec::new();
+
+        for path in paths.iter().copied() {
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let file_name = file_name.to_lowercase();
+            if !file_name.starts_with("dockerfile") && !file_name.ends_with(".dockerfile") {
+                continue;
+            }
+            let Some(content) = fs_analyzer.read_repo_file(repo_path, path) else {
+                continue;
+            };
+
+            let mut stages: Vec<String> = Vec::new();
+            for captures in self.from_line.captures_iter(&content) {
+                let image = captures[1].to_string();
+                let is_stage = stages
+                    .iter()
+                    .any(|stage| stage.eq_ignore_ascii_case(&image));
+
+                if !is_stage && image != "scratch" {
+                    images.push(DockerBaseImage {
+                        path: path.clone(),
+                        digest_pinned: image.contains("@sha256:"),
+                        image,
+                    });
+                }
+                if let Some(stage) = captures.get(2) {
+                    stages.push(stage.as_str().to_string());
+                }
+            }
+        }
+
+        images
+    }
+}
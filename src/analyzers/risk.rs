@@ -0,0 +1,475 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use crate::types::{AffiliationPeriod, RepositoryAnalysis, RiskAssessment, RiskCategory};
+use crate::utils::distinct_labels;
+
+// Licenses by GitHub key that require derived work to be released the same way
+const STRONG_COPYLEFT_LICENSES: &[&str] = &["agpl-3.0", "sspl-1.0"];
+const COPYLEFT_LICENSES: &[&str] = &["gpl-2.0", "gpl-3.0"];
+const WEAK_COPYLEFT_LICENSES: &[&str] = &["lgpl-2.1", "lgpl-3.0", "mpl-2.0", "epl-1.0", "epl-2.0"];
+
+// Days without commits after which maintenance is a medium and a high risk
+const STALE_DAYS: i64 = 180;
+const ABANDONED_DAYS: i64 = 365;
+
+// Unpinned dependencies at which dependency health is a high risk
+const MANY_UNPINNED_DEPENDENCIES: usize = 5;
+
+// Share of the top contributors' commits at which one organization backs the project
+const BACKING_ORGANIZATION_SHARE: f64 = 0.5;
+
+// Share of a quarter's commits at which one organization controls development
+const DOMINANT_ORGANIZATION_SHARE: f64 = 0.75;
+
+// Quarters with fewer commits say little about who drives development
+const MIN_QUARTER_COMMITS: u32 = 10;
+
+// Rates security, maintenance, bus factor, license, dependency and
+// sustainability risks of an
+// analysis as low, medium or high, each with the evidence behind it
+pub struct RiskAssessor;
+
+impl RiskAssessor {
+    pub fn assess(&self, analysis: &RepositoryAnalysis) -> RiskAssessment {
+        let bus_factor = bus_factor(analysis);
+        let mut categories = vec![self.security(analysis), self.maintenance(analysis)];
+        if let Some(bus_factor) = bus_factor {
+            categories.push(self.bus_factor(bus_factor));
+        }
+        categories.push(self.license(analysis));
+        categories.push(self.dependencies(analysis));
+        categories.push(self.sustainability(analysis));
+
+        RiskAssessment {
+            overall: overall_level(&categories),
+            bus_factor,
+            categories,
+        }
+    }
+
+    fn security(&self, analysis: &RepositoryAnalysis) -> RiskCategory {
+        let security = &analysis.security_info;
+        let mut evidence = Vec::new();
+        let mut level = "low";
+
+        if !security.vulnerability_alerts.is_empty() {
+            level = "high";
+            evidence.push(format!(
+                "{} known vulnerabilities affect the dependencies.",
+                security.vulnerability_alerts.len()
+            ));
+        }
+        if !security.secret_findings.is_empty() {
+            level = "high";
+            evidence.push(format!(
+                "{} possible secrets are committed to the repository.",
+                security.secret_findings.len()
+            ));
+        }
+        // Publishing advisories shows a working disclosure process, publishing
+        // them without a fix leaves users nothing to upgrade to
+        if let Some(advisories) = &security.published_advisories
+            && !advisories.advisories.is_empty()
+        {
+            evidence.push(format!(
+                "The project published {} security advisories about its own code.",
+                advisories.advisories.len()
+            ));
+            if advisories.unpatched > 0 {
+                level = raise(level, "medium");
+                evidence.push(format!(
+                    "{} published advisories list no patched version.",
+                    advisories.unpatched
+                ));
+            }
+        }
+        // A download that doesn't hash to its published checksum is a broken
+        // or tampered release
+        if let Some(check) = &analysis.release_hygiene.verification
+            && !check.verified
+        {
+            level = "high";
+            evidence.push(format!(
+                "{} of {} does not match its published checksum.",
+                check.asset, check.tag_name
+            ));
+        }
+        if security.ci_security_checks.is_empty() {
+            level = raise(level, "medium");
+            evidence.push(
+                "CI runs no security checks such as CodeQL, Dependabot or cargo-audit.".to_string(),
+            );
+        } else {
+            evidence.push(format!(
+                "CI runs security checks: {}.",
+                security.ci_security_checks.join(", ")
+            ));
+        }
+        // Testing beyond examples, which finds the inputs nobody thought of
+        let depth = &security.testing_depth;
+        let fuzzing_services = distinct_labels(&depth.continuous_fuzzing);
+        if !depth.fuzz_targets.is_empty() {
+            evidence.push(format!(
+                "{} fuzz targets are written with {}{}.",
+                depth.fuzz_targets.len(),
+                distinct_labels(&depth.fuzz_targets).join(", "),
+                if fuzzing_services.is_empty() {
+                    String::new()
+                } else {
+                    format!(" and run continuously by {}", fuzzing_services.join(", "))
+                }
+            ));
+        } else if !fuzzing_services.is_empty() {
+            evidence.push(format!(
+                "The project is fuzzed continuously by {}.",
+                fuzzing_services.join(", ")
+            ));
+        }
+        if !depth.property_testing.is_empty() {
+            evidence.push(format!(
+                "Property-based tests use {}.",
+                distinct_labels(&depth.property_testing).join(", ")
+            ));
+        }
+        if depth.sast.is_empty() {
+            evidence.push("No static analysis (SAST) runs in CI.".to_string());
+        } else {
+            evidence.push(format!(
+                "Static analysis runs in CI: {}.",
+                distinct_labels(&depth.sast).join(", ")
+            ));
+        }
+        if !security.has_security_policy {
+            evidence.push("There is no security policy for reporting vulnerabilities.".to_string());
+        }
+        if let Some(unsafe_usage) = &security.unsafe_usage
+            && unsafe_usage.total_unsafe_blocks + unsafe_usage.total_unsafe_functions > 0
+        {
+            evidence.push(format!(
+                "The Rust code has {} unsafe blocks and {} unsafe functions to review.",
+                unsafe_usage.total_unsafe_blocks, unsafe_usage.total_unsafe_functions
+            ));
+        }
+
+        category("security", level, evidence)
+    }
+
+    fn maintenance(&self, analysis: &RepositoryAnalysis) -> RiskCategory {
+        let status = &analysis.repository_status;
+        let activity = &analysis.git_analysis.activity;
+        let mut evidence = Vec::new();
+        let mut level = "low";
+
+        if status.archived || status.read_only {
+            level = "high";
+            evidence
+                .push("The repository is archived and receives no further changes.".to_string());
+        }
+        match analysis.git_analysis.last_commit_date {
+            Some(last_commit) => {
+                let days = (Utc::now() - last_commit).num_days();
+                if days > ABANDONED_DAYS {
+                    level = "high";
+                } else if days > STALE_DAYS {
+                    level = raise(level, "medium");
+                }
+                evidence.push(format!("The last commit was {} days ago.", days));
+            }
+            None => {
+                level = raise(level, "medium");
+                evidence.push("The commit history could not be read.".to_string());
+            }
+        }
+        if activity.trend == "declining" {
+            level = raise(level, "medium");
+        }
+        if !activity.trend.is_empty() {
+            evidence.push(format!(
+                "{} commits in the last 90 days against {} in the 90 days before ({}).",
+                activity.commits_last_90_days, activity.commits_previous_90_days, activity.trend
+            ));
+        }
+
+        category("maintenance", level, evidence)
+    }
+
+    fn bus_factor(&self, bus_factor: u32) -> RiskCategory {
+        let level = match bus_factor {
+            0 | 1 => "high",
+            2 => "medium",
+            _ => "low",
+        };
+        let evidence = if bus_factor <= 1 {
+            "A single contributor wrote most of the commits.".to_string()
+        } else {
+            format!(
+                "{} contributors together wrote most of the commits.",
+                bus_factor
+            )
+        };
+        category("bus-factor", level, vec![evidence])
+    }
+
+    fn license(&self, analysis: &RepositoryAnalysis) -> RiskCategory {
+        let mut evidence = Vec::new();
+        let mut level = match &analysis.metadata.license {
+            None => {
+                evidence.push(
+                    "No license was detected, so all rights are reserved by default.".to_string(),
+                );
+                "high"
+            }
+            Some(license) => {
+                let key = license.key.to_lowercase();
+                if STRONG_COPYLEFT_LICENSES.contains(&key.as_str()) {
+                    evidence.push(format!(
+                        "{} requires releasing the source of derived work, including network services.",
+                        license.name
+                    ));
+                    "high"
+                } else if COPYLEFT_LICENSES.contains(&key.as_str()) {
+                    evidence.push(format!(
+                        "{} requires releasing derived work under the same license.",
+                        license.name
+                    ));
+                    "medium"
+                } else if key == "other" || key == "noassertion" {
+                    evidence.push(
+                        "The license is not a recognized open source license and needs legal review."
+                            .to_string(),
+                    );
+                    "medium"
+                } else if WEAK_COPYLEFT_LICENSES.contains(&key.as_str()) {
+                    evidence.push(format!(
+                        "{} is a weak copyleft license: changes to its own files must be shared.",
+                        license.name
+                    ));
+                    "low"
+                } else {
+                    evidence.push(format!("{} is a permissive license.", license.name));
+                    "low"
+                }
+            }
+        };
+
+        let compatibility = &analysis.security_info.license_compatibility;
+        if !compatibility.is_empty() {
+            level = raise(level, "medium");
+            evidence.extend(compatibility.iter().cloned());
+        }
+
+        category("license", level, evidence)
+    }
+
+    fn dependencies(&self, analysis: &RepositoryAnalysis) -> RiskCategory {
+        let security = &analysis.security_info;
+        let declared: usize = analysis
+            .config_files
+            .iter()
+            .filter_map(|config| config.parsed_dependencies.as_ref())
+            .map(|deps| deps.len())
+            .sum();
+        if declared == 0 {
+            return category(
+                "dependencies",
+                "low",
+                vec!["No dependencies are declared in the detected manifests.".to_string()],
+            );
+        }
+
+        let mut evidence = vec![format!("{} dependencies are declared.", declared)];
+        let unpinned = security.outdated_dependencies.len();
+        let mut level = if unpinned >= MANY_UNPINNED_DEPENDENCIES {
+            "high"
+        } else if unpinned > 0 {
+            "medium"
+        } else {
+            "low"
+        };
+        if unpinned > 0 {
+            evidence.push(format!(
+                "{} dependencies accept any version (* or latest).",
+                unpinned
+            ));
+        }
+        if !security.has_dependabot {
+            level = raise(level, "medium");
+            evidence.push("No automated dependency updates are configured.".to_string());
+        }
+
+        category("dependencies", level, evidence)
+    }
+
+    // Nobody paid, employed or hosting the project to maintain it is a medium
+    // risk, any one of them is enough for low unless one organization
+    // dominates recent development
+    fn sustainability(&self, analysis: &RepositoryAnalysis) -> RiskCategory {
+        let sustainability = &analysis.sustainability;
+        let mut evidence = Vec::new();
+
+        if let Some(foundation) = &sustainability.foundation {
+            evidence.push(format!(
+                "The project names the {} in {}.",
+                foundation.foundation,
+                foundation.source.display()
+            ));
+        }
+        if !sustainability.funding.is_empty() {
+            let mut platforms: Vec<&str> = sustainability
+                .funding
+                .iter()
+                .map(|link| link.platform.as_str())
+                .collect();
+            platforms.sort();
+            platforms.dedup();
+            evidence.push(format!(
+                "The project accepts funding through {}.",
+                platforms.join(", ")
+            ));
+        }
+        match sustainability.affiliations.first() {
+            Some(top) if top.share >= BACKING_ORGANIZATION_SHARE => evidence.push(format!(
+                "{:.0}% of the top contributors' commits come from {} addresses.",
+                top.share * 100.0,
+                top.domain
+            )),
+            Some(_) => {
+                let domains: Vec<&str> = sustainability
+                    .affiliations
+                    .iter()
+                    .map(|share| share.domain.as_str())
+                    .collect();
+                evidence.push(format!(
+                    "Top contributors commit from organization addresses: {}.",
+                    domains.join(", ")
+                ));
+            }
+            None => {}
+        }
+
+        let mut level = if evidence.is_empty() {
+            evidence.push("No funding, backing organization or foundation was found.".to_string());
+            "medium"
+        } else {
+            "low"
+        };
+        if let Some(dominance) = dominance(&sustainability.affiliation_history) {
+            level = raise(level, "medium");
+            evidence.push(dominance);
+        }
+        if sustainability.governance_files.is_empty() {
+            evidence.push("Governance is not documented.".to_string());
+        }
+
+        category("sustainability", level, evidence)
+    }
+}
+
+// One organization writing most of the latest significant quarter's commits,
+// compared with the earliest one so adopters see whether that is new
+fn dominance(history: &[AffiliationPeriod]) -> Option<String> {
+    let significant: Vec<&AffiliationPeriod> = history
+        .iter()
+        .filter(|period| period.commits >= MIN_QUARTER_COMMITS)
+        .collect();
+    let latest = significant.last()?;
+    let top = latest.organizations.first()?;
+    if top.share < DOMINANT_ORGANIZATION_SHARE {
+        return None;
+    }
+
+    let mut evidence = format!(
+        "{} wrote {:.0}% of the commits in {}",
+        top.domain,
+        top.share * 100.0,
+        latest.period
+    );
+    if let Some(earliest) = significant.first().filter(|p| p.period != latest.period) {
+        let before = earliest
+            .organizations
+            .iter()
+            .find(|org| org.domain == top.domain)
+            .map_or(0.0, |org| org.share);
+        evidence.push_str(&format!(
+            ", against {:.0}% in {}",
+            before * 100.0,
+            earliest.period
+        ));
+    }
+    evidence.push_str(", a single-vendor governance risk.");
+    Some(evidence)
+}
+
+// Fewest contributors who wrote more than half of the commits, from the
+// people in the history read (identities merged), else the GitHub
+// contribution counts or else the recent commits
+fn bus_factor(analysis: &RepositoryAnalysis) -> Option<u32> {
+    let git = &analysis.git_analysis;
+    let contributors = if git.history_contributors.is_empty() {
+        &git.contributors
+    } else {
+        &git.history_contributors
+    };
+    let mut counts: Vec<u32> = contributors
+        .iter()
+        .filter_map(|contributor| contributor.contributions)
+        .collect();
+    if counts.is_empty() {
+        let mut authors: HashMap<&str, u32> = HashMap::new();
+        for commit in &git.recent_commits {
+            *authors.entry(commit.author.login.as_str()).or_insert(0) += 1;
+        }
+        counts = authors.into_values().collect();
+    }
+    let total: u32 = counts.iter().sum();
+    if total == 0 {
+        return None;
+    }
+
+    counts.sort_unstable_by(|a, b| b.cmp(a));
+    let mut covered = 0;
+    for (index, count) in counts.iter().enumerate() {
+        covered += count;
+        if covered * 2 > total {
+            return Some(index as u32 + 1);
+        }
+    }
+    Some(counts.len() as u32)
+}
+
+// The highest level of the categories
+pub fn overall_level(categories: &[RiskCategory]) -> String {
+    categories
+        .iter()
+        .map(|category| category.level.as_str())
+        .max_by_key(|level| level_rank(level))
+        .unwrap_or("low")
+        .to_string()
+}
+
+fn category(name: &str, level: &str, evidence: Vec<String>) -> RiskCategory {
+    RiskCategory {
+        category: name.to_string(),
+        level: level.to_string(),
+        evidence,
+    }
+}
+
+fn level_rank(level: &str) -> u8 {
+    match level {
+        "high" => 2,
+        "medium" => 1,
+        _ => 0,
+    }
+}
+
+// The higher of two levels
+fn raise<'a>(level: &'a str, to: &'a str) -> &'a str {
+    if level_rank(to) > level_rank(level) {
+        to
+    } else {
+        level
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::file_index::FileIndex;
+use crate::types::RuleFinding;
+
+const SEVERITIES: &[&str] = &["low", "medium", "high"];
+
+// Findings kept per rule, a rule matching everything shouldn't drown the report
+#[cfg_attr(not(feature = "rhai-rules"), allow(dead_code))]
+const MAX_FINDINGS_PER_RULE: usize = 200;
+
+// One `[[rules]]` entry of analyzer.toml
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConfig {
+    pub id: String,
+    #[serde(default = "default_severity")]
+    pub severity: String,
+    pub script: Option<PathBuf>, // Relative to the config file
+    pub source: Option<String>,  // Inline script instead of a file
+    #[serde(default)]
+    pub languages: Vec<String>, // Only files in these languages, all when empty
+}
+
+fn default_severity() -> String {
+    "medium".to_string()
+}
+
+#[cfg_attr(not(feature = "rhai-rules"), allow(dead_code))]
+struct Rule {
+    id: String,
+    severity: String,
+    languages: Vec<String>,
+    source: String,
+}
+
+// Custom detection rules written in Rhai, listed as `[[rules]]` in
+// analyzer.toml. Each script runs once per source file with a `file` map in
+// scope (path, name, extension, language, lines_of_code, complexity, size,
+// imports, functions) and returns nothing, a message, a `#{ line, message }`
+// map or an array of those. Scripts have no file system or network access and
+// run within operation limits
+#[derive(Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    // Findings of every rule over every indexed file, in rule order
+    pub fn evaluate(
+        &self,
+        index: &FileIndex,
+        imports: &HashMap<PathBuf, Vec<String>>,
+    ) -> Vec<RuleFinding> {
+        if self.rules.is_empty() {
+            return Vec::new();
+        }
+        evaluate_rhai(&self.rules, index, imports)
+    }
+}
+
+// Scripts must exist and the engine must be built in when any rule is listed
+pub fn load_rules(config: &[RuleConfig]) -> Result<RuleSet> {
+    if config.is_empty() {
+        return Ok(RuleSet::default());
+    }
+
+    let mut rules = Vec::new();
+    for rule in config {
+        if rules.iter().any(|r: &Rule| r.id == rule.id) {
+            anyhow::bail!("rule {} is defined twice", rule.id);
+        }
+        let severity = rule.severity.to_lowercase();
+        if !SEVERITIES.contains(&severity.as_str()) {
+            anyhow::bail!(
+                "rule {} has an unknown severity {}, expected one of {}",
+                rule.id,
+                rule.severity,
+                SEVERITIES.join(", ")
+            );
+        }
+        let source = match (&rule.script, &rule.source) {
+            (Some(script), None) => fs::read_to_string(script)
+                .with_context(|| format!("rule {} script {}", rule.id, script.display()))?,
+            (None, Some(source)) => source.clone(),
+            _ => anyhow::bail!("rule {} needs exactly one of script or source", rule.id),
+        };
+        rules.push(Rule {
+            id: rule.id.clone(),
+            severity,
+            languages: rule.languages.iter().map(|l| l.to_lowercase()).collect(),
+            source,
+        });
+    }
+    if !cfg!(feature = "rhai-rules") {
+        anyhow::bail!(
+            "custom rules are not part of this build, rebuild with --features rhai-rules"
+        );
+    }
+    Ok(RuleSet { rules })
+}
+
+#[cfg(feature = "rhai-rules")]
+fn evaluate_rhai(
+    rules: &[Rule],
+    index: &FileIndex,
+    imports: &HashMap<PathBuf, Vec<String>>,
+) -> Vec<RuleFinding> {
+    use log::{info, warn};
+    use rhai::{AST, Dynamic, Engine, Scope};
+
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(1_000_000)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(1 << 20)
+        .set_max_array_size(100_000)
+        .set_max_map_size(10_000);
+    engine.on_print(|text| info!("rule: {}", text));
+    engine.on_debug(|text, _, _| log::debug!("rule: {}", text));
+
+    let compiled: Vec<(&Rule, AST)> = rules
+        .iter()
+        .filter_map(|rule| match engine.compile(&rule.source) {
+            Ok(ast) => Some((rule, ast)),
+            Err(e) => {
+                warn!("Rule {} doesn't compile: {}", rule.id, e);
+                None
+            }
+        })
+        .collect();
+
+    let mut findings = Vec::new();
+    for (rule, ast) in compiled {
+        info!("Evaluating rule {}...", rule.id);
+        let mut matched = 0;
+        for file in index.files() {
+            let language = file.language.as_deref().map(str::to_lowercase);
+            if !rule.languages.is_empty()
+                && !language.is_some_and(|language| rule.languages.contains(&language))
+            {
+                continue;
+            }
+
+            let mut map = rhai::Map::new();
+            map.insert(
+                "path".into(),
+                file.path.to_string_lossy().replace('\\', "/").into(),
+            );
+            map.insert("name".into(), file.name.clone().into());
+            map.insert("extension".into(), optional(file.extension.clone()));
+            map.insert("language".into(), optional(file.language.clone()));
+            map.insert("size".into(), (file.size as i64).into());
+            map.insert(
+                "lines_of_code".into(),
+                optional(file.lines_of_code.map(i64::from)),
+            );
+            map.insert(
+                "complexity".into(),
+                optional(file.complexity.map(i64::from)),
+            );
+            let file_imports: rhai::Array = imports
+                .get(&file.path)
+                .map(|imports| imports.iter().map(|i| i.clone().into()).collect())
+                .unwrap_or_default();
+            map.insert("imports".into(), file_imports.into());
+            map.insert(
+                "functions".into(),
+                rhai::serde::to_dynamic(&file.functions).unwrap_or(Dynamic::UNIT),
+            );
+            let mut scope = Scope::new();
+            scope.push_constant("file", map);
+
+            let result = match engine.eval_ast_with_scope::<Dynamic>(&mut scope, &ast) {
+                Ok(result) => result,
+                Err(e) => {
+                    // One broken script shouldn't flood the log with an error per file
+                    warn!(
+                        "Rule {} failed on {}, skipping it: {}",
+                        rule.id,
+                        file.path.display(),
+                        e
+                    );
+                    break;
+                }
+            };
+            let results = if result.is_array() {
+                result.cast::<rhai::Array>()
+            } else {
+                vec![result]
+            };
+            for result in results {
+                let Some((line, message)) = to_match(result) else {
+                    continue;
+                };
+                matched += 1;
+                if matched <= MAX_FINDINGS_PER_RULE {
+                    findings.push(RuleFinding {
+                        rule: rule.id.clone(),
+                        severity: rule.severity.clone(),
+                        path: file.path.clone(),
+                        line,
+                        message,
+                        url: None,
+                    });
+                }
+            }
+        }
+        if matched > MAX_FINDINGS_PER_RULE {
+            warn!(
+                "Rule {} matched {} times, keeping the first {}",
+                rule.id, matched, MAX_FINDINGS_PER_RULE
+            );
+        }
+    }
+    findings
+}
+
+#[cfg(feature = "rhai-rules")]
+fn optional<T: Into<rhai::Dynamic>>(value: Option<T>) -> rhai::Dynamic {
+    value.map_or(rhai::Dynamic::UNIT, Into::into)
+}
+
+// A message, or a map with a message and optionally a line. Anything else,
+// unit included, is no match
+#[cfg(feature = "rhai-rules")]
+fn to_match(result: rhai::Dynamic) -> Option<(Option<u32>, String)> {
+    if result.is_string() {
+        return Some((None, result.into_string().ok()?));
+    }
+    let map = result.try_cast::<rhai::Map>()?;
+    let message = map.get("message")?.clone().into_string().ok()?;
+    let line = map
+        .get("line")
+        .and_then(|line| line.as_int().ok())
+        .and_then(|line| u32::try_from(line).ok());
+    Some((line, message))
+}
+
+// load_rules refuses rules without the engine
+#[cfg(not(feature = "rhai-rules"))]
+fn evaluate_rhai(
+    _rules: &[Rule],
+    _index: &FileIndex,
+    _imports: &HashMap<PathBuf, Vec<String>>,
+) -> Vec<RuleFinding> {
+    Vec::new()
+}
@@ -0,0 +1,211 @@
+use std::cell::OnceCell;
+use std::fs::File;
+use std::io::{self, Read};
+use std::ops::Deref;
+use std::path::Path;
+
+use log::debug;
+use memmap2::Mmap;
+
+// Depth down to which classifiers other than the structure one see files
+// in directories the structure scan skips (.github, build output, ...)
+pub const METADATA_DEPTH: usize = 3;
+
+// Files from this size on are mapped instead of read, smaller ones are
+// cheaper to copy than to map
+const MMAP_THRESHOLD: u64 = 64 * 1024;
+
+// Longest string literal kept by strip_comments, "C-unwind" and
+// "system-unwind" fit
+const MAX_ABI_LEN: usize = 16;
+
+// Bytes of a whole file: mapped when large, read into the heap when small,
+// when the platform or file system can't map it, or when the tree isn't the
+// analyzer's own
+pub enum FileBytes {
+    Mapped(Mmap),
+    Read(Vec<u8>),
+}
+
+impl FileBytes {
+    // `map` is only set for clones in the work directory. A user's checkout
+    // can be truncated by an editor or a build while it's scanned, and reading
+    // past the end of a truncated map is a SIGBUS
+    pub fn open(path: &Path, map: bool) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let size = file.metadata()?.len();
+
+        if map && size >= MMAP_THRESHOLD {
+            // SAFETY: the map is read-only and dropped once the file is
+            // classified. It's only made for clones the analyzer owns, which
+            // nothing else writes to while they're scanned
+            match unsafe { Mmap::map(&file) } {
+                Ok(map) => return Ok(FileBytes::Mapped(map)),
+                Err(e) => debug!("Can't map {:?}, reading it instead: {}", path, e),
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(size as usize);
+        file.read_to_end(&mut bytes)?;
+        Ok(FileBytes::Read(bytes))
+    }
+}
+
+impl Deref for FileBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            FileBytes::Mapped(map) => map,
+            FileBytes::Read(bytes) => bytes,
+        }
+    }
+}
+
+// A file reached by the shared walk. Its bytes are read (or mapped) at most
+// once, on the first classifier asking for them
+pub struct ScannedFile<'a> {
+    pub path: &'a Path,     // Absolute, for reading
+    pub relative: &'a Path, // Repository-relative, with forward slashes
+    pub depth: usize,       // 1 for files at the repository root
+    pub in_structure: bool, // False below directories the structure scan skips
+    max_file_size: u64,
+    map: bool,
+    bytes: OnceCell<Option<FileBytes>>,
+}
+
+impl<'a> ScannedFile<'a> {
+    pub fn new(
+        path: &'a Path,
+        relative: &'a Path,
+        depth: usize,
+        in_structure: bool,
+        max_file_size: u64,
+        map: bool,
+    ) -> Self {
+        Self {
+            path,
+            relative,
+            depth,
+            in_structure,
+            max_file_size,
+            map,
+            bytes: OnceCell::new(),
+        }
+    }
+
+    pub fn file_name(&self) -> &'a str {
+        self.path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("")
+    }
+
+    // The whole file, whatever its size
+    pub fn bytes(&self) -> Option<&[u8]> {
+        self.bytes
+            .get_or_init(|| FileBytes::open(self.path, self.map).ok())
+            .as_deref()
+    }
+
+    // UTF-8 content within the size limit
+    pub fn content(&self) -> Option<&str> {
+        let bytes = self.bytes()?;
+        if bytes.len() as u64 > self.max_file_size {
+            return None;
+        }
+        std::str::from_utf8(bytes).ok()
+    }
+}
+
+// Registered with the walk to pick what it needs out of each file. The walk
+// visits a directory's files before its subdirectories
+pub trait FileClassifier {
+    // Called before the files of a subdirectory, `in_structure` as for files
+    fn enter_directory(&mut self, _path: &Path, _in_structure: bool) {}
+
+    // Called once a subdirectory is done, `completed` is false when reading it failed
+    fn leave_directory(&mut self, _in_structure: bool, _completed: bool) {}
+
+    fn classify(&mut self, file: &ScannedFile<'_>);
+}
+
+// Drops comments and string and char contents of Rust and C-family sources
+// so none counts as code. Short identifier-like strings are kept, an ABI such
+// as "C" is one, and so are line breaks
+pub fn strip_comments(content: &str) -> String {
+    let mut code = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        code.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut depth = 1;
+                while depth > 0 {
+                    match chars.next() {
+                        Some('*') if chars.peek() == Some(&'/') => {
+                            chars.next();
+                            depth -= 1;
+                        }
+                        Some('/') if chars.peek() == Some(&'*') => {
+                            chars.next();
+                            depth += 1;
+                        }
+                        Some('\n') => code.push('\n'),
+                        Some(_) => {}
+                        None => break,
+                    }
+                }
+            }
+            // A char literal, emptied, or a lifetime which is left as is
+            '\'' => {
+                code.push(c);
+                let mut lookahead = chars.clone();
+                let literal_len = match (lookahead.next(), lookahead.next()) {
+                    (Some('\\'), _) => lookahead.position(|c| c == '\'').map(|end| end + 3),
+                    (Some(_), Some('\'')) => Some(2),
+                    _ => None,
+                };
+                if let Some(len) = literal_len {
+                    chars.nth(len - 1);
+                    code.push('\'');
+                }
+            }
+            '"' => {
+                let mut literal = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => literal.push(c),
+                    }
+                }
+                let abi_like = literal.len() <= MAX_ABI_LEN
+                    && literal
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+                code.push('"');
+                if abi_like {
+                    code.push_str(&literal);
+                } else {
+                    code.extend(literal.chars().filter(|c| *c == '\n'));
+                }
+                code.push('"');
+            }
+            _ => code.push(c),
+        }
+    }
+
+    code
+}
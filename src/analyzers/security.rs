@@ -1,26 +1,88 @@
-use crate::types::{ConfigFile, DirectoryInfo, FileInfo, SecurityInfo};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::analyzers::scan::{FileClassifier, ScannedFile};
+use crate::analyzers::testing_depth::{self, TestingDepthClassifier};
+use crate::analyzers::unsafe_code::UnsafeCodeClassifier;
+use crate::file_index::FileIndex;
+use crate::types::{
+    AdvisorySummary, ConfigFile, EvidenceRef, PublishedAdvisory, SecretFinding, SecurityInfo,
+    SecurityScan,
+};
+use crate::utils::manifest_line;
+
+// Credential formats with a recognizable prefix, as (kind, pattern)
+const SECRET_PATTERNS: &[(&str, &str)] = &[
+    ("aws-access-key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    ("github-token", r"\bgh[pousr]_[A-Za-z0-9]{36,}\b"),
+    ("github-token", r"\bgithub_pat_[A-Za-z0-9_]{80,}"),
+    (
+        "private-key",
+        r"-----BEGIN (?:RSA |EC |DSA |OPENSSH |PGP )?PRIVATE KEY(?: BLOCK)?-----",
+    ),
+    ("slack-token", r"\bxox[abprs]-[0-9A-Za-z-]{10,}"),
+    ("google-api-key", r"\bAIza[0-9A-Za-z_\-]{35}\b"),
+    ("stripe-live-key", r"\b[rs]k_live_[0-9A-Za-z]{20,}\b"),
+];
+
+// Workflow content markers of security checks run in CI, as (marker, check)
+const CI_SECURITY_MARKERS: &[(&str, &str)] = &[
+    ("github/codeql-action", "codeql"),
+    ("actions/dependency-review-action", "dependency-review"),
+    ("ossf/scorecard-action", "scorecard"),
+    ("aquasecurity/trivy-action", "trivy"),
+    ("gitleaks/gitleaks-action", "gitleaks"),
+    ("trufflesecurity/trufflehog", "trufflehog"),
+    ("semgrep", "semgrep"),
+    ("snyk/actions", "snyk"),
+    ("rustsec/audit-check", "cargo-audit"),
+    ("cargo audit", "cargo-audit"),
+    ("cargo deny", "cargo-deny"),
+    ("npm audit", "npm-audit"),
+    ("pip-audit", "pip-audit"),
+    ("govulncheck", "govulncheck"),
+];
 
 // Security analyzer
-pub struct SecurityAnalyzer;
+pub struct SecurityAnalyzer {
+    secret_patterns: Vec<(&'static str, Regex)>,
+}
+
+impl Default for SecurityAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl SecurityAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            secret_patterns: SECRET_PATTERNS
+                .iter()
+                .map(|(kind, pattern)| (*kind, Regex::new(pattern).unwrap()))
+                .collect(),
+        }
+    }
+
     pub fn analyze_security(
         &self,
-        file_structure: &DirectoryInfo,
+        file_structure: &FileIndex,
         config_files: &[ConfigFile],
+        scan: SecurityScan,
+        advisories: Option<Vec<PublishedAdvisory>>,
     ) -> SecurityInfo {
         let mut has_security_policy = false;
-        let mut has_dependabot = false;
-        let mut has_codeql = false;
         let vulnerability_alerts = Vec::new(); // Would need external service integration
         let mut outdated_dependencies = Vec::new();
+        let mut dependency_evidence = Vec::new();
         let license_compatibility = Vec::new();
 
         // Check for security-related files
-        let mut all_files = Vec::new();
-        self.collect_all_files(file_structure, &mut all_files);
+        let all_files = file_structure.files();
 
-        for file in &all_files {
+        for file in all_files {
             match file.name.to_lowercase().as_str() {
                 "security.md" | "security.txt" | ".security" => {
                     has_security_policy = true;
@@ -30,13 +92,15 @@ impl SecurityAnalyzer {
         }
 
         // Check for GitHub security features
-        if self.has_github_workflow_file(file_structure, "dependabot") {
-            has_dependabot = true;
-        }
-
-        if self.has_github_workflow_file(file_structure, "codeql") {
-            has_codeql = true;
-        }
+        let SecurityScan {
+            ci_security_checks,
+            secret_findings,
+            unsafe_usage,
+            mut testing_depth,
+        } = scan;
+        testing_depth.property_testing = testing_depth::property_testing(config_files);
+        let has_dependabot = ci_security_checks.iter().any(|check| check == "dependabot");
+        let has_codeql = ci_security_checks.iter().any(|check| check == "codeql");
 
         // Analyze dependencies for potential issues
         for config in config_files {
@@ -44,7 +108,14 @@ impl SecurityAnalyzer {
                 for (name, version) in deps {
                     // Simple version check (in real implementation, would check against vulnerability databases)
                     if version.contains("*") || version.contains("latest") {
-                        outdated_dependencies.push(format!("{}: {}", name, version));
+                        let label = format!("{}: {}", name, version);
+                        dependency_evidence.push(EvidenceRef {
+                            label: label.clone(),
+                            path: config.path.clone(),
+                            line: manifest_line(&config.content, name),
+                            url: None,
+                        });
+                        outdated_dependencies.push(label);
                     }
                 }
             }
@@ -57,39 +128,151 @@ impl SecurityAnalyzer {
             vulnerability_alerts,
             outdated_dependencies,
             license_compatibility,
+            ci_security_checks,
+            secret_findings,
+            unsafe_usage,
+            dependency_evidence,
+            published_advisories: advisories.map(advisory_summary),
+            testing_depth,
         }
     }
 
-    fn collect_all_files(&self, dir: &DirectoryInfo, all_files: &mut Vec<FileInfo>) {
-        for file in &dir.files {
-            all_files.push(file.clone());
+    // Collects CI checks and secrets while the file system is walked
+    pub fn classifier(&self) -> SecurityClassifier<'_> {
+        SecurityClassifier {
+            secret_patterns: &self.secret_patterns,
+            has_dependabot: false,
+            workflows: Vec::new(),
+            secret_findings: Vec::new(),
+            unsafe_code: UnsafeCodeClassifier::new(),
+            testing_depth: TestingDepthClassifier::new(),
         }
+    }
+}
+
+pub struct SecurityClassifier<'a> {
+    secret_patterns: &'a [(&'static str, Regex)],
+    has_dependabot: bool,
+    workflows: Vec<(PathBuf, Vec<&'static str>)>, // Checks found per workflow
+    secret_findings: Vec<SecretFinding>,
+    unsafe_code: UnsafeCodeClassifier,
+    testing_depth: TestingDepthClassifier,
+}
 
-        for subdir in &dir.subdirectories {
-            self.collect_all_files(subdir, all_files);
+impl SecurityClassifier<'_> {
+    pub fn finish(mut self) -> SecurityScan {
+        let mut ci_security_checks: Vec<String> = Vec::new();
+        if self.has_dependabot {
+            ci_security_checks.push("dependabot".to_string());
+        }
+
+        self.workflows.sort_by(|a, b| a.0.cmp(&b.0));
+        for (_, checks) in self.workflows {
+            for check in checks {
+                if !ci_security_checks.iter().any(|c| c == check) {
+                    ci_security_checks.push(check.to_string());
+                }
+            }
+        }
+
+        SecurityScan {
+            ci_security_checks,
+            secret_findings: self.secret_findings,
+            unsafe_usage: self.unsafe_code.finish(),
+            testing_depth: self.testing_depth.finish(),
         }
     }
 
-    fn has_github_workflow_file(&self, file_structure: &DirectoryInfo, keyword: &str) -> bool {
-        let github_dir = file_structure
-            .subdirectories
-            .iter()
-            .find(|d| d.name == ".github");
+    // .github is skipped by the structure scan but still reaches classifiers
+    fn detect_ci_security_checks(&mut self, file: &ScannedFile<'_>) {
+        let Some(parent) = file.relative.parent() else {
+            return;
+        };
+        if parent == Path::new(".github")
+            && file
+                .path
+                .file_stem()
+                .is_some_and(|stem| stem.eq_ignore_ascii_case("dependabot"))
+        {
+            self.has_dependabot = true;
+        }
 
-        if let Some(github_dir) = github_dir {
-            let workflows_dir = github_dir
-                .subdirectories
-                .iter()
-                .find(|d| d.name == "workflows");
+        if parent != Path::new(".github/workflows") {
+            return;
+        }
+        let Some(content) = file.content() else {
+            return;
+        };
+        let content = content.to_lowercase();
+        let checks = CI_SECURITY_MARKERS
+            .iter()
+            .filter(|(marker, _)| content.contains(marker))
+            .map(|(_, check)| *check)
+            .collect();
+        self.workflows.push((file.relative.to_path_buf(), checks));
+    }
 
-            if let Some(workflows_dir) = workflows_dir {
-                return workflows_dir
-                    .files
-                    .iter()
-                    .any(|f| f.name.to_lowercase().contains(keyword));
+    // Only the location and kind of a match are kept, never the secret itself
+    fn scan_secrets(&mut self, file: &ScannedFile<'_>) {
+        if !file.in_structure {
+            return;
+        }
+        let Some(content) = file.content() else {
+            return;
+        };
+        if content.contains('\0') {
+            return;
+        }
+        for (index, line) in content.lines().enumerate() {
+            for (kind, pattern) in self.secret_patterns {
+                if pattern.is_match(line) {
+                    self.secret_findings.push(SecretFinding {
+                        path: file.relative.to_path_buf(),
+                        line: index + 1,
+                        kind: kind.to_string(),
+                        url: None,
+                    });
+                }
             }
         }
+    }
+}
+
+impl FileClassifier for SecurityClassifier<'_> {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        self.detect_ci_security_checks(file);
+        self.scan_secrets(file);
+        self.unsafe_code.classify(file);
+        self.testing_depth.classify(file);
+    }
+}
+
+// Severity counts and how long it took from the advisory draft to publishing
+// the fix, the latency users were exposed before they could upgrade
+fn advisory_summary(mut advisories: Vec<PublishedAdvisory>) -> AdvisorySummary {
+    advisories.sort_by_key(|advisory| std::cmp::Reverse(advisory.published_at));
+    let mut by_severity = BTreeMap::new();
+    for advisory in &advisories {
+        *by_severity.entry(advisory.severity.clone()).or_insert(0) += 1;
+    }
+    let unpatched = advisories
+        .iter()
+        .filter(|advisory| advisory.patched_versions.is_empty())
+        .count() as u32;
 
-        false
+    let mut days: Vec<f64> = advisories
+        .iter()
+        .filter_map(|advisory| {
+            let published = advisory.published_at?;
+            Some((published - advisory.created_at).num_hours().max(0) as f64 / 24.0)
+        })
+        .collect();
+    days.sort_by(f64::total_cmp);
+    AdvisorySummary {
+        median_patch_days: (!days.is_empty()).then(|| days[(days.len() - 1) / 2]),
+        max_patch_days: days.last().copied(),
+        advisories,
+        by_severity,
+        unpatched,
     }
 }
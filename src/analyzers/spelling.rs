@@ -0,0 +1,368 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::analyzers::scan::{FileClassifier, ScannedFile};
+use crate::types::{DocsSpelling, EvidenceRef, TermInconsistency};
+
+const MISSPELLINGS: &str = include_str!("misspellings.txt");
+
+// Product names with their official casing, as (casing, case-insensitive
+// pattern, whether all-lowercase uses are wrong too). Lowercase is left alone
+// for names that double as commands
+const TERMS: &[(&str, &str, bool)] = &[
+    ("GitHub", "github", true),
+    ("GitLab", "gitlab", true),
+    ("JavaScript", "javascript", true),
+    ("TypeScript", "typescript", true),
+    ("Node.js", r"node\.?js", true),
+    ("macOS", "macos", true),
+    ("iOS", "ios", false),
+    ("PostgreSQL", "postgresql", true),
+    ("MySQL", "mysql", false),
+    ("SQLite", "sqlite", false),
+    ("MongoDB", "mongodb", false),
+    ("WebAssembly", "webassembly", true),
+    ("GraphQL", "graphql", false),
+    ("OAuth", "oauth", false),
+    ("OpenAPI", "openapi", false),
+    ("PyPI", "pypi", false),
+    ("npm", "npm", false),
+    ("YAML", "yaml", false),
+    ("JSON", "json", false),
+    ("TOML", "toml", false),
+    ("Kubernetes", "kubernetes", false),
+    ("Docker", "docker", false),
+    ("Rust", "rust", false),
+    ("Python", "python", false),
+];
+
+// Markdown and reStructuredText at the root and anywhere under these
+const DOCS_DIRS: &[&str] = &["docs", "doc"];
+const DOC_EXTENSIONS: &[&str] = &["md", "markdown", "rst", "adoc"];
+
+// Root files that are boilerplate or lists of names rather than prose
+const SKIPPED_FILES: &[&str] = &[
+    "changelog",
+    "changes",
+    "history",
+    "license",
+    "licence",
+    "notice",
+    "code_of_conduct",
+    "authors",
+    "contributors",
+];
+
+const MAX_FILES: u32 = 200;
+// Misspellings kept as examples, all of them are counted
+const MAX_EXAMPLES: usize = 20;
+
+// A line of a Markdown or reStructuredText document
+pub(crate) enum DocLine<'a> {
+    Prose(&'a str),
+    Code, // Fenced, indented or literal block
+    Blank,
+}
+
+// Uses of one name in one casing
+struct Form {
+    count: u32,
+    first: EvidenceRef,
+}
+
+// Checks the prose of the README and docs during the shared walk against a
+// list of common misspellings, and the casing of product names and of the
+// project's own name
+pub struct SpellingClassifier {
+    misspellings: HashMap<&'static str, &'static str>,
+    terms: Vec<Regex>,
+    project_name: Option<Regex>,
+    inline_noise: Regex,
+    // Per term, then the project's name last: forms by casing
+    forms: Vec<HashMap<String, Form>>,
+    spelling: DocsSpelling,
+}
+
+impl SpellingClassifier {
+    pub fn new(project_name: &str) -> Self {
+        let misspellings = MISSPELLINGS
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once("->"))
+            .collect();
+        let terms = TERMS
+            .iter()
+            .map(|(_, pattern, _)| Regex::new(&format!(r"(?i)\b{}\b", pattern)).unwrap())
+            .collect();
+        // Names of one or two letters, or that are a product name already,
+        // say nothing about consistency
+        let project_name = (project_name.len() >= 3
+            && project_name.chars().any(|c| c.is_alphabetic())
+            && !TERMS
+                .iter()
+                .any(|(term, _, _)| term.eq_ignore_ascii_case(project_name)))
+        .then(|| Regex::new(&format!(r"(?i)\b{}\b", regex::escape(project_name))).unwrap());
+        Self {
+            misspellings,
+            terms,
+            project_name,
+            // Inline code, URLs, link targets and HTML tags
+            inline_noise: Regex::new(r"`[^`]*`|https?://\S+|\]\([^)]*\)|<[^>]+>").unwrap(),
+            forms: (0..=TERMS.len()).map(|_| HashMap::new()).collect(),
+            spelling: DocsSpelling::default(),
+        }
+    }
+
+    pub fn finish(mut self) -> DocsSpelling {
+        let spelling = &mut self.spelling;
+        if spelling.words_checked > 0 {
+            spelling.misspellings_per_1k_words =
+                spelling.misspelling_count as f64 * 1000.0 / spelling.words_checked as f64;
+        }
+
+        for (index, forms) in self.forms.into_iter().enumerate() {
+            let mut forms: Vec<(String, Form)> = forms.into_iter().collect();
+            forms.sort_by(|a, b| b.1.count.cmp(&a.1.count).then_with(|| a.0.cmp(&b.0)));
+            let term = match TERMS.get(index) {
+                Some((term, _, flag_lowercase)) => {
+                    forms.retain(|(form, _)| {
+                        form != term && (*flag_lowercase || *form != form.to_lowercase())
+                    });
+                    term.to_string()
+                }
+                // The project's own name has no official casing, the most used
+                // one stands in for it
+                None if forms.len() > 1 => forms.remove(0).0,
+                None => continue,
+            };
+            if forms.is_empty() {
+                continue;
+            }
+            spelling.terminology.push(TermInconsistency {
+                term,
+                occurrences: forms.iter().map(|(_, form)| form.count).sum(),
+                variants: forms.iter().map(|(form, _)| form.clone()).collect(),
+                evidence: forms.into_iter().map(|(_, form)| form.first).collect(),
+            });
+        }
+
+        spelling.signals = signals(spelling);
+        self.spelling
+    }
+
+    fn check(&mut self, relative: &Path, content: &str) {
+        self.spelling.files_checked += 1;
+        for (line_number, line) in doc_lines(content) {
+            if let DocLine::Prose(line) = line {
+                let prose = self.inline_noise.replace_all(line, " ");
+                self.check_words(relative, &prose, line_number);
+                self.check_terms(relative, &prose, line_number);
+            }
+        }
+    }
+
+    fn check_words(&mut self, relative: &Path, prose: &str, line: u32) {
+        for token in prose.split_whitespace() {
+            let word = token.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'');
+            let word = word.trim_matches('\'');
+            // Paths, identifiers, versions and acronyms are not prose
+            if word.is_empty()
+                || !word.chars().all(|c| c.is_alphabetic() || c == '\'')
+                || word.chars().skip(1).any(|c| c.is_uppercase())
+            {
+                continue;
+            }
+            if token
+                .trim_end_matches(|c: char| ".,;:!?)]\"'".contains(c))
+                .contains(['/', '_', '.', '@', '#', '=', '\\'])
+            {
+                continue;
+            }
+            self.spelling.words_checked += 1;
+            let lowercase = word.to_lowercase();
+            if let Some(correction) = self.misspellings.get(lowercase.as_str()) {
+                self.spelling.misspelling_count += 1;
+                if self.spelling.misspellings.len() < MAX_EXAMPLES {
+                    let label = format!("{} -> {}", lowercase, correction);
+                    self.spelling
+                        .misspellings
+                        .push(evidence(&label, relative, line));
+                }
+            }
+        }
+    }
+
+    fn check_terms(&mut self, relative: &Path, prose: &str, line: u32) {
+        let mut found: Vec<(usize, String)> = Vec::new();
+        for (index, term) in self.terms.iter().enumerate() {
+            for m in term.find_iter(prose) {
+                if is_prose_word(prose, m.start(), m.end()) {
+                    found.push((index, m.as_str().to_string()));
+                }
+            }
+        }
+        if let Some(name) = &self.project_name {
+            for m in name.find_iter(prose) {
+                // Capitalized at the start of a sentence says nothing
+                let before = prose[..m.start()].trim_end();
+                let sentence_start = before.is_empty()
+                    || before.ends_with(['.', '!', '?', '#', '*', '-', '>', '|', '"']);
+                if !sentence_start && is_prose_word(prose, m.start(), m.end()) {
+                    found.push((TERMS.len(), m.as_str().to_string()));
+                }
+            }
+        }
+        for (index, form) in found {
+            let label = form.clone();
+            self.forms[index]
+                .entry(form)
+                .and_modify(|form| form.count += 1)
+                .or_insert_with(|| Form {
+                    count: 1,
+                    first: evidence(&label, relative, line),
+                });
+        }
+    }
+}
+
+impl FileClassifier for SpellingClassifier {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        if self.spelling.files_checked >= MAX_FILES || !is_doc_file(file) {
+            return;
+        }
+        if let Some(content) = file.content() {
+            self.check(file.relative, content);
+        }
+    }
+}
+
+// README-like files at the root and the docs, without changelogs, licenses
+// and name lists
+pub(crate) fn is_doc_file(file: &ScannedFile<'_>) -> bool {
+    if !file.in_structure {
+        return false;
+    }
+    let name = file.file_name().to_lowercase();
+    let Some((stem, extension)) = name.rsplit_once('.') else {
+        return false;
+    };
+    if !DOC_EXTENSIONS.contains(&extension) {
+        return false;
+    }
+    let in_docs_dir = file
+        .relative
+        .components()
+        .next()
+        .and_then(|dir| dir.as_os_str().to_str())
+        .is_some_and(|dir| DOCS_DIRS.contains(&dir.to_lowercase().as_str()));
+    in_docs_dir || (file.depth == 1 && !SKIPPED_FILES.contains(&stem))
+}
+
+// Lines numbered from 1, front matter and fence markers left out
+pub(crate) fn doc_lines(content: &str) -> Vec<(u32, DocLine<'_>)> {
+    let mut lines = Vec::new();
+    let mut in_fence = false;
+    let mut in_literal = false; // reStructuredText literal block after `::`
+    let mut in_front_matter = content.starts_with("---");
+
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index as u32 + 1;
+        let trimmed = line.trim();
+        if in_front_matter {
+            in_front_matter = index == 0 || trimmed != "---";
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if trimmed.is_empty() {
+            lines.push((line_number, DocLine::Blank));
+            continue;
+        }
+        if in_fence {
+            lines.push((line_number, DocLine::Code));
+            continue;
+        }
+        if in_literal {
+            if line.starts_with(char::is_whitespace) {
+                lines.push((line_number, DocLine::Code));
+                continue;
+            }
+            in_literal = false;
+        }
+        if trimmed.ends_with("::") {
+            in_literal = true;
+        }
+        // Indented code blocks, nested list items are still prose
+        if (line.starts_with("    ") || line.starts_with('\t'))
+            && !trimmed.starts_with(['-', '*', '+'])
+        {
+            lines.push((line_number, DocLine::Code));
+            continue;
+        }
+        lines.push((line_number, DocLine::Prose(line)));
+    }
+    lines
+}
+
+// Not part of a path, domain, file name or identifier
+fn is_prose_word(line: &str, start: usize, end: usize) -> bool {
+    let before = line[..start].chars().next_back();
+    let mut after = line[end..].chars();
+    let next = after.next();
+    if before.is_some_and(|c| "/.@-_\\".contains(c)) {
+        return false;
+    }
+    match next {
+        Some('/' | '_' | '-' | '@' | '\\') => false,
+        Some('.') => !after.next().is_some_and(|c| c.is_alphanumeric()),
+        _ => true,
+    }
+}
+
+// Density, then each inconsistently cased name
+fn signals(spelling: &DocsSpelling) -> Vec<String> {
+    if spelling.words_checked == 0 {
+        return Vec::new();
+    }
+    let mut signals = Vec::new();
+    if spelling.misspelling_count == 0 {
+        signals.push(format!(
+            "No common misspellings in {} words of docs.",
+            spelling.words_checked
+        ));
+    } else {
+        signals.push(format!(
+            "{} likely misspellings in {} words of docs ({:.1} per 1,000 words).",
+            spelling.misspelling_count, spelling.words_checked, spelling.misspellings_per_1k_words
+        ));
+        if spelling.misspellings_per_1k_words >= 2.0 {
+            signals.push("Misspellings are frequent enough for readers to notice.".to_string());
+        }
+    }
+    for term in &spelling.terminology {
+        let uses = match term.occurrences {
+            1 => "once".to_string(),
+            n => format!("{} times", n),
+        };
+        signals.push(format!(
+            "{} is also written {} ({}).",
+            term.term,
+            term.variants.join(", "),
+            uses
+        ));
+    }
+    signals
+}
+
+fn evidence(label: &str, path: &Path, line: u32) -> EvidenceRef {
+    EvidenceRef {
+        label: label.to_string(),
+        path: path.to_path_buf(),
+        line: Some(line),
+        url: None,
+    }
+}
@@ -0,0 +1,265 @@
+use regex::Regex;
+
+use crate::types::{
+    AffiliationPeriod, DocumentationFile, FoundationSignal, FundingLink, GitAnalysis,
+    OrganizationShare, Sustainability,
+};
+
+// Where each FUNDING.yml key points, custom entries are full URLs
+const FUNDING_PLATFORMS: &[(&str, &str)] = &[
+    ("github", "https://github.com/sponsors/"),
+    ("patreon", "https://www.patreon.com/"),
+    ("open_collective", "https://opencollective.com/"),
+    ("ko_fi", "https://ko-fi.com/"),
+    ("tidelift", "https://tidelift.com/funding/github/"),
+    (
+        "community_bridge",
+        "https://funding.communitybridge.org/projects/",
+    ),
+    ("liberapay", "https://liberapay.com/"),
+    ("issuehunt", "https://issuehunt.io/r/"),
+    (
+        "lfx_crowdfunding",
+        "https://crowdfunding.lfx.linuxfoundation.org/projects/",
+    ),
+    ("polar", "https://polar.sh/"),
+    ("buy_me_a_coffee", "https://buymeacoffee.com/"),
+    ("thanks_dev", "https://thanks.dev/"),
+];
+
+// Sponsor page hosts linked from a README, as (host, platform)
+const SPONSOR_HOSTS: &[(&str, &str)] = &[
+    ("github.com/sponsors", "github"),
+    ("opencollective.com", "open_collective"),
+    ("patreon.com", "patreon"),
+    ("ko-fi.com", "ko_fi"),
+    ("liberapay.com", "liberapay"),
+    ("buymeacoffee.com", "buy_me_a_coffee"),
+    ("polar.sh", "polar"),
+    ("tidelift.com/funding", "tidelift"),
+];
+
+// Foundations hosting projects, with the lowercase phrases naming them
+const FOUNDATIONS: &[(&str, &[&str])] = &[
+    (
+        "Cloud Native Computing Foundation",
+        &["cloud native computing foundation", "cncf.io"],
+    ),
+    (
+        "Apache Software Foundation",
+        &["apache software foundation"],
+    ),
+    ("OpenJS Foundation", &["openjs foundation"]),
+    ("Eclipse Foundation", &["eclipse foundation"]),
+    (
+        "Python Software Foundation",
+        &["python software foundation"],
+    ),
+    ("NumFOCUS", &["numfocus"]),
+    (
+        "Software Freedom Conservancy",
+        &["software freedom conservancy"],
+    ),
+    ("OpenSSF", &["open source security foundation", "openssf"]),
+    (".NET Foundation", &[".net foundation"]),
+    ("OSGeo", &["osgeo"]),
+    ("Linux Foundation", &["linux foundation"]),
+];
+
+// Documents a foundation's hosting is stated in
+const GOVERNANCE_DOCS: &[&str] = &[
+    "governance",
+    "maintainers",
+    "readme",
+    "code_of_conduct",
+    "contributing",
+];
+
+// Mail providers anyone can sign up with, they say nothing about an employer
+const PERSONAL_DOMAINS: &[&str] = &[
+    "gmail.com",
+    "googlemail.com",
+    "outlook.com",
+    "hotmail.com",
+    "live.com",
+    "msn.com",
+    "yahoo.com",
+    "icloud.com",
+    "me.com",
+    "mac.com",
+    "aol.com",
+    "protonmail.com",
+    "proton.me",
+    "pm.me",
+    "gmx.de",
+    "gmx.net",
+    "web.de",
+    "mail.ru",
+    "yandex.ru",
+    "qq.com",
+    "163.com",
+    "126.com",
+    "fastmail.com",
+    "hey.com",
+    "users.noreply.github.com",
+    "localhost",
+];
+
+// Funding links, the organizations behind the top contributors' commits and
+// governance files, for the sustainability risk
+pub struct SustainabilityAnalyzer;
+
+impl SustainabilityAnalyzer {
+    pub fn analyze(
+        &self,
+        documentation: &[DocumentationFile],
+        git_analysis: &GitAnalysis,
+    ) -> Sustainability {
+        let mut funding = Vec::new();
+        for doc in documentation {
+            let links = match doc.file_type.as_str() {
+                "funding" => funding_file_links(doc),
+                "readme" => readme_sponsor_links(doc),
+                _ => continue,
+            };
+            for link in links {
+                if !funding.iter().any(|l: &FundingLink| l.url == link.url) {
+                    funding.push(link);
+                }
+            }
+        }
+
+        let affiliations = git_analysis
+            .top_contributor_domains
+            .iter()
+            .filter(|share| is_organization_domain(&share.domain))
+            .cloned()
+            .collect();
+
+        let governance_files = documentation
+            .iter()
+            .filter(|doc| matches!(doc.file_type.as_str(), "governance" | "maintainers"))
+            .map(|doc| doc.path.clone())
+            .collect();
+
+        Sustainability {
+            funding,
+            affiliations,
+            governance_files,
+            foundation: foundation(documentation),
+            affiliation_history: affiliation_history(git_analysis),
+        }
+    }
+}
+
+// Every platform key of FUNDING.yml, a name or a list of names
+fn funding_file_links(doc: &DocumentationFile) -> Vec<FundingLink> {
+    let Ok(serde_yaml::Value::Mapping(funding)) = serde_yaml::from_str(&doc.content) else {
+        return Vec::new();
+    };
+    let mut links = Vec::new();
+    for (key, value) in funding {
+        let Some(platform) = key.as_str() else {
+            continue;
+        };
+        let names: Vec<&str> = match &value {
+            serde_yaml::Value::String(name) => vec![name.as_str()],
+            serde_yaml::Value::Sequence(names) => {
+                names.iter().filter_map(|name| name.as_str()).collect()
+            }
+            _ => continue,
+        };
+        let prefix = FUNDING_PLATFORMS
+            .iter()
+            .find(|(key, _)| *key == platform)
+            .map(|(_, prefix)| *prefix);
+        for name in names.into_iter().map(str::trim).filter(|n| !n.is_empty()) {
+            let url = match prefix {
+                Some(prefix) => format!("{}{}", prefix, name),
+                None => name.to_string(), // custom, or a platform added after this list
+            };
+            links.push(FundingLink {
+                platform: platform.to_string(),
+                url,
+                source: doc.path.clone(),
+            });
+        }
+    }
+    links
+}
+
+fn readme_sponsor_links(doc: &DocumentationFile) -> Vec<FundingLink> {
+    let link = Regex::new(r"https?://(?:www\.)?([a-z0-9.-]+(?:/[A-Za-z0-9_.-]+)+)").unwrap();
+    link.captures_iter(&doc.content)
+        .filter_map(|captures| {
+            let target = captures[1].trim_end_matches(['.', ')']);
+            let (_, platform) = SPONSOR_HOSTS
+                .iter()
+                .find(|(host, _)| target.to_lowercase().starts_with(&format!("{}/", host)))?;
+            Some(FundingLink {
+                platform: platform.to_string(),
+                url: format!("https://{}", target),
+                source: doc.path.clone(),
+            })
+        })
+        .collect()
+}
+
+// Commits of each quarter split into organizations and independent
+// contributors, to see whether one company took over or stepped back
+fn affiliation_history(git_analysis: &GitAnalysis) -> Vec<AffiliationPeriod> {
+    git_analysis
+        .commits_by_domain
+        .iter()
+        .map(|(period, domains)| {
+            let commits: u32 = domains.values().sum();
+            let mut independent_commits = 0;
+            let mut organizations = Vec::new();
+            for (domain, &domain_commits) in domains {
+                if is_organization_domain(domain) {
+                    organizations.push(OrganizationShare {
+                        domain: domain.clone(),
+                        commits: domain_commits,
+                        share: domain_commits as f64 / commits.max(1) as f64,
+                    });
+                } else {
+                    independent_commits += domain_commits;
+                }
+            }
+            organizations.sort_by(|a, b| {
+                b.commits
+                    .cmp(&a.commits)
+                    .then_with(|| a.domain.cmp(&b.domain))
+            });
+            AffiliationPeriod {
+                period: period.clone(),
+                commits,
+                independent_commits,
+                organizations,
+            }
+        })
+        .collect()
+}
+
+fn is_organization_domain(domain: &str) -> bool {
+    !PERSONAL_DOMAINS.contains(&domain) && domain.contains('.') && !domain.ends_with(".local")
+}
+
+// The first foundation named in the governance documents, the more specific
+// foundations are listed before the Linux Foundation umbrella
+fn foundation(documentation: &[DocumentationFile]) -> Option<FoundationSignal> {
+    let docs: Vec<(&DocumentationFile, String)> = documentation
+        .iter()
+        .filter(|doc| GOVERNANCE_DOCS.contains(&doc.file_type.as_str()))
+        .map(|doc| (doc, doc.content.to_lowercase()))
+        .collect();
+    FOUNDATIONS.iter().find_map(|(foundation, phrases)| {
+        let (doc, _) = docs
+            .iter()
+            .find(|(_, content)| phrases.iter().any(|phrase| content.contains(phrase)))?;
+        Some(FoundationSignal {
+            foundation: foundation.to_string(),
+            source: doc.path.clone(),
+        })
+    })
+}
@@ -0,0 +1,172 @@
+use crate::types::{ContributionTemplate, DocumentationFile, TemplateField, TemplateInventory};
+
+// Issue and pull request template analyzer
+pub struct TemplateAnalyzer;
+
+impl TemplateAnalyzer {
+    pub fn analyze_templates(&self, documentation: &[DocumentationFile]) -> TemplateInventory {
+        let mut templates = Vec::new();
+        let mut blank_issues_enabled = None;
+        let mut contact_links = 0;
+
+        for doc in documentation {
+            match doc.file_type.as_str() {
+                "issue_template" => templates.push(self.parse_template(doc, "issue")),
+                "pull_request_template" => templates.push(self.parse_template(doc, "pull_request")),
+                "issue_template_config" => {
+                    if let Ok(config) = serde_yaml::from_str::<serde_yaml::Value>(&doc.content) {
+                        blank_issues_enabled = config["blank_issues_enabled"].as_bool();
+                        contact_links = config["contact_links"]
+                            .as_sequence()
+                            .map(|links| links.len() as u32)
+                            .unwrap_or(0);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mentions = |template: &ContributionTemplate, keywords: &[&str]| {
+            let haystack = format!(
+                "{} {}",
+                template.path.to_string_lossy(),
+                template.name.as_deref().unwrap_or("")
+            )
+            .to_lowercase();
+            keywords.iter().any(|keyword| haystack.contains(keyword))
+        };
+        let has_bug_report = templates
+            .iter()
+            .any(|t| t.kind == "issue" && mentions(t, &["bug"]));
+        let has_feature_request = templates
+            .iter()
+            .any(|t| t.kind == "issue" && mentions(t, &["feature", "enhancement", "request"]));
+        let has_pull_request_template = templates.iter().any(|t| t.kind == "pull_request");
+
+        // Completeness: the templates most projects are expected to provide
+        let checks = [
+            ("bug report template", has_bug_report),
+            ("feature request template", has_feature_request),
+            ("pull request template", has_pull_request_template),
+            (
+                "issue template chooser config",
+                blank_issues_enabled.is_some() || contact_links > 0,
+            ),
+        ];
+        let passed = checks.iter().filter(|(_, ok)| *ok).count();
+
+        TemplateInventory {
+            templates,
+            blank_issues_enabled,
+            contact_links,
+            has_bug_report,
+            has_feature_request,
+            has_pull_request_template,
+            completeness_score: passed as f64 / checks.len() as f64 * 100.0,
+            missing: checks
+                .iter()
+                .filter(|(_, ok)| !*ok)
+                .map(|(name, _)| name.to_string())
+                .collect(),
+        }
+    }
+
+    fn parse_template(&self, doc: &DocumentationFile, kind: &str) -> ContributionTemplate {
+        let is_form = matches!(
+            doc.path.extension().and_then(|e| e.to_str()),
+            Some("yml" | "yaml")
+        );
+
+        if is_form {
+            self.parse_issue_form(doc, kind)
+        } else {
+            self.parse_markdown_template(doc, kind)
+        }
+    }
+
+    // GitHub issue forms: top-level metadata plus typed `body` elements
+    fn parse_issue_form(&self, doc: &DocumentationFile, kind: &str) -> ContributionTemplate {
+        let form: serde_yaml::Value = serde_yaml::from_str(&doc.content).unwrap_or_default();
+
+        let fields = form["body"]
+            .as_sequence()
+            .map(|body| {
+                body.iter()
+                    // Markdown elements are static text, not fields
+                    .filter(|element| element["type"].as_str() != Some("markdown"))
+                    .map(|element| TemplateField {
+                        id: element["id"].as_str().map(|s| s.to_string()),
+                        label: element["attributes"]["label"]
+                            .as_str()
+                            .unwrap_or("")
+                            .to_string(),
+                        field_type: element["type"].as_str().unwrap_or("unknown").to_string(),
+                        required: element["validations"]["required"]
+                            .as_bool()
+                            .unwrap_or(false),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        ContributionTemplate {
+            path: doc.path.clone(),
+            kind: kind.to_string(),
+            format: "form".to_string(),
+            name: form["name"].as_str().map(|s| s.to_string()),
+            description: form["description"].as_str().map(|s| s.to_string()),
+            labels: yaml_labels(&form["labels"]),
+            fields,
+        }
+    }
+
+    // Markdown templates: optional front matter, headings act as the fields
+    fn parse_markdown_template(&self, doc: &DocumentationFile, kind: &str) -> ContributionTemplate {
+        let mut front_matter = serde_yaml::Value::Null;
+        let mut body = doc.content.as_str();
+        if let Some(rest) = doc.content.strip_prefix("---")
+            && let Some(end) = rest.find("\n---")
+        {
+            front_matter = serde_yaml::from_str(&rest[..end]).unwrap_or_default();
+            body = &rest[end + 4..];
+        }
+
+        let fields = body
+            .lines()
+            .filter(|line| line.trim_start().starts_with('#'))
+            .map(|line| TemplateField {
+                id: None,
+                label: line.trim_start_matches('#').trim().to_string(),
+                field_type: "section".to_string(),
+                required: false,
+            })
+            .collect();
+
+        ContributionTemplate {
+            path: doc.path.clone(),
+            kind: kind.to_string(),
+            format: "markdown".to_string(),
+            name: front_matter["name"].as_str().map(|s| s.to_string()),
+            description: front_matter["about"].as_str().map(|s| s.to_string()),
+            labels: yaml_labels(&front_matter["labels"]),
+            fields,
+        }
+    }
+}
+
+// Labels are either a list or a comma separated string
+fn yaml_labels(value: &serde_yaml::Value) -> Vec<String> {
+    match value {
+        serde_yaml::Value::Sequence(labels) => labels
+            .iter()
+            .filter_map(|label| label.as_str())
+            .map(|label| label.to_string())
+            .collect(),
+        serde_yaml::Value::String(labels) => labels
+            .split(',')
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,234 @@
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::analyzers::scan::{FileClassifier, ScannedFile};
+use crate::types::{ConfigFile, EvidenceRef, TestingDepth};
+use crate::utils::{first_line, manifest_line};
+
+// Workflow content markers of static analysis (SAST) steps, as (marker, tool)
+const SAST_MARKERS: &[(&str, &str)] = &[
+    ("github/codeql-action/analyze", "codeql"),
+    ("semgrep", "semgrep"),
+    ("snyk/actions", "snyk"),
+    ("securego/gosec", "gosec"),
+    ("gosec ", "gosec"),
+    ("bandit ", "bandit"),
+    ("brakeman", "brakeman"),
+    ("sonarsource/", "sonarqube"),
+    ("sonar-scanner", "sonarqube"),
+    ("njsscan", "njsscan"),
+    ("flawfinder", "flawfinder"),
+    ("cppcheck", "cppcheck"),
+    ("psalm --taint-analysis", "psalm"),
+    ("horusec", "horusec"),
+];
+
+// Workflow markers of continuous fuzzing services, as (marker, service)
+const FUZZING_SERVICE_MARKERS: &[(&str, &str)] = &[
+    ("google/oss-fuzz/infra/cifuzz", "OSS-Fuzz"),
+    ("google/clusterfuzzlite", "ClusterFuzzLite"),
+];
+
+// README mentions of an OSS-Fuzz project: its badge, build logs or tracker
+const OSS_FUZZ_README_MARKERS: &[&str] = &[
+    "oss-fuzz-build-logs",
+    "bugs.chromium.org/p/oss-fuzz",
+    "issues.oss-fuzz.com",
+    "google/oss-fuzz",
+];
+
+// Property-based testing libraries, as dependency names
+const PROPERTY_TESTING_LIBRARIES: &[&str] = &[
+    "proptest",
+    "quickcheck",
+    "hypothesis",
+    "fast-check",
+    "jsverify",
+    "jqwik",
+    "junit-quickcheck",
+    "scalacheck",
+    "pgregory.net/rapid",
+    "github.com/leanovate/gopter",
+    "hedgehog",
+    "propcheck",
+    "stream_data",
+];
+
+// Fuzzing, property-based testing and static analysis found during the
+// shared walk. Property-based testing is read from the parsed manifests
+pub struct TestingDepthClassifier {
+    go_fuzz: Regex,
+    depth: TestingDepth,
+}
+
+impl Default for TestingDepthClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestingDepthClassifier {
+    pub fn new() -> Self {
+        Self {
+            go_fuzz: Regex::new(r"func Fuzz\w*\(\s*\w+\s+\*testing\.F\s*\)").unwrap(),
+            depth: TestingDepth::default(),
+        }
+    }
+
+    pub fn finish(mut self) -> TestingDepth {
+        for refs in [
+            &mut self.depth.continuous_fuzzing,
+            &mut self.depth.fuzz_targets,
+            &mut self.depth.sast,
+        ] {
+            refs.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.label.cmp(&b.label)));
+        }
+        // One entry per service, however many files name it
+        let mut services: Vec<String> = Vec::new();
+        self.depth.continuous_fuzzing.retain(|evidence| {
+            let first = !services.contains(&evidence.label);
+            if first {
+                services.push(evidence.label.clone());
+            }
+            first
+        });
+        self.depth
+    }
+
+    fn detect_workflow_steps(&mut self, file: &ScannedFile<'_>, content: &str) {
+        let content = content.to_lowercase();
+        for (markers, refs) in [
+            (SAST_MARKERS, &mut self.depth.sast),
+            (FUZZING_SERVICE_MARKERS, &mut self.depth.continuous_fuzzing),
+        ] {
+            let mut found: Vec<&str> = Vec::new();
+            for (marker, label) in markers {
+                if found.contains(label) {
+                    continue;
+                }
+                if let Some(line) = first_line(&content, |line| line.contains(marker)) {
+                    found.push(label);
+                    refs.push(evidence(label, file.relative, Some(line)));
+                }
+            }
+        }
+    }
+
+    fn detect_fuzz_target(&mut self, file: &ScannedFile<'_>) {
+        let name = file.file_name();
+        let extension = Path::new(name)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+        let engines: &[(&str, &str)] = match extension {
+            "rs" => &[
+                ("libfuzzer_sys", "cargo-fuzz"),
+                ("afl::fuzz", "afl.rs"),
+                ("honggfuzz::fuzz", "honggfuzz"),
+                ("bolero::check", "bolero"),
+            ],
+            "c" | "cc" | "cpp" | "cxx" | "c++" => &[("LLVMFuzzerTestOneInput", "libFuzzer")],
+            "py" => &[("import atheris", "atheris")],
+            "java" | "kt" => &[("@FuzzTest", "Jazzer"), ("fuzzerTestOneInput", "Jazzer")],
+            "go" if name.ends_with("_test.go") => &[],
+            _ => return,
+        };
+        let Some(content) = file.content() else {
+            return;
+        };
+
+        if extension == "go" {
+            if let Some(line) = first_line(content, |line| self.go_fuzz.is_match(line)) {
+                self.depth
+                    .fuzz_targets
+                    .push(evidence("go test -fuzz", file.relative, Some(line)));
+            }
+            return;
+        }
+        if let Some((marker, engine)) = engines.iter().find(|(marker, _)| content.contains(marker))
+        {
+            let line = first_line(content, |line| line.contains(marker));
+            self.depth
+                .fuzz_targets
+                .push(evidence(engine, file.relative, line));
+        }
+    }
+}
+
+impl FileClassifier for TestingDepthClassifier {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        let relative = file.relative;
+        if relative.starts_with(".clusterfuzzlite") {
+            self.depth
+                .continuous_fuzzing
+                .push(evidence("ClusterFuzzLite", relative, None));
+            return;
+        }
+        if relative.parent() == Some(Path::new(".github/workflows")) {
+            if let Some(content) = file.content() {
+                self.detect_workflow_steps(file, content);
+            }
+            return;
+        }
+        if file.depth == 1 && file.file_name().to_uppercase().starts_with("README") {
+            if let Some(content) = file.content() {
+                let lowercase = content.to_lowercase();
+                let line = first_line(&lowercase, |line| {
+                    OSS_FUZZ_README_MARKERS
+                        .iter()
+                        .any(|marker| line.contains(marker))
+                });
+                if line.is_some() {
+                    self.depth
+                        .continuous_fuzzing
+                        .push(evidence("OSS-Fuzz", relative, line));
+                }
+            }
+            return;
+        }
+        if file.in_structure {
+            self.detect_fuzz_target(file);
+        }
+    }
+}
+
+// Property-based testing libraries among the declared dependencies. Cargo
+// dev-dependencies aren't parsed, so a manifest line declaring the library
+// counts too
+pub fn property_testing(config_files: &[ConfigFile]) -> Vec<EvidenceRef> {
+    let mut found = Vec::new();
+    for config in config_files {
+        let declared: Vec<String> = config
+            .parsed_dependencies
+            .iter()
+            .flat_map(|deps| deps.keys())
+            .map(|name| name.trim_end_matches(" (dev)").to_lowercase())
+            .collect();
+        for library in PROPERTY_TESTING_LIBRARIES {
+            let key = Regex::new(&format!(
+                r#"^\s*["']?{}["']?\s*[=:<>~!\[]"#,
+                regex::escape(library)
+            ))
+            .unwrap();
+            let line = first_line(&config.content, |line| key.is_match(line));
+            if declared.iter().any(|name| name == library) || line.is_some() {
+                found.push(evidence(
+                    library,
+                    &config.path,
+                    line.or_else(|| manifest_line(&config.content, library)),
+                ));
+            }
+        }
+    }
+    found
+}
+
+fn evidence(label: &str, path: &Path, line: Option<u32>) -> EvidenceRef {
+    EvidenceRef {
+        label: label.to_string(),
+        path: path.to_path_buf(),
+        line,
+        url: None,
+    }
+}
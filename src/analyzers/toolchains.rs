@@ -0,0 +1,461 @@
+use std::cmp::Ordering;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::analyzers::scan::{FileClassifier, METADATA_DEPTH, ScannedFile};
+use crate::types::{EvidenceRef, ToolchainRequirement, ToolchainRequirements};
+use crate::utils::first_line;
+
+// Languages in report order
+const LANGUAGES: &[&str] = &["Rust", "Node.js", "Python", "Go", "Java"];
+
+// Workflow keys naming the versions a job runs, as (key, language). Matrix
+// variables are usually named after the language
+const CI_KEYS: &[(&str, &str)] = &[
+    ("rust-version", "Rust"),
+    ("toolchain", "Rust"),
+    ("rust", "Rust"),
+    ("msrv", "Rust"),
+    ("node-version", "Node.js"),
+    ("node", "Node.js"),
+    ("python-version", "Python"),
+    ("python", "Python"),
+    ("go-version", "Go"),
+    ("go", "Go"),
+    ("java-version", "Java"),
+    ("java", "Java"),
+];
+
+// A declared minimum, before one per language is kept
+struct Declaration {
+    language: &'static str,
+    constraint: String,
+    minimum: String,
+    depth: usize,
+    evidence: EvidenceRef,
+}
+
+// Minimum toolchain versions declared in manifests, and the numbered
+// versions CI runs, collected during the shared walk
+pub struct ToolchainClassifier {
+    yaml_key: Regex,
+    yaml_item: Regex,
+    ci_toolchain: Regex,
+    go_directive: Regex,
+    python_requires: Regex,
+    java_versions: Vec<(Regex, &'static str)>,
+    comparator: Regex,
+    declarations: Vec<Declaration>,
+    ci: Vec<(&'static str, Vec<String>, EvidenceRef)>,
+}
+
+impl Default for ToolchainClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolchainClassifier {
+    pub fn new() -> Self {
+        Self {
+            yaml_key: Regex::new(r"^\s*(?:-\s+)?([\w-]+)\s*:\s*(.*?)\s*(?:#.*)?$").unwrap(),
+            yaml_item: Regex::new(r"^\s*-\s+(.+?)\s*(?:#.*)?$").unwrap(),
+            ci_toolchain: Regex::new(
+                r"(?:rust-toolchain@|cargo \+|rustup (?:toolchain install|default) )(\d+\.\d+(?:\.\d+)?)",
+            )
+            .unwrap(),
+            go_directive: Regex::new(r"^go\s+(\d+\.\d+(?:\.\d+)?)\s*$").unwrap(),
+            python_requires: Regex::new(r#"python_requires\s*=\s*['"]?([^'"\n,)]+(?:,[^'"\n)]+)*)"#)
+                .unwrap(),
+            java_versions: [
+                (r"JavaLanguageVersion\.of\(\s*(\d+)\s*\)", "java toolchain"),
+                (r"jvmToolchain\(\s*(\d+)\s*\)", "jvmToolchain"),
+                (r"<maven\.compiler\.release>\s*(\d+(?:\.\d+)?)\s*<", "maven.compiler.release"),
+                (r"<release>\s*(\d+(?:\.\d+)?)\s*</release>", "compiler release"),
+                (r"<java\.version>\s*(\d+(?:\.\d+)?)\s*<", "java.version"),
+                (r"<maven\.compiler\.source>\s*(\d+(?:\.\d+)?)\s*<", "maven.compiler.source"),
+                (
+                    r#"sourceCompatibility\s*=\s*(?:JavaVersion\.VERSION_)?['"]?(\d+(?:[._]\d+)?)"#,
+                    "sourceCompatibility",
+                ),
+            ]
+            .into_iter()
+            .map(|(pattern, label)| (Regex::new(pattern).unwrap(), label))
+            .collect(),
+            comparator: Regex::new(r"(>=|<=|~=|==|!=|\^|~|>|<|=)?\s*v?(\d+(?:\.(?:\d+|x|\*))*)")
+                .unwrap(),
+            declarations: Vec::new(),
+            ci: Vec::new(),
+        }
+    }
+
+    pub fn finish(self) -> ToolchainRequirements {
+        let mut requirements = Vec::new();
+        for language in LANGUAGES {
+            // The manifest closest to the root declares the project's minimum
+            let declaration = self
+                .declarations
+                .iter()
+                .filter(|d| d.language == *language)
+                .min_by(|a, b| {
+                    a.depth
+                        .cmp(&b.depth)
+                        .then_with(|| compare_versions(&a.minimum, &b.minimum))
+                });
+            let mut ci_versions: Vec<String> = self
+                .ci
+                .iter()
+                .filter(|(l, _, _)| l == language)
+                .flat_map(|(_, versions, _)| versions.iter().cloned())
+                .collect();
+            ci_versions.sort_by(|a, b| compare_versions(a, b));
+            ci_versions.dedup();
+            let mut ci: Vec<EvidenceRef> = self
+                .ci
+                .iter()
+                .filter(|(l, _, _)| l == language)
+                .map(|(_, _, evidence)| evidence.clone())
+                .collect();
+            ci.sort_by(|a, b| a.path.cmp(&b.path));
+            if declaration.is_none() && ci_versions.is_empty() {
+                continue;
+            }
+            requirements.push(ToolchainRequirement {
+                language: language.to_string(),
+                minimum: declaration.map(|d| d.minimum.clone()),
+                constraint: declaration.map(|d| d.constraint.clone()),
+                declared_in: declaration.map(|d| d.evidence.clone()),
+                ci_versions,
+                ci,
+            });
+        }
+        let signals = signals(&requirements);
+        ToolchainRequirements {
+            requirements,
+            signals,
+        }
+    }
+
+    // The lowest version a constraint allows, upper bounds and exclusions
+    // aside: ">=3.9,<4" is 3.9, "^18 || >=20" is 18
+    fn minimum(&self, language: &str, constraint: &str) -> Option<String> {
+        self.comparator
+            .captures_iter(constraint)
+            .filter(|c| !matches!(c.get(1).map(|op| op.as_str()), Some("<" | "<=" | "!=")))
+            .map(|c| normalize(language, &c[2]))
+            .min_by(|a, b| compare_versions(a, b))
+    }
+
+    fn declare(
+        &mut self,
+        file: &ScannedFile<'_>,
+        language: &'static str,
+        label: &str,
+        constraint: &str,
+        line: Option<u32>,
+    ) {
+        let constraint = constraint.trim().trim_matches(|c| c == '"' || c == '\'');
+        let Some(minimum) = self.minimum(language, constraint) else {
+            return;
+        };
+        self.declarations.push(Declaration {
+            language,
+            constraint: constraint.to_string(),
+            minimum,
+            depth: file.depth,
+            evidence: evidence(label, file.relative, line),
+        });
+    }
+
+    fn detect_manifest(&mut self, file: &ScannedFile<'_>, name: &str, content: &str) {
+        let line_of = |needle: &str| first_line(content, |line| line.contains(needle));
+        match name {
+            "cargo.toml" => {
+                let Ok(manifest) = content.parse::<toml::Value>() else {
+                    return;
+                };
+                // rust-version.workspace = true inherits, and is not a string
+                let rust_version = manifest
+                    .get("package")
+                    .and_then(|package| package.get("rust-version"))
+                    .or_else(|| {
+                        manifest
+                            .get("workspace")?
+                            .get("package")?
+                            .get("rust-version")
+                    })
+                    .and_then(|version| version.as_str());
+                if let Some(version) = rust_version {
+                    self.declare(
+                        file,
+                        "Rust",
+                        "rust-version",
+                        version,
+                        line_of("rust-version"),
+                    );
+                }
+            }
+            "package.json" => {
+                let Ok(manifest) = serde_json::from_str::<serde_json::Value>(content) else {
+                    return;
+                };
+                if let Some(node) = manifest["engines"]["node"].as_str() {
+                    let line = first_line(content, |line| {
+                        line.contains("\"node\"") && line.contains(node)
+                    });
+                    self.declare(file, "Node.js", "engines.node", node, line);
+                }
+            }
+            "pyproject.toml" => {
+                let Ok(manifest) = content.parse::<toml::Value>() else {
+                    return;
+                };
+                let requires_python = manifest
+                    .get("project")
+                    .and_then(|project| project.get("requires-python"))
+                    .and_then(|version| version.as_str());
+                let poetry_python = manifest
+                    .get("tool")
+                    .and_then(|tool| tool.get("poetry")?.get("dependencies")?.get("python"))
+                    .and_then(|version| version.as_str());
+                if let Some(version) = requires_python {
+                    self.declare(
+                        file,
+                        "Python",
+                        "requires-python",
+                        version,
+                        line_of("requires-python"),
+                    );
+                } else if let Some(version) = poetry_python {
+                    let line = first_line(content, |line| line.trim_start().starts_with("python"));
+                    self.declare(file, "Python", "poetry python", version, line);
+                }
+            }
+            "setup.py" | "setup.cfg" => {
+                if let Some(captures) = self.python_requires.captures(content) {
+                    let version = captures[1].to_string();
+                    self.declare(
+                        file,
+                        "Python",
+                        "python_requires",
+                        &version,
+                        line_of("python_requires"),
+                    );
+                }
+            }
+            "go.mod" => {
+                // Since Go 1.21 the directive is the minimum required version
+                let found = content.lines().enumerate().find_map(|(index, line)| {
+                    let captures = self.go_directive.captures(line.trim())?;
+                    Some((captures[1].to_string(), index as u32 + 1))
+                });
+                if let Some((version, line)) = found {
+                    self.declare(file, "Go", "go directive", &version, Some(line));
+                }
+            }
+            "pom.xml" | "build.gradle" | "build.gradle.kts" => {
+                let found = self.java_versions.iter().find_map(|(pattern, label)| {
+                    let captures = pattern.captures(content)?;
+                    let version = captures[1].replace('_', ".");
+                    let matched = captures.get(0)?.as_str();
+                    Some((version, *label, line_of(matched)))
+                });
+                if let Some((version, label, line)) = found {
+                    self.declare(file, "Java", label, &version, line);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn detect_ci(&mut self, file: &ScannedFile<'_>, content: &str) {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut found: Vec<(&'static str, String, u32)> = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            let line_number = index as u32 + 1;
+            if let Some(captures) = self.ci_toolchain.captures(line) {
+                found.push(("Rust", captures[1].to_string(), line_number));
+            }
+            let Some(captures) = self.yaml_key.captures(line) else {
+                continue;
+            };
+            let Some((_, language)) = CI_KEYS.iter().find(|(key, _)| *key == &captures[1]) else {
+                continue;
+            };
+            let value = &captures[2];
+            // Inline value, flow list, or a block list on the following lines
+            let items: Vec<(String, u32)> = if let Some(list) = value.strip_prefix('[') {
+                list.trim_end_matches(']')
+                    .split(',')
+                    .map(|item| (item.to_string(), line_number))
+                    .collect()
+            } else if value.is_empty() {
+                lines[index + 1..]
+                    .iter()
+                    .enumerate()
+                    .map_while(|(offset, next)| {
+                        let captures = self.yaml_item.captures(next)?;
+                        Some((captures[1].to_string(), line_number + offset as u32 + 1))
+                    })
+                    .collect()
+            } else {
+                vec![(value.to_string(), line_number)]
+            };
+            for (item, line) in items {
+                let item = item.trim().trim_matches(|c| c == '"' || c == '\'');
+                if let Some(version) = numbered_version(item) {
+                    found.push((language, normalize(language, &version), line));
+                }
+            }
+        }
+
+        for language in LANGUAGES {
+            let versions: Vec<&(&str, String, u32)> =
+                found.iter().filter(|(l, _, _)| l == language).collect();
+            let Some(first) = versions.iter().map(|(_, _, line)| *line).min() else {
+                continue;
+            };
+            self.ci.push((
+                language,
+                versions
+                    .iter()
+                    .map(|(_, version, _)| version.clone())
+                    .collect(),
+                evidence(language, file.relative, Some(first)),
+            ));
+        }
+    }
+}
+
+impl FileClassifier for ToolchainClassifier {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        if file.relative.parent() == Some(Path::new(".github/workflows")) {
+            if let Some(content) = file.content() {
+                self.detect_ci(file, content);
+            }
+            return;
+        }
+        if !file.in_structure || file.depth > METADATA_DEPTH {
+            return;
+        }
+        let name = file.file_name().to_lowercase();
+        if [
+            "cargo.toml",
+            "package.json",
+            "pyproject.toml",
+            "setup.py",
+            "setup.cfg",
+            "go.mod",
+            "pom.xml",
+            "build.gradle",
+            "build.gradle.kts",
+        ]
+        .contains(&name.as_str())
+            && let Some(content) = file.content()
+        {
+            self.detect_manifest(file, &name, content);
+        }
+    }
+}
+
+// "Requires: Rust 1.70+, Python 3.9+" for the summary, CI's oldest version
+// standing in for an undeclared minimum
+pub fn summary_line(requirements: &ToolchainRequirements) -> Option<String> {
+    let parts: Vec<String> = requirements
+        .requirements
+        .iter()
+        .filter_map(
+            |requirement| match (&requirement.minimum, requirement.ci_versions.first()) {
+                (Some(minimum), _) => Some(format!("{} {}+", requirement.language, minimum)),
+                (None, Some(oldest)) => Some(format!(
+                    "{} {}+ (tested in CI)",
+                    requirement.language, oldest
+                )),
+                (None, None) => None,
+            },
+        )
+        .collect();
+    (!parts.is_empty()).then(|| format!("Requires: {}", parts.join(", ")))
+}
+
+// How the declared minimums compare with what CI runs
+fn signals(requirements: &[ToolchainRequirement]) -> Vec<String> {
+    let mut signals = Vec::new();
+    for requirement in requirements {
+        let language = &requirement.language;
+        match (&requirement.minimum, requirement.ci_versions.first()) {
+            (Some(minimum), Some(oldest)) => {
+                if compare_versions(oldest, minimum) == Ordering::Greater {
+                    signals.push(format!(
+                        "{} {} is declared but CI's oldest {} is {}, the minimum is not tested.",
+                        language, minimum, language, oldest
+                    ));
+                } else {
+                    signals.push(format!(
+                        "CI tests the declared {} minimum, {}.",
+                        language, minimum
+                    ));
+                }
+            }
+            (None, Some(_)) => signals.push(format!(
+                "No minimum {} version is declared, CI runs {}.",
+                language,
+                requirement.ci_versions.join(", ")
+            )),
+            _ => {}
+        }
+    }
+    signals
+}
+
+// A version number from a CI value, None for channels and expressions such
+// as stable, lts/* or ${{ matrix.node }}
+fn numbered_version(value: &str) -> Option<String> {
+    let version = value.trim_start_matches('v');
+    let numbered = !version.is_empty()
+        && version.starts_with(|c: char| c.is_ascii_digit())
+        && version
+            .split('.')
+            .all(|part| part == "x" || part == "*" || part.chars().all(|c| c.is_ascii_digit()));
+    numbered.then(|| version.to_string())
+}
+
+// Wildcard parts dropped ("18.x" is 18), and Java's old 1.8 spelling is 8
+fn normalize(language: &str, version: &str) -> String {
+    let parts: Vec<&str> = version
+        .split('.')
+        .take_while(|part| *part != "x" && *part != "*")
+        .collect();
+    if language == "Java" && parts.len() > 1 && parts[0] == "1" {
+        return parts[1..].join(".");
+    }
+    parts.join(".")
+}
+
+// Numeric comparison part by part, missing parts count as 0
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |version: &str| -> Vec<u64> {
+        version
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    let (a, b) = (parts(a), parts(b));
+    for index in 0..a.len().max(b.len()) {
+        let ordering = a.get(index).unwrap_or(&0).cmp(b.get(index).unwrap_or(&0));
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+fn evidence(label: &str, path: &Path, line: Option<u32>) -> EvidenceRef {
+    EvidenceRef {
+        label: label.to_string(),
+        path: path.to_path_buf(),
+        line,
+        url: None,
+    }
+}
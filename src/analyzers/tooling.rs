@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+
+use crate::file_index::FileIndex;
+use crate::types::{ConfigFile, DeveloperTool, DeveloperTooling};
+
+// (file name prefix, tool, category), matched case-insensitively near the root
+const TOOL_FILES: &[(&str, &str, &str)] = &[
+    ("clippy.toml", "clippy", "linter"),
+    (".clippy.toml", "clippy", "linter"),
+    ("rustfmt.toml", "rustfmt", "formatter"),
+    (".rustfmt.toml", "rustfmt", "formatter"),
+    ("ruff.toml", "ruff", "linter"),
+    (".ruff.toml", "ruff", "linter"),
+    (".flake8", "flake8", "linter"),
+    (".pylintrc", "pylint", "linter"),
+    ("mypy.ini", "mypy", "type_checker"),
+    (".golangci.", "golangci-lint", "linter"),
+    (".eslintrc", "eslint", "linter"),
+    ("eslint.config.", "eslint", "linter"),
+    (".prettierrc", "prettier", "formatter"),
+    ("prettier.config.", "prettier", "formatter"),
+    ("biome.json", "biome", "linter"),
+    (".stylelintrc", "stylelint", "linter"),
+    (".rubocop.yml", "rubocop", "linter"),
+    (".clang-format", "clang-format", "formatter"),
+    (".clang-tidy", "clang-tidy", "linter"),
+    (".editorconfig", "editorconfig", "editor_config"),
+    ("commitlint.config.", "commitlint", "commit_conventions"),
+    (".commitlintrc", "commitlint", "commit_conventions"),
+    (".pre-commit-config.yaml", "pre-commit", "git_hooks"),
+    ("lefthook.yml", "lefthook", "git_hooks"),
+    (".lintstagedrc", "lint-staged", "git_hooks"),
+];
+
+// pyproject.toml tables that configure a tool
+const PYPROJECT_TOOLS: &[(&str, &str, &str)] = &[
+    ("[tool.ruff", "ruff", "linter"),
+    ("[tool.black", "black", "formatter"),
+    ("[tool.isort", "isort", "formatter"),
+    ("[tool.pylint", "pylint", "linter"),
+    ("[tool.mypy", "mypy", "type_checker"),
+];
+
+// package.json keys or dev dependencies that configure a tool
+const PACKAGE_JSON_TOOLS: &[(&str, &str, &str)] = &[
+    ("eslint", "eslint", "linter"),
+    ("eslintConfig", "eslint", "linter"),
+    ("prettier", "prettier", "formatter"),
+    ("@biomejs/biome", "biome", "linter"),
+    ("husky", "husky", "git_hooks"),
+    ("lint-staged", "lint-staged", "git_hooks"),
+    ("@commitlint/cli", "commitlint", "commit_conventions"),
+    ("commitlint", "commitlint", "commit_conventions"),
+];
+
+// Categories that count towards maturity, with their weight out of 100
+const MATURITY_WEIGHTS: &[(&str, f64)] = &[
+    ("linter", 30.0),
+    ("formatter", 25.0),
+    ("git_hooks", 20.0),
+    ("commit_conventions", 15.0),
+    ("editor_config", 10.0),
+];
+
+// Linter, formatter, hook and editor configuration analyzer
+pub struct ToolingAnalyzer;
+
+impl ToolingAnalyzer {
+    pub fn analyze_tooling(
+        &self,
+        file_structure: &FileIndex,
+        config_files: &[ConfigFile],
+    ) -> DeveloperTooling {
+        let mut tools: Vec<DeveloperTool> = Vec::new();
+        let mut add = |name: &str, category: &str, config_path: &Path| {
+            if !tools.iter().any(|tool| tool.name == name) {
+                tools.push(DeveloperTool {
+                    name: name.to_string(),
+                    category: category.to_string(),
+                    config_path: config_path.to_path_buf(),
+                });
+            }
+        };
+
+        let mut paths: Vec<&PathBuf> = file_structure
+            .files()
+            .iter()
+            .map(|file| &file.path)
+            .collect();
+        paths.sort_by_key(|path| path.components().count());
+
+        for path in paths.iter().filter(|path| path.components().count() <= 3) {
+            // Husky keeps its hooks in a .husky directory rather than a config file
+            if path.starts_with(".husky") {
+                add("husky", "git_hooks", Path::new(".husky"));
+                continue;
+            }
+
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let file_name = file_name.to_lowercase();
+            if let Some((_, name, category)) = TOOL_FILES
+                .iter()
+                .find(|(prefix, _, _)| file_name.starts_with(prefix))
+            {
+                add(name, category, path);
+            }
+        }
+
+        for config in config_files {
+            match config.file_type.as_str() {
+                "python" => {
+                    for (table, name, category) in PYPROJECT_TOOLS {
+                        if config.content.contains(table) {
+                            add(name, category, &config.path);
+                        }
+                    }
+                }
+                "npm" => {
+                    let Ok(package) = serde_json::from_str::<serde_json::Value>(&config.content)
+                    else {
+                        continue;
+                    };
+                    for (key, name, category) in PACKAGE_JSON_TOOLS {
+                        let configured =
+                            ["", "devDependencies", "dependencies"]
+                                .iter()
+                                .any(|section| {
+                                    let table = if section.is_empty() {
+                                        &package
+                                    } else {
+                                        &package[section]
+                                    };
+                                    table.get(key).is_some()
+                                });
+                        if configured {
+                            add(name, category, &config.path);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let has_category = |category: &str| tools.iter().any(|tool| tool.category == category);
+        let maturity_score = MATURITY_WEIGHTS
+            .iter()
+            .filter(|(category, _)| has_category(category))
+            .fold(0.0, |score, (_, weight)| score + weight);
+        let missing_categories = MATURITY_WEIGHTS
+            .iter()
+            .filter(|(category, _)| !has_category(category))
+            .map(|(category, _)| category.to_string())
+            .collect();
+
+        DeveloperTooling {
+            tools,
+            maturity_score,
+            missing_categories,
+        }
+    }
+}
@@ -1,18 +1,74 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
+use crate::analyzers::detections::{Category, DetectionRules, add_detection};
+use crate::analyzers::environments::ExecutionEnvironmentDetector;
+use crate::analyzers::feature_flags::FeatureFlagDetector;
+use crate::file_index::FileIndex;
 use crate::types::ConfigFile;
-use crate::types::DirectoryInfo;
-use crate::types::FileInfo;
+use crate::types::LanguageBoundary;
+use crate::types::LanguageLayout;
 use crate::types::ProjectInfo;
 
+// Data, markup, styling and build languages, which don't make a code language
+const NON_CODE_LANGUAGES: &[&str] = &[
+    "JSON",
+    "YAML",
+    "TOML",
+    "INI",
+    "XML",
+    "Markdown",
+    "LaTeX",
+    "HTML",
+    "CSS",
+    "SCSS",
+    "Sass",
+    "Less",
+    "SQL",
+    "Protocol Buffers",
+    "GraphQL",
+    "Dockerfile",
+    "Makefile",
+    "CMake",
+];
+
+const FRONTEND_LANGUAGES: &[&str] = &["JavaScript", "TypeScript", "Vue", "Svelte"];
+
+// Directories that group several projects, whose children are the real boundaries
+const CONTAINER_DIRECTORIES: &[&str] = &[
+    "apps",
+    "packages",
+    "services",
+    "crates",
+    "libs",
+    "modules",
+    "cmd",
+    "components",
+];
+
+// Leaders closer than this (percent of the leader's lines) are too close to call locally
+const AMBIGUOUS_MARGIN: f64 = 10.0;
+
+// Share of a codebase a language needs to count as one of its languages
+const SIGNIFICANT_SHARE: f64 = 10.0;
+// Share of a directory's code a language needs to own it
+const BOUNDARY_SHARE: f64 = 60.0;
+
 // Project type detector
-pub struct ProjectTypeDetector;
+pub struct ProjectTypeDetector {
+    rules: DetectionRules,
+}
 
 impl ProjectTypeDetector {
+    pub fn new(rules: DetectionRules) -> Self {
+        Self { rules }
+    }
+
     pub fn detect_project_info(
         &self,
         config_files: &[ConfigFile],
-        file_structure: &DirectoryInfo,
+        file_structure: &FileIndex,
+        github_language: Option<&str>,
     ) -> ProjectInfo {
         let mut project_types = Vec::new();
         let mut frameworks = Vec::new();
@@ -21,69 +77,35 @@ impl ProjectTypeDetector {
         let mut testing_frameworks = Vec::new();
         let mut ci_cd_tools = Vec::new();
         let mut deployment_configs = Vec::new();
-        let database_technologies = Vec::new();
-
-        // Analyze config files
-        for config in config_files {
-            match config.file_type.as_str() {
-                "npm" => {
-                    package_managers.push("npm".to_string());
-                    self.detect_js_frameworks(&config.content, &mut frameworks);
-                    self.detect_js_tools(
-                        &config.content,
-                        &mut build_tools,
-                        &mut testing_frameworks,
-                    );
-                }
-                "cargo" => {
-                    package_managers.push("cargo".to_string());
-                    build_tools.push("cargo".to_string());
-                    project_types.push("rust".to_string());
-                }
-                "pip" => {
-                    package_managers.push("pip".to_string());
-                    project_types.push("python".to_string());
-                }
-                "maven" => {
-                    package_managers.push("maven".to_string());
-                    build_tools.push("maven".to_string());
-                    project_types.push("java".to_string());
-                }
-                "gradle" => {
-                    package_managers.push("gradle".to_string());
-                    build_tools.push("gradle".to_string());
-                    project_types.push("java".to_string());
-                }
-                "docker" => {
-                    deployment_configs.push("docker".to_string());
-                }
-                "docker-compose" => {
-                    deployment_configs.push("docker-compose".to_string());
-                }
-                "kubernetes" => {
-                    deployment_configs.push("kubernetes".to_string());
-                }
-                "terraform" => {
-                    deployment_configs.push("terraform".to_string());
-                }
-                "github-actions" => {
-                    ci_cd_tools.push("github-actions".to_string());
-                }
-                "travis" => {
-                    ci_cd_tools.push("travis-ci".to_string());
-                }
-                _ => {}
-            }
+        let mut database_technologies = Vec::new();
+
+        // Match the detection rule packs against manifests and the tree
+        for (category, detection) in self.rules.detect(config_files, file_structure) {
+            let list = match category {
+                Category::ProjectType => &mut project_types,
+                Category::Framework => &mut frameworks,
+                Category::BuildTool => &mut build_tools,
+                Category::PackageManager => &mut package_managers,
+                Category::TestingFramework => &mut testing_frameworks,
+                Category::CiCd => &mut ci_cd_tools,
+                Category::Deployment => &mut deployment_configs,
+                Category::Database => &mut database_technologies,
+            };
+            add_detection(list, detection);
         }
 
-        // Detect primary language from file extensions
-        let primary_language = self.detect_primary_language(file_structure);
+        // Detect primary language from lines of code
+        let primary_language = self.detect_primary_language(file_structure, github_language);
+
+        let language_layout = self.detect_language_layout(file_structure);
 
-        // Detect project types based on file structure
-        self.detect_project_types_from_structure(file_structure, &mut project_types);
+        let feature_flags = FeatureFlagDetector.detect(config_files, file_structure);
+        let execution_environments =
+            ExecutionEnvironmentDetector.detect(config_files, file_structure);
 
         ProjectInfo {
             primary_language,
+            language_layout,
             project_type: project_types,
             frameworks,
             build_tools,
@@ -92,151 +114,136 @@ impl ProjectTypeDetector {
             ci_cd_tools,
             deployment_configs,
             database_technologies,
+            feature_flags,
+            execution_environments,
         }
     }
 
-    fn detect_js_frameworks(&self, content: &str, frameworks: &mut Vec<String>) {
-        let frameworks_to_check = vec![
-            ("react", "React"),
-            ("vue", "Vue.js"),
-            ("angular", "Angular"),
-            ("svelte", "Svelte"),
-            ("express", "Express.js"),
-            ("nestjs", "NestJS"),
-            ("next", "Next.js"),
-            ("nuxt", "Nuxt.js"),
-            ("gatsby", "Gatsby"),
-            ("electron", "Electron"),
-        ];
-
-        for (dep_name, framework_name) in frameworks_to_check {
-            if content.contains(dep_name) {
-                frameworks.push(framework_name.to_string());
-            }
-        }
-    }
-
-    fn detect_js_tools(
+    // Code language with the most lines; GitHub's language settles close calls
+    // and repositories without recognised code
+    fn detect_primary_language(
         &self,
-        content: &str,
-        build_tools: &mut Vec<String>,
-        testing_frameworks: &mut Vec<String>,
-    ) {
-        let build_tools_to_check = vec![
-            ("webpack", "Webpack"),
-            ("vite", "Vite"),
-            ("rollup", "Rollup"),
-            ("parcel", "Parcel"),
-            ("esbuild", "ESBuild"),
-            ("snowpack", "Snowpack"),
-        ];
-
-        let testing_tools_to_check = vec![
-            ("jest", "Jest"),
-            ("mocha", "Mocha"),
-            ("chai", "Chai"),
-            ("cypress", "Cypress"),
-            ("playwright", "Playwright"),
-            ("puppeteer", "Puppeteer"),
-            ("jasmine", "Jasmine"),
-        ];
-
-        for (tool_name, tool_display) in build_tools_to_check {
-            if content.contains(tool_name) {
-                build_tools.push(tool_display.to_string());
+        file_structure: &FileIndex,
+        github_language: Option<&str>,
+    ) -> Option<String> {
+        let mut language_loc: HashMap<String, u64> = HashMap::new();
+        for file in file_structure.files() {
+            if let Some(language) = file.language.as_ref().filter(|l| is_code_language(l)) {
+                *language_loc.entry(language.clone()).or_insert(0) +=
+                    file.lines_of_code.unwrap_or(0) as u64;
             }
         }
 
-        for (tool_name, tool_display) in testing_tools_to_check {
-            if content.contains(tool_name) {
-                testing_frameworks.push(tool_display.to_string());
-            }
-        }
-    }
+        let mut ranked: Vec<(String, u64)> = language_loc
+            .into_iter()
+            .filter(|(_, loc)| *loc > 0)
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
 
-    fn detect_primary_language(&self, file_structure: &DirectoryInfo) -> Option<String> {
-        let mut language_counts: HashMap<String, u32> = HashMap::new();
-        self.count_languages(file_structure, &mut language_counts);
+        let Some((leader, leader_loc)) = ranked.first() else {
+            return github_language.map(|language| language.to_string());
+        };
 
-        language_counts
-            .into_iter()
-            .max_by_key(|(_, count)| *count)
-            .map(|(lang, _)| lang)
+        let contenders: Vec<&String> = ranked
+            .iter()
+            .take_while(|(_, loc)| {
+                (leader_loc - loc) as f64 / *leader_loc as f64 * 100.0 <= AMBIGUOUS_MARGIN
+            })
+            .map(|(language, _)| language)
+            .collect();
+        let github_choice = github_language.and_then(|github| {
+            contenders
+                .iter()
+                .find(|language| language.eq_ignore_ascii_case(github))
+        });
+
+        Some(github_choice.copied().unwrap_or(leader).clone())
     }
 
-    fn count_languages(&self, dir: &DirectoryInfo, language_counts: &mut HashMap<String, u32>) {
-        for file in &dir.files {
-            if let Some(language) = &file.language {
-                *language_counts.entry(language.clone()).or_insert(0) += 1;
+    // Single-language, polyglot or frontend/backend split, with the language owning each directory
+    fn detect_language_layout(&self, file_structure: &FileIndex) -> LanguageLayout {
+        let mut totals: HashMap<String, u64> = HashMap::new();
+        let mut by_directory: HashMap<PathBuf, HashMap<String, u64>> = HashMap::new();
+        for file in file_structure.files() {
+            let Some(language) = file.language.as_ref().filter(|l| is_code_language(l)) else {
+                continue;
+            };
+            let loc = file.lines_of_code.unwrap_or(0) as u64;
+            *totals.entry(language.clone()).or_insert(0) += loc;
+            if let Some(directory) = boundary_directory(&file.path) {
+                *by_directory
+                    .entry(directory)
+                    .or_default()
+                    .entry(language.clone())
+                    .or_insert(0) += loc;
             }
         }
 
-        for subdir in &dir.subdirectories {
-            self.count_languages(subdir, language_counts);
-        }
-    }
-
-    fn detect_project_types_from_structure(
-        &self,
-        file_structure: &DirectoryInfo,
-        project_types: &mut Vec<String>,
-    ) {
-        let mut all_files = Vec::new();
-        self.collect_all_files(file_structure, &mut all_files);
-
-        // Check for common project patterns
-        let _has_src_dir = self.has_directory(file_structure, "src");
-        let _has_lib_dir = self.has_directory(file_structure, "lib");
-        let _has_bin_dir = self.has_directory(file_structure, "bin");
-        let has_tests_dir = self.has_directory(file_structure, "tests")
-            || self.has_directory(file_structure, "test");
-        let has_docs_dir = self.has_directory(file_structure, "docs")
-            || self.has_directory(file_structure, "documentation");
-        let has_examples_dir = self.has_directory(file_structure, "examples");
-
-        // Check for specific file patterns
-        let has_main_rs = all_files.iter().any(|f| f.name == "main.rs");
-        let has_lib_rs = all_files.iter().any(|f| f.name == "lib.rs");
-        let has_index_html = all_files.iter().any(|f| f.name == "index.html");
-        let has_server_files = all_files
-            .iter()
-            .any(|f| f.name.contains("server") || f.name.contains("app"));
+        let total_loc: u64 = totals.values().sum();
+        let mut languages: Vec<(String, u64)> = totals
+            .into_iter()
+            .filter(|(_, loc)| {
+                total_loc > 0 && *loc as f64 / total_loc as f64 * 100.0 >= SIGNIFICANT_SHARE
+            })
+            .collect();
+        languages.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
 
-        // Determine project types
-        if has_main_rs {
-            project_types.push("cli-application".to_string());
-        }
-        if has_lib_rs {
-            project_types.push("library".to_string());
-        }
-        if has_index_html {
-            project_types.push("web-application".to_string());
-        }
-        if has_server_files {
-            project_types.push("backend-service".to_string());
-        }
-        if has_tests_dir {
-            project_types.push("tested-project".to_string());
-        }
-        if has_docs_dir {
-            project_types.push("documented-project".to_string());
-        }
-        if has_examples_dir {
-            project_types.push("example-driven".to_string());
+        let mut boundaries: Vec<LanguageBoundary> = by_directory
+            .into_iter()
+            .filter_map(|(directory, counts)| {
+                let directory_loc: u64 = counts.values().sum();
+                let (language, loc) = counts.into_iter().max_by_key(|(_, loc)| *loc)?;
+                let share = loc as f64 / directory_loc.max(1) as f64 * 100.0;
+                (loc > 0 && share >= BOUNDARY_SHARE).then_some(LanguageBoundary {
+                    directory,
+                    language,
+                    share,
+                    lines_of_code: loc,
+                })
+            })
+            .collect();
+        boundaries.sort_by_key(|boundary| std::cmp::Reverse(boundary.lines_of_code));
+
+        // A split needs both sides to own directories of their own
+        let owns = |frontend: bool| {
+            boundaries.iter().any(|boundary| {
+                FRONTEND_LANGUAGES.contains(&boundary.language.as_str()) == frontend
+                    && languages.iter().any(|(l, _)| *l == boundary.language)
+            })
+        };
+        let classification = match languages.len() {
+            0 => "unknown",
+            1 => "single-language",
+            _ if owns(true) && owns(false) => "frontend-backend",
+            _ => "polyglot",
+        };
+
+        LanguageLayout {
+            classification: classification.to_string(),
+            languages: languages
+                .into_iter()
+                .map(|(language, _)| language)
+                .collect(),
+            boundaries,
         }
     }
+}
 
-    fn has_directory(&self, dir: &DirectoryInfo, name: &str) -> bool {
-        dir.subdirectories.iter().any(|d| d.name == name)
-    }
+pub fn is_code_language(language: &str) -> bool {
+    !NON_CODE_LANGUAGES.contains(&language)
+}
 
-    fn collect_all_files(&self, dir: &DirectoryInfo, all_files: &mut Vec<FileInfo>) {
-        for file in &dir.files {
-            all_files.push(file.clone());
-        }
+// Top-level directory of a file, or its child for monorepo container directories
+fn boundary_directory(path: &Path) -> Option<PathBuf> {
+    let components: Vec<_> = path.components().collect();
+    if components.len() < 2 {
+        return None;
+    }
 
-        for subdir in &dir.subdirectories {
-            self.collect_all_files(subdir, all_files);
-        }
+    let top = components[0].as_os_str().to_string_lossy();
+    if CONTAINER_DIRECTORIES.contains(&top.as_ref()) && components.len() > 2 {
+        Some(components[..2].iter().collect())
+    } else {
+        Some(components[..1].iter().collect())
     }
 }
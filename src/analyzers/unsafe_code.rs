@@ -0,0 +1,306 @@
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::analyzers::scan::{FileClassifier, ScannedFile, strip_comments};
+use crate::types::{CrateUnsafety, UnsafeHotspot, UnsafeUsage};
+
+// Build dependencies that compile or bind to foreign code
+const FFI_BUILD_TOOLS: &[&str] = &[
+    "bindgen",
+    "cc",
+    "cmake",
+    "pkg-config",
+    "cxx-build",
+    "autotools",
+];
+
+// Files listed per crate as the places to start a review
+const HOTSPOTS_PER_CRATE: usize = 5;
+
+// Unsafe code counts of one source file
+struct FileUnsafety {
+    path: PathBuf,
+    unsafe_blocks: u32,
+    unsafe_functions: u32,
+    unsafe_impls: u32,
+    extern_blocks: u32,
+    exported_functions: u32,
+    lint: Option<String>, // Crate-level forbid or deny of unsafe_code
+}
+
+// A Cargo.toml declaring a package
+struct Manifest {
+    dir: PathBuf,
+    name: String,
+    roots: Vec<PathBuf>, // lib and bin crate roots, where #![forbid] applies
+    lint: Option<String>,
+    inherits_lints: bool,
+    build_tools: Vec<String>,
+    links: Option<String>,
+    has_build_script: bool,
+}
+
+// Counts unsafe code and FFI boundaries of Rust crates during the shared walk
+pub struct UnsafeCodeClassifier {
+    patterns: UnsafePatterns,
+    files: Vec<FileUnsafety>,
+    manifests: Vec<Manifest>,
+    workspace_lint: Option<String>,
+    build_scripts: Vec<PathBuf>,
+}
+
+struct UnsafePatterns {
+    block: Regex,
+    function: Regex,
+    implementation: Regex,
+    extern_block: Regex,
+    exported: Regex,
+    lint: Regex,
+}
+
+impl Default for UnsafeCodeClassifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UnsafeCodeClassifier {
+    pub fn new() -> Self {
+        Self {
+            patterns: UnsafePatterns {
+                block: Regex::new(r"\bunsafe\s*\{").unwrap(),
+                function: Regex::new(r#"\bunsafe\s+(?:extern\s+"[^"]*"\s+)?fn\s+\w"#).unwrap(),
+                implementation: Regex::new(r"\bunsafe\s+impl\b").unwrap(),
+                extern_block: Regex::new(r#"\bextern\s+"(?:C|C-unwind|system)"\s*\{"#).unwrap(),
+                exported: Regex::new(r"#\[\s*(?:unsafe\s*\(\s*)?no_mangle\b").unwrap(),
+                lint: Regex::new(r"#!\[\s*(forbid|deny)\s*\([^)]*\bunsafe_code\b").unwrap(),
+            },
+            files: Vec::new(),
+            manifests: Vec::new(),
+            workspace_lint: None,
+            build_scripts: Vec::new(),
+        }
+    }
+
+    // None when the repository has no Rust package
+    pub fn finish(self) -> Option<UnsafeUsage> {
+        if self.manifests.is_empty() {
+            return None;
+        }
+
+        let mut crates: Vec<CrateUnsafety> = self
+            .manifests
+            .iter()
+            .map(|manifest| CrateUnsafety {
+                name: manifest.name.clone(),
+                manifest: manifest.dir.join("Cargo.toml"),
+                unsafe_blocks: 0,
+                unsafe_functions: 0,
+                unsafe_impls: 0,
+                extern_blocks: 0,
+                exported_functions: 0,
+                unsafe_code_lint: match &manifest.lint {
+                    Some(lint) => Some(lint.clone()),
+                    None if manifest.inherits_lints => self.workspace_lint.clone(),
+                    None => None,
+                },
+                build_script: manifest.has_build_script
+                    || self.build_scripts.contains(&manifest.dir.join("build.rs")),
+                ffi_build_tools: manifest.build_tools.clone(),
+                links: manifest.links.clone(),
+                hotspots: Vec::new(),
+            })
+            .collect();
+
+        for file in &self.files {
+            // The innermost package directory holding the file owns it
+            let Some(index) = self
+                .manifests
+                .iter()
+                .enumerate()
+                .filter(|(_, manifest)| file.path.starts_with(&manifest.dir))
+                .max_by_key(|(_, manifest)| manifest.dir.components().count())
+                .map(|(index, _)| index)
+            else {
+                continue;
+            };
+            let manifest = &self.manifests[index];
+            let entry = &mut crates[index];
+
+            entry.unsafe_blocks += file.unsafe_blocks;
+            entry.unsafe_functions += file.unsafe_functions;
+            entry.unsafe_impls += file.unsafe_impls;
+            entry.extern_blocks += file.extern_blocks;
+            entry.exported_functions += file.exported_functions;
+            if file.lint.is_some() && manifest.roots.contains(&file.path) {
+                entry.unsafe_code_lint = file.lint.clone();
+            }
+
+            let count = file.unsafe_blocks + file.unsafe_functions + file.unsafe_impls;
+            if count > 0 {
+                entry.hotspots.push(UnsafeHotspot {
+                    path: file.path.clone(),
+                    unsafe_count: count,
+                    url: None,
+                });
+            }
+        }
+
+        for entry in &mut crates {
+            entry.hotspots.sort_by(|a, b| {
+                b.unsafe_count
+                    .cmp(&a.unsafe_count)
+                    .then(a.path.cmp(&b.path))
+            });
+            entry.hotspots.truncate(HOTSPOTS_PER_CRATE);
+        }
+        crates.sort_by(|a, b| a.manifest.cmp(&b.manifest));
+
+        Some(UnsafeUsage {
+            total_unsafe_blocks: crates.iter().map(|c| c.unsafe_blocks).sum(),
+            total_unsafe_functions: crates.iter().map(|c| c.unsafe_functions).sum(),
+            crates_forbidding_unsafe: crates
+                .iter()
+                .filter(|c| c.unsafe_code_lint.as_deref() == Some("forbid"))
+                .count() as u32,
+            crates,
+        })
+    }
+
+    fn add_manifest(&mut self, file: &ScannedFile<'_>, content: &str) {
+        let Ok(toml) = content.parse::<toml::Value>() else {
+            return;
+        };
+        if let Some(level) = toml
+            .get("workspace")
+            .and_then(|w| w.get("lints"))
+            .and_then(lint_level)
+        {
+            self.workspace_lint = Some(level);
+        }
+        let Some(package) = toml.get("package") else {
+            return;
+        };
+
+        let dir = file
+            .relative
+            .parent()
+            .unwrap_or(Path::new(""))
+            .to_path_buf();
+        let lints = toml.get("lints");
+        let target_path = |section: &str, default: &str| {
+            toml.get(section)
+                .and_then(|t| t.get("path"))
+                .and_then(|p| p.as_str())
+                .map_or_else(|| dir.join(default), |path| dir.join(path))
+        };
+        let mut roots = vec![target_path("lib", "src/lib.rs"), dir.join("src/main.rs")];
+        for bin in toml
+            .get("bin")
+            .and_then(|b| b.as_array())
+            .into_iter()
+            .flatten()
+        {
+            if let Some(path) = bin.get("path").and_then(|p| p.as_str()) {
+                roots.push(dir.join(path));
+            }
+        }
+
+        let build_tools = toml
+            .get("build-dependencies")
+            .and_then(|d| d.as_table())
+            .map(|deps| {
+                FFI_BUILD_TOOLS
+                    .iter()
+                    .filter(|tool| deps.contains_key(**tool))
+                    .map(|tool| tool.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        self.manifests.push(Manifest {
+            name: package
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("")
+                .to_string(),
+            roots,
+            lint: lints.and_then(lint_level),
+            inherits_lints: lints
+                .and_then(|l| l.get("workspace"))
+                .and_then(|w| w.as_bool())
+                == Some(true),
+            build_tools,
+            links: package
+                .get("links")
+                .and_then(|l| l.as_str())
+                .map(|l| l.to_string()),
+            has_build_script: package.get("build").and_then(|b| b.as_str()).is_some(),
+            dir,
+        });
+    }
+
+    fn add_source(&mut self, file: &ScannedFile<'_>, content: &str) {
+        let code = strip_comments(content);
+        let patterns = &self.patterns;
+        let count = |regex: &Regex| regex.find_iter(&code).count() as u32;
+
+        let unsafety = FileUnsafety {
+            path: file.relative.to_path_buf(),
+            unsafe_blocks: count(&patterns.block),
+            unsafe_functions: count(&patterns.function),
+            unsafe_impls: count(&patterns.implementation),
+            extern_blocks: count(&patterns.extern_block),
+            exported_functions: count(&patterns.exported),
+            lint: patterns
+                .lint
+                .captures(&code)
+                .map(|captures| captures[1].to_string()),
+        };
+        if unsafety.lint.is_some()
+            || unsafety.unsafe_blocks
+                + unsafety.unsafe_functions
+                + unsafety.unsafe_impls
+                + unsafety.extern_blocks
+                + unsafety.exported_functions
+                > 0
+        {
+            self.files.push(unsafety);
+        }
+    }
+}
+
+impl FileClassifier for UnsafeCodeClassifier {
+    fn classify(&mut self, file: &ScannedFile<'_>) {
+        let file_name = file.file_name();
+        // build.rs falls under the "build" ignore pattern of the structure scan
+        if file_name == "build.rs" {
+            self.build_scripts.push(file.relative.to_path_buf());
+        }
+        if !file.in_structure {
+            return;
+        }
+        if file_name == "Cargo.toml" {
+            if let Some(content) = file.content() {
+                self.add_manifest(file, content);
+            }
+            return;
+        }
+        if !file_name.ends_with(".rs") {
+            return;
+        }
+        if let Some(content) = file.content() {
+            self.add_source(file, content);
+        }
+    }
+}
+
+// Level of the unsafe_code lint in a [lints] or [workspace.lints] table
+fn lint_level(lints: &toml::Value) -> Option<String> {
+    let lint = lints.get("rust")?.get("unsafe_code")?;
+    let level = lint
+        .as_str()
+        .or_else(|| lint.get("level").and_then(|l| l.as_str()))?;
+    matches!(level, "forbid" | "deny").then(|| level.to_string())
+}
@@ -0,0 +1,532 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::Utc;
+use log::{info, warn};
+use serde::Deserialize;
+
+use crate::ai::AiAnalyst;
+use crate::analyzers::linguist::LinguistClassifier;
+use crate::file_index::FileIndex;
+use crate::types::{
+    AssessmentReport, ConfigFile, DocumentationFile, QuestionAnswer, RepositoryAnalysis,
+};
+use crate::utils::join_relative;
+
+// Number of lines per retrievable chunk
+const CHUNK_LINES: usize = 40;
+
+// Number of chunks given to the model per question
+const CHUNKS_PER_QUESTION: usize = 6;
+
+// Lines quoted per citation
+const MAX_QUOTED_LINES: usize = 40;
+
+// Candidates retrieved for the reranking pass to choose from
+const RERANK_CANDIDATES: usize = 18;
+
+// Files larger than this are not indexed for retrieval
+const MAX_INDEXED_FILE_SIZE: u64 = 256 * 1024;
+
+// BM25 term frequency saturation and document length normalization
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+// Added per query term found in a chunk's path
+const PATH_MATCH_BOOST: f64 = 2.0;
+
+// Added per identifier of the query (parse_config, FileIndex::new, ...)
+// found verbatim in a chunk, so the definition and uses of an exact name
+// outrank chunks that only share its words
+const EXACT_MATCH_BOOST: f64 = 5.0;
+
+// Extensions of prose files, indexed as documentation
+const DOC_EXTENSIONS: &[&str] = &["md", "markdown", "mdx", "rst", "adoc", "txt"];
+
+// Languages of data files, indexed as configuration
+const CONFIG_LANGUAGES: &[&str] = &["JSON", "YAML", "TOML", "INI", "XML"];
+
+// Words too common to help retrieval
+const STOPWORDS: &[&str] = &[
+    "the",
+    "and",
+    "for",
+    "are",
+    "does",
+    "how",
+    "what",
+    "which",
+    "who",
+    "why",
+    "when",
+    "where",
+    "this",
+    "that",
+    "with",
+    "from",
+    "into",
+    "its",
+    "can",
+    "use",
+    "used",
+    "uses",
+    "there",
+    "any",
+    "handled",
+    "support",
+    "supports",
+    "project",
+    "repository",
+    "repo",
+    "code",
+    "have",
+    "has",
+    "is",
+    "it",
+    "be",
+    "do",
+    "of",
+    "to",
+    "in",
+    "on",
+    "or",
+    "an",
+    "a",
+];
+
+// A question file is either a plain list or a `questions:` mapping
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum QuestionFile {
+    List(Vec<QuestionEntry>),
+    Document { questions: Vec<QuestionEntry> },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum QuestionEntry {
+    Text(String),
+    Detailed {
+        question: String,
+        #[serde(default)]
+        sources: Vec<ChunkKind>,
+    },
+}
+
+// A question and the kinds of excerpts it may be answered from
+#[derive(Debug, Clone)]
+pub struct Question {
+    pub text: String,
+    pub sources: Vec<ChunkKind>, // Empty for every kind
+}
+
+pub fn load_questions(path: &Path) -> Result<Vec<Question>> {
+    let content = fs::read_to_string(path)?;
+    let file: QuestionFile = serde_yaml::from_str(&content)?;
+    let entries = match file {
+        QuestionFile::List(entries) => entries,
+        QuestionFile::Document { questions } => questions,
+    };
+
+    let questions: Vec<Question> = entries
+        .into_iter()
+        .map(|entry| match entry {
+            QuestionEntry::Text(text) => Question {
+                text,
+                sources: Vec::new(),
+            },
+            QuestionEntry::Detailed { question, sources } => Question {
+                text: question,
+                sources,
+            },
+        })
+        .filter(|question| !question.text.trim().is_empty())
+        .collect();
+
+    if questions.is_empty() {
+        anyhow::bail!("No questions found in {}", path.display());
+    }
+    Ok(questions)
+}
+
+// What a chunk was cut from, so retrieval can be limited to documentation,
+// configuration or code
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChunkKind {
+    Doc,
+    Config,
+    Code,
+}
+
+impl ChunkKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind.trim().to_lowercase().as_str() {
+            "doc" | "docs" | "documentation" => Some(Self::Doc),
+            "config" | "configuration" => Some(Self::Config),
+            "code" => Some(Self::Code),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Doc => "doc",
+            Self::Config => "config",
+            Self::Code => "code",
+        }
+    }
+}
+
+// A contiguous range of lines from a repository file
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub path: PathBuf,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub text: String,
+    pub kind: ChunkKind,
+    pub language: Option<String>,
+    pub heading: Option<String>, // Markdown section the chunk starts in
+}
+
+// Lexical retrieval index over the text files of an analyzed repository. What
+// counts as text is what the analysis decided while scanning; vendored and
+// generated files (.gitattributes or Linguist's default paths) are left out.
+// Chunks are ranked by BM25 over words and identifier parts, plus exact
+// identifier matches
+pub struct RetrievalIndex {
+    chunks: Vec<Chunk>,
+    term_counts: Vec<HashMap<String, u32>>, // Per chunk
+    lengths: Vec<u32>,                      // Terms per chunk
+    document_frequency: HashMap<String, u32>,
+    average_length: f64,
+}
+
+impl RetrievalIndex {
+    pub fn build(
+        file_structure: &FileIndex,
+        config_files: &[ConfigFile],
+        documentation: &[DocumentationFile],
+    ) -> Self {
+        let repo_root = file_structure.root().path();
+        let linguist = LinguistClassifier::new(config_files);
+        let config_paths: HashSet<&Path> = config_files.iter().map(|c| c.path.as_path()).collect();
+        let doc_paths: HashSet<&Path> = documentation.iter().map(|d| d.path.as_path()).collect();
+        let files = file_structure.files().iter().filter(|file| {
+            file.is_text && file.size <= MAX_INDEXED_FILE_SIZE && !linguist.is_generated(file)
+        });
+
+        let mut chunks = Vec::new();
+        for file in files {
+            let content = match fs::read_to_string(join_relative(repo_root, &file.path)) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+
+            let kind = if doc_paths.contains(file.path.as_path())
+                || file
+                    .extension
+                    .as_deref()
+                    .is_some_and(|ext| DOC_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+            {
+                ChunkKind::Doc
+            } else if config_paths.contains(file.path.as_path())
+                || file
+                    .language
+                    .as_deref()
+                    .is_some_and(|language| CONFIG_LANGUAGES.contains(&language))
+            {
+                ChunkKind::Config
+            } else {
+                ChunkKind::Code
+            };
+            let headings = match kind {
+                ChunkKind::Doc => markdown_headings(&content),
+                _ => Vec::new(),
+            };
+
+            let lines: Vec<&str> = content.lines().collect();
+            for (index, window) in lines.chunks(CHUNK_LINES).enumerate() {
+                let start_line = index * CHUNK_LINES + 1;
+                let end_line = start_line + window.len() - 1;
+                // The section the chunk starts in, or the first one it opens
+                let heading = headings
+                    .iter()
+                    .rev()
+                    .find(|(line, _)| *line <= start_line)
+                    .or_else(|| headings.iter().find(|(line, _)| *line <= end_line))
+                    .map(|(_, heading)| heading.clone());
+                chunks.push(Chunk {
+                    path: file.path.clone(),
+                    start_line: start_line as u32,
+                    end_line: end_line as u32,
+                    text: window.join("\n"),
+                    kind,
+                    language: file.language.clone(),
+                    heading,
+                });
+            }
+        }
+
+        let mut term_counts = Vec::with_capacity(chunks.len());
+        let mut lengths = Vec::with_capacity(chunks.len());
+        let mut document_frequency: HashMap<String, u32> = HashMap::new();
+        for chunk in &chunks {
+            let mut counts: HashMap<String, u32> = HashMap::new();
+            let terms = tokens(&chunk.text);
+            lengths.push(terms.len() as u32);
+            for term in terms {
+                *counts.entry(term).or_insert(0) += 1;
+            }
+            for term in counts.keys() {
+                *document_frequency.entry(term.clone()).or_insert(0) += 1;
+            }
+            term_counts.push(counts);
+        }
+        let average_length = if chunks.is_empty() {
+            0.0
+        } else {
+            lengths.iter().map(|l| *l as f64).sum::<f64>() / chunks.len() as f64
+        };
+
+        info!("Indexed {} chunks for retrieval", chunks.len());
+        Self {
+            chunks,
+            term_counts,
+            lengths,
+            document_frequency,
+            average_length,
+        }
+    }
+
+    // Quote the cited lines from the snapshot. Answers whose citations can't
+    // all be found are flagged as ungrounded and get low confidence
+    pub fn verify_citations(&self, answer: &mut QuestionAnswer) {
+        for citation in &mut answer.citations {
+            if let Ok(path) = citation.path.strip_prefix("./") {
+                citation.path = path.to_path_buf();
+            }
+            citation.snippet = self.quote(&citation.path, citation.start_line, citation.end_line);
+            citation.verified = citation.snippet.is_some();
+        }
+
+        answer.grounded =
+            !answer.citations.is_empty() && answer.citations.iter().all(|c| c.verified);
+        if !answer.grounded {
+            answer.confidence = "low".to_string();
+        }
+    }
+
+    // Lines start_line..=end_line of an indexed file, None when the file isn't
+    // indexed or has fewer lines. Long ranges are cut to MAX_QUOTED_LINES
+    fn quote(&self, path: &Path, start_line: u32, end_line: u32) -> Option<String> {
+        let chunks: Vec<&Chunk> = self.chunks.iter().filter(|c| c.path == path).collect();
+        let last_line = chunks.iter().map(|c| c.end_line).max()?;
+        if start_line == 0 || start_line > end_line || end_line > last_line {
+            return None;
+        }
+
+        let lines: Vec<&str> = chunks
+            .iter()
+            .flat_map(|chunk| {
+                (chunk.start_line..)
+                    .zip(chunk.text.split('\n'))
+                    .filter(|(line, _)| (start_line..=end_line).contains(line))
+                    .map(|(_, text)| text)
+            })
+            .take(MAX_QUOTED_LINES)
+            .collect();
+        Some(lines.join("\n"))
+    }
+
+    // Best matching chunks of the given kinds, of every kind when empty
+    pub fn search(&self, query: &str, limit: usize, kinds: &[ChunkKind]) -> Vec<&Chunk> {
+        let terms = query_terms(query);
+        let identifiers = query_identifiers(query);
+        if terms.is_empty() && identifiers.is_empty() {
+            return Vec::new();
+        }
+
+        let mut scored: Vec<(f64, &Chunk)> = self
+            .chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, chunk)| kinds.is_empty() || kinds.contains(&chunk.kind))
+            .map(|(id, chunk)| (self.score(id, &terms, &identifiers), chunk))
+            .filter(|(score, _)| *score > 0.0)
+            .collect();
+
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        scored
+            .into_iter()
+            .take(limit)
+            .map(|(_, chunk)| chunk)
+            .collect()
+    }
+
+    fn score(&self, id: usize, terms: &HashSet<String>, identifiers: &[String]) -> f64 {
+        let chunk = &self.chunks[id];
+        let counts = &self.term_counts[id];
+        let length_ratio = self.lengths[id] as f64 / self.average_length.max(1.0);
+        let chunk_count = self.chunks.len() as f64;
+        let path = chunk.path.to_string_lossy().to_lowercase();
+
+        let mut score = 0.0;
+        for term in terms {
+            if let Some(count) = counts.get(term) {
+                let frequency = self.document_frequency.get(term).copied().unwrap_or(0) as f64;
+                let idf = (1.0 + (chunk_count - frequency + 0.5) / (frequency + 0.5)).ln();
+                let tf = *count as f64;
+                score += idf * tf * (BM25_K1 + 1.0)
+                    / (tf + BM25_K1 * (1.0 - BM25_B + BM25_B * length_ratio));
+            }
+            if path.contains(term.as_str()) {
+                score += PATH_MATCH_BOOST;
+            }
+        }
+        score += EXACT_MATCH_BOOST
+            * identifiers
+                .iter()
+                .filter(|identifier| chunk.text.contains(identifier.as_str()))
+                .count() as f64;
+        score
+    }
+}
+
+// Answer each question from retrieved repository excerpts
+pub async fn run_assessment(
+    analyst: &AiAnalyst,
+    analysis: &RepositoryAnalysis,
+    questions: &[Question],
+) -> AssessmentReport {
+    let index = RetrievalIndex::build(
+        &analysis.file_structure,
+        &analysis.config_files,
+        &analysis.documentation,
+    );
+    let mut answers = Vec::new();
+
+    for question in questions {
+        info!("Answering: {}", question.text);
+        let mut chunks = if analyst.reranks() {
+            let candidates = index.search(&question.text, RERANK_CANDIDATES, &question.sources);
+            // A failed rerank keeps the BM25 order
+            match analyst.rerank(&question.text, &candidates).await {
+                Ok(order) => order.into_iter().map(|i| candidates[i]).collect(),
+                Err(e) => {
+                    warn!("Failed to rerank excerpts for \"{}\": {}", question.text, e);
+                    candidates
+                }
+            }
+        } else {
+            index.search(&question.text, CHUNKS_PER_QUESTION, &question.sources)
+        };
+        chunks.truncate(CHUNKS_PER_QUESTION);
+
+        match analyst
+            .answer_question(&analysis.analysis_summary, &question.text, &chunks)
+            .await
+        {
+            Ok(mut answer) => {
+                index.verify_citations(&mut answer);
+                if !answer.grounded {
+                    warn!(
+                        "Answer to \"{}\" is not grounded in its citations",
+                        question.text
+                    );
+                }
+                answers.push(answer);
+            }
+            Err(e) => warn!("Failed to answer \"{}\": {}", question.text, e),
+        }
+    }
+
+    AssessmentReport {
+        url: analysis.url.clone(),
+        assessed_at: Utc::now(),
+        head_sha: analysis.git_analysis.head_sha.clone(),
+        answers,
+    }
+}
+
+fn query_terms(query: &str) -> HashSet<String> {
+    tokens(query)
+        .into_iter()
+        .filter(|term| term.len() > 2 && !STOPWORDS.contains(&term.as_str()))
+        .collect()
+}
+
+// Words of the query written as code: snake_case, camelCase, paths such as
+// FileIndex::new, or calls such as parse()
+fn query_identifiers(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric() && c != '_' && c != ')')
+                .trim_end_matches("()")
+        })
+        .filter(|word| {
+            word.len() > 2
+                && word
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || "_:.".contains(c))
+                && (word.contains('_')
+                    || word.contains("::")
+                    || word
+                        .chars()
+                        .zip(word.chars().skip(1))
+                        .any(|(a, b)| a.is_lowercase() && b.is_uppercase()))
+        })
+        .map(|word| word.to_string())
+        .collect()
+}
+
+// Lowercased words, with the parts of snake_case and camelCase identifiers
+// as terms of their own next to the whole identifier
+fn tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for word in text
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+    {
+        let mut parts = Vec::new();
+        for piece in word.split('_').filter(|piece| !piece.is_empty()) {
+            let mut part = String::new();
+            let mut previous_lower = false;
+            for c in piece.chars() {
+                if c.is_uppercase() && previous_lower {
+                    parts.push(std::mem::take(&mut part));
+                }
+                previous_lower = c.is_lowercase() || c.is_ascii_digit();
+                part.extend(c.to_lowercase());
+            }
+            parts.push(part);
+        }
+        if parts.len() > 1 {
+            tokens.extend(parts);
+        }
+        tokens.push(word.to_lowercase());
+    }
+    tokens
+}
+
+// ATX headings with their 1-based line, fenced code skipped
+fn markdown_headings(content: &str) -> Vec<(usize, String)> {
+    let mut headings = Vec::new();
+    let mut in_code = false;
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+        } else if !in_code && trimmed.starts_with('#') {
+            let heading = trimmed.trim_start_matches('#');
+            if heading.starts_with(' ') {
+                headings.push((index + 1, heading.trim().to_string()));
+            }
+        }
+    }
+    headings
+}
@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use reqwest::{Client, RequestBuilder};
+
+use crate::forge::{Forge, without_credentials};
+use crate::network::{self, SendLogged};
+use crate::telemetry;
+use crate::types::{GitHubIssue, GitHubRelease, GitHubUser, RepositoryMetadata};
+
+// Largest page every endpoint used here serves
+const PAGE_SIZE: usize = 50;
+
+// Contributors are counted over this many of the latest commits, there is
+// no contributors endpoint
+const COMMIT_SAMPLE: usize = 500;
+
+// Bitbucket Cloud 2.0 API client, the Bitbucket counterpart of GitHubClient
+pub struct BitbucketClient {
+    client: Client,
+    token: Option<String>,
+    base_url: String,
+}
+
+impl BitbucketClient {
+    // An access token, or "username:app password" (or "email:API token")
+    // for basic auth
+    pub fn new(token: Option<String>) -> Self {
+        Self {
+            client: network::http_client(),
+            token,
+            base_url: "https://api.bitbucket.org/2.0".to_string(),
+        }
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        let Some(token) = &self.token else {
+            return request;
+        };
+        match token.split_once(':') {
+            Some((username, password)) => request.basic_auth(username, Some(password)),
+            None => request.bearer_auth(token),
+        }
+    }
+
+    fn repository_url(&self, owner: &str, repo: &str) -> String {
+        format!("{}/repositories/{}/{}", self.base_url, owner, repo)
+    }
+
+    async fn get_json(&self, url: &str) -> Result<serde_json::Value> {
+        let response = self.authorize(self.client.get(url)).send_logged().await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Failed to fetch {}: {} - {}",
+                url,
+                response.status(),
+                telemetry::text(response).await?
+            );
+        }
+        telemetry::json(response).await
+    }
+
+    // GET of a paginated endpoint, following the `next` link of each page
+    // until limit values. A failed page is warned about and ends the list,
+    // the first one leaves it empty
+    async fn get_pages(&self, url: &str, limit: usize) -> Result<Vec<serde_json::Value>> {
+        let mut values = Vec::new();
+        let mut next = Some(url.to_string());
+        while let Some(page_url) = next.take()
+            && values.len() < limit
+        {
+            let response = self
+                .authorize(self.client.get(&page_url))
+                .send_logged()
+                .await?;
+            if !response.status().is_success() {
+                if values.is_empty() {
+                    warn!("Failed to fetch {}: {}", url, response.status());
+                } else {
+                    warn!(
+                        "Stopped paging {} after {} items: {}",
+                        url,
+                        values.len(),
+                        response.status()
+                    );
+                }
+                break;
+            }
+            let mut page: serde_json::Value = telemetry::json(response).await?;
+            next = page["next"].as_str().map(|s| s.to_string());
+            if let Some(page_values) = page["values"].as_array_mut() {
+                values.append(page_values);
+            }
+        }
+        values.truncate(limit);
+        Ok(values)
+    }
+
+    // Total of a paginated endpoint, from its first page
+    async fn count(&self, url: &str) -> u32 {
+        match self.get_json(&format!("{}?pagelen=1", url)).await {
+            Ok(page) => page["size"].as_u64().unwrap_or(0) as u32,
+            Err(e) => {
+                warn!("Failed to count {}: {}", url, e);
+                0
+            }
+        }
+    }
+
+    pub async fn get_repository_metadata(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<RepositoryMetadata> {
+        let url = self.repository_url(owner, repo);
+        info!("Fetching repository metadata from: {}", url);
+        let repository = self.get_json(&url).await?;
+
+        // Watchers are what Bitbucket has for stars
+        let watchers = self.count(&format!("{}/watchers", url)).await;
+        let forks = self.count(&format!("{}/forks", url)).await;
+        // Clone links carry the name of the authenticated user
+        let links = &repository["links"];
+        let clone_url = |name: &str| {
+            links["clone"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|link| link["name"].as_str() == Some(name))
+                .and_then(|link| link["href"].as_str())
+                .map(without_credentials)
+                .unwrap_or_default()
+        };
+        let language = repository["language"]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string());
+        let owner_value = &repository["owner"];
+        let updated_at = parse_date(&repository["updated_on"]);
+
+        Ok(RepositoryMetadata {
+            id: 0, // Repositories are identified by UUIDs
+            name: repository["name"].as_str().unwrap_or(repo).to_string(),
+            full_name: repository["full_name"].as_str().unwrap_or("").to_string(),
+            description: repository["description"]
+                .as_str()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string()),
+            homepage: repository["website"]
+                .as_str()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string()),
+            html_url: links["html"]["href"].as_str().unwrap_or("").to_string(),
+            clone_url: clone_url("https"),
+            ssh_url: clone_url("ssh"),
+            git_url: clone_url("https"),
+            owner: GitHubUser {
+                login: owner_value["username"]
+                    .as_str()
+                    .or(owner_value["nickname"].as_str())
+                    .unwrap_or(owner)
+                    .to_string(),
+                id: 0,
+                avatar_url: owner_value["links"]["avatar"]["href"]
+                    .as_str()
+                    .unwrap_or("")
+                    .to_string(),
+                html_url: owner_value["links"]["html"]["href"]
+                    .as_str()
+                    .unwrap_or("")
+                    .to_string(),
+                contributions: None,
+            },
+            private: repository["is_private"].as_bool().unwrap_or(false),
+            fork: repository["parent"].is_object(),
+            archived: false,
+            disabled: false,
+            has_issues: repository["has_issues"].as_bool().unwrap_or(false),
+            has_projects: false,
+            has_wiki: repository["has_wiki"].as_bool().unwrap_or(false),
+            has_pages: false,
+            has_downloads: false,
+            has_discussions: false,
+            stargazers_count: watchers,
+            watchers_count: watchers,
+            forks_count: forks,
+            subscribers_count: None,
+            network_count: None,
+            open_issues_count: 0,
+            license: None,
+            topics: Vec::new(),
+            default_branch: repository["mainbranch"]["name"]
+                .as_str()
+                .unwrap_or("main")
+                .to_string(),
+            size: (repository["size"].as_u64().unwrap_or(0) / 1024) as u32,
+            languages: language
+                .iter()
+                .map(|language| (language.clone(), 1))
+                .collect(),
+            language,
+            created_at: parse_date(&repository["created_on"]),
+            updated_at,
+            pushed_at: updated_at,
+            parent: repository["parent"]["full_name"]
+                .as_str()
+                .map(|s| s.to_string()),
+            mirror_url: None,
+        })
+    }
+
+    // Authors of the latest commits by number of commits, accounts when
+    // the commit email is linked to one, else the name of the commit author
+    pub async fn get_contributors(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubUser>> {
+        let url = format!(
+            "{}/commits?pagelen={}",
+            self.repository_url(owner, repo),
+            PAGE_SIZE
+        );
+        let commits = self.get_pages(&url, COMMIT_SAMPLE).await?;
+
+        let mut contributors: Vec<GitHubUser> = Vec::new();
+        for commit in &commits {
+            let author = &commit["author"];
+            let contributor = if author["user"].is_object() {
+                user(&author["user"])
+            } else {
+                let raw = author["raw"].as_str().unwrap_or("");
+                GitHubUser {
+                    login: raw.split('<').next().unwrap_or(raw).trim().to_string(),
+                    id: 0,
+                    avatar_url: String::new(),
+                    html_url: String::new(),
+                    contributions: None,
+                }
+            };
+            if contributor.login.is_empty() {
+                continue;
+            }
+            match contributors
+                .iter_mut()
+                .find(|known| known.login == contributor.login)
+            {
+                Some(known) => *known.contributions.get_or_insert(0) += 1,
+                None => contributors.push(GitHubUser {
+                    contributions: Some(1),
+                    ..contributor
+                }),
+            }
+        }
+        contributors.sort_by_key(|contributor| std::cmp::Reverse(contributor.contributions));
+        contributors.truncate(limit);
+        Ok(contributors)
+    }
+
+    // Bitbucket has tags and downloads but no releases
+    pub async fn get_releases(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _limit: usize,
+    ) -> Result<Vec<GitHubRelease>> {
+        Ok(Vec::new())
+    }
+
+    // Issues of the built-in tracker, an empty list when it is disabled.
+    // The kind and component stand in for labels
+    pub async fn get_recent_issues(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubIssue>> {
+        let url = format!(
+            "{}/issues?sort=-updated_on&pagelen={}",
+            self.repository_url(owner, repo),
+            limit.clamp(1, PAGE_SIZE)
+        );
+        let issues = self.get_pages(&url, limit).await?;
+        Ok(issues
+            .into_iter()
+            .map(|i| GitHubIssue {
+                number: i["id"].as_u64().unwrap_or(0) as u32,
+                title: i["title"].as_str().unwrap_or("").to_string(),
+                state: match i["state"].as_str() {
+                    Some("new" | "open" | "on hold") => "open".to_string(),
+                    _ => "closed".to_string(),
+                },
+                created_at: parse_date(&i["created_on"]),
+                updated_at: parse_date(&i["updated_on"]),
+                closed_at: None,
+                author: user(&i["reporter"]),
+                labels: [&i["kind"], &i["component"]["name"]]
+                    .into_iter()
+                    .filter_map(|label| label.as_str())
+                    .map(|label| label.to_string())
+                    .collect(),
+                comments: 0, // Not part of the issue
+                author_association: String::new(),
+                first_response_at: None,
+            })
+            .collect())
+    }
+
+    // Only the main language the owner set, there is no breakdown
+    pub async fn get_languages(&self, owner: &str, repo: &str) -> Result<HashMap<String, u64>> {
+        let repository = self.get_json(&self.repository_url(owner, repo)).await?;
+        Ok(repository["language"]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .map(|language| (language.to_string(), 1))
+            .into_iter()
+            .collect())
+    }
+}
+
+impl Forge for BitbucketClient {
+    fn get_repository_metadata(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> impl Future<Output = Result<RepositoryMetadata>> + Send {
+        BitbucketClient::get_repository_metadata(self, owner, repo)
+    }
+
+    fn get_contributors(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<GitHubUser>>> + Send {
+        BitbucketClient::get_contributors(self, owner, repo, limit)
+    }
+
+    fn get_releases(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<GitHubRelease>>> + Send {
+        BitbucketClient::get_releases(self, owner, repo, limit)
+    }
+
+    fn get_recent_issues(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<GitHubIssue>>> + Send {
+        BitbucketClient::get_recent_issues(self, owner, repo, limit)
+    }
+
+    fn get_languages(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> impl Future<Output = Result<HashMap<String, u64>>> + Send {
+        BitbucketClient::get_languages(self, owner, repo)
+    }
+}
+
+fn user(value: &serde_json::Value) -> GitHubUser {
+    GitHubUser {
+        login: value["nickname"]
+            .as_str()
+            .or(value["display_name"].as_str())
+            .unwrap_or("")
+            .to_string(),
+        id: 0,
+        avatar_url: value["links"]["avatar"]["href"]
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
+        html_url: value["links"]["html"]["href"]
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
+        contributions: None,
+    }
+}
+
+fn parse_date(value: &serde_json::Value) -> DateTime<Utc> {
+    value
+        .as_str()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_default()
+}
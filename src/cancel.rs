@@ -0,0 +1,68 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::warn;
+use tokio::sync::Notify;
+
+// Process-wide cancellation flag, set on SIGINT/SIGTERM. Async work is
+// cancelled by racing it against `cancelled()`; blocking work (clone,
+// file system walk, sandbox container) polls `is_cancelled()`.
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+static NOTIFY: Notify = Notify::const_new();
+
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+// The flag itself, for gitoxide's interruptible operations
+#[cfg(feature = "gix")]
+pub fn flag() -> &'static AtomicBool {
+    &CANCELLED
+}
+
+fn cancel() {
+    CANCELLED.store(true, Ordering::SeqCst);
+    NOTIFY.notify_waiters();
+}
+
+// Completes once cancellation has been requested
+pub async fn cancelled() {
+    loop {
+        let notified = NOTIFY.notified();
+        if is_cancelled() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+// Turn the first SIGINT/SIGTERM into a cancellation request
+pub fn install_signal_handlers() {
+    tokio::spawn(async {
+        wait_for_signal().await;
+        warn!("Cancellation requested, stopping...");
+        cancel();
+    });
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    match signal(SignalKind::terminate()) {
+        Ok(mut sigterm) => {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = sigterm.recv() => {}
+            }
+        }
+        Err(e) => {
+            warn!("Failed to install SIGTERM handler: {}", e);
+            let _ = tokio::signal::ctrl_c().await;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
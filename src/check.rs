@@ -0,0 +1,308 @@
+use std::path::Path;
+
+use chrono::Utc;
+use serde::Deserialize;
+
+use crate::analyzers::type_detector::is_code_language;
+use crate::types::{CheckAnnotation, CheckReport, CheckResult, FileInfo, RepositoryAnalysis};
+use crate::utils::{is_test_file, to_slash_path};
+
+// Exit code of the check command when a threshold is violated
+pub const EXIT_VIOLATIONS: i32 = 2;
+
+// Offenders listed per failed check
+const MAX_DETAILS: usize = 10;
+
+// Thresholds left unset are not checked, except that vulnerabilities and
+// secrets fail the gate unless explicitly allowed
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CheckThresholds {
+    pub max_function_complexity: Option<u32>,
+    pub max_file_complexity: Option<u32>,
+    pub min_test_ratio: Option<f64>, // Test lines of code over all code lines
+    pub max_vulnerabilities: Option<usize>,
+    pub max_secrets: Option<usize>,
+}
+
+impl Default for CheckThresholds {
+    fn default() -> Self {
+        Self {
+            max_function_complexity: None,
+            max_file_complexity: None,
+            min_test_ratio: None,
+            max_vulnerabilities: Some(0),
+            max_secrets: Some(0),
+        }
+    }
+}
+
+pub fn run_checks(analysis: &RepositoryAnalysis, thresholds: &CheckThresholds) -> CheckReport {
+    let files = analysis.file_structure.files();
+    let mut results = Vec::new();
+
+    if let Some(max) = thresholds.max_function_complexity {
+        let mut functions: Vec<_> = files
+            .iter()
+            .flat_map(|file| file.functions.iter())
+            .filter(|function| function.complexity > max)
+            .collect();
+        functions.sort_by_key(|function| std::cmp::Reverse(function.complexity));
+        let actual = files
+            .iter()
+            .flat_map(|file| file.functions.iter())
+            .map(|function| function.complexity)
+            .max()
+            .unwrap_or(0);
+        results.push(CheckResult {
+            name: "max_function_complexity".to_string(),
+            threshold: max.to_string(),
+            actual: actual.to_string(),
+            passed: functions.is_empty(),
+            details: functions
+                .iter()
+                .take(MAX_DETAILS)
+                .map(|function| {
+                    format!(
+                        "{} ({}:{}) has complexity {}",
+                        function.name,
+                        function.file.display(),
+                        function.start_line,
+                        function.complexity
+                    )
+                })
+                .collect(),
+        });
+    }
+
+    if let Some(max) = thresholds.max_file_complexity {
+        let mut complex: Vec<(&FileInfo, u32)> = files
+            .iter()
+            .filter_map(|file| file.complexity.map(|complexity| (file, complexity)))
+            .filter(|(_, complexity)| *complexity > max)
+            .collect();
+        complex.sort_by_key(|(_, complexity)| std::cmp::Reverse(*complexity));
+        let actual = files
+            .iter()
+            .filter_map(|file| file.complexity)
+            .max()
+            .unwrap_or(0);
+        results.push(CheckResult {
+            name: "max_file_complexity".to_string(),
+            threshold: max.to_string(),
+            actual: actual.to_string(),
+            passed: complex.is_empty(),
+            details: complex
+                .iter()
+                .take(MAX_DETAILS)
+                .map(|(file, complexity)| {
+                    format!("{} has complexity {}", file.path.display(), complexity)
+                })
+                .collect(),
+        });
+    }
+
+    if let Some(min) = thresholds.min_test_ratio {
+        let ratio = test_ratio(files);
+        results.push(CheckResult {
+            name: "min_test_ratio".to_string(),
+            threshold: format!("{:.2}", min),
+            actual: format!("{:.2}", ratio),
+            passed: ratio >= min,
+            details: Vec::new(),
+        });
+    }
+
+    let security = &analysis.security_info;
+    if let Some(max) = thresholds.max_vulnerabilities {
+        results.push(CheckResult {
+            name: "max_vulnerabilities".to_string(),
+            threshold: max.to_string(),
+            actual: security.vulnerability_alerts.len().to_string(),
+            passed: security.vulnerability_alerts.len() <= max,
+            details: security
+                .vulnerability_alerts
+                .iter()
+                .take(MAX_DETAILS)
+                .cloned()
+                .collect(),
+        });
+    }
+
+    if let Some(max) = thresholds.max_secrets {
+        results.push(CheckResult {
+            name: "max_secrets".to_string(),
+            threshold: max.to_string(),
+            actual: security.secret_findings.len().to_string(),
+            passed: security.secret_findings.len() <= max,
+            details: security
+                .secret_findings
+                .iter()
+                .take(MAX_DETAILS)
+                .map(|finding| {
+                    format!(
+                        "{} at {}:{}",
+                        finding.kind,
+                        finding.path.display(),
+                        finding.line
+                    )
+                })
+                .collect(),
+        });
+    }
+
+    CheckReport {
+        url: analysis.url.clone(),
+        checked_at: Utc::now(),
+        passed: results.iter().all(|result| result.passed),
+        results,
+    }
+}
+
+// File/line annotations for a published check run: complexity hotspots over
+// the threshold, secrets and loosely pinned dependencies
+pub fn annotations(
+    analysis: &RepositoryAnalysis,
+    thresholds: &CheckThresholds,
+) -> Vec<CheckAnnotation> {
+    let files = analysis.file_structure.files();
+    let mut annotations = Vec::new();
+
+    for finding in &analysis.security_info.secret_findings {
+        annotations.push(CheckAnnotation {
+            path: path_string(&finding.path),
+            start_line: finding.line as u32,
+            end_line: finding.line as u32,
+            annotation_level: "failure".to_string(),
+            title: format!("Possible committed {}", finding.kind),
+            message: "Revoke the credential and remove it from the history".to_string(),
+        });
+    }
+
+    if let Some(max) = thresholds.max_function_complexity {
+        for function in files
+            .iter()
+            .flat_map(|file| file.functions.iter())
+            .filter(|function| function.complexity > max)
+        {
+            annotations.push(CheckAnnotation {
+                path: path_string(&function.file),
+                start_line: function.start_line,
+                end_line: function.end_line,
+                annotation_level: "warning".to_string(),
+                title: format!("Complexity hotspot: {}", function.name),
+                message: format!(
+                    "Cyclomatic complexity {} exceeds the threshold of {}",
+                    function.complexity, max
+                ),
+            });
+        }
+    }
+
+    if let Some(max) = thresholds.max_file_complexity {
+        for (file, complexity) in files
+            .iter()
+            .filter_map(|file| file.complexity.map(|complexity| (file, complexity)))
+            .filter(|(_, complexity)| *complexity > max)
+        {
+            annotations.push(CheckAnnotation {
+                path: path_string(&file.path),
+                start_line: 1,
+                end_line: 1,
+                annotation_level: "warning".to_string(),
+                title: "Complex file".to_string(),
+                message: format!(
+                    "Cyclomatic complexity {} exceeds the threshold of {}",
+                    complexity, max
+                ),
+            });
+        }
+    }
+
+    // Dependencies are listed as "name: version", located by the manifest line naming them
+    for dependency in &analysis.security_info.outdated_dependencies {
+        let name = dependency.split(": ").next().unwrap_or(dependency);
+        let manifest = analysis.config_files.iter().find(|config| {
+            config
+                .parsed_dependencies
+                .as_ref()
+                .is_some_and(|deps| deps.contains_key(name))
+        });
+        let Some(manifest) = manifest else {
+            continue;
+        };
+        let line = manifest
+            .content
+            .lines()
+            .position(|line| line.contains(name))
+            .map(|index| index as u32 + 1)
+            .unwrap_or(1);
+        annotations.push(CheckAnnotation {
+            path: path_string(&manifest.path),
+            start_line: line,
+            end_line: line,
+            annotation_level: "warning".to_string(),
+            title: format!("Unpinned dependency: {}", name),
+            message: format!("{} is not pinned to a version range", dependency),
+        });
+    }
+
+    annotations
+}
+
+pub fn print_report(report: &CheckReport) {
+    for result in &report.results {
+        println!(
+            "[{}] {}: {} (threshold {})",
+            if result.passed { "PASS" } else { "FAIL" },
+            result.name,
+            result.actual,
+            result.threshold
+        );
+        for detail in &result.details {
+            println!("       {}", detail);
+        }
+    }
+
+    let failed = report
+        .results
+        .iter()
+        .filter(|result| !result.passed)
+        .count();
+    if failed == 0 {
+        println!("All {} checks passed", report.results.len());
+    } else {
+        println!("{} of {} checks failed", failed, report.results.len());
+    }
+}
+
+// Share of code lines that live in test files, judged by path
+fn test_ratio(files: &[FileInfo]) -> f64 {
+    let mut code_loc = 0u64;
+    let mut test_loc = 0u64;
+
+    for file in files {
+        let Some(language) = file.language.as_deref() else {
+            continue;
+        };
+        if !is_code_language(language) {
+            continue;
+        }
+        let loc = file.lines_of_code.unwrap_or(0) as u64;
+        code_loc += loc;
+        if is_test_file(&file.path) {
+            test_loc += loc;
+        }
+    }
+
+    if code_loc == 0 {
+        0.0
+    } else {
+        test_loc as f64 / code_loc as f64
+    }
+}
+
+// Annotation paths are repository-relative with forward slashes
+fn path_string(path: &Path) -> String {
+    to_slash_path(path).display().to_string()
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::file_index::FileIndex;
+use crate::types::{
+    ApiDocs, ConfigFile, DeploymentTopology, DocsProse, DocsSpelling, DocumentationFile,
+    EmbeddedCode, ErrorHandling, PanicDensity, PerformanceCulture, PlatformSupport, SecurityScan,
+    ToolchainRequirements,
+};
+
+pub const METADATA: &str = "metadata";
+pub const GIT_ANALYSIS: &str = "git_analysis";
+pub const FS_SCAN: &str = "fs_scan";
+
+// Result of the file system phase, checkpointed as a whole
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FsScan {
+    pub file_structure: FileIndex,
+    #[serde(default)]
+    pub embedded_code: Vec<EmbeddedCode>,
+    pub config_files: Vec<ConfigFile>,
+    pub documentation: Vec<DocumentationFile>,
+    pub security: SecurityScan,
+    #[serde(default)]
+    pub panic_density: Option<PanicDensity>,
+    #[serde(default)]
+    pub error_handling: Option<ErrorHandling>,
+    #[serde(default)]
+    pub deployment: DeploymentTopology,
+    #[serde(default)]
+    pub performance: PerformanceCulture,
+    #[serde(default)]
+    pub platforms: PlatformSupport,
+    #[serde(default)]
+    pub api_docs: ApiDocs,
+    #[serde(default)]
+    pub spelling: DocsSpelling,
+    #[serde(default)]
+    pub prose: DocsProse,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub toolchains: ToolchainRequirements,
+    #[serde(default)]
+    pub imports: HashMap<PathBuf, Vec<String>>, // Only collected when custom rules are set
+}
+
+// Intermediate phase results persisted in the work dir, so an interrupted run
+// can pick up where it stopped with `--resume`
+pub struct CheckpointStore {
+    dir: Option<PathBuf>,
+    resume: bool,
+}
+
+impl CheckpointStore {
+    pub fn new(dir: PathBuf, resume: bool) -> Self {
+        Self {
+            dir: Some(dir),
+            resume,
+        }
+    }
+
+    // Used where nothing should be written, e.g. inside the sandbox container
+    pub fn disabled() -> Self {
+        Self {
+            dir: None,
+            resume: false,
+        }
+    }
+
+    pub fn load<T: DeserializeOwned>(&self, phase: &str) -> Option<T> {
+        if !self.resume {
+            return None;
+        }
+
+        let path = self.dir.as_ref()?.join(format!("{}.json", phase));
+        let content = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(value) => {
+                info!("Resuming from checkpoint {:?}", path);
+                Some(value)
+            }
+            Err(e) => {
+                warn!("Ignoring unreadable checkpoint {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    pub fn save<T: Serialize>(&self, phase: &str, value: &T) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+
+        let result = fs::create_dir_all(dir)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| Ok(serde_json::to_string(value)?))
+            .and_then(|json| {
+                // Write then rename so a crash never leaves a truncated checkpoint
+                let path = dir.join(format!("{}.json", phase));
+                let tmp_path = path.with_extension("json.tmp");
+                fs::write(&tmp_path, json)?;
+                fs::rename(&tmp_path, &path)?;
+                Ok(())
+            });
+
+        if let Err(e) = result {
+            warn!("Failed to save checkpoint {}: {}", phase, e);
+        }
+    }
+
+    // Phases with a checkpoint on disk, reported when a run is cancelled
+    pub fn saved_phases(&self) -> Vec<String> {
+        let Some(dir) = &self.dir else {
+            return Vec::new();
+        };
+
+        [METADATA, GIT_ANALYSIS, FS_SCAN]
+            .iter()
+            .filter(|phase| dir.join(format!("{}.json", phase)).exists())
+            .map(|phase| phase.to_string())
+            .collect()
+    }
+
+    // Drop the checkpoints once the analysis has completed
+    pub fn clear(&self) {
+        if let Some(dir) = &self.dir
+            && dir.exists()
+            && let Err(e) = fs::remove_dir_all(dir)
+        {
+            warn!("Failed to remove checkpoints {:?}: {}", dir, e);
+        }
+    }
+}
@@ -0,0 +1,674 @@
+// Command line interface. Each subcommand takes its own flags, the ones every
+// command understands (token, output, config and directories) can go anywhere.
+// A bare repository URL runs `analyze`, as before the subcommands existed
+
+use std::ffi::OsString;
+
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser};
+use clap::error::ErrorKind;
+use clap::{Arg, Args, Command as ClapCommand, CommandFactory, Parser, Subcommand, ValueEnum};
+
+use ai_repo_analyzer_rs::assess::ChunkKind;
+use ai_repo_analyzer_rs::export_context::ContextFormat;
+use ai_repo_analyzer_rs::report::ReportFormat;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Analyzes GitHub repositories: structure, dependencies, security, activity and AI insights",
+    after_help = "A bare URL runs analyze: ai-repo-analyzer-rs https://github.com/owner/repo"
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+    #[command(flatten)]
+    pub global: GlobalArgs,
+}
+
+#[derive(Args)]
+pub struct GlobalArgs {
+    /// GitHub token, GITHUB_TOKEN when not given
+    #[arg(long, global = true)]
+    pub token: Option<String>,
+    /// GitLab token for gitlab.com repositories, GITLAB_TOKEN when not given
+    #[arg(long, global = true)]
+    pub gitlab_token: Option<String>,
+    /// Bitbucket access token, or USERNAME:APP_PASSWORD, BITBUCKET_TOKEN when not given
+    #[arg(long, global = true)]
+    pub bitbucket_token: Option<String>,
+    /// json or yaml, onepager for report
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Json, value_name = "FORMAT")]
+    pub output: OutputFormat,
+    /// Compact analysis sized for a model prompt, within TOKENS (8000 by default), for analyze and report
+    #[arg(
+        long,
+        global = true,
+        value_name = "TOKENS",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "8000"
+    )]
+    pub llm_context: Option<usize>,
+    /// Writes the result to a file instead of stdout
+    #[arg(long, global = true, value_name = "PATH")]
+    pub output_file: Option<String>,
+    /// analyzer.toml, in the current or user config directory when not given
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<String>,
+    /// Workspace for clones, checkpoints and container outputs
+    #[arg(long, global = true, value_name = "DIR")]
+    pub work_dir: Option<String>,
+    /// Directory of the persistent AI cache
+    #[arg(long, global = true, value_name = "DIR")]
+    pub cache_dir: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Analyzes a repository (the default command), or a local checkout without the forge API
+    Analyze(AnalyzeCommand),
+    /// Answers a question set about a repository, with citations
+    Assess(AssessCommand),
+    /// Runs the local analysis phase in a sandboxed container
+    SelfContainer(SelfContainerCommand),
+    /// Offline analysis phase executed inside the container
+    AnalyzeLocal(AnalyzeLocalCommand),
+    /// Compares two tags of a repository
+    ReleaseDiff(ReleaseDiffCommand),
+    /// Bundles the tree and the most relevant files into one document for a model prompt
+    #[command(visible_alias = "ingest")]
+    ExportContext(ExportContextCommand),
+    /// Renders a saved analysis
+    Report(ReportCommand),
+    /// Compares the security posture of two saved analyses
+    #[command(visible_alias = "compare")]
+    Diff(DiffCommand),
+    /// Gates CI on the thresholds in analyzer.toml
+    Check(CheckCommand),
+    /// Posts a condensed analysis to a pull request
+    PrComment(PrCommentCommand),
+    /// Re-analyzes the repositories in analyzer.toml on their schedules
+    Schedule(ScheduleCommand),
+    /// Proposes topics and a description for a saved analysis
+    SuggestMetadata(SuggestMetadataCommand),
+    /// Prints a shell completion script
+    Completions { shell: Shell },
+}
+
+#[derive(Args)]
+pub struct AnalyzeCommand {
+    #[arg(value_name = "REPO_URL|PATH")]
+    pub url: String,
+    /// No network access at all: needs a local path, and leaves out AI insights
+    #[arg(long, conflicts_with = "webhooks")]
+    pub offline: bool,
+    #[command(flatten)]
+    pub analysis: AnalysisArgs,
+    #[command(flatten)]
+    pub ai: AiArgs,
+    #[command(flatten)]
+    pub notify: NotifyArgs,
+    /// Human corrections applied to the analysis
+    #[arg(long, value_name = "PATH")]
+    pub overrides: Option<String>,
+}
+
+#[derive(Args)]
+pub struct AssessCommand {
+    #[arg(value_name = "REPO_URL")]
+    pub url: String,
+    /// YAML list of questions
+    #[arg(long, value_name = "PATH")]
+    pub questions: String,
+    /// Excerpts the questions without their own sources are answered from
+    #[arg(long, value_delimiter = ',', value_parser = chunk_kind_parser(), value_name = "KINDS")]
+    pub sources: Vec<ChunkKind>,
+    /// Lets the AI order the best excerpts before answering
+    #[arg(long)]
+    pub rerank: bool,
+    /// Reranks with another model, implies --rerank
+    #[arg(long, value_name = "MODEL")]
+    pub rerank_model: Option<String>,
+    #[command(flatten)]
+    pub analysis: AnalysisArgs,
+    #[command(flatten)]
+    pub ai: AiArgs,
+}
+
+#[derive(Args)]
+pub struct SelfContainerCommand {
+    #[arg(value_name = "REPO_URL")]
+    pub url: String,
+    /// Container image
+    #[arg(long)]
+    pub image: Option<String>,
+    #[arg(long, value_parser = ["docker", "podman"])]
+    pub runtime: Option<String>,
+    #[command(flatten)]
+    pub analysis: AnalysisArgs,
+    #[command(flatten)]
+    pub ai: AiArgs,
+    #[command(flatten)]
+    pub notify: NotifyArgs,
+    #[arg(long, value_name = "PATH")]
+    pub overrides: Option<String>,
+}
+
+#[derive(Args)]
+pub struct AnalyzeLocalCommand {
+    /// The mounted repository
+    #[arg(value_name = "REPO_PATH")]
+    pub path: String,
+    /// Remote data fetched by the host
+    #[arg(long, value_name = "PATH")]
+    pub remote: String,
+    #[command(flatten)]
+    pub analysis: AnalysisArgs,
+    #[arg(long, value_name = "PATH")]
+    pub overrides: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ReleaseDiffCommand {
+    #[arg(value_name = "REPO_URL")]
+    pub url: String,
+    pub from_tag: String,
+    pub to_tag: String,
+    /// Adds AI-written release notes
+    #[arg(long)]
+    pub ai_summary: bool,
+    #[command(flatten)]
+    pub analysis: AnalysisArgs,
+    #[command(flatten)]
+    pub ai: AiArgs,
+}
+
+#[derive(Args)]
+pub struct ExportContextCommand {
+    #[arg(value_name = "REPO_URL|PATH")]
+    pub target: String,
+    /// md or text
+    #[arg(long, default_value = "md", value_parser = context_format_parser())]
+    pub format: ContextFormat,
+    /// Files are added, most relevant first, while they fit
+    #[arg(long, default_value_t = 100_000, value_name = "TOKENS")]
+    pub max_tokens: usize,
+    #[command(flatten)]
+    pub analysis: AnalysisArgs,
+}
+
+#[derive(Args)]
+pub struct ReportCommand {
+    #[arg(value_name = "ANALYSIS_JSON")]
+    pub file: String,
+    #[arg(long, default_value = "md", value_parser = report_format_parser(), value_name = "FORMAT")]
+    pub format: ReportFormat,
+    /// Appends the answers of a saved assessment
+    #[arg(long, value_name = "ASSESSMENT_JSON")]
+    pub appendix: Option<String>,
+    /// Keeps only these appendix answers, numbered from 1
+    #[arg(long, value_delimiter = ',', value_parser = parse_answer_number, requires = "appendix", value_name = "1,3")]
+    pub appendix_answers: Vec<usize>,
+}
+
+#[derive(Args)]
+pub struct DiffCommand {
+    #[arg(value_name = "OLD_ANALYSIS_JSON")]
+    pub old: String,
+    #[arg(value_name = "NEW_ANALYSIS_JSON")]
+    pub new: String,
+}
+
+#[derive(Args)]
+pub struct CheckCommand {
+    #[arg(value_name = "REPO_URL|ANALYSIS_JSON")]
+    pub target: String,
+    /// Publishes the result as a GitHub check run
+    #[arg(long)]
+    pub publish: bool,
+    /// Commit of the check run, GITHUB_SHA when not given
+    #[arg(long, requires = "publish", value_name = "COMMIT")]
+    pub sha: Option<String>,
+    #[arg(long, value_name = "PATH")]
+    pub overrides: Option<String>,
+    #[command(flatten)]
+    pub analysis: AnalysisArgs,
+}
+
+#[derive(Args)]
+pub struct PrCommentCommand {
+    #[arg(value_name = "ANALYSIS_JSON")]
+    pub file: String,
+    /// Earlier analysis the changes are shown against
+    #[arg(long, value_name = "ANALYSIS_JSON")]
+    pub baseline: Option<String>,
+    /// Pull request, the comment is only printed without it
+    #[arg(long, value_name = "NUMBER")]
+    pub pr: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct ScheduleCommand {
+    #[arg(value_name = "ANALYZER_TOML")]
+    pub file: String,
+    /// Runs every repository once and exits
+    #[arg(long)]
+    pub once: bool,
+    #[command(flatten)]
+    pub analysis: AnalysisArgs,
+}
+
+#[derive(Args)]
+pub struct SuggestMetadataCommand {
+    #[arg(value_name = "ANALYSIS_JSON")]
+    pub file: String,
+    /// Applies the suggestions to the GitHub repository
+    #[arg(long)]
+    pub apply: bool,
+}
+
+// Options of the analysis pipeline
+#[derive(Args)]
+pub struct AnalysisArgs {
+    /// Tighter limits for untrusted repositories
+    #[arg(long)]
+    pub paranoid: bool,
+    /// Continues from the checkpoints of a cancelled run
+    #[arg(long)]
+    pub resume: bool,
+    /// Detects languages with Linguist's definitions
+    #[arg(long)]
+    pub linguist: bool,
+    /// Summarizes the README even with an AI provider
+    #[arg(long)]
+    pub readme_summary: bool,
+    /// Files kept in the importance ranking
+    #[arg(long, default_value_t = 10, value_name = "N")]
+    pub top_files: usize,
+    /// Leaves matching files out of the ranking, repeatable
+    #[arg(long, value_name = "GLOB")]
+    pub rank_exclude: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct AiArgs {
+    #[arg(long, value_name = "N")]
+    pub max_tokens: Option<usize>,
+    #[arg(long, value_name = "USD")]
+    pub max_ai_cost: Option<f64>,
+    /// Ignores cached AI responses
+    #[arg(long)]
+    pub force_ai: bool,
+}
+
+#[derive(Args)]
+pub struct NotifyArgs {
+    /// Webhook the summary is sent to, repeatable
+    #[arg(long = "notify", value_name = "WEBHOOK_URL")]
+    pub webhooks: Vec<String>,
+    /// Earlier analysis the notification is compared with
+    #[arg(long, value_name = "ANALYSIS_JSON")]
+    pub baseline: Option<String>,
+    /// Link to the full report in the notification
+    #[arg(long, value_name = "URL")]
+    pub report_url: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    Yaml,
+    Onepager,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Command {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Analyze(_) => "analyze",
+            Self::Assess(_) => "assess",
+            Self::SelfContainer(_) => "self-container",
+            Self::AnalyzeLocal(_) => "analyze-local",
+            Self::ReleaseDiff(_) => "release-diff",
+            Self::ExportContext(_) => "export-context",
+            Self::Report(_) => "report",
+            Self::Diff(_) => "diff",
+            Self::Check(_) => "check",
+            Self::PrComment(_) => "pr-comment",
+            Self::Schedule(_) => "schedule",
+            Self::SuggestMetadata(_) => "suggest-metadata",
+            Self::Completions { .. } => "completions",
+        }
+    }
+
+    // The --output formats the command writes, the others are refused rather
+    // than ignored
+    pub fn output_formats(&self) -> &'static [OutputFormat] {
+        match self {
+            Self::Report(_) => &[
+                OutputFormat::Json,
+                OutputFormat::Yaml,
+                OutputFormat::Onepager,
+            ],
+            Self::Analyze(_)
+            | Self::Assess(_)
+            | Self::SelfContainer(_)
+            | Self::AnalyzeLocal(_)
+            | Self::ReleaseDiff(_) => &[OutputFormat::Json, OutputFormat::Yaml],
+            _ => &[OutputFormat::Json],
+        }
+    }
+}
+
+// Parses the arguments, with `analyze` inserted before a bare URL
+pub fn parse() -> Cli {
+    let mut args: Vec<OsString> = std::env::args_os().collect();
+    let mut command = Cli::command();
+    if let Some(first) = args.get(1).and_then(|arg| arg.to_str())
+        && !first.starts_with('-')
+        && command.find_subcommand(first).is_none()
+        && first != "help"
+    {
+        args.insert(1, "analyze".into());
+    }
+    let cli = Cli::parse_from(args);
+    if !cli.command.output_formats().contains(&cli.global.output) {
+        command
+            .error(
+                ErrorKind::InvalidValue,
+                format!(
+                    "{} can't write --output {}",
+                    cli.command.name(),
+                    format_name(cli.global.output)
+                ),
+            )
+            .exit();
+    }
+    cli
+}
+
+fn format_name(format: impl ValueEnum) -> String {
+    format
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+// Completion scripts for the subcommands and their flags, walked from the
+// command definitions, so new commands, aliases and flags complete without
+// touching the scripts
+pub fn completions(shell: Shell) -> String {
+    let mut command = Cli::command();
+    // Building adds --help and --version and copies the global flags into
+    // every subcommand
+    command.build();
+    let bin = command.get_name().to_string();
+    match shell {
+        Shell::Bash => bash_completions(&command, &bin),
+        // zsh runs the bash script through its compatibility layer
+        Shell::Zsh => format!(
+            "autoload -U +X bashcompinit && bashcompinit\n{}",
+            bash_completions(&command, &bin)
+        ),
+        Shell::Fish => fish_completions(&command, &bin),
+    }
+}
+
+fn bash_completions(command: &ClapCommand, bin: &str) -> String {
+    let function = format!("_{}", bin.replace('-', "_"));
+    let mut words: Vec<String> = subcommands(command)
+        .flat_map(|sub| names(sub))
+        .map(str::to_string)
+        .collect();
+    words.extend(flags(command));
+
+    let mut script = format!(
+        "{function}() {{\n    local cur prev command\n    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"\n    command=\"${{COMP_WORDS[1]}}\"\n\n    if [[ $COMP_CWORD -eq 1 ]]; then\n        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n        return\n    fi\n\n    case \"$command:$prev\" in\n",
+        words.join(" ")
+    );
+    // Flags with a fixed set of values complete them, per command since the
+    // same flag can take different values
+    for sub in subcommands(command) {
+        for arg in visible_arguments(sub) {
+            let values = possible_values(arg);
+            if values.is_empty() {
+                continue;
+            }
+            let patterns: Vec<String> = names(sub)
+                .iter()
+                .flat_map(|name| {
+                    spellings(arg)
+                        .into_iter()
+                        .map(move |spelling| format!("{name}:{spelling}"))
+                })
+                .collect();
+            if patterns.is_empty() {
+                continue;
+            }
+            script.push_str(&format!(
+                "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;\n",
+                patterns.join("|"),
+                values.join(" ")
+            ));
+        }
+    }
+    script.push_str("    esac\n\n    case \"$command\" in\n");
+    for sub in subcommands(command) {
+        let mut words = flags(sub);
+        words.extend(sub.get_positionals().flat_map(possible_values));
+        script.push_str(&format!(
+            "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;\n",
+            names(sub).join("|"),
+            words.join(" ")
+        ));
+    }
+    script.push_str(&format!(
+        "    esac\n}}\ncomplete -o default -F {function} {bin}\n"
+    ));
+    script
+}
+
+fn fish_completions(command: &ClapCommand, bin: &str) -> String {
+    let mut script = String::new();
+    for sub in subcommands(command) {
+        for name in names(sub) {
+            script.push_str(&format!(
+                "complete -c {bin} -n __fish_use_subcommand -f -a {name} -d '{}'\n",
+                help(sub.get_about().map(|about| about.to_string()))
+            ));
+        }
+    }
+    for arg in visible_arguments(command) {
+        script.push_str(&fish_flag(bin, "__fish_use_subcommand", arg));
+    }
+    for sub in subcommands(command) {
+        let condition = format!("__fish_seen_subcommand_from {}", names(sub).join(" "));
+        for arg in visible_arguments(sub) {
+            script.push_str(&fish_flag(bin, &condition, arg));
+        }
+        let values: Vec<String> = sub.get_positionals().flat_map(possible_values).collect();
+        if !values.is_empty() {
+            script.push_str(&format!(
+                "complete -c {bin} -n '{condition}' -f -a '{}'\n",
+                values.join(" ")
+            ));
+        }
+    }
+    script
+}
+
+// One fish line for a flag, with its short and long forms and its values
+fn fish_flag(bin: &str, condition: &str, arg: &Arg) -> String {
+    let mut options = String::new();
+    if let Some(short) = arg.get_short() {
+        options.push_str(&format!(" -s {short}"));
+    }
+    for long in arg
+        .get_long()
+        .into_iter()
+        .chain(arg.get_visible_aliases().unwrap_or_default())
+    {
+        options.push_str(&format!(" -l {long}"));
+    }
+    if options.is_empty() {
+        return String::new();
+    }
+    let values = possible_values(arg);
+    if !values.is_empty() {
+        options.push_str(&format!(" -x -a '{}'", values.join(" ")));
+    }
+    format!(
+        "complete -c {bin} -n '{condition}'{options} -d '{}'\n",
+        help(arg.get_help().map(|help| help.to_string()))
+    )
+}
+
+fn subcommands(command: &ClapCommand) -> impl Iterator<Item = &ClapCommand> {
+    command
+        .get_subcommands()
+        .filter(|sub| !sub.is_hide_set() && sub.get_name() != "help")
+}
+
+// The name of a subcommand and its visible aliases
+fn names(command: &ClapCommand) -> Vec<&str> {
+    std::iter::once(command.get_name())
+        .chain(command.get_visible_aliases())
+        .collect()
+}
+
+fn visible_arguments(command: &ClapCommand) -> impl Iterator<Item = &Arg> {
+    command.get_arguments().filter(|arg| !arg.is_hide_set())
+}
+
+// Every way to write a flag: short, long and visible long aliases
+fn spellings(arg: &Arg) -> Vec<String> {
+    let mut spellings: Vec<String> = arg
+        .get_short()
+        .map(|short| format!("-{short}"))
+        .into_iter()
+        .collect();
+    spellings.extend(arg.get_long().map(|long| format!("--{long}")));
+    spellings.extend(
+        arg.get_visible_aliases()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|alias| format!("--{alias}")),
+    );
+    spellings
+}
+
+fn flags(command: &ClapCommand) -> Vec<String> {
+    visible_arguments(command).flat_map(spellings).collect()
+}
+
+fn possible_values(arg: &Arg) -> Vec<String> {
+    arg.get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect()
+}
+
+// First line of the help, quoted for fish
+fn help(text: Option<String>) -> String {
+    text.unwrap_or_default()
+        .lines()
+        .next()
+        .unwrap_or("")
+        .replace('\'', "\\'")
+}
+
+// Listed values, so the completions offer them
+fn report_format_parser() -> impl TypedValueParser<Value = ReportFormat> {
+    PossibleValuesParser::new([
+        PossibleValue::new("md").alias("markdown"),
+        PossibleValue::new("html"),
+        PossibleValue::new("pdf"),
+    ])
+    .try_map(|format| ReportFormat::parse(&format).ok_or("expected md, html or pdf"))
+}
+
+fn context_format_parser() -> impl TypedValueParser<Value = ContextFormat> {
+    PossibleValuesParser::new([
+        PossibleValue::new("md").alias("markdown"),
+        PossibleValue::new("text").alias("txt"),
+    ])
+    .try_map(|format| ContextFormat::parse(&format).ok_or("expected md or text"))
+}
+
+fn chunk_kind_parser() -> impl TypedValueParser<Value = ChunkKind> {
+    PossibleValuesParser::new([
+        PossibleValue::new("doc").aliases(["docs", "documentation"]),
+        PossibleValue::new("config").alias("configuration"),
+        PossibleValue::new("code"),
+    ])
+    .try_map(|kind| ChunkKind::parse(&kind).ok_or("expected doc, config or code"))
+}
+
+fn parse_answer_number(value: &str) -> Result<usize, String> {
+    value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|number| *number > 0)
+        .ok_or_else(|| "expected an answer number, counting from 1".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_definitions_are_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn completions_cover_every_command_and_alias() {
+        let bash = completions(Shell::Bash);
+        let fish = completions(Shell::Fish);
+        for name in ["export-context", "ingest", "diff", "compare", "completions"] {
+            assert!(bash.contains(name), "{name} missing from bash");
+            assert!(
+                fish.contains(&format!("-a {name} ")),
+                "{name} missing from fish"
+            );
+        }
+    }
+
+    #[test]
+    fn onepager_is_only_written_by_report() {
+        let formats = |args: &[&str]| {
+            let cli = Cli::try_parse_from(args).unwrap();
+            (cli.command.output_formats(), cli.global.output)
+        };
+        let (supported, requested) = formats(&["bin", "report", "a.json", "--output", "onepager"]);
+        assert!(supported.contains(&requested));
+        let (supported, requested) = formats(&[
+            "bin",
+            "analyze",
+            "https://github.com/o/r",
+            "--output",
+            "onepager",
+        ]);
+        assert!(!supported.contains(&requested));
+        let (supported, requested) =
+            formats(&["bin", "diff", "a.json", "b.json", "--output", "yaml"]);
+        assert!(!supported.contains(&requested));
+        assert!(Cli::try_parse_from(["bin", "report", "a.json", "--output", "pdf"]).is_err());
+    }
+
+    #[test]
+    fn flag_values_are_completed_per_command() {
+        let bash = completions(Shell::Bash);
+        assert!(bash.contains("report:--format) COMPREPLY=($(compgen -W \"md html pdf\""));
+        assert!(bash.contains("ingest:--format"));
+        assert!(completions(Shell::Fish).contains("-l format -x -a 'md text'"));
+    }
+}
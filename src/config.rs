@@ -0,0 +1,121 @@
+// analyzer.toml, read once per run. Each module owns the type of its section
+// and gets it from here
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::analyzers::detections::DetectionsConfig;
+use crate::analyzers::rules::RuleConfig;
+use crate::check::CheckThresholds;
+use crate::git::GitConfig;
+use crate::github::GitHubConfig;
+use crate::network::NetworkConfig;
+use crate::paths::PathsConfig;
+use crate::plugins::PluginConfig;
+use crate::report::theme::Theme;
+use crate::schedule::ScheduleConfig;
+
+// Config file read from the working directory when --config is not given
+pub const DEFAULT_CONFIG_FILE: &str = "analyzer.toml";
+
+// Every section, missing ones take their defaults
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AnalyzerConfig {
+    pub check: CheckThresholds,
+    pub theme: Theme,
+    pub plugins: PluginConfig,
+    pub detections: DetectionsConfig,
+    pub rules: Vec<RuleConfig>,
+    pub paths: PathsConfig,
+    pub network: NetworkConfig,
+    pub github: GitHubConfig,
+    pub schedule: ScheduleConfig,
+    pub git: GitConfig,
+}
+
+// An explicit config path must exist, the default one is optional. Paths in
+// the file are relative to it
+pub fn load(path: Option<&Path>) -> Result<AnalyzerConfig> {
+    let (content, dir) = match path {
+        Some(path) => (
+            fs::read_to_string(path)?,
+            path.parent().unwrap_or(Path::new("")).to_path_buf(),
+        ),
+        None => match fs::read_to_string(DEFAULT_CONFIG_FILE) {
+            Ok(content) => (content, PathBuf::new()),
+            Err(_) => return Ok(AnalyzerConfig::default()),
+        },
+    };
+    let mut config: AnalyzerConfig = toml::from_str(&content)?;
+    let rule_scripts = config
+        .rules
+        .iter_mut()
+        .filter_map(|rule| rule.script.as_mut());
+    for path in rule_scripts
+        .chain(&mut config.theme.logo)
+        .chain(&mut config.plugins.wasm)
+        .chain(&mut config.detections.packs)
+        .chain(&mut config.paths.cache_dir)
+        .chain(&mut config.paths.work_dir)
+        .chain(&mut config.network.ca_bundle)
+    {
+        *path = dir.join(&*path);
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_are_relative_to_the_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("analyzer.toml");
+        fs::write(
+            &file,
+            r#"
+            [network]
+            ca_bundle = "certs/ca.pem"
+
+            [paths]
+            work_dir = "/srv/work"
+
+            [detections]
+            packs = ["packs/internal.yaml"]
+            "#,
+        )
+        .unwrap();
+        let config = load(Some(&file)).unwrap();
+        assert_eq!(
+            config.network.ca_bundle,
+            Some(dir.path().join("certs/ca.pem"))
+        );
+        // Absolute paths stay as they are
+        #[cfg(unix)]
+        assert_eq!(config.paths.work_dir, Some(PathBuf::from("/srv/work")));
+        assert_eq!(
+            config.detections.packs,
+            vec![dir.path().join("packs/internal.yaml")]
+        );
+        assert_eq!(config.check.max_secrets, Some(0));
+    }
+
+    #[test]
+    fn an_explicit_config_must_exist() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load(Some(&dir.path().join("missing.toml"))).is_err());
+    }
+
+    #[test]
+    fn unknown_keys_of_a_section_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("analyzer.toml");
+        fs::write(&file, "[network]\nproxi = \"http://proxy:3128\"\n").unwrap();
+        assert!(load(Some(&file)).is_err());
+    }
+}
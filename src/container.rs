@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+
+use crate::{
+    cancel,
+    types::{RemoteRepositoryData, RepositoryAnalysis},
+};
+
+pub const DEFAULT_IMAGE: &str = "ai-repo-analyzer-rs:latest";
+pub const DEFAULT_RUNTIME: &str = "docker";
+
+const REMOTE_FILE: &str = "remote.json";
+const ANALYSIS_FILE: &str = "analysis.json";
+
+// Runs the offline analysis phase inside a locked-down container. The host
+// keeps the network-facing work (GitHub API, clone, registries, AI) and the
+// untrusted tree is only ever read by the sandboxed process.
+pub struct ContainerRunner {
+    runtime: String,
+    image: String,
+    local_args: Vec<String>,
+}
+
+impl ContainerRunner {
+    // `local_args` are passed through to `analyze-local`, e.g. --paranoid
+    pub fn new(runtime: Option<String>, image: Option<String>, local_args: Vec<String>) -> Self {
+        Self {
+            runtime: runtime.unwrap_or_else(|| DEFAULT_RUNTIME.to_string()),
+            image: image.unwrap_or_else(|| DEFAULT_IMAGE.to_string()),
+            local_args,
+        }
+    }
+
+    pub fn runtime(&self) -> &str {
+        &self.runtime
+    }
+
+    pub fn analyze(
+        &self,
+        remote: &RemoteRepositoryData,
+        repo_path: &Path,
+        exchange_dir: &Path,
+    ) -> Result<RepositoryAnalysis> {
+        fs::create_dir_all(exchange_dir)?;
+        let exchange_dir = exchange_dir.canonicalize()?;
+        let repo_path = repo_path.canonicalize()?;
+
+        let analysis_file = exchange_dir.join(ANALYSIS_FILE);
+        if analysis_file.exists() {
+            fs::remove_file(&analysis_file)?;
+        }
+        fs::write(
+            exchange_dir.join(REMOTE_FILE),
+            serde_json::to_string(remote)?,
+        )?;
+
+        let args = self.run_args(&repo_path, &exchange_dir)?;
+        info!("Running {} {}", self.runtime, args.join(" "));
+
+        let mut child = Command::new(&self.runtime)
+            .args(&args)
+            .spawn()
+            .with_context(|| format!("Failed to start container runtime '{}'", self.runtime))?;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if cancel::is_cancelled() {
+                child.kill()?;
+                child.wait()?;
+                self.cleanup(&exchange_dir);
+                anyhow::bail!("Sandboxed analysis cancelled");
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        };
+        if !status.success() {
+            self.cleanup(&exchange_dir);
+            anyhow::bail!("Sandboxed analysis failed with {}", status);
+        }
+
+        let content = fs::read_to_string(&analysis_file)
+            .context("Sandboxed analysis did not produce an output file")?;
+        self.cleanup(&exchange_dir);
+        let mut analysis: RepositoryAnalysis = serde_json::from_str(&content)?;
+        restore_previews(&mut analysis);
+
+        Ok(analysis)
+    }
+
+    fn cleanup(&self, exchange_dir: &Path) {
+        if let Err(e) = fs::remove_dir_all(exchange_dir) {
+            warn!("Failed to remove {:?}: {}", exchange_dir, e);
+        }
+    }
+
+    fn run_args(&self, repo_path: &Path, exchange_dir: &Path) -> Result<Vec<String>> {
+        let mut args: Vec<String> = [
+            "run",
+            "--rm",
+            "--network",
+            "none",
+            "--read-only",
+            "--tmpfs",
+            "/tmp",
+            "--cap-drop",
+            "ALL",
+            "--security-opt",
+            "no-new-privileges",
+            "--pids-limit",
+            "256",
+            "-e",
+            "RUST_LOG",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+        // Run as the owner of the exchange directory so the output file can be written
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let metadata = fs::metadata(exchange_dir)?;
+            args.push("--user".to_string());
+            args.push(format!("{}:{}", metadata.uid(), metadata.gid()));
+        }
+
+        args.push("-v".to_string());
+        args.push(format!("{}:/repo:ro", repo_path.display()));
+        args.push("-v".to_string());
+        args.push(format!("{}:/exchange", exchange_dir.display()));
+        args.push(self.image.clone());
+
+        let container_exchange = PathBuf::from("/exchange");
+        args.extend([
+            "analyze-local".to_string(),
+            "/repo".to_string(),
+            "--remote".to_string(),
+            container_exchange.join(REMOTE_FILE).display().to_string(),
+            "--output-file".to_string(),
+            container_exchange.join(ANALYSIS_FILE).display().to_string(),
+        ]);
+        args.extend(self.local_args.iter().cloned());
+
+        Ok(args)
+    }
+}
+
+// Key file previews are not serialized, refill them from the file structure
+fn restore_previews(analysis: &mut RepositoryAnalysis) {
+    let previews: HashMap<&PathBuf, &String> = analysis
+        .file_structure
+        .files()
+        .iter()
+        .filter_map(|file| Some((&file.path, file.content_preview.as_ref()?)))
+        .collect();
+
+    for file in &mut analysis.interesting_files {
+        file.preview = previews.get(&file.path).map(|preview| preview.to_string());
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashSet;
+use std::io::IsTerminal;
+
+use crate::types::{RepositoryAnalysis, SecurityChange, SecurityDelta, SecurityInfo};
+
+// Exit codes of the diff command, so CI can gate on security regressions
+pub const EXIT_NO_REGRESSIONS: i32 = 0;
+pub const EXIT_REGRESSIONS: i32 = 2;
+pub const EXIT_HIGH_SEVERITY_REGRESSIONS: i32 = 3;
+
+const SEVERITIES: &[&str] = &["critical", "high", "medium", "low"];
+
+// Compare the security posture of two saved analyses of the same repository
+pub fn security_delta(from: &RepositoryAnalysis, to: &RepositoryAnalysis) -> SecurityDelta {
+    let (old, new) = (&from.security_info, &to.security_info);
+    let mut introduced = Vec::new();
+    let mut resolved = Vec::new();
+
+    for alert in added(&old.vulnerability_alerts, &new.vulnerability_alerts) {
+        introduced.push(change("high", "vulnerable-dependency", alert));
+    }
+    for alert in added(&new.vulnerability_alerts, &old.vulnerability_alerts) {
+        resolved.push(change("high", "vulnerable-dependency", alert));
+    }
+    for dependency in added(&old.outdated_dependencies, &new.outdated_dependencies) {
+        introduced.push(change(
+            "medium",
+            "vulnerable-dependency",
+            &format!("unpinned {}", dependency),
+        ));
+    }
+    for dependency in added(&new.outdated_dependencies, &old.outdated_dependencies) {
+        resolved.push(change(
+            "medium",
+            "vulnerable-dependency",
+            &format!("unpinned {}", dependency),
+        ));
+    }
+
+    // Secrets are matched by file and kind since line numbers shift between runs
+    let (old_secrets, new_secrets) = (secrets(old), secrets(new));
+    for (path, kind, line) in &new_secrets {
+        if !old_secrets.iter().any(|(p, k, _)| p == path && k == kind) {
+            introduced.push(secret_change(
+                kind,
+                &format!("{} in {}:{}", kind, path, line),
+            ));
+        }
+    }
+    for (path, kind, _) in &old_secrets {
+        if !new_secrets.iter().any(|(p, k, _)| p == path && k == kind) {
+            resolved.push(secret_change(kind, &format!("{} in {}", kind, path)));
+        }
+    }
+
+    let (old_checks, new_checks) = (security_checks(old), security_checks(new));
+    for check in added(&new_checks, &old_checks) {
+        introduced.push(change(
+            "medium",
+            "ci-check",
+            &format!("{} removed from CI", check),
+        ));
+    }
+    for check in added(&old_checks, &new_checks) {
+        resolved.push(change(
+            "medium",
+            "ci-check",
+            &format!("{} added to CI", check),
+        ));
+    }
+
+    match (old.has_security_policy, new.has_security_policy) {
+        (true, false) => introduced.push(change("low", "policy", "security policy removed")),
+        (false, true) => resolved.push(change("low", "policy", "security policy added")),
+        _ => {}
+    }
+
+    introduced.sort_by_key(|c| severity_rank(&c.severity));
+    resolved.sort_by_key(|c| severity_rank(&c.severity));
+
+    SecurityDelta {
+        from_analyzed_at: from.analyzed_at,
+        to_analyzed_at: to.analyzed_at,
+        introduced,
+        resolved,
+    }
+}
+
+pub fn exit_code(delta: &SecurityDelta) -> i32 {
+    if delta
+        .introduced
+        .iter()
+        .any(|c| matches!(c.severity.as_str(), "critical" | "high"))
+    {
+        EXIT_HIGH_SEVERITY_REGRESSIONS
+    } else if !delta.introduced.is_empty() {
+        EXIT_REGRESSIONS
+    } else {
+        EXIT_NO_REGRESSIONS
+    }
+}
+
+pub fn print_security_delta(delta: &SecurityDelta) {
+    // Colors only on a terminal, and never when NO_COLOR is set
+    let colored = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    let paint = |code: &str, text: &str| {
+        if colored {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    };
+
+    println!(
+        "{}",
+        paint(
+            "1",
+            &format!(
+                "Security delta: {} -> {}",
+                delta.from_analyzed_at.format("%Y-%m-%d %H:%M"),
+                delta.to_analyzed_at.format("%Y-%m-%d %H:%M")
+            )
+        )
+    );
+
+    if delta.introduced.is_empty() {
+        println!("{}", paint("32", "No new security findings"));
+    } else {
+        println!();
+        println!("Introduced ({}):", delta.introduced.len());
+        for change in &delta.introduced {
+            let color = match change.severity.as_str() {
+                "critical" => "1;31",
+                "high" => "31",
+                "medium" => "33",
+                _ => "36",
+            };
+            println!(
+                "  {} {:<22} {}",
+                paint(color, &format!("{:<8}", change.severity.to_uppercase())),
+                change.category,
+                change.description
+            );
+        }
+    }
+
+    if !delta.resolved.is_empty() {
+        println!();
+        println!("Resolved ({}):", delta.resolved.len());
+        for change in &delta.resolved {
+            println!(
+                "  {} {:<22} {}",
+                paint("32", &format!("{:<8}", "FIXED")),
+                change.category,
+                change.description
+            );
+        }
+    }
+}
+
+fn change(severity: &str, category: &str, description: &str) -> SecurityChange {
+    SecurityChange {
+        severity: severity.to_string(),
+        category: category.to_string(),
+        description: description.to_string(),
+    }
+}
+
+// A committed private key is worse than a scoped token
+fn secret_change(kind: &str, description: &str) -> SecurityChange {
+    let severity = if kind == "private-key" {
+        "critical"
+    } else {
+        "high"
+    };
+    change(severity, "secret", description)
+}
+
+// Items of `to` that are missing from `from`
+fn added<'a>(from: &[String], to: &'a [String]) -> Vec<&'a String> {
+    let from: HashSet<&String> = from.iter().collect();
+    to.iter().filter(|item| !from.contains(item)).collect()
+}
+
+fn secrets(info: &SecurityInfo) -> Vec<(String, String, usize)> {
+    info.secret_findings
+        .iter()
+        .map(|finding| {
+            (
+                finding.path.display().to_string(),
+                finding.kind.clone(),
+                finding.line,
+            )
+        })
+        .collect()
+}
+
+// Analyses saved before CI checks were listed only carry the CodeQL and Dependabot flags
+fn security_checks(info: &SecurityInfo) -> Vec<String> {
+    let mut checks = info.ci_security_checks.clone();
+    for (enabled, check) in [
+        (info.has_codeql, "codeql"),
+        (info.has_dependabot, "dependabot"),
+    ] {
+        if enabled && !checks.iter().any(|c| c == check) {
+            checks.push(check.to_string());
+        }
+    }
+    checks
+}
+
+fn severity_rank(severity: &str) -> usize {
+    SEVERITIES
+        .iter()
+        .position(|s| *s == severity)
+        .unwrap_or(SEVERITIES.len())
+}
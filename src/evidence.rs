@@ -0,0 +1,144 @@
+use std::path::Path;
+
+use url::Url;
+
+use crate::forge::ForgeKind;
+use crate::types::{EvidenceRef, RepositoryAnalysis};
+
+// Builds links to files and lines on the GitHub, GitLab or Bitbucket web UI
+// at the analyzed commit, so every exporter renders the same clickable evidence
+pub struct EvidenceLinker {
+    base: Option<Url>, // https://github.com/owner/repo/blob/<sha>, .../-/blob/<sha> on GitLab
+    forge: ForgeKind,
+}
+
+impl EvidenceLinker {
+    // The branch stands in for the commit when the history couldn't be read.
+    // Repositories without a web page get no links
+    pub fn new(analysis: &RepositoryAnalysis) -> Self {
+        let revision = analysis
+            .git_analysis
+            .head_sha
+            .as_deref()
+            .unwrap_or(&analysis.metadata.default_branch);
+        let base = Url::parse(analysis.metadata.html_url.trim_end_matches('/'))
+            .ok()
+            .filter(|url| url.scheme().starts_with("http") && !revision.is_empty());
+        // Pages on other hosts are linked like GitHub's
+        let forge = base
+            .as_ref()
+            .and_then(|url| url.host_str())
+            .and_then(ForgeKind::of_host)
+            .unwrap_or(ForgeKind::GitHub);
+        let base = base.and_then(|mut url| {
+            url.path_segments_mut()
+                .ok()?
+                .extend(forge.file_segments())
+                .push(revision);
+            Some(url)
+        });
+        Self { base, forge }
+    }
+
+    pub fn url(&self, path: &Path, line: Option<u32>) -> Option<String> {
+        let mut url = self.base.clone()?;
+        let path = path.to_string_lossy().replace('\\', "/");
+        url.path_segments_mut().ok()?.extend(
+            path.split('/')
+                .filter(|part| !part.is_empty() && *part != "."),
+        );
+        if let Some(line) = line {
+            url.set_fragment(Some(&self.forge.line_fragment(line)));
+        }
+        Some(url.to_string())
+    }
+
+    // Fills in the links of every finding that points at a file
+    pub fn link(&self, analysis: &mut RepositoryAnalysis) {
+        if self.base.is_none() {
+            return;
+        }
+
+        let security = &mut analysis.security_info;
+        for finding in &mut security.secret_findings {
+            finding.url = self.url(&finding.path, Some(finding.line as u32));
+        }
+        if let Some(usage) = &mut security.unsafe_usage {
+            for hotspot in usage.crates.iter_mut().flat_map(|c| c.hotspots.iter_mut()) {
+                hotspot.url = self.url(&hotspot.path, None);
+            }
+        }
+        self.link_refs(&mut security.dependency_evidence);
+        let depth = &mut security.testing_depth;
+        for refs in [
+            &mut depth.continuous_fuzzing,
+            &mut depth.fuzz_targets,
+            &mut depth.property_testing,
+            &mut depth.sast,
+        ] {
+            self.link_refs(refs);
+        }
+        let info = &mut analysis.project_info;
+        for detection in [
+            &mut info.project_type,
+            &mut info.frameworks,
+            &mut info.build_tools,
+            &mut info.package_managers,
+            &mut info.testing_frameworks,
+            &mut info.ci_cd_tools,
+            &mut info.deployment_configs,
+            &mut info.database_technologies,
+            &mut info.execution_environments,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            self.link_refs(&mut detection.evidence);
+        }
+
+        let metrics = &mut analysis.code_metrics;
+        if let Some(panics) = &mut metrics.panic_density {
+            for hotspot in &mut panics.hotspots {
+                hotspot.url = self.url(&hotspot.path, None);
+            }
+        }
+        if let Some(errors) = &mut metrics.error_handling {
+            for finding in &mut errors.findings {
+                finding.url = self.url(&finding.path, Some(finding.line));
+            }
+        }
+
+        let performance = &mut analysis.performance;
+        for refs in [
+            &mut performance.benchmarks,
+            &mut performance.ci_jobs,
+            &mut performance.profiling,
+        ] {
+            self.link_refs(refs);
+        }
+        self.link_refs(&mut analysis.api_docs.tools);
+        let spelling = &mut analysis.docs_spelling;
+        self.link_refs(&mut spelling.misspellings);
+        for term in &mut spelling.terminology {
+            self.link_refs(&mut term.evidence);
+        }
+        for requirement in &mut analysis.toolchain_requirements.requirements {
+            self.link_refs(requirement.declared_in.as_mut_slice());
+            self.link_refs(&mut requirement.ci);
+        }
+        for platform in &mut analysis.platform_support.platforms {
+            self.link_refs(&mut platform.ci);
+            self.link_refs(&mut platform.code);
+        }
+
+        for finding in &mut analysis.rule_findings {
+            finding.url = self.url(&finding.path, finding.line);
+        }
+    }
+
+    fn link_refs(&self, refs: &mut [EvidenceRef]) {
+        for evidence in refs {
+            evidence.url = self.url(&evidence.path, evidence.line);
+        }
+    }
+}
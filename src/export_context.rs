@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use log::{info, warn};
+
+use crate::ai::count_tokens;
+use crate::analyzers::interesting_files::InterestingFileSelector;
+use crate::file_index::{DirectoryView, FileIndex};
+use crate::types::{FileInfo, InterestingFile};
+use crate::utils::to_slash_path;
+
+// Files are no longer tried once less than this is left of the budget
+const MIN_FILE_TOKENS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContextFormat {
+    Markdown,
+    Text,
+}
+
+impl ContextFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.to_lowercase().as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "txt" | "text" => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+// The repository as one document, like gitingest and repomix: the tree,
+// then the files with their contents in order of relevance (READMEs, the
+// ranked interesting files, then the rest from the shallowest) for as long
+// as they fit in `max_tokens`
+pub fn render(
+    name: &str,
+    file_structure: &FileIndex,
+    ranked: &[InterestingFile],
+    format: ContextFormat,
+    max_tokens: usize,
+    read: impl Fn(&Path) -> Option<String>,
+) -> String {
+    let files = prioritize(file_structure, ranked);
+    let tree = render_tree(file_structure.root(), name);
+
+    let mut sections = Vec::new();
+    let mut tokens = count_tokens(&tree);
+    if tokens > max_tokens {
+        warn!(
+            "The tree alone is {} tokens, over the budget of {}",
+            tokens, max_tokens
+        );
+    }
+    let mut omitted = 0;
+    for file in &files {
+        if tokens + MIN_FILE_TOKENS > max_tokens {
+            omitted += 1;
+            continue;
+        }
+        let Some(content) = read(&file.path) else {
+            continue;
+        };
+        let section = render_file(file, &content, format);
+        let section_tokens = count_tokens(&section);
+        if tokens + section_tokens > max_tokens {
+            omitted += 1;
+            continue;
+        }
+        tokens += section_tokens;
+        sections.push(section);
+    }
+    info!(
+        "Exported {} files in {} tokens, {} left out to fit the budget",
+        sections.len(),
+        tokens,
+        omitted
+    );
+
+    let summary = format!(
+        "{} of {} files, about {} tokens{}",
+        sections.len(),
+        files.len(),
+        tokens,
+        if omitted > 0 {
+            format!(", {} left out to fit the budget", omitted)
+        } else {
+            String::new()
+        }
+    );
+    match format {
+        ContextFormat::Markdown => format!(
+            "# {}\n\n{}\n\n## Directory Structure\n\n```\n{}```\n\n## Files\n\n{}",
+            name,
+            summary,
+            tree,
+            sections.join("\n")
+        ),
+        ContextFormat::Text => format!(
+            "Repository: {}\nFiles: {}\n\nDirectory structure:\n{}\n{}",
+            name,
+            summary,
+            tree,
+            sections.join("\n")
+        ),
+    }
+}
+
+// Text files in the order they are exported
+fn prioritize<'a>(file_structure: &'a FileIndex, ranked: &[InterestingFile]) -> Vec<&'a FileInfo> {
+    let selector = InterestingFileSelector::new(0);
+    let files: Vec<&FileInfo> = file_structure
+        .files()
+        .iter()
+        .filter(|file| !file.is_binary)
+        .collect();
+    let rank = |file: &FileInfo| ranked.iter().position(|ranked| ranked.path == file.path);
+
+    let mut ordered: Vec<&FileInfo> = files
+        .iter()
+        .filter(|file| file.path.components().count() == 1 && is_readme(file))
+        .copied()
+        .collect();
+    let mut ranked_files: Vec<(usize, &FileInfo)> = files
+        .iter()
+        .filter_map(|file| Some((rank(file)?, *file)))
+        .collect();
+    ranked_files.sort_by_key(|(position, _)| *position);
+    ordered.extend(ranked_files.into_iter().map(|(_, file)| file));
+
+    let seen: HashSet<&Path> = ordered.iter().map(|file| file.path.as_path()).collect();
+    let mut rest: Vec<&FileInfo> = files
+        .into_iter()
+        .filter(|file| !seen.contains(file.path.as_path()))
+        .collect();
+    rest.sort_by_key(|file| {
+        (
+            selector.is_noise(file),
+            file.path.components().count(),
+            file.path.clone(),
+        )
+    });
+    ordered.extend(rest);
+    ordered
+}
+
+fn is_readme(file: &FileInfo) -> bool {
+    file.name.to_lowercase().starts_with("readme")
+}
+
+// gitingest's tree: directories first, then files, with box-drawing guides
+fn render_tree(root: DirectoryView<'_>, name: &str) -> String {
+    let mut tree = format!("└── {}/\n", name);
+    render_directory(root, "    ", &mut tree);
+    tree
+}
+
+fn render_directory(directory: DirectoryView<'_>, prefix: &str, tree: &mut String) {
+    let subdirectories: Vec<DirectoryView<'_>> = directory.subdirectories().collect();
+    let files = directory.files();
+    let count = subdirectories.len() + files.len();
+
+    for (i, subdirectory) in subdirectories.iter().enumerate() {
+        let last = i + 1 == count;
+        tree.push_str(&format!(
+            "{}{}{}/\n",
+            prefix,
+            if last { "└── " } else { "├── " },
+            subdirectory.name()
+        ));
+        let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        render_directory(*subdirectory, &child_prefix, tree);
+    }
+    for (i, file) in files.iter().enumerate() {
+        let last = subdirectories.len() + i + 1 == count;
+        tree.push_str(&format!(
+            "{}{}{}\n",
+            prefix,
+            if last { "└── " } else { "├── " },
+            file.name
+        ));
+    }
+}
+
+fn render_file(file: &FileInfo, content: &str, format: ContextFormat) -> String {
+    let path = to_slash_path(&file.path);
+    let content = content.trim_end();
+    match format {
+        ContextFormat::Markdown => {
+            // Longer than any backtick run of the content, so it can't close early
+            let longest = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+            let fence = "`".repeat(longest.max(2) + 1);
+            format!(
+                "### {}\n\n{}{}\n{}\n{}\n",
+                path.display(),
+                fence,
+                file.extension.as_deref().unwrap_or(""),
+                content,
+                fence
+            )
+        }
+        ContextFormat::Text => format!(
+            "{sep}\nFILE: {}\n{sep}\n{}\n",
+            path.display(),
+            content,
+            sep = "=".repeat(48)
+        ),
+    }
+}
@@ -0,0 +1,229 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use serde::de::Deserializer;
+use serde::ser::{SerializeStruct, Serializer};
+use serde::{Deserialize, Serialize};
+
+use crate::types::FileInfo;
+
+// Every scanned file lives once in a flat arena, in pre-order: a directory's
+// own files first, then each subdirectory's. That keeps the files of any
+// subtree contiguous, so analyzers borrow slices instead of collecting
+// (or cloning) the tree, and the nested tree is only produced as a view.
+// It (de)serializes as the nested directory tree saved analyses contain.
+#[derive(Debug, Clone)]
+pub struct FileIndex {
+    files: Vec<FileInfo>,
+    directories: Vec<Directory>, // The root is the first one
+}
+
+#[derive(Debug, Clone)]
+struct Directory {
+    path: PathBuf,
+    name: String,
+    parent: Option<usize>,
+    file_count: usize,          // Own files, the first ones of `all_files`
+    all_files: Range<usize>,    // Files of the whole subtree
+    subdirectories: Vec<usize>, // Directory ids
+    total_size: u64,
+}
+
+impl FileIndex {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            files: Vec::new(),
+            directories: vec![Directory::new(root, None, 0)],
+        }
+    }
+
+    pub fn root(&self) -> DirectoryView<'_> {
+        DirectoryView { index: self, id: 0 }
+    }
+
+    // Every file of the repository, in pre-order
+    pub fn files(&self) -> &[FileInfo] {
+        &self.files
+    }
+
+    // Files must be added to the directory created last, before any of its
+    // subdirectories, to keep subtrees contiguous
+    pub fn add_file(&mut self, directory: usize, file: FileInfo) {
+        debug_assert_eq!(directory, self.directories.len() - 1);
+        let size = file.size;
+        self.files.push(file);
+        self.directories[directory].file_count += 1;
+
+        let mut current = Some(directory);
+        while let Some(id) = current {
+            let dir = &mut self.directories[id];
+            dir.all_files.end += 1;
+            dir.total_size += size;
+            current = dir.parent;
+        }
+    }
+
+    pub fn add_directory(&mut self, parent: usize, path: &Path) -> usize {
+        let id = self.directories.len();
+        self.directories
+            .push(Directory::new(path, Some(parent), self.files.len()));
+        self.directories[parent].subdirectories.push(id);
+        id
+    }
+
+    // Drops a directory added last, with everything below it, when its scan fails
+    pub fn remove_directory(&mut self, directory: usize) {
+        let Some(parent) = self.directories[directory].parent else {
+            return;
+        };
+        let Directory {
+            all_files,
+            total_size,
+            ..
+        } = self.directories[directory].clone();
+
+        self.files.truncate(all_files.start);
+        self.directories.truncate(directory);
+        self.directories[parent]
+            .subdirectories
+            .retain(|id| *id != directory);
+
+        let mut current = Some(parent);
+        while let Some(id) = current {
+            let dir = &mut self.directories[id];
+            dir.all_files.end -= all_files.len();
+            dir.total_size -= total_size;
+            current = dir.parent;
+        }
+    }
+
+    fn add_tree(&mut self, parent: Option<usize>, tree: SavedDirectory) {
+        let id = match parent {
+            Some(parent) => self.add_directory(parent, &tree.path),
+            None => 0,
+        };
+        self.directories[id].name = tree.name;
+        for file in tree.files {
+            self.add_file(id, file);
+        }
+        for subdirectory in tree.subdirectories {
+            self.add_tree(Some(id), subdirectory);
+        }
+    }
+}
+
+impl Directory {
+    fn new(path: &Path, parent: Option<usize>, first_file: usize) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            name: path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_string(),
+            parent,
+            file_count: 0,
+            all_files: first_file..first_file,
+            subdirectories: Vec::new(),
+            total_size: 0,
+        }
+    }
+}
+
+// A directory of the index, borrowed
+#[derive(Clone, Copy)]
+pub struct DirectoryView<'a> {
+    index: &'a FileIndex,
+    id: usize,
+}
+
+impl<'a> DirectoryView<'a> {
+    fn directory(&self) -> &'a Directory {
+        &self.index.directories[self.id]
+    }
+
+    pub fn path(&self) -> &'a Path {
+        &self.directory().path
+    }
+
+    pub fn name(&self) -> &'a str {
+        &self.directory().name
+    }
+
+    pub fn file_count(&self) -> u32 {
+        self.directory().file_count as u32
+    }
+
+    pub fn subdirectory_count(&self) -> u32 {
+        self.directory().subdirectories.len() as u32
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.directory().total_size
+    }
+
+    // The directory's own files
+    pub fn files(&self) -> &'a [FileInfo] {
+        let dir = self.directory();
+        &self.index.files[dir.all_files.start..dir.all_files.start + dir.file_count]
+    }
+
+    // Files of the directory and everything below it
+    pub fn all_files(&self) -> &'a [FileInfo] {
+        &self.index.files[self.directory().all_files.clone()]
+    }
+
+    pub fn subdirectories(&self) -> impl Iterator<Item = DirectoryView<'a>> + 'a {
+        let index = self.index;
+        self.directory()
+            .subdirectories
+            .iter()
+            .map(move |id| DirectoryView { index, id: *id })
+    }
+}
+
+impl Serialize for FileIndex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.root().serialize(serializer)
+    }
+}
+
+impl Serialize for DirectoryView<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("DirectoryInfo", 7)?;
+        state.serialize_field("path", self.path())?;
+        state.serialize_field("name", self.name())?;
+        state.serialize_field("file_count", &self.file_count())?;
+        state.serialize_field("subdirectory_count", &self.subdirectory_count())?;
+        state.serialize_field("total_size", &self.total_size())?;
+        state.serialize_field("files", self.files())?;
+        state.serialize_field("subdirectories", &Subdirectories(*self))?;
+        state.end()
+    }
+}
+
+struct Subdirectories<'a>(DirectoryView<'a>);
+
+impl Serialize for Subdirectories<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.subdirectories())
+    }
+}
+
+// Nested tree as saved, the counts and sizes are recomputed while indexing
+#[derive(Deserialize)]
+struct SavedDirectory {
+    path: PathBuf,
+    name: String,
+    files: Vec<FileInfo>,
+    subdirectories: Vec<SavedDirectory>,
+}
+
+impl<'de> Deserialize<'de> for FileIndex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tree = SavedDirectory::deserialize(deserializer)?;
+        let mut index = FileIndex::new(&tree.path);
+        index.add_tree(None, tree);
+        Ok(index)
+    }
+}
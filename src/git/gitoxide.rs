@@ -0,0 +1,257 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use gix::bstr::ByteSlice;
+use gix::object::tree::diff::{Action, Change};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{Checkout, CommitRecord, History, TREE_ENTRY_LIMIT, TagDiff};
+use crate::types::FileChange;
+use crate::{cancel, network};
+
+// Returns the bytes received, measured as the size of the fetched packs.
+// The transfer and checkout are interrupted through the cancellation flag
+pub(super) fn clone(clone_url: &str, repo_path: &Path) -> Result<u64> {
+    // The reqwest transport builds its own client: it reads the proxy
+    // environment variables only, and trusts its built-in roots. A proxy set
+    // in analyzer.toml would be bypassed, so the clone is refused instead
+    if network::proxy().is_some() {
+        anyhow::bail!(
+            "The gix backend can't clone through the proxy set in analyzer.toml, set HTTPS_PROXY instead or use the git2 backend"
+        );
+    }
+    if let Some(bundle) = network::ca_bundle() {
+        log::warn!(
+            "The gix backend doesn't trust the CA bundle {}, clones through a proxy that re-signs TLS fail; use the git2 backend",
+            bundle.display()
+        );
+    }
+    let mut overrides = Vec::new();
+    for header in network::extra_headers(clone_url) {
+        overrides.push(format!("http.extraHeader={}", header));
+    }
+    let mut prepare =
+        gix::prepare_clone(clone_url, repo_path)?.with_in_memory_config_overrides(overrides);
+    let (mut checkout, _) = prepare.fetch_then_checkout(gix::progress::Discard, cancel::flag())?;
+    checkout.main_worktree(gix::progress::Discard, cancel::flag())?;
+
+    let packs = repo_path.join(".git").join("objects").join("pack");
+    let received_bytes = fs::read_dir(packs)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.metadata().ok())
+                .map(|metadata| metadata.len())
+                .sum()
+        })
+        .unwrap_or(0);
+    Ok(received_bytes)
+}
+
+pub(super) fn read_history(repo_path: &Path, limit: usize) -> Result<History> {
+    let repo = gix::open(repo_path)?;
+    let head_id = repo.head_id()?;
+
+    let walk = repo
+        .rev_walk([head_id.detach()])
+        .sorting(gix::revision::walk::Sorting::ByCommitTime(
+            Default::default(),
+        ))
+        .all()?;
+
+    let mut commits = Vec::new();
+    for info in walk.take(limit) {
+        let info = info?;
+        let commit = info.object()?;
+        let author = commit.author()?;
+
+        let mut tree_entries = Vec::new();
+        if let Ok(tree) = commit.tree() {
+            walk_tree(&repo, &tree, Path::new(""), &mut |_, name, _| {
+                tree_entries.push(name.to_string());
+                tree_entries.len() < TREE_ENTRY_LIMIT
+            })?;
+        }
+
+        commits.push(CommitRecord {
+            sha: info.id.to_string(),
+            time: DateTime::from_timestamp(commit.time()?.seconds, 0).unwrap_or_else(Utc::now),
+            author_name: author.name.to_str().ok().map(str::to_string),
+            author_email: author.email.to_str().ok().map(str::to_string),
+            message: commit.message_raw_sloppy().to_str_lossy().into_owned(),
+            tree_entries,
+        });
+    }
+
+    let references = repo.references()?;
+    let branch_count = references.local_branches()?.count() as u32;
+    let tag_count = references.tags()?.count() as u32;
+
+    Ok(History {
+        commits,
+        branch_count,
+        tag_count,
+        head_sha: Some(head_id.to_string()),
+    })
+}
+
+pub(super) fn read_checkout(repo_path: &Path) -> Result<Checkout> {
+    let repo = gix::open(repo_path)?;
+    let remote_url = repo
+        .find_default_remote(gix::remote::Direction::Fetch)
+        .and_then(|remote| remote.ok())
+        .and_then(|remote| {
+            remote
+                .url(gix::remote::Direction::Fetch)
+                .map(|url| url.to_bstring().to_string())
+        });
+    let branch = repo
+        .head_name()
+        .ok()
+        .flatten()
+        .map(|name| name.shorten().to_string());
+    Ok(Checkout { remote_url, branch })
+}
+
+pub(super) fn diff_tags(repo_path: &Path, from_tag: &str, to_tag: &str) -> Result<TagDiff> {
+    let repo = gix::open(repo_path)?;
+    let from = resolve_tag(&repo, from_tag)?;
+    let to = resolve_tag(&repo, to_tag)?;
+    let from_tree = from.tree()?;
+    let to_tree = to.tree()?;
+
+    // Rename detection and line counts as git diff does them
+    let mut resource_cache = repo.diff_resource_cache_for_tree_diff()?;
+    let mut files = Vec::new();
+    from_tree
+        .changes()?
+        .options(|options| {
+            options
+                .track_path()
+                .track_rewrites(Some(Default::default()));
+        })
+        .for_each_to_obtain_tree(&to_tree, |change| {
+            if change.entry_mode().is_tree() {
+                return Ok::<_, std::convert::Infallible>(Action::Continue);
+            }
+            let (additions, deletions) = change
+                .diff(&mut resource_cache)
+                .ok()
+                .and_then(|mut platform| platform.line_counts().ok())
+                .flatten()
+                .map(|counts| (counts.insertions, counts.removals))
+                .unwrap_or((0, 0));
+            resource_cache.clear_resource_cache_keep_allocation();
+
+            let path = PathBuf::from(change.location().to_str_lossy().as_ref());
+            let (status, old_path) = match &change {
+                Change::Addition { .. } => ("added", None),
+                Change::Deletion { .. } => ("deleted", None),
+                Change::Modification { .. } => ("modified", None),
+                Change::Rewrite {
+                    source_location,
+                    copy,
+                    ..
+                } => {
+                    let old = PathBuf::from(source_location.to_str_lossy().as_ref());
+                    if *copy {
+                        ("added", None)
+                    } else {
+                        ("renamed", Some(old).filter(|old| *old != path))
+                    }
+                }
+            };
+            files.push(FileChange {
+                path,
+                status: status.to_string(),
+                old_path,
+                additions,
+                deletions,
+            });
+            Ok(Action::Continue)
+        })?;
+
+    // Commits reachable from the newer tag but not the older one
+    let walk = repo.rev_walk([to.id]).with_hidden([from.id]).all()?;
+    let mut commit_authors = Vec::new();
+    for info in walk {
+        let commit = info?.object()?;
+        let author = commit.author()?;
+        commit_authors.push((
+            author.name.to_str().unwrap_or("Unknown").to_string(),
+            author.email.to_str().unwrap_or("").to_string(),
+        ));
+    }
+
+    let mut from_directories = HashSet::new();
+    walk_tree(
+        &repo,
+        &from_tree,
+        Path::new(""),
+        &mut |root, name, is_tree| {
+            if is_tree {
+                from_directories.insert(root.join(name));
+            }
+            true
+        },
+    )?;
+
+    let insertions = files.iter().map(|file| file.additions).sum();
+    let deletions = files.iter().map(|file| file.deletions).sum();
+    Ok(TagDiff {
+        from_sha: from.id.to_string(),
+        to_sha: to.id.to_string(),
+        files_changed: files.len() as u32,
+        insertions,
+        deletions,
+        files,
+        commit_authors,
+        from_directories,
+    })
+}
+
+pub(super) fn file_at_tag(repo_path: &Path, tag: &str, path: &Path) -> Option<String> {
+    let repo = gix::open(repo_path).ok()?;
+    let commit = resolve_tag(&repo, tag).ok()?;
+    let entry = commit.tree().ok()?.lookup_entry_by_path(path).ok()??;
+    let object = entry.object().ok()?;
+    String::from_utf8(object.data.clone()).ok()
+}
+
+// Annotated and lightweight tags both resolve to their commit
+fn resolve_tag<'r>(repo: &'r gix::Repository, tag: &str) -> Result<gix::Commit<'r>> {
+    let id = repo
+        .rev_parse_single(format!("refs/tags/{}", tag).as_str())
+        .ok()
+        .or_else(|| repo.rev_parse_single(tag).ok())
+        .ok_or_else(|| anyhow::anyhow!("Tag '{}' not found", tag))?;
+    Ok(id.object()?.peel_to_commit()?)
+}
+
+// Pre-order walk like libgit2's, calling `visit` with the parent directory,
+// the entry name and whether it's a tree. The walk stops once `visit`
+// returns false
+fn walk_tree(
+    repo: &gix::Repository,
+    tree: &gix::Tree<'_>,
+    root: &Path,
+    visit: &mut dyn FnMut(&Path, &str, bool) -> bool,
+) -> Result<bool> {
+    for entry in tree.iter() {
+        let entry = entry?;
+        let Ok(name) = entry.filename().to_str() else {
+            continue;
+        };
+        let is_tree = entry.mode().is_tree();
+        if !visit(root, name, is_tree) {
+            return Ok(false);
+        }
+        if is_tree {
+            let subtree = repo.find_tree(entry.oid())?;
+            if !walk_tree(repo, &subtree, &root.join(name), visit)? {
+                return Ok(false);
+            }
+        }
+    }
+    Ok(true)
+}
@@ -0,0 +1,404 @@
+// Commit author identities folded into people, so one person committing from
+// several addresses or spellings counts once. `.mailmap` is applied first,
+// like `git shortlog` does, then identities sharing an email, a name
+// (ignoring case and spacing) or a GitHub account are merged
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::types::GitHubUser;
+
+// A larger .mailmap is not a mailmap
+const MAX_MAILMAP_SIZE: u64 = 1 << 20;
+
+// Names shared by unrelated committers, such as CI users and defaults
+const GENERIC_NAMES: &[&str] = &[
+    "root",
+    "admin",
+    "administrator",
+    "user",
+    "unknown",
+    "ubuntu",
+    "runner",
+    "git",
+    "github",
+    "github action",
+    "github actions",
+    "your name",
+];
+
+struct MailmapEntry {
+    proper_name: Option<String>,
+    proper_email: Option<String>,
+    commit_name: Option<String>, // Lowercase, the entry only applies to this name
+    commit_email: String,        // Lowercase
+}
+
+#[derive(Default)]
+pub(super) struct Mailmap {
+    entries: Vec<MailmapEntry>,
+}
+
+impl Mailmap {
+    // The clone is untrusted: only a regular file of reasonable size is read
+    pub(super) fn load(repo_path: &Path) -> Self {
+        let path = repo_path.join(".mailmap");
+        match fs::symlink_metadata(&path) {
+            Ok(metadata) if metadata.is_file() && metadata.len() <= MAX_MAILMAP_SIZE => {
+                fs::read_to_string(&path)
+                    .map(|content| Self::parse(&content))
+                    .unwrap_or_default()
+            }
+            _ => Self::default(),
+        }
+    }
+
+    // Lines are `Proper Name <commit@email>`, `<proper@email> <commit@email>`,
+    // `Proper Name <proper@email> <commit@email>` or
+    // `Proper Name <proper@email> Commit Name <commit@email>`
+    fn parse(content: &str) -> Self {
+        let line_pattern =
+            Regex::new(r"^\s*([^<#]*?)\s*<([^>]*)>\s*(?:([^<#]*?)\s*<([^>]*)>)?").unwrap();
+        let non_empty = |text: &str| {
+            let text = text.trim();
+            (!text.is_empty()).then(|| text.to_string())
+        };
+
+        let mut entries = Vec::new();
+        for line in content.lines() {
+            let Some(captures) = line_pattern.captures(line) else {
+                continue;
+            };
+            let proper_name = non_empty(&captures[1]);
+            let entry = match captures.get(4) {
+                Some(commit_email) => MailmapEntry {
+                    proper_name,
+                    proper_email: non_empty(&captures[2]),
+                    commit_name: captures
+                        .get(3)
+                        .and_then(|name| non_empty(name.as_str()))
+                        .map(|name| name.to_lowercase()),
+                    commit_email: commit_email.as_str().trim().to_lowercase(),
+                },
+                None => MailmapEntry {
+                    proper_name,
+                    proper_email: None,
+                    commit_name: None,
+                    commit_email: captures[2].trim().to_lowercase(),
+                },
+            };
+            entries.push(entry);
+        }
+        Self { entries }
+    }
+
+    // The proper name and email of a commit author, entries naming the commit
+    // name win over those matching the email alone
+    pub(super) fn resolve(&self, name: &str, email: &str) -> (String, String) {
+        let email_key = email.to_lowercase();
+        let name_key = name.to_lowercase();
+        let matching = |entry: &&MailmapEntry| entry.commit_email == email_key;
+        let entry = self
+            .entries
+            .iter()
+            .filter(matching)
+            .find(|entry| entry.commit_name.as_deref() == Some(name_key.as_str()))
+            .or_else(|| {
+                self.entries
+                    .iter()
+                    .filter(matching)
+                    .find(|entry| entry.commit_name.is_none())
+            });
+        match entry {
+            Some(entry) => (
+                entry
+                    .proper_name
+                    .clone()
+                    .unwrap_or_else(|| name.to_string()),
+                entry
+                    .proper_email
+                    .clone()
+                    .unwrap_or_else(|| email.to_string()),
+            ),
+            None => (name.to_string(), email.to_string()),
+        }
+    }
+}
+
+// One person behind one or more identities
+pub(super) struct Person {
+    pub name: String,  // The spelling with the most commits
+    pub email: String, // The address with the most commits
+    pub commits: u32,
+    pub account: Option<GitHubUser>, // Of the linked identity with the most commits
+}
+
+impl Person {
+    // The linked GitHub account, or the name alone with id 0
+    pub(super) fn user(&self, contributions: Option<u32>) -> GitHubUser {
+        match &self.account {
+            Some(account) => GitHubUser {
+                contributions,
+                ..account.clone()
+            },
+            None => GitHubUser {
+                login: self.name.clone(),
+                id: 0,
+                avatar_url: String::new(),
+                html_url: String::new(),
+                contributions,
+            },
+        }
+    }
+}
+
+// The account behind a GitHub noreply address, `login@users.noreply.github.com`
+// or, since 2017, `12345+login@users.noreply.github.com`
+pub(super) fn noreply_account(email: &str) -> Option<GitHubUser> {
+    let noreply =
+        Regex::new(r"(?i)^(?:(\d+)\+)?([a-z0-9][a-z0-9-]*)@users\.noreply\.github\.com$").unwrap();
+    let captures = noreply.captures(email.trim())?;
+    let login = captures[2].to_string();
+    let id = captures
+        .get(1)
+        .and_then(|id| id.as_str().parse().ok())
+        .unwrap_or(0);
+    Some(GitHubUser {
+        avatar_url: if id != 0 {
+            format!("https://avatars.githubusercontent.com/u/{}?v=4", id)
+        } else {
+            format!("https://github.com/{}.png", login)
+        },
+        html_url: format!("https://github.com/{}", login),
+        login,
+        id,
+        contributions: None,
+    })
+}
+
+// People from (name, email) identities with their commit counts, most
+// commits first, and the person index of each identity. Identities GitHub
+// linked to the same account are one person whatever their names
+pub(super) fn merge_identities(
+    identities: &HashMap<(String, String), u32>,
+    accounts: &HashMap<(String, String), GitHubUser>,
+) -> (Vec<Person>, HashMap<(String, String), usize>) {
+    let mut keys: Vec<&(String, String)> = identities.keys().collect();
+    keys.sort();
+
+    // Union-find over the identities, linked through shared emails and names
+    let mut parent: Vec<usize> = (0..keys.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    let mut first_seen: HashMap<String, usize> = HashMap::new();
+    for (index, key) in keys.iter().enumerate() {
+        let email = key.1.trim().to_lowercase();
+        let name = normalized_name(&key.0);
+        let links = [
+            (!email.is_empty()).then(|| format!("email:{}", email)),
+            name.map(|name| format!("name:{}", name)),
+            accounts
+                .get(*key)
+                .map(|account| format!("account:{}", account.login.to_lowercase())),
+        ];
+        for link in links.into_iter().flatten() {
+            let other = *first_seen.entry(link).or_insert(index);
+            let (a, b) = (root(&mut parent, index), root(&mut parent, other));
+            if a != b {
+                parent[a] = b;
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for index in 0..keys.len() {
+        groups
+            .entry(root(&mut parent, index))
+            .or_default()
+            .push(index);
+    }
+    let mut grouped: Vec<Vec<usize>> = groups.into_values().collect();
+    grouped.sort();
+
+    // Most used name and email of each group
+    let mut people: Vec<(Person, Vec<usize>)> = grouped
+        .into_iter()
+        .map(|members| {
+            let mut names: HashMap<&str, u32> = HashMap::new();
+            let mut emails: HashMap<&str, u32> = HashMap::new();
+            let mut commits = 0;
+            let mut account: Option<(u32, &GitHubUser)> = None;
+            for &index in &members {
+                let (name, email) = keys[index];
+                let count = identities[keys[index]];
+                *names.entry(name).or_insert(0) += count;
+                *emails.entry(email).or_insert(0) += count;
+                commits += count;
+                if let Some(linked) = accounts.get(keys[index])
+                    && account.is_none_or(|(most, _)| count > most)
+                {
+                    account = Some((count, linked));
+                }
+            }
+            let person = Person {
+                name: most_used(names),
+                email: most_used(emails),
+                commits,
+                account: account.map(|(_, account)| account.clone()),
+            };
+            (person, members)
+        })
+        .collect();
+    people.sort_by(|(a, _), (b, _)| b.commits.cmp(&a.commits).then_with(|| a.name.cmp(&b.name)));
+
+    let mut person_of = HashMap::new();
+    for (position, (_, members)) in people.iter().enumerate() {
+        for &index in members {
+            person_of.insert(keys[index].clone(), position);
+        }
+    }
+    (
+        people.into_iter().map(|(person, _)| person).collect(),
+        person_of,
+    )
+}
+
+// The value with the most commits, ties to the first in order
+fn most_used(counts: HashMap<&str, u32>) -> String {
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(value, _)| value.to_string())
+        .unwrap_or_default()
+}
+
+// Lowercase with single spaces, None for names too generic to link people
+fn normalized_name(name: &str) -> Option<String> {
+    let name = name
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    (name.chars().count() >= 3 && !GENERIC_NAMES.contains(&name.as_str())).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(name: &str, email: &str) -> (String, String) {
+        (name.to_string(), email.to_string())
+    }
+
+    #[test]
+    fn each_mailmap_form_is_parsed() {
+        let mailmap = Mailmap::parse(
+            "# Old Name <ignored@example.com>\n\
+             Proper Name <one@example.com>\n\
+             <proper-two@example.com> <two@example.com>\n\
+             Proper Three <proper-three@example.com> <three@example.com>\n\
+             Proper Four <proper-four@example.com> Commit Four <four@example.com>\n",
+        );
+
+        assert_eq!(
+            mailmap.resolve("old", "ONE@example.com"),
+            identity("Proper Name", "ONE@example.com")
+        );
+        assert_eq!(
+            mailmap.resolve("old", "two@example.com"),
+            identity("old", "proper-two@example.com")
+        );
+        assert_eq!(
+            mailmap.resolve("old", "three@example.com"),
+            identity("Proper Three", "proper-three@example.com")
+        );
+        assert_eq!(
+            mailmap.resolve("commit four", "four@example.com"),
+            identity("Proper Four", "proper-four@example.com")
+        );
+        // The last form only applies to the commit name it gives
+        assert_eq!(
+            mailmap.resolve("Someone Else", "four@example.com"),
+            identity("Someone Else", "four@example.com")
+        );
+        assert_eq!(
+            mailmap.resolve("Old Name", "ignored@example.com"),
+            identity("Old Name", "ignored@example.com")
+        );
+    }
+
+    #[test]
+    fn entries_naming_the_commit_name_win() {
+        // The email-only entry comes first, the order of the lines doesn't matter
+        let mailmap = Mailmap::parse(
+            "Build Bot <shared@example.com>\n\
+             Alice <alice@example.com> alice <shared@example.com>\n",
+        );
+
+        assert_eq!(
+            mailmap.resolve("Alice", "shared@example.com"),
+            identity("Alice", "alice@example.com")
+        );
+        assert_eq!(
+            mailmap.resolve("bob", "shared@example.com"),
+            identity("Build Bot", "shared@example.com")
+        );
+    }
+
+    #[test]
+    fn generic_names_do_not_merge_people() {
+        let identities = HashMap::from([
+            (identity("root", "a@example.com"), 3),
+            (identity("root", "b@example.org"), 2),
+            (identity("GitHub Actions", "c@example.com"), 1),
+            (identity("github  actions", "d@example.org"), 1),
+            (identity("Carol Jones", "carol@example.com"), 4),
+            (identity("carol  jones", "cj@example.org"), 1),
+        ]);
+
+        let (people, person_of) = merge_identities(&identities, &HashMap::new());
+
+        // Only the two spellings of Carol's name are one person
+        assert_eq!(people.len(), 5);
+        assert_eq!(people[0].name, "Carol Jones");
+        assert_eq!(people[0].commits, 5);
+        assert_ne!(
+            person_of[&identity("root", "a@example.com")],
+            person_of[&identity("root", "b@example.org")]
+        );
+    }
+
+    #[test]
+    fn a_shared_email_or_account_merges_people() {
+        let identities = HashMap::from([
+            (identity("Alice Smith", "alice@example.com"), 5),
+            (identity("asmith", "ALICE@example.com"), 2),
+            (identity("Bob", "bob@work.example"), 1),
+            (identity("Robert", "bob@home.example"), 3),
+        ]);
+        let bob = noreply_account("1234+bob@users.noreply.github.com").unwrap();
+        let accounts = HashMap::from([
+            (identity("Bob", "bob@work.example"), bob.clone()),
+            (identity("Robert", "bob@home.example"), bob),
+        ]);
+
+        let (people, person_of) = merge_identities(&identities, &accounts);
+
+        assert_eq!(people.len(), 2);
+        assert_eq!(people[0].name, "Alice Smith");
+        assert_eq!(people[0].email, "alice@example.com");
+        assert_eq!(people[0].commits, 7);
+        assert_eq!(people[1].name, "Robert");
+        assert_eq!(people[1].commits, 4);
+        assert_eq!(people[1].user(None).login, "bob");
+        assert_eq!(person_of[&identity("Bob", "bob@work.example")], 1);
+    }
+}
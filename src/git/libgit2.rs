@@ -0,0 +1,213 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use git2::{FetchOptions, ProxyOptions, RemoteCallbacks, Repository, build::RepoBuilder};
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+use super::{Checkout, CommitRecord, History, TREE_ENTRY_LIMIT, TagDiff};
+use crate::types::FileChange;
+use crate::{cancel, network};
+
+static CA_BUNDLE: Once = Once::new();
+
+// Returns the bytes received. Returning false from the progress callback
+// aborts the transfer on cancellation. Without a configured proxy, libgit2
+// reads http.proxy and the proxy environment variables. The extra headers
+// configured for the host of the clone URL are sent too
+pub(super) fn clone(clone_url: &str, repo_path: &Path) -> Result<u64> {
+    if let Some(bundle) = network::ca_bundle() {
+        CA_BUNDLE.call_once(|| {
+            // SAFETY: runs once, before the first clone, while no other
+            // libgit2 operation is in progress
+            if let Err(e) = unsafe { git2::opts::set_ssl_cert_file(bundle) } {
+                log::warn!("Failed to load CA bundle for git: {}", e);
+            }
+        });
+    }
+    let received_bytes = Cell::new(0);
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.transfer_progress(|progress| {
+        received_bytes.set(progress.received_bytes());
+        !cancel::is_cancelled()
+    });
+    let mut proxy_options = ProxyOptions::new();
+    match network::proxy() {
+        Some(proxy) => proxy_options.url(proxy),
+        None => proxy_options.auto(),
+    };
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(callbacks);
+    fetch_options.proxy_options(proxy_options);
+    let headers = network::extra_headers(clone_url);
+    fetch_options.custom_headers(&headers.iter().map(String::as_str).collect::<Vec<_>>());
+
+    RepoBuilder::new()
+        .fetch_options(fetch_options)
+        .clone(clone_url, repo_path)?;
+    Ok(received_bytes.get() as u64)
+}
+
+pub(super) fn read_history(repo_path: &Path, limit: usize) -> Result<History> {
+    let repo = Repository::open(repo_path)?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(git2::Sort::TIME)?;
+
+    let mut commits = Vec::new();
+    for oid in revwalk.take(limit) {
+        let oid = oid?;
+        let commit = repo.find_commit(oid)?;
+        let author = commit.author();
+
+        let mut tree_entries = Vec::new();
+        if let Ok(tree) = commit.tree() {
+            tree.walk(git2::TreeWalkMode::PreOrder, |_root, entry| {
+                if let Some(name) = entry.name() {
+                    tree_entries.push(name.to_string());
+                }
+                if tree_entries.len() >= TREE_ENTRY_LIMIT {
+                    git2::TreeWalkResult::Abort
+                } else {
+                    git2::TreeWalkResult::Ok
+                }
+            })?;
+        }
+
+        commits.push(CommitRecord {
+            sha: oid.to_string(),
+            time: DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_else(Utc::now),
+            author_name: author.name().map(str::to_string),
+            author_email: author.email().map(str::to_string),
+            message: commit.message().unwrap_or("").to_string(),
+            tree_entries,
+        });
+    }
+
+    let branch_count = repo.branches(Some(git2::BranchType::Local))?.count() as u32;
+    let tag_count = repo.tag_names(None)?.len() as u32;
+    let head_sha = repo
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .map(|oid| oid.to_string());
+
+    Ok(History {
+        commits,
+        branch_count,
+        tag_count,
+        head_sha,
+    })
+}
+
+pub(super) fn read_checkout(repo_path: &Path) -> Result<Checkout> {
+    let repo = Repository::open(repo_path)?;
+    let remotes = repo.remotes()?;
+    let remote_name = remotes
+        .iter()
+        .flatten()
+        .find(|name| *name == "origin")
+        .or_else(|| remotes.iter().flatten().next());
+    let remote_url = remote_name
+        .and_then(|name| repo.find_remote(name).ok())
+        .and_then(|remote| remote.url().map(str::to_string));
+    let branch = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(str::to_string));
+    Ok(Checkout { remote_url, branch })
+}
+
+pub(super) fn diff_tags(repo_path: &Path, from_tag: &str, to_tag: &str) -> Result<TagDiff> {
+    let repo = Repository::open(repo_path)?;
+    let from = resolve_tag(&repo, from_tag)?;
+    let to = resolve_tag(&repo, to_tag)?;
+    let from_tree = from.tree()?;
+    let to_tree = to.tree()?;
+
+    let mut diff = repo.diff_tree_to_tree(Some(&from_tree), Some(&to_tree), None)?;
+    diff.find_similar(None)?;
+    let stats = diff.stats()?;
+
+    let mut files = Vec::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let (additions, deletions) = match git2::Patch::from_diff(&diff, index)? {
+            Some(patch) => {
+                let (_, additions, deletions) = patch.line_stats()?;
+                (additions as u32, deletions as u32)
+            }
+            None => (0, 0),
+        };
+        let status = match delta.status() {
+            git2::Delta::Added => "added",
+            git2::Delta::Deleted => "deleted",
+            git2::Delta::Renamed => "renamed",
+            _ => "modified",
+        };
+        let new_path = delta.new_file().path().map(|p| p.to_path_buf());
+        let old_path = delta.old_file().path().map(|p| p.to_path_buf());
+
+        files.push(FileChange {
+            path: new_path.clone().or(old_path.clone()).unwrap_or_default(),
+            status: status.to_string(),
+            old_path: old_path.filter(|old| status == "renamed" && Some(old) != new_path.as_ref()),
+            additions,
+            deletions,
+        });
+    }
+
+    // Commits reachable from the newer tag but not the older one
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(to.id())?;
+    revwalk.hide(from.id())?;
+    let mut commit_authors = Vec::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let author = commit.author();
+        commit_authors.push((
+            author.name().unwrap_or("Unknown").to_string(),
+            author.email().unwrap_or("").to_string(),
+        ));
+    }
+
+    let mut from_directories = HashSet::new();
+    from_tree.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() == Some(git2::ObjectType::Tree)
+            && let Some(name) = entry.name()
+        {
+            from_directories.insert(PathBuf::from(root).join(name));
+        }
+        git2::TreeWalkResult::Ok
+    })?;
+
+    Ok(TagDiff {
+        from_sha: from.id().to_string(),
+        to_sha: to.id().to_string(),
+        files,
+        files_changed: stats.files_changed() as u32,
+        insertions: stats.insertions() as u32,
+        deletions: stats.deletions() as u32,
+        commit_authors,
+        from_directories,
+    })
+}
+
+pub(super) fn file_at_tag(repo_path: &Path, tag: &str, path: &Path) -> Option<String> {
+    let repo = Repository::open(repo_path).ok()?;
+    let commit = resolve_tag(&repo, tag).ok()?;
+    let entry = commit.tree().ok()?.get_path(path).ok()?;
+    let blob = entry.to_object(&repo).ok()?.peel_to_blob().ok()?;
+    String::from_utf8(blob.content().to_vec()).ok()
+}
+
+// Annotated and lightweight tags both resolve to their commit
+fn resolve_tag<'r>(repo: &'r Repository, tag: &str) -> Result<git2::Commit<'r>> {
+    let object = repo
+        .revparse_single(&format!("refs/tags/{}", tag))
+        .or_else(|_| repo.revparse_single(tag))
+        .map_err(|_| anyhow::anyhow!("Tag '{}' not found", tag))?;
+    Ok(object.peel_to_commit()?)
+}
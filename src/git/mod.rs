@@ -0,0 +1,565 @@
+// Git access goes through one of two backends: libgit2 (git2) or gitoxide
+// (gix). Each is behind its cargo feature; gix alone allows fully static
+// musl builds. The backend is chosen at runtime with `[git] backend` in
+// analyzer.toml, and everything beyond reading the repository lives here.
+
+#[cfg(feature = "gix")]
+mod gitoxide;
+mod identity;
+#[cfg(feature = "git2")]
+mod libgit2;
+mod topics;
+
+#[cfg(not(any(feature = "git2", feature = "gix")))]
+compile_error!("enable the git2 or gix feature to select a git backend");
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Utc};
+use log::{info, warn};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::types::{
+    CommitActivity, DomainShare, FileChange, GitAnalysis, GitHubCommit, GitHubUser, NewModule,
+    ReleaseContributor, ReleaseDiff,
+};
+use crate::{cancel, telemetry};
+
+use self::identity::{Mailmap, Person};
+
+// Commits read from history, newest first
+const HISTORY_LIMIT: usize = 1000;
+
+// Recent commits kept in the analysis
+const RECENT_COMMITS: usize = 50;
+
+// Tree entries read per commit for the file activity counts
+const TREE_ENTRY_LIMIT: usize = 101;
+
+// Contributors whose commit email domains are kept, by commit count
+const TOP_CONTRIBUTORS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GitBackend {
+    #[cfg(feature = "git2")]
+    Git2,
+    #[cfg(feature = "gix")]
+    Gix,
+}
+
+impl GitBackend {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "git2" | "libgit2" => {
+                #[cfg(feature = "git2")]
+                return Ok(Self::Git2);
+                #[cfg(not(feature = "git2"))]
+                anyhow::bail!(
+                    "The git2 backend is not part of this build, rebuild with --features git2"
+                );
+            }
+            "gix" | "gitoxide" => {
+                #[cfg(feature = "gix")]
+                return Ok(Self::Gix);
+                #[cfg(not(feature = "gix"))]
+                anyhow::bail!(
+                    "The gix backend is not part of this build, rebuild with --features gix"
+                );
+            }
+            _ => anyhow::bail!("Unknown git backend '{}', expected git2 or gix", name),
+        }
+    }
+}
+
+// libgit2 when it's compiled in, since it's the reference implementation
+impl Default for GitBackend {
+    fn default() -> Self {
+        #[cfg(feature = "git2")]
+        return Self::Git2;
+        #[cfg(not(feature = "git2"))]
+        return Self::Gix;
+    }
+}
+
+// `[git]` in analyzer.toml
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GitConfig {
+    pub backend: Option<String>,
+}
+
+// Backend from `[git] backend`, the default one when not set
+pub fn configured_backend(config: &GitConfig) -> Result<GitBackend> {
+    match &config.backend {
+        Some(name) => GitBackend::parse(name),
+        None => Ok(GitBackend::default()),
+    }
+}
+
+// A commit as read by either backend
+struct CommitRecord {
+    sha: String,
+    time: DateTime<Utc>,
+    author_name: Option<String>,
+    author_email: Option<String>,
+    message: String,
+    tree_entries: Vec<String>, // Names of the first TREE_ENTRY_LIMIT entries, pre-order
+}
+
+struct History {
+    commits: Vec<CommitRecord>, // Newest first
+    branch_count: u32,
+    tag_count: u32,
+    head_sha: Option<String>,
+}
+
+// Where a local checkout came from, read instead of the forge's metadata
+// when analyzing it offline
+pub struct Checkout {
+    pub remote_url: Option<String>, // Of origin, else of the first remote
+    pub branch: Option<String>,     // None on a detached HEAD
+}
+
+// Raw comparison of two tags, before new modules are derived
+struct TagDiff {
+    from_sha: String,
+    to_sha: String,
+    files: Vec<FileChange>,
+    files_changed: u32,
+    insertions: u32,
+    deletions: u32,
+    commit_authors: Vec<(String, String)>, // (name, email) per commit in the range
+    from_directories: HashSet<PathBuf>,    // Every directory of the older tag's tree
+}
+
+/// Git repository manager for cloning and analyzing repositories
+pub struct GitManager {
+    work_dir: PathBuf,
+    backend: GitBackend,
+}
+
+impl GitManager {
+    pub fn new(work_dir: Option<PathBuf>) -> Self {
+        let work_dir = work_dir.unwrap_or_else(crate::paths::work_dir);
+
+        // Create work directory if it doesn't exist
+        if !work_dir.exists() {
+            std::fs::create_dir_all(&work_dir).unwrap_or_else(|e| {
+                warn!("Failed to create work directory: {}", e);
+            });
+        }
+
+        Self {
+            work_dir,
+            backend: GitBackend::default(),
+        }
+    }
+
+    pub fn with_backend(mut self, backend: GitBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn work_dir(&self) -> &Path {
+        &self.work_dir
+    }
+
+    pub async fn clone_or_update_repository(
+        &self,
+        clone_url: &str,
+        repo_name: &str,
+    ) -> Result<PathBuf> {
+        let repo_path = self.work_dir.join(repo_name);
+
+        // Remove existing directory if it exists
+        if repo_path.exists() {
+            info!("Removing existing repository directory: {:?}", repo_path);
+            fs::remove_dir_all(&repo_path)?;
+        }
+
+        info!("Cloning repository from {} to {:?}", clone_url, repo_path);
+
+        // Clone the repository, keeping track of the bytes received for telemetry.
+        // Both backends abort the transfer on cancellation
+        telemetry::record_request();
+        let result = match self.backend {
+            #[cfg(feature = "git2")]
+            GitBackend::Git2 => libgit2::clone(clone_url, &repo_path),
+            #[cfg(feature = "gix")]
+            GitBackend::Gix => gitoxide::clone(clone_url, &repo_path),
+        };
+        if let Ok(received_bytes) = &result {
+            telemetry::record_download(*received_bytes);
+        }
+        if let Err(e) = result {
+            // Never leave a half-cloned repository behind
+            if repo_path.exists() {
+                fs::remove_dir_all(&repo_path).unwrap_or_else(|e| {
+                    warn!("Failed to remove partial clone {:?}: {}", repo_path, e);
+                });
+            }
+            if cancel::is_cancelled() {
+                anyhow::bail!("Clone cancelled");
+            }
+            anyhow::bail!("Failed to clone repository: {}", e);
+        }
+
+        info!("Successfully cloned repository to {:?}", repo_path);
+        Ok(repo_path)
+    }
+
+    // `accounts` maps commit shas to the GitHub account GitHub linked their
+    // author to, from the commits API
+    pub fn analyze_git_history(
+        &self,
+        repo_path: &Path,
+        accounts: &HashMap<String, GitHubUser>,
+    ) -> Result<GitAnalysis> {
+        let history = match self.backend {
+            #[cfg(feature = "git2")]
+            GitBackend::Git2 => libgit2::read_history(repo_path, HISTORY_LIMIT)?,
+            #[cfg(feature = "gix")]
+            GitBackend::Gix => gitoxide::read_history(repo_path, HISTORY_LIMIT)?,
+        };
+
+        let mut total_commits = 0;
+        let mailmap = Mailmap::load(repo_path);
+        let mut identities: HashMap<(String, String), u32> = HashMap::new();
+        let mut raw_identities: HashSet<(String, String)> = HashSet::new();
+        let mut identity_accounts: HashMap<(String, String), GitHubUser> = HashMap::new();
+        let mut recent_identities: Vec<Option<(String, String)>> = Vec::new();
+        let mut commits_by_domain: BTreeMap<String, BTreeMap<String, u32>> = BTreeMap::new();
+        let mut subjects: Vec<(String, String)> = Vec::new(); // (quarter, subject)
+        let mut recent_commits = Vec::new();
+        let mut commit_frequency: HashMap<String, u32> = HashMap::new();
+        let mut weekly_commit_frequency: HashMap<String, u32> = HashMap::new();
+        let mut daily_commit_frequency: HashMap<String, u32> = HashMap::new();
+        let mut activity = CommitActivity::default();
+        let now = Utc::now();
+        let mut file_modifications: HashMap<String, u32> = HashMap::new();
+        let mut first_commit_date: Option<DateTime<Utc>> = None;
+        let mut last_commit_date: Option<DateTime<Utc>> = None;
+
+        for commit in history.commits {
+            total_commits += 1;
+            let commit_time = commit.time;
+
+            if first_commit_date.is_none() {
+                first_commit_date = Some(commit_time);
+            }
+            last_commit_date = Some(commit_time);
+
+            // Track commit frequency by month
+            let month_key = commit_time.format("%Y-%m").to_string();
+            *commit_frequency.entry(month_key).or_insert(0) += 1;
+            let week_key = commit_time.format("%G-W%V").to_string();
+            *weekly_commit_frequency.entry(week_key).or_insert(0) += 1;
+            let day_key = commit_time.format("%Y-%m-%d").to_string();
+            *daily_commit_frequency.entry(day_key).or_insert(0) += 1;
+            let quarter = format!("{}-Q{}", commit_time.year(), commit_time.month0() / 3 + 1);
+            if let Some(subject) = commit.message.lines().next() {
+                subjects.push((quarter.clone(), subject.trim().to_string()));
+            }
+
+            // Rolling activity windows
+            let age = now - commit_time;
+            if age <= Duration::days(30) {
+                activity.commits_last_30_days += 1;
+            }
+            if age <= Duration::days(90) {
+                activity.commits_last_90_days += 1;
+            } else if age <= Duration::days(180) {
+                activity.commits_previous_90_days += 1;
+            }
+
+            // Track contributors under their .mailmap identity
+            let identity = match (&commit.author_name, &commit.author_email) {
+                (Some(name), Some(email)) => {
+                    raw_identities.insert((name.clone(), email.clone()));
+                    Some(mailmap.resolve(name, email))
+                }
+                _ => None,
+            };
+            if let Some((name, email)) = &identity {
+                *identities.entry((name.clone(), email.clone())).or_insert(0) += 1;
+                // Linked by GitHub or read from a noreply address, the older
+                // form of which carries no account id
+                if let Some(account) = accounts
+                    .get(&commit.sha)
+                    .cloned()
+                    .or_else(|| identity::noreply_account(email))
+                {
+                    let linked = identity_accounts
+                        .entry((name.clone(), email.clone()))
+                        .or_insert_with(|| account.clone());
+                    if linked.id == 0 {
+                        *linked = account;
+                    }
+                }
+                // Only the domain is kept, addresses stay out of the analysis
+                if let Some((_, domain)) = email.rsplit_once('@') {
+                    *commits_by_domain
+                        .entry(quarter)
+                        .or_default()
+                        .entry(domain.to_lowercase())
+                        .or_insert(0) += 1;
+                }
+            }
+
+            // Track file modifications (simplified)
+            for name in &commit.tree_entries {
+                *file_modifications.entry(name.clone()).or_insert(0) += 1;
+            }
+
+            // Store recent commits (first 50)
+            if recent_commits.len() < RECENT_COMMITS {
+                let git_commit = GitHubCommit {
+                    sha: commit.sha,
+                    message: commit.message,
+                    author: GitHubUser {
+                        login: commit.author_name.unwrap_or_else(|| "Unknown".to_string()),
+                        id: 0,
+                        avatar_url: String::new(),
+                        html_url: String::new(),
+                        contributions: None,
+                    },
+                    date: commit_time,
+                    additions: 0, // Diff stats per commit are not computed
+                    deletions: 0,
+                    files_changed: 0,
+                };
+                recent_commits.push(git_commit);
+                recent_identities.push(identity);
+            }
+        }
+
+        // One person per set of identities, recent commits under their
+        // account or name
+        let (people, person_of) = identity::merge_identities(&identities, &identity_accounts);
+        for (commit, identity) in recent_commits.iter_mut().zip(&recent_identities) {
+            if let Some(&person) = identity.as_ref().and_then(|key| person_of.get(key)) {
+                commit.author = people[person].user(None);
+            }
+        }
+
+        activity.trend = commit_trend(
+            activity.commits_last_90_days,
+            activity.commits_previous_90_days,
+        )
+        .to_string();
+
+        // Get most active files
+        let mut most_active_files: Vec<_> = file_modifications.into_iter().collect();
+        most_active_files.sort_by_key(|f| std::cmp::Reverse(f.1));
+        most_active_files.truncate(20);
+
+        let git_analysis = GitAnalysis {
+            total_commits,
+            contributors: Vec::new(), // GitHub's list, set by the caller
+            history_contributors: people
+                .iter()
+                .map(|person| person.user(Some(person.commits)))
+                .collect(),
+            merged_identities: (raw_identities.len() - people.len()) as u32,
+            top_contributor_domains: top_contributor_domains(&people),
+            commits_by_domain,
+            recent_commits,
+            commit_frequency,
+            weekly_commit_frequency,
+            daily_commit_frequency,
+            activity,
+            commit_topics: topics::commit_topics(&subjects),
+            most_active_files,
+            branch_count: history.branch_count,
+            tag_count: history.tag_count,
+            first_commit_date,
+            last_commit_date,
+            head_sha: history.head_sha,
+        };
+
+        Ok(git_analysis)
+    }
+
+    // Files, line counts, authors and new directories between two tags.
+    // Dependency changes and the AI summary are filled in by the caller
+    pub fn diff_tags(&self, repo_path: &Path, from_tag: &str, to_tag: &str) -> Result<ReleaseDiff> {
+        let diff = match self.backend {
+            #[cfg(feature = "git2")]
+            GitBackend::Git2 => libgit2::diff_tags(repo_path, from_tag, to_tag)?,
+            #[cfg(feature = "gix")]
+            GitBackend::Gix => gitoxide::diff_tags(repo_path, from_tag, to_tag)?,
+        };
+        let files = diff.files;
+
+        let commit_count = diff.commit_authors.len() as u32;
+        let mailmap = Mailmap::load(repo_path);
+        let mut identities: HashMap<(String, String), u32> = HashMap::new();
+        for (name, email) in &diff.commit_authors {
+            *identities.entry(mailmap.resolve(name, email)).or_insert(0) += 1;
+        }
+        // Already ordered by commits
+        let contributors: Vec<ReleaseContributor> =
+            identity::merge_identities(&identities, &HashMap::new())
+                .0
+                .into_iter()
+                .map(|person| ReleaseContributor {
+                    name: person.name,
+                    email: person.email,
+                    commits: person.commits,
+                })
+                .collect();
+
+        // Added files are grouped under the shallowest directory missing from the older tag
+        let mut modules: HashMap<PathBuf, NewModule> = HashMap::new();
+        for file in files.iter().filter(|file| file.status == "added") {
+            let mut ancestors: Vec<&Path> = file.path.ancestors().skip(1).collect();
+            ancestors.reverse();
+            let Some(root) = ancestors
+                .into_iter()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .find(|dir| !diff.from_directories.contains(*dir))
+            else {
+                continue;
+            };
+            let module = modules
+                .entry(root.to_path_buf())
+                .or_insert_with(|| NewModule {
+                    path: root.to_path_buf(),
+                    files: 0,
+                    lines_added: 0,
+                });
+            module.files += 1;
+            module.lines_added += file.additions;
+        }
+        let mut new_modules: Vec<NewModule> = modules.into_values().collect();
+        new_modules.sort_by_key(|module| std::cmp::Reverse(module.lines_added));
+
+        Ok(ReleaseDiff {
+            url: String::new(),
+            from_tag: from_tag.to_string(),
+            to_tag: to_tag.to_string(),
+            from_sha: diff.from_sha,
+            to_sha: diff.to_sha,
+            commit_count,
+            files_changed: diff.files_changed,
+            insertions: diff.insertions,
+            deletions: diff.deletions,
+            loc_delta: diff.insertions as i64 - diff.deletions as i64,
+            files,
+            contributors,
+            dependency_changes: Vec::new(),
+            new_modules,
+            ai_summary: None,
+        })
+    }
+
+    pub fn read_checkout(&self, repo_path: &Path) -> Result<Checkout> {
+        match self.backend {
+            #[cfg(feature = "git2")]
+            GitBackend::Git2 => libgit2::read_checkout(repo_path),
+            #[cfg(feature = "gix")]
+            GitBackend::Gix => gitoxide::read_checkout(repo_path),
+        }
+    }
+
+    // Content of a file as of a tag, None when it doesn't exist there or isn't UTF-8
+    pub fn file_at_tag(&self, repo_path: &Path, tag: &str, path: &Path) -> Option<String> {
+        match self.backend {
+            #[cfg(feature = "git2")]
+            GitBackend::Git2 => libgit2::file_at_tag(repo_path, tag, path),
+            #[cfg(feature = "gix")]
+            GitBackend::Gix => gitoxide::file_at_tag(repo_path, tag, path),
+        }
+    }
+}
+
+// Last 90 days against the 90 before them, with a 25% band counted as stable
+// Authors left unlinked whose name is the login of one of GitHub's
+// contributors, as for people committing under their handle. Needs
+// `contributors` set; people found to share an account are merged
+pub fn link_contributor_logins(git_analysis: &mut GitAnalysis) {
+    let GitAnalysis {
+        contributors,
+        history_contributors,
+        recent_commits,
+        merged_identities,
+        ..
+    } = git_analysis;
+    let users = history_contributors
+        .iter_mut()
+        .chain(recent_commits.iter_mut().map(|commit| &mut commit.author));
+    for user in users.filter(|user| user.id == 0) {
+        if let Some(account) = contributors
+            .iter()
+            .find(|c| c.id != 0 && c.login.eq_ignore_ascii_case(&user.login))
+        {
+            *user = GitHubUser {
+                contributions: user.contributions,
+                ..account.clone()
+            };
+        }
+    }
+
+    let mut people: Vec<GitHubUser> = Vec::new();
+    for user in history_contributors.drain(..) {
+        match people.iter_mut().find(|p| p.id != 0 && p.id == user.id) {
+            Some(person) => {
+                person.contributions =
+                    Some(person.contributions.unwrap_or(0) + user.contributions.unwrap_or(0));
+                *merged_identities += 1;
+            }
+            None => people.push(user),
+        }
+    }
+    people.sort_by_key(|user| std::cmp::Reverse(user.contributions));
+    *history_contributors = people;
+}
+
+fn commit_trend(recent: u32, previous: u32) -> &'static str {
+    let (recent, previous) = (recent as f64, previous as f64);
+    if recent > previous * 1.25 {
+        "growing"
+    } else if recent < previous * 0.75 {
+        "declining"
+    } else {
+        "stable"
+    }
+}
+
+// Commit email domains of the most active contributors with their share of
+// those contributors' commits, largest first
+fn top_contributor_domains(people: &[Person]) -> Vec<DomainShare> {
+    let top = &people[..people.len().min(TOP_CONTRIBUTORS)];
+    let total: u32 = top.iter().map(|person| person.commits).sum();
+
+    let mut shares: Vec<DomainShare> = Vec::new();
+    for person in top {
+        let Some((_, domain)) = person.email.rsplit_once('@') else {
+            continue;
+        };
+        let domain = domain.to_lowercase();
+        match shares.iter_mut().find(|s| s.domain == domain) {
+            Some(share) => {
+                share.contributors += 1;
+                share.commits += person.commits;
+            }
+            None => shares.push(DomainShare {
+                domain,
+                contributors: 1,
+                commits: person.commits,
+                share: 0.0,
+            }),
+        }
+    }
+    for share in &mut shares {
+        share.share = share.commits as f64 / total.max(1) as f64;
+    }
+    shares.sort_by(|a, b| {
+        b.commits
+            .cmp(&a.commits)
+            .then_with(|| a.domain.cmp(&b.domain))
+    });
+    shares
+}
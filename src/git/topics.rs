@@ -0,0 +1,259 @@
+// What the team worked on per quarter, from the commit subjects. Commits are
+// sorted into themes by their conventional commit type or their words (CI,
+// performance, docs, ...), and the rest of their words are clustered
+// greedily: the keyword most commits share makes a topic of those commits,
+// named after it and their common theme or second keyword ("auth
+// refactoring"), and so on with the commits left
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use regex::Regex;
+
+use crate::types::{CommitTopic, QuarterTopics};
+
+// Quarters summarized, the newest ones
+const TOPIC_QUARTERS: usize = 4;
+const TOPICS_PER_QUARTER: usize = 5;
+// Commits a theme or keyword needs in a quarter to make a topic
+const MIN_TOPIC_COMMITS: u32 = 2;
+
+// Themes with the conventional commit types and the lowercase words that
+// mark them
+const THEMES: &[(&str, &[&str])] = &[
+    (
+        "CI",
+        &[
+            "ci",
+            "workflow",
+            "workflows",
+            "pipeline",
+            "actions",
+            "github-actions",
+        ],
+    ),
+    (
+        "performance",
+        &[
+            "perf",
+            "performance",
+            "optimize",
+            "optimise",
+            "optimization",
+            "faster",
+            "speed",
+            "speedup",
+            "benchmark",
+            "benchmarks",
+        ],
+    ),
+    (
+        "dependencies",
+        &[
+            "deps",
+            "dependency",
+            "dependencies",
+            "bump",
+            "dependabot",
+            "renovate",
+            "upgrade",
+        ],
+    ),
+    ("docs", &["docs", "doc", "documentation", "readme", "typo"]),
+    ("tests", &["test", "tests", "testing", "coverage"]),
+    (
+        "refactoring",
+        &[
+            "refactor",
+            "refactoring",
+            "cleanup",
+            "restructure",
+            "rename",
+        ],
+    ),
+    (
+        "security",
+        &[
+            "security",
+            "cve",
+            "vulnerability",
+            "vulnerabilities",
+            "xss",
+            "csrf",
+        ],
+    ),
+    ("releases", &["release", "changelog"]),
+    ("build", &["build", "packaging", "dockerfile", "makefile"]),
+];
+
+// Words every kind of commit uses
+const GENERIC_WORDS: &[&str] = &[
+    "the", "and", "for", "with", "from", "into", "when", "not", "all", "some", "more", "new",
+    "add", "adds", "added", "adding", "fix", "fixes", "fixed", "fixing", "update", "updates",
+    "updated", "updating", "remove", "removes", "removed", "removing", "change", "changes",
+    "changed", "use", "uses", "using", "make", "makes", "support", "supports", "improve",
+    "improves", "improved", "allow", "allows", "handle", "handles", "revert", "wip", "minor",
+    "feat", "feature", "chore", "style", "bug", "issue", "version", "initial", "instead", "also",
+    "now", "only", "can", "don", "should", "been", "was", "are", "per", "via", "other", "out",
+    "its", "this", "that", "one", "first", "default", "code", "file", "files", "main", "master",
+    "commit", "merge", "branch", "pull", "request", "detect", "detects", "show", "shows",
+    "include", "includes", "check", "checks", "run", "runs", "read", "reads", "keep", "move",
+    "skip", "apply", "extract",
+];
+
+struct Commit<'a> {
+    subject: &'a str,
+    themes: Vec<&'static str>,
+    words: HashSet<String>,
+}
+
+// Topics of the newest quarters from (quarter, subject) pairs, newest commit
+// first
+pub fn commit_topics(subjects: &[(String, String)]) -> Vec<QuarterTopics> {
+    let conventional = Regex::new(r"^(\w+)(?:\(([^)]*)\))?!?:\s*").unwrap();
+    // Ticket tags in brackets and issue references
+    let references = Regex::new(r"^\[[^\]]*\]\s*|\(?\S*#[\w-]+\)?").unwrap();
+    let mut quarters: BTreeMap<&str, Vec<Commit<'_>>> = BTreeMap::new();
+    for (quarter, subject) in subjects {
+        // Merge commits are named after branches and pull requests
+        if subject.starts_with("Merge ") {
+            continue;
+        }
+        quarters
+            .entry(quarter)
+            .or_default()
+            .push(parse(&conventional, &references, subject));
+    }
+
+    quarters
+        .into_iter()
+        .rev()
+        .take(TOPIC_QUARTERS)
+        .map(|(quarter, commits)| QuarterTopics {
+            quarter: quarter.to_string(),
+            commits: commits.len() as u32,
+            topics: quarter_topics(&commits),
+        })
+        .collect()
+}
+
+fn parse<'a>(conventional: &Regex, references: &Regex, subject: &'a str) -> Commit<'a> {
+    let mut words: HashSet<String> = HashSet::new();
+    let stripped = references.replace_all(subject, " ");
+    let mut text = stripped.trim();
+    // feat(auth): ... counts its type and scope as words
+    if let Some(captures) = conventional.captures(text) {
+        words.insert(captures[1].to_lowercase());
+        if let Some(scope) = captures.get(2) {
+            words.extend(tokens(scope.as_str()));
+        }
+        text = &text[captures[0].len()..];
+    }
+    words.extend(tokens(text));
+
+    let themes = THEMES
+        .iter()
+        .filter(|(_, markers)| markers.iter().any(|marker| words.contains(*marker)))
+        .map(|(theme, _)| *theme)
+        .collect();
+    words.retain(|word| {
+        !GENERIC_WORDS.contains(&word.as_str())
+            && !THEMES
+                .iter()
+                .any(|(_, markers)| markers.contains(&word.as_str()))
+    });
+    Commit {
+        subject,
+        themes,
+        words,
+    }
+}
+
+// Lowercase words of three letters or more, without numbers and hashes
+fn tokens(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '-')
+        .map(|word| word.trim_matches('-').to_lowercase())
+        .filter(|word| word.len() >= 3 && word.chars().any(|c| c.is_alphabetic()))
+        .filter(|word| !(word.len() >= 7 && word.chars().all(|c| c.is_ascii_hexdigit())))
+}
+
+fn quarter_topics(commits: &[Commit<'_>]) -> Vec<CommitTopic> {
+    let mut topics = Vec::new();
+
+    for (theme, _) in THEMES {
+        let themed: Vec<&Commit<'_>> = commits
+            .iter()
+            .filter(|commit| commit.themes.contains(theme))
+            .collect();
+        if themed.len() as u32 >= MIN_TOPIC_COMMITS {
+            topics.push(CommitTopic {
+                label: theme.to_string(),
+                commits: themed.len() as u32,
+                example: themed[0].subject.to_string(),
+            });
+        }
+    }
+
+    // The keyword shared by the most commits left takes them
+    let mut left: Vec<&Commit<'_>> = commits.iter().collect();
+    for _ in 0..TOPICS_PER_QUARTER {
+        let mut counts: HashMap<&str, u32> = HashMap::new();
+        for commit in &left {
+            for word in &commit.words {
+                *counts.entry(word.as_str()).or_default() += 1;
+            }
+        }
+        let Some((keyword, count)) = counts
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        else {
+            break;
+        };
+        if count < MIN_TOPIC_COMMITS {
+            break;
+        }
+        let keyword = keyword.to_string();
+        let (cluster, rest): (Vec<&Commit<'_>>, Vec<&Commit<'_>>) = left
+            .into_iter()
+            .partition(|commit| commit.words.contains(&keyword));
+        left = rest;
+        topics.push(CommitTopic {
+            label: cluster_label(&keyword, &cluster),
+            commits: cluster.len() as u32,
+            example: cluster[0].subject.to_string(),
+        });
+    }
+
+    topics.sort_by(|a, b| {
+        b.commits
+            .cmp(&a.commits)
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    topics.truncate(TOPICS_PER_QUARTER);
+    topics
+}
+
+// The keyword with the theme or second keyword most of its commits share
+fn cluster_label(keyword: &str, cluster: &[&Commit<'_>]) -> String {
+    let half = (cluster.len() as u32).div_ceil(2).max(MIN_TOPIC_COMMITS);
+    let mut themes: HashMap<&str, u32> = HashMap::new();
+    let mut words: HashMap<&str, u32> = HashMap::new();
+    for commit in cluster {
+        for theme in &commit.themes {
+            *themes.entry(*theme).or_default() += 1;
+        }
+        for word in commit.words.iter().filter(|word| *word != keyword) {
+            *words.entry(word.as_str()).or_default() += 1;
+        }
+    }
+    let most_shared = |counts: HashMap<&str, u32>| {
+        counts
+            .into_iter()
+            .filter(|(_, count)| *count >= half)
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(name, _)| name.to_string())
+    };
+    match most_shared(themes).or_else(|| most_shared(words)) {
+        Some(second) => format!("{} {}", keyword, second),
+        None => keyword.to_string(),
+    }
+}
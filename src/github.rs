@@ -1,39 +1,306 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
 
 use crate::RepositoryMetadata;
+use crate::analyzers::release_hygiene::{RECENT_RELEASES, is_checksum_file, parse_checksums};
+use crate::cancel;
+use crate::forge::Forge;
+use crate::network::{self, SendLogged};
+use crate::telemetry;
+use crate::types::CheckAnnotation;
+use crate::types::CheckReport;
+use crate::types::ChecksumVerification;
+use crate::types::ForkRelationship;
 use crate::types::GitHubIssue;
 use crate::types::GitHubLicense;
+use crate::types::GitHubMilestone;
+use crate::types::GitHubRateLimit;
 use crate::types::GitHubRelease;
 use crate::types::GitHubUser;
+use crate::types::ProjectBoard;
+use crate::types::PublishedAdvisory;
+use crate::types::RelatedProject;
+use crate::types::ReleaseAsset;
 use anyhow::Result;
-use chrono::Utc;
-use reqwest::Client;
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
 
-use log::{error, info, warn};
+use log::{info, warn};
 
 // GitHub API client
 pub struct GitHubClient {
     client: Client,
     token: Option<String>,
     base_url: String,
+    rate_limit_retries: u32,
+    max_rate_limit_wait: Duration,
+    rate_limits: Mutex<BTreeMap<String, GitHubRateLimit>>, // Latest per resource
+}
+
+// The Checks API accepts at most this many annotations per request
+const ANNOTATIONS_PER_REQUEST: usize = 50;
+
+// Projects v2 boards of a repository with the Status of their first items
+const PROJECT_BOARDS_QUERY: &str = r#"
+query($owner: String!, $name: String!) {
+  repository(owner: $owner, name: $name) {
+    projectsV2(first: 20) {
+      nodes {
+        number
+        title
+        url
+        public
+        closed
+        updatedAt
+        items(first: 100) {
+          totalCount
+          nodes {
+            fieldValueByName(name: "Status") {
+              ... on ProjectV2ItemFieldSingleSelectValue { name }
+            }
+          }
+        }
+      }
+    }
+  }
+}
+"#;
+
+// Larger checksum files are not checksum files, and larger artifacts are not
+// worth downloading to verify one
+const MAX_CHECKSUM_FILE_SIZE: u64 = 1 << 20;
+const MAX_VERIFIED_ASSET_SIZE: u64 = 50 << 20;
+
+// Name shown for the published check run
+const CHECK_RUN_NAME: &str = "ai-repo-analyzer";
+
+// Largest page the REST API serves
+const PAGE_SIZE: usize = 100;
+
+// First wait before retrying a rate limited request that names no reset
+// time, doubled on every retry
+const BACKOFF_BASE: Duration = Duration::from_secs(1);
+
+// `[github]` in analyzer.toml. Items fetched from the paginated lists, every
+// 100 items cost a request out of the 60 an hour allowed without a token
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GitHubConfig {
+    pub max_contributors: usize,
+    pub max_issues: usize,
+    pub max_releases: usize,
+    pub rate_limit_retries: u32,
+    pub max_rate_limit_wait: u64, // Seconds, longer waits for a reset give up
+}
+
+impl Default for GitHubConfig {
+    fn default() -> Self {
+        Self {
+            max_contributors: 500,
+            max_issues: 100,
+            max_releases: 100,
+            rate_limit_retries: 3,
+            max_rate_limit_wait: 60,
+        }
+    }
+}
+
+// Claims of the JWT a GitHub App signs to request installation tokens
+#[derive(Serialize)]
+struct AppClaims {
+    iat: i64,
+    exp: i64,
+    iss: String,
 }
 
 impl GitHubClient {
     pub fn new(token: Option<String>) -> Self {
         Self {
-            client: Client::new(),
+            client: network::http_client(),
             token,
             base_url: "https://api.github.com".to_string(),
+            rate_limit_retries: GitHubConfig::default().rate_limit_retries,
+            max_rate_limit_wait: Duration::from_secs(GitHubConfig::default().max_rate_limit_wait),
+            rate_limits: Mutex::new(BTreeMap::new()),
         }
     }
 
-    fn get_auth_headers(&self) -> reqwest::header::HeaderMap {
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            reqwest::header::USER_AGENT,
-            reqwest::header::HeaderValue::from_static("ai-repo-analyzer-rs/1.0"),
+    pub fn with_config(mut self, config: &GitHubConfig) -> Self {
+        self.rate_limit_retries = config.rate_limit_retries;
+        self.max_rate_limit_wait = Duration::from_secs(config.max_rate_limit_wait);
+        self
+    }
+
+    // Quota left as of the latest response of each resource (core, search,
+    // graphql) used so far
+    pub fn rate_limits(&self) -> Vec<GitHubRateLimit> {
+        self.rate_limits.lock().unwrap().values().cloned().collect()
+    }
+
+    // Every API request goes through here: the quota is recorded from the
+    // X-RateLimit-* headers, and rate limited requests are retried after
+    // the reset time or Retry-After GitHub gives, else with exponential
+    // backoff. A limit that resets too late is returned as is, for the
+    // caller to handle like any failed request
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let mut request = request;
+        let mut attempt = 0;
+        loop {
+            let retry = request.try_clone();
+            let response = request.send_logged().await?;
+            let quota = rate_limit(response.headers());
+            if let Some(quota) = &quota {
+                self.rate_limits
+                    .lock()
+                    .unwrap()
+                    .insert(quota.resource.clone(), quota.clone());
+            }
+
+            let exhausted = quota.as_ref().is_some_and(|quota| quota.remaining == 0);
+            let retry_after = retry_after(response.headers());
+            let limited = response.status() == StatusCode::TOO_MANY_REQUESTS
+                || (response.status() == StatusCode::FORBIDDEN
+                    && (exhausted || retry_after.is_some()));
+            let Some(retry) = retry.filter(|_| limited) else {
+                return Ok(response);
+            };
+
+            let wait = retry_after
+                .or_else(|| {
+                    let quota = quota.as_ref().filter(|_| exhausted)?;
+                    let until_reset = (quota.reset_at - Utc::now()).to_std().unwrap_or_default();
+                    Some(until_reset + Duration::from_secs(1))
+                })
+                .unwrap_or(BACKOFF_BASE * 2u32.pow(attempt));
+            if attempt >= self.rate_limit_retries || wait > self.max_rate_limit_wait {
+                match &quota {
+                    Some(quota) => warn!(
+                        "GitHub API rate limit exceeded ({} requests per hour), resets at {}",
+                        quota.limit, quota.reset_at
+                    ),
+                    None => warn!("GitHub API rate limit exceeded"),
+                }
+                return Ok(response);
+            }
+            attempt += 1;
+            warn!(
+                "GitHub API rate limit reached, retrying in {} s ({}/{})",
+                wait.as_secs(),
+                attempt,
+                self.rate_limit_retries
+            );
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = cancel::cancelled() => return Ok(response),
+            }
+            request = retry;
+        }
+    }
+
+    // Client for writing results back to the repository: the GitHub App from
+    // GITHUB_APP_ID and GITHUB_APP_PRIVATE_KEY when set, otherwise the token
+    pub async fn for_publishing(token: Option<String>, owner: &str, repo: &str) -> Result<Self> {
+        let (Ok(app_id), Ok(private_key)) = (
+            std::env::var("GITHUB_APP_ID"),
+            std::env::var("GITHUB_APP_PRIVATE_KEY"),
+        ) else {
+            return Ok(Self::new(token));
+        };
+
+        // The key is given inline or as a path to the PEM file
+        let private_key = if private_key.trim_start().starts_with("-----BEGIN") {
+            private_key
+        } else {
+            std::fs::read_to_string(&private_key)?
+        };
+        Self::from_app(&app_id, &private_key, owner, repo).await
+    }
+
+    // Client authenticated as the GitHub App installation on the repository.
+    // Check runs can only be created by Apps or by the Actions token
+    pub async fn from_app(
+        app_id: &str,
+        private_key_pem: &str,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Self> {
+        // Backdated against clock drift, GitHub rejects expiries over 10 minutes
+        let now = Utc::now().timestamp();
+        let claims = AppClaims {
+            iat: now - 60,
+            exp: now + 540,
+            iss: app_id.to_string(),
+        };
+        let jwt = jsonwebtoken::encode(
+            &jsonwebtoken::Header::new(jsonwebtoken::Algorithm::RS256),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_rsa_pem(private_key_pem.as_bytes())?,
+        )?;
+        let app_client = Self::new(Some(jwt));
+
+        let url = format!(
+            "{}/repos/{}/{}/installation",
+            app_client.base_url, owner, repo
+        );
+        let response = app_client
+            .send(
+                app_client
+                    .client
+                    .get(&url)
+                    .headers(app_client.get_auth_headers()),
+            )
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "GitHub App is not installed on {}/{}: {}",
+                owner,
+                repo,
+                response.status()
+            );
+        }
+        let installation: serde_json::Value = telemetry::json(response).await?;
+        let Some(installation_id) = installation["id"].as_u64() else {
+            anyhow::bail!("Installation response has no id");
+        };
+
+        let url = format!(
+            "{}/app/installations/{}/access_tokens",
+            app_client.base_url, installation_id
+        );
+        let response = app_client
+            .send(
+                app_client
+                    .client
+                    .post(&url)
+                    .headers(app_client.get_auth_headers()),
+            )
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Failed to create installation token: {} - {}",
+                response.status(),
+                telemetry::text(response).await?
+            );
+        }
+        let token: serde_json::Value = telemetry::json(response).await?;
+        let Some(token) = token["token"].as_str() else {
+            anyhow::bail!("Installation token response has no token");
+        };
+
+        info!(
+            "Authenticated as GitHub App installation {}",
+            installation_id
         );
+        Ok(Self::new(Some(token.to_string())))
+    }
 
+    fn get_auth_headers(&self) -> reqwest::header::HeaderMap {
+        // The User-Agent is the client's
+        let mut headers = reqwest::header::HeaderMap::new();
         if let Some(token) = &self.token {
             let auth_value = format!("Bearer {}", token);
             headers.insert(
@@ -54,21 +321,18 @@ impl GitHubClient {
         info!("Fetching repository metadata from: {}", url);
 
         let response = self
-            .client
-            .get(&url)
-            .headers(self.get_auth_headers())
-            .send()
+            .send(self.client.get(&url).headers(self.get_auth_headers()))
             .await?;
 
         if !response.status().is_success() {
             anyhow::bail!(
                 "Failed to fetch repository: {} - {}",
                 response.status(),
-                response.text().await?
+                telemetry::text(response).await?
             );
         }
 
-        let repo_data: serde_json::Value = response.json().await?;
+        let repo_data: serde_json::Value = telemetry::json(response).await?;
 
         // Fetch additional data
         let languages = self.get_languages(owner, repo).await.unwrap_or_default();
@@ -154,23 +418,135 @@ impl GitHubClient {
             )
             .unwrap()
             .with_timezone(&Utc),
+            parent: repo_data["parent"]["full_name"]
+                .as_str()
+                .map(|s| s.to_string()),
+            mirror_url: repo_data["mirror_url"].as_str().map(|s| s.to_string()),
         };
 
         Ok(metadata)
     }
 
+    // Upstream details and ahead/behind counts of the fork's default branch
+    pub async fn get_fork_relationship(
+        &self,
+        metadata: &RepositoryMetadata,
+    ) -> Result<Option<ForkRelationship>> {
+        let Some(parent_full_name) = metadata.parent.as_deref().filter(|_| metadata.fork) else {
+            return Ok(None);
+        };
+
+        let url = format!("{}/repos/{}", self.base_url, parent_full_name);
+        info!("Fetching upstream repository: {}", url);
+        let response = self
+            .send(self.client.get(&url).headers(self.get_auth_headers()))
+            .await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to fetch upstream repository: {}", response.status());
+        }
+        let parent: serde_json::Value = telemetry::json(response).await?;
+        let parent_default_branch = parent["default_branch"]
+            .as_str()
+            .unwrap_or("main")
+            .to_string();
+
+        // Three-dot compare from the upstream default branch to the fork's
+        let url = format!(
+            "{}/repos/{}/compare/{}...{}:{}",
+            self.base_url,
+            parent_full_name,
+            parent_default_branch,
+            metadata.owner.login,
+            metadata.default_branch
+        );
+        let response = self
+            .send(
+                self.client
+                    .get(&url)
+                    .headers(self.get_auth_headers())
+                    .query(&[("per_page", "1")]),
+            )
+            .await?;
+
+        let comparison: Option<serde_json::Value> = if response.status().is_success() {
+            Some(telemetry::json(response).await?)
+        } else {
+            warn!(
+                "Failed to compare fork with upstream: {}",
+                response.status()
+            );
+            None
+        };
+        let ahead_by = comparison
+            .as_ref()
+            .and_then(|c| c["ahead_by"].as_u64())
+            .map(|n| n as u32);
+        let behind_by = comparison
+            .as_ref()
+            .and_then(|c| c["behind_by"].as_u64())
+            .map(|n| n as u32);
+
+        let recently_pushed = Utc::now() - metadata.pushed_at <= chrono::Duration::days(180);
+        let (assessment, note) = match (ahead_by, behind_by) {
+            (Some(0), Some(0)) => ("in-sync", format!("Identical to {}", parent_full_name)),
+            (Some(0), Some(behind)) => (
+                "stale-mirror",
+                format!(
+                    "No changes of its own and {} commits behind {}",
+                    behind, parent_full_name
+                ),
+            ),
+            (Some(ahead), Some(behind)) if recently_pushed => (
+                "active-hard-fork",
+                format!(
+                    "{} commits ahead and {} behind {}, with recent activity",
+                    ahead, behind, parent_full_name
+                ),
+            ),
+            (Some(ahead), Some(behind)) => (
+                "inactive-fork",
+                format!(
+                    "{} commits ahead and {} behind {}, no pushes in the last 180 days",
+                    ahead, behind, parent_full_name
+                ),
+            ),
+            _ => (
+                "unknown",
+                format!("Fork of {}, divergence unavailable", parent_full_name),
+            ),
+        };
+
+        Ok(Some(ForkRelationship {
+            parent_full_name: parent_full_name.to_string(),
+            parent_html_url: parent["html_url"].as_str().unwrap_or("").to_string(),
+            parent_default_branch,
+            parent_stars: parent["stargazers_count"].as_u64().unwrap_or(0) as u32,
+            parent_archived: parent["archived"].as_bool().unwrap_or(false),
+            parent_pushed_at: parent["pushed_at"]
+                .as_str()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                .map(|dt| dt.with_timezone(&Utc)),
+            ahead_by,
+            behind_by,
+            compare_status: comparison
+                .as_ref()
+                .and_then(|c| c["status"].as_str())
+                .map(|s| s.to_string()),
+            assessment: assessment.to_string(),
+            note,
+        }))
+    }
+
     pub async fn get_languages(&self, owner: &str, repo: &str) -> Result<HashMap<String, u64>> {
         let url = format!("{}/repos/{}/{}/languages", self.base_url, owner, repo);
 
         let response = self
-            .client
-            .get(&url)
-            .headers(self.get_auth_headers())
-            .send()
+            .send(self.client.get(&url).headers(self.get_auth_headers()))
             .await?;
 
         if response.status().is_success() {
-            let languages: HashMap<String, u64> = response.json().await?;
+            let languages: HashMap<String, u64> = telemetry::json(response).await?;
             Ok(languages)
         } else {
             Ok(HashMap::new())
@@ -181,14 +557,11 @@ impl GitHubClient {
         let url = format!("{}/repos/{}/{}/topics", self.base_url, owner, repo);
 
         let response = self
-            .client
-            .get(&url)
-            .headers(self.get_auth_headers())
-            .send()
+            .send(self.client.get(&url).headers(self.get_auth_headers()))
             .await?;
 
         if response.status().is_success() {
-            let data: serde_json::Value = response.json().await?;
+            let data: serde_json::Value = telemetry::json(response).await?;
             let topics = data["names"]
                 .as_array()
                 .unwrap_or(&Vec::new())
@@ -202,34 +575,133 @@ impl GitHubClient {
         }
     }
 
-    pub async fn get_contributors(&self, owner: &str, repo: &str) -> Result<Vec<GitHubUser>> {
-        let url = format!("{}/repos/{}/{}/contributors", self.base_url, owner, repo);
+    // Replaces all topics of the repository
+    pub async fn replace_topics(&self, owner: &str, repo: &str, topics: &[String]) -> Result<()> {
+        let url = format!("{}/repos/{}/{}/topics", self.base_url, owner, repo);
+        let response = self
+            .send(
+                self.client
+                    .put(&url)
+                    .headers(self.get_auth_headers())
+                    .json(&serde_json::json!({ "names": topics })),
+            )
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Failed to update the topics of {}/{}: {} - {}{}",
+                owner,
+                repo,
+                response.status(),
+                permission_hint(response.status()),
+                telemetry::text(response).await?
+            );
+        }
+        Ok(())
+    }
 
+    pub async fn update_description(
+        &self,
+        owner: &str,
+        repo: &str,
+        description: &str,
+    ) -> Result<()> {
+        let url = format!("{}/repos/{}/{}", self.base_url, owner, repo);
         let response = self
-            .client
-            .get(&url)
-            .headers(self.get_auth_headers())
-            .send()
+            .send(
+                self.client
+                    .patch(&url)
+                    .headers(self.get_auth_headers())
+                    .json(&serde_json::json!({ "description": description })),
+            )
             .await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Failed to update the description of {}/{}: {} - {}{}",
+                owner,
+                repo,
+                response.status(),
+                permission_hint(response.status()),
+                telemetry::text(response).await?
+            );
+        }
+        Ok(())
+    }
 
-        if response.status().is_success() {
-            let contributors: Vec<serde_json::Value> = response.json().await?;
-            let users = contributors
-                .into_iter()
-                .map(|c| GitHubUser {
-                    login: c["login"].as_str().unwrap_or("").to_string(),
-                    id: c["id"].as_u64().unwrap_or(0),
-                    avatar_url: c["avatar_url"].as_str().unwrap_or("").to_string(),
-                    html_url: c["html_url"].as_str().unwrap_or("").to_string(),
-                    contributions: c["contributions"].as_u64().map(|x| x as u32),
-                })
-                .collect();
-            Ok(users)
-        } else {
-            Ok(Vec::new())
+    // Most active first, up to limit
+    pub async fn get_contributors(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubUser>> {
+        let url = format!(
+            "{}/repos/{}/{}/contributors?per_page={}",
+            self.base_url,
+            owner,
+            repo,
+            limit.clamp(1, PAGE_SIZE)
+        );
+
+        let contributors = self.get_pages(&url, limit, |_| true).await?;
+        let users = contributors
+            .into_iter()
+            .map(|c| GitHubUser {
+                login: c["login"].as_str().unwrap_or("").to_string(),
+                id: c["id"].as_u64().unwrap_or(0),
+                avatar_url: c["avatar_url"].as_str().unwrap_or("").to_string(),
+                html_url: c["html_url"].as_str().unwrap_or("").to_string(),
+                contributions: c["contributions"].as_u64().map(|x| x as u32),
+            })
+            .collect();
+        Ok(users)
+    }
+
+    // GitHub account of the author of the latest commits on the default
+    // branch, keyed by sha. Commits whose email matches no account are left out
+    pub async fn get_commit_authors(
+        &self,
+        owner: &str,
+        repo: &str,
+        pages: u32,
+    ) -> Result<HashMap<String, GitHubUser>> {
+        let mut authors = HashMap::new();
+        for page in 1..=pages {
+            let url = format!(
+                "{}/repos/{}/{}/commits?per_page=100&page={}",
+                self.base_url, owner, repo, page
+            );
+            let response = self
+                .send(self.client.get(&url).headers(self.get_auth_headers()))
+                .await?;
+            if !response.status().is_success() {
+                anyhow::bail!("Failed to list commits: {}", response.status());
+            }
+
+            let commits: Vec<serde_json::Value> = telemetry::json(response).await?;
+            for c in &commits {
+                let (Some(sha), Some(login)) = (c["sha"].as_str(), c["author"]["login"].as_str())
+                else {
+                    continue;
+                };
+                authors.insert(
+                    sha.to_string(),
+                    GitHubUser {
+                        login: login.to_string(),
+                        id: c["author"]["id"].as_u64().unwrap_or(0),
+                        avatar_url: c["author"]["avatar_url"].as_str().unwrap_or("").to_string(),
+                        html_url: c["author"]["html_url"].as_str().unwrap_or("").to_string(),
+                        contributions: None,
+                    },
+                );
+            }
+            if commits.len() < 100 {
+                break;
+            }
         }
+        Ok(authors)
     }
 
+    // Newest first, up to limit
     pub async fn get_releases(
         &self,
         owner: &str,
@@ -238,112 +710,921 @@ impl GitHubClient {
     ) -> Result<Vec<GitHubRelease>> {
         let url = format!(
             "{}/repos/{}/{}/releases?per_page={}",
+            self.base_url,
+            owner,
+            repo,
+            limit.clamp(1, PAGE_SIZE)
+        );
+
+        let releases = self.get_pages(&url, limit, |_| true).await?;
+        let parsed_releases = releases
+            .into_iter()
+            .map(|r| GitHubRelease {
+                tag_name: r["tag_name"].as_str().unwrap_or("").to_string(),
+                name: r["name"].as_str().map(|s| s.to_string()),
+                body: r["body"].as_str().map(|s| s.to_string()),
+                draft: r["draft"].as_bool().unwrap_or(false),
+                prerelease: r["prerelease"].as_bool().unwrap_or(false),
+                created_at: chrono::DateTime::parse_from_rfc3339(
+                    r["created_at"].as_str().unwrap_or("1970-01-01T00:00:00Z"),
+                )
+                .unwrap()
+                .with_timezone(&Utc),
+                published_at: r["published_at"]
+                    .as_str()
+                    .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                    .map(|dt| dt.with_timezone(&Utc)),
+                author: GitHubUser {
+                    login: r["author"]["login"].as_str().unwrap_or("").to_string(),
+                    id: r["author"]["id"].as_u64().unwrap_or(0),
+                    avatar_url: r["author"]["avatar_url"].as_str().unwrap_or("").to_string(),
+                    html_url: r["author"]["html_url"].as_str().unwrap_or("").to_string(),
+                    contributions: None,
+                },
+                assets_count: r["assets"].as_array().map(|a| a.len()).unwrap_or(0),
+                assets: r["assets"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|asset| ReleaseAsset {
+                        name: asset["name"].as_str().unwrap_or("").to_string(),
+                        size: asset["size"].as_u64().unwrap_or(0),
+                        download_url: asset["browser_download_url"]
+                            .as_str()
+                            .unwrap_or("")
+                            .to_string(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok(parsed_releases)
+    }
+
+    // Most recently updated first, up to limit issues. The endpoint lists pull
+    // requests too, which are left out
+    pub async fn get_recent_issues(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubIssue>> {
+        let url = format!(
+            "{}/repos/{}/{}/issues?state=all&per_page={}&sort=updated",
+            self.base_url,
+            owner,
+            repo,
+            limit.clamp(1, PAGE_SIZE)
+        );
+
+        let issues = self
+            .get_pages(&url, limit, |i| i["pull_request"].is_null())
+            .await?;
+        let parsed_issues = issues
+            .into_iter()
+            .map(|i| GitHubIssue {
+                number: i["number"].as_u64().unwrap_or(0) as u32,
+                title: i["title"].as_str().unwrap_or("").to_string(),
+                state: i["state"].as_str().unwrap_or("").to_string(),
+                created_at: chrono::DateTime::parse_from_rfc3339(
+                    i["created_at"].as_str().unwrap_or("1970-01-01T00:00:00Z"),
+                )
+                .unwrap()
+                .with_timezone(&Utc),
+                updated_at: chrono::DateTime::parse_from_rfc3339(
+                    i["updated_at"].as_str().unwrap_or("1970-01-01T00:00:00Z"),
+                )
+                .unwrap()
+                .with_timezone(&Utc),
+                closed_at: i["closed_at"]
+                    .as_str()
+                    .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                    .map(|dt| dt.with_timezone(&Utc)),
+                author: GitHubUser {
+                    login: i["user"]["login"].as_str().unwrap_or("").to_string(),
+                    id: i["user"]["id"].as_u64().unwrap_or(0),
+                    avatar_url: i["user"]["avatar_url"].as_str().unwrap_or("").to_string(),
+                    html_url: i["user"]["html_url"].as_str().unwrap_or("").to_string(),
+                    contributions: None,
+                },
+                labels: i["labels"]
+                    .as_array()
+                    .unwrap_or(&Vec::new())
+                    .iter()
+                    .filter_map(|l| l["name"].as_str())
+                    .map(|s| s.to_string())
+                    .collect(),
+                comments: i["comments"].as_u64().unwrap_or(0) as u32,
+                author_association: i["author_association"].as_str().unwrap_or("").to_string(),
+                first_response_at: None, // Needs the comments, see get_first_response
+            })
+            .collect();
+        Ok(parsed_issues)
+    }
+
+    // GET of a list endpoint, following the next links of the Link header
+    // until limit kept items. A failed page is warned about and ends the list,
+    // the first one leaves it empty
+    async fn get_pages(
+        &self,
+        url: &str,
+        limit: usize,
+        keep: impl Fn(&serde_json::Value) -> bool,
+    ) -> Result<Vec<serde_json::Value>> {
+        let mut items = Vec::new();
+        let mut next = Some(url.to_string());
+        while let Some(page_url) = next.take()
+            && items.len() < limit
+        {
+            let response = self
+                .send(self.client.get(&page_url).headers(self.get_auth_headers()))
+                .await?;
+            if !response.status().is_success() {
+                if items.is_empty() {
+                    warn!("Failed to fetch {}: {}", url, response.status());
+                } else {
+                    warn!(
+                        "Stopped paging {} after {} items: {}",
+                        url,
+                        items.len(),
+                        response.status()
+                    );
+                }
+                break;
+            }
+            next = next_page(response.headers());
+            let page: Vec<serde_json::Value> = telemetry::json(response).await?;
+            items.extend(page.into_iter().filter(|item| keep(item)));
+        }
+        items.truncate(limit);
+        Ok(items)
+    }
+
+    pub async fn get_milestones(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubMilestone>> {
+        let url = format!(
+            "{}/repos/{}/{}/milestones?state=all&sort=due_on&direction=desc&per_page={}",
             self.base_url, owner, repo, limit
         );
+        let response = self
+            .send(self.client.get(&url).headers(self.get_auth_headers()))
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to list milestones: {}", response.status());
+        }
 
+        let milestones: Vec<serde_json::Value> = telemetry::json(response).await?;
+        let time = |value: &serde_json::Value| {
+            value
+                .as_str()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                .map(|dt| dt.with_timezone(&Utc))
+        };
+        Ok(milestones
+            .iter()
+            .map(|m| GitHubMilestone {
+                number: m["number"].as_u64().unwrap_or(0) as u32,
+                title: m["title"].as_str().unwrap_or("").to_string(),
+                state: m["state"].as_str().unwrap_or("").to_string(),
+                due_on: time(&m["due_on"]),
+                open_issues: m["open_issues"].as_u64().unwrap_or(0) as u32,
+                closed_issues: m["closed_issues"].as_u64().unwrap_or(0) as u32,
+                updated_at: time(&m["updated_at"]).unwrap_or_default(),
+                closed_at: time(&m["closed_at"]),
+            })
+            .collect())
+    }
+
+    // Projects v2 boards linked to the repository, only reachable through
+    // GraphQL. None without a token, which GraphQL requires
+    pub async fn get_project_boards(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Option<Vec<ProjectBoard>>> {
+        if self.token.is_none() {
+            return Ok(None);
+        }
         let response = self
-            .client
-            .get(&url)
-            .headers(self.get_auth_headers())
-            .send()
+            .send(
+                self.client
+                    .post(format!("{}/graphql", self.base_url))
+                    .headers(self.get_auth_headers())
+                    .json(&serde_json::json!({
+                        "query": PROJECT_BOARDS_QUERY,
+                        "variables": { "owner": owner, "name": repo },
+                    })),
+            )
             .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to query project boards: {}", response.status());
+        }
 
-        if response.status().is_success() {
-            let releases: Vec<serde_json::Value> = response.json().await?;
-            let parsed_releases = releases
-                .into_iter()
-                .map(|r| GitHubRelease {
-                    tag_name: r["tag_name"].as_str().unwrap_or("").to_string(),
-                    name: r["name"].as_str().map(|s| s.to_string()),
-                    body: r["body"].as_str().map(|s| s.to_string()),
-                    draft: r["draft"].as_bool().unwrap_or(false),
-                    prerelease: r["prerelease"].as_bool().unwrap_or(false),
-                    created_at: chrono::DateTime::parse_from_rfc3339(
-                        r["created_at"].as_str().unwrap_or("1970-01-01T00:00:00Z"),
-                    )
-                    .unwrap()
-                    .with_timezone(&Utc),
-                    published_at: r["published_at"]
+        // Errors such as a missing read:project scope come back with a 200
+        let data: serde_json::Value = telemetry::json(response).await?;
+        let Some(nodes) = data["data"]["repository"]["projectsV2"]["nodes"].as_array() else {
+            anyhow::bail!(
+                "Failed to query project boards: {}",
+                data["errors"][0]["message"]
+                    .as_str()
+                    .unwrap_or("no projects in the response")
+            );
+        };
+        let boards = nodes
+            .iter()
+            .map(|p| {
+                let mut status_counts = BTreeMap::new();
+                for item in p["items"]["nodes"].as_array().into_iter().flatten() {
+                    let status = item["fieldValueByName"]["name"]
+                        .as_str()
+                        .unwrap_or("No status");
+                    *status_counts.entry(status.to_string()).or_insert(0) += 1;
+                }
+                ProjectBoard {
+                    number: p["number"].as_u64().unwrap_or(0) as u32,
+                    title: p["title"].as_str().unwrap_or("").to_string(),
+                    url: p["url"].as_str().unwrap_or("").to_string(),
+                    public: p["public"].as_bool().unwrap_or(false),
+                    closed: p["closed"].as_bool().unwrap_or(false),
+                    updated_at: p["updatedAt"]
                         .as_str()
                         .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-                        .map(|dt| dt.with_timezone(&Utc)),
-                    author: GitHubUser {
-                        login: r["author"]["login"].as_str().unwrap_or("").to_string(),
-                        id: r["author"]["id"].as_u64().unwrap_or(0),
-                        avatar_url: r["author"]["avatar_url"].as_str().unwrap_or("").to_string(),
-                        html_url: r["author"]["html_url"].as_str().unwrap_or("").to_string(),
-                        contributions: None,
-                    },
-                    assets_count: r["assets"].as_array().map(|a| a.len()).unwrap_or(0),
-                })
-                .collect();
-            Ok(parsed_releases)
-        } else {
-            Ok(Vec::new())
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_default(),
+                    items: p["items"]["totalCount"].as_u64().unwrap_or(0) as u32,
+                    status_counts,
+                }
+            })
+            .collect();
+        Ok(Some(boards))
+    }
+
+    // Security advisories the repository published, newest first. Drafts and
+    // triage reports are only visible to its maintainers and are left out
+    pub async fn get_security_advisories(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Vec<PublishedAdvisory>> {
+        let url = format!(
+            "{}/repos/{}/{}/security-advisories?state=published&sort=published&direction=desc&per_page=100",
+            self.base_url, owner, repo
+        );
+        let response = self
+            .send(self.client.get(&url).headers(self.get_auth_headers()))
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to list security advisories: {}", response.status());
         }
+
+        let advisories: Vec<serde_json::Value> = telemetry::json(response).await?;
+        let time = |value: &serde_json::Value| {
+            value
+                .as_str()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                .map(|dt| dt.with_timezone(&Utc))
+        };
+        Ok(advisories
+            .iter()
+            .map(|a| PublishedAdvisory {
+                ghsa_id: a["ghsa_id"].as_str().unwrap_or("").to_string(),
+                cve_id: a["cve_id"].as_str().map(|s| s.to_string()),
+                summary: a["summary"].as_str().unwrap_or("").to_string(),
+                severity: a["severity"].as_str().unwrap_or("unknown").to_string(),
+                created_at: time(&a["created_at"]).unwrap_or_default(),
+                published_at: time(&a["published_at"]),
+                patched_versions: a["vulnerabilities"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|v| v["patched_versions"].as_str())
+                    .filter(|versions| !versions.trim().is_empty())
+                    .map(|versions| versions.to_string())
+                    .collect(),
+                url: a["html_url"].as_str().unwrap_or("").to_string(),
+            })
+            .collect())
     }
 
-    pub async fn get_recent_issues(
+    // First comment on an issue by a maintainer other than its author
+    // Downloads a release checksum file and the smallest artifact it lists,
+    // and compares the two. None when no recent release allows it
+    pub async fn verify_release_checksum(
         &self,
         owner: &str,
         repo: &str,
-        limit: usize,
-    ) -> Result<Vec<GitHubIssue>> {
+        releases: &[GitHubRelease],
+    ) -> Result<Option<ChecksumVerification>> {
+        for release in releases
+            .iter()
+            .filter(|release| !release.draft)
+            .take(RECENT_RELEASES)
+        {
+            for checksum_file in release.assets.iter().filter(|asset| {
+                is_checksum_file(&asset.name) && asset.size <= MAX_CHECKSUM_FILE_SIZE
+            }) {
+                let content = self
+                    .download_asset(checksum_file, MAX_CHECKSUM_FILE_SIZE)
+                    .await?;
+                let entries =
+                    parse_checksums(&checksum_file.name, &String::from_utf8_lossy(&content));
+                let Some((asset, expected)) = release
+                    .assets
+                    .iter()
+                    .filter(|asset| asset.size <= MAX_VERIFIED_ASSET_SIZE)
+                    .filter_map(|asset| {
+                        let (digest, _) = entries.iter().find(|(_, name)| *name == asset.name)?;
+                        Some((asset, digest))
+                    })
+                    .min_by_key(|(asset, _)| asset.size)
+                else {
+                    continue;
+                };
+
+                info!("Verifying the checksum of {}...", asset.name);
+                let bytes = self.download_asset(asset, MAX_VERIFIED_ASSET_SIZE).await?;
+                let sha256 = format!("{:x}", Sha256::digest(&bytes));
+                let (algorithm, actual) = if expected.len() == 128 {
+                    ("sha512", format!("{:x}", Sha512::digest(&bytes)))
+                } else {
+                    ("sha256", sha256.clone())
+                };
+                let attested = self
+                    .has_attestation(owner, repo, &sha256)
+                    .await
+                    .inspect_err(|e| warn!("Failed to look up attestations: {}", e))
+                    .ok();
+                return Ok(Some(ChecksumVerification {
+                    tag_name: release.tag_name.clone(),
+                    checksum_file: checksum_file.name.clone(),
+                    asset: asset.name.clone(),
+                    algorithm: algorithm.to_string(),
+                    verified: actual == *expected,
+                    expected: expected.clone(),
+                    actual,
+                    attested,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    // Release downloads redirect to a storage host, which reqwest doesn't
+    // forward the token to
+    async fn download_asset(&self, asset: &ReleaseAsset, max_size: u64) -> Result<Vec<u8>> {
+        let mut response = self
+            .send(
+                self.client
+                    .get(&asset.download_url)
+                    .headers(self.get_auth_headers()),
+            )
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to download {}: {}", asset.name, response.status());
+        }
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if (bytes.len() + chunk.len()) as u64 > max_size {
+                anyhow::bail!("{} is larger than {} bytes", asset.name, max_size);
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(bytes)
+    }
+
+    // Whether GitHub stores an artifact attestation for a SHA-256 digest
+    async fn has_attestation(&self, owner: &str, repo: &str, sha256: &str) -> Result<bool> {
         let url = format!(
-            "{}/repos/{}/{}/issues?state=all&per_page={}&sort=updated",
-            self.base_url, owner, repo, limit
+            "{}/repos/{}/{}/attestations/sha256:{}",
+            self.base_url, owner, repo, sha256
         );
+        let response = self
+            .send(self.client.get(&url).headers(self.get_auth_headers()))
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to list attestations: {}", response.status());
+        }
+        let attestations: serde_json::Value = telemetry::json(response).await?;
+        Ok(attestations["attestations"]
+            .as_array()
+            .is_some_and(|list| !list.is_empty()))
+    }
 
+    pub async fn get_first_response(
+        &self,
+        owner: &str,
+        repo: &str,
+        issue: &GitHubIssue,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let url = format!(
+            "{}/repos/{}/{}/issues/{}/comments?per_page=30",
+            self.base_url, owner, repo, issue.number
+        );
         let response = self
-            .client
-            .get(&url)
-            .headers(self.get_auth_headers())
-            .send()
+            .send(self.client.get(&url).headers(self.get_auth_headers()))
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Failed to list comments of #{}: {}",
+                issue.number,
+                response.status()
+            );
+        }
+
+        let comments: Vec<serde_json::Value> = telemetry::json(response).await?;
+        Ok(comments
+            .iter()
+            .filter(|c| c["user"]["login"].as_str() != Some(issue.author.login.as_str()))
+            .filter(|c| {
+                c["author_association"]
+                    .as_str()
+                    .is_some_and(is_maintainer_association)
+            })
+            .find_map(|c| c["created_at"].as_str())
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.with_timezone(&Utc)))
+    }
+
+    // Open issues carrying any of the labels, from the issue search
+    pub async fn count_open_issues(&self, owner: &str, repo: &str, labels: &[&str]) -> Result<u32> {
+        let labels: Vec<String> = labels.iter().map(|l| format!("\"{}\"", l)).collect();
+        let query = format!(
+            "repo:{}/{} is:issue is:open label:{}",
+            owner,
+            repo,
+            labels.join(",")
+        );
+        let response = self
+            .send(
+                self.client
+                    .get(format!("{}/search/issues", self.base_url))
+                    .headers(self.get_auth_headers())
+                    .query(&[("q", query.as_str()), ("per_page", "1")]),
+            )
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to search issues: {}", response.status());
+        }
+        let data: serde_json::Value = telemetry::json(response).await?;
+        Ok(data["total_count"].as_u64().unwrap_or(0) as u32)
+    }
+
+    pub async fn search_similar_repositories(
+        &self,
+        metadata: &RepositoryMetadata,
+        keywords: &[String],
+        limit: usize,
+    ) -> Result<Vec<RelatedProject>> {
+        let query = if !metadata.topics.is_empty() {
+            // Multiple topic qualifiers are combined with AND, so keep the query broad
+            metadata
+                .topics
+                .iter()
+                .take(2)
+                .map(|topic| format!("topic:{}", topic))
+                .collect::<Vec<_>>()
+                .join(" ")
+        } else if !keywords.is_empty() {
+            // The most salient words of the code and docs, searched in READMEs
+            // too since they need not appear in a description
+            format!(
+                "{} in:name,description,readme",
+                keywords
+                    .iter()
+                    .take(2)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+        } else if let Some(description) = &metadata.description {
+            let keywords: Vec<&str> = description
+                .split(|c: char| !c.is_alphanumeric() && c != '-')
+                .filter(|word| word.len() > 3)
+                .take(3)
+                .collect();
+            if keywords.is_empty() {
+                return Ok(Vec::new());
+            }
+            format!("{} in:name,description", keywords.join(" "))
+        } else {
+            return Ok(Vec::new());
+        };
+
+        let url = format!("{}/search/repositories", self.base_url);
+        info!("Searching similar repositories: {}", query);
+
+        let response = self
+            .send(
+                self.client
+                    .get(&url)
+                    .headers(self.get_auth_headers())
+                    .query(&[
+                        ("q", query.as_str()),
+                        ("sort", "stars"),
+                        ("order", "desc"),
+                        ("per_page", &(limit + 1).to_string()),
+                    ]),
+            )
             .await?;
 
         if response.status().is_success() {
-            let issues: Vec<serde_json::Value> = response.json().await?;
-            let parsed_issues = issues
-                .into_iter()
-                .filter(|i| i["pull_request"].is_null()) // Filter out pull requests
-                .map(|i| GitHubIssue {
-                    number: i["number"].as_u64().unwrap_or(0) as u32,
-                    title: i["title"].as_str().unwrap_or("").to_string(),
-                    state: i["state"].as_str().unwrap_or("").to_string(),
-                    created_at: chrono::DateTime::parse_from_rfc3339(
-                        i["created_at"].as_str().unwrap_or("1970-01-01T00:00:00Z"),
-                    )
-                    .unwrap()
-                    .with_timezone(&Utc),
-                    updated_at: chrono::DateTime::parse_from_rfc3339(
-                        i["updated_at"].as_str().unwrap_or("1970-01-01T00:00:00Z"),
-                    )
-                    .unwrap()
-                    .with_timezone(&Utc),
-                    closed_at: i["closed_at"]
+            let data: serde_json::Value = telemetry::json(response).await?;
+            let projects = data["items"]
+                .as_array()
+                .unwrap_or(&Vec::new())
+                .iter()
+                .filter(|r| r["full_name"].as_str() != Some(metadata.full_name.as_str()))
+                .take(limit)
+                .map(|r| RelatedProject {
+                    full_name: r["full_name"].as_str().unwrap_or("").to_string(),
+                    html_url: r["html_url"].as_str().unwrap_or("").to_string(),
+                    description: r["description"].as_str().map(|s| s.to_string()),
+                    stars: r["stargazers_count"].as_u64().unwrap_or(0) as u32,
+                    last_activity: r["pushed_at"]
                         .as_str()
                         .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
                         .map(|dt| dt.with_timezone(&Utc)),
-                    author: GitHubUser {
-                        login: i["user"]["login"].as_str().unwrap_or("").to_string(),
-                        id: i["user"]["id"].as_u64().unwrap_or(0),
-                        avatar_url: i["user"]["avatar_url"].as_str().unwrap_or("").to_string(),
-                        html_url: i["user"]["html_url"].as_str().unwrap_or("").to_string(),
-                        contributions: None,
-                    },
-                    labels: i["labels"]
-                        .as_array()
-                        .unwrap_or(&Vec::new())
-                        .iter()
-                        .filter_map(|l| l["name"].as_str())
-                        .map(|s| s.to_string())
-                        .collect(),
-                    comments: i["comments"].as_u64().unwrap_or(0) as u32,
+                    license: r["license"]["spdx_id"]
+                        .as_str()
+                        .or_else(|| r["license"]["name"].as_str())
+                        .map(|s| s.to_string()),
                 })
                 .collect();
-            Ok(parsed_issues)
+            Ok(projects)
         } else {
             Ok(Vec::new())
         }
     }
+
+    // Publishes the check outcome as a completed check run on the commit and
+    // returns its URL. Annotations beyond the first batch are added by updates
+    pub async fn publish_check_run(
+        &self,
+        owner: &str,
+        repo: &str,
+        head_sha: &str,
+        report: &CheckReport,
+        annotations: &[CheckAnnotation],
+    ) -> Result<String> {
+        let failed: Vec<&str> = report
+            .results
+            .iter()
+            .filter(|result| !result.passed)
+            .map(|result| result.name.as_str())
+            .collect();
+        let title = if failed.is_empty() {
+            format!("All {} checks passed", report.results.len())
+        } else {
+            format!("{} of {} checks failed", failed.len(), report.results.len())
+        };
+        let summary = report
+            .results
+            .iter()
+            .map(|result| {
+                format!(
+                    "- {} **{}**: {} (threshold {})",
+                    if result.passed { "✅" } else { "❌" },
+                    result.name,
+                    result.actual,
+                    result.threshold
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let output = |batch: &[CheckAnnotation]| {
+            serde_json::json!({
+                "title": title,
+                "summary": summary,
+                "annotations": batch,
+            })
+        };
+        let mut batches = annotations.chunks(ANNOTATIONS_PER_REQUEST);
+
+        let url = format!("{}/repos/{}/{}/check-runs", self.base_url, owner, repo);
+        info!("Publishing check run to: {}", url);
+        let response = self
+            .send(
+                self.client
+                    .post(&url)
+                    .headers(self.get_auth_headers())
+                    .json(&serde_json::json!({
+                        "name": CHECK_RUN_NAME,
+                        "head_sha": head_sha,
+                        "status": "completed",
+                        "conclusion": if report.passed { "success" } else { "failure" },
+                        "completed_at": report.checked_at.to_rfc3339(),
+                        "output": output(batches.next().unwrap_or_default()),
+                    })),
+            )
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Failed to create check run: {} - {}",
+                response.status(),
+                telemetry::text(response).await?
+            );
+        }
+        let check_run: serde_json::Value = telemetry::json(response).await?;
+        let check_run_id = check_run["id"].as_u64().unwrap_or(0);
+
+        for batch in batches {
+            let url = format!(
+                "{}/repos/{}/{}/check-runs/{}",
+                self.base_url, owner, repo, check_run_id
+            );
+            let response = self
+                .send(
+                    self.client
+                        .patch(&url)
+                        .headers(self.get_auth_headers())
+                        .json(&serde_json::json!({ "output": output(batch) })),
+                )
+                .await?;
+            if !response.status().is_success() {
+                warn!("Failed to add check run annotations: {}", response.status());
+                break;
+            }
+        }
+
+        Ok(check_run["html_url"].as_str().unwrap_or("").to_string())
+    }
+
+    // Creates the pull request comment, or edits the earlier one carrying the
+    // marker so repeated runs don't pile up comments. Returns the comment URL
+    pub async fn upsert_pr_comment(
+        &self,
+        owner: &str,
+        repo: &str,
+        pr_number: u64,
+        body: &str,
+        marker: &str,
+    ) -> Result<String> {
+        let url = format!(
+            "{}/repos/{}/{}/issues/{}/comments",
+            self.base_url, owner, repo, pr_number
+        );
+        let mut existing = None;
+        for page in 1.. {
+            let response = self
+                .send(
+                    self.client
+                        .get(&url)
+                        .headers(self.get_auth_headers())
+                        .query(&[("per_page", "100"), ("page", &page.to_string())]),
+                )
+                .await?;
+            if !response.status().is_success() {
+                anyhow::bail!(
+                    "Failed to list comments of #{}: {}",
+                    pr_number,
+                    response.status()
+                );
+            }
+            let comments: Vec<serde_json::Value> = telemetry::json(response).await?;
+            existing = comments
+                .iter()
+                .find(|comment| {
+                    comment["body"]
+                        .as_str()
+                        .is_some_and(|body| body.starts_with(marker))
+                })
+                .and_then(|comment| comment["id"].as_u64());
+            if existing.is_some() || comments.len() < 100 {
+                break;
+            }
+        }
+
+        let request = match existing {
+            Some(comment_id) => {
+                info!("Updating comment {} on #{}", comment_id, pr_number);
+                self.client.patch(format!(
+                    "{}/repos/{}/{}/issues/comments/{}",
+                    self.base_url, owner, repo, comment_id
+                ))
+            }
+            None => {
+                info!("Commenting on #{}", pr_number);
+                self.client.post(&url)
+            }
+        };
+        let response = self
+            .send(
+                request
+                    .headers(self.get_auth_headers())
+                    .json(&serde_json::json!({ "body": body })),
+            )
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Failed to post comment on #{}: {} - {}",
+                pr_number,
+                response.status(),
+                telemetry::text(response).await?
+            );
+        }
+        let comment: serde_json::Value = telemetry::json(response).await?;
+        Ok(comment["html_url"].as_str().unwrap_or("").to_string())
+    }
+}
+
+impl Forge for GitHubClient {
+    fn get_repository_metadata(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> impl Future<Output = Result<RepositoryMetadata>> + Send {
+        GitHubClient::get_repository_metadata(self, owner, repo)
+    }
+
+    fn get_contributors(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<GitHubUser>>> + Send {
+        GitHubClient::get_contributors(self, owner, repo, limit)
+    }
+
+    fn get_releases(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<GitHubRelease>>> + Send {
+        GitHubClient::get_releases(self, owner, repo, limit)
+    }
+
+    fn get_recent_issues(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<GitHubIssue>>> + Send {
+        GitHubClient::get_recent_issues(self, owner, repo, limit)
+    }
+
+    fn get_languages(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> impl Future<Output = Result<HashMap<String, u64>>> + Send {
+        GitHubClient::get_languages(self, owner, repo)
+    }
+}
+
+pub fn is_maintainer_association(association: &str) -> bool {
+    matches!(association, "OWNER" | "MEMBER" | "COLLABORATOR")
+}
+
+// Repository settings need admin access, GitHub answers 404 rather than 403
+// when the token can't see the repository at all
+fn permission_hint(status: reqwest::StatusCode) -> &'static str {
+    match status.as_u16() {
+        403 | 404 => {
+            "the token needs admin access to the repository and the repo (or public_repo) scope, a GitHub App needs the Administration write permission - "
+        }
+        _ => "",
+    }
+}
+
+// Quota of the API resource a response counted against, from the
+// X-RateLimit-* headers. None when a header is missing or malformed, as on
+// endpoints without rate limits
+fn rate_limit(headers: &HeaderMap) -> Option<GitHubRateLimit> {
+    let header = |name: &str| headers.get(name)?.to_str().ok();
+    Some(GitHubRateLimit {
+        resource: header("x-ratelimit-resource").unwrap_or("core").to_string(),
+        limit: header("x-ratelimit-limit")?.parse().ok()?,
+        remaining: header("x-ratelimit-remaining")?.parse().ok()?,
+        reset_at: DateTime::from_timestamp(header("x-ratelimit-reset")?.parse().ok()?, 0)?,
+    })
+}
+
+// Retry-After in seconds, as secondary rate limits send it
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = headers.get("retry-after")?.to_str().ok()?.parse().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+// The rel="next" URL of a Link header, GitLab sends the same
+pub(crate) fn next_page(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    let link = headers.get(reqwest::header::LINK)?.to_str().ok()?;
+    link.split(',').find_map(|entry| {
+        let (url, params) = entry.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == "rel=\"next\"")
+            .then(|| {
+                url.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string()
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderValue, LINK};
+
+    use super::*;
+
+    fn link(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(LINK, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn next_page_is_the_rel_next_entry() {
+        let headers = link(
+            "<https://api.github.com/repositories/1/contributors?page=3>; rel=\"next\", <https://api.github.com/repositories/1/contributors?page=9>; rel=\"last\"",
+        );
+        assert_eq!(
+            next_page(&headers).as_deref(),
+            Some("https://api.github.com/repositories/1/contributors?page=3")
+        );
+    }
+
+    #[test]
+    fn next_page_takes_any_entry_order_and_extra_params() {
+        let headers = link(
+            "<https://gitlab.com/api/v4/projects/1/issues?page=1>; rel=\"first\", <https://gitlab.com/api/v4/projects/1/issues?page=2>; type=\"page\"; rel=\"next\"",
+        );
+        assert_eq!(
+            next_page(&headers).as_deref(),
+            Some("https://gitlab.com/api/v4/projects/1/issues?page=2")
+        );
+    }
+
+    #[test]
+    fn last_page_has_no_next() {
+        let headers = link(
+            "<https://api.github.com/repositories/1/releases?page=1>; rel=\"prev\", <https://api.github.com/repositories/1/releases?page=1>; rel=\"first\"",
+        );
+        assert_eq!(next_page(&headers), None);
+        assert_eq!(next_page(&HeaderMap::new()), None);
+    }
+
+    fn headers(entries: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in entries {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn rate_limit_reads_the_quota_headers() {
+        let limit = rate_limit(&headers(&[
+            ("x-ratelimit-resource", "search"),
+            ("x-ratelimit-limit", "30"),
+            ("x-ratelimit-remaining", "12"),
+            ("x-ratelimit-reset", "1700000000"),
+        ]))
+        .unwrap();
+        assert_eq!(limit.resource, "search");
+        assert_eq!(limit.limit, 30);
+        assert_eq!(limit.remaining, 12);
+        assert_eq!(limit.reset_at.timestamp(), 1_700_000_000);
+    }
+
+    #[test]
+    fn rate_limit_defaults_to_the_core_resource() {
+        let limit = rate_limit(&headers(&[
+            ("x-ratelimit-limit", "5000"),
+            ("x-ratelimit-remaining", "4999"),
+            ("x-ratelimit-reset", "1700000000"),
+        ]))
+        .unwrap();
+        assert_eq!(limit.resource, "core");
+    }
+
+    #[test]
+    fn missing_or_malformed_quota_headers_give_none() {
+        assert!(rate_limit(&HeaderMap::new()).is_none());
+        assert!(
+            rate_limit(&headers(&[
+                ("x-ratelimit-limit", "5000"),
+                ("x-ratelimit-remaining", "many"),
+                ("x-ratelimit-reset", "1700000000"),
+            ]))
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn retry_after_is_in_seconds() {
+        assert_eq!(
+            retry_after(&headers(&[("retry-after", "60")])),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(retry_after(&headers(&[("retry-after", "soon")])), None);
+    }
 }
@@ -0,0 +1,405 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use reqwest::Client;
+use reqwest::header::{HeaderMap, HeaderValue};
+
+use crate::forge::Forge;
+use crate::github;
+use crate::network::{self, SendLogged};
+use crate::telemetry;
+use crate::types::{
+    GitHubIssue, GitHubLicense, GitHubRelease, GitHubUser, ReleaseAsset, RepositoryMetadata,
+};
+
+// Largest page the REST API serves
+const PAGE_SIZE: usize = 100;
+
+// GitLab REST API client, the GitLab counterpart of GitHubClient
+pub struct GitLabClient {
+    client: Client,
+    token: Option<String>,
+    base_url: String,
+}
+
+impl GitLabClient {
+    pub fn new(token: Option<String>) -> Self {
+        Self {
+            client: network::http_client(),
+            token,
+            base_url: "https://gitlab.com/api/v4".to_string(),
+        }
+    }
+
+    fn get_auth_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if let Some(token) = &self.token
+            && let Ok(mut value) = HeaderValue::from_str(token)
+        {
+            value.set_sensitive(true);
+            headers.insert("PRIVATE-TOKEN", value);
+        }
+        headers
+    }
+
+    // Projects are addressed by their URL-encoded full path
+    fn project_url(&self, owner: &str, repo: &str) -> String {
+        format!(
+            "{}/projects/{}",
+            self.base_url,
+            format!("{}/{}", owner, repo).replace('/', "%2F")
+        )
+    }
+
+    async fn get_json(&self, url: &str) -> Result<serde_json::Value> {
+        let response = self
+            .client
+            .get(url)
+            .headers(self.get_auth_headers())
+            .send_logged()
+            .await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Failed to fetch {}: {} - {}",
+                url,
+                response.status(),
+                telemetry::text(response).await?
+            );
+        }
+        telemetry::json(response).await
+    }
+
+    // GET of a list endpoint, following the next links of the Link header
+    // until limit items. A failed page is warned about and ends the list,
+    // the first one leaves it empty
+    async fn get_pages(&self, url: &str, limit: usize) -> Result<Vec<serde_json::Value>> {
+        let mut items = Vec::new();
+        let mut next = Some(url.to_string());
+        while let Some(page_url) = next.take()
+            && items.len() < limit
+        {
+            let response = self
+                .client
+                .get(&page_url)
+                .headers(self.get_auth_headers())
+                .send_logged()
+                .await?;
+            if !response.status().is_success() {
+                if items.is_empty() {
+                    warn!("Failed to fetch {}: {}", url, response.status());
+                } else {
+                    warn!(
+                        "Stopped paging {} after {} items: {}",
+                        url,
+                        items.len(),
+                        response.status()
+                    );
+                }
+                break;
+            }
+            next = github::next_page(response.headers());
+            let page: Vec<serde_json::Value> = telemetry::json(response).await?;
+            items.extend(page);
+        }
+        items.truncate(limit);
+        Ok(items)
+    }
+
+    pub async fn get_repository_metadata(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<RepositoryMetadata> {
+        let url = format!("{}?license=true", self.project_url(owner, repo));
+        info!("Fetching project metadata from: {}", url);
+        let project = self.get_json(&url).await?;
+
+        let languages = self.get_languages(owner, repo).await.unwrap_or_default();
+        let language = languages
+            .iter()
+            .max_by_key(|(_, amount)| **amount)
+            .map(|(language, _)| language.clone());
+        let namespace = &project["namespace"];
+        let stars = project["star_count"].as_u64().unwrap_or(0) as u32;
+        let last_activity = parse_date(&project["last_activity_at"]);
+        let mirror = project["mirror"].as_bool().unwrap_or(false);
+
+        Ok(RepositoryMetadata {
+            id: project["id"].as_u64().unwrap_or(0),
+            name: project["path"].as_str().unwrap_or(repo).to_string(),
+            full_name: project["path_with_namespace"]
+                .as_str()
+                .unwrap_or("")
+                .to_string(),
+            description: project["description"]
+                .as_str()
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string()),
+            homepage: None,
+            html_url: project["web_url"].as_str().unwrap_or("").to_string(),
+            clone_url: project["http_url_to_repo"]
+                .as_str()
+                .unwrap_or("")
+                .to_string(),
+            ssh_url: project["ssh_url_to_repo"]
+                .as_str()
+                .unwrap_or("")
+                .to_string(),
+            git_url: project["http_url_to_repo"]
+                .as_str()
+                .unwrap_or("")
+                .to_string(),
+            owner: GitHubUser {
+                login: namespace["full_path"].as_str().unwrap_or(owner).to_string(),
+                id: namespace["id"].as_u64().unwrap_or(0),
+                avatar_url: namespace["avatar_url"].as_str().unwrap_or("").to_string(),
+                html_url: namespace["web_url"].as_str().unwrap_or("").to_string(),
+                contributions: None,
+            },
+            private: project["visibility"].as_str() != Some("public"),
+            fork: project["forked_from_project"].is_object(),
+            archived: project["archived"].as_bool().unwrap_or(false),
+            disabled: false,
+            has_issues: project["issues_enabled"].as_bool().unwrap_or(false),
+            has_projects: false,
+            has_wiki: project["wiki_enabled"].as_bool().unwrap_or(false),
+            has_pages: false,
+            has_downloads: false,
+            has_discussions: false,
+            stargazers_count: stars,
+            watchers_count: stars,
+            forks_count: project["forks_count"].as_u64().unwrap_or(0) as u32,
+            subscribers_count: None,
+            network_count: None,
+            open_issues_count: project["open_issues_count"].as_u64().unwrap_or(0) as u32,
+            license: Some(&project["license"])
+                .filter(|license| license.is_object())
+                .map(|license| GitHubLicense {
+                    key: license["key"].as_str().unwrap_or("").to_string(),
+                    name: license["name"].as_str().unwrap_or("").to_string(),
+                    spdx_id: None,
+                    url: license["html_url"].as_str().map(|s| s.to_string()),
+                }),
+            topics: project["topics"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|topic| topic.as_str())
+                .map(|topic| topic.to_string())
+                .collect(),
+            default_branch: project["default_branch"]
+                .as_str()
+                .unwrap_or("main")
+                .to_string(),
+            // Only served to project members
+            size: (project["statistics"]["repository_size"]
+                .as_u64()
+                .unwrap_or(0)
+                / 1024) as u32,
+            language,
+            languages,
+            created_at: parse_date(&project["created_at"]),
+            updated_at: last_activity,
+            pushed_at: last_activity,
+            parent: project["forked_from_project"]["path_with_namespace"]
+                .as_str()
+                .map(|s| s.to_string()),
+            mirror_url: project["import_url"]
+                .as_str()
+                .filter(|_| mirror)
+                .map(|s| s.to_string()),
+        })
+    }
+
+    // Contributors are commit author names and emails, not accounts
+    pub async fn get_contributors(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubUser>> {
+        let url = format!(
+            "{}/repository/contributors?order_by=commits&sort=desc&per_page={}",
+            self.project_url(owner, repo),
+            limit.clamp(1, PAGE_SIZE)
+        );
+        let contributors = self.get_pages(&url, limit).await?;
+        Ok(contributors
+            .into_iter()
+            .map(|c| GitHubUser {
+                login: c["name"].as_str().unwrap_or("").to_string(),
+                id: 0,
+                avatar_url: String::new(),
+                html_url: String::new(),
+                contributions: c["commits"].as_u64().map(|x| x as u32),
+            })
+            .collect())
+    }
+
+    pub async fn get_releases(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubRelease>> {
+        let url = format!(
+            "{}/releases?per_page={}",
+            self.project_url(owner, repo),
+            limit.clamp(1, PAGE_SIZE)
+        );
+        let releases = self.get_pages(&url, limit).await?;
+        Ok(releases
+            .into_iter()
+            .map(|r| {
+                let assets: Vec<ReleaseAsset> = r["assets"]["links"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|link| ReleaseAsset {
+                        name: link["name"].as_str().unwrap_or("").to_string(),
+                        size: 0, // Links don't give one
+                        download_url: link["url"].as_str().unwrap_or("").to_string(),
+                    })
+                    .collect();
+                GitHubRelease {
+                    tag_name: r["tag_name"].as_str().unwrap_or("").to_string(),
+                    name: r["name"].as_str().map(|s| s.to_string()),
+                    body: r["description"].as_str().map(|s| s.to_string()),
+                    draft: false,
+                    prerelease: r["upcoming_release"].as_bool().unwrap_or(false),
+                    created_at: parse_date(&r["created_at"]),
+                    published_at: r["released_at"]
+                        .as_str()
+                        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                        .map(|dt| dt.with_timezone(&Utc)),
+                    author: user(&r["author"]),
+                    assets_count: assets.len(),
+                    assets,
+                }
+            })
+            .collect())
+    }
+
+    // Issues are numbered by their iid, the number shown in the project
+    pub async fn get_recent_issues(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> Result<Vec<GitHubIssue>> {
+        let url = format!(
+            "{}/issues?scope=all&order_by=updated_at&sort=desc&per_page={}",
+            self.project_url(owner, repo),
+            limit.clamp(1, PAGE_SIZE)
+        );
+        let issues = self.get_pages(&url, limit).await?;
+        Ok(issues
+            .into_iter()
+            .map(|i| GitHubIssue {
+                number: i["iid"].as_u64().unwrap_or(0) as u32,
+                title: i["title"].as_str().unwrap_or("").to_string(),
+                state: match i["state"].as_str() {
+                    Some("opened") => "open".to_string(),
+                    state => state.unwrap_or("").to_string(),
+                },
+                created_at: parse_date(&i["created_at"]),
+                updated_at: parse_date(&i["updated_at"]),
+                closed_at: i["closed_at"]
+                    .as_str()
+                    .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                    .map(|dt| dt.with_timezone(&Utc)),
+                author: user(&i["author"]),
+                labels: i["labels"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|label| label.as_str())
+                    .map(|label| label.to_string())
+                    .collect(),
+                comments: i["user_notes_count"].as_u64().unwrap_or(0) as u32,
+                author_association: String::new(), // GitLab doesn't say
+                first_response_at: None,
+            })
+            .collect())
+    }
+
+    // Percentages with two decimals, scaled to integers: GitLab doesn't give
+    // byte counts, only their shares
+    pub async fn get_languages(&self, owner: &str, repo: &str) -> Result<HashMap<String, u64>> {
+        let url = format!("{}/languages", self.project_url(owner, repo));
+        let languages = self.get_json(&url).await?;
+        Ok(languages
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(language, share)| {
+                Some((language.clone(), (share.as_f64()? * 100.0).round() as u64))
+            })
+            .collect())
+    }
+}
+
+impl Forge for GitLabClient {
+    fn get_repository_metadata(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> impl Future<Output = Result<RepositoryMetadata>> + Send {
+        GitLabClient::get_repository_metadata(self, owner, repo)
+    }
+
+    fn get_contributors(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<GitHubUser>>> + Send {
+        GitLabClient::get_contributors(self, owner, repo, limit)
+    }
+
+    fn get_releases(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<GitHubRelease>>> + Send {
+        GitLabClient::get_releases(self, owner, repo, limit)
+    }
+
+    fn get_recent_issues(
+        &self,
+        owner: &str,
+        repo: &str,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<GitHubIssue>>> + Send {
+        GitLabClient::get_recent_issues(self, owner, repo, limit)
+    }
+
+    fn get_languages(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> impl Future<Output = Result<HashMap<String, u64>>> + Send {
+        GitLabClient::get_languages(self, owner, repo)
+    }
+}
+
+fn user(value: &serde_json::Value) -> GitHubUser {
+    GitHubUser {
+        login: value["username"].as_str().unwrap_or("").to_string(),
+        id: value["id"].as_u64().unwrap_or(0),
+        avatar_url: value["avatar_url"].as_str().unwrap_or("").to_string(),
+        html_url: value["web_url"].as_str().unwrap_or("").to_string(),
+        contributions: None,
+    }
+}
+
+fn parse_date(value: &serde_json::Value) -> DateTime<Utc> {
+    value
+        .as_str()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_default()
+}
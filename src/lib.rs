@@ -0,0 +1,64 @@
+//! Repository analysis as a library: clone a GitHub, GitLab or Bitbucket
+//! repository, read its structure, dependencies, security posture and
+//! history, and export the result. The binary is a command line front end over these modules.
+//!
+//! ```no_run
+//! use ai_repo_analyzer_rs::{RepositoryAnalyzer, config};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let config = config::load(Some("analyzer.toml".as_ref()))?;
+//! let analyzer = RepositoryAnalyzer::builder()
+//!     .with_github_token(std::env::var("GITHUB_TOKEN")?)
+//!     .with_config(&config)
+//!     .build()?;
+//! let analysis = analyzer
+//!     .analyze_repository("https://github.com/owner/repo")
+//!     .await?;
+//! println!("{}", analysis.analysis_summary);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`RepositoryAnalyzer::analyze_with_progress`] runs the same analysis
+//! while streaming [`ProgressEvent`]s for GUI and TUI frontends.
+//!
+//! Proxy, CA bundle and header settings are process-wide, applied with
+//! [`network::configure`] before the analyzer is built.
+
+pub mod ai;
+pub mod analyzers;
+pub mod assess;
+pub mod bitbucket;
+pub mod cancel;
+pub mod check;
+pub mod checkpoint;
+pub mod config;
+pub mod container;
+pub mod diff;
+pub mod evidence;
+pub mod export_context;
+pub mod file_index;
+pub mod forge;
+pub mod git;
+pub mod github;
+pub mod gitlab;
+pub mod llm_context;
+pub mod network;
+pub mod notify;
+pub mod overrides;
+pub mod paths;
+pub mod plugins;
+pub mod progress;
+pub mod registry;
+pub mod report;
+pub mod sandbox;
+pub mod schedule;
+pub mod telemetry;
+pub mod topics;
+pub mod types;
+pub mod utils;
+
+pub use analyzers::repo::{RepositoryAnalyzer, RepositoryAnalyzerBuilder};
+pub use types::{
+    AnalysisPhase, ProgressEvent, RemoteRepositoryData, RepositoryAnalysis, RepositoryMetadata,
+};
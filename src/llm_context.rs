@@ -0,0 +1,156 @@
+use anyhow::Result;
+use log::warn;
+use serde_json::Value;
+
+use crate::ai::count_tokens;
+use crate::file_index::DirectoryView;
+use crate::types::RepositoryAnalysis;
+use crate::utils::to_slash_path;
+
+// Previews, file contents and links cost the most tokens and tell a model
+// little the rest of the analysis doesn't
+const PRUNED_KEYS: &[&str] = &[
+    "content",
+    "content_preview",
+    "preview",
+    "snippet",
+    "hash",
+    "functions",
+    "telemetry",
+    "overrides",
+];
+
+// (list items, string characters, nesting depth) kept, tried in order
+// until the context fits the budget
+const LEVELS: &[(usize, usize, usize)] = &[
+    (20, 600, usize::MAX),
+    (10, 300, usize::MAX),
+    (5, 150, usize::MAX),
+    (2, 80, usize::MAX),
+    (1, 60, 4),
+    (1, 60, 3),
+    (1, 60, 2),
+];
+
+// Compact analysis for a model prompt: no previews, contents or links, the
+// file tree flattened to its directories, and lists and strings truncated
+// until it fits in `max_tokens`
+pub fn render(analysis: &RepositoryAnalysis, max_tokens: usize, yaml: bool) -> Result<String> {
+    let mut value = serde_json::to_value(analysis)?;
+    if let Value::Object(fields) = &mut value {
+        fields.insert(
+            "file_structure".to_string(),
+            Value::from(flatten_tree(analysis.file_structure.root())),
+        );
+    }
+    prune(&mut value, 0);
+
+    let mut rendered = String::new();
+    let mut tokens = 0;
+    for &(max_items, max_chars, max_depth) in LEVELS {
+        let mut context = value.clone();
+        truncate(&mut context, max_items, max_chars, max_depth, 0);
+        rendered = if yaml {
+            serde_yaml::to_string(&context)?
+        } else {
+            serde_json::to_string(&context)?
+        };
+        tokens = count_tokens(&rendered);
+        if tokens <= max_tokens {
+            return Ok(rendered);
+        }
+    }
+    warn!(
+        "The LLM context is {} tokens, over the budget of {}",
+        tokens, max_tokens
+    );
+    Ok(rendered)
+}
+
+// "dir/ (files)" lines relative to the repository root, parents before
+// their subdirectories
+fn flatten_tree(root: DirectoryView<'_>) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut stack = vec![root];
+    while let Some(directory) = stack.pop() {
+        let path = directory
+            .path()
+            .strip_prefix(root.path())
+            .unwrap_or(directory.path());
+        let path = match to_slash_path(path).to_string_lossy().as_ref() {
+            "" => ".".to_string(),
+            path => path.to_string(),
+        };
+        lines.push(format!("{}/ ({} files)", path, directory.file_count()));
+        let mut subdirectories: Vec<_> = directory.subdirectories().collect();
+        subdirectories.reverse();
+        stack.extend(subdirectories);
+    }
+    lines
+}
+
+// Drop the pruned keys, links, false flags and anything empty, and round
+// the ratios; the repository URL at the top level is kept
+fn prune(value: &mut Value, depth: usize) {
+    match value {
+        Value::Object(fields) => {
+            fields.retain(|key, _| {
+                !PRUNED_KEYS.contains(&key.as_str())
+                    && !(depth > 0 && (key == "url" || key.ends_with("_url")))
+            });
+            for field in fields.values_mut() {
+                prune(field, depth + 1);
+            }
+            fields.retain(|_, field| !is_empty(field));
+        }
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                prune(item, depth + 1);
+            }
+            items.retain(|item| !is_empty(item));
+        }
+        Value::Number(number) => {
+            if let Some(float) = number.as_f64().filter(|_| number.is_f64()) {
+                *value = Value::from((float * 100.0).round() / 100.0);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::String(text) => text.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(fields) => fields.is_empty(),
+        _ => false,
+    }
+}
+
+// The top-level strings (the summary, URL and date) are kept whole
+fn truncate(value: &mut Value, max_items: usize, max_chars: usize, max_depth: usize, depth: usize) {
+    match value {
+        Value::Object(fields) => {
+            fields.retain(|_, field| depth < max_depth || !(field.is_object() || field.is_array()));
+            for field in fields.values_mut() {
+                truncate(field, max_items, max_chars, max_depth, depth + 1);
+            }
+        }
+        Value::Array(items) => {
+            if items.len() > max_items {
+                let more = items.len() - max_items;
+                items.truncate(max_items);
+                items.push(Value::from(format!("... {} more", more)));
+            }
+            for item in items.iter_mut() {
+                truncate(item, max_items, max_chars, max_depth, depth + 1);
+            }
+        }
+        Value::String(text) if depth > 1 && text.chars().count() > max_chars => {
+            let truncated: String = text.chars().take(max_chars).collect();
+            *text = format!("{}...", truncated.trim_end());
+        }
+        _ => {}
+    }
+}
@@ -1,179 +1,653 @@
-mod analyzers;
-mod git;
-mod github;
-mod types;
-mod utils;
+mod cli;
 
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use ai_repo_analyzer_rs::{
+    RemoteRepositoryData, RepositoryAnalyzer, ai, assess, cancel, check, checkpoint, config,
+    container, diff, forge, github, llm_context, network, notify, overrides, paths, plugins,
+    report, sandbox, schedule, telemetry, topics, types, utils,
+};
 use anyhow::Result;
 use log::{error, info, warn};
-use rig::{client::ProviderClient, completion::Prompt, providers::gemini};
+use rig::providers::gemini;
+use serde::Serialize;
 
-use crate::{analyzers::repo::RepositoryAnalyzer, types::RepositoryMetadata};
+// Where rig's Gemini client sends its requests
+const GEMINI_HOST: &str = "generativelanguage.googleapis.com";
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Before logging, so --help and completions print alone
+    let cli = cli::parse();
+    if let cli::Command::Completions { shell } = cli.command {
+        print!("{}", cli::completions(shell));
+        return Ok(());
+    }
+
     // Initialize logging
     env_logger::Builder::from_default_env()
         .filter_level(log::LevelFilter::Info)
         .init();
 
     info!("AI Repository Analyzer starting...");
+    let started_at = Instant::now();
 
-    // Get command line arguments
-    let args: Vec<String> = std::env::args().collect();
+    // The schedule config doubles as the analyzer config, which is looked up
+    // in the user config directory too
+    let config_path = match (&cli.global.config, &cli.command) {
+        (Some(path), cli::Command::Schedule(args)) if *path != args.file => {
+            eprintln!(
+                "Error: schedule reads its config from {}, not --config",
+                args.file
+            );
+            std::process::exit(1);
+        }
+        (Some(path), _) => Some(PathBuf::from(path)),
+        (None, cli::Command::Schedule(args)) => Some(PathBuf::from(&args.file)),
+        _ => paths::default_config_file(),
+    };
+    let config = config::load(config_path.as_deref())?;
+    // Proxy and CA bundle, before any client is built
+    network::configure(&config.network)?;
+    let run = Run::new(cli.global, config, started_at);
 
-    if args.len() < 2 {
-        eprintln!(
-            "Usage: {} <github-repo-url> [--token <github-token>] [--output <json|yaml>] [--output-file <path>]",
-            args[0]
+    match cli.command {
+        cli::Command::Analyze(args) => analyze(&run, args).await,
+        cli::Command::Assess(args) => assess(&run, args).await,
+        cli::Command::SelfContainer(args) => self_container(&run, args).await,
+        cli::Command::AnalyzeLocal(args) => analyze_local(&run, args),
+        cli::Command::ReleaseDiff(args) => release_diff(&run, args).await,
+        cli::Command::ExportContext(args) => export_context(&run, args).await,
+        cli::Command::Report(args) => render_report(&run, args),
+        cli::Command::Diff(args) => security_diff(&run, args),
+        cli::Command::Check(args) => check(&run, args).await,
+        cli::Command::PrComment(args) => pr_comment(&run, args).await,
+        cli::Command::Schedule(args) => run_schedule(&run, args).await,
+        cli::Command::SuggestMetadata(args) => suggest_metadata(&run, args).await,
+        cli::Command::Completions { .. } => unreachable!("printed before logging starts"),
+    }
+}
+
+// The flags every command takes and the config they read
+struct Run {
+    github_token: Option<String>,
+    gitlab_token: Option<String>,
+    bitbucket_token: Option<String>,
+    output_format: cli::OutputFormat,
+    output_file: Option<String>,
+    llm_context_tokens: Option<usize>,
+    work_dir: Option<String>,
+    cache_dir: PathBuf,
+    config: config::AnalyzerConfig,
+    started_at: Instant,
+}
+
+impl Run {
+    fn new(global: cli::GlobalArgs, config: config::AnalyzerConfig, started_at: Instant) -> Self {
+        let cache_dir = global
+            .cache_dir
+            .map(PathBuf::from)
+            .or_else(|| config.paths.cache_dir.clone())
+            .unwrap_or_else(paths::cache_dir);
+        Run {
+            github_token: global.token.or_else(|| std::env::var("GITHUB_TOKEN").ok()),
+            gitlab_token: global
+                .gitlab_token
+                .or_else(|| std::env::var("GITLAB_TOKEN").ok()),
+            bitbucket_token: global
+                .bitbucket_token
+                .or_else(|| std::env::var("BITBUCKET_TOKEN").ok()),
+            output_format: global.output,
+            output_file: global.output_file,
+            llm_context_tokens: global.llm_context,
+            work_dir: global.work_dir,
+            cache_dir,
+            config,
+            started_at,
+        }
+    }
+
+    // The git backend, detections, rules and work dir come from the config
+    fn analyzer(
+        &self,
+        analysis_args: &cli::AnalysisArgs,
+        summarize_readme: bool,
+    ) -> Result<RepositoryAnalyzer> {
+        plugins::check_plugins(&self.config.plugins)?;
+        let limits = if analysis_args.paranoid {
+            sandbox::AnalysisLimits::paranoid()
+        } else {
+            sandbox::AnalysisLimits::standard()
+        };
+
+        let mut builder = RepositoryAnalyzer::builder()
+            .with_limits(limits)
+            .with_resume(analysis_args.resume)
+            .with_linguist(analysis_args.linguist)
+            .with_readme_summary(summarize_readme)
+            .with_ranking(analysis_args.top_files, &analysis_args.rank_exclude);
+        if let Some(token) = &self.github_token {
+            builder = builder.with_github_token(token);
+        }
+        if let Some(token) = &self.gitlab_token {
+            builder = builder.with_gitlab_token(token);
+        }
+        if let Some(token) = &self.bitbucket_token {
+            builder = builder.with_bitbucket_token(token);
+        }
+        builder = builder.with_config(&self.config);
+        if let Some(work_dir) = &self.work_dir {
+            builder = builder.with_work_dir(work_dir);
+        }
+        builder.build()
+    }
+
+    // The gemini-backed AI analyst using rig core, through the configured
+    // proxy and with the headers set for its host. Caches outlive the runs,
+    // each run keeps them under their size limit
+    fn ai_analyst(&self, ai_args: &cli::AiArgs, api_key: &str) -> Result<ai::AiAnalyst> {
+        paths::evict_cache(
+            &self.cache_dir,
+            self.config.paths.cache_max_size_mb * 1024 * 1024,
         );
-        eprintln!("Example: {} https://github.com/owner/repo", args[0]);
-        eprintln!(
-            "Example: {} https://github.com/owner/repo --token ghp_xxxx --output json --output-file analysis.json",
-            args[0]
+        let client = gemini::Client::builder(api_key)
+            .custom_client(network::http_client_for(GEMINI_HOST))
+            .build()?;
+        let budget = ai::AiBudget {
+            max_tokens: ai_args.max_tokens,
+            max_cost: ai_args.max_ai_cost,
+        };
+        let ai_cache = ai::AiCache::new(self.cache_dir.join("ai"));
+        Ok(ai::AiAnalyst::new(
+            client,
+            budget,
+            ai_cache,
+            ai_args.force_ai,
+        ))
+    }
+
+    // For the insights of a full analysis, which go without AI when no key is
+    // set or the run is offline
+    fn insights_analyst(
+        &self,
+        ai_args: &cli::AiArgs,
+        offline: bool,
+    ) -> Result<Option<ai::AiAnalyst>> {
+        match gemini_key() {
+            _ if offline => {
+                info!("Offline analysis, skipping AI insights");
+                Ok(None)
+            }
+            Some(api_key) => self.ai_analyst(ai_args, &api_key).map(Some),
+            None => {
+                info!("GEMINI_API_KEY is not set, skipping AI insights");
+                Ok(None)
+            }
+        }
+    }
+
+    // GitLab and Bitbucket repositories are read anonymously just as well,
+    // public ones at least
+    fn warn_without_token(&self, repo_url: &str) {
+        let other_forge = matches!(
+            forge::parse_repository_url(repo_url),
+            Ok((kind, _, _)) if kind != forge::ForgeKind::GitHub
         );
+        if self.github_token.is_none() && !other_forge {
+            warn!(
+                "No GitHub token provided. API rate limits may apply. Set GITHUB_TOKEN environment variable or use --token option."
+            );
+        }
+    }
+
+    fn serialize(&self, value: &impl Serialize) -> Result<String> {
+        Ok(match self.output_format {
+            cli::OutputFormat::Yaml => serde_yaml::to_string(value)?,
+            _ => serde_json::to_string_pretty(value)?,
+        })
+    }
+
+    // To --output-file, or stdout
+    fn write(&self, output: &str, what: &str) -> Result<()> {
+        if let Some(file_path) = &self.output_file {
+            std::fs::write(file_path, output)?;
+            info!("{} saved to: {}", what, file_path);
+        } else {
+            println!("{}", output);
+        }
+        Ok(())
+    }
+}
+
+fn gemini_key() -> Option<String> {
+    std::env::var("GEMINI_API_KEY").ok()
+}
+
+fn read_analysis(path: &str) -> Result<types::RepositoryAnalysis> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+// `analyze <path>` reads a local checkout instead of the forge API and a
+// clone, --offline keeps the AI provider out too
+async fn analyze(run: &Run, args: cli::AnalyzeCommand) -> Result<()> {
+    let local_checkout = Path::new(&args.url).is_dir();
+    if args.offline && !local_checkout {
+        eprintln!("Error: --offline needs the path of a local checkout");
         std::process::exit(1);
     }
+    if !local_checkout {
+        run.warn_without_token(&args.url);
+    }
+    let overrides = overrides::load_overrides(args.overrides.as_deref().map(Path::new))?;
 
-    let repo_url = &args[1];
+    // Without an AI provider the summary describes the project from the README
+    let ai_analyst = run.insights_analyst(&args.ai, args.offline)?;
+    let summarize_readme = args.analysis.readme_summary || ai_analyst.is_none();
+    let analyzer = run.analyzer(&args.analysis, summarize_readme)?;
 
-    // Parse command line options
-    let mut github_token = std::env::var("GITHUB_TOKEN").ok();
-    let mut output_format = "json".to_string();
-    let mut output_file: Option<String> = None;
+    // SIGINT/SIGTERM cancel in-flight requests and the clone instead of killing the process
+    cancel::install_signal_handlers();
+    let analysis = if local_checkout {
+        let analysis = async { analyzer.analyze_path(Path::new(&args.url)) };
+        run_analysis(run, &analyzer, &args.url, analysis).await?
+    } else {
+        let analysis = analyzer.analyze_repository(&args.url);
+        run_analysis(run, &analyzer, &args.url, analysis).await?
+    };
+    finish_analysis(
+        run,
+        &analyzer,
+        analysis,
+        ai_analyst.as_ref(),
+        &overrides,
+        &args.notify,
+    )
+    .await
+}
 
-    let mut i = 2;
-    while i < args.len() {
-        match args[i].as_str() {
-            "--token" => {
-                if i + 1 < args.len() {
-                    github_token = Some(args[i + 1].clone());
-                    i += 2;
-                } else {
-                    eprintln!("Error: --token requires a value");
-                    std::process::exit(1);
-                }
-            }
-            "--output" => {
-                if i + 1 < args.len() {
-                    output_format = args[i + 1].clone();
-                    i += 2;
-                } else {
-                    eprintln!("Error: --output requires a value (json or yaml)");
-                    std::process::exit(1);
-                }
-            }
-            "--output-file" => {
-                if i + 1 < args.len() {
-                    output_file = Some(args[i + 1].clone());
-                    i += 2;
-                } else {
-                    eprintln!("Error: --output-file requires a path");
-                    std::process::exit(1);
-                }
-            }
-            _ => {
-                eprintln!("Unknown option: {}", args[i]);
-                std::process::exit(1);
-            }
+// The local phase runs in the container, the forge API and AI on the host
+async fn self_container(run: &Run, args: cli::SelfContainerCommand) -> Result<()> {
+    run.warn_without_token(&args.url);
+    let overrides = overrides::load_overrides(args.overrides.as_deref().map(Path::new))?;
+    let ai_analyst = run.insights_analyst(&args.ai, false)?;
+    let summarize_readme = args.analysis.readme_summary || ai_analyst.is_none();
+    let analyzer = run.analyzer(&args.analysis, summarize_readme)?;
+
+    let mut local_args: Vec<String> = [
+        ("--paranoid", args.analysis.paranoid),
+        ("--linguist", args.analysis.linguist),
+        ("--readme-summary", summarize_readme),
+    ]
+    .iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(flag, _)| flag.to_string())
+    .collect();
+    local_args.extend([
+        "--top-files".to_string(),
+        args.analysis.top_files.to_string(),
+    ]);
+    for glob in &args.analysis.rank_exclude {
+        local_args.extend(["--rank-exclude".to_string(), glob.clone()]);
+    }
+    let runner = container::ContainerRunner::new(args.runtime, args.image, local_args);
+
+    cancel::install_signal_handlers();
+    let analysis = analyzer.analyze_repository_in_container(&args.url, &runner);
+    let analysis = run_analysis(run, &analyzer, &args.url, analysis).await?;
+    finish_analysis(
+        run,
+        &analyzer,
+        analysis,
+        ai_analyst.as_ref(),
+        &overrides,
+        &args.notify,
+    )
+    .await
+}
+
+// Answers a question set instead of producing the full analysis
+async fn assess(run: &Run, args: cli::AssessCommand) -> Result<()> {
+    let mut questions = match assess::load_questions(Path::new(&args.questions)) {
+        Ok(questions) => questions,
+        Err(e) => {
+            eprintln!("Error: failed to load questions: {}", e);
+            std::process::exit(1);
         }
+    };
+    // --sources applies to the questions that don't name their own
+    for question in questions.iter_mut().filter(|q| q.sources.is_empty()) {
+        question.sources = args.sources.clone();
     }
+    let Some(api_key) = gemini_key() else {
+        eprintln!("Error: assess needs GEMINI_API_KEY");
+        std::process::exit(1);
+    };
+
+    run.warn_without_token(&args.url);
+    let analyzer = run.analyzer(&args.analysis, args.analysis.readme_summary)?;
+    let mut ai_analyst = run.ai_analyst(&args.ai, &api_key)?;
+    if args.rerank || args.rerank_model.is_some() {
+        ai_analyst = ai_analyst.with_reranking(args.rerank_model);
+    }
+
+    cancel::install_signal_handlers();
+    let analysis = analyzer.analyze_repository(&args.url);
+    let analysis = run_analysis(run, &analyzer, &args.url, analysis).await?;
+    let report = tokio::select! {
+        report = assess::run_assessment(&ai_analyst, &analysis, &questions) => report,
+        _ = cancel::cancelled() => {
+            eprintln!("Assessment cancelled");
+            std::process::exit(130);
+        }
+    };
+    run.write(&run.serialize(&report)?, "Assessment")?;
 
-    if github_token.is_none() {
-        warn!(
-            "No GitHub token provided. API rate limits may apply. Set GITHUB_TOKEN environment variable or use --token option."
+    eprintln!("\n=== Assessment ===");
+    for answer in &report.answers {
+        let flag = if answer.grounded {
+            ""
+        } else {
+            ", unverified citations"
+        };
+        eprintln!(
+            "Q: {}\nA ({}{}): {}\n",
+            answer.question, answer.confidence, flag, answer.answer
         );
     }
+    eprintln!("==================");
+    Ok(())
+}
 
-    // Create analyzer
-    let analyzer = RepositoryAnalyzer::new(github_token, None);
-
-    // Initialize a gemini AI agent using rig core
-    let ai_client = gemini::Client::from_env();
-    let ai_agent = ai_client
-        .agent("gemini-2.5-flash").temperature(0.0)
-        .preamble("You are an expert software engineer and technical analyst specializing in code repository analysis. You will be provided with detailed analysis data about a GitHub repository in JSON format.
-
-Your task is to generate a comprehensive technical development report that includes:
-
-## Executive Summary
-- Brief overview of the project's purpose and main functionality
-- Key technologies and architecture highlights
-- Current development status and maturity level
-
-## Technical Architecture
-- Primary programming languages and their usage distribution
-- Framework and library ecosystem
-- Project structure and organization patterns
-- Build system and deployment configurations
-
-## Code Quality Assessment
-- Code metrics analysis (lines of code, complexity, file organization, code quality, duplication, following best practices)
-- Security considerations and potential vulnerabilities
-- Documentation completeness and quality
-- Testing coverage and framework usage
-
-## Development Activity
-- Git history analysis (commit frequency, contributor engagement)
-- Recent development trends and focus areas
-- Release management and versioning strategy
-
-## Strengths and Opportunities
-- Key strengths of the codebase
-- Potential areas for improvement
-- Technical debt assessment
-- Recommendations for future development
-
-## Risk Assessment
-- Security vulnerabilities or concerns
-- Outdated dependencies or compatibility issues
-- Maintenance challenges or scalability concerns
-
-Provide your analysis in a clear, professional format with specific examples from the data when relevant. Be concise but thorough, focusing on actionable insights that would help developers understand and improve the project.")
-        .build();
-
-    // Perform analysis
-    match analyzer.analyze_repository(repo_url).await {
-        Ok(mut analysis) => {
-            info!("Analysis completed successfully!");
+// The offline phase executed inside the container, on the mounted repository.
+// The host decides on the README summary, through --readme-summary
+fn analyze_local(run: &Run, args: cli::AnalyzeLocalCommand) -> Result<()> {
+    let overrides = overrides::load_overrides(args.overrides.as_deref().map(Path::new))?;
+    let remote: RemoteRepositoryData =
+        serde_json::from_str(&std::fs::read_to_string(&args.remote)?)?;
+    let analyzer = run.analyzer(&args.analysis, args.analysis.readme_summary)?;
+
+    let mut analysis = analyzer.analyze_local(
+        remote,
+        Path::new(&args.path),
+        &checkpoint::CheckpointStore::disabled(),
+    )?;
+    overrides.apply(&mut analysis);
+    analysis.metadata_suggestions = topics::suggest(&analysis);
+    analysis.plugin_sections = plugins::run_plugins(&run.config.plugins, &analysis);
+    let output = match run.output_format {
+        cli::OutputFormat::Yaml => analyzer.export_analysis_yaml(&analysis)?,
+        _ => analyzer.export_analysis_json(&analysis)?,
+    };
+    run.write(&output, "Local analysis")
+}
+
+async fn release_diff(run: &Run, args: cli::ReleaseDiffCommand) -> Result<()> {
+    let ai_analyst = if args.ai_summary {
+        let Some(api_key) = gemini_key() else {
+            eprintln!("Error: --ai-summary needs GEMINI_API_KEY");
+            std::process::exit(1);
+        };
+        Some(run.ai_analyst(&args.ai, &api_key)?)
+    } else {
+        None
+    };
+    let summarize_readme = args.analysis.readme_summary || gemini_key().is_none();
+    let analyzer = run.analyzer(&args.analysis, summarize_readme)?;
 
-            // Generate AI-powered technical report
-            info!("Generating AI-powered technical report...");
-            match serde_json::to_string_pretty(&analysis) {
-                Ok(analysis_json) => {
-                    match ai_agent.prompt(&format!("Please analyze this repository data and generate a comprehensive technical report:\n\n{}", analysis_json)).await {
-                        Ok(response) => {
-                            analysis.ai_insights = Some(response);
-                            info!("AI report generated successfully!");
-                        }
-                        Err(e) => {
-                            warn!("Failed to generate AI report: {}. Proceeding with standard analysis.", e);
-                        }
+    cancel::install_signal_handlers();
+    let release_future = async {
+        let mut diff = analyzer
+            .release_diff(&args.url, &args.from_tag, &args.to_tag)
+            .await?;
+        if let Some(ai_analyst) = &ai_analyst {
+            diff.ai_summary = ai_analyst.summarize_release(&diff).await;
+        }
+        Ok::<_, anyhow::Error>(diff)
+    };
+    let diff = tokio::select! {
+        diff = release_future => diff?,
+        _ = cancel::cancelled() => {
+            eprintln!("Release diff cancelled");
+            std::process::exit(130);
+        }
+    };
+    run.write(&run.serialize(&diff)?, "Release diff")?;
+
+    eprintln!("\n=== {}..{} ===", diff.from_tag, diff.to_tag);
+    eprintln!(
+        "{} commits by {} contributors, {} files changed (+{} -{})",
+        diff.commit_count,
+        diff.contributors.len(),
+        diff.files_changed,
+        diff.insertions,
+        diff.deletions
+    );
+    if let Some(summary) = &diff.ai_summary {
+        eprintln!("\n{}", summary);
+    }
+    Ok(())
+}
+
+async fn export_context(run: &Run, args: cli::ExportContextCommand) -> Result<()> {
+    if !Path::new(&args.target).is_dir() {
+        run.warn_without_token(&args.target);
+    }
+    let summarize_readme = args.analysis.readme_summary || gemini_key().is_none();
+    let analyzer = run.analyzer(&args.analysis, summarize_readme)?;
+
+    cancel::install_signal_handlers();
+    let context = tokio::select! {
+        context = analyzer.export_context(&args.target, args.format, args.max_tokens) => context?,
+        _ = cancel::cancelled() => {
+            eprintln!("Context export cancelled");
+            std::process::exit(130);
+        }
+    };
+    if let Some(file_path) = &run.output_file {
+        std::fs::write(file_path, &context)?;
+        info!("Context saved to: {}", file_path);
+    } else {
+        print!("{}", context);
+    }
+    Ok(())
+}
+
+// Rendering a saved analysis needs neither the network nor the analyzer
+fn render_report(run: &Run, args: cli::ReportCommand) -> Result<()> {
+    let analysis = read_analysis(&args.file)?;
+    let mut appendix = Vec::new();
+    if let Some(path) = &args.appendix {
+        let assessment: types::AssessmentReport =
+            serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        appendix = if args.appendix_answers.is_empty() {
+            assessment.answers
+        } else {
+            let mut selected = Vec::new();
+            for number in &args.appendix_answers {
+                match assessment.answers.get(number - 1) {
+                    Some(answer) => selected.push(answer.clone()),
+                    None => {
+                        eprintln!("Error: {} has no answer {}", path, number);
+                        std::process::exit(1);
                     }
                 }
-                Err(e) => {
-                    warn!("Failed to serialize analysis for AI: {}. Proceeding with standard analysis.", e);
-                }
             }
+            selected
+        };
+    }
+    if let Some(max_tokens) = run.llm_context_tokens {
+        let context = llm_context::render(
+            &analysis,
+            max_tokens,
+            run.output_format == cli::OutputFormat::Yaml,
+        )?;
+        return run.write(&context, "LLM context");
+    }
+    let theme = &run.config.theme;
+    report::theme::check_theme(theme)?;
+    let rendered = if run.output_format == cli::OutputFormat::Onepager {
+        report::onepager::render(&analysis, args.format, theme)?
+    } else {
+        report::render(&analysis, &appendix, args.format, theme)?
+    };
+
+    if let Some(file_path) = &run.output_file {
+        std::fs::write(file_path, &rendered)?;
+        info!("Report saved to: {}", file_path);
+    } else {
+        use std::io::Write;
+        std::io::stdout().write_all(&rendered)?;
+    }
+    Ok(())
+}
+
+// Exits with 2 when the newer analysis introduces security findings and 3
+// when any of them is high or critical
+fn security_diff(run: &Run, args: cli::DiffCommand) -> Result<()> {
+    let delta = diff::security_delta(&read_analysis(&args.old)?, &read_analysis(&args.new)?);
+    diff::print_security_delta(&delta);
+
+    if let Some(file_path) = &run.output_file {
+        std::fs::write(file_path, serde_json::to_string_pretty(&delta)?)?;
+        info!("Security delta saved to: {}", file_path);
+    }
+    std::process::exit(diff::exit_code(&delta));
+}
+
+// Exits with 2 when any threshold is violated. Takes a saved analysis instead
+// of a repository URL too, which already has its overrides
+async fn check(run: &Run, args: cli::CheckCommand) -> Result<()> {
+    let analysis = if Path::new(&args.target).is_file() {
+        read_analysis(&args.target)?
+    } else {
+        run.warn_without_token(&args.target);
+        let overrides = overrides::load_overrides(args.overrides.as_deref().map(Path::new))?;
+        let summarize_readme = args.analysis.readme_summary || gemini_key().is_none();
+        let analyzer = run.analyzer(&args.analysis, summarize_readme)?;
 
-            // Export analysis
-            let output = match output_format.as_str() {
-                "yaml" => analyzer.export_analysis_yaml(&analysis)?,
-                "json" | _ => analyzer.export_analysis_json(&analysis)?,
-            };
-
-            // Write to file or stdout
-            if let Some(file_path) = output_file {
-                std::fs::write(&file_path, &output)?;
-                info!("Analysis saved to: {}", file_path);
-            } else {
-                println!("{}", output);
+        cancel::install_signal_handlers();
+        let mut analysis = tokio::select! {
+            analysis = analyzer.analyze_repository(&args.target) => analysis?,
+            _ = cancel::cancelled() => {
+                eprintln!("Check cancelled");
+                std::process::exit(130);
             }
+        };
+        overrides.apply(&mut analysis);
+        analysis
+    };
+
+    let report = check::run_checks(&analysis, &run.config.check);
+    check::print_report(&report);
+    if let Some(file_path) = &run.output_file {
+        std::fs::write(file_path, serde_json::to_string_pretty(&report)?)?;
+        info!("Check report saved to: {}", file_path);
+    }
 
-            // Print summary to stderr so it doesn't interfere with output
-            eprintln!("\n=== Analysis Summary ===");
-            eprintln!("{}", analysis.analysis_summary);
-            eprintln!("========================");
+    // With the token, GitHub Actions needs the checks: write permission
+    if args.publish {
+        let Some(head_sha) = args.sha.or_else(|| std::env::var("GITHUB_SHA").ok()) else {
+            eprintln!("Error: --publish requires --sha <commit> or GITHUB_SHA");
+            std::process::exit(1);
+        };
+        let (owner, repo) = utils::parse_github_url(&analysis.url)?;
+        let client =
+            github::GitHubClient::for_publishing(run.github_token.clone(), &owner, &repo).await?;
+        let annotations = check::annotations(&analysis, &run.config.check);
+        let check_run_url = client
+            .publish_check_run(&owner, &repo, &head_sha, &report, &annotations)
+            .await?;
+        info!("Check run published: {}", check_run_url);
+    }
+    if !report.passed {
+        std::process::exit(check::EXIT_VIOLATIONS);
+    }
+    Ok(())
+}
+
+// Without --pr the comment is only printed, for a dry run
+async fn pr_comment(run: &Run, args: cli::PrCommentCommand) -> Result<()> {
+    let analysis = read_analysis(&args.file)?;
+    let baseline = args.baseline.as_deref().map(read_analysis).transpose()?;
+    let body = report::comment::render(&analysis, baseline.as_ref());
+
+    let Some(pr_number) = args.pr else {
+        println!("{}", body);
+        return Ok(());
+    };
+    let (owner, repo) = utils::parse_github_url(&analysis.url)?;
+    let client =
+        github::GitHubClient::for_publishing(run.github_token.clone(), &owner, &repo).await?;
+    let comment_url = client
+        .upsert_pr_comment(
+            &owner,
+            &repo,
+            pr_number,
+            &body,
+            report::comment::COMMENT_MARKER,
+        )
+        .await?;
+    info!("Comment posted: {}", comment_url);
+    Ok(())
+}
+
+// The positional argument is the config listing the tracked repositories,
+// loaded as the analyzer config of the run
+async fn run_schedule(run: &Run, args: cli::ScheduleCommand) -> Result<()> {
+    let summarize_readme = args.analysis.readme_summary || gemini_key().is_none();
+    let analyzer = run.analyzer(&args.analysis, summarize_readme)?;
+    cancel::install_signal_handlers();
+    schedule::run(&analyzer, &run.config.schedule, args.once).await
+}
+
+// Without --apply the suggestions are only printed
+async fn suggest_metadata(run: &Run, args: cli::SuggestMetadataCommand) -> Result<()> {
+    let analysis = read_analysis(&args.file)?;
+    let suggestions = topics::suggest(&analysis);
+    topics::print_suggestions(&suggestions);
+
+    if !args.apply {
+        return Ok(());
+    }
+    let (owner, repo) = utils::parse_github_url(&analysis.url)?;
+    let client =
+        github::GitHubClient::for_publishing(run.github_token.clone(), &owner, &repo).await?;
+    topics::apply(&client, &owner, &repo, &suggestions).await
+}
+
+// Runs an analysis to its end. A cancelled one writes what it saved and exits
+// with 130, the checkpoints written so far stay in the work dir for --resume
+async fn run_analysis(
+    run: &Run,
+    analyzer: &RepositoryAnalyzer,
+    repo_url: &str,
+    analysis: impl Future<Output = Result<types::RepositoryAnalysis>>,
+) -> Result<types::RepositoryAnalysis> {
+    let result = tokio::select! {
+        result = analysis => result,
+        _ = cancel::cancelled() => Err(anyhow::anyhow!("Analysis cancelled")),
+    };
+
+    if result.is_err() && cancel::is_cancelled() {
+        let cancelled = types::CancelledAnalysis {
+            url: repo_url.to_string(),
+            cancelled: true,
+            cancelled_at: chrono::Utc::now(),
+            checkpointed_phases: analyzer
+                .checkpoints(repo_url)
+                .map(|checkpoints| checkpoints.saved_phases())
+                .unwrap_or_default(),
+        };
+        run.write(&run.serialize(&cancelled)?, "Cancelled analysis")?;
+        eprintln!("Analysis cancelled, rerun with --resume to continue");
+        std::process::exit(130);
+    }
+
+    match result {
+        Ok(analysis) => {
+            info!("Analysis completed successfully!");
+            Ok(analysis)
         }
         Err(e) => {
             error!("Analysis failed: {}", e);
@@ -181,6 +655,77 @@ Provide your analysis in a clear, professional format with specific examples fro
             std::process::exit(1);
         }
     }
+}
+
+// AI insights, reusing cached ones for unchanged inputs, then the overrides,
+// the output and the notifications. On cancellation the completed analysis is
+// still written, marked as cancelled
+async fn finish_analysis(
+    run: &Run,
+    analyzer: &RepositoryAnalyzer,
+    mut analysis: types::RepositoryAnalysis,
+    ai_analyst: Option<&ai::AiAnalyst>,
+    overrides: &overrides::Overrides,
+    notify_args: &cli::NotifyArgs,
+) -> Result<()> {
+    if let Some(ai_analyst) = ai_analyst {
+        tokio::select! {
+            (insights, tags) = async {
+                (
+                    ai_analyst.generate_insights(&analysis).await,
+                    ai_analyst.tag_repository(&analysis).await,
+                )
+            } => {
+                analysis.ai_insights = Some(insights);
+                analysis.tags = tags;
+            }
+            _ = cancel::cancelled() => {
+                analysis.cancelled = true;
+            }
+        }
+    }
+    // After tagging, so pinned tags replace the AI ones
+    overrides.apply(&mut analysis);
+    analysis.metadata_suggestions = topics::suggest(&analysis);
+    analysis.plugin_sections = plugins::run_plugins(&run.config.plugins, &analysis);
+    analysis.telemetry = Some(telemetry::resource_usage(
+        run.started_at,
+        analyzer.work_dir(),
+    ));
+
+    let output = match (run.llm_context_tokens, run.output_format) {
+        (Some(max_tokens), format) => {
+            llm_context::render(&analysis, max_tokens, format == cli::OutputFormat::Yaml)?
+        }
+        (None, cli::OutputFormat::Yaml) => analyzer.export_analysis_yaml(&analysis)?,
+        (None, _) => analyzer.export_analysis_json(&analysis)?,
+    };
+    run.write(&output, "Analysis")?;
+
+    // A failed notification doesn't fail the analysis
+    if !notify_args.webhooks.is_empty() {
+        let baseline = notify_args
+            .baseline
+            .as_deref()
+            .map(read_analysis)
+            .transpose()?;
+        for webhook in &notify_args.webhooks {
+            if let Err(e) = notify::send_summary(
+                webhook,
+                &analysis,
+                baseline.as_ref(),
+                notify_args.report_url.as_deref(),
+            )
+            .await
+            {
+                warn!("Failed to send notification: {}", e);
+            }
+        }
+    }
 
+    // Print summary to stderr so it doesn't interfere with output
+    eprintln!("\n=== Analysis Summary ===");
+    eprintln!("{}", analysis.analysis_summary);
+    eprintln!("========================");
     Ok(())
 }
@@ -0,0 +1,183 @@
+use anyhow::Result;
+use log::info;
+use serde_json::{Value, json};
+
+use crate::network::{self, SendLogged};
+use crate::report::key_metrics;
+use crate::telemetry;
+use crate::types::RepositoryAnalysis;
+use crate::utils::detection_names;
+
+// Chat platform a webhook URL belongs to, which decides the payload shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WebhookKind {
+    Slack,
+    Discord,
+    Teams,
+}
+
+impl WebhookKind {
+    // Unknown hosts get the Slack format, which Mattermost and Rocket.Chat also accept
+    fn from_url(webhook_url: &str) -> Self {
+        let host = url::Url::parse(webhook_url)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.to_lowercase()))
+            .unwrap_or_default();
+        if host.ends_with("discord.com") || host.ends_with("discordapp.com") {
+            WebhookKind::Discord
+        } else if host.ends_with("webhook.office.com") || host.ends_with("logic.azure.com") {
+            WebhookKind::Teams
+        } else {
+            WebhookKind::Slack
+        }
+    }
+}
+
+// One line of the card: metric name and its value, with the change when a
+// baseline analysis is given
+struct Fact {
+    name: String,
+    value: String,
+}
+
+// Posts a summary card of the analysis to a Slack, Teams or Discord webhook
+pub async fn send_summary(
+    webhook_url: &str,
+    analysis: &RepositoryAnalysis,
+    baseline: Option<&RepositoryAnalysis>,
+    report_url: Option<&str>,
+) -> Result<()> {
+    let kind = WebhookKind::from_url(webhook_url);
+    let title = format!("Repository analysis: {}", analysis.metadata.full_name);
+    let link = report_url.unwrap_or(&analysis.metadata.html_url);
+    let description = description(analysis);
+    let facts = facts(analysis, baseline);
+
+    let payload = match kind {
+        WebhookKind::Slack => slack_payload(&title, &description, &facts, link),
+        WebhookKind::Discord => discord_payload(&title, &description, &facts, link, analysis),
+        WebhookKind::Teams => teams_payload(&title, &description, &facts, link),
+    };
+
+    info!("Sending {:?} notification", kind);
+    let response = network::http_client()
+        .post(webhook_url)
+        .json(&payload)
+        .send_logged()
+        .await?;
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "Webhook returned {} - {}",
+            response.status(),
+            telemetry::text(response).await?
+        );
+    }
+    Ok(())
+}
+
+fn description(analysis: &RepositoryAnalysis) -> String {
+    let project = &analysis.project_info;
+    let mut parts = Vec::new();
+    if let Some(language) = &project.primary_language {
+        parts.push(language.clone());
+    }
+    parts.extend(detection_names(&project.project_type));
+    if analysis.repository_status.read_only {
+        parts.push("read-only".to_string());
+    }
+
+    let activity = &analysis.git_analysis.activity;
+    let activity = format!(
+        "{} commits in the last 90 days ({})",
+        activity.commits_last_90_days, activity.trend
+    );
+    if parts.is_empty() {
+        activity
+    } else {
+        format!("{}. {}", parts.join(", "), activity)
+    }
+}
+
+fn facts(analysis: &RepositoryAnalysis, baseline: Option<&RepositoryAnalysis>) -> Vec<Fact> {
+    let before = baseline.map(key_metrics);
+    key_metrics(analysis)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, value))| {
+            let change = before
+                .as_ref()
+                .map(|before| value.round() - before[index].1.round())
+                .filter(|change| *change != 0.0);
+            Fact {
+                name: name.to_string(),
+                value: match change {
+                    Some(change) => format!("{:.0} ({:+.0})", value, change),
+                    None => format!("{:.0}", value),
+                },
+            }
+        })
+        .collect()
+}
+
+fn slack_payload(title: &str, description: &str, facts: &[Fact], link: &str) -> Value {
+    let fields: Vec<Value> = facts
+        .iter()
+        .map(|fact| json!({ "type": "mrkdwn", "text": format!("*{}*\n{}", fact.name, fact.value) }))
+        .collect();
+    json!({
+        "text": title,
+        "blocks": [
+            { "type": "header", "text": { "type": "plain_text", "text": title } },
+            { "type": "section", "text": { "type": "mrkdwn", "text": description } },
+            { "type": "section", "fields": fields },
+            { "type": "section", "text": { "type": "mrkdwn", "text": format!("<{}|Full report>", link) } },
+        ],
+    })
+}
+
+fn discord_payload(
+    title: &str,
+    description: &str,
+    facts: &[Fact],
+    link: &str,
+    analysis: &RepositoryAnalysis,
+) -> Value {
+    let fields: Vec<Value> = facts
+        .iter()
+        .map(|fact| json!({ "name": fact.name, "value": fact.value, "inline": true }))
+        .collect();
+    json!({
+        "embeds": [{
+            "title": title,
+            "url": link,
+            "description": description,
+            "fields": fields,
+            "timestamp": analysis.analyzed_at.to_rfc3339(),
+        }],
+    })
+}
+
+// Adaptive card, accepted by both Workflows and Office 365 connector webhooks
+fn teams_payload(title: &str, description: &str, facts: &[Fact], link: &str) -> Value {
+    let facts: Vec<Value> = facts
+        .iter()
+        .map(|fact| json!({ "title": fact.name, "value": fact.value }))
+        .collect();
+    json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": "application/vnd.microsoft.card.adaptive",
+            "content": {
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "type": "AdaptiveCard",
+                "version": "1.4",
+                "body": [
+                    { "type": "TextBlock", "text": title, "weight": "Bolder", "size": "Medium", "wrap": true },
+                    { "type": "TextBlock", "text": description, "wrap": true },
+                    { "type": "FactSet", "facts": facts },
+                ],
+                "actions": [{ "type": "Action.OpenUrl", "title": "Full report", "url": link }],
+            },
+        }],
+    })
+}
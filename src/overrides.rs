@@ -0,0 +1,450 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use globset::{Glob, GlobMatcher};
+use log::warn;
+use serde::Deserialize;
+
+use crate::ai::{TAG_CATEGORIES, normalize_tags};
+use crate::analyzers::risk::{RiskAssessor, overall_level};
+use crate::types::{AppliedOverride, Detection, RepositoryAnalysis, RepositoryTag, RiskCategory};
+use crate::utils::{detection_names, to_slash_path};
+
+// Overrides file read from the working directory when --overrides is not given
+pub const DEFAULT_OVERRIDES_FILE: &str = "analyzer-overrides.toml";
+
+// Findings a suppression can remove
+const SUPPRESSIBLE_KINDS: &[&str] = &[
+    "secret",
+    "dependency",
+    "framework",
+    "error-handling",
+    "panic-hotspot",
+    "unsafe-hotspot",
+    "rule",
+];
+
+// Classifications a pin can set, besides tag.<category> and risk.<category>
+const PINNABLE_FIELDS: &[&str] = &["primary_language", "project_type", "frameworks"];
+
+const RISK_LEVELS: &[&str] = &["low", "medium", "high"];
+
+// Human corrections to the analysis: suppressed false positives, notes and
+// pinned classifications. They are applied to every new analysis and recorded
+// in it, so reports rendered later keep them
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Overrides {
+    suppress: Vec<Suppression>,
+    note: Vec<Note>,
+    pin: Vec<Pin>,
+    #[serde(skip)]
+    source: PathBuf,
+}
+
+// Removes the findings of a kind matching every criterion given
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Suppression {
+    kind: String,
+    path: Option<String>, // Glob relative to the repository root
+    line: Option<u32>,
+    name: Option<String>, // Dependency, framework, secret kind or error-handling kind
+    reason: String,
+    author: Option<String>,
+    #[serde(skip)]
+    matcher: Option<GlobMatcher>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Note {
+    text: String,
+    path: Option<String>, // File or directory the note is about
+    author: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Pin {
+    field: String,
+    value: PinValue,
+    reason: Option<String>,
+    author: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PinValue {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl PinValue {
+    fn values(&self) -> Vec<String> {
+        match self {
+            PinValue::One(value) => vec![value.clone()],
+            PinValue::Many(values) => values.clone(),
+        }
+    }
+}
+
+// An explicit overrides path must exist, the default one is optional
+pub fn load_overrides(path: Option<&Path>) -> Result<Overrides> {
+    let (content, source) = match path {
+        Some(path) => (fs::read_to_string(path)?, path.to_path_buf()),
+        None => match fs::read_to_string(DEFAULT_OVERRIDES_FILE) {
+            Ok(content) => (content, PathBuf::from(DEFAULT_OVERRIDES_FILE)),
+            Err(_) => return Ok(Overrides::default()),
+        },
+    };
+    let mut overrides: Overrides = toml::from_str(&content)
+        .with_context(|| format!("invalid overrides file {}", source.display()))?;
+    overrides.source = source;
+
+    for suppression in &mut overrides.suppress {
+        if !SUPPRESSIBLE_KINDS.contains(&suppression.kind.as_str()) {
+            anyhow::bail!(
+                "cannot suppress {} findings, expected one of {}",
+                suppression.kind,
+                SUPPRESSIBLE_KINDS.join(", ")
+            );
+        }
+        if suppression.path.is_none() && suppression.name.is_none() {
+            anyhow::bail!(
+                "suppression of {} findings needs a path or a name",
+                suppression.kind
+            );
+        }
+        if let Some(path) = &suppression.path {
+            suppression.matcher = Some(Glob::new(path)?.compile_matcher());
+        }
+    }
+    for pin in &overrides.pin {
+        validate_pin(pin)?;
+    }
+    Ok(overrides)
+}
+
+fn validate_pin(pin: &Pin) -> Result<()> {
+    let values = pin.value.values();
+    if let Some(category) = pin.field.strip_prefix("tag.") {
+        if !TAG_CATEGORIES.contains(&category) {
+            anyhow::bail!(
+                "cannot pin tag {}, expected one of {}",
+                category,
+                TAG_CATEGORIES.join(", ")
+            );
+        }
+    } else if pin.field.starts_with("risk.") {
+        if values.len() != 1 || !RISK_LEVELS.contains(&values[0].as_str()) {
+            anyhow::bail!("pin {} must be one of low, medium or high", pin.field);
+        }
+    } else if !PINNABLE_FIELDS.contains(&pin.field.as_str()) {
+        anyhow::bail!(
+            "cannot pin {}, expected one of {}, tag.<category> or risk.<category>",
+            pin.field,
+            PINNABLE_FIELDS.join(", ")
+        );
+    } else if pin.field == "primary_language" && values.len() != 1 {
+        anyhow::bail!("pin primary_language takes a single value");
+    }
+    Ok(())
+}
+
+impl Overrides {
+    fn is_empty(&self) -> bool {
+        self.suppress.is_empty() && self.note.is_empty() && self.pin.is_empty()
+    }
+
+    // Suppressions go first so the risks are rated without the false
+    // positives, then pins overwrite what was computed
+    pub fn apply(&self, analysis: &mut RepositoryAnalysis) {
+        if self.is_empty() {
+            return;
+        }
+
+        let mut applied = Vec::new();
+        for suppression in &self.suppress {
+            let matched = suppression.remove(analysis);
+            if matched == 0 {
+                warn!(
+                    "Override suppressing {} matched no finding, it may be stale",
+                    suppression.target()
+                );
+            }
+            applied.push(self.record(
+                "suppress",
+                suppression.target(),
+                suppression.reason.clone(),
+                &suppression.author,
+                matched,
+            ));
+        }
+        if !self.suppress.is_empty() {
+            analysis.risk_assessment = RiskAssessor.assess(analysis);
+        }
+
+        for pin in &self.pin {
+            let previous = pin.set(analysis);
+            let mut detail = format!("{} (was {})", pin.value.values().join(", "), previous);
+            if let Some(reason) = &pin.reason {
+                detail.push_str(&format!(": {}", reason));
+            }
+            applied.push(self.record("pin", pin.field.clone(), detail, &pin.author, 0));
+        }
+
+        for note in &self.note {
+            applied.push(
+                self.record(
+                    "note",
+                    note.path
+                        .clone()
+                        .unwrap_or_else(|| "repository".to_string()),
+                    note.text.clone(),
+                    &note.author,
+                    0,
+                ),
+            );
+        }
+
+        analysis.overrides = applied;
+    }
+
+    fn record(
+        &self,
+        action: &str,
+        target: String,
+        detail: String,
+        author: &Option<String>,
+        matched: u32,
+    ) -> AppliedOverride {
+        AppliedOverride {
+            action: action.to_string(),
+            target,
+            detail,
+            author: author.clone(),
+            source: self.source.clone(),
+            matched,
+        }
+    }
+}
+
+impl Suppression {
+    // e.g. secret aws-access-key in tests/fixtures/**:3
+    fn target(&self) -> String {
+        let mut target = self.kind.clone();
+        if let Some(name) = &self.name {
+            target.push_str(&format!(" {}", name));
+        }
+        if let Some(path) = &self.path {
+            target.push_str(&format!(" in {}", path));
+            if let Some(line) = self.line {
+                target.push_str(&format!(":{}", line));
+            }
+        }
+        target
+    }
+
+    // Findings without a line or a name only match when none is asked for
+    fn matches(&self, path: &Path, line: Option<u32>, name: Option<&str>) -> bool {
+        let path_matches = self
+            .matcher
+            .as_ref()
+            .is_none_or(|matcher| matcher.is_match(to_slash_path(path)));
+        let line_matches = self.line.is_none() || self.line == line;
+        let name_matches = match (&self.name, name) {
+            (None, _) => true,
+            (Some(wanted), Some(name)) => wanted.eq_ignore_ascii_case(name),
+            (Some(_), None) => false,
+        };
+        path_matches && line_matches && name_matches
+    }
+
+    // Number of findings removed
+    fn remove(&self, analysis: &mut RepositoryAnalysis) -> u32 {
+        let mut removed = 0;
+        let mut keep = |matched: bool| {
+            if matched {
+                removed += 1;
+            }
+            !matched
+        };
+
+        match self.kind.as_str() {
+            "secret" => analysis.security_info.secret_findings.retain(|finding| {
+                keep(self.matches(
+                    &finding.path,
+                    Some(finding.line as u32),
+                    Some(&finding.kind),
+                ))
+            }),
+            // outdated_dependencies has no paths, its entries go with their evidence
+            "dependency" => {
+                let security = &mut analysis.security_info;
+                let mut labels = Vec::new();
+                security.dependency_evidence.retain(|evidence| {
+                    let name = evidence.label.split(": ").next().unwrap_or_default();
+                    let matched = self.matches(&evidence.path, evidence.line, Some(name));
+                    if matched {
+                        labels.push(evidence.label.clone());
+                    }
+                    keep(matched)
+                });
+                for label in labels {
+                    if let Some(index) = security
+                        .outdated_dependencies
+                        .iter()
+                        .position(|dependency| *dependency == label)
+                    {
+                        security.outdated_dependencies.remove(index);
+                    }
+                }
+            }
+            "framework" => analysis.project_info.frameworks.retain_mut(|framework| {
+                let before = framework.evidence.len();
+                framework.evidence.retain(|evidence| {
+                    keep(self.matches(&evidence.path, evidence.line, Some(&framework.name)))
+                });
+                // A framework stays while other evidence still shows it
+                let named = self
+                    .name
+                    .as_ref()
+                    .is_some_and(|name| name.eq_ignore_ascii_case(&framework.name));
+                if !named || !framework.evidence.is_empty() {
+                    return true;
+                }
+                // Counted by its evidence, or on its own when it had none
+                if before == 0 {
+                    return keep(true);
+                }
+                false
+            }),
+            // The per-language counts keep covering every handler found
+            "error-handling" => {
+                if let Some(errors) = &mut analysis.code_metrics.error_handling {
+                    errors.findings.retain(|finding| {
+                        keep(self.matches(&finding.path, Some(finding.line), Some(&finding.kind)))
+                    });
+                }
+            }
+            "panic-hotspot" => {
+                if let Some(panics) = &mut analysis.code_metrics.panic_density {
+                    panics
+                        .hotspots
+                        .retain(|hotspot| keep(self.matches(&hotspot.path, None, None)));
+                }
+            }
+            "unsafe-hotspot" => {
+                if let Some(usage) = &mut analysis.security_info.unsafe_usage {
+                    for entry in &mut usage.crates {
+                        entry
+                            .hotspots
+                            .retain(|hotspot| keep(self.matches(&hotspot.path, None, None)));
+                    }
+                }
+            }
+            // Matched by the rule id
+            "rule" => analysis.rule_findings.retain(|finding| {
+                keep(self.matches(&finding.path, finding.line, Some(&finding.rule)))
+            }),
+            _ => {}
+        }
+        removed
+    }
+}
+
+impl Pin {
+    // Sets the field and returns its previous value for the record
+    fn set(&self, analysis: &mut RepositoryAnalysis) -> String {
+        let values = self.value.values();
+        let info = &mut analysis.project_info;
+        let previous = |values: &[String]| {
+            if values.is_empty() {
+                "none".to_string()
+            } else {
+                values.join(", ")
+            }
+        };
+
+        if let Some(category) = self.field.strip_prefix("tag.") {
+            let old: Vec<String> = analysis
+                .tags
+                .iter()
+                .filter(|tag| tag.category == category)
+                .map(|tag| tag.value.clone())
+                .collect();
+            let mut tags: Vec<RepositoryTag> = analysis
+                .tags
+                .drain(..)
+                .filter(|tag| tag.category != category)
+                .collect();
+            tags.extend(values.into_iter().map(|value| RepositoryTag {
+                category: category.to_string(),
+                value,
+                confidence: 1.0,
+            }));
+            analysis.tags = normalize_tags(tags);
+            return previous(&old);
+        }
+
+        if let Some(category) = self.field.strip_prefix("risk.") {
+            let risks = &mut analysis.risk_assessment;
+            let level = values[0].clone();
+            let evidence = format!(
+                "Rated {} by a reviewer{}.",
+                level,
+                self.reason
+                    .as_ref()
+                    .map(|reason| format!(": {}", reason))
+                    .unwrap_or_default()
+            );
+            let old = match risks.categories.iter_mut().find(|c| c.category == category) {
+                Some(existing) => {
+                    existing.evidence.push(evidence);
+                    std::mem::replace(&mut existing.level, level)
+                }
+                None => {
+                    risks.categories.push(RiskCategory {
+                        category: category.to_string(),
+                        level,
+                        evidence: vec![evidence],
+                    });
+                    "none".to_string()
+                }
+            };
+            risks.overall = overall_level(&risks.categories);
+            return old;
+        }
+
+        match self.field.as_str() {
+            "primary_language" => info
+                .primary_language
+                .replace(values[0].clone())
+                .unwrap_or_else(|| "none".to_string()),
+            "project_type" => previous(&pin_detections(&mut info.project_type, values)),
+            "frameworks" => previous(&pin_detections(&mut info.frameworks, values)),
+            _ => String::new(),
+        }
+    }
+}
+
+// Replaces the detections with the pinned names, certain now, keeping the
+// evidence of those that were detected too. Returns the previous names
+fn pin_detections(detections: &mut Vec<Detection>, names: Vec<String>) -> Vec<String> {
+    let previous = std::mem::take(detections);
+    *detections = names
+        .into_iter()
+        .map(|name| Detection {
+            evidence: previous
+                .iter()
+                .find(|d| d.name.eq_ignore_ascii_case(&name))
+                .map(|d| d.evidence.clone())
+                .unwrap_or_default(),
+            name,
+            confidence: 1.0,
+        })
+        .collect();
+    detection_names(&previous)
+}
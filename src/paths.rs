@@ -0,0 +1,133 @@
+// Where the analyzer keeps its files. Persistent caches (AI responses) live in
+// the user cache directory and are evicted by size, the clones, checkpoints
+// and container outputs of a run live in the workspace under the temp dir.
+// Directories follow the XDG base directory spec on Unix, and the platform
+// conventions on macOS and Windows. `[paths]` in analyzer.toml overrides them
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use log::{info, warn};
+use serde::Deserialize;
+use walkdir::WalkDir;
+
+const APP_DIR: &str = "ai-repo-analyzer";
+
+const DEFAULT_CACHE_MAX_SIZE_MB: u64 = 512;
+
+// `[paths]` in analyzer.toml, directories relative to the config file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathsConfig {
+    pub cache_dir: Option<PathBuf>,
+    pub work_dir: Option<PathBuf>,
+    pub cache_max_size_mb: u64,
+}
+
+impl Default for PathsConfig {
+    fn default() -> Self {
+        Self {
+            cache_dir: None,
+            work_dir: None,
+            cache_max_size_mb: DEFAULT_CACHE_MAX_SIZE_MB,
+        }
+    }
+}
+
+// analyzer.toml in the current directory, else in the user config directory
+pub fn default_config_file() -> Option<PathBuf> {
+    let local = PathBuf::from(crate::config::DEFAULT_CONFIG_FILE);
+    if local.is_file() {
+        return Some(local);
+    }
+    config_dir()
+        .map(|dir| dir.join(crate::config::DEFAULT_CONFIG_FILE))
+        .filter(|path| path.is_file())
+}
+
+// ~/.config/ai-repo-analyzer, ~/Library/Application Support/ai-repo-analyzer
+// or %APPDATA%\ai-repo-analyzer
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "macos") {
+        home_dir().map(|home| home.join("Library/Application Support"))
+    } else if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        xdg_dir("XDG_CONFIG_HOME").or_else(|| home_dir().map(|home| home.join(".config")))
+    };
+    base.map(|base| base.join(APP_DIR))
+}
+
+// ~/.cache/ai-repo-analyzer, ~/Library/Caches/ai-repo-analyzer or
+// %LOCALAPPDATA%\ai-repo-analyzer, the temp dir without a home
+pub fn cache_dir() -> PathBuf {
+    let base = if cfg!(target_os = "macos") {
+        home_dir().map(|home| home.join("Library/Caches"))
+    } else if cfg!(windows) {
+        env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else {
+        xdg_dir("XDG_CACHE_HOME").or_else(|| home_dir().map(|home| home.join(".cache")))
+    };
+    base.map(|base| base.join(APP_DIR))
+        .unwrap_or_else(|| env::temp_dir().join(format!("{}-cache", APP_DIR)))
+}
+
+// Clones, checkpoints and container outputs, removed by the system with the
+// rest of the temp dir
+pub fn work_dir() -> PathBuf {
+    env::temp_dir().join(APP_DIR)
+}
+
+// Deletes the least recently used cache files until the cache fits in
+// max_bytes
+pub fn evict_cache(dir: &Path, max_bytes: u64) {
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some((metadata.modified().ok()?, metadata.len(), entry.into_path()))
+        })
+        .collect();
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    if total <= max_bytes {
+        return;
+    }
+
+    files.sort_by_key(|(modified, _, _)| *modified);
+    let mut freed = 0;
+    for (_, size, path) in files {
+        if total <= max_bytes {
+            break;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                total -= size;
+                freed += size;
+            }
+            Err(e) => warn!("Failed to evict {}: {}", path.display(), e),
+        }
+    }
+    info!(
+        "Evicted {} KB from the cache at {}",
+        freed / 1024,
+        dir.display()
+    );
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+// Relative values are invalid per the spec and ignored
+fn xdg_dir(var: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+}
@@ -0,0 +1,133 @@
+// Third-party analyzers that add sections to the report without forking the
+// crate. A plugin is a WebAssembly module listed under `[plugins]` in
+// analyzer.toml. It gets the file index and manifests as JSON and returns its
+// sections as JSON. Plugins run with no imports, so no file system, network or
+// clock, and within fuel and memory limits. The wasmtime runtime is behind the
+// wasm-plugins feature.
+
+#[cfg(feature = "wasm-plugins")]
+mod wasm;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{ConfigFile, FileInfo, PluginSection, RepositoryAnalysis};
+
+// Roughly the instructions a plugin may execute, and its memory
+const DEFAULT_FUEL: u64 = 10_000_000_000;
+const DEFAULT_MAX_MEMORY_MB: usize = 256;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PluginConfig {
+    pub wasm: Vec<PathBuf>, // Relative to the config file
+    pub fuel: u64,
+    pub max_memory_mb: usize,
+}
+
+impl Default for PluginConfig {
+    fn default() -> Self {
+        Self {
+            wasm: Vec::new(),
+            fuel: DEFAULT_FUEL,
+            max_memory_mb: DEFAULT_MAX_MEMORY_MB,
+        }
+    }
+}
+
+// What a plugin receives
+#[derive(Serialize)]
+struct PluginInput<'a> {
+    url: &'a str,
+    full_name: &'a str,
+    head_sha: Option<&'a str>,
+    primary_language: Option<&'a str>,
+    files: &'a [FileInfo],
+    config_files: &'a [ConfigFile],
+}
+
+// What a plugin returns
+#[derive(Deserialize)]
+struct PluginOutput {
+    #[serde(default)]
+    sections: Vec<PluginSection>,
+}
+
+// Plugin files must exist and the runtime must be built in when any is listed
+pub fn check_plugins(config: &PluginConfig) -> Result<()> {
+    if config.wasm.is_empty() {
+        return Ok(());
+    }
+
+    if config.fuel == 0 || config.max_memory_mb == 0 {
+        anyhow::bail!("plugins fuel and max_memory_mb must be positive");
+    }
+    for plugin in &config.wasm {
+        fs::metadata(plugin).with_context(|| format!("plugin {}", plugin.display()))?;
+    }
+    if !cfg!(feature = "wasm-plugins") {
+        anyhow::bail!(
+            "WASM plugins are not part of this build, rebuild with --features wasm-plugins"
+        );
+    }
+    Ok(())
+}
+
+// Sections of every plugin, in the configured order. A failing plugin is
+// logged and skipped, it doesn't fail the analysis
+pub fn run_plugins(config: &PluginConfig, analysis: &RepositoryAnalysis) -> Vec<PluginSection> {
+    if config.wasm.is_empty() {
+        return Vec::new();
+    }
+    let input = PluginInput {
+        url: &analysis.url,
+        full_name: &analysis.metadata.full_name,
+        head_sha: analysis.git_analysis.head_sha.as_deref(),
+        primary_language: analysis.project_info.primary_language.as_deref(),
+        files: analysis.file_structure.files(),
+        config_files: &analysis.config_files,
+    };
+    let input = match serde_json::to_vec(&input) {
+        Ok(input) => input,
+        Err(e) => {
+            warn!("Failed to serialize the plugin input: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut sections = Vec::new();
+    for path in &config.wasm {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        info!("Running plugin {}...", name);
+        let output = run_wasm(config, path, &input).and_then(|output| {
+            serde_json::from_slice::<PluginOutput>(&output)
+                .context("plugin output is not valid JSON")
+        });
+        match output {
+            Ok(output) => sections.extend(output.sections.into_iter().map(|mut section| {
+                section.plugin = name.clone();
+                section
+            })),
+            Err(e) => warn!("Plugin {} failed: {:#}", path.display(), e),
+        }
+    }
+    sections
+}
+
+#[cfg(feature = "wasm-plugins")]
+fn run_wasm(config: &PluginConfig, path: &Path, input: &[u8]) -> Result<Vec<u8>> {
+    wasm::run(path, input, config.fuel, config.max_memory_mb)
+}
+
+// check_plugins refuses plugins without the runtime
+#[cfg(not(feature = "wasm-plugins"))]
+fn run_wasm(_config: &PluginConfig, _path: &Path, _input: &[u8]) -> Result<Vec<u8>> {
+    anyhow::bail!("WASM plugins are not part of this build")
+}
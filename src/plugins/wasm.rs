@@ -0,0 +1,50 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+// The plugin ABI: the module exports its `memory`, `alloc(len) -> ptr` for the
+// host to write the input into, and `analyze(ptr, len) -> i64` returning the
+// output's pointer in the high 32 bits and its length in the low ones. Both
+// input and output are UTF-8 JSON
+pub fn run(path: &Path, input: &[u8], fuel: u64, max_memory_mb: usize) -> Result<Vec<u8>> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::from_file(&engine, path)?;
+    // Nothing is provided to import, the plugin only computes
+    if let Some(import) = module.imports().next() {
+        anyhow::bail!(
+            "plugins cannot import anything, it imports {}::{}",
+            import.module(),
+            import.name()
+        );
+    }
+
+    let limits = StoreLimitsBuilder::new()
+        .memory_size(max_memory_mb * 1024 * 1024)
+        .instances(1)
+        .build();
+    let mut store = Store::new(&engine, limits);
+    store.limiter(|limits: &mut StoreLimits| limits);
+    store.set_fuel(fuel)?;
+
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let memory = instance
+        .get_memory(&mut store, "memory")
+        .context("the plugin exports no memory")?;
+    let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+    let analyze = instance.get_typed_func::<(i32, i32), i64>(&mut store, "analyze")?;
+
+    let length = i32::try_from(input.len()).context("the plugin input is too large")?;
+    let pointer = alloc.call(&mut store, length)?;
+    memory.write(&mut store, pointer as u32 as usize, input)?;
+
+    let packed = analyze.call(&mut store, (pointer, length))? as u64;
+    let (pointer, length) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+    let mut output = vec![0; length];
+    memory
+        .read(&store, pointer, &mut output)
+        .context("the plugin output is outside its memory")?;
+    Ok(output)
+}
@@ -0,0 +1,61 @@
+use std::future::Future;
+use std::time::Instant;
+
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::types::{AnalysisPhase, ProgressEvent};
+
+// Events go to the sink of the task running the analysis; outside of
+// `track` there is none and they are dropped
+tokio::task_local! {
+    static SINK: UnboundedSender<ProgressEvent>;
+}
+
+// Run `work` with its progress events sent to the returned receiver, which
+// ends once `work` has completed
+pub fn track<F: Future>(
+    work: F,
+) -> (
+    UnboundedReceiver<ProgressEvent>,
+    impl Future<Output = F::Output>,
+) {
+    let (sender, receiver) = mpsc::unbounded();
+    (receiver, SINK.scope(sender, work))
+}
+
+pub fn emit(event: ProgressEvent) {
+    let _ = SINK.try_with(|sink| sink.unbounded_send(event));
+}
+
+// Emits PhaseStarted now and PhaseFinished when dropped, also on errors
+pub struct PhaseGuard {
+    phase: AnalysisPhase,
+    started_at: Instant,
+}
+
+pub fn phase(phase: AnalysisPhase) -> PhaseGuard {
+    emit(ProgressEvent::PhaseStarted { phase });
+    PhaseGuard {
+        phase,
+        started_at: Instant::now(),
+    }
+}
+
+impl Drop for PhaseGuard {
+    fn drop(&mut self) {
+        emit(ProgressEvent::PhaseFinished {
+            phase: self.phase,
+            duration_ms: self.started_at.elapsed().as_millis() as u64,
+        });
+    }
+}
+
+// log::warn! that also reaches the progress stream
+macro_rules! warning {
+    ($($arg:tt)+) => {{
+        let message = format!($($arg)+);
+        log::warn!("{}", message);
+        $crate::progress::emit($crate::types::ProgressEvent::Warning { message });
+    }};
+}
+pub(crate) use warning;
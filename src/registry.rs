@@ -0,0 +1,336 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use reqwest::Client;
+
+use crate::{
+    network::{self, SendLogged},
+    telemetry,
+    types::{ConfigFile, PackagePublishing, PublishedPackage},
+};
+
+// Maximum number of packages looked up per repository
+const MAX_PACKAGES: usize = 10;
+
+// A package declared by one of the repository manifests
+struct DeclaredPackage {
+    registry: &'static str,
+    name: String,
+    version: Option<String>,
+    manifest_path: PathBuf,
+}
+
+// Release history collected from a registry
+struct RegistryRelease {
+    version: String,
+    published_at: Option<DateTime<Utc>>,
+    yanked: bool,
+}
+
+// Package registry client for crates.io, npm and PyPI, which also checks docs links
+pub struct RegistryClient {
+    client: Client,
+}
+
+impl Default for RegistryClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RegistryClient {
+    pub fn new() -> Self {
+        Self {
+            client: network::http_client(),
+        }
+    }
+
+    pub async fn fetch_publishing(&self, config_files: &[ConfigFile]) -> PackagePublishing {
+        let mut packages = Vec::new();
+
+        for declared in self.declared_packages(config_files) {
+            info!(
+                "Fetching {} metadata for package {}",
+                declared.registry, declared.name
+            );
+            let result = match declared.registry {
+                "crates.io" => self.fetch_crate(&declared).await,
+                "npm" => self.fetch_npm_package(&declared).await,
+                _ => self.fetch_pypi_package(&declared).await,
+            };
+
+            match result {
+                Ok(Some(package)) => packages.push(package),
+                Ok(None) => info!("Package {} is not published", declared.name),
+                Err(e) => warn!("Failed to fetch package {}: {}", declared.name, e),
+            }
+        }
+
+        PackagePublishing { packages }
+    }
+
+    fn declared_packages(&self, config_files: &[ConfigFile]) -> Vec<DeclaredPackage> {
+        let mut declared = Vec::new();
+        let mut seen = HashSet::new();
+
+        for config in config_files {
+            let package = match config.file_type.as_str() {
+                "cargo" => self.declared_crate(config),
+                "npm" => self.declared_npm_package(config),
+                "python" => self.declared_python_package(config),
+                _ => None,
+            };
+
+            if let Some(package) = package
+                && seen.insert((package.registry, package.name.clone()))
+            {
+                declared.push(package);
+            }
+        }
+
+        declared.truncate(MAX_PACKAGES);
+        declared
+    }
+
+    fn declared_crate(&self, config: &ConfigFile) -> Option<DeclaredPackage> {
+        let toml: toml::Value = config.content.parse().ok()?;
+        let package = toml.get("package")?;
+        if package.get("publish").and_then(|p| p.as_bool()) == Some(false) {
+            return None;
+        }
+
+        Some(DeclaredPackage {
+            registry: "crates.io",
+            name: package.get("name")?.as_str()?.to_string(),
+            version: package
+                .get("version")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            manifest_path: config.path.clone(),
+        })
+    }
+
+    fn declared_npm_package(&self, config: &ConfigFile) -> Option<DeclaredPackage> {
+        let json: serde_json::Value = serde_json::from_str(&config.content).ok()?;
+        if json["private"].as_bool() == Some(true) {
+            return None;
+        }
+
+        Some(DeclaredPackage {
+            registry: "npm",
+            name: json["name"].as_str()?.to_string(),
+            version: json["version"].as_str().map(|s| s.to_string()),
+            manifest_path: config.path.clone(),
+        })
+    }
+
+    fn declared_python_package(&self, config: &ConfigFile) -> Option<DeclaredPackage> {
+        let toml: toml::Value = config.content.parse().ok()?;
+        let project = toml
+            .get("project")
+            .or_else(|| toml.get("tool").and_then(|tool| tool.get("poetry")))?;
+
+        Some(DeclaredPackage {
+            registry: "pypi",
+            name: project.get("name")?.as_str()?.to_string(),
+            version: project
+                .get("version")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            manifest_path: config.path.clone(),
+        })
+    }
+
+    // Whether a GET of the URL ends in a success status, after redirects
+    pub async fn url_resolves(&self, url: &str) -> Result<bool> {
+        let response = self.client.get(url).send_logged().await?;
+        Ok(response.status().is_success())
+    }
+
+    async fn get_json(&self, url: &str) -> Result<Option<serde_json::Value>> {
+        let response = self.client.get(url).send_logged().await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("{} returned {}", url, response.status());
+        }
+
+        Ok(Some(telemetry::json(response).await?))
+    }
+
+    async fn fetch_crate(&self, declared: &DeclaredPackage) -> Result<Option<PublishedPackage>> {
+        let url = format!("https://crates.io/api/v1/crates/{}", declared.name);
+        let Some(data) = self.get_json(&url).await? else {
+            return Ok(None);
+        };
+
+        let releases = data["versions"]
+            .as_array()
+            .unwrap_or(&Vec::new())
+            .iter()
+            .map(|v| RegistryRelease {
+                version: v["num"].as_str().unwrap_or("").to_string(),
+                published_at: parse_date(v["created_at"].as_str()),
+                yanked: v["yanked"].as_bool().unwrap_or(false),
+            })
+            .collect();
+
+        Ok(Some(
+            self.build_package(
+                declared,
+                data["crate"]["max_stable_version"]
+                    .as_str()
+                    .or_else(|| data["crate"]["max_version"].as_str())
+                    .map(|s| s.to_string()),
+                data["crate"]["downloads"].as_u64(),
+                data["crate"]["recent_downloads"].as_u64(),
+                releases,
+                format!("https://crates.io/crates/{}", declared.name),
+            ),
+        ))
+    }
+
+    async fn fetch_npm_package(
+        &self,
+        declared: &DeclaredPackage,
+    ) -> Result<Option<PublishedPackage>> {
+        // Scoped package names keep the @ but escape the slash
+        let encoded_name = declared.name.replace('/', "%2f");
+        let url = format!("https://registry.npmjs.org/{}", encoded_name);
+        let Some(data) = self.get_json(&url).await? else {
+            return Ok(None);
+        };
+
+        // npm has no yanking; deprecated versions are the closest equivalent
+        let releases = data["versions"]
+            .as_object()
+            .map(|versions| {
+                versions
+                    .iter()
+                    .map(|(version, info)| RegistryRelease {
+                        version: version.clone(),
+                        published_at: parse_date(data["time"][version].as_str()),
+                        yanked: info["deprecated"].is_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let downloads_url = format!(
+            "https://api.npmjs.org/downloads/point/last-month/{}",
+            declared.name
+        );
+        let recent_downloads = self
+            .get_json(&downloads_url)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|d| d["downloads"].as_u64());
+
+        Ok(Some(self.build_package(
+            declared,
+            data["dist-tags"]["latest"].as_str().map(|s| s.to_string()),
+            None,
+            recent_downloads,
+            releases,
+            format!("https://www.npmjs.com/package/{}", declared.name),
+        )))
+    }
+
+    async fn fetch_pypi_package(
+        &self,
+        declared: &DeclaredPackage,
+    ) -> Result<Option<PublishedPackage>> {
+        let url = format!("https://pypi.org/pypi/{}/json", declared.name);
+        let Some(data) = self.get_json(&url).await? else {
+            return Ok(None);
+        };
+
+        // A release is published when its first file is uploaded and yanked when all files are
+        let releases = data["releases"]
+            .as_object()
+            .map(|releases| {
+                releases
+                    .iter()
+                    .map(|(version, files)| {
+                        let files = files.as_array().cloned().unwrap_or_default();
+                        RegistryRelease {
+                            version: version.clone(),
+                            published_at: files
+                                .iter()
+                                .filter_map(|f| parse_date(f["upload_time_iso_8601"].as_str()))
+                                .min(),
+                            yanked: !files.is_empty()
+                                && files.iter().all(|f| f["yanked"].as_bool() == Some(true)),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Some(self.build_package(
+            declared,
+            data["info"]["version"].as_str().map(|s| s.to_string()),
+            None,
+            None,
+            releases,
+            format!("https://pypi.org/project/{}/", declared.name),
+        )))
+    }
+
+    fn build_package(
+        &self,
+        declared: &DeclaredPackage,
+        latest_version: Option<String>,
+        total_downloads: Option<u64>,
+        recent_downloads: Option<u64>,
+        releases: Vec<RegistryRelease>,
+        package_url: String,
+    ) -> PublishedPackage {
+        let mut publish_dates: Vec<DateTime<Utc>> =
+            releases.iter().filter_map(|r| r.published_at).collect();
+        publish_dates.sort();
+
+        let average_days_between_releases = if publish_dates.len() > 1 {
+            let span = *publish_dates.last().unwrap() - publish_dates[0];
+            Some(span.num_hours() as f64 / 24.0 / (publish_dates.len() - 1) as f64)
+        } else {
+            None
+        };
+
+        PublishedPackage {
+            registry: declared.registry.to_string(),
+            name: declared.name.clone(),
+            manifest_path: declared.manifest_path.clone(),
+            manifest_version: declared.version.clone(),
+            manifest_matches_latest: match (&declared.version, &latest_version) {
+                (Some(manifest), Some(latest)) => Some(manifest == latest),
+                _ => None,
+            },
+            latest_version,
+            total_downloads,
+            recent_downloads,
+            version_count: releases.len() as u32,
+            yanked_versions: releases
+                .iter()
+                .filter(|r| r.yanked)
+                .map(|r| r.version.clone())
+                .collect(),
+            first_published: publish_dates.first().copied(),
+            last_published: publish_dates.last().copied(),
+            average_days_between_releases,
+            package_url,
+        }
+    }
+}
+
+fn parse_date(value: Option<&str>) -> Option<DateTime<Utc>> {
+    value
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
@@ -0,0 +1,132 @@
+use crate::diff;
+use crate::types::RepositoryAnalysis;
+
+use super::{Block, Report, Section, key_metrics, markdown, section};
+
+// Hidden marker that identifies the bot's comment, so reruns edit it in place
+pub const COMMENT_MARKER: &str = "<!-- ai-repo-analyzer -->";
+
+// Largest source files listed when there is no baseline
+const HOTSPOTS: usize = 3;
+
+// Condensed health summary for a pull request comment, with the change from
+// the baseline analysis when one is given
+pub fn render(analysis: &RepositoryAnalysis, baseline: Option<&RepositoryAnalysis>) -> String {
+    let mut sections = vec![health(analysis, baseline)];
+    sections.push(match baseline {
+        Some(baseline) => security_changes(baseline, analysis),
+        None => hotspots(analysis),
+    });
+
+    let report = Report {
+        title: format!("Repository health: {}", analysis.metadata.full_name),
+        sections: sections
+            .into_iter()
+            .filter(|section| !section.blocks.is_empty())
+            .collect(),
+        footer: format!(
+            "ai-repo-analyzer, analysis of {}",
+            analysis.analyzed_at.format("%Y-%m-%d %H:%M UTC")
+        ),
+    };
+    format!("{}\n{}", COMMENT_MARKER, markdown::render(&report))
+}
+
+fn health(analysis: &RepositoryAnalysis, baseline: Option<&RepositoryAnalysis>) -> Section {
+    let current = key_metrics(analysis);
+    let Some(baseline) = baseline else {
+        let rows = current
+            .iter()
+            .map(|(name, value)| vec![name.to_string(), format!("{:.0}", value)])
+            .collect();
+        return section(
+            "Health",
+            vec![Block::Table {
+                headers: vec!["Metric".to_string(), "Value".to_string()],
+                rows,
+            }],
+        );
+    };
+
+    let rows = current
+        .iter()
+        .zip(key_metrics(baseline))
+        .map(|((name, value), (_, before))| {
+            let change = value.round() - before.round();
+            vec![
+                name.to_string(),
+                format!("{:.0}", before),
+                format!("{:.0}", value),
+                if change == 0.0 {
+                    "-".to_string()
+                } else {
+                    format!("{:+.0}", change)
+                },
+            ]
+        })
+        .collect();
+    section(
+        "Health",
+        vec![Block::Table {
+            headers: vec![
+                "Metric".to_string(),
+                "Baseline".to_string(),
+                "Now".to_string(),
+                "Change".to_string(),
+            ],
+            rows,
+        }],
+    )
+}
+
+fn security_changes(baseline: &RepositoryAnalysis, analysis: &RepositoryAnalysis) -> Section {
+    let delta = diff::security_delta(baseline, analysis);
+    let mut blocks = Vec::new();
+
+    if !delta.introduced.is_empty() {
+        blocks.push(Block::Paragraph("**Introduced**".to_string()));
+        blocks.push(Block::List(
+            delta
+                .introduced
+                .iter()
+                .map(|change| format!("`{}` {}", change.severity, change.description))
+                .collect(),
+        ));
+    }
+    if !delta.resolved.is_empty() {
+        blocks.push(Block::Paragraph("**Resolved**".to_string()));
+        blocks.push(Block::List(
+            delta
+                .resolved
+                .iter()
+                .map(|change| change.description.clone())
+                .collect(),
+        ));
+    }
+
+    section("Security changes", blocks)
+}
+
+fn hotspots(analysis: &RepositoryAnalysis) -> Section {
+    let files = &analysis.code_metrics.most_complex_files;
+    if files.is_empty() {
+        return section("Largest files", Vec::new());
+    }
+
+    section(
+        "Largest files",
+        vec![Block::List(
+            files
+                .iter()
+                .take(HOTSPOTS)
+                .map(|file| {
+                    format!(
+                        "`{}` ({} lines of code)",
+                        file.path.display(),
+                        file.lines_of_code.unwrap_or(0)
+                    )
+                })
+                .collect(),
+        )],
+    )
+}
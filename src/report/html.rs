@@ -0,0 +1,237 @@
+use std::fs;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use log::warn;
+use regex::Regex;
+
+use super::theme::Theme;
+use super::{Block, Report};
+
+const STYLE: &str = "body{font-family:-apple-system,Segoe UI,Helvetica,Arial,sans-serif;max-width:960px;margin:2rem auto;padding:0 1rem;color:#1f2328;line-height:1.5}\
+h1{border-bottom:1px solid #d0d7de;padding-bottom:.3em}h2{margin-top:2rem;border-bottom:1px solid #d0d7de;padding-bottom:.2em}\
+table{border-collapse:collapse;margin:1rem 0}th,td{border:1px solid #d0d7de;padding:4px 10px;text-align:left}th{background:#f6f8fa}\
+code,pre{background:#f6f8fa;border-radius:4px}pre{padding:12px;overflow:auto}footer{margin-top:3rem;color:#656d76;font-size:.9em}";
+
+// Renders <pre class="mermaid"> blocks in the browser, the only script of the page
+const MERMAID_SCRIPT: &str = "<script type=\"module\">import mermaid from 'https://cdn.jsdelivr.net/npm/mermaid@11/dist/mermaid.esm.min.mjs';mermaid.initialize({startOnLoad:true});</script>\n";
+
+pub fn render(report: &Report, theme: &Theme) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}{}</style>\n</head>\n<body>\n",
+        escape(&report.title),
+        STYLE,
+        theme_style(theme)
+    );
+    if let Some(logo) = logo_data_uri(theme) {
+        out.push_str(&format!(
+            "<img class=\"logo\" src=\"{}\" alt=\"{}\">\n",
+            logo,
+            escape(theme.company_name.as_deref().unwrap_or("Logo"))
+        ));
+    }
+    out.push_str(&format!("<h1>{}</h1>\n", escape(&report.title)));
+    if let Some(company) = &theme.company_name {
+        out.push_str(&format!(
+            "<p class=\"company\">Prepared by {}</p>\n",
+            escape(company)
+        ));
+    }
+
+    for section in &report.sections {
+        out.push_str(&format!("<h2>{}</h2>\n", escape(&section.heading)));
+        for block in &section.blocks {
+            match block {
+                Block::Paragraph(text) => out.push_str(&format!("<p>{}</p>\n", linkify(text))),
+                Block::List(items) => {
+                    out.push_str("<ul>\n");
+                    for item in items {
+                        out.push_str(&format!("<li>{}</li>\n", linkify(item)));
+                    }
+                    out.push_str("</ul>\n");
+                }
+                Block::Table { headers, rows } => {
+                    out.push_str("<table>\n<tr>");
+                    for header in headers {
+                        out.push_str(&format!("<th>{}</th>", escape(header)));
+                    }
+                    out.push_str("</tr>\n");
+                    for row in rows {
+                        out.push_str("<tr>");
+                        for cell in row {
+                            out.push_str(&format!("<td>{}</td>", linkify(cell)));
+                        }
+                        out.push_str("</tr>\n");
+                    }
+                    out.push_str("</table>\n");
+                }
+                Block::Markdown(markdown) => out.push_str(&markdown_to_html(markdown)),
+                Block::Mermaid(diagram) => out.push_str(&format!(
+                    "<pre class=\"mermaid\">{}</pre>\n",
+                    escape(diagram)
+                )),
+            }
+        }
+    }
+
+    let has_diagrams = report
+        .sections
+        .iter()
+        .flat_map(|section| &section.blocks)
+        .any(|block| matches!(block, Block::Mermaid(_)));
+    if has_diagrams {
+        out.push_str(MERMAID_SCRIPT);
+    }
+
+    out.push_str(&format!(
+        "<footer>{}</footer>\n</body>\n</html>\n",
+        escape(theme.footer.as_deref().unwrap_or(&report.footer))
+    ));
+    out
+}
+
+// Rules added after STYLE for the theme colors
+fn theme_style(theme: &Theme) -> String {
+    let mut style = String::from(".logo{max-height:64px;display:block}.company{color:#656d76}");
+    if let Some(primary) = &theme.primary_color {
+        style.push_str(&format!("h1,h2,h3{{color:{}}}", primary));
+    }
+    if let Some((r, g, b)) = theme.accent_rgb() {
+        // Dark text on light accents, white on dark ones
+        let luminance = 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64;
+        let text = if luminance > 150.0 {
+            "#1f2328"
+        } else {
+            "#ffffff"
+        };
+        style.push_str(&format!(
+            "h1,h2{{border-bottom-color:rgb({r},{g},{b})}}th{{background:rgb({r},{g},{b});color:{text}}}footer{{border-top:2px solid rgb({r},{g},{b});padding-top:.5em}}"
+        ));
+    }
+    style
+}
+
+// The logo embedded in the page, so the report stays a single file
+fn logo_data_uri(theme: &Theme) -> Option<String> {
+    let path = theme.logo.as_ref()?;
+    let mime = match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .as_deref()
+    {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("svg") => "image/svg+xml",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        _ => {
+            warn!("Unsupported logo format: {}", path.display());
+            return None;
+        }
+    };
+    match fs::read(path) {
+        Ok(bytes) => Some(format!("data:{};base64,{}", mime, STANDARD.encode(bytes))),
+        Err(e) => {
+            warn!("Failed to read logo {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Enough Markdown for model output: headings, lists, code blocks, paragraphs and
+// bold/code spans. Everything is escaped first, so no raw HTML gets through
+fn markdown_to_html(markdown: &str) -> String {
+    let mut out = String::new();
+    let mut in_list = false;
+    let mut in_code = false;
+    let mut paragraph: Vec<String> = Vec::new();
+
+    let flush = |out: &mut String, paragraph: &mut Vec<String>| {
+        if !paragraph.is_empty() {
+            out.push_str(&format!("<p>{}</p>\n", inline(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    };
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") {
+            flush(&mut out, &mut paragraph);
+            out.push_str(if in_code {
+                "</code></pre>\n"
+            } else {
+                "<pre><code>"
+            });
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            out.push_str(&format!("{}\n", escape(line)));
+            continue;
+        }
+
+        let item = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "));
+        if item.is_none() && in_list {
+            out.push_str("</ul>\n");
+            in_list = false;
+        }
+
+        if let Some(item) = item {
+            flush(&mut out, &mut paragraph);
+            if !in_list {
+                out.push_str("<ul>\n");
+                in_list = true;
+            }
+            out.push_str(&format!("<li>{}</li>\n", inline(item)));
+        } else if trimmed.starts_with('#') {
+            flush(&mut out, &mut paragraph);
+            // Model headings sit below the report's own h2
+            let level = (trimmed.chars().take_while(|c| *c == '#').count() + 2).min(6);
+            out.push_str(&format!(
+                "<h{}>{}</h{}>\n",
+                level,
+                inline(trimmed.trim_start_matches('#').trim()),
+                level
+            ));
+        } else if trimmed.is_empty() {
+            flush(&mut out, &mut paragraph);
+        } else {
+            paragraph.push(trimmed.to_string());
+        }
+    }
+
+    flush(&mut out, &mut paragraph);
+    if in_list {
+        out.push_str("</ul>\n");
+    }
+    if in_code {
+        out.push_str("</code></pre>\n");
+    }
+    out
+}
+
+// Escapes the text and turns [text](https://...) evidence links into anchors
+fn linkify(text: &str) -> String {
+    let link = Regex::new(r"\[([^\]]+)\]\((https?://[^)\s]+)\)").unwrap();
+    link.replace_all(&escape(text), "<a href=\"$2\">$1</a>")
+        .to_string()
+}
+
+fn inline(text: &str) -> String {
+    let escaped = linkify(text);
+    let bold = Regex::new(r"\*\*([^*]+)\*\*").unwrap();
+    let code = Regex::new(r"`([^`]+)`").unwrap();
+    let escaped = bold.replace_all(&escaped, "<strong>$1</strong>");
+    code.replace_all(&escaped, "<code>$1</code>").to_string()
+}
@@ -0,0 +1,46 @@
+use super::{Block, Report};
+
+pub fn render(report: &Report) -> String {
+    let mut out = format!("# {}\n", report.title);
+
+    for section in &report.sections {
+        out.push_str(&format!("\n## {}\n", section.heading));
+        for block in &section.blocks {
+            out.push('\n');
+            match block {
+                Block::Paragraph(text) => out.push_str(&format!("{}\n", text)),
+                Block::List(items) => {
+                    for item in items {
+                        out.push_str(&format!("- {}\n", item));
+                    }
+                }
+                Block::Table { headers, rows } => {
+                    out.push_str(&table_row(headers));
+                    out.push_str(&table_row(&vec!["---".to_string(); headers.len()]));
+                    for row in rows {
+                        out.push_str(&table_row(row));
+                    }
+                }
+                // Headings are pushed below the section heading
+                Block::Markdown(markdown) => {
+                    for line in markdown.lines() {
+                        if line.starts_with('#') {
+                            out.push_str(&format!("##{}\n", line));
+                        } else {
+                            out.push_str(&format!("{}\n", line));
+                        }
+                    }
+                }
+                Block::Mermaid(diagram) => out.push_str(&format!("```mermaid\n{}\n```\n", diagram)),
+            }
+        }
+    }
+
+    out.push_str(&format!("\n---\n\n_{}_\n", report.footer));
+    out
+}
+
+fn table_row(cells: &[String]) -> String {
+    let cells: Vec<String> = cells.iter().map(|cell| cell.replace('|', "\\|")).collect();
+    format!("| {} |\n", cells.join(" | "))
+}
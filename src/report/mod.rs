@@ -0,0 +1,1846 @@
+// Human-readable reports rendered from a saved RepositoryAnalysis. The analysis is
+// first laid out as sections of simple blocks, which each format then renders.
+
+pub mod comment;
+mod html;
+mod markdown;
+pub mod onepager;
+mod pdf;
+pub mod theme;
+
+use anyhow::Result;
+
+use self::theme::Theme;
+use crate::types::{
+    CommitTopic, DeploymentTopology, Detection, EvidenceRef, PluginBlock, PluginSection,
+    QuestionAnswer, RepositoryAnalysis, RuleFinding,
+};
+use crate::utils::distinct_labels;
+
+// Detections less certain than this are marked as guesses
+const WEAK_DETECTION: f64 = 0.5;
+
+// Number of rows shown in per-file and per-contributor tables
+const TABLE_ROWS: usize = 10;
+
+// Quarters of the affiliation history shown in the report
+const AFFILIATION_QUARTERS: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+    Pdf,
+}
+
+impl ReportFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.to_lowercase().as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" => Some(Self::Html),
+            "pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+}
+
+pub struct Report {
+    pub title: String,
+    pub sections: Vec<Section>,
+    pub footer: String,
+}
+
+pub struct Section {
+    pub heading: String,
+    pub blocks: Vec<Block>,
+}
+
+pub enum Block {
+    Paragraph(String),
+    List(Vec<String>),
+    Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    // Markdown produced elsewhere, such as the AI report
+    Markdown(String),
+    // Mermaid diagram source, drawn where the format can
+    Mermaid(String),
+}
+
+// Q&A exchanges from an assessment are appended to the report when given.
+// The theme brands the HTML and PDF formats
+pub fn render(
+    analysis: &RepositoryAnalysis,
+    appendix: &[QuestionAnswer],
+    format: ReportFormat,
+    theme: &Theme,
+) -> Result<Vec<u8>> {
+    let report = Report::from_analysis(analysis).with_appendix(appendix);
+    render_report(&report, format, theme)
+}
+
+fn render_report(report: &Report, format: ReportFormat, theme: &Theme) -> Result<Vec<u8>> {
+    match format {
+        ReportFormat::Markdown => Ok(markdown::render(report).into_bytes()),
+        ReportFormat::Html => Ok(html::render(report, theme).into_bytes()),
+        ReportFormat::Pdf => pdf::render(report, theme),
+    }
+}
+
+impl Report {
+    pub fn from_analysis(analysis: &RepositoryAnalysis) -> Self {
+        let sections = [
+            overview(analysis),
+            summary(analysis),
+            risks(analysis),
+            overrides(analysis),
+            languages(analysis),
+            code_metrics(analysis),
+            activity(analysis),
+            project(analysis),
+            metadata_suggestions(analysis),
+            getting_started(analysis),
+            modules(analysis),
+            deployment(analysis),
+            quality(analysis),
+            api_docs(analysis),
+            performance(analysis),
+            platform_support(analysis),
+            contributor_friendliness(analysis),
+            security(analysis),
+            rule_findings(analysis),
+            ownership(analysis),
+            sustainability(analysis),
+            planning(analysis),
+            releases(analysis),
+            release_artifacts(analysis),
+            packages(analysis),
+            related_projects(analysis),
+            ai_report(analysis),
+            issue_triage(analysis),
+        ]
+        .into_iter()
+        .chain(analysis.plugin_sections.iter().map(plugin_section))
+        .filter(|section| !section.blocks.is_empty())
+        .collect();
+
+        Report {
+            title: format!("Repository Report: {}", analysis.metadata.full_name),
+            sections,
+            footer: format!(
+                "Generated from the analysis of {} on {}",
+                analysis.url,
+                analysis.analyzed_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+        }
+    }
+
+    pub fn with_appendix(mut self, answers: &[QuestionAnswer]) -> Self {
+        if !answers.is_empty() {
+            self.sections.push(section(
+                "Appendix: Questions and Answers",
+                answers.iter().map(question_answer).collect(),
+            ));
+        }
+        self
+    }
+}
+
+// Headline metrics shared by the pull request comment and notifications
+pub fn key_metrics(analysis: &RepositoryAnalysis) -> Vec<(&'static str, f64)> {
+    let security = &analysis.security_info;
+    vec![
+        ("Files", analysis.code_metrics.total_files as f64),
+        ("Lines of code", analysis.code_metrics.total_loc as f64),
+        (
+            "Max file complexity",
+            analysis.code_metrics.distributions.complexity.max as f64,
+        ),
+        (
+            "Tooling maturity",
+            analysis.developer_tooling.maturity_score,
+        ),
+        (
+            "Build reproducibility",
+            analysis.build_reproducibility.score,
+        ),
+        (
+            "CI security checks",
+            security.ci_security_checks.len() as f64,
+        ),
+        ("Possible secrets", security.secret_findings.len() as f64),
+        (
+            "Unpinned dependencies",
+            security.outdated_dependencies.len() as f64,
+        ),
+    ]
+}
+
+fn section(heading: &str, blocks: Vec<Block>) -> Section {
+    Section {
+        heading: heading.to_string(),
+        blocks,
+    }
+}
+
+fn overview(analysis: &RepositoryAnalysis) -> Section {
+    let metadata = &analysis.metadata;
+    let mut blocks = Vec::new();
+    if let Some(description) = &metadata.description {
+        blocks.push(Block::Paragraph(description.clone()));
+    }
+
+    let mut facts = vec![
+        format!("URL: {}", metadata.html_url),
+        format!(
+            "Stars: {}, forks: {}, open issues: {}",
+            metadata.stargazers_count, metadata.forks_count, metadata.open_issues_count
+        ),
+        format!("Default branch: {}", metadata.default_branch),
+        format!(
+            "Created {}, last push {}",
+            metadata.created_at.format("%Y-%m-%d"),
+            metadata.pushed_at.format("%Y-%m-%d")
+        ),
+    ];
+    if let Some(license) = &metadata.license {
+        facts.push(format!("License: {}", license.name));
+    }
+    if let Some(language) = &analysis.project_info.primary_language {
+        facts.push(format!("Primary language: {}", language));
+    }
+    facts.extend(analysis.repository_status.notes.iter().cloned());
+    if let Some(fork) = &analysis.fork_relationship {
+        facts.push(format!("Fork of {}: {}", fork.parent_full_name, fork.note));
+    }
+    if !analysis.tags.is_empty() {
+        let tags: Vec<String> = analysis
+            .tags
+            .iter()
+            .map(|tag| {
+                format!(
+                    "{}: {} ({:.0}%)",
+                    tag.category,
+                    tag.value,
+                    tag.confidence * 100.0
+                )
+            })
+            .collect();
+        facts.push(format!("Tags: {}", tags.join(", ")));
+    }
+    blocks.push(Block::List(facts));
+
+    section("Overview", blocks)
+}
+
+fn summary(analysis: &RepositoryAnalysis) -> Section {
+    let lines: Vec<String> = analysis
+        .analysis_summary
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.to_string())
+        .collect();
+    let blocks = if lines.is_empty() {
+        Vec::new()
+    } else {
+        vec![Block::List(lines)]
+    };
+    section("Summary", blocks)
+}
+
+fn risks(analysis: &RepositoryAnalysis) -> Section {
+    let risk = &analysis.risk_assessment;
+    if risk.categories.is_empty() {
+        return section("Risk Assessment", Vec::new());
+    }
+
+    let mut overall = format!("Overall risk: {}.", risk.overall);
+    if let Some(bus_factor) = risk.bus_factor {
+        overall.push_str(&format!(" Bus factor: {}.", bus_factor));
+    }
+    let rows = risk
+        .categories
+        .iter()
+        .map(|category| {
+            vec![
+                category.category.clone(),
+                category.level.clone(),
+                category.evidence.join(" "),
+            ]
+        })
+        .collect();
+    section(
+        "Risk Assessment",
+        vec![
+            Block::Paragraph(overall),
+            Block::Table {
+                headers: strings(&["Category", "Level", "Evidence"]),
+                rows,
+            },
+        ],
+    )
+}
+
+fn plugin_section(plugin: &PluginSection) -> Section {
+    let blocks = plugin
+        .blocks
+        .iter()
+        .map(|block| match block {
+            PluginBlock::Paragraph { text } => Block::Paragraph(text.clone()),
+            PluginBlock::List { items } => Block::List(items.clone()),
+            PluginBlock::Table { headers, rows } => Block::Table {
+                headers: headers.clone(),
+                rows: rows.clone(),
+            },
+        })
+        .collect();
+    section(&plugin.heading, blocks)
+}
+
+// Matches of the custom rules, high severity first
+fn rule_findings(analysis: &RepositoryAnalysis) -> Section {
+    let rank = |severity: &str| match severity {
+        "high" => 0,
+        "medium" => 1,
+        _ => 2,
+    };
+    let mut findings: Vec<&RuleFinding> = analysis.rule_findings.iter().collect();
+    findings.sort_by_key(|finding| rank(&finding.severity));
+    let rows: Vec<Vec<String>> = findings
+        .into_iter()
+        .map(|finding| {
+            let location = match finding.line {
+                Some(line) => format!("{}:{}", finding.path.display(), line),
+                None => finding.path.display().to_string(),
+            };
+            vec![
+                finding.rule.clone(),
+                finding.severity.clone(),
+                linked(&location, &finding.url),
+                finding.message.clone(),
+            ]
+        })
+        .collect();
+
+    let mut blocks = Vec::new();
+    if !rows.is_empty() {
+        blocks.push(Block::Table {
+            headers: strings(&["Rule", "Severity", "Location", "Message"]),
+            rows,
+        });
+    }
+    section("Custom Rules", blocks)
+}
+
+// Reviewer notes first, then the suppressions and pins with their provenance
+fn overrides(analysis: &RepositoryAnalysis) -> Section {
+    let by = |author: &Option<String>| {
+        author
+            .as_ref()
+            .map(|author| format!(" ({})", author))
+            .unwrap_or_default()
+    };
+    let notes: Vec<String> = analysis
+        .overrides
+        .iter()
+        .filter(|o| o.action == "note")
+        .map(|o| format!("{}: {}{}", o.target, o.detail, by(&o.author)))
+        .collect();
+    let rows: Vec<Vec<String>> = analysis
+        .overrides
+        .iter()
+        .filter(|o| o.action != "note")
+        .map(|o| {
+            let action = if o.action == "suppress" {
+                format!("suppress ({} removed)", o.matched)
+            } else {
+                o.action.clone()
+            };
+            vec![
+                action,
+                o.target.clone(),
+                o.detail.clone(),
+                o.author.clone().unwrap_or_default(),
+                o.source.display().to_string(),
+            ]
+        })
+        .collect();
+
+    let mut blocks = Vec::new();
+    if !notes.is_empty() {
+        blocks.push(Block::List(notes));
+    }
+    if !rows.is_empty() {
+        blocks.push(Block::Table {
+            headers: strings(&["Override", "Target", "Detail", "By", "Source"]),
+            rows,
+        });
+    }
+    section("Reviewer Notes and Overrides", blocks)
+}
+
+fn languages(analysis: &RepositoryAnalysis) -> Section {
+    let mut stats: Vec<_> = analysis.code_metrics.language_stats.values().collect();
+    stats.sort_by(|a, b| b.percentage.total_cmp(&a.percentage));
+
+    let mut blocks = Vec::new();
+    if !stats.is_empty() {
+        blocks.push(Block::Table {
+            headers: strings(&["Language", "Files", "Lines of code", "Share"]),
+            rows: stats
+                .iter()
+                .map(|s| {
+                    vec![
+                        s.language.clone(),
+                        s.file_count.to_string(),
+                        s.lines_of_code.to_string(),
+                        format!("{:.1}%", s.percentage),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    let layout = &analysis.project_info.language_layout;
+    if !layout.boundaries.is_empty() {
+        blocks.push(Block::Paragraph(format!(
+            "Layout: {}",
+            layout.classification
+        )));
+        blocks.push(Block::List(
+            layout
+                .boundaries
+                .iter()
+                .map(|b| {
+                    format!(
+                        "{}/ is {} ({:.0}%)",
+                        b.directory.display(),
+                        b.language,
+                        b.share
+                    )
+                })
+                .collect(),
+        ));
+    }
+
+    section("Languages", blocks)
+}
+
+fn code_metrics(analysis: &RepositoryAnalysis) -> Section {
+    let metrics = &analysis.code_metrics;
+    let mut blocks = vec![Block::List(vec![
+        format!("Files: {}", metrics.total_files),
+        format!(
+            "Lines: {} ({} code, {} comments, {} blank)",
+            metrics.total_lines,
+            metrics.total_loc,
+            metrics.total_comment_lines,
+            metrics.total_blank_lines
+        ),
+        format!("Size: {} KB", metrics.total_size / 1024),
+        format!(
+            "Lines of code per file: median {}, p90 {}",
+            metrics.distributions.lines_of_code.p50, metrics.distributions.lines_of_code.p90
+        ),
+    ])];
+
+    if !metrics.longest_functions.is_empty() {
+        blocks.push(Block::Paragraph("Longest functions".to_string()));
+        blocks.push(Block::Table {
+            headers: strings(&["Function", "Location", "Lines", "Complexity"]),
+            rows: metrics
+                .longest_functions
+                .iter()
+                .take(TABLE_ROWS)
+                .map(|f| {
+                    vec![
+                        f.name.clone(),
+                        format!("{}:{}", f.file.display(), f.start_line),
+                        f.lines_of_code.to_string(),
+                        f.complexity.to_string(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    if !metrics.largest_files.is_empty() {
+        blocks.push(Block::Paragraph("Largest files".to_string()));
+        blocks.push(Block::Table {
+            headers: strings(&["File", "Size", "Lines of code"]),
+            rows: metrics
+                .largest_files
+                .iter()
+                .take(TABLE_ROWS)
+                .map(|f| {
+                    vec![
+                        f.path.display().to_string(),
+                        format!("{} KB", f.size / 1024),
+                        f.lines_of_code.map(|l| l.to_string()).unwrap_or_default(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    if let Some(panics) = &metrics.panic_density {
+        blocks.push(Block::Paragraph(format!(
+            "Error-handling hygiene: {:.0}/100, {:.1} panicking calls per KLOC of non-test Rust code ({} unwrap, {} expect, {} panic!, {} todo!/unimplemented! in {} lines).",
+            panics.score,
+            panics.per_kloc,
+            panics.unwraps,
+            panics.expects,
+            panics.panics,
+            panics.todos,
+            panics.rust_loc
+        )));
+        if !panics.hotspots.is_empty() {
+            blocks.push(Block::List(
+                panics
+                    .hotspots
+                    .iter()
+                    .take(TABLE_ROWS)
+                    .map(|h| {
+                        format!(
+                            "{} ({} panicking calls)",
+                            linked(&h.path.display().to_string(), &h.url),
+                            h.count
+                        )
+                    })
+                    .collect(),
+            ));
+        }
+    }
+
+    if let Some(errors) = &metrics.error_handling {
+        blocks.push(Block::Paragraph("Error handling".to_string()));
+        blocks.push(Block::Table {
+            headers: strings(&[
+                "Language",
+                "Handlers",
+                "Broad catches",
+                "Empty handlers",
+                "Ignored errors",
+                "Issues per KLOC",
+            ]),
+            rows: errors
+                .languages
+                .iter()
+                .map(|l| {
+                    vec![
+                        l.language.clone(),
+                        l.handlers.to_string(),
+                        l.broad_catches.to_string(),
+                        l.empty_handlers.to_string(),
+                        l.ignored_errors.to_string(),
+                        format!("{:.1}", l.issues_per_kloc),
+                    ]
+                })
+                .collect(),
+        });
+        if !errors.findings.is_empty() {
+            blocks.push(Block::List(
+                errors
+                    .findings
+                    .iter()
+                    .take(TABLE_ROWS)
+                    .map(|f| {
+                        let location = format!("{}:{}", f.path.display(), f.line);
+                        format!("{} at {}", f.kind, linked(&location, &f.url))
+                    })
+                    .collect(),
+            ));
+        }
+    }
+
+    if !analysis.embedded_code.is_empty() {
+        blocks.push(Block::Paragraph(
+            "Embedded third-party code, left out of the metrics".to_string(),
+        ));
+        blocks.push(Block::Table {
+            headers: strings(&["Directory", "Project", "Kind", "Files", "Size"]),
+            rows: analysis
+                .embedded_code
+                .iter()
+                .map(|e| {
+                    // Totals of trees too large to count in full are lower bounds
+                    let more = if e.partial_count { "+" } else { "" };
+                    vec![
+                        e.path.display().to_string(),
+                        linked(&e.project, &e.source),
+                        e.kind.clone(),
+                        format!("{}{}", e.file_count, more),
+                        format!("{} KB{}", e.total_size / 1024, more),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    section("Code Metrics", blocks)
+}
+
+fn activity(analysis: &RepositoryAnalysis) -> Section {
+    let git = &analysis.git_analysis;
+    let mut facts = vec![
+        format!("Commits analyzed: {}", git.total_commits),
+        format!("Branches: {}, tags: {}", git.branch_count, git.tag_count),
+    ];
+    if !analysis.repository_status.read_only {
+        facts.push(format!(
+            "Last 30 days: {} commits, last 90 days: {} ({})",
+            git.activity.commits_last_30_days,
+            git.activity.commits_last_90_days,
+            git.activity.trend
+        ));
+    }
+    if let Some(last) = git.first_commit_date {
+        facts.push(format!("Latest commit: {}", last.format("%Y-%m-%d")));
+    }
+    if !git.history_contributors.is_empty() {
+        facts.push(format!(
+            "Authors in the history read: {} ({} duplicate identities merged, {} linked to GitHub accounts)",
+            git.history_contributors.len(),
+            git.merged_identities,
+            git.history_contributors.iter().filter(|c| c.id != 0).count()
+        ));
+    }
+    let mut blocks = vec![Block::List(facts)];
+
+    if !git.commit_topics.is_empty() {
+        blocks.push(Block::Paragraph(
+            "What the commits of the last quarters were about".to_string(),
+        ));
+        blocks.push(Block::Table {
+            headers: strings(&["Quarter", "Commits", "Topics"]),
+            rows: git
+                .commit_topics
+                .iter()
+                .map(|quarter| {
+                    vec![
+                        quarter.quarter.clone(),
+                        quarter.commits.to_string(),
+                        describe_topics(&quarter.topics),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    let mut contributors: Vec<_> = git.contributors.iter().collect();
+    contributors.sort_by_key(|c| std::cmp::Reverse(c.contributions.unwrap_or(0)));
+    if !contributors.is_empty() {
+        blocks.push(Block::Table {
+            headers: strings(&["Contributor", "Contributions"]),
+            rows: contributors
+                .iter()
+                .take(TABLE_ROWS)
+                .map(|c| {
+                    vec![
+                        c.login.clone(),
+                        c.contributions.map(|n| n.to_string()).unwrap_or_default(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    section("Development Activity", blocks)
+}
+
+// "CI (12), auth refactoring (8)"
+fn describe_topics(topics: &[CommitTopic]) -> String {
+    topics
+        .iter()
+        .map(|topic| format!("{} ({})", topic.label, topic.commits))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn project(analysis: &RepositoryAnalysis) -> Section {
+    let info = &analysis.project_info;
+    let mut facts: Vec<String> = [
+        ("Project types", &info.project_type),
+        ("Frameworks", &info.frameworks),
+        ("Build tools", &info.build_tools),
+        ("Package managers", &info.package_managers),
+        ("Testing", &info.testing_frameworks),
+        ("CI/CD", &info.ci_cd_tools),
+        ("Deployment", &info.deployment_configs),
+        ("Databases", &info.database_technologies),
+        ("Execution environments", &info.execution_environments),
+    ]
+    .iter()
+    .filter(|(_, values)| !values.is_empty())
+    .map(|(label, values)| {
+        let values: Vec<String> = values.iter().map(detection_item).collect();
+        format!("{}: {}", label, values.join(", "))
+    })
+    .collect();
+
+    let flags = &info.feature_flags;
+    if !flags.systems.is_empty() {
+        facts.push(format!("Feature flags: {}", flags.systems.join(", ")));
+    }
+    if !flags.preprocessor_flags.is_empty() {
+        facts.push(format!(
+            "Preprocessor flags: {}",
+            flags.preprocessor_flags.join(", ")
+        ));
+    }
+    if !analysis.keywords.is_empty() {
+        facts.push(format!("Keywords: {}", analysis.keywords.join(", ")));
+    }
+
+    let mut blocks = Vec::new();
+    if !facts.is_empty() {
+        blocks.push(Block::List(facts));
+    }
+    if !flags.cargo_features.is_empty() {
+        blocks.push(Block::Paragraph("Cargo features".to_string()));
+        blocks.push(Block::Table {
+            headers: strings(&["Feature", "Manifest", "Default", "Enables"]),
+            rows: flags
+                .cargo_features
+                .iter()
+                .map(|f| {
+                    let enables: Vec<String> = f
+                        .features
+                        .iter()
+                        .cloned()
+                        .chain(f.dependencies.iter().map(|d| format!("dep:{}", d)))
+                        .chain(f.dependency_features.iter().cloned())
+                        .collect();
+                    vec![
+                        f.name.clone(),
+                        f.manifest.display().to_string(),
+                        if f.default { "yes" } else { "no" }.to_string(),
+                        enables.join(", "),
+                    ]
+                })
+                .collect(),
+        });
+    }
+    section("Project", blocks)
+}
+
+// Topics and description the repository could declare, from `suggest-metadata`
+fn metadata_suggestions(analysis: &RepositoryAnalysis) -> Section {
+    let suggestions = &analysis.metadata_suggestions;
+    let rows: Vec<Vec<String>> = suggestions
+        .topics
+        .iter()
+        .map(|t| vec!["add".to_string(), t.topic.clone(), t.reason.clone()])
+        .chain(
+            suggestions
+                .remove_topics
+                .iter()
+                .map(|t| vec!["reconsider".to_string(), t.topic.clone(), t.reason.clone()]),
+        )
+        .collect();
+
+    let mut blocks = Vec::new();
+    if !rows.is_empty() {
+        blocks.push(Block::Table {
+            headers: strings(&["Action", "Topic", "Reason"]),
+            rows,
+        });
+    }
+    if let Some(description) = &suggestions.description {
+        blocks.push(Block::Paragraph(format!(
+            "Suggested description: {} ({})",
+            description.suggested,
+            description.reasons.join("; ")
+        )));
+    }
+    section("Topics and Description", blocks)
+}
+
+fn getting_started(analysis: &RepositoryAnalysis) -> Section {
+    let run = &analysis.run_instructions;
+    let mut blocks = Vec::new();
+    let toolchains = &analysis.toolchain_requirements;
+    if !toolchains.requirements.is_empty() {
+        blocks.push(Block::Paragraph("Requirements".to_string()));
+        blocks.push(Block::Table {
+            headers: strings(&[
+                "Language",
+                "Minimum",
+                "Constraint",
+                "Declared in",
+                "CI runs",
+            ]),
+            rows: toolchains
+                .requirements
+                .iter()
+                .map(|requirement| {
+                    let declared_in = requirement
+                        .declared_in
+                        .as_ref()
+                        .map(|evidence| {
+                            format!(
+                                "{} ({})",
+                                linked(&evidence.path.display().to_string(), &evidence.url),
+                                evidence.label
+                            )
+                        })
+                        .unwrap_or_else(|| "-".to_string());
+                    vec![
+                        requirement.language.clone(),
+                        requirement
+                            .minimum
+                            .clone()
+                            .unwrap_or_else(|| "-".to_string()),
+                        requirement
+                            .constraint
+                            .clone()
+                            .unwrap_or_else(|| "-".to_string()),
+                        declared_in,
+                        if requirement.ci_versions.is_empty() {
+                            "-".to_string()
+                        } else {
+                            requirement.ci_versions.join(", ")
+                        },
+                    ]
+                })
+                .collect(),
+        });
+        if !toolchains.signals.is_empty() {
+            blocks.push(Block::List(toolchains.signals.clone()));
+        }
+    }
+    if !run.steps.is_empty() {
+        blocks.push(Block::List(run.steps.clone()));
+    }
+
+    if !run.entry_points.is_empty() {
+        blocks.push(Block::Paragraph("Entry points".to_string()));
+        blocks.push(Block::Table {
+            headers: strings(&["Name", "Kind", "Command", "Defined in"]),
+            rows: run
+                .entry_points
+                .iter()
+                .take(TABLE_ROWS)
+                .map(|e| {
+                    vec![
+                        e.name.clone(),
+                        e.kind.clone(),
+                        e.command.clone().unwrap_or_default(),
+                        e.path.display().to_string(),
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    if !run.tasks.is_empty() {
+        blocks.push(Block::Paragraph(format!(
+            "Tasks: {}",
+            run.tasks
+                .iter()
+                .map(|t| t.command.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+
+    section("Getting Started", blocks)
+}
+
+// AI summaries of the top-level directories, a map for newcomers
+fn modules(analysis: &RepositoryAnalysis) -> Section {
+    let Some(insights) = &analysis.ai_insights else {
+        return section("Modules", Vec::new());
+    };
+    if insights.module_summaries.is_empty() {
+        return section("Modules", Vec::new());
+    }
+
+    let rows = insights
+        .module_summaries
+        .iter()
+        .map(|(name, summary)| {
+            vec![
+                format!("{}/", name),
+                summary.purpose.clone(),
+                summary.key_types.join(", "),
+                summary.dependencies.join(", "),
+            ]
+        })
+        .collect();
+    section(
+        "Modules",
+        vec![Block::Table {
+            headers: strings(&["Module", "Purpose", "Key types", "Depends on"]),
+            rows,
+        }],
+    )
+}
+
+fn deployment(analysis: &RepositoryAnalysis) -> Section {
+    let topology = &analysis.deployment;
+    if topology.services.is_empty() {
+        return section("Deployment", Vec::new());
+    }
+
+    let blocks = vec![
+        Block::Table {
+            headers: strings(&[
+                "Service",
+                "Kind",
+                "Image",
+                "Ports",
+                "Replicas",
+                "CPU request",
+                "Memory request",
+                "Source",
+            ]),
+            rows: topology
+                .services
+                .iter()
+                .map(|s| {
+                    vec![
+                        s.name.clone(),
+                        s.kind.clone(),
+                        s.image.clone().unwrap_or_default(),
+                        s.ports.join(", "),
+                        s.replicas.map(|r| r.to_string()).unwrap_or_default(),
+                        s.cpu_request.clone().unwrap_or_default(),
+                        s.memory_request.clone().unwrap_or_default(),
+                        s.source.display().to_string(),
+                    ]
+                })
+                .collect(),
+        },
+        Block::Mermaid(deployment_diagram(topology)),
+    ];
+    section("Deployment", blocks)
+}
+
+// Services grouped by the file defining them, with their links
+fn deployment_diagram(topology: &DeploymentTopology) -> String {
+    // Quotes would end a Mermaid label
+    let label = |text: &str| text.replace('"', "#quot;");
+    let mut lines = vec!["graph LR".to_string()];
+
+    for (group, source) in topology.sources.iter().enumerate() {
+        lines.push(format!(
+            "  subgraph g{}[\"{}\"]",
+            group,
+            label(&source.display().to_string())
+        ));
+        for (index, service) in topology
+            .services
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.source == *source)
+        {
+            let mut text = format!("{} ({})", service.name, service.kind);
+            if !service.ports.is_empty() {
+                text.push_str(&format!("<br/>{}", service.ports.join(", ")));
+            }
+            lines.push(format!("    s{}[\"{}\"]", index, label(&text)));
+        }
+        lines.push("  end".to_string());
+    }
+    for link in &topology.links {
+        lines.push(format!(
+            "  s{} -->|{}| s{}",
+            link.from, link.relation, link.to
+        ));
+    }
+
+    lines.join("\n")
+}
+
+fn quality(analysis: &RepositoryAnalysis) -> Section {
+    let tooling = &analysis.developer_tooling;
+    let reproducibility = &analysis.build_reproducibility;
+    let templates = &analysis.contribution_templates;
+
+    let mut facts = vec![
+        format!(
+            "Developer tooling: {:.0}/100 ({})",
+            tooling.maturity_score,
+            if tooling.tools.is_empty() {
+                "no tools detected".to_string()
+            } else {
+                tooling
+                    .tools
+                    .iter()
+                    .map(|t| t.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        ),
+        format!("Build reproducibility: {:.0}/100", reproducibility.score),
+        format!(
+            "Contribution templates: {:.0}/100",
+            templates.completeness_score
+        ),
+    ];
+    if let Some(docs) = &analysis.docs_site {
+        facts.push(format!(
+            "Documentation site: {}{}",
+            docs.generator.as_deref().unwrap_or("GitHub Pages"),
+            docs.site_url
+                .as_ref()
+                .map(|url| format!(" at {}", url))
+                .unwrap_or_default()
+        ));
+    }
+    let spelling = &analysis.docs_spelling;
+    if spelling.words_checked > 0 {
+        facts.push(format!(
+            "Docs spelling: {:.1} likely misspellings per 1,000 words, {} inconsistently cased names",
+            spelling.misspellings_per_1k_words,
+            spelling.terminology.len()
+        ));
+    }
+    let prose = &analysis.docs_prose;
+    if prose.word_count > 0 {
+        facts.push(format!(
+            "Docs prose: {} words in {} sections, {:.0} words per sentence, {:.2} code lines per line of prose",
+            prose.word_count,
+            prose.section_count,
+            prose.avg_sentence_words,
+            prose.code_to_prose_ratio
+        ));
+    }
+    let mut blocks = vec![Block::List(facts)];
+
+    if !reproducibility.findings.is_empty() {
+        blocks.push(Block::Paragraph("Reproducibility findings".to_string()));
+        blocks.push(Block::List(reproducibility.findings.clone()));
+    }
+    if spelling.misspelling_count > 0 || !spelling.terminology.is_empty() {
+        blocks.push(Block::Paragraph("Docs polish".to_string()));
+        let examples = spelling
+            .misspellings
+            .iter()
+            .chain(
+                spelling
+                    .terminology
+                    .iter()
+                    .flat_map(|term| term.evidence.first()),
+            )
+            .take(TABLE_ROWS)
+            .map(evidence_item);
+        blocks.push(Block::List(
+            spelling.signals.iter().cloned().chain(examples).collect(),
+        ));
+    }
+    if !prose.signals.is_empty() {
+        blocks.push(Block::Paragraph("Docs readability".to_string()));
+        blocks.push(Block::List(prose.signals.clone()));
+    }
+
+    section("Quality and Tooling", blocks)
+}
+
+fn api_docs(analysis: &RepositoryAnalysis) -> Section {
+    let api_docs = &analysis.api_docs;
+    // Analyses from before API docs were looked for have no signals at all
+    if api_docs.signals.is_empty() && api_docs.urls.is_empty() {
+        return section("API Documentation", Vec::new());
+    }
+    let mut blocks = Vec::new();
+    if !api_docs.signals.is_empty() {
+        blocks.push(Block::List(api_docs.signals.clone()));
+    }
+    if !api_docs.urls.is_empty() {
+        let rows = api_docs
+            .urls
+            .iter()
+            .map(|url| {
+                vec![
+                    url.url.clone(),
+                    url.source.clone(),
+                    match url.resolves {
+                        Some(true) => "yes",
+                        Some(false) => "no",
+                        None => "not checked",
+                    }
+                    .to_string(),
+                ]
+            })
+            .collect();
+        blocks.push(Block::Table {
+            headers: strings(&["URL", "Source", "Resolves"]),
+            rows,
+        });
+    }
+    if !api_docs.tools.is_empty() {
+        blocks.push(Block::Paragraph("Doc generators".to_string()));
+        blocks.push(Block::List(
+            api_docs
+                .tools
+                .iter()
+                .take(TABLE_ROWS)
+                .map(evidence_item)
+                .collect(),
+        ));
+    }
+    section("API Documentation", blocks)
+}
+
+fn performance(analysis: &RepositoryAnalysis) -> Section {
+    let performance = &analysis.performance;
+    // Analyses from before performance was read have no signals at all
+    if performance.signals.is_empty() {
+        return section("Performance", Vec::new());
+    }
+    let mut facts = vec![format!(
+        "Measures performance: {}",
+        if performance.measures_performance {
+            "yes"
+        } else {
+            "no"
+        }
+    )];
+    facts.extend(performance.signals.iter().cloned());
+
+    let mut blocks = vec![Block::List(facts)];
+    let evidence: Vec<String> = [
+        &performance.benchmarks,
+        &performance.ci_jobs,
+        &performance.profiling,
+    ]
+    .into_iter()
+    .flatten()
+    .take(TABLE_ROWS)
+    .map(evidence_item)
+    .collect();
+    if !evidence.is_empty() {
+        blocks.push(Block::Paragraph("Performance evidence".to_string()));
+        blocks.push(Block::List(evidence));
+    }
+    section("Performance", blocks)
+}
+
+// Platforms with CI runs, target-specific code or release artifacts, the
+// evidence count of each in the cells
+fn platform_support(analysis: &RepositoryAnalysis) -> Section {
+    let platforms = &analysis.platform_support.platforms;
+    if platforms.is_empty() {
+        return section("Platform Support", Vec::new());
+    }
+    let cell = |count: usize, noun: &str| match count {
+        0 => "-".to_string(),
+        1 => format!("1 {}", noun),
+        n => format!("{} {}s", n, noun),
+    };
+    let rows = platforms
+        .iter()
+        .map(|signals| {
+            vec![
+                signals.platform.clone(),
+                cell(signals.ci.len(), "workflow"),
+                cell(signals.code_files as usize, "file"),
+                cell(signals.release_assets.len(), "artifact"),
+            ]
+        })
+        .collect();
+    let mut blocks = vec![Block::Table {
+        headers: strings(&[
+            "Platform",
+            "CI",
+            "Target-specific code",
+            "Release artifacts",
+        ]),
+        rows,
+    }];
+
+    let evidence: Vec<String> = platforms
+        .iter()
+        .flat_map(|signals| signals.ci.iter().take(1).chain(&signals.code))
+        .take(TABLE_ROWS)
+        .map(evidence_item)
+        .collect();
+    if !evidence.is_empty() {
+        blocks.push(Block::Paragraph("Platform evidence".to_string()));
+        blocks.push(Block::List(evidence));
+    }
+    section("Platform Support", blocks)
+}
+
+// Starter issues, contributing docs and maintainer responsiveness, what gets
+// a newcomer's first contribution merged
+fn contributor_friendliness(analysis: &RepositoryAnalysis) -> Section {
+    let friendliness = &analysis.contributor_friendliness;
+    let count = |count: Option<u32>| count.map_or("unknown".to_string(), |c| c.to_string());
+    let mut facts = vec![
+        format!("Score: {:.0}/100", friendliness.score),
+        format!(
+            "Open starter issues: {}, help wanted: {}",
+            count(friendliness.starter_issues.good_first_issues),
+            count(friendliness.starter_issues.help_wanted_issues)
+        ),
+    ];
+    if friendliness.sampled_issues > 0 {
+        facts.push(format!(
+            "Median first response: {} ({} of {} recent newcomer issues answered)",
+            friendliness.median_first_response_hours.map_or(
+                "most issues unanswered".to_string(),
+                |hours| format!("{:.1} hours", hours)
+            ),
+            friendliness.responded_issues,
+            friendliness.sampled_issues
+        ));
+    }
+
+    let mut blocks = vec![Block::List(facts)];
+    if !friendliness.strengths.is_empty() {
+        blocks.push(Block::Paragraph("Strengths".to_string()));
+        blocks.push(Block::List(friendliness.strengths.clone()));
+    }
+    if !friendliness.gaps.is_empty() {
+        blocks.push(Block::Paragraph("Gaps".to_string()));
+        blocks.push(Block::List(friendliness.gaps.clone()));
+    }
+    section("Contributor Friendliness", blocks)
+}
+
+fn security(analysis: &RepositoryAnalysis) -> Section {
+    let security = &analysis.security_info;
+    let yes_no = |value: bool| if value { "yes" } else { "no" };
+    let mut facts = vec![
+        format!("Security policy: {}", yes_no(security.has_security_policy)),
+        format!("Dependabot: {}", yes_no(security.has_dependabot)),
+        format!("CodeQL: {}", yes_no(security.has_codeql)),
+    ];
+    if !security.ci_security_checks.is_empty() {
+        facts.push(format!(
+            "CI security checks: {}",
+            security.ci_security_checks.join(", ")
+        ));
+    }
+    if let Some(advisories) = &security.published_advisories {
+        let mut fact = format!(
+            "Published security advisories: {}",
+            advisories.advisories.len()
+        );
+        if !advisories.by_severity.is_empty() {
+            let severities: Vec<String> = advisories
+                .by_severity
+                .iter()
+                .map(|(severity, count)| format!("{} {}", count, severity))
+                .collect();
+            fact.push_str(&format!(" ({})", severities.join(", ")));
+        }
+        if let (Some(median), Some(max)) = (advisories.median_patch_days, advisories.max_patch_days)
+        {
+            fact.push_str(&format!(
+                ", median {:.0} days from report to published fix (longest {:.0})",
+                median, max
+            ));
+        }
+        facts.push(fact);
+    }
+    let depth = &security.testing_depth;
+    let tools = |refs: &[EvidenceRef]| {
+        let labels = distinct_labels(refs);
+        if labels.is_empty() {
+            "none".to_string()
+        } else {
+            labels.join(", ")
+        }
+    };
+    facts.push(format!(
+        "Fuzzing: {} targets ({}), continuous fuzzing: {}",
+        depth.fuzz_targets.len(),
+        tools(&depth.fuzz_targets),
+        tools(&depth.continuous_fuzzing)
+    ));
+    facts.push(format!(
+        "Property-based testing: {}",
+        tools(&depth.property_testing)
+    ));
+    facts.push(format!("Static analysis in CI: {}", tools(&depth.sast)));
+    facts.extend(security.vulnerability_alerts.iter().cloned());
+    facts.extend(security.secret_findings.iter().map(|finding| {
+        let location = format!("{}:{}", finding.path.display(), finding.line);
+        format!(
+            "Possible {} committed at {}",
+            finding.kind,
+            linked(&location, &finding.url)
+        )
+    }));
+    let mut blocks = vec![Block::List(facts)];
+
+    if let Some(advisories) = security
+        .published_advisories
+        .as_ref()
+        .filter(|advisories| !advisories.advisories.is_empty())
+    {
+        blocks.push(Block::Paragraph(
+            "Advisories published by the repository".to_string(),
+        ));
+        blocks.push(Block::Table {
+            headers: strings(&[
+                "Advisory",
+                "Severity",
+                "Published",
+                "Days to fix",
+                "Patched",
+            ]),
+            rows: advisories
+                .advisories
+                .iter()
+                .take(TABLE_ROWS)
+                .map(|a| {
+                    let id = match &a.cve_id {
+                        Some(cve) => format!("{} / {}", a.ghsa_id, cve),
+                        None => a.ghsa_id.clone(),
+                    };
+                    vec![
+                        format!(
+                            "{}: {}",
+                            linked(&id, &(!a.url.is_empty()).then(|| a.url.clone())),
+                            a.summary
+                        ),
+                        a.severity.clone(),
+                        a.published_at
+                            .map(|d| d.format("%Y-%m-%d").to_string())
+                            .unwrap_or_default(),
+                        a.published_at
+                            .map(|d| {
+                                format!(
+                                    "{:.0}",
+                                    (d - a.created_at).num_hours().max(0) as f64 / 24.0
+                                )
+                            })
+                            .unwrap_or_default(),
+                        if a.patched_versions.is_empty() {
+                            "no".to_string()
+                        } else {
+                            a.patched_versions.join(", ")
+                        },
+                    ]
+                })
+                .collect(),
+        });
+    }
+
+    let depth_evidence: Vec<String> = [
+        &depth.continuous_fuzzing,
+        &depth.fuzz_targets,
+        &depth.property_testing,
+        &depth.sast,
+    ]
+    .into_iter()
+    .flatten()
+    .take(TABLE_ROWS)
+    .map(evidence_item)
+    .collect();
+    if !depth_evidence.is_empty() {
+        blocks.push(Block::Paragraph("Testing depth evidence".to_string()));
+        blocks.push(Block::List(depth_evidence));
+    }
+
+    if !security.dependency_evidence.is_empty() {
+        blocks.push(Block::Paragraph("Unpinned dependencies".to_string()));
+        blocks.push(Block::List(
+            security
+                .dependency_evidence
+                .iter()
+                .take(TABLE_ROWS)
+                .map(evidence_item)
+                .collect(),
+        ));
+    }
+
+    if let Some(usage) = &security.unsafe_usage {
+        blocks.push(Block::Paragraph(format!(
+            "Unsafe code: {} unsafe blocks and {} unsafe functions across {} crates, {} of which forbid unsafe_code.",
+            usage.total_unsafe_blocks,
+            usage.total_unsafe_functions,
+            usage.crates.len(),
+            usage.crates_forbidding_unsafe
+        )));
+        blocks.push(Block::Table {
+            headers: strings(&[
+                "Crate",
+                "Unsafe blocks",
+                "Unsafe fns",
+                "Unsafe impls",
+                "FFI",
+                "Build tools",
+                "unsafe_code lint",
+            ]),
+            rows: usage
+                .crates
+                .iter()
+                .map(|c| {
+                    let mut ffi = Vec::new();
+                    if c.extern_blocks > 0 {
+                        ffi.push(format!("{} extern blocks", c.extern_blocks));
+                    }
+                    if c.exported_functions > 0 {
+                        ffi.push(format!("{} exports", c.exported_functions));
+                    }
+                    if let Some(links) = &c.links {
+                        ffi.push(format!("links {}", links));
+                    }
+                    let mut tools = c.ffi_build_tools.clone();
+                    if c.build_script {
+                        tools.insert(0, "build.rs".to_string());
+                    }
+                    vec![
+                        c.name.clone(),
+                        c.unsafe_blocks.to_string(),
+                        c.unsafe_functions.to_string(),
+                        c.unsafe_impls.to_string(),
+                        ffi.join(", "),
+                        tools.join(", "),
+                        c.unsafe_code_lint.clone().unwrap_or_default(),
+                    ]
+                })
+                .collect(),
+        });
+
+        let hotspots: Vec<String> = usage
+            .crates
+            .iter()
+            .flat_map(|c| c.hotspots.iter())
+            .take(TABLE_ROWS)
+            .map(|h| {
+                format!(
+                    "{} ({} unsafe)",
+                    linked(&h.path.display().to_string(), &h.url),
+                    h.unsafe_count
+                )
+            })
+            .collect();
+        if !hotspots.is_empty() {
+            blocks.push(Block::Paragraph("Unsafe hotspots".to_string()));
+            blocks.push(Block::List(hotspots));
+        }
+    }
+    section("Security", blocks)
+}
+
+fn ownership(analysis: &RepositoryAnalysis) -> Section {
+    let ownership = &analysis.ownership;
+    let Some(path) = &ownership.codeowners_path else {
+        return section("Ownership", Vec::new());
+    };
+
+    let mut blocks = vec![Block::Paragraph(format!(
+        "{} assigns owners to {} of {} files.",
+        path.display(),
+        ownership.owned_files,
+        ownership.total_files
+    ))];
+    if !ownership.unowned_paths.is_empty() {
+        blocks.push(Block::Paragraph("Unowned paths".to_string()));
+        blocks.push(Block::List(
+            ownership
+                .unowned_paths
+                .iter()
+                .take(TABLE_ROWS)
+                .map(|p| p.display().to_string())
+                .collect(),
+        ));
+    }
+    section("Ownership", blocks)
+}
+
+// Funding, backing organizations and governance behind the sustainability risk
+fn sustainability(analysis: &RepositoryAnalysis) -> Section {
+    let sustainability = &analysis.sustainability;
+    let mut facts = Vec::new();
+    if let Some(foundation) = &sustainability.foundation {
+        facts.push(format!(
+            "Foundation: {} (named in {})",
+            foundation.foundation,
+            foundation.source.display()
+        ));
+    }
+    if !sustainability.governance_files.is_empty() {
+        let files: Vec<String> = sustainability
+            .governance_files
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        facts.push(format!("Governance: {}", files.join(", ")));
+    }
+    for link in &sustainability.funding {
+        facts.push(format!(
+            "Funding: [{}]({}) ({})",
+            link.platform,
+            link.url,
+            link.source.display()
+        ));
+    }
+
+    let mut blocks = Vec::new();
+    if !facts.is_empty() {
+        blocks.push(Block::List(facts));
+    }
+    if !sustainability.affiliations.is_empty() {
+        blocks.push(Block::Paragraph(
+            "Organizations behind the top contributors' commits".to_string(),
+        ));
+        blocks.push(Block::Table {
+            headers: strings(&["Domain", "Contributors", "Commits", "Share"]),
+            rows: sustainability
+                .affiliations
+                .iter()
+                .map(|share| {
+                    vec![
+                        share.domain.clone(),
+                        share.contributors.to_string(),
+                        share.commits.to_string(),
+                        format!("{:.0}%", share.share * 100.0),
+                    ]
+                })
+                .collect(),
+        });
+    }
+    // The most recent quarters, older history is in the JSON
+    let history = &sustainability.affiliation_history;
+    if !history.is_empty() {
+        blocks.push(Block::Paragraph(
+            "Commits by affiliation per quarter".to_string(),
+        ));
+        blocks.push(Block::Table {
+            headers: strings(&[
+                "Quarter",
+                "Commits",
+                "Largest organization",
+                "Organizations",
+                "Independent",
+            ]),
+            rows: history
+                .iter()
+                .skip(history.len().saturating_sub(AFFILIATION_QUARTERS))
+                .map(|period| {
+                    let share = |commits: u32| commits as f64 / period.commits.max(1) as f64;
+                    let organization_commits: u32 =
+                        period.organizations.iter().map(|org| org.commits).sum();
+                    vec![
+                        period.period.clone(),
+                        period.commits.to_string(),
+                        period.organizations.first().map_or("-".to_string(), |org| {
+                            format!("{} ({:.0}%)", org.domain, org.share * 100.0)
+                        }),
+                        format!("{:.0}%", share(organization_commits) * 100.0),
+                        format!("{:.0}%", share(period.independent_commits) * 100.0),
+                    ]
+                })
+                .collect(),
+        });
+    }
+    section("Sustainability", blocks)
+}
+
+fn planning(analysis: &RepositoryAnalysis) -> Section {
+    let planning = &analysis.planning;
+    // Analyses from before planning was read have no signals at all
+    if planning.signals.is_empty() {
+        return section("Planning", Vec::new());
+    }
+    let mut facts = vec![format!(
+        "Public roadmap: {}",
+        if planning.public_roadmap { "yes" } else { "no" }
+    )];
+    if !planning.milestones.is_empty() {
+        facts.push(format!(
+            "Milestones: {} open ({} overdue), {} closed",
+            planning.open_milestones, planning.overdue_milestones, planning.closed_milestones
+        ));
+    }
+    facts.extend(planning.signals.iter().cloned());
+
+    let mut blocks = vec![Block::List(facts)];
+    if !planning.milestones.is_empty() {
+        blocks.push(Block::Table {
+            headers: strings(&["Milestone", "State", "Due", "Issues", "Done"]),
+            rows: planning
+                .milestones
+                .iter()
+                .take(TABLE_ROWS)
+                .map(|m| {
+                    let due = m
+                        .due_on
+                        .map_or(String::new(), |due| due.format("%Y-%m-%d").to_string());
+                    vec![
+                        m.title.clone(),
+                        m.state.clone(),
+                        if m.overdue {
+                            format!("{} (overdue)", due)
+                        } else {
+                            due
+                        },
+                        (m.open_issues + m.closed_issues).to_string(),
+                        format!("{:.0}%", m.completion * 100.0),
+                    ]
+                })
+                .collect(),
+        });
+    }
+    let boards = planning.project_boards.as_deref().unwrap_or_default();
+    if !boards.is_empty() {
+        blocks.push(Block::Table {
+            headers: strings(&["Project board", "Visibility", "Items", "Status"]),
+            rows: boards
+                .iter()
+                .map(|board| {
+                    let statuses: Vec<String> = board
+                        .status_counts
+                        .iter()
+                        .map(|(status, count)| format!("{}: {}", status, count))
+                        .collect();
+                    vec![
+                        linked(
+                            &board.title,
+                            &(!board.url.is_empty()).then(|| board.url.clone()),
+                        ),
+                        match (board.public, board.closed) {
+                            (_, true) => "closed",
+                            (true, false) => "public",
+                            (false, false) => "private",
+                        }
+                        .to_string(),
+                        board.items.to_string(),
+                        statuses.join(", "),
+                    ]
+                })
+                .collect(),
+        });
+    }
+    section("Planning", blocks)
+}
+
+fn releases(analysis: &RepositoryAnalysis) -> Section {
+    let blocks = if analysis.releases.is_empty() {
+        Vec::new()
+    } else {
+        vec![Block::Table {
+            headers: strings(&["Tag", "Name", "Published"]),
+            rows: analysis
+                .releases
+                .iter()
+                .take(TABLE_ROWS)
+                .map(|r| {
+                    vec![
+                        r.tag_name.clone(),
+                        r.name.clone().unwrap_or_default(),
+                        r.published_at
+                            .map(|d| d.format("%Y-%m-%d").to_string())
+                            .unwrap_or_default(),
+                    ]
+                })
+                .collect(),
+        }]
+    };
+    section("Releases", blocks)
+}
+
+// Checksums, signatures and attestations of the recent releases, and the
+// checksum checked against its download
+fn release_artifacts(analysis: &RepositoryAnalysis) -> Section {
+    let hygiene = &analysis.release_hygiene;
+    if hygiene.signals.is_empty() {
+        return section("Release Artifacts", Vec::new());
+    }
+    let mut blocks = vec![Block::List(hygiene.signals.clone())];
+    let files = |names: &[String]| {
+        if names.is_empty() {
+            "-".to_string()
+        } else {
+            names.join(", ")
+        }
+    };
+    let rows: Vec<Vec<String>> = hygiene
+        .releases
+        .iter()
+        .filter(|release| !release.artifacts.is_empty())
+        .map(|release| {
+            vec![
+                release.tag_name.clone(),
+                release.artifacts.len().to_string(),
+                files(&release.checksum_files),
+                files(&release.signatures),
+                files(&release.attestations),
+            ]
+        })
+        .collect();
+    if !rows.is_empty() {
+        blocks.push(Block::Table {
+            headers: strings(&[
+                "Release",
+                "Artifacts",
+                "Checksums",
+                "Signatures",
+                "Attestations",
+            ]),
+            rows,
+        });
+    }
+    section("Release Artifacts", blocks)
+}
+
+fn packages(analysis: &RepositoryAnalysis) -> Section {
+    let packages = &analysis.package_publishing.packages;
+    let blocks = if packages.is_empty() {
+        Vec::new()
+    } else {
+        vec![Block::Table {
+            headers: strings(&["Registry", "Package", "Latest", "Downloads"]),
+            rows: packages
+                .iter()
+                .map(|p| {
+                    vec![
+                        p.registry.clone(),
+                        p.name.clone(),
+                        p.latest_version.clone().unwrap_or_default(),
+                        p.total_downloads.map(|d| d.to_string()).unwrap_or_default(),
+                    ]
+                })
+                .collect(),
+        }]
+    };
+    section("Published Packages", blocks)
+}
+
+fn related_projects(analysis: &RepositoryAnalysis) -> Section {
+    let blocks = if analysis.related_projects.is_empty() {
+        Vec::new()
+    } else {
+        vec![Block::List(
+            analysis
+                .related_projects
+                .iter()
+                .map(|p| format!("{} ({} stars): {}", p.full_name, p.stars, p.html_url))
+                .collect(),
+        )]
+    };
+    section("Related Projects", blocks)
+}
+
+fn ai_report(analysis: &RepositoryAnalysis) -> Section {
+    let blocks = analysis
+        .ai_insights
+        .as_ref()
+        .and_then(|insights| insights.report.clone())
+        .map(|report| vec![Block::Markdown(report)])
+        .unwrap_or_default();
+    section("AI Technical Report", blocks)
+}
+
+fn issue_triage(analysis: &RepositoryAnalysis) -> Section {
+    let Some(triage) = analysis
+        .ai_insights
+        .as_ref()
+        .and_then(|insights| insights.issue_triage.as_ref())
+    else {
+        return section("Issue Triage", Vec::new());
+    };
+
+    let mut blocks = vec![Block::Paragraph(triage.themes_summary.clone())];
+    if !triage.bug_clusters.is_empty() {
+        blocks.push(Block::List(
+            triage
+                .bug_clusters
+                .iter()
+                .map(|cluster| {
+                    let issues: Vec<String> = cluster
+                        .issue_numbers
+                        .iter()
+                        .map(|n| format!("#{}", n))
+                        .collect();
+                    format!(
+                        "{}: {} ({})",
+                        cluster.title,
+                        cluster.description,
+                        issues.join(", ")
+                    )
+                })
+                .collect(),
+        ));
+    }
+    section("Issue Triage", blocks)
+}
+
+// One exchange with its confidence and the cited lines
+fn question_answer(answer: &QuestionAnswer) -> Block {
+    // Headings of Markdown blocks are nested below the section's own
+    let mut markdown = format!(
+        "# {}\n\n{}\n\n**Confidence:** {}",
+        answer.question, answer.answer, answer.confidence
+    );
+    if !answer.grounded {
+        markdown.push_str(" (citations could not be verified)");
+    }
+    markdown.push('\n');
+
+    for citation in &answer.citations {
+        markdown.push_str(&format!(
+            "\n- `{}` lines {}-{}{}\n",
+            citation.path.display(),
+            citation.start_line,
+            citation.end_line,
+            if citation.verified {
+                ""
+            } else {
+                " (not found)"
+            }
+        ));
+        if let Some(snippet) = &citation.snippet {
+            markdown.push_str(&format!("\n```\n{}\n```\n", snippet));
+        }
+    }
+    Block::Markdown(markdown)
+}
+
+// "label in path:line", linked to the evidence
+fn evidence_item(evidence: &EvidenceRef) -> String {
+    let location = match evidence.line {
+        Some(line) => format!("{}:{}", evidence.path.display(), line),
+        None => evidence.path.display().to_string(),
+    };
+    format!("{} in {}", evidence.label, linked(&location, &evidence.url))
+}
+
+// Links to the first file the detection was seen in, with how many files
+// showed it in monorepos. Guesses are marked
+fn detection_item(detection: &Detection) -> String {
+    let url = detection.evidence.iter().find_map(|e| e.url.clone());
+    let mut notes = Vec::new();
+    if detection.confidence < WEAK_DETECTION {
+        notes.push("possible".to_string());
+    }
+    let mut paths: Vec<&std::path::Path> = detection
+        .evidence
+        .iter()
+        .map(|e| e.path.as_path())
+        .collect();
+    paths.sort();
+    paths.dedup();
+    if paths.len() > 1 {
+        notes.push(format!("{} files", paths.len()));
+    }
+
+    let name = linked(&detection.name, &url);
+    if notes.is_empty() {
+        name
+    } else {
+        format!("{} ({})", name, notes.join(", "))
+    }
+}
+
+// Markdown link to the evidence on GitHub when there is one. The HTML and PDF
+// renderers turn these into clickable links too
+fn linked(text: &str, url: &Option<String>) -> String {
+    match url {
+        Some(url) => format!("[{}]({})", text, url),
+        None => text.to_string(),
+    }
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
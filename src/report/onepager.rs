@@ -0,0 +1,132 @@
+use anyhow::Result;
+
+use crate::types::RepositoryAnalysis;
+
+use super::theme::Theme;
+use super::{Block, Report, ReportFormat, Section, key_metrics, render_report, risks, section};
+
+// Score of each risk level in the health score
+const RISK_LEVEL_SCORES: &[(&str, f64)] = &[("low", 100.0), ("medium", 60.0), ("high", 20.0)];
+
+// Executive one-pager: the health score, the risk assessment, the key metrics
+// and the AI executive summary, for readers who won't read the full report
+pub fn render(
+    analysis: &RepositoryAnalysis,
+    format: ReportFormat,
+    theme: &Theme,
+) -> Result<Vec<u8>> {
+    let sections = vec![
+        summary(analysis),
+        health(analysis),
+        risks(analysis),
+        metrics(analysis),
+    ];
+
+    let report = Report {
+        title: format!("Executive Summary: {}", analysis.metadata.full_name),
+        sections: sections
+            .into_iter()
+            .filter(|section| !section.blocks.is_empty())
+            .collect(),
+        footer: format!(
+            "Generated from the analysis of {} on {}",
+            analysis.url,
+            analysis.analyzed_at.format("%Y-%m-%d %H:%M UTC")
+        ),
+    };
+    render_report(&report, format, theme)
+}
+
+// Health score from 0 to 100 with its components: the risk categories, tooling
+// maturity, build reproducibility and, for Rust, panic hygiene. Maintenance is
+// left out for archived repositories, which are finished rather than unhealthy
+fn health_score(analysis: &RepositoryAnalysis) -> Option<(f64, Vec<(String, f64)>)> {
+    let read_only = analysis.repository_status.read_only;
+    let mut components: Vec<(String, f64)> = analysis
+        .risk_assessment
+        .categories
+        .iter()
+        .filter(|category| !(read_only && category.category == "maintenance"))
+        .filter_map(|category| {
+            RISK_LEVEL_SCORES
+                .iter()
+                .find(|(level, _)| *level == category.level)
+                .map(|(_, score)| {
+                    (
+                        format!("{} ({} risk)", category.category, category.level),
+                        *score,
+                    )
+                })
+        })
+        .collect();
+    if components.is_empty() {
+        return None;
+    }
+
+    components.push((
+        "Tooling maturity".to_string(),
+        analysis.developer_tooling.maturity_score,
+    ));
+    components.push((
+        "Build reproducibility".to_string(),
+        analysis.build_reproducibility.score,
+    ));
+    if let Some(density) = &analysis.code_metrics.panic_density {
+        components.push(("Panic hygiene".to_string(), density.score));
+    }
+
+    let score = components.iter().map(|(_, score)| score).sum::<f64>() / components.len() as f64;
+    Some((score, components))
+}
+
+// The AI paragraphs, or the description and the analysis summary without them
+fn summary(analysis: &RepositoryAnalysis) -> Section {
+    let paragraphs = analysis
+        .ai_insights
+        .as_ref()
+        .map(|insights| insights.executive_summary.clone())
+        .unwrap_or_default();
+    if !paragraphs.is_empty() {
+        return section(
+            "Summary",
+            paragraphs.into_iter().map(Block::Paragraph).collect(),
+        );
+    }
+
+    let mut fallback = super::summary(analysis);
+    if let Some(description) = &analysis.metadata.description {
+        fallback
+            .blocks
+            .insert(0, Block::Paragraph(description.clone()));
+    }
+    fallback
+}
+
+fn health(analysis: &RepositoryAnalysis) -> Section {
+    let Some((score, components)) = health_score(analysis) else {
+        return section("Health", Vec::new());
+    };
+
+    let mut blocks = vec![Block::Paragraph(format!("Health score: {:.0}/100", score))];
+    blocks.push(Block::List(
+        components
+            .iter()
+            .map(|(name, score)| format!("{}: {:.0}", name, score))
+            .collect(),
+    ));
+    section("Health", blocks)
+}
+
+fn metrics(analysis: &RepositoryAnalysis) -> Section {
+    let rows = key_metrics(analysis)
+        .iter()
+        .map(|(name, value)| vec![name.to_string(), format!("{:.0}", value)])
+        .collect();
+    section(
+        "Key Metrics",
+        vec![Block::Table {
+            headers: vec!["Metric".to_string(), "Value".to_string()],
+            rows,
+        }],
+    )
+}
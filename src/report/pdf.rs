@@ -0,0 +1,565 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use log::warn;
+use lopdf::content::{Content, Operation};
+use lopdf::{Dictionary, Document, Object, Stream, dictionary};
+use regex::Regex;
+
+use super::theme::Theme;
+use super::{Block, Report};
+
+// A4 in points
+const PAGE_WIDTH: f64 = 595.0;
+const PAGE_HEIGHT: f64 = 842.0;
+const MARGIN: f64 = 50.0;
+
+// Longest table cell before it is cut, in characters
+const MAX_CELL_CHARS: usize = 40;
+
+// Height of the logo in the top right corner of the first page
+const LOGO_HEIGHT: f64 = 40.0;
+
+#[derive(Clone, Copy)]
+enum Style {
+    Title,
+    Heading,
+    Subheading,
+    Body,
+    Mono,
+}
+
+impl Style {
+    // (font resource, size)
+    fn font(self) -> (&'static str, f64) {
+        match self {
+            Style::Title => ("F2", 18.0),
+            Style::Heading => ("F2", 14.0),
+            Style::Subheading => ("F2", 11.0),
+            Style::Body => ("F1", 10.0),
+            Style::Mono => ("F3", 8.5),
+        }
+    }
+
+    // Characters per line; Helvetica is estimated at an average glyph width of half an em
+    fn line_chars(self, indent: f64) -> usize {
+        let (font, size) = self.font();
+        let glyph_width = if font == "F3" { 0.6 } else { 0.5 } * size;
+        ((PAGE_WIDTH - 2.0 * MARGIN - indent) / glyph_width) as usize
+    }
+}
+
+struct Line {
+    style: Style,
+    indent: f64,
+    text: String,
+    space_before: f64,
+    link: Option<String>, // Evidence URL, the whole line is clickable
+}
+
+pub fn render(report: &Report, theme: &Theme) -> Result<Vec<u8>> {
+    let lines = layout(report, theme);
+    let color = |rgb: Option<(u8, u8, u8)>| {
+        let (r, g, b) = rgb.unwrap_or((0, 0, 0));
+        Operation::new(
+            "rg",
+            vec![
+                (r as f64 / 255.0).into(),
+                (g as f64 / 255.0).into(),
+                (b as f64 / 255.0).into(),
+            ],
+        )
+    };
+
+    // Fill pages top to bottom
+    let mut pages: Vec<Vec<Operation>> = vec![Vec::new()];
+    let mut annotations: Vec<Vec<Dictionary>> = vec![Vec::new()];
+    let mut y = PAGE_HEIGHT - MARGIN;
+    for line in &lines {
+        let (font, size) = line.style.font();
+        let height = size * 1.4 + line.space_before;
+        if y - height < MARGIN {
+            pages.push(Vec::new());
+            annotations.push(Vec::new());
+            y = PAGE_HEIGHT - MARGIN;
+        }
+        y -= height;
+
+        if let Some(link) = &line.link {
+            let rect: Vec<Object> = vec![
+                (MARGIN + line.indent).into(),
+                (y - size * 0.3).into(),
+                (PAGE_WIDTH - MARGIN).into(),
+                (y + size).into(),
+            ];
+            annotations
+                .last_mut()
+                .expect("at least one page")
+                .push(dictionary! {
+                    "Type" => "Annot",
+                    "Subtype" => "Link",
+                    "Rect" => rect,
+                    "Border" => vec![0.into(), 0.into(), 0.into()],
+                    "A" => dictionary! {
+                        "S" => "URI",
+                        "URI" => Object::string_literal(link.as_str()),
+                    },
+                });
+        }
+
+        let operations = pages.last_mut().expect("at least one page");
+        let rgb = match line.style {
+            Style::Title | Style::Heading => theme.primary_rgb(),
+            Style::Subheading => theme.accent_rgb(),
+            Style::Body | Style::Mono => None,
+        };
+        operations.extend([
+            Operation::new("BT", vec![]),
+            color(rgb),
+            Operation::new("Tf", vec![font.into(), size.into()]),
+            Operation::new("Td", vec![(MARGIN + line.indent).into(), y.into()]),
+            Operation::new("Tj", vec![Object::string_literal(win_ansi(&line.text))]),
+            Operation::new("ET", vec![]),
+        ]);
+    }
+
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font = |doc: &mut Document, base: &str| {
+        doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => base,
+            "Encoding" => "WinAnsiEncoding",
+        })
+    };
+    let fonts = dictionary! {
+        "F1" => font(&mut doc, "Helvetica"),
+        "F2" => font(&mut doc, "Helvetica-Bold"),
+        "F3" => font(&mut doc, "Courier"),
+    };
+    let mut resources = dictionary! { "Font" => fonts };
+    let logo = theme.logo.as_deref().and_then(logo_image);
+    if let Some((image, _, _)) = &logo {
+        let image_id = doc.add_object(image.clone());
+        resources.set("XObject", dictionary! { "Logo" => image_id });
+    }
+    let resources_id = doc.add_object(resources);
+
+    let page_count = pages.len();
+    let mut kids: Vec<Object> = Vec::new();
+    for (index, (mut operations, links)) in pages.into_iter().zip(annotations).enumerate() {
+        if index == 0
+            && let Some((_, width, height)) = &logo
+        {
+            let width = LOGO_HEIGHT * *width as f64 / *height as f64;
+            operations.extend([
+                Operation::new("q", vec![]),
+                Operation::new(
+                    "cm",
+                    vec![
+                        width.into(),
+                        0.into(),
+                        0.into(),
+                        LOGO_HEIGHT.into(),
+                        (PAGE_WIDTH - MARGIN - width).into(),
+                        (PAGE_HEIGHT - MARGIN + 5.0 - LOGO_HEIGHT).into(),
+                    ],
+                ),
+                Operation::new("Do", vec!["Logo".into()]),
+                Operation::new("Q", vec![]),
+            ]);
+        }
+        // The theme footer on every page
+        if let Some(footer) = &theme.footer {
+            operations.extend([
+                Operation::new("BT", vec![]),
+                color(theme.accent_rgb()),
+                Operation::new("Tf", vec!["F1".into(), 8.into()]),
+                Operation::new("Td", vec![MARGIN.into(), 25.into()]),
+                Operation::new("Tj", vec![Object::string_literal(win_ansi(footer))]),
+                Operation::new("ET", vec![]),
+            ]);
+        }
+
+        // Page numbers in the footer
+        operations.extend([
+            Operation::new("BT", vec![]),
+            color(None),
+            Operation::new("Tf", vec!["F1".into(), 8.into()]),
+            Operation::new("Td", vec![(PAGE_WIDTH / 2.0 - 15.0).into(), 25.into()]),
+            Operation::new(
+                "Tj",
+                vec![Object::string_literal(format!(
+                    "{} / {}",
+                    index + 1,
+                    page_count
+                ))],
+            ),
+            Operation::new("ET", vec![]),
+        ]);
+
+        let content = Content { operations };
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode()?));
+        let mut page = dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        };
+        if !links.is_empty() {
+            let links: Vec<Object> = links
+                .into_iter()
+                .map(|link| doc.add_object(link).into())
+                .collect();
+            page.set("Annots", links);
+        }
+        let page_id = doc.add_object(page);
+        kids.push(page_id.into());
+    }
+
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => page_count as i64,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), PAGE_WIDTH.into(), PAGE_HEIGHT.into()],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.compress();
+
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+    Ok(bytes)
+}
+
+// Flatten the report into wrapped, styled lines
+fn layout(report: &Report, theme: &Theme) -> Vec<Line> {
+    let mut lines = Vec::new();
+    push_wrapped(&mut lines, Style::Title, 0.0, &report.title, 0.0);
+    if let Some(company) = &theme.company_name {
+        push_wrapped(
+            &mut lines,
+            Style::Subheading,
+            0.0,
+            &format!("Prepared by {}", company),
+            4.0,
+        );
+    }
+
+    for section in &report.sections {
+        push_wrapped(&mut lines, Style::Heading, 0.0, &section.heading, 12.0);
+        for block in &section.blocks {
+            match block {
+                Block::Paragraph(text) => push_wrapped(&mut lines, Style::Body, 0.0, text, 4.0),
+                Block::List(items) => {
+                    for item in items {
+                        push_bullet(&mut lines, item);
+                    }
+                }
+                // Cells are cut to fit, so their links are dropped
+                Block::Table { headers, rows } => {
+                    let rows: Vec<Vec<String>> = rows
+                        .iter()
+                        .map(|row| row.iter().map(|cell| strip_links(cell).0).collect())
+                        .collect();
+                    for (index, row) in table_lines(headers, &rows).into_iter().enumerate() {
+                        let space_before = if index == 0 { 4.0 } else { 0.0 };
+                        push_wrapped(&mut lines, Style::Mono, 0.0, &row, space_before);
+                    }
+                }
+                Block::Markdown(markdown) => push_markdown(&mut lines, markdown),
+                // Not drawn, the tables next to diagrams carry the same data
+                Block::Mermaid(_) => {}
+            }
+        }
+    }
+
+    let footer = theme.footer.as_deref().unwrap_or(&report.footer);
+    push_wrapped(&mut lines, Style::Body, 0.0, footer, 16.0);
+    lines
+}
+
+// Image XObject of a JPEG, or of an 8-bit grayscale or RGB PNG without
+// interlacing, with its size in pixels. The compressed data is embedded as is
+fn logo_image(path: &Path) -> Option<(Stream, u32, u32)> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("Failed to read logo {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    let image = if bytes.starts_with(&[0xFF, 0xD8]) {
+        jpeg_image(bytes)
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png_image(&bytes)
+    } else {
+        None
+    };
+    if image.is_none() {
+        warn!(
+            "Logo {} left out of the PDF: only JPEG and 8-bit grayscale or RGB PNG images without transparency are supported",
+            path.display()
+        );
+    }
+    image
+}
+
+fn jpeg_image(bytes: Vec<u8>) -> Option<(Stream, u32, u32)> {
+    // The frame header (SOF0 to SOF15, except DHT, JPG and DAC) holds the size
+    let mut at = 2;
+    while at + 9 < bytes.len() {
+        if bytes[at] != 0xFF {
+            return None;
+        }
+        let marker = bytes[at + 1];
+        let length = u16::from_be_bytes([bytes[at + 2], bytes[at + 3]]) as usize;
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let height = u16::from_be_bytes([bytes[at + 5], bytes[at + 6]]) as u32;
+            let width = u16::from_be_bytes([bytes[at + 7], bytes[at + 8]]) as u32;
+            let color_space = match bytes[at + 9] {
+                1 => "DeviceGray",
+                3 => "DeviceRGB",
+                4 => "DeviceCMYK",
+                _ => return None,
+            };
+            let dict = dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => width as i64,
+                "Height" => height as i64,
+                "ColorSpace" => color_space,
+                "BitsPerComponent" => 8,
+                "Filter" => "DCTDecode",
+            };
+            return Some((
+                Stream::new(dict, bytes).with_compression(false),
+                width,
+                height,
+            ));
+        }
+        at += 2 + length;
+    }
+    None
+}
+
+fn png_image(bytes: &[u8]) -> Option<(Stream, u32, u32)> {
+    let mut at = 8;
+    let mut header: Option<(u32, u32, u8, u8, u8)> = None;
+    let mut data = Vec::new();
+    while at + 8 <= bytes.len() {
+        let length = u32::from_be_bytes(bytes[at..at + 4].try_into().ok()?) as usize;
+        let kind = &bytes[at + 4..at + 8];
+        let chunk = bytes.get(at + 8..at + 8 + length)?;
+        match kind {
+            b"IHDR" if length >= 13 => {
+                header = Some((
+                    u32::from_be_bytes(chunk[0..4].try_into().ok()?),
+                    u32::from_be_bytes(chunk[4..8].try_into().ok()?),
+                    chunk[8],  // Bit depth
+                    chunk[9],  // Color type
+                    chunk[12], // Interlace method
+                ));
+            }
+            b"IDAT" => data.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => {}
+        }
+        at += 12 + length; // Length, type, data and CRC
+    }
+
+    let (width, height, depth, color_type, interlace) = header?;
+    let (color_space, colors) = match color_type {
+        0 => ("DeviceGray", 1),
+        2 => ("DeviceRGB", 3),
+        _ => return None,
+    };
+    if depth != 8 || interlace != 0 || data.is_empty() {
+        return None;
+    }
+    // PNG rows keep their filter byte, which the PNG predictors undo
+    let dict = dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Image",
+        "Width" => width as i64,
+        "Height" => height as i64,
+        "ColorSpace" => color_space,
+        "BitsPerComponent" => 8,
+        "Filter" => "FlateDecode",
+        "DecodeParms" => dictionary! {
+            "Predictor" => 15,
+            "Colors" => colors,
+            "BitsPerComponent" => 8,
+            "Columns" => width as i64,
+        },
+    };
+    Some((
+        Stream::new(dict, data).with_compression(false),
+        width,
+        height,
+    ))
+}
+
+fn push_markdown(lines: &mut Vec<Line>, markdown: &str) {
+    let mut in_code = false;
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            push_wrapped(lines, Style::Mono, 10.0, line, 0.0);
+            continue;
+        }
+
+        let text = trimmed.replace("**", "").replace('`', "");
+        if trimmed.starts_with('#') {
+            push_wrapped(
+                lines,
+                Style::Subheading,
+                0.0,
+                text.trim_start_matches('#').trim(),
+                8.0,
+            );
+        } else if let Some(item) = text.strip_prefix("- ").or_else(|| text.strip_prefix("* ")) {
+            push_bullet(lines, item);
+        } else if !text.is_empty() {
+            push_wrapped(lines, Style::Body, 0.0, &text, 2.0);
+        }
+    }
+}
+
+fn push_bullet(lines: &mut Vec<Line>, item: &str) {
+    let start = lines.len();
+    push_wrapped(lines, Style::Body, 12.0, item, 0.0);
+    if let Some(first) = lines.get_mut(start) {
+        first.indent = 0.0;
+        first.text = format!("\u{2022}  {}", first.text);
+    }
+}
+
+fn push_wrapped(lines: &mut Vec<Line>, style: Style, indent: f64, text: &str, space_before: f64) {
+    let width = style.line_chars(indent).max(10);
+    let (text, link) = strip_links(text);
+    for (index, wrapped) in wrap(&text, width).into_iter().enumerate() {
+        lines.push(Line {
+            style,
+            indent,
+            text: wrapped,
+            space_before: if index == 0 { space_before } else { 0.0 },
+            link: link.clone(),
+        });
+    }
+}
+
+// Replaces [text](https://...) links with their text, returning the first URL
+fn strip_links(text: &str) -> (String, Option<String>) {
+    let pattern = Regex::new(r"\[([^\]]+)\]\((https?://[^)\s]+)\)").unwrap();
+    let link = pattern
+        .captures(text)
+        .map(|captures| captures[2].to_string());
+    (pattern.replace_all(text, "$1").to_string(), link)
+}
+
+// Greedy word wrap; words longer than a line are split
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > width {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            lines.push(word.drain(..width).collect());
+        }
+        let word: String = word.into_iter().collect();
+
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+// Fixed-width columns for the monospace font
+fn table_lines(headers: &[String], rows: &[Vec<String>]) -> Vec<String> {
+    let cut = |cell: &str| -> String {
+        if cell.chars().count() > MAX_CELL_CHARS {
+            let kept: String = cell.chars().take(MAX_CELL_CHARS - 3).collect();
+            format!("{}...", kept)
+        } else {
+            cell.to_string()
+        }
+    };
+    let headers: Vec<String> = headers.iter().map(|h| cut(h)).collect();
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(|c| cut(c)).collect())
+        .collect();
+
+    let widths: Vec<usize> = (0..headers.len())
+        .map(|column| {
+            std::iter::once(&headers)
+                .chain(rows.iter())
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let format_row = |row: &[String]| {
+        row.iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![format_row(&headers)];
+    lines.push(
+        widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("  "),
+    );
+    lines.extend(rows.iter().map(|row| format_row(row)));
+    lines
+}
+
+// The standard fonts only cover Windows-1252, anything else becomes '?'
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| {
+            let mut buffer = [0u8; 4];
+            let (bytes, _, unmappable) =
+                encoding_rs::WINDOWS_1252.encode(c.encode_utf8(&mut buffer));
+            if unmappable || bytes.len() != 1 {
+                b'?'
+            } else {
+                bytes[0]
+            }
+        })
+        .collect()
+}
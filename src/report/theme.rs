@@ -0,0 +1,54 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+// Branding of the HTML and PDF reports, from `[theme]` in analyzer.toml
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub company_name: Option<String>,
+    pub logo: Option<PathBuf>, // PNG or JPEG, SVG and GIF also work in HTML
+    pub primary_color: Option<String>, // #rrggbb, title and headings
+    pub accent_color: Option<String>, // #rrggbb, table headers, rules and footers
+    pub footer: Option<String>, // Replaces the generated footer
+}
+
+impl Theme {
+    pub fn primary_rgb(&self) -> Option<(u8, u8, u8)> {
+        self.primary_color.as_deref().and_then(parse_color)
+    }
+
+    pub fn accent_rgb(&self) -> Option<(u8, u8, u8)> {
+        self.accent_color.as_deref().and_then(parse_color)
+    }
+}
+
+// Colors must be #rrggbb and the logo must exist, the config resolves its
+// path against the config file
+pub fn check_theme(theme: &Theme) -> Result<()> {
+    for (name, color) in [
+        ("primary_color", &theme.primary_color),
+        ("accent_color", &theme.accent_color),
+    ] {
+        if let Some(color) = color
+            && parse_color(color).is_none()
+        {
+            anyhow::bail!("theme {} must be a #rrggbb color, got {}", name, color);
+        }
+    }
+    if let Some(logo) = &theme.logo {
+        fs::metadata(logo).with_context(|| format!("theme logo {}", logo.display()))?;
+    }
+    Ok(())
+}
+
+fn parse_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |at: usize| u8::from_str_radix(&hex[at..at + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Result;
+
+// Threat model: the cloned repository is hostile. It may contain symlinks that
+// point outside the clone (e.g. to ~/.ssh), trees nested deep enough to exhaust
+// the stack, huge or absurdly named files, and build scripts, hooks or package
+// manifests that would run code if invoked.
+//
+// The analyzer only reads regular files inside the clone, within the limits
+// below, and never executes repository content: no build tools, package
+// managers or scripts are run, and libgit2 does not run hooks or external
+// filter drivers. Everything read from the tree is treated as data, including
+// what ends up in AI prompts.
+#[derive(Debug, Clone)]
+pub struct AnalysisLimits {
+    pub max_depth: usize,
+    pub max_file_name_len: usize,
+    pub max_path_len: usize,
+    pub max_file_size: u64,
+    pub max_files: usize,
+    pub follow_internal_symlinks: bool,
+}
+
+impl AnalysisLimits {
+    pub fn standard() -> Self {
+        Self {
+            max_depth: 32,
+            max_file_name_len: 255,
+            max_path_len: 4096,
+            max_file_size: 1_000_000, // 1MB
+            max_files: 100_000,
+            follow_internal_symlinks: true,
+        }
+    }
+
+    // Stricter limits for `--paranoid`: no symlinks at all, smaller files, shallower trees
+    pub fn paranoid() -> Self {
+        Self {
+            max_depth: 12,
+            max_file_name_len: 128,
+            max_path_len: 1024,
+            max_file_size: 256_000, // 256KB
+            max_files: 20_000,
+            follow_internal_symlinks: false,
+        }
+    }
+}
+
+impl Default for AnalysisLimits {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+// Checks paths found while walking the clone against the limits
+pub struct PathGuard<'a> {
+    root: PathBuf,
+    canonical_root: PathBuf,
+    limits: &'a AnalysisLimits,
+}
+
+impl<'a> PathGuard<'a> {
+    pub fn new(root: &Path, limits: &'a AnalysisLimits) -> Result<Self> {
+        Ok(Self {
+            root: root.to_path_buf(),
+            canonical_root: root.canonicalize()?,
+            limits,
+        })
+    }
+
+    pub fn check(&self, path: &Path) -> Result<()> {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+
+        let depth = relative
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_)))
+            .count();
+        if depth > self.limits.max_depth {
+            anyhow::bail!(
+                "path is nested deeper than {} levels",
+                self.limits.max_depth
+            );
+        }
+
+        if relative.as_os_str().len() > self.limits.max_path_len {
+            anyhow::bail!("path is longer than {} bytes", self.limits.max_path_len);
+        }
+
+        if let Some(name) = path.file_name()
+            && name.len() > self.limits.max_file_name_len
+        {
+            anyhow::bail!(
+                "file name is longer than {} bytes",
+                self.limits.max_file_name_len
+            );
+        }
+
+        let metadata = fs::symlink_metadata(path)?;
+        if metadata.file_type().is_symlink() {
+            if !self.limits.follow_internal_symlinks {
+                anyhow::bail!("symlinks are not followed in paranoid mode");
+            }
+
+            // Dangling links and links resolving outside the clone are refused
+            let target = path
+                .canonicalize()
+                .map_err(|_| anyhow::anyhow!("symlink target does not exist"))?;
+            if !target.starts_with(&self.canonical_root) {
+                anyhow::bail!("symlink escapes the repository");
+            }
+        }
+
+        Ok(())
+    }
+}
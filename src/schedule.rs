@@ -0,0 +1,227 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use log::{error, info, warn};
+use serde::Deserialize;
+
+use crate::analyzers::repo::RepositoryAnalyzer;
+use crate::forge::parse_repository_url;
+use crate::types::RepositoryAnalysis;
+use crate::{cancel, notify};
+
+// Directory the results are kept in when history_dir is not set
+const DEFAULT_HISTORY_DIR: &str = "analysis-history";
+
+// Results kept per repository when keep_last is not set
+const DEFAULT_KEEP_LAST: usize = 30;
+
+// `[schedule]` in analyzer.toml
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScheduleConfig {
+    pub history_dir: Option<PathBuf>,
+    pub keep_last: Option<usize>,
+    pub max_age_days: Option<i64>,
+    pub notify: Vec<String>, // Webhooks notified after every run
+    pub repositories: Vec<ScheduledRepository>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduledRepository {
+    pub url: String,
+    pub cron: String, // 5 or 6 field cron expression, or nightly/daily/weekly/hourly
+    #[serde(default)]
+    pub notify: Vec<String>,
+}
+
+struct Job<'a> {
+    repository: &'a ScheduledRepository,
+    schedule: cron::Schedule,
+    next_run: Option<DateTime<Utc>>,
+}
+
+// Re-analyzes each repository whenever its cron expression fires, until
+// cancelled. With `once` every repository is analyzed immediately, one time
+pub async fn run(analyzer: &RepositoryAnalyzer, config: &ScheduleConfig, once: bool) -> Result<()> {
+    if config.repositories.is_empty() {
+        anyhow::bail!("No [[schedule.repositories]] in the config");
+    }
+    let mut jobs = Vec::new();
+    for repository in &config.repositories {
+        let schedule = parse_cron(&repository.cron)
+            .with_context(|| format!("Invalid cron expression for {}", repository.url))?;
+        let next_run = if once {
+            Some(Utc::now())
+        } else {
+            schedule.upcoming(Utc).next()
+        };
+        jobs.push(Job {
+            repository,
+            schedule,
+            next_run,
+        });
+    }
+
+    loop {
+        let Some(job) = jobs
+            .iter_mut()
+            .filter(|job| job.next_run.is_some())
+            .min_by_key(|job| job.next_run)
+        else {
+            return Ok(());
+        };
+        let next_run = job.next_run.unwrap_or_else(Utc::now);
+        info!("Next run: {} at {}", job.repository.url, next_run);
+
+        let wait = (next_run - Utc::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = cancel::cancelled() => {
+                info!("Scheduler stopped");
+                return Ok(());
+            }
+        }
+
+        run_job(analyzer, config, job.repository).await;
+        job.next_run = if once {
+            None
+        } else {
+            job.schedule.after(&Utc::now()).next()
+        };
+    }
+}
+
+// One repository's failure is logged and doesn't stop the scheduler
+async fn run_job(
+    analyzer: &RepositoryAnalyzer,
+    config: &ScheduleConfig,
+    repository: &ScheduledRepository,
+) {
+    let result = tokio::select! {
+        result = analyzer.analyze_repository(&repository.url) => result,
+        _ = cancel::cancelled() => return,
+    };
+    let analysis = match result {
+        Ok(analysis) => analysis,
+        Err(e) => {
+            error!("Scheduled analysis of {} failed: {}", repository.url, e);
+            return;
+        }
+    };
+
+    let history = match history_dir(config, &repository.url) {
+        Ok(history) => history,
+        Err(e) => {
+            error!("No history directory for {}: {}", repository.url, e);
+            return;
+        }
+    };
+    let baseline = latest_result(&history);
+    if let Err(e) = save_result(analyzer, &history, &analysis) {
+        error!("Failed to save the analysis of {}: {}", repository.url, e);
+    }
+    apply_retention(config, &history);
+
+    for webhook in config.notify.iter().chain(&repository.notify) {
+        if let Err(e) = notify::send_summary(webhook, &analysis, baseline.as_ref(), None).await {
+            warn!("Failed to send notification: {}", e);
+        }
+    }
+}
+
+// Results of a repository live in <history_dir>/<owner>/<repo>/<timestamp>.json,
+// below every group of a GitLab namespace
+fn history_dir(config: &ScheduleConfig, url: &str) -> Result<PathBuf> {
+    let (_, owner, repo) = parse_repository_url(url)?;
+    let root = config
+        .history_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_HISTORY_DIR));
+    let dir = root.join(owner).join(repo);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn save_result(
+    analyzer: &RepositoryAnalyzer,
+    history: &Path,
+    analysis: &RepositoryAnalysis,
+) -> Result<()> {
+    let path = history.join(format!(
+        "{}.json",
+        analysis.analyzed_at.format("%Y%m%dT%H%M%SZ")
+    ));
+    fs::write(&path, analyzer.export_analysis_json(analysis)?)?;
+    info!("Scheduled analysis saved to: {}", path.display());
+    Ok(())
+}
+
+// Timestamped names sort chronologically
+fn result_files(history: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(history)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+fn latest_result(history: &Path) -> Option<RepositoryAnalysis> {
+    let path = result_files(history).pop()?;
+    let content = fs::read_to_string(&path).ok()?;
+    serde_json::from_str(&content)
+        .map_err(|e| warn!("Ignoring unreadable result {}: {}", path.display(), e))
+        .ok()
+}
+
+// Keeps the newest keep_last results and drops those older than max_age_days,
+// but never the newest one
+fn apply_retention(config: &ScheduleConfig, history: &Path) {
+    let keep_last = config.keep_last.unwrap_or(DEFAULT_KEEP_LAST).max(1);
+    let cutoff = config
+        .max_age_days
+        .map(|days| Utc::now() - chrono::Duration::days(days));
+
+    let mut files = result_files(history);
+    files.pop();
+    let excess = (files.len() + 1).saturating_sub(keep_last);
+
+    for (index, path) in files.iter().enumerate() {
+        let expired = cutoff.is_some_and(|cutoff| {
+            path.file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| chrono::NaiveDateTime::parse_from_str(stem, "%Y%m%dT%H%M%SZ").ok())
+                .is_some_and(|saved_at| saved_at.and_utc() < cutoff)
+        });
+        if index < excess || expired {
+            match fs::remove_file(path) {
+                Ok(()) => info!("Removed old result {}", path.display()),
+                Err(e) => warn!("Failed to remove {}: {}", path.display(), e),
+            }
+        }
+    }
+}
+
+// Accepts the usual 5 field expressions and named schedules on top of the
+// 6 and 7 field (with seconds) syntax of the cron crate
+fn parse_cron(expression: &str) -> Result<cron::Schedule> {
+    let expression = expression.trim();
+    let normalized = match expression.to_lowercase().as_str() {
+        "nightly" | "daily" => "@daily".to_string(),
+        "weekly" => "@weekly".to_string(),
+        "hourly" => "@hourly".to_string(),
+        "monthly" => "@monthly".to_string(),
+        _ if expression.split_whitespace().count() == 5 => format!("0 {}", expression),
+        _ => expression.to_string(),
+    };
+    Ok(cron::Schedule::from_str(&normalized)?)
+}
@@ -0,0 +1,62 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use walkdir::WalkDir;
+
+use crate::types::ResourceUsage;
+
+// Process-wide counters, every HTTP request and the clone report into these
+static NETWORK_REQUESTS: AtomicU64 = AtomicU64::new(0);
+static BYTES_DOWNLOADED: AtomicU64 = AtomicU64::new(0);
+
+pub fn record_request() {
+    NETWORK_REQUESTS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_download(bytes: u64) {
+    BYTES_DOWNLOADED.fetch_add(bytes, Ordering::Relaxed);
+}
+
+// Read a response body as JSON, counting the bytes received
+pub async fn json<T: DeserializeOwned>(response: reqwest::Response) -> Result<T> {
+    let body = response.bytes().await?;
+    record_download(body.len() as u64);
+    Ok(serde_json::from_slice(&body)?)
+}
+
+pub async fn text(response: reqwest::Response) -> Result<String> {
+    let body = response.text().await?;
+    record_download(body.len() as u64);
+    Ok(body)
+}
+
+pub fn resource_usage(started_at: Instant, work_dir: &Path) -> ResourceUsage {
+    ResourceUsage {
+        duration_ms: started_at.elapsed().as_millis() as u64,
+        peak_memory_bytes: peak_memory_bytes(),
+        temp_disk_bytes: disk_usage(work_dir),
+        bytes_downloaded: BYTES_DOWNLOADED.load(Ordering::Relaxed),
+        network_requests: NETWORK_REQUESTS.load(Ordering::Relaxed),
+    }
+}
+
+// Peak resident set size, only available on Linux
+fn peak_memory_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+fn disk_usage(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
@@ -0,0 +1,412 @@
+use anyhow::Result;
+use log::{info, warn};
+
+use crate::analyzers::detections::{Category, DetectionRules};
+use crate::github::GitHubClient;
+use crate::types::{
+    DescriptionSuggestion, Detection, MetadataSuggestions, RepositoryAnalysis, TopicSuggestion,
+};
+
+// GitHub allows 20 topics of at most 50 characters
+const MAX_TOPICS: usize = 20;
+const MAX_TOPIC_LENGTH: usize = 50;
+
+// Weaker detections are guesses, not something to advertise
+const MIN_CONFIDENCE: f64 = 0.7;
+
+// Shorter descriptions say too little to be found in search
+const MIN_DESCRIPTION_WORDS: usize = 4;
+// Longer summary sentences make a poor description
+const MAX_DESCRIPTION_LENGTH: usize = 160;
+
+// Names whose usual topic isn't what slugging them gives
+const TOPIC_NAMES: &[(&str, &str)] = &[
+    ("c++", "cpp"),
+    ("c#", "csharp"),
+    ("f#", "fsharp"),
+    ("objective-c++", "objective-cpp"),
+    ("vue.js", "vuejs"),
+    ("next.js", "nextjs"),
+    ("nuxt.js", "nuxtjs"),
+    ("express.js", "expressjs"),
+    ("cli-application", "cli"),
+    ("web-application", "web-app"),
+    ("backend-service", "backend"),
+];
+
+// Declared topics spelled differently than the suggestion for the same thing
+const TOPIC_ALIASES: &[(&str, &str)] = &[
+    ("golang", "go"),
+    ("vue", "vuejs"),
+    ("express", "expressjs"),
+    ("postgres", "postgresql"),
+    ("mongo", "mongodb"),
+    ("k8s", "kubernetes"),
+    ("command-line", "cli"),
+    ("command-line-tool", "cli"),
+];
+
+// Project traits about the state of the repository, not what it is
+const NOT_TOPICS: &[&str] = &["tested-project", "documented-project", "example-driven"];
+
+// Languages a declared topic is checked against
+const LANGUAGES: &[&str] = &[
+    "Rust",
+    "Python",
+    "JavaScript",
+    "TypeScript",
+    "Java",
+    "C++",
+    "C#",
+    "Go",
+    "PHP",
+    "Ruby",
+    "Swift",
+    "Kotlin",
+    "Scala",
+    "Haskell",
+    "OCaml",
+];
+
+// Topics the detected languages, frameworks, databases and AI tags imply that
+// aren't declared, declared topics naming a technology the analysis didn't
+// find, and a description when the current one is missing or says little
+pub fn suggest(analysis: &RepositoryAnalysis) -> MetadataSuggestions {
+    let declared: Vec<String> = analysis
+        .metadata
+        .topics
+        .iter()
+        .map(|topic| canonical(topic))
+        .collect();
+    let candidates = candidates(analysis);
+
+    let room = MAX_TOPICS.saturating_sub(declared.len());
+    let topics: Vec<TopicSuggestion> = candidates
+        .iter()
+        .filter(|candidate| !declared.contains(&candidate.topic))
+        .take(room)
+        .cloned()
+        .collect();
+
+    // Only technologies the detectors know, a topic they can't see isn't wrong
+    let rules = DetectionRules::builtin();
+    let known: Vec<(String, String)> = LANGUAGES
+        .iter()
+        .copied()
+        .chain(rules.names(Category::Framework))
+        .chain(rules.names(Category::Database))
+        .map(|name| (canonical(&topic(name)), name.to_string()))
+        .collect();
+    // Any trace counts as found here, however small or unsure
+    let info = &analysis.project_info;
+    let found: Vec<String> = analysis
+        .code_metrics
+        .language_stats
+        .keys()
+        .chain(info.primary_language.iter())
+        .chain(info.frameworks.iter().map(|d| &d.name))
+        .chain(info.database_technologies.iter().map(|d| &d.name))
+        .map(|name| canonical(&topic(name)))
+        .collect();
+    let remove_topics = analysis
+        .metadata
+        .topics
+        .iter()
+        .filter_map(|declared| {
+            let declared_topic = canonical(declared);
+            let (_, name) = known.iter().find(|(topic, _)| *topic == declared_topic)?;
+            if found.contains(&declared_topic) {
+                return None;
+            }
+            Some(TopicSuggestion {
+                topic: declared.clone(),
+                reason: format!("declared, but no {} was detected", name),
+            })
+        })
+        .collect();
+
+    MetadataSuggestions {
+        topics,
+        remove_topics,
+        description: suggest_description(analysis),
+    }
+}
+
+// Most telling first: languages, then frameworks and databases, the project
+// shape, deployment and the AI tags
+fn candidates(analysis: &RepositoryAnalysis) -> Vec<TopicSuggestion> {
+    let info = &analysis.project_info;
+    let mut candidates: Vec<TopicSuggestion> = Vec::new();
+    let mut add = |name: &str, reason: String| {
+        let topic = canonical(&topic(name));
+        if !topic.is_empty() && !candidates.iter().any(|c| c.topic == topic) {
+            candidates.push(TopicSuggestion { topic, reason });
+        }
+    };
+
+    if let Some(language) = &info.primary_language {
+        add(language, "primary language".to_string());
+    }
+    for language in &info.language_layout.languages {
+        add(language, "significant share of the code".to_string());
+    }
+    let detections = [
+        &info.frameworks,
+        &info.database_technologies,
+        &info.project_type,
+        &info.deployment_configs,
+    ];
+    for detection in detections.into_iter().flatten() {
+        if detection.confidence < MIN_CONFIDENCE || NOT_TOPICS.contains(&detection.name.as_str()) {
+            continue;
+        }
+        add(&detection.name, detected(detection));
+    }
+    for tag in &analysis.tags {
+        if matches!(tag.category.as_str(), "domain" | "architecture")
+            && tag.confidence >= MIN_CONFIDENCE
+        {
+            add(
+                &tag.value,
+                format!("{} tag ({:.2})", tag.category, tag.confidence),
+            );
+        }
+    }
+    candidates
+}
+
+fn detected(detection: &Detection) -> String {
+    match detection.evidence.first() {
+        Some(evidence) => format!("{} detected ({})", detection.name, evidence.label),
+        None => format!("{} detected", detection.name),
+    }
+}
+
+// GitHub topic form: lowercase letters, digits and single hyphens
+fn topic(name: &str) -> String {
+    let lower = name.to_lowercase();
+    if let Some((_, topic)) = TOPIC_NAMES.iter().find(|(n, _)| *n == lower) {
+        return topic.to_string();
+    }
+    let mut topic = String::new();
+    for c in lower.chars() {
+        if c.is_ascii_alphanumeric() {
+            topic.push(c);
+        } else if !topic.is_empty() && !topic.ends_with('-') {
+            topic.push('-');
+        }
+    }
+    topic.truncate(MAX_TOPIC_LENGTH);
+    topic.trim_end_matches('-').to_string()
+}
+
+// Declared spelling variants compare as the suggested one
+fn canonical(topic: &str) -> String {
+    let topic = topic.to_lowercase();
+    match TOPIC_ALIASES.iter().find(|(alias, _)| *alias == topic) {
+        Some((_, canonical)) => canonical.to_string(),
+        None => topic,
+    }
+}
+
+fn suggest_description(analysis: &RepositoryAnalysis) -> Option<DescriptionSuggestion> {
+    let info = &analysis.project_info;
+    let current = analysis
+        .metadata
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|description| !description.is_empty());
+    let stack: Vec<&str> = info
+        .primary_language
+        .iter()
+        .map(String::as_str)
+        .chain(
+            info.frameworks
+                .iter()
+                .filter(|d| d.confidence >= MIN_CONFIDENCE)
+                .map(|d| d.name.as_str()),
+        )
+        .collect();
+
+    let mut reasons = Vec::new();
+    match current {
+        None => reasons.push("the repository has no description".to_string()),
+        Some(description) => {
+            let words = description.split_whitespace().count();
+            let squashed = |text: &str| {
+                text.to_lowercase()
+                    .chars()
+                    .filter(char::is_ascii_alphanumeric)
+                    .collect::<String>()
+            };
+            if squashed(description) == squashed(&analysis.metadata.name) {
+                reasons.push("the description repeats the repository name".to_string());
+            } else if words < MIN_DESCRIPTION_WORDS {
+                reasons.push(format!("the description has only {} words", words));
+            }
+            let lower = description.to_lowercase();
+            let mentioned = stack
+                .iter()
+                .any(|name| lower.contains(&name.to_lowercase()) || lower.contains(&topic(name)));
+            if !stack.is_empty() && !mentioned {
+                reasons.push(format!(
+                    "the description doesn't mention the stack ({})",
+                    stack.join(", ")
+                ));
+            }
+        }
+    }
+    if reasons.is_empty() {
+        return None;
+    }
+
+    let suggested = summary_sentence(analysis).unwrap_or_else(|| template(analysis));
+    if current == Some(suggested.as_str()) {
+        return None;
+    }
+    Some(DescriptionSuggestion {
+        current: current.map(str::to_string),
+        suggested,
+        reasons,
+    })
+}
+
+// First sentence of the AI executive summary, when short enough
+fn summary_sentence(analysis: &RepositoryAnalysis) -> Option<String> {
+    let paragraph = analysis.ai_insights.as_ref()?.executive_summary.first()?;
+    let sentence = match paragraph.find(". ") {
+        Some(end) => &paragraph[..=end],
+        None => paragraph.as_str(),
+    };
+    let sentence = sentence.trim();
+    (!sentence.is_empty() && sentence.len() <= MAX_DESCRIPTION_LENGTH).then(|| sentence.to_string())
+}
+
+// "Rust command-line tool built with Clap and SQLite" from the detections
+fn template(analysis: &RepositoryAnalysis) -> String {
+    let info = &analysis.project_info;
+    let confident = |detections: &[Detection]| -> Vec<String> {
+        detections
+            .iter()
+            .filter(|d| d.confidence >= MIN_CONFIDENCE)
+            .map(|d| d.name.clone())
+            .collect()
+    };
+    let kind = confident(&info.project_type)
+        .iter()
+        .find_map(|name| match name.as_str() {
+            "cli-application" => Some("command-line tool"),
+            "library" => Some("library"),
+            "web-application" => Some("web application"),
+            "backend-service" => Some("backend service"),
+            _ => None,
+        })
+        .unwrap_or("project");
+
+    let mut description = match &info.primary_language {
+        Some(language) => format!("{} {}", language, kind),
+        None => {
+            let mut kind = kind.to_string();
+            kind[..1].make_ascii_uppercase();
+            kind
+        }
+    };
+    let mut built_with = confident(&info.frameworks);
+    built_with.extend(confident(&info.database_technologies));
+    built_with.truncate(3);
+    if let Some((last, rest)) = built_with.split_last() {
+        description.push_str(" built with ");
+        if !rest.is_empty() {
+            description.push_str(&rest.join(", "));
+            description.push_str(" and ");
+        }
+        description.push_str(last);
+    }
+    if let Some(domain) = analysis
+        .tags
+        .iter()
+        .find(|tag| tag.category == "domain" && tag.confidence >= MIN_CONFIDENCE)
+    {
+        description.push_str(" for ");
+        description.push_str(&domain.value.replace('-', " "));
+    }
+    description
+}
+
+pub fn print_suggestions(suggestions: &MetadataSuggestions) {
+    let MetadataSuggestions {
+        topics,
+        remove_topics,
+        description,
+    } = suggestions;
+    if topics.is_empty() && remove_topics.is_empty() && description.is_none() {
+        println!("The topics and description already match the analysis");
+        return;
+    }
+
+    if !topics.is_empty() {
+        println!("Topics to add:");
+        for topic in topics {
+            println!("  + {:<24} {}", topic.topic, topic.reason);
+        }
+    }
+    if !remove_topics.is_empty() {
+        println!("Topics to reconsider:");
+        for topic in remove_topics {
+            println!("  - {:<24} {}", topic.topic, topic.reason);
+        }
+    }
+    if let Some(description) = description {
+        println!("Description:");
+        println!(
+            "  current:   {}",
+            description.current.as_deref().unwrap_or("(none)")
+        );
+        println!("  suggested: {}", description.suggested);
+        for reason in &description.reasons {
+            println!("  because {}", reason);
+        }
+    }
+}
+
+// Adds the suggested topics to the live ones and sets the suggested
+// description. Topics to reconsider are left for a human to remove
+pub async fn apply(
+    client: &GitHubClient,
+    owner: &str,
+    repo: &str,
+    suggestions: &MetadataSuggestions,
+) -> Result<()> {
+    if !suggestions.topics.is_empty() {
+        // The saved analysis may be older than the topics on GitHub
+        let mut topics = client.get_topics(owner, repo).await?;
+        let before = topics.len();
+        for suggestion in &suggestions.topics {
+            if topics.len() < MAX_TOPICS && !topics.contains(&suggestion.topic) {
+                topics.push(suggestion.topic.clone());
+            }
+        }
+        if topics.len() > before {
+            client.replace_topics(owner, repo, &topics).await?;
+            info!(
+                "Added {} topics to {}/{}",
+                topics.len() - before,
+                owner,
+                repo
+            );
+        } else {
+            warn!(
+                "{}/{} already has the suggested topics or no room for more",
+                owner, repo
+            );
+        }
+    }
+    if let Some(description) = &suggestions.description {
+        client
+            .update_description(owner, repo, &description.suggested)
+            .await?;
+        info!("Updated the description of {}/{}", owner, repo);
+    }
+    Ok(())
+}
@@ -1,8 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
+use crate::file_index::FileIndex;
+
 // GitHub API response structures
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GitHubUser {
@@ -21,18 +23,6 @@ pub struct GitHubLicense {
     pub url: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct GitHubTopic {
-    pub name: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct GitHubLanguage {
-    pub name: String,
-    pub bytes: u64,
-    pub percentage: f64,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GitHubRelease {
     pub tag_name: String,
@@ -44,6 +34,46 @@ pub struct GitHubRelease {
     pub published_at: Option<DateTime<Utc>>,
     pub author: GitHubUser,
     pub assets_count: usize,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub size: u64, // Bytes
+    pub download_url: String,
+}
+
+// An artifact of a release downloaded and hashed against the checksum file
+// shipped next to it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChecksumVerification {
+    pub tag_name: String,
+    pub checksum_file: String,
+    pub asset: String,
+    pub algorithm: String, // sha256 or sha512
+    pub expected: String,
+    pub actual: String,
+    pub verified: bool,
+    pub attested: Option<bool>, // GitHub holds a build provenance attestation for the digest
+}
+
+// Checksums, signatures and attestations shipped with the recent releases
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReleaseHygiene {
+    pub releases: Vec<ReleaseArtifacts>,
+    pub verification: Option<ChecksumVerification>,
+    pub signals: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReleaseArtifacts {
+    pub tag_name: String,
+    pub artifacts: Vec<String>, // Downloads, checksum, signature and attestation files aside
+    pub checksum_files: Vec<String>, // SHA256SUMS, *.sha256, checksums.txt, ...
+    pub signatures: Vec<String>, // .sig, .asc, .minisig, cosign certificates and bundles
+    pub attestations: Vec<String>, // in-toto provenance files
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -57,6 +87,35 @@ pub struct GitHubIssue {
     pub author: GitHubUser,
     pub labels: Vec<String>,
     pub comments: u32,
+    #[serde(default)]
+    pub author_association: String, // OWNER, MEMBER, COLLABORATOR, CONTRIBUTOR, NONE, ...
+    #[serde(default)]
+    pub first_response_at: Option<DateTime<Utc>>, // First comment by a maintainer other than the author
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GitHubMilestone {
+    pub number: u32,
+    pub title: String,
+    pub state: String, // open or closed
+    pub due_on: Option<DateTime<Utc>>,
+    pub open_issues: u32,
+    pub closed_issues: u32,
+    pub updated_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+// A Projects v2 board linked to the repository
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectBoard {
+    pub number: u32,
+    pub title: String,
+    pub url: String,
+    pub public: bool,
+    pub closed: bool,
+    pub updated_at: DateTime<Utc>,
+    pub items: u32,
+    pub status_counts: BTreeMap<String, u32>, // Status field value -> items, of the first 100 items
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -108,6 +167,72 @@ pub struct RepositoryMetadata {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub pushed_at: DateTime<Utc>,
+    #[serde(default)]
+    pub parent: Option<String>, // full_name of the upstream repository for forks
+    #[serde(default)]
+    pub mirror_url: Option<String>,
+}
+
+// Archived, disabled and mirrored repositories are read-only copies or snapshots
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RepositoryStatus {
+    pub read_only: bool,
+    pub archived: bool,
+    pub disabled: bool,
+    pub is_mirror: bool,
+    pub canonical_source: Option<String>, // Where development actually happens, for mirrors
+    pub notes: Vec<String>,
+}
+
+// How a fork relates to its upstream repository
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ForkRelationship {
+    pub parent_full_name: String,
+    pub parent_html_url: String,
+    pub parent_default_branch: String,
+    pub parent_stars: u32,
+    pub parent_archived: bool,
+    pub parent_pushed_at: Option<DateTime<Utc>>,
+    pub ahead_by: Option<u32>, // Commits on the fork's default branch missing upstream
+    pub behind_by: Option<u32>, // Upstream commits missing from the fork
+    pub compare_status: Option<String>, // identical, ahead, behind, diverged
+    pub assessment: String,    // in-sync, stale-mirror, active-hard-fork, inactive-fork, unknown
+    pub note: String,
+}
+
+// Similar repository found through GitHub search
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelatedProject {
+    pub full_name: String,
+    pub html_url: String,
+    pub description: Option<String>,
+    pub stars: u32,
+    pub last_activity: Option<DateTime<Utc>>,
+    pub license: Option<String>,
+}
+
+// Package registry data for packages published from the repository
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PublishedPackage {
+    pub registry: String, // crates.io, npm, pypi
+    pub name: String,
+    pub manifest_path: PathBuf,
+    pub manifest_version: Option<String>,
+    pub latest_version: Option<String>,
+    pub manifest_matches_latest: Option<bool>,
+    pub total_downloads: Option<u64>,
+    pub recent_downloads: Option<u64>,
+    pub version_count: u32,
+    pub yanked_versions: Vec<String>,
+    pub first_published: Option<DateTime<Utc>>,
+    pub last_published: Option<DateTime<Utc>>,
+    pub average_days_between_releases: Option<f64>,
+    pub package_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PackagePublishing {
+    pub packages: Vec<PublishedPackage>,
 }
 
 // File analysis structures
@@ -127,17 +252,24 @@ pub struct FileInfo {
     pub encoding: Option<String>,
     pub hash: String,
     pub content_preview: Option<String>, // First few lines for analysis
+    #[serde(default)]
+    pub complexity: Option<u32>, // Estimated cyclomatic complexity, source files only
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub functions: Vec<FunctionMetrics>,
 }
 
+// Another project's code inside the repository, left out of the structure
+// and the metrics like ignored directories
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct DirectoryInfo {
-    pub path: PathBuf,
-    pub name: String,
+pub struct EmbeddedCode {
+    pub path: PathBuf,          // Repository-relative directory
+    pub kind: String,           // nested-repository, submodule, vendored
+    pub project: String,        // Known project, else the directory or remote name
+    pub source: Option<String>, // Remote URL of a nested repository, homepage of a known project
     pub file_count: u32,
-    pub subdirectory_count: u32,
     pub total_size: u64,
-    pub files: Vec<FileInfo>,
-    pub subdirectories: Vec<DirectoryInfo>,
+    #[serde(default)]
+    pub partial_count: bool, // Counting stopped early, the totals are lower bounds
 }
 
 // Code analysis structures
@@ -153,6 +285,54 @@ pub struct LanguageStats {
     pub complexity_score: Option<f64>,
 }
 
+// Function found in a source file, see analyzers/complexity.rs
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FunctionMetrics {
+    pub name: String,
+    pub file: PathBuf,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub lines_of_code: u32,
+    pub complexity: u32,
+    pub parameter_count: u32,
+}
+
+// Spread of a metric over files (or functions), with power-of-two histogram buckets
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Distribution {
+    pub count: u32,
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub histogram: Vec<HistogramBucket>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistogramBucket {
+    pub upper_bound: u64, // Inclusive, the lower bound is the previous bucket's
+    pub count: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MetricDistributions {
+    pub file_size: Distribution,
+    pub lines_of_code: Distribution,
+    pub complexity: Distribution,
+    pub function_length: Distribution,
+}
+
+// Raw per-extension breakdown, including extensions with no known language
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExtensionStats {
+    pub extension: String, // Lowercased, "(none)" for files without an extension
+    pub file_count: u32,
+    pub total_bytes: u64,
+    pub lines_of_code: u32,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CodeMetrics {
     pub total_files: u32,
@@ -162,9 +342,67 @@ pub struct CodeMetrics {
     pub total_comment_lines: u32,
     pub total_size: u64,
     pub language_stats: HashMap<String, LanguageStats>,
+    pub extension_stats: HashMap<String, ExtensionStats>,
+    pub distributions: MetricDistributions,
+    pub language_distributions: HashMap<String, MetricDistributions>,
+    pub longest_functions: Vec<FunctionMetrics>,
     pub average_file_size: f64,
     pub largest_files: Vec<FileInfo>,
     pub most_complex_files: Vec<FileInfo>,
+    #[serde(default)]
+    pub panic_density: Option<PanicDensity>, // Rust repositories only
+    #[serde(default)]
+    pub error_handling: Option<ErrorHandling>,
+}
+
+// Panicking calls in non-test Rust code, an error-handling hygiene measure
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PanicDensity {
+    pub rust_loc: u32, // Non-blank, non-comment lines outside tests
+    pub unwraps: u32,
+    pub expects: u32,
+    pub panics: u32,
+    pub todos: u32, // todo! and unimplemented!
+    pub per_kloc: f64,
+    pub score: f64, // 0-100, halved at 5 panicking calls per KLOC
+    pub hotspots: Vec<PanicHotspot>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PanicHotspot {
+    pub path: PathBuf,
+    pub count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>, // Link to the file at the analyzed commit
+}
+
+// Broad, empty and ignored error handling in non-test code of languages with
+// exceptions or error values
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ErrorHandling {
+    pub languages: Vec<LanguageErrorHandling>,
+    pub findings: Vec<ErrorHandlingFinding>, // The first ones found
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LanguageErrorHandling {
+    pub language: String,
+    pub loc: u32,
+    pub handlers: u32,      // catch/except/rescue blocks, Go err != nil checks
+    pub broad_catches: u32, // bare except:, catch (Exception e), rescue Exception, ...
+    pub empty_handlers: u32,
+    pub ignored_errors: u32, // Go `_ = err` and discarded error returns, Swift try?
+    pub issues_per_kloc: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ErrorHandlingFinding {
+    pub path: PathBuf,
+    pub line: u32,
+    pub language: String,
+    pub kind: String, // broad catch, empty handler, ignored error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>, // Link to the file at the analyzed commit
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -188,31 +426,232 @@ pub struct DocumentationFile {
 }
 
 // Git analysis structures
+// Rolling commit counts up to the analysis time
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CommitActivity {
+    pub commits_last_30_days: u32,
+    pub commits_last_90_days: u32,
+    pub commits_previous_90_days: u32, // The 90 days before the last 90
+    pub trend: String,                 // growing, stable, declining
+}
+
+// What the commits of one quarter were about
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuarterTopics {
+    pub quarter: String, // 2024-Q3
+    pub commits: u32,
+    pub topics: Vec<CommitTopic>, // Most commits first
+}
+
+// Commits sharing a theme (CI, performance, ...) or a keyword
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommitTopic {
+    pub label: String, // "CI", "auth refactoring"
+    pub commits: u32,
+    pub example: String, // Subject of the newest of them
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GitAnalysis {
     pub total_commits: u32,
     pub contributors: Vec<GitHubUser>,
+    #[serde(default)]
+    pub history_contributors: Vec<GitHubUser>, // Authors of the commits read, one per person
+    #[serde(default)]
+    pub merged_identities: u32, // Author identities folded into another by .mailmap or a shared name or email
+    #[serde(default)]
+    pub top_contributor_domains: Vec<DomainShare>,
+    #[serde(default)]
+    pub commits_by_domain: BTreeMap<String, BTreeMap<String, u32>>, // quarter (2024-Q3) -> email domain -> commits
     pub recent_commits: Vec<GitHubCommit>,
     pub commit_frequency: HashMap<String, u32>, // month -> commit count
-    pub most_active_files: Vec<(String, u32)>,  // file path -> modification count
+    #[serde(default)]
+    pub weekly_commit_frequency: HashMap<String, u32>, // ISO week (2024-W07) -> commit count
+    #[serde(default)]
+    pub daily_commit_frequency: HashMap<String, u32>, // day -> commit count
+    #[serde(default)]
+    pub activity: CommitActivity,
+    #[serde(default)]
+    pub commit_topics: Vec<QuarterTopics>, // Newest quarter first
+    pub most_active_files: Vec<(String, u32)>, // file path -> modification count
     pub branch_count: u32,
     pub tag_count: u32,
     pub first_commit_date: Option<DateTime<Utc>>,
     pub last_commit_date: Option<DateTime<Utc>>,
+    pub head_sha: Option<String>,
+}
+
+// Commit email domain of some of the top contributors
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DomainShare {
+    pub domain: String,
+    pub contributors: u32,
+    pub commits: u32,
+    pub share: f64, // 0 to 1, of the top contributors' commits
+}
+
+// Changes between two release tags
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub status: String, // added, modified, deleted, renamed
+    pub old_path: Option<PathBuf>,
+    pub additions: u32,
+    pub deletions: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReleaseContributor {
+    pub name: String,
+    pub email: String,
+    pub commits: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DependencyChange {
+    pub manifest: PathBuf,
+    pub name: String,
+    pub change: String, // added, removed, updated
+    pub from_version: Option<String>,
+    pub to_version: Option<String>,
+}
+
+// Directory that didn't exist at the earlier tag
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NewModule {
+    pub path: PathBuf,
+    pub files: u32,
+    pub lines_added: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReleaseDiff {
+    pub url: String,
+    pub from_tag: String,
+    pub to_tag: String,
+    pub from_sha: String,
+    pub to_sha: String,
+    pub commit_count: u32,
+    pub files_changed: u32,
+    pub insertions: u32,
+    pub deletions: u32,
+    pub loc_delta: i64,
+    pub files: Vec<FileChange>,
+    pub contributors: Vec<ReleaseContributor>,
+    pub dependency_changes: Vec<DependencyChange>,
+    pub new_modules: Vec<NewModule>,
+    pub ai_summary: Option<String>,
+}
+
+// Language that owns a directory, e.g. web/ is TypeScript
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LanguageBoundary {
+    pub directory: PathBuf,
+    pub language: String,
+    pub share: f64, // Percentage of the directory's code lines
+    pub lines_of_code: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LanguageLayout {
+    pub classification: String, // single-language, polyglot, frontend-backend, unknown
+    pub languages: Vec<String>, // Code languages with a significant share, largest first
+    pub boundaries: Vec<LanguageBoundary>,
 }
 
 // Project type detection
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProjectInfo {
     pub primary_language: Option<String>,
-    pub project_type: Vec<String>, // web, cli, library, framework, etc.
-    pub frameworks: Vec<String>,
-    pub build_tools: Vec<String>,
-    pub package_managers: Vec<String>,
-    pub testing_frameworks: Vec<String>,
-    pub ci_cd_tools: Vec<String>,
-    pub deployment_configs: Vec<String>,
-    pub database_technologies: Vec<String>,
+    pub language_layout: LanguageLayout,
+    pub project_type: Vec<Detection>, // web, cli, library, framework, etc.
+    pub frameworks: Vec<Detection>,
+    pub build_tools: Vec<Detection>,
+    pub package_managers: Vec<Detection>,
+    pub testing_frameworks: Vec<Detection>,
+    pub ci_cd_tools: Vec<Detection>,
+    pub deployment_configs: Vec<Detection>,
+    pub database_technologies: Vec<Detection>,
+    #[serde(default)]
+    pub feature_flags: FeatureFlags,
+    #[serde(default)]
+    pub execution_environments: Vec<Detection>, // wasm32, no_std, embedded, browser, node, deno
+}
+
+// Technology or project trait a detector found, with how sure it is and the
+// files it was seen in
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(from = "DetectionRecord")]
+pub struct Detection {
+    pub name: String,
+    pub confidence: f64, // 0 to 1, a declared dependency is near certain, a file name a guess
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evidence: Vec<EvidenceRef>, // The signals that matched, label says which
+}
+
+// Analyses saved before detections had a confidence list bare names
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DetectionRecord {
+    Name(String),
+    Detection {
+        name: String,
+        confidence: f64,
+        #[serde(default)]
+        evidence: Vec<EvidenceRef>,
+    },
+}
+
+impl From<DetectionRecord> for Detection {
+    fn from(record: DetectionRecord) -> Self {
+        match record {
+            DetectionRecord::Name(name) => Self {
+                name,
+                confidence: 0.5, // Unknown
+                evidence: Vec::new(),
+            },
+            DetectionRecord::Detection {
+                name,
+                confidence,
+                evidence,
+            } => Self {
+                name,
+                confidence,
+                evidence,
+            },
+        }
+    }
+}
+
+// File and line backing a finding, with its link on the GitHub web UI at the
+// analyzed commit
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EvidenceRef {
+    pub label: String,
+    pub path: PathBuf,
+    pub line: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+// Feature flag systems in use and the features they declare
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FeatureFlags {
+    pub systems: Vec<String>, // Cargo features, LaunchDarkly, Unleash, Preprocessor flags, ...
+    pub cargo_features: Vec<CargoFeature>,
+    pub preprocessor_flags: Vec<String>, // ENABLE_*, FEATURE_*, ... macros in #if guards
+}
+
+// A [features] entry of a Cargo.toml and what enabling it implies
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CargoFeature {
+    pub manifest: PathBuf,
+    pub name: String,
+    pub default: bool,                    // Turned on by the default feature
+    pub implicit: bool,                   // Created by Cargo for an optional dependency
+    pub features: Vec<String>,            // Other features of the crate it enables
+    pub dependencies: Vec<String>,        // Optional dependencies it pulls in
+    pub dependency_features: Vec<String>, // dependency/feature entries
 }
 
 // Security and quality analysis
@@ -224,6 +663,739 @@ pub struct SecurityInfo {
     pub vulnerability_alerts: Vec<String>,
     pub outdated_dependencies: Vec<String>,
     pub license_compatibility: Vec<String>,
+    #[serde(default)]
+    pub ci_security_checks: Vec<String>, // codeql, dependabot, cargo-audit, ...
+    #[serde(default)]
+    pub secret_findings: Vec<SecretFinding>,
+    #[serde(default)]
+    pub unsafe_usage: Option<UnsafeUsage>, // Rust repositories only
+    #[serde(default)]
+    pub dependency_evidence: Vec<EvidenceRef>, // Manifest lines of the outdated dependencies
+    #[serde(default)]
+    pub published_advisories: Option<AdvisorySummary>, // None when they couldn't be listed
+    #[serde(default)]
+    pub testing_depth: TestingDepth,
+}
+
+// Testing beyond example-based unit tests: fuzzing, property-based testing
+// and static analysis in CI
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TestingDepth {
+    pub continuous_fuzzing: Vec<EvidenceRef>, // OSS-Fuzz or ClusterFuzzLite, one per service
+    pub fuzz_targets: Vec<EvidenceRef>,       // Labeled with the fuzzing engine
+    pub property_testing: Vec<EvidenceRef>,   // Library in the manifest declaring it
+    pub sast: Vec<EvidenceRef>,               // Tool in the workflow running it
+}
+
+// A GitHub Security Advisory the repository published about its own code,
+// as opposed to advisories affecting its dependencies
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PublishedAdvisory {
+    pub ghsa_id: String,
+    pub cve_id: Option<String>,
+    pub summary: String,
+    pub severity: String, // critical, high, medium, low or unknown
+    pub created_at: DateTime<Utc>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub patched_versions: Vec<String>, // One range per affected package, empty when unpatched
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AdvisorySummary {
+    pub advisories: Vec<PublishedAdvisory>, // Newest first
+    pub by_severity: BTreeMap<String, u32>,
+    pub unpatched: u32,                 // Published without a patched version
+    pub median_patch_days: Option<f64>, // From the advisory draft to its publication
+    pub max_patch_days: Option<f64>,
+}
+
+// Unsafe code and FFI boundaries of the Rust crates of a repository
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UnsafeUsage {
+    pub crates: Vec<CrateUnsafety>,
+    pub total_unsafe_blocks: u32,
+    pub total_unsafe_functions: u32,
+    pub crates_forbidding_unsafe: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CrateUnsafety {
+    pub name: String,
+    pub manifest: PathBuf,
+    pub unsafe_blocks: u32,
+    pub unsafe_functions: u32,
+    pub unsafe_impls: u32,
+    pub extern_blocks: u32, // extern "C" blocks importing foreign functions
+    pub exported_functions: u32, // #[no_mangle] items exported to foreign code
+    pub unsafe_code_lint: Option<String>, // forbid or deny, from #![...] or [lints]
+    pub build_script: bool,
+    pub ffi_build_tools: Vec<String>, // bindgen, cc, cmake, ... build dependencies
+    pub links: Option<String>,        // Native library named by the links key
+    pub hotspots: Vec<UnsafeHotspot>,
+}
+
+// File with the most unsafe blocks, functions and impls of its crate
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UnsafeHotspot {
+    pub path: PathBuf,
+    pub unsafe_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>, // Link to the file at the analyzed commit
+}
+
+// Quality gate outcome of the check command
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CheckResult {
+    pub name: String, // Threshold key from analyzer.toml
+    pub threshold: String,
+    pub actual: String,
+    pub passed: bool,
+    pub details: Vec<String>, // Offending functions, files or findings
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CheckReport {
+    pub url: String,
+    pub checked_at: DateTime<Utc>,
+    pub passed: bool,
+    pub results: Vec<CheckResult>,
+}
+
+// File/line annotation published with a GitHub check run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CheckAnnotation {
+    pub path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub annotation_level: String, // notice, warning or failure
+    pub title: String,
+    pub message: String,
+}
+
+// Security posture change between two saved analyses
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SecurityChange {
+    pub severity: String, // critical, high, medium, low
+    pub category: String, // vulnerable-dependency, secret, ci-check, policy
+    pub description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SecurityDelta {
+    pub from_analyzed_at: DateTime<Utc>,
+    pub to_analyzed_at: DateTime<Utc>,
+    pub introduced: Vec<SecurityChange>,
+    pub resolved: Vec<SecurityChange>,
+}
+
+// Security findings collected during the file system scan
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SecurityScan {
+    pub ci_security_checks: Vec<String>,
+    pub secret_findings: Vec<SecretFinding>,
+    #[serde(default)]
+    pub unsafe_usage: Option<UnsafeUsage>,
+    #[serde(default)]
+    pub testing_depth: TestingDepth,
+}
+
+// Location of a likely committed credential
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SecretFinding {
+    pub path: PathBuf,
+    pub line: usize,
+    pub kind: String, // aws-access-key, github-token, private-key, ...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>, // Link to the file at the analyzed commit
+}
+
+// Documentation site / GitHub Pages
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DocsSite {
+    pub generator: Option<String>, // MkDocs, Docusaurus, mdBook, Sphinx, Jekyll, ...
+    pub config_path: Option<PathBuf>,
+    pub site_url: Option<String>,
+    pub has_pages: bool,
+    pub build_workflows: Vec<PathBuf>,
+}
+
+// Extractive summary of the README, what the summary says about the project
+// when no AI provider is configured
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReadmeSummary {
+    pub sentences: Vec<String>, // Key sentences in README order, the lead sentence first
+    pub sections: Vec<String>,  // Top-level sections, setup and credits left out
+}
+
+// Likely misspellings and inconsistently cased product names in the README
+// and docs, a polish signal for documentation quality
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DocsSpelling {
+    pub files_checked: u32,
+    pub words_checked: u32, // Prose only, code, URLs and identifiers are skipped
+    pub misspelling_count: u32,
+    pub misspellings_per_1k_words: f64,
+    pub misspellings: Vec<EvidenceRef>, // Labeled "recieve -> receive", the first few only
+    pub terminology: Vec<TermInconsistency>,
+    pub signals: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TermInconsistency {
+    pub term: String, // Official casing, or the most used one for the project's name
+    pub variants: Vec<String>, // Other casings used, most used first
+    pub occurrences: u32, // Of the variants
+    pub evidence: Vec<EvidenceRef>, // First use of each variant
+}
+
+// How the README and docs read, next to how much of them there is:
+// readability, sentence and section lengths, and code samples per line of prose
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DocsProse {
+    pub files_checked: u32,
+    pub word_count: u32,
+    pub character_count: u32, // Letters and digits of the words
+    pub sentence_count: u32,
+    pub section_count: u32, // Headings with prose under them, and the text before the first
+    pub avg_word_length: f64,
+    pub avg_sentence_words: f64,
+    pub avg_section_words: f64,
+    pub flesch_reading_ease: Option<f64>, // Higher is easier, 60-70 is plain English
+    pub flesch_kincaid_grade: Option<f64>, // US school grade
+    pub prose_lines: u32,
+    pub code_lines: u32, // In code blocks
+    pub code_to_prose_ratio: f64,
+    pub signals: Vec<String>,
+}
+
+// Generated API reference docs: the generators set up to build them, where
+// they are published, and whether a library ships any at all
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ApiDocs {
+    pub tools: Vec<EvidenceRef>, // Labeled with the tool: docs.rs, TypeDoc, Sphinx autodoc, Javadoc, ...
+    pub urls: Vec<ApiDocsUrl>,
+    pub library: bool,
+    pub signals: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiDocsUrl {
+    pub url: String,
+    pub source: String, // Cargo.toml documentation, README badge, crate name, ...
+    pub inferred: bool, // Built from a package name rather than linked by the project
+    pub resolves: Option<bool>, // Needs network, None until checked
+}
+
+// Build reproducibility: lockfiles, toolchain pins and Docker digests
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ManifestLock {
+    pub manifest: PathBuf,
+    pub ecosystem: String,
+    pub lockfile: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ToolchainPin {
+    pub path: PathBuf,
+    pub tool: String, // rust, node, python, ...
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DockerBaseImage {
+    pub path: PathBuf,
+    pub image: String,
+    pub digest_pinned: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BuildReproducibility {
+    pub manifests: Vec<ManifestLock>,
+    pub toolchain_pins: Vec<ToolchainPin>,
+    pub docker_images: Vec<DockerBaseImage>,
+    pub score: f64, // 0-100, over the components that apply
+    pub findings: Vec<String>,
+}
+
+// Minimum language and toolchain versions declared in manifests, and the
+// versions CI runs
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ToolchainRequirements {
+    pub requirements: Vec<ToolchainRequirement>,
+    pub signals: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ToolchainRequirement {
+    pub language: String,                 // Rust, Node.js, Python, Go or Java
+    pub minimum: Option<String>,          // Lowest version the declaration allows
+    pub constraint: Option<String>,       // As declared, e.g. ">=3.9,<4"
+    pub declared_in: Option<EvidenceRef>, // Labeled with the field, rust-version, engines.node, ...
+    pub ci_versions: Vec<String>,         // Numbered versions CI runs, oldest first
+    pub ci: Vec<EvidenceRef>,             // One per workflow
+}
+
+// Linters, formatters, hooks and editor configuration
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeveloperTool {
+    pub name: String,     // clippy, ruff, golangci-lint, husky, ...
+    pub category: String, // linter, formatter, type_checker, git_hooks, commit_conventions, editor_config
+    pub config_path: PathBuf,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DeveloperTooling {
+    pub tools: Vec<DeveloperTool>,
+    pub maturity_score: f64, // 0-100, weighted by category
+    pub missing_categories: Vec<String>,
+}
+
+// Program of the repository and the command that starts it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EntryPoint {
+    pub name: String,
+    pub kind: String, // cargo-bin, npm-main, npm-bin, python-main, python-script, docker
+    pub path: PathBuf, // Source file, or the manifest or Dockerfile declaring it
+    pub command: Option<String>,
+}
+
+// Named task of a build tool: Make and just targets, npm and composer scripts
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectTask {
+    pub name: String,
+    pub runner: String, // make, just, npm, yarn, pnpm, bun, composer
+    pub command: String,
+    pub source: PathBuf,
+}
+
+// How to run the project, synthesized from entry points, tasks and manifests
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RunInstructions {
+    pub entry_points: Vec<EntryPoint>,
+    pub tasks: Vec<ProjectTask>,
+    pub steps: Vec<String>, // Install, build, test and run, in that order
+}
+
+// Benchmark suites, performance CI jobs and profiling setups, for users
+// choosing a library for hot paths
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PerformanceCulture {
+    pub measures_performance: bool, // Benchmarks or performance CI jobs were found
+    pub benchmarks: Vec<EvidenceRef>, // One per benchmark file, labeled with its harness
+    pub benchmark_dirs: Vec<PathBuf>,
+    pub ci_jobs: Vec<EvidenceRef>,   // Tool in the workflow running it
+    pub profiling: Vec<EvidenceRef>, // One per tool, where it is set up
+    pub signals: Vec<String>,
+}
+
+// Platforms the project is tested on, has target-specific code for or ships
+// release artifacts for
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PlatformSupport {
+    pub platforms: Vec<PlatformSignals>, // Only those with a signal
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PlatformSignals {
+    pub platform: String,            // Linux, macOS, Windows, WASM or ARM
+    pub ci: Vec<EvidenceRef>,        // One per workflow running on or building for it
+    pub code_files: u32,             // Files with code specific to it
+    pub code: Vec<EvidenceRef>,      // The first few of them
+    pub release_assets: Vec<String>, // Of the latest release shipping any
+}
+
+// Services of the repository's Dockerfiles, compose files, Helm charts and
+// Kubernetes manifests, and how they connect
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DeploymentTopology {
+    pub services: Vec<DeployedService>,
+    pub links: Vec<ServiceLink>,
+    pub sources: Vec<PathBuf>, // Files the services were read from
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeployedService {
+    pub name: String,
+    pub kind: String, // dockerfile, compose, helm chart, or the Kubernetes kind
+    pub source: PathBuf,
+    pub image: Option<String>, // Base image for Dockerfiles
+    pub ports: Vec<String>,    // "published:target", or a single port
+    pub replicas: Option<u32>,
+    pub cpu_request: Option<String>,
+    pub memory_request: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceLink {
+    pub from: usize, // Indexes into services
+    pub to: usize,
+    pub relation: String, // built from, depends on, routes to
+}
+
+// Issue and pull request templates
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TemplateField {
+    pub id: Option<String>,
+    pub label: String,
+    pub field_type: String, // textarea, input, dropdown, checkboxes, or section for markdown
+    pub required: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContributionTemplate {
+    pub path: PathBuf,
+    pub kind: String,   // issue, pull_request
+    pub format: String, // form, markdown
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub labels: Vec<String>,
+    pub fields: Vec<TemplateField>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TemplateInventory {
+    pub templates: Vec<ContributionTemplate>,
+    pub blank_issues_enabled: Option<bool>,
+    pub contact_links: u32,
+    pub has_bug_report: bool,
+    pub has_feature_request: bool,
+    pub has_pull_request_template: bool,
+    pub completeness_score: f64, // 0-100
+    pub missing: Vec<String>,
+}
+
+// How easy it is for a newcomer to start contributing, for OSS program managers
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ContributorFriendliness {
+    pub score: f64, // 0-100, over the signals that could be measured
+    pub starter_issues: StarterIssues,
+    pub has_contributing_guide: bool,
+    pub has_code_of_conduct: bool,
+    pub has_issue_templates: bool,
+    pub has_pull_request_template: bool,
+    pub median_first_response_hours: Option<f64>, // None without answered issues
+    pub sampled_issues: u32,                      // Recent issues opened by non-maintainers
+    pub responded_issues: u32,                    // Of those, answered by a maintainer
+    pub strengths: Vec<String>,
+    pub gaps: Vec<String>,
+}
+
+// Whether the project tracks roadmap work in public, from milestones,
+// Projects v2 boards and a roadmap document
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Planning {
+    pub milestones: Vec<MilestoneProgress>, // Open first, then by due date
+    pub open_milestones: u32,
+    pub closed_milestones: u32,
+    pub overdue_milestones: u32,
+    pub project_boards: Option<Vec<ProjectBoard>>, // None when they couldn't be read
+    pub roadmap_files: Vec<PathBuf>,
+    pub public_roadmap: bool,
+    pub signals: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MilestoneProgress {
+    pub title: String,
+    pub state: String,
+    pub due_on: Option<DateTime<Utc>>,
+    pub open_issues: u32,
+    pub closed_issues: u32,
+    pub completion: f64, // Closed share of its issues, 0-1
+    pub overdue: bool,   // Open past its due date
+}
+
+// Code ownership from CODEOWNERS
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OwnershipRule {
+    pub pattern: String,
+    pub owners: Vec<String>,
+    pub matched_files: u32, // Files for which this rule is the last match
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OwnerActivity {
+    pub owner: String, // @user, @org/team or email
+    pub is_team: bool,
+    pub owned_files: u32,
+    pub contributions: Option<u32>,
+    pub recent_commits: u32,
+    pub last_commit_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct OwnershipAnalysis {
+    pub codeowners_path: Option<PathBuf>,
+    pub rules: Vec<OwnershipRule>,
+    pub owned_files: u32,
+    pub total_files: u32,
+    pub unowned_paths: Vec<PathBuf>, // Fully unowned directories are listed once
+    pub owners: Vec<OwnerActivity>,
+}
+
+// Architecturally relevant file selected for AI context
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InterestingFile {
+    pub path: PathBuf,
+    pub category: String, // entry-point, config, large-export, core-module
+    pub score: f64,
+    pub reasons: Vec<String>,
+    #[serde(skip)]
+    pub preview: Option<String>, // Already part of file_structure, only kept for prompts
+}
+
+// AI-generated insights
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AiInsights {
+    pub report: Option<String>,
+    pub issue_triage: Option<IssueTriage>,
+    #[serde(default)]
+    pub module_summaries: BTreeMap<String, ModuleSummary>, // By top-level directory
+    #[serde(default)]
+    pub executive_summary: Vec<String>, // Three paragraphs for the one-pager
+}
+
+// Normalized tag for filtering and grouping many analyses
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RepositoryTag {
+    pub category: String, // domain, maturity, audience, architecture
+    pub value: String,    // Lowercase and hyphenated, e.g. web-framework
+    pub confidence: f64,  // 0 to 1
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModuleSummary {
+    pub purpose: String,
+    #[serde(default)]
+    pub key_types: Vec<String>,
+    #[serde(default)]
+    pub dependencies: Vec<String>, // Other modules and libraries it relies on
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IssueTriage {
+    pub themes_summary: String,
+    #[serde(default)]
+    pub bug_clusters: Vec<IssueCluster>,
+    #[serde(default)]
+    pub suggested_labels: Vec<LabelSuggestion>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IssueCluster {
+    pub title: String,
+    pub description: String,
+    pub issue_numbers: Vec<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LabelSuggestion {
+    pub issue_number: u32,
+    pub labels: Vec<String>,
+}
+
+// Due diligence question answering
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Citation {
+    pub path: PathBuf,
+    pub start_line: u32,
+    pub end_line: u32,
+    #[serde(default)]
+    pub verified: bool, // The path and lines exist in the analyzed snapshot
+    #[serde(default)]
+    pub snippet: Option<String>, // The cited lines, quoted from the snapshot
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuestionAnswer {
+    pub question: String,
+    pub answer: String,
+    pub confidence: String, // high, medium, low
+    pub citations: Vec<Citation>,
+    #[serde(default)]
+    pub grounded: bool, // Cites the snapshot and every citation was verified
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AssessmentReport {
+    pub url: String,
+    pub assessed_at: DateTime<Utc>,
+    pub head_sha: Option<String>,
+    pub answers: Vec<QuestionAnswer>,
+}
+
+// Funding, backing organizations and governance, whether someone is likely
+// to keep maintaining the project
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Sustainability {
+    pub funding: Vec<FundingLink>,
+    pub affiliations: Vec<DomainShare>, // Organization domains of the top contributors
+    pub governance_files: Vec<PathBuf>,
+    pub foundation: Option<FoundationSignal>,
+    #[serde(default)]
+    pub affiliation_history: Vec<AffiliationPeriod>, // Oldest quarter first
+}
+
+// Who wrote the commits of a quarter, organizations by commit email domain
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AffiliationPeriod {
+    pub period: String, // 2024-Q3
+    pub commits: u32,
+    pub independent_commits: u32, // From personal mail providers
+    pub organizations: Vec<OrganizationShare>, // Largest first
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrganizationShare {
+    pub domain: String,
+    pub commits: u32,
+    pub share: f64, // 0 to 1, of the period's commits
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FundingLink {
+    pub platform: String, // github, open_collective, patreon, custom, ...
+    pub url: String,
+    pub source: PathBuf, // FUNDING.yml or the README linking it
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FoundationSignal {
+    pub foundation: String,
+    pub source: PathBuf,
+}
+
+// Risk per category for decision makers, derived from the rest of the analysis
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RiskAssessment {
+    pub overall: String,         // low, medium, high; the highest of the categories
+    pub bus_factor: Option<u32>, // Fewest contributors behind most of the commits
+    pub categories: Vec<RiskCategory>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RiskCategory {
+    pub category: String, // security, maintenance, bus-factor, license, dependencies, sustainability
+    pub level: String,    // low, medium, high
+    pub evidence: Vec<String>,
+}
+
+// Human correction from the overrides file, kept in the output so regenerated
+// reports show what was changed, why and by whom
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AppliedOverride {
+    pub action: String, // suppress, note, pin
+    pub target: String, // secret src/keys.py:3, frameworks, tag.domain, risk.security, ...
+    pub detail: String, // Reason, note text or the pinned value with the one it replaced
+    pub author: Option<String>,
+    pub source: PathBuf, // Overrides file it came from
+    #[serde(default)]
+    pub matched: u32, // Findings removed by a suppression
+}
+
+// Match of a custom rule script
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RuleFinding {
+    pub rule: String,     // Rule id from the config
+    pub severity: String, // low, medium or high
+    pub path: PathBuf,
+    pub line: Option<u32>,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>, // Link to the file at the analyzed commit
+}
+
+// Topics and description the repository could declare, from what was detected
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MetadataSuggestions {
+    pub topics: Vec<TopicSuggestion>, // Missing from the declared topics
+    pub remove_topics: Vec<TopicSuggestion>, // Declared but contradicted by the analysis
+    pub description: Option<DescriptionSuggestion>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TopicSuggestion {
+    pub topic: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DescriptionSuggestion {
+    pub current: Option<String>,
+    pub suggested: String,
+    pub reasons: Vec<String>,
+}
+
+// Report section emitted by a third-party plugin
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PluginSection {
+    #[serde(default)]
+    pub plugin: String, // Set from the plugin file name, not by the plugin
+    pub heading: String,
+    #[serde(default)]
+    pub blocks: Vec<PluginBlock>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PluginBlock {
+    Paragraph {
+        text: String,
+    },
+    List {
+        items: Vec<String>,
+    },
+    Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+}
+
+// Resource usage of a single analysis run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResourceUsage {
+    pub duration_ms: u64,
+    pub peak_memory_bytes: Option<u64>, // Peak RSS, Linux only
+    pub temp_disk_bytes: u64,           // Size of the work directory (clones and caches)
+    pub bytes_downloaded: u64,          // GitHub/registry responses and the clone
+    pub network_requests: u64,
+}
+
+// Data fetched over the network before the offline analysis phase
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemoteRepositoryData {
+    pub url: String,
+    pub metadata: RepositoryMetadata,
+    pub contributors: Vec<GitHubUser>,
+    pub releases: Vec<GitHubRelease>,
+    pub recent_issues: Vec<GitHubIssue>,
+    pub related_projects: Vec<RelatedProject>,
+    #[serde(default)]
+    pub fork_relationship: Option<ForkRelationship>,
+    #[serde(default)]
+    pub status: RepositoryStatus,
+    #[serde(default)]
+    pub starter_issues: StarterIssues,
+    #[serde(default)]
+    pub commit_authors: HashMap<String, GitHubUser>, // commit sha -> account GitHub linked its author to
+    #[serde(default)]
+    pub milestones: Vec<GitHubMilestone>,
+    #[serde(default)]
+    pub project_boards: Option<Vec<ProjectBoard>>, // None when GraphQL couldn't be queried, it needs a token
+    #[serde(default)]
+    pub security_advisories: Option<Vec<PublishedAdvisory>>, // None when the listing failed
+    #[serde(default)]
+    pub checksum_verification: Option<ChecksumVerification>, // None when nothing could be verified
+    #[serde(default)]
+    pub offline: bool, // Read from a local checkout, nothing came from a forge
+}
+
+// Open issues labeled for newcomers, None when the search failed
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StarterIssues {
+    pub good_first_issues: Option<u32>,
+    pub help_wanted_issues: Option<u32>,
 }
 
 // Comprehensive repository analysis result
@@ -232,15 +1404,130 @@ pub struct RepositoryAnalysis {
     pub url: String,
     pub analyzed_at: DateTime<Utc>,
     pub metadata: RepositoryMetadata,
-    pub file_structure: DirectoryInfo,
+    pub repository_status: RepositoryStatus,
+    pub file_structure: FileIndex,
+    #[serde(default)]
+    pub embedded_code: Vec<EmbeddedCode>,
     pub code_metrics: CodeMetrics,
     pub git_analysis: GitAnalysis,
     pub project_info: ProjectInfo,
     pub config_files: Vec<ConfigFile>,
     pub documentation: Vec<DocumentationFile>,
+    pub contribution_templates: TemplateInventory,
+    #[serde(default)]
+    pub contributor_friendliness: ContributorFriendliness,
+    pub docs_site: Option<DocsSite>,
+    #[serde(default)]
+    pub api_docs: ApiDocs,
+    #[serde(default)]
+    pub docs_spelling: DocsSpelling,
+    #[serde(default)]
+    pub docs_prose: DocsProse,
+    #[serde(default)]
+    pub readme_summary: Option<ReadmeSummary>,
+    #[serde(default)]
+    pub keywords: Vec<String>, // Domain vocabulary of the docs and code, most salient first
+    pub developer_tooling: DeveloperTooling,
+    #[serde(default)]
+    pub run_instructions: RunInstructions,
+    #[serde(default)]
+    pub deployment: DeploymentTopology,
+    pub build_reproducibility: BuildReproducibility,
+    #[serde(default)]
+    pub toolchain_requirements: ToolchainRequirements,
     pub security_info: SecurityInfo,
+    pub ownership: OwnershipAnalysis,
+    #[serde(default)]
+    pub sustainability: Sustainability,
+    #[serde(default)]
+    pub planning: Planning,
+    #[serde(default)]
+    pub performance: PerformanceCulture,
+    #[serde(default)]
+    pub platform_support: PlatformSupport,
+    pub interesting_files: Vec<InterestingFile>,
     pub releases: Vec<GitHubRelease>,
+    #[serde(default)]
+    pub release_hygiene: ReleaseHygiene,
     pub recent_issues: Vec<GitHubIssue>,
+    pub related_projects: Vec<RelatedProject>,
+    pub fork_relationship: Option<ForkRelationship>,
+    pub package_publishing: PackagePublishing,
+    #[serde(default)]
+    pub risk_assessment: RiskAssessment,
     pub analysis_summary: String,
-    pub ai_insights: Option<String>,
+    pub ai_insights: Option<AiInsights>,
+    #[serde(default)]
+    pub tags: Vec<RepositoryTag>,
+    #[serde(default)]
+    pub rule_findings: Vec<RuleFinding>,
+    #[serde(default)]
+    pub overrides: Vec<AppliedOverride>,
+    #[serde(default)]
+    pub plugin_sections: Vec<PluginSection>,
+    #[serde(default)]
+    pub metadata_suggestions: MetadataSuggestions,
+    pub telemetry: Option<ResourceUsage>,
+    #[serde(default)]
+    pub github_rate_limits: Vec<GitHubRateLimit>, // Quota left once the analysis was done
+    #[serde(default)]
+    pub cancelled: bool, // Interrupted during the AI phase, insights are missing
+}
+
+// GitHub API quota from the X-RateLimit-* headers
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GitHubRateLimit {
+    pub resource: String, // core, search, graphql
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset_at: DateTime<Utc>,
+}
+
+// Written instead of the analysis when a run is interrupted before it completes
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CancelledAnalysis {
+    pub url: String,
+    pub cancelled: bool,
+    pub cancelled_at: DateTime<Utc>,
+    pub checkpointed_phases: Vec<String>, // Reused by --resume
+}
+
+// Live progress of an analysis, streamed to GUI and TUI frontends
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    PhaseStarted {
+        phase: AnalysisPhase,
+    },
+    PhaseFinished {
+        phase: AnalysisPhase,
+        duration_ms: u64,
+    },
+    FileAnalyzed {
+        path: PathBuf,
+        files_analyzed: usize, // Including this one
+    },
+    ApiCall {
+        method: String,
+        url: String,         // Credentials redacted
+        status: Option<u16>, // None when the request failed
+        duration_ms: u64,
+    },
+    Warning {
+        message: String,
+    },
+}
+
+// Phases skipped on --resume, restored from their checkpoints, emit no events
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalysisPhase {
+    ForgeData,
+    Clone,
+    GitHistory,
+    FileScan,
+    LocalAnalysis,
+    PackageRegistries,
+    ApiDocs,
+    RelatedProjects,
 }
@@ -1,6 +1,13 @@
+use std::path::{Path, PathBuf};
+
 use anyhow::Result;
 use url::Url;
 
+use crate::types::{Detection, EvidenceRef};
+
+// Directories whose files are counted as tests
+const TEST_DIRECTORIES: &[&str] = &["test", "tests", "__tests__", "spec", "specs", "testing"];
+
 // Utility function to parse GitHub URL
 pub fn parse_github_url(url: &str) -> Result<(String, String)> {
     let parsed_url = Url::parse(url)?;
@@ -23,3 +30,147 @@ pub fn parse_github_url(url: &str) -> Result<(String, String)> {
 
     Ok((owner, repo))
 }
+
+// Prefix absolute Windows paths with \\?\ so deep trees (e.g. node_modules)
+// don't run into the 260 character MAX_PATH limit. Windows doesn't resolve
+// `.`, `..` or '/' in \\?\ paths, so the path is made absolute (which does)
+// first, and it's built component by component to keep non-UTF-8 names intact
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> PathBuf {
+    use std::{
+        ffi::OsString,
+        path::{Component, Prefix},
+    };
+
+    let Ok(absolute) = std::path::absolute(path) else {
+        return path.to_path_buf();
+    };
+
+    let mut components = absolute.components();
+    let mut long = match components.next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(_) => {
+                let mut long = OsString::from(r"\\?\");
+                long.push(prefix.as_os_str());
+                PathBuf::from(long)
+            }
+            Prefix::UNC(server, share) => {
+                let mut long = OsString::from(r"\\?\UNC\");
+                long.push(server);
+                long.push(r"\");
+                long.push(share);
+                PathBuf::from(long)
+            }
+            // Already verbatim, or a device path
+            _ => return absolute,
+        },
+        _ => return absolute,
+    };
+    long.extend(components);
+    long
+}
+
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+// Relative paths are recorded with forward slashes on every platform
+pub fn to_slash_path(path: &Path) -> PathBuf {
+    let parts: Vec<String> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    PathBuf::from(parts.join("/"))
+}
+
+// Join a recorded forward-slash path onto a root, which may be \\?\ prefixed
+// and therefore not accept '/' as a separator
+pub fn join_relative(root: &Path, relative: &Path) -> PathBuf {
+    let mut path = root.to_path_buf();
+    for part in relative.to_string_lossy().split('/') {
+        if !part.is_empty() {
+            path.push(part);
+        }
+    }
+    path
+}
+
+// 1-based line of the first quoted occurrence of a name in a manifest, else of
+// the first bare one
+pub fn manifest_line(content: &str, name: &str) -> Option<u32> {
+    let quoted = [format!("\"{}\"", name), format!("'{}'", name)];
+    content
+        .lines()
+        .position(|line| quoted.iter().any(|quoted| line.contains(quoted.as_str())))
+        .or_else(|| content.lines().position(|line| line.contains(name)))
+        .map(|index| index as u32 + 1)
+}
+
+// 1-based number of the first line matching
+pub fn first_line(content: &str, matches: impl Fn(&str) -> bool) -> Option<u32> {
+    content
+        .lines()
+        .position(matches)
+        .map(|index| index as u32 + 1)
+}
+
+// Tools named by evidence refs, in order and once each
+pub fn distinct_labels(refs: &[EvidenceRef]) -> Vec<String> {
+    let mut labels: Vec<String> = Vec::new();
+    for evidence in refs {
+        if !labels.contains(&evidence.label) {
+            labels.push(evidence.label.clone());
+        }
+    }
+    labels
+}
+
+// Test files are recognized by path: test directories and test_x / x_test /
+// x.spec / FooTest style names
+pub fn is_test_file(path: &Path) -> bool {
+    let in_test_directory = path.parent().is_some_and(|parent| {
+        parent.components().any(|component| {
+            let name = component.as_os_str().to_string_lossy().to_lowercase();
+            TEST_DIRECTORIES.contains(&name.as_str())
+        })
+    });
+    if in_test_directory {
+        return true;
+    }
+
+    let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+        return false;
+    };
+    // FooTest.java and FooTests.cs style names are matched case-sensitively
+    if stem.ends_with("Test") || stem.ends_with("Tests") {
+        return true;
+    }
+    let stem = stem.to_lowercase();
+    stem.starts_with("test_")
+        || stem.ends_with("_test")
+        || stem.ends_with("_tests")
+        || stem.ends_with("_spec")
+        || stem.ends_with(".test")
+        || stem.ends_with(".spec")
+}
+
+pub fn detection_names(detections: &[Detection]) -> Vec<String> {
+    detections.iter().map(|d| d.name.clone()).collect()
+}
+
+// Names with their confidence and first signal, so a reader (or the AI) can
+// tell a declared dependency from a guess on a file name
+pub fn describe_detections(detections: &[Detection]) -> String {
+    detections
+        .iter()
+        .map(|detection| match detection.evidence.first() {
+            Some(evidence) => format!(
+                "{} ({:.2}, {})",
+                detection.name, detection.confidence, evidence.label
+            ),
+            None => format!("{} ({:.2})", detection.name, detection.confidence),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}